@@ -0,0 +1,183 @@
+// File: crates/icn_api/src/errors.rs
+
+use icn_common::IcnError;
+use serde::{Serialize, Deserialize};
+use std::convert::Infallible;
+use warp::http::StatusCode;
+use warp::{Rejection, Reply};
+
+/// Media type of error responses, from RFC 7807.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// Base of the `type` URI of each problem; the error code follows it.
+const PROBLEM_TYPE_BASE: &str = "https://intercooperative.network/problems/";
+
+/// An error on its way to becoming a problem+json response.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// Stable, machine-readable code such as `currency_rejected` that
+    /// clients can branch on instead of parsing the message.
+    pub code: &'static str,
+    pub detail: String,
+}
+
+impl warp::reject::Reject for ApiError {}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, detail: impl Into<String>) -> Self {
+        ApiError { status, code, detail: detail.into() }
+    }
+
+    pub fn problem(&self) -> Problem {
+        Problem {
+            problem_type: format!("{}{}", PROBLEM_TYPE_BASE, self.code),
+            title: self.status.canonical_reason().unwrap_or("Error").to_string(),
+            status: self.status.as_u16(),
+            detail: self.detail.clone(),
+            code: self.code.to_string(),
+        }
+    }
+}
+
+impl From<IcnError> for ApiError {
+    fn from(error: IcnError) -> Self {
+        let detail = error.to_string();
+        let (status, code) = match &error {
+            IcnError::Blockchain(message) => domain_error(message, "block_not_found", "blockchain_rejected"),
+            IcnError::Currency(message) => domain_error(message, "account_not_found", "currency_rejected"),
+            IcnError::Governance(message) => domain_error(message, "proposal_not_found", "governance_rejected"),
+            IcnError::Sharding(message) => domain_error(message, "shard_not_found", "sharding_rejected"),
+            IcnError::Storage(message) => domain_error(message, "content_not_found", "storage_rejected"),
+            IcnError::SmartContract(message) => domain_error(message, "contract_not_found", "contract_rejected"),
+            IcnError::Dao(message) => domain_error(message, "dao_not_found", "dao_rejected"),
+            IcnError::Dispute(message) => domain_error(message, "dispute_not_found", "dispute_rejected"),
+            IcnError::Bridge(message) => domain_error(message, "bridge_not_found", "bridge_rejected"),
+            IcnError::Keystore(message) => domain_error(message, "key_not_found", "keystore_rejected"),
+            IcnError::Marketplace(message) => domain_error(message, "marketplace_not_found", "marketplace_rejected"),
+            IcnError::Identity(message) if is_not_found(message) => (StatusCode::NOT_FOUND, "identity_not_found"),
+            IcnError::Identity(_) => (StatusCode::FORBIDDEN, "identity_rejected"),
+            IcnError::Consensus(_) => (StatusCode::CONFLICT, "consensus_conflict"),
+            IcnError::Network(_) => (StatusCode::BAD_GATEWAY, "network_unavailable"),
+            IcnError::NodeManagement(_) => (StatusCode::SERVICE_UNAVAILABLE, "node_unavailable"),
+            IcnError::Vm(_) => (StatusCode::UNPROCESSABLE_ENTITY, "vm_error"),
+            IcnError::VmOutOfGas(_) => (StatusCode::UNPROCESSABLE_ENTITY, "vm_out_of_gas"),
+            IcnError::VmOutOfMemory(_) => (StatusCode::UNPROCESSABLE_ENTITY, "vm_out_of_memory"),
+            IcnError::VmTimeout(_) => (StatusCode::UNPROCESSABLE_ENTITY, "vm_timeout"),
+            IcnError::Zkp(_) => (StatusCode::UNPROCESSABLE_ENTITY, "proof_invalid"),
+            IcnError::Validation(_) => (StatusCode::BAD_REQUEST, "validation_failed"),
+            IcnError::Serialization(_) => (StatusCode::BAD_REQUEST, "malformed_input"),
+            IcnError::Io(_) => (StatusCode::INTERNAL_SERVER_ERROR, "io_error"),
+            IcnError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error"),
+            IcnError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "node_misconfigured"),
+            IcnError::Unknown(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        };
+        ApiError::new(status, code, detail)
+    }
+}
+
+/// Errors from a subsystem are about something missing or about a request
+/// the subsystem refused. The messages are the only place that shows.
+fn domain_error(message: &str, not_found: &'static str, rejected: &'static str) -> (StatusCode, &'static str) {
+    if is_not_found(message) {
+        (StatusCode::NOT_FOUND, not_found)
+    } else {
+        (StatusCode::UNPROCESSABLE_ENTITY, rejected)
+    }
+}
+
+fn is_not_found(message: &str) -> bool {
+    message.to_ascii_lowercase().contains("not found")
+}
+
+/// An RFC 7807 problem details body, with the error code as an extension
+/// member.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Problem {
+    #[serde(rename = "type")]
+    pub problem_type: String,
+    pub title: String,
+    pub status: u16,
+    pub detail: String,
+    pub code: String,
+}
+
+/// Turns every rejection into a problem+json response: node errors by
+/// their variant, and warp's own rejections for unknown routes, bad
+/// bodies and the like by what went wrong.
+pub async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Infallible> {
+    let error = if let Some(error) = rejection.find::<ApiError>() {
+        ApiError::new(error.status, error.code, error.detail.clone())
+    } else if rejection.is_not_found() {
+        ApiError::new(StatusCode::NOT_FOUND, "route_not_found", "No route matches this path")
+    } else if let Some(error) = rejection.find::<warp::filters::body::BodyDeserializeError>() {
+        ApiError::new(StatusCode::BAD_REQUEST, "malformed_body", error.to_string())
+    } else if let Some(error) = rejection.find::<warp::reject::InvalidQuery>() {
+        ApiError::new(StatusCode::BAD_REQUEST, "malformed_query", error.to_string())
+    } else if let Some(error) = rejection.find::<warp::reject::MissingHeader>() {
+        ApiError::new(StatusCode::BAD_REQUEST, "missing_header", error.to_string())
+    } else if let Some(error) = rejection.find::<warp::reject::InvalidHeader>() {
+        ApiError::new(StatusCode::BAD_REQUEST, "invalid_header", error.to_string())
+    } else if let Some(error) = rejection.find::<warp::reject::UnsupportedMediaType>() {
+        ApiError::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported_media_type", error.to_string())
+    } else if let Some(error) = rejection.find::<warp::reject::PayloadTooLarge>() {
+        ApiError::new(StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", error.to_string())
+    } else if let Some(error) = rejection.find::<warp::reject::MethodNotAllowed>() {
+        ApiError::new(StatusCode::METHOD_NOT_ALLOWED, "method_not_allowed", error.to_string())
+    } else {
+        ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", format!("Unhandled rejection: {:?}", rejection))
+    };
+    Ok(problem_reply(&error))
+}
+
+pub fn problem_reply(error: &ApiError) -> impl Reply {
+    warp::reply::with_header(
+        warp::reply::with_status(warp::reply::json(&error.problem()), error.status),
+        "content-type",
+        PROBLEM_JSON,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warp::Filter;
+
+    #[test]
+    fn test_errors_map_to_codes() {
+        let error = ApiError::from(IcnError::Currency("Insufficient balance".into()));
+        assert_eq!((error.status, error.code), (StatusCode::UNPROCESSABLE_ENTITY, "currency_rejected"));
+        let error = ApiError::from(IcnError::Dao("Cooperative not found".into()));
+        assert_eq!((error.status, error.code), (StatusCode::NOT_FOUND, "dao_not_found"));
+        let error = ApiError::from(IcnError::Identity("Signature verification failed".into()));
+        assert_eq!((error.status, error.code), (StatusCode::FORBIDDEN, "identity_rejected"));
+
+        let problem = ApiError::from(IcnError::VmOutOfGas("used 1000".into())).problem();
+        assert_eq!(problem.status, 422);
+        assert_eq!(problem.code, "vm_out_of_gas");
+        assert_eq!(problem.problem_type, "https://intercooperative.network/problems/vm_out_of_gas");
+        assert_eq!(problem.detail, "VM out of gas: used 1000");
+    }
+
+    #[tokio::test]
+    async fn test_rejections_become_problems() {
+        let routes = warp::path!("fail")
+            .and_then(|| async { Err::<String, _>(warp::reject::custom(ApiError::from(IcnError::NodeManagement("Node is in maintenance mode".into())))) })
+            .or(warp::path!("echo").and(warp::body::json()).map(|body: serde_json::Value| warp::reply::json(&body)))
+            .recover(handle_rejection);
+
+        let response = warp::test::request().path("/fail").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()["content-type"], PROBLEM_JSON);
+        let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(problem.code, "node_unavailable");
+        assert_eq!(problem.title, "Service Unavailable");
+
+        let response = warp::test::request().path("/missing").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = warp::test::request().method("POST").path("/echo").body("{").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let problem: Problem = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(problem.code, "malformed_body");
+    }
+}
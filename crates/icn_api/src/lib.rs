@@ -1,12 +1,38 @@
 // File: crates/icn_api/src/lib.rs
 
+pub mod errors;
+pub mod replica;
+
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use warp::{Filter, Rejection, Reply};
 use serde::{Deserialize, Serialize};
-use icn_common::{IcnResult, IcnError, Transaction, Proposal, CurrencyType, ProposalType, ProposalCategory, ProposalStatus};
+use icn_common::{IcnResult, IcnError, Amount, BallotKind, Transaction, TransactionReceipt, Proposal, CurrencyType, ProposalType, ProposalCategory, ProposalStatus, ProposalAction, ProposalScope, SignedBallot, TransactionMemo, VotingWeightMode, BlockHeader, HeaderBatch, TransactionProof};
+#[cfg(feature = "fault_injection")]
+use icn_common::{Fault, FaultStats};
+use icn_identity::{Attestation as MemberAttestation, CapabilityToken, DisclosurePolicy, DisclosureRecord, IdentityPresentation, NotificationPreferences, ReputationChange, RosterFormat, SchemePublicKey, SelectionAudit};
+use icn_blockchain::{TransactionFilter, TransactionPage};
+use icn_bridge::{Attestation, DepositRecord, ExternalDeposit, PeggedAsset, Withdrawal};
+use icn_marketplace::{Listing, ListingKind, Order};
+use icn_dao::{ContractCall, ContributionMint, ContributionRecord, Cooperative, GrantRound, Milestone, ProfitDistribution};
+use icn_network::{PeerLatency, PropagationStats};
+use icn_sharding::{HistoryAvailability, ShardHistory};
+use icn_zkp::{ConfidentialTransfer, ConfidentialWithdrawal, EncryptedBalance, ReserveInclusion, ReserveProof};
 use serde_json::json;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use std::convert::Infallible;
+use std::ops::Bound;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use futures_util::SinkExt;
+use crate::errors::{handle_rejection, ApiError};
+
+/// Most headers one `GET /headers` request returns.
+pub const MAX_HEADERS_PER_REQUEST: usize = 500;
+
+/// Most blocks one `GET /blocks` request returns; `GET /blocks/export`
+/// streams any number.
+pub const MAX_BLOCKS_PER_REQUEST: usize = 100;
 
 // ApiLayer struct remains unchanged
 pub struct ApiLayer {
@@ -19,19 +45,59 @@ impl ApiLayer {
     }
 
     // Existing methods remain unchanged
-    pub async fn submit_transaction(&self, transaction: Transaction) -> IcnResult<()> {
+    pub async fn submit_transaction(&self, transaction: Transaction) -> IcnResult<TransactionReceipt> {
         let node = self.node.read().await;
         node.process_transaction(transaction).await
     }
 
-    pub async fn create_proposal(&self, proposal: Proposal) -> IcnResult<String> {
+    pub async fn get_receipt(&self, tx_hash: &str) -> IcnResult<TransactionReceipt> {
+        let node = self.node.read().await;
+        node.get_receipt(tx_hash).await
+    }
+
+    pub async fn get_transaction_availability(&self, tx_hash: &str) -> IcnResult<HistoryAvailability> {
+        let node = self.node.read().await;
+        node.get_transaction_availability(tx_hash).await
+    }
+
+    pub async fn get_shard_history(&self, shard_id: u64) -> IcnResult<ShardHistory> {
+        let node = self.node.read().await;
+        node.get_shard_history(shard_id).await
+    }
+
+    pub async fn create_proposal(&self, proposal: Proposal, signature: &[u8]) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.create_proposal(proposal, signature).await
+    }
+
+    pub async fn create_proposal_bundle(&self, bundle_id: &str, proposals: Vec<(Proposal, Vec<u8>)>) -> IcnResult<Vec<String>> {
+        let node = self.node.read().await;
+        node.create_proposal_bundle(bundle_id, proposals).await
+    }
+
+    pub async fn prepare_template_proposal(&self, request: &icn_governance::TemplateProposal, created_at: DateTime<Utc>) -> IcnResult<Proposal> {
+        let node = self.node.read().await;
+        node.prepare_template_proposal(request, created_at).await
+    }
+
+    pub async fn create_proposal_from_template(&self, request: icn_governance::TemplateProposal, created_at: DateTime<Utc>, signature: &[u8]) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.create_proposal_from_template(request, created_at, signature).await
+    }
+
+    pub async fn list_proposal_templates(&self) -> Vec<icn_governance::ProposalTemplate> {
         let node = self.node.read().await;
-        node.create_proposal(proposal).await
+        node.list_proposal_templates().await
     }
 
-    pub async fn vote_on_proposal(&self, proposal_id: &str, voter: String, in_favor: bool, weight: f64) -> IcnResult<()> {
+    pub async fn vote_signing_bytes(&self, proposal_id: &str, voter: &str, in_favor: bool, timestamp: i64) -> IcnResult<Vec<u8>> {
         let node = self.node.read().await;
-        node.vote_on_proposal(proposal_id, voter, in_favor, weight).await
+        node.vote_signing_bytes(proposal_id, voter, in_favor, timestamp).await
+    }
+
+    pub async fn vote_on_proposal(&self, proposal_id: &str, voter: String, in_favor: bool, timestamp: i64, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.vote_on_proposal(proposal_id, voter, in_favor, timestamp, signature).await
     }
 
     pub async fn finalize_proposal(&self, proposal_id: &str) -> IcnResult<ProposalStatus> {
@@ -39,9 +105,36 @@ impl ApiLayer {
         node.finalize_proposal(proposal_id).await
     }
 
-    pub async fn get_balance(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<f64> {
+    pub async fn get_balance(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<icn_currency::BalanceSummary> {
+        let node = self.node.read().await;
+        node.get_balance_summary(address, currency_type).await
+    }
+
+    pub async fn delegated_transfer(&self, token: &CapabilityToken, to: &str, amount: f64, currency_type: CurrencyType, timestamp: i64, signature: Vec<u8>) -> IcnResult<TransactionReceipt> {
+        let node = self.node.read().await;
+        node.delegated_transfer(token, to, amount, currency_type, timestamp, signature).await
+    }
+
+    pub async fn revoke_capability(&self, issuer: &str, token_id: &str, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.revoke_capability(issuer, token_id, signature).await
+    }
+
+    pub async fn list_revoked_capabilities(&self) -> Vec<String> {
         let node = self.node.read().await;
-        node.get_balance(address, currency_type).await
+        node.list_revoked_capabilities().await
+    }
+
+    pub async fn transfer_vested(&self, request: VestedTransferRequest) -> IcnResult<icn_common::VestingSchedule> {
+        let node = self.node.read().await;
+        node.transfer_vested(
+            &request.from,
+            &request.to,
+            request.amount,
+            &request.currency_type,
+            Duration::seconds(request.cliff_secs),
+            Duration::seconds(request.duration_secs),
+        ).await
     }
 
     pub async fn mint_currency(&self, address: &str, currency_type: &CurrencyType, amount: f64) -> IcnResult<()> {
@@ -49,387 +142,5187 @@ impl ApiLayer {
         node.mint_currency(address, currency_type, amount).await
     }
 
-    pub async fn create_identity(&self, attributes: std::collections::HashMap<String, String>) -> IcnResult<String> {
-        let node = self.node.write().await;
-        node.create_identity(attributes).await
+    pub async fn exchange_currency(&self, from: &str, source_currency: &CurrencyType, target_currency: &CurrencyType, amount: Amount, min_amount_out: Amount) -> IcnResult<Amount> {
+        let node = self.node.read().await;
+        node.exchange_currency(from, source_currency, target_currency, amount, min_amount_out).await
     }
 
-    pub async fn allocate_resource(&self, resource_type: &str, amount: u64) -> IcnResult<()> {
-        let node = self.node.write().await;
-        node.allocate_resource(resource_type, amount).await
+    pub async fn list_liquidity_pools(&self) -> IcnResult<Vec<icn_currency::LiquidityPool>> {
+        let node = self.node.read().await;
+        node.list_liquidity_pools().await
     }
 
-    pub async fn get_network_stats(&self) -> IcnResult<icn_common::NetworkStats> {
+    pub async fn add_liquidity(&self, provider: &str, pool_id: &str, amount_a: Amount, amount_b: Amount) -> IcnResult<Amount> {
         let node = self.node.read().await;
-        node.get_network_stats().await
+        node.add_liquidity(provider, pool_id, amount_a, amount_b).await
     }
 
-    // New method to get proposal status
-    pub async fn get_proposal_status(&self, proposal_id: &str) -> IcnResult<ProposalStatus> {
+    pub async fn remove_liquidity(&self, provider: &str, pool_id: &str, shares: Amount) -> IcnResult<(Amount, Amount)> {
         let node = self.node.read().await;
-        node.get_proposal_status(proposal_id).await
+        node.remove_liquidity(provider, pool_id, shares).await
     }
 
-    // New method to get block information
-    pub async fn get_block_info(&self, identifier: &str) -> IcnResult<icn_blockchain::Block> {
+    pub async fn issue_bond(&self, issuer: &str, terms: icn_currency::BondTerms, units: f64) -> IcnResult<String> {
         let node = self.node.read().await;
-        if let Ok(height) = identifier.parse::<u64>() {
-            node.get_block_by_height(height).await
-        } else {
-            node.get_block_by_hash(identifier).await
+        node.issue_bond(issuer, terms, units).await
+    }
+
+    pub async fn get_bond(&self, bond_id: &str) -> IcnResult<icn_currency::Bond> {
+        let node = self.node.read().await;
+        node.get_bond(bond_id).await
+    }
+
+    pub async fn get_storage_usage(&self, owner: &str) -> IcnResult<(usize, usize)> {
+        let node = self.node.read().await;
+        node.get_storage_usage(owner).await
+    }
+
+    /// Stores data billed to `owner`, deleted after `ttl` if one is given.
+    pub async fn store_data(&self, owner: &str, key: &str, value: Vec<u8>, ttl: Option<Duration>) -> IcnResult<()> {
+        let node = self.node.read().await;
+        match ttl {
+            Some(ttl) => node.store_temporary_data(owner, key, value, ttl).await,
+            None => node.store_data(owner, key, value).await,
         }
     }
 
-    // New method to get current network difficulty
-    pub async fn get_network_difficulty(&self) -> IcnResult<f64> {
+    pub async fn get_storage_gc_stats(&self) -> IcnResult<icn_storage::GcStats> {
         let node = self.node.read().await;
-        node.get_network_difficulty().await
+        node.get_storage_gc_stats().await
     }
 
-    // New method to submit a new smart contract
-    pub async fn submit_smart_contract(&self, code: String) -> IcnResult<String> {
-        let node = self.node.write().await;
-        node.deploy_smart_contract(code).await
+    pub async fn list_archived_proposals(&self) -> Vec<icn_governance::ArchiveEntry> {
+        let node = self.node.read().await;
+        node.list_archived_proposals().await
     }
 
-    // New method to execute a smart contract
-    pub async fn execute_smart_contract(&self, contract_id: &str, function: &str, args: Vec<icn_vm::Value>) -> IcnResult<Option<icn_vm::Value>> {
-        let node = self.node.write().await;
-        node.execute_smart_contract(contract_id, function, args).await
+    pub async fn audit_supply(&self) -> IcnResult<Vec<icn_core::Alert>> {
+        let node = self.node.read().await;
+        node.audit_supply().await
     }
-}
 
-// Request and response structs
-#[derive(Deserialize)]
-struct CreateProposalRequest {
-    title: String,
-    description: String,
-    proposer: String,
-    proposal_type: ProposalType,
-    category: ProposalCategory,
-}
+    pub async fn resume_minting(&self) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.resume_minting().await
+    }
 
-#[derive(Serialize)]
-struct CreateProposalResponse {
-    proposal_id: String,
-}
+    pub async fn publish_reserve_proofs(&self) -> Vec<ReserveProof> {
+        let node = self.node.read().await;
+        node.publish_reserve_proofs().await
+    }
 
-#[derive(Deserialize)]
-struct GetProposalStatusRequest {
-    proposal_id: String,
-}
+    pub async fn get_reserve_proofs(&self) -> Vec<ReserveProof> {
+        let node = self.node.read().await;
+        node.get_reserve_proofs().await
+    }
 
-#[derive(Serialize)]
-struct GetProposalStatusResponse {
-    status: ProposalStatus,
-}
+    pub async fn verify_reserve_proof(&self, proof: &ReserveProof) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.verify_reserve_proof(proof).await
+    }
 
-#[derive(Deserialize)]
-struct GetBlockInfoRequest {
-    identifier: String,
-}
+    pub async fn get_reserve_inclusion(&self, address: &str, currency_type: &CurrencyType, nonce: u64, signature: &[u8]) -> IcnResult<ReserveInclusion> {
+        let node = self.node.read().await;
+        node.get_reserve_inclusion(address, currency_type, nonce, signature).await
+    }
 
-#[derive(Serialize)]
-struct GetBlockInfoResponse {
-    block: icn_blockchain::Block,
-}
+    pub async fn restore_archived_proposal(&self, proposal_id: &str) -> IcnResult<Proposal> {
+        let node = self.node.read().await;
+        node.restore_archived_proposal(proposal_id).await
+    }
 
-#[derive(Serialize)]
-struct GetNetworkDifficultyResponse {
-    difficulty: f64,
-}
+    pub async fn purge_archived_proposal(&self, proposal_id: &str) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.purge_archived_proposal(proposal_id).await
+    }
 
-#[derive(Deserialize)]
-struct SubmitSmartContractRequest {
-    code: String,
-}
+    pub async fn list_archived_dao_proposals(&self, dao_id: &str) -> IcnResult<Vec<icn_dao::ProposalArchiveEntry>> {
+        let node = self.node.read().await;
+        node.list_archived_dao_proposals(dao_id).await
+    }
 
-#[derive(Serialize)]
-struct SubmitSmartContractResponse {
-    contract_id: String,
-}
+    pub async fn restore_archived_dao_proposal(&self, dao_id: &str, proposal_id: &str) -> IcnResult<icn_dao::Proposal> {
+        let node = self.node.read().await;
+        node.restore_archived_dao_proposal(dao_id, proposal_id).await
+    }
 
-#[derive(Deserialize)]
-struct ExecuteSmartContractRequest {
-    contract_id: String,
-    function: String,
-    args: Vec<icn_vm::Value>,
-}
+    pub async fn purge_archived_dao_proposal(&self, dao_id: &str, proposal_id: &str) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.purge_archived_dao_proposal(dao_id, proposal_id).await
+    }
 
-#[derive(Serialize)]
-struct ExecuteSmartContractResponse {
-    result: Option<icn_vm::Value>,
-}
+    pub async fn get_validators(&self) -> Vec<icn_consensus::ValidatorPerformance> {
+        let node = self.node.read().await;
+        node.get_validators().await
+    }
 
-// Helper function to convert IcnError to warp::Rejection
-fn icn_error_to_rejection(error: IcnError) -> warp::Rejection {
-    warp::reject::custom(error)
-}
+    pub async fn get_account_freeze(&self, address: &str) -> Option<icn_core::AccountFreeze> {
+        let node = self.node.read().await;
+        node.get_account_freeze(address).await
+    }
 
-// API routes
-pub fn api_routes(
-    api_layer: Arc<RwLock<ApiLayer>>,
-) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
-    let api_layer = warp::any().map(move || api_layer.clone());
+    pub async fn list_account_freezes(&self) -> Vec<icn_core::AccountFreeze> {
+        let node = self.node.read().await;
+        node.list_account_freezes().await
+    }
 
-    let submit_transaction = warp::post()
-        .and(warp::path("transaction"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_submit_transaction);
+    pub async fn register_confidential_account(&self, address: &str, viewing_key: Vec<u8>) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.register_confidential_account(address, viewing_key).await
+    }
 
-    let create_proposal = warp::post()
-        .and(warp::path("proposal"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_create_proposal);
+    pub async fn shield_funds(&self, transaction: Transaction) -> IcnResult<TransactionReceipt> {
+        let node = self.node.read().await;
+        node.shield_funds(transaction).await
+    }
 
-    let vote_on_proposal = warp::post()
-        .and(warp::path("vote"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_vote_on_proposal);
+    pub async fn submit_confidential_transfer(&self, transfer: ConfidentialTransfer) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.submit_confidential_transfer(transfer).await
+    }
 
-    let get_balance = warp::get()
-        .and(warp::path("balance"))
-        .and(warp::query())
-        .and(api_layer.clone())
-        .and_then(handle_get_balance);
+    pub async fn unshield_funds(&self, withdrawal: ConfidentialWithdrawal) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.unshield_funds(withdrawal).await
+    }
+
+    pub async fn get_confidential_balance(&self, address: &str, currency_type: &CurrencyType) -> EncryptedBalance {
+        let node = self.node.read().await;
+        node.get_confidential_balance(address, currency_type).await
+    }
+
+    pub async fn get_confidential_transfers(&self, address: &str) -> Vec<ConfidentialTransfer> {
+        let node = self.node.read().await;
+        node.get_confidential_transfers(address).await
+    }
+
+    pub async fn record_contribution(&self, dao_id: &str, record: ContributionRecord) -> IcnResult<ContributionMint> {
+        let node = self.node.read().await;
+        node.record_contribution(dao_id, record).await
+    }
+
+    pub async fn create_dao_proposal(&self, dao_id: &str, title: String, description: String, proposer: String, duration: Duration, contract_call: Option<ContractCall>) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.create_dao_proposal(dao_id, title, description, proposer, duration, contract_call).await
+    }
+
+    pub async fn vote_on_dao_proposal(&self, dao_id: &str, proposal_id: &str, member_id: &str, in_favor: bool) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.vote_on_dao_proposal(dao_id, proposal_id, member_id, in_favor).await
+    }
+
+    pub async fn finalize_dao_proposal(&self, dao_id: &str, proposal_id: &str) -> IcnResult<icn_dao::ProposalStatus> {
+        let node = self.node.read().await;
+        node.finalize_dao_proposal(dao_id, proposal_id).await
+    }
+
+    pub async fn get_dao_proposal(&self, dao_id: &str, proposal_id: &str) -> IcnResult<icn_dao::Proposal> {
+        let node = self.node.read().await;
+        node.get_dao_proposal(dao_id, proposal_id).await
+    }
+
+    pub async fn get_contributions(&self, dao_id: &str, contributor: Option<&str>) -> IcnResult<Vec<ContributionMint>> {
+        let node = self.node.read().await;
+        node.get_contributions(dao_id, contributor).await
+    }
+
+    pub async fn apply_for_grant(&self, dao_id: &str, round_id: &str, applicant: &str, title: &str, description: &str, milestones: Vec<Milestone>) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.apply_for_grant(dao_id, round_id, applicant, title, description, milestones).await
+    }
+
+    pub async fn vote_for_grant(&self, dao_id: &str, round_id: &str, application_id: &str, member_id: &str, amount: Amount, nonce: u64, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.vote_for_grant(dao_id, round_id, application_id, member_id, amount, nonce, signature).await
+    }
+
+    pub async fn finalize_grant_round(&self, dao_id: &str, round_id: &str) -> IcnResult<Vec<(String, Amount)>> {
+        let node = self.node.read().await;
+        node.finalize_grant_round(dao_id, round_id).await
+    }
+
+    pub async fn settle_grant_milestone(&self, dao_id: &str, round_id: &str, application_id: &str, steward: &str, delivered: bool, nonce: u64, signature: &[u8]) -> IcnResult<Amount> {
+        let node = self.node.read().await;
+        node.settle_grant_milestone(dao_id, round_id, application_id, steward, delivered, nonce, signature).await
+    }
+
+    pub async fn get_grant_round(&self, dao_id: &str, round_id: &str) -> IcnResult<GrantRound> {
+        let node = self.node.read().await;
+        node.get_grant_round(dao_id, round_id).await
+    }
+
+    pub async fn distribute_profits(&self, dao_id: &str, steward: &str, total_profit: f64, currency_type: CurrencyType) -> IcnResult<ProfitDistribution> {
+        let node = self.node.read().await;
+        node.distribute_profits(dao_id, steward, total_profit, currency_type).await
+    }
+
+    pub async fn get_profit_distributions(&self, dao_id: &str) -> IcnResult<Vec<ProfitDistribution>> {
+        let node = self.node.read().await;
+        node.get_profit_distributions(dao_id).await
+    }
+
+    /// The tenant an `X-Api-Key` header acts for. Every tenant route goes
+    /// through here, so a tenant only ever reaches its own state.
+    pub async fn authenticate_tenant(&self, api_key: &str) -> IcnResult<icn_core::Tenant> {
+        let node = self.node.read().await;
+        node.authenticate_tenant(api_key).await
+    }
+
+    pub async fn get_tenant_usage(&self, tenant_id: &str) -> IcnResult<icn_core::TenantUsage> {
+        let node = self.node.read().await;
+        node.get_tenant_usage(tenant_id).await
+    }
+
+    pub async fn create_tenant_identity(&self, tenant_id: &str, attributes: std::collections::HashMap<String, String>) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.create_tenant_identity(tenant_id, attributes).await
+    }
+
+    pub async fn register_tenant_cooperative(&self, tenant_id: &str, cooperative: Cooperative) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.register_tenant_cooperative(tenant_id, cooperative).await
+    }
+
+    pub async fn list_tenant_cooperatives(&self, tenant_id: &str) -> IcnResult<Vec<Cooperative>> {
+        let node = self.node.read().await;
+        node.list_tenant_cooperatives(tenant_id).await
+    }
+
+    pub async fn ensure_shared_cooperative(&self, dao_id: &str) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.ensure_shared_cooperative(dao_id).await
+    }
+
+    pub async fn add_tenant_currency(&self, tenant_id: &str, name: &str, initial_supply: f64, issuance_rate: f64) -> IcnResult<CurrencyType> {
+        let node = self.node.read().await;
+        node.add_tenant_currency(tenant_id, name, initial_supply, issuance_rate).await
+    }
+
+    pub async fn mint_tenant_currency(&self, tenant_id: &str, name: &str, to: &str, amount: f64) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.mint_tenant_currency(tenant_id, name, to, amount).await
+    }
+
+    pub async fn store_tenant_data(&self, tenant_id: &str, key: &str, value: Vec<u8>) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.store_tenant_data(tenant_id, key, value).await
+    }
+
+    pub async fn retrieve_tenant_data(&self, tenant_id: &str, key: &str) -> IcnResult<Vec<u8>> {
+        let node = self.node.read().await;
+        node.retrieve_tenant_data(tenant_id, key).await
+    }
+
+    pub async fn list_bridge_pegs(&self) -> Vec<PeggedAsset> {
+        let node = self.node.read().await;
+        node.list_bridge_pegs().await
+    }
+
+    pub async fn attest_bridge_deposit(&self, deposit: ExternalDeposit, attestation: Attestation) -> IcnResult<DepositRecord> {
+        let node = self.node.read().await;
+        node.attest_bridge_deposit(deposit, attestation).await
+    }
+
+    pub async fn get_bridge_deposit(&self, chain: &str, tx_hash: &str) -> IcnResult<DepositRecord> {
+        let node = self.node.read().await;
+        node.get_bridge_deposit(chain, tx_hash).await
+    }
+
+    pub async fn request_bridge_withdrawal(&self, holder: &str, asset_id: &str, amount: Amount, external_recipient: String, nonce: u64, signature: &[u8]) -> IcnResult<Withdrawal> {
+        let node = self.node.read().await;
+        node.request_bridge_withdrawal(holder, asset_id, amount, external_recipient, nonce, signature).await
+    }
+
+    pub async fn challenge_bridge_withdrawal(&self, withdrawal_id: &str, relayer: &str, reason: String, signature: &[u8]) -> IcnResult<Withdrawal> {
+        let node = self.node.read().await;
+        node.challenge_bridge_withdrawal(withdrawal_id, relayer, reason, signature).await
+    }
+
+    pub async fn release_bridge_withdrawals(&self) -> IcnResult<Vec<Withdrawal>> {
+        let node = self.node.read().await;
+        node.release_bridge_withdrawals().await
+    }
+
+    pub async fn get_bridge_withdrawal(&self, withdrawal_id: &str) -> IcnResult<Withdrawal> {
+        let node = self.node.read().await;
+        node.get_bridge_withdrawal(withdrawal_id).await
+    }
+
+    pub async fn register_validator_bls_key(&self, id: &str) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.register_validator_bls_key(id).await
+    }
+
+    pub async fn certify_block(&self, block_index: u64, approvals: Vec<(String, Vec<u8>)>) -> IcnResult<icn_consensus::BlockCertificate> {
+        let node = self.node.read().await;
+        node.certify_block(block_index, approvals).await
+    }
+
+    pub async fn get_committee(&self, block_index: u64) -> IcnResult<icn_consensus::Committee> {
+        let node = self.node.read().await;
+        node.get_committee(block_index).await
+    }
+
+    pub async fn audit_committee(&self, block_index: u64) -> IcnResult<SelectionAudit> {
+        let node = self.node.read().await;
+        node.audit_committee(block_index).await
+    }
+
+    pub async fn audit_dispute_jury(&self, dispute_id: &str) -> IcnResult<SelectionAudit> {
+        let node = self.node.read().await;
+        node.audit_dispute_jury(dispute_id).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn post_listing(&self, seller: &str, kind: ListingKind, title: &str, description: &str, unit_price: Amount, currency_type: CurrencyType, quantity: u32) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.post_listing(seller, kind, title, description, unit_price, currency_type, quantity).await
+    }
+
+    pub async fn withdraw_listing(&self, listing_id: &str, seller: &str, nonce: u64, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.withdraw_listing(listing_id, seller, nonce, signature).await
+    }
+
+    pub async fn get_listing(&self, listing_id: &str) -> IcnResult<Listing> {
+        let node = self.node.read().await;
+        node.get_listing(listing_id).await
+    }
+
+    pub async fn list_listings(&self, kind: Option<ListingKind>) -> Vec<Listing> {
+        let node = self.node.read().await;
+        node.list_listings(kind).await
+    }
+
+    pub async fn place_order(&self, listing_id: &str, buyer: &str, quantity: u32, nonce: u64, signature: &[u8]) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.place_order(listing_id, buyer, quantity, nonce, signature).await
+    }
+
+    pub async fn get_order(&self, order_id: &str) -> IcnResult<Order> {
+        let node = self.node.read().await;
+        node.get_order(order_id).await
+    }
+
+    pub async fn list_orders_for(&self, member: &str) -> Vec<Order> {
+        let node = self.node.read().await;
+        node.list_orders_for(member).await
+    }
+
+    pub async fn confirm_order_fulfillment(&self, order_id: &str, buyer: &str, nonce: u64, signature: &[u8]) -> IcnResult<Amount> {
+        let node = self.node.read().await;
+        node.confirm_order_fulfillment(order_id, buyer, nonce, signature).await
+    }
+
+    pub async fn cancel_order(&self, order_id: &str, seller: &str, nonce: u64, signature: &[u8]) -> IcnResult<Amount> {
+        let node = self.node.read().await;
+        node.cancel_order(order_id, seller, nonce, signature).await
+    }
+
+    pub async fn dispute_order(&self, order_id: &str, claimant: &str, description: String) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.dispute_order(order_id, claimant, description).await
+    }
+
+    pub async fn raise_fraud_challenge(&self, challenger: &str, block_index: u64) -> IcnResult<icn_consensus::FraudChallenge> {
+        let node = self.node.read().await;
+        node.raise_fraud_challenge(challenger, block_index).await
+    }
+
+    pub async fn get_fraud_challenges(&self) -> Vec<icn_consensus::FraudChallenge> {
+        let node = self.node.read().await;
+        node.get_fraud_challenges().await
+    }
+
+    pub async fn submit_double_sign_evidence(&self, reporter: &str, evidence: icn_common::DoubleSignEvidence) -> IcnResult<icn_consensus::SlashingRecord> {
+        let node = self.node.read().await;
+        node.submit_double_sign_evidence(reporter, evidence).await
+    }
+
+    pub async fn get_slashings(&self) -> Vec<icn_consensus::SlashingRecord> {
+        let node = self.node.read().await;
+        node.get_slashings().await
+    }
+
+    pub async fn get_validator(&self, id: &str) -> IcnResult<icn_consensus::ValidatorPerformance> {
+        let node = self.node.read().await;
+        node.get_validator(id).await
+    }
+
+    pub async fn prepare_transaction(&self, from: &str, to: &str, amount: f64, currency_type: CurrencyType, memo: Option<TransactionMemo>) -> IcnResult<icn_core::PreparedTransaction> {
+        let node = self.node.read().await;
+        node.prepare_transaction(from, to, amount, currency_type, memo).await
+    }
+
+    pub async fn submit_transaction_signature(&self, hash: &str, signature: Vec<u8>) -> IcnResult<TransactionReceipt> {
+        let node = self.node.read().await;
+        node.submit_transaction_signature(hash, signature).await
+    }
+
+    pub async fn estimate_fee(&self, amount: Amount) -> icn_currency::FeeEstimate {
+        let node = self.node.read().await;
+        node.estimate_fee(amount).await
+    }
+
+    pub async fn get_exchange_rates(&self) -> Vec<icn_currency::ExchangeRate> {
+        let node = self.node.read().await;
+        node.get_exchange_rates().await
+    }
+
+    pub async fn get_rate_history(&self, pair: Option<(&CurrencyType, &CurrencyType)>) -> Vec<icn_currency::ExchangeRate> {
+        let node = self.node.read().await;
+        node.get_rate_history(pair).await
+    }
+
+    pub async fn get_rate_oracles(&self) -> Vec<String> {
+        let node = self.node.read().await;
+        node.get_rate_oracles().await
+    }
+
+    pub async fn convert_currency(&self, amount: f64, from: &CurrencyType, to: &CurrencyType) -> IcnResult<(f64, f64)> {
+        let node = self.node.read().await;
+        let rate = node.get_exchange_rate(from, to).await?;
+        Ok((node.convert_currency(amount, from, to).await?, rate))
+    }
+
+    pub async fn submit_oracle_quote(&self, quote: icn_currency::OracleQuote) -> IcnResult<icn_currency::ExchangeRate> {
+        let node = self.node.read().await;
+        node.submit_oracle_quote(quote).await
+    }
+
+    pub async fn get_parameters(&self) -> Vec<icn_common::ParameterDefinition> {
+        let node = self.node.read().await;
+        node.get_parameters().await
+    }
+
+    pub async fn get_parameter_history(&self, name: Option<&str>) -> Vec<icn_common::ParameterChange> {
+        let node = self.node.read().await;
+        node.get_parameter_history(name).await
+    }
+
+    pub async fn get_mempool(&self, address: Option<&str>) -> Vec<icn_core::MempoolEntry> {
+        let node = self.node.read().await;
+        node.get_mempool(address).await
+    }
+
+    pub async fn store_memo(&self, content: &str) -> IcnResult<TransactionMemo> {
+        let node = self.node.read().await;
+        node.store_memo(content).await
+    }
+
+    #[cfg(feature = "fault_injection")]
+    pub async fn inject_fault(&self, fault: Fault) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.inject_fault(fault).await
+    }
+
+    #[cfg(feature = "fault_injection")]
+    pub async fn clear_faults(&self) {
+        let node = self.node.read().await;
+        node.clear_faults();
+    }
+
+    #[cfg(feature = "fault_injection")]
+    pub async fn fault_stats(&self) -> FaultStats {
+        let node = self.node.read().await;
+        node.fault_stats()
+    }
+
+    pub async fn search_transactions(&self, filter: &TransactionFilter, offset: usize, limit: usize) -> IcnResult<TransactionPage> {
+        let node = self.node.read().await;
+        node.search_transactions(filter, offset, limit).await
+    }
+
+    pub async fn search_transactions_by_memo(&self, query: &str) -> IcnResult<Vec<icn_core::MemoMatch>> {
+        let node = self.node.read().await;
+        node.search_transactions_by_memo(query).await
+    }
+
+    pub async fn get_chain_height(&self) -> u64 {
+        let node = self.node.read().await;
+        node.get_chain_height().await
+    }
+
+    pub async fn get_block_headers(&self, from: u64, limit: usize) -> Vec<BlockHeader> {
+        let node = self.node.read().await;
+        node.get_block_headers(from, limit).await
+    }
+
+    /// Long-polls for headers after `resume_token`. The node lock is taken
+    /// for one check at a time, so a waiting light client doesn't hold off
+    /// requests that need to write to the node.
+    pub async fn subscribe_headers(&self, resume_token: Option<&str>, limit: usize, wait: std::time::Duration) -> IcnResult<HeaderBatch> {
+        let deadline = tokio::time::Instant::now() + wait.min(icn_core::MAX_HEADER_WAIT);
+        loop {
+            let batch = {
+                let node = self.node.read().await;
+                node.subscribe_headers(resume_token, limit, std::time::Duration::ZERO).await?
+            };
+            if !batch.headers.is_empty() || tokio::time::Instant::now() >= deadline {
+                return Ok(batch);
+            }
+            tokio::time::sleep(icn_core::HEADER_POLL_INTERVAL).await;
+        }
+    }
+
+    pub async fn export_ledger(&self, chart: &icn_currency::ChartOfAccounts, format: icn_currency::LedgerFormat, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> String {
+        let node = self.node.read().await;
+        node.export_ledger(chart, format, from, to).await
+    }
+
+    pub async fn get_blocks(&self, from: u64, limit: usize) -> Vec<icn_blockchain::Block> {
+        let node = self.node.read().await;
+        node.stream_blocks(from..).take(limit).collect().await
+    }
+
+    pub async fn stream_blocks(&self, from: u64, to: Option<u64>) -> impl futures_util::Stream<Item = icn_blockchain::Block> + Send + 'static {
+        let node = self.node.read().await;
+        let end = to.map_or(Bound::Unbounded, Bound::Excluded);
+        node.stream_blocks((Bound::Included(from), end))
+    }
+
+    pub async fn get_transaction_proof(&self, hash: &str) -> IcnResult<TransactionProof> {
+        let node = self.node.read().await;
+        node.get_transaction_proof(hash).await
+    }
+
+    pub async fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<icn_core::NodeEvent> {
+        let node = self.node.read().await;
+        node.subscribe_events()
+    }
+
+    pub async fn get_alerts(&self, limit: usize) -> Vec<icn_core::Alert> {
+        let node = self.node.read().await;
+        node.get_alerts(limit).await
+    }
+
+    pub async fn subscribe_alerts(&self) -> tokio::sync::broadcast::Receiver<icn_core::Alert> {
+        let node = self.node.read().await;
+        node.subscribe_alerts().await
+    }
+
+    pub async fn pay_bond_coupons(&self, bond_id: &str) -> IcnResult<f64> {
+        let node = self.node.read().await;
+        node.pay_bond_coupons(bond_id).await
+    }
+
+    pub async fn redeem_bond(&self, bond_id: &str, holder: &str) -> IcnResult<f64> {
+        let node = self.node.read().await;
+        node.redeem_bond(bond_id, holder).await
+    }
+
+    pub async fn issue_asset_token(&self, issuer: &str, terms: icn_currency::AssetTokenTerms, initial_supply: Amount) -> IcnResult<String> {
+        let node = self.node.read().await;
+        node.issue_asset_token(issuer, terms, initial_supply).await
+    }
+
+    pub async fn get_asset_token(&self, asset_id: &str) -> IcnResult<icn_currency::AssetToken> {
+        let node = self.node.read().await;
+        node.get_asset_token(asset_id).await
+    }
+
+    pub async fn mint_asset_tokens(&self, asset_id: &str, caller: &str, amount: Amount) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.mint_asset_tokens(asset_id, caller, amount).await
+    }
+
+    pub async fn redeem_asset_tokens(&self, asset_id: &str, holder: &str, amount: Amount) -> IcnResult<Amount> {
+        let node = self.node.read().await;
+        node.redeem_asset_tokens(asset_id, holder, amount).await
+    }
+
+    pub async fn get_trace(&self, correlation_id: &str) -> IcnResult<Vec<icn_core::TraceEvent>> {
+        let node = self.node.read().await;
+        node.get_trace(correlation_id)
+    }
+
+    pub async fn get_pause_status(&self) -> icn_core::PauseStatus {
+        let node = self.node.read().await;
+        node.get_pause_status().await
+    }
+
+    pub async fn apply_guardian_signatures(&self, paused: bool, reason: Option<String>, signatures: Vec<icn_core::GuardianSignature>) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.apply_guardian_signatures(paused, reason, signatures).await
+    }
+
+    pub async fn get_maintenance_status(&self) -> icn_core::MaintenanceStatus {
+        let node = self.node.read().await;
+        node.get_maintenance_status().await
+    }
+
+    pub async fn set_maintenance_mode(&self, enabled: bool, reason: Option<String>) {
+        let node = self.node.read().await;
+        node.set_maintenance_mode(enabled, reason).await
+    }
+
+    pub async fn get_runtime_settings(&self) -> icn_core::RuntimeSettings {
+        let node = self.node.read().await;
+        node.get_runtime_settings().await
+    }
+
+    pub async fn update_runtime_settings(&self, token: &CapabilityToken, change: icn_core::SettingsChange) -> IcnResult<icn_core::ConfigAuditEntry> {
+        let node = self.node.read().await;
+        node.update_runtime_settings(token, change).await
+    }
+
+    pub async fn get_config_audit(&self, limit: usize) -> Vec<icn_core::ConfigAuditEntry> {
+        let node = self.node.read().await;
+        node.get_config_audit(limit).await
+    }
+
+    pub async fn create_identity(&self, attributes: std::collections::HashMap<String, String>) -> IcnResult<String> {
+        let node = self.node.write().await;
+        node.create_identity(attributes).await
+    }
+
+    pub async fn import_identities(&self, data: &str, format: RosterFormat, dry_run: bool) -> IcnResult<icn_identity::ImportReport> {
+        let node = self.node.read().await;
+        node.import_identities(data, format, dry_run, None).await
+    }
+
+    pub async fn export_identities(&self) -> Vec<icn_identity::ExportedIdentity> {
+        let node = self.node.read().await;
+        node.export_identities().await
+    }
+
+    pub async fn register_identity_key(&self, id: &str, key: SchemePublicKey, authorization: &[u8], proof_of_possession: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.register_identity_key(id, key, authorization, proof_of_possession).await
+    }
+
+    pub async fn get_identity_keys(&self, id: &str) -> IcnResult<Vec<SchemePublicKey>> {
+        let node = self.node.read().await;
+        node.get_identity_keys(id).await
+    }
+
+    pub async fn get_request_nonce(&self, id: &str) -> IcnResult<u64> {
+        let node = self.node.read().await;
+        node.get_request_nonce(id).await
+    }
+
+    pub async fn add_attestation(&self, issuer: &str, subject: &str, claim: &str, issued_at: i64, signature: &[u8]) -> IcnResult<MemberAttestation> {
+        let node = self.node.read().await;
+        node.add_attestation(issuer, subject, claim, issued_at, signature).await
+    }
+
+    pub async fn revoke_attestation(&self, subject: &str, attestation_id: &str, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.revoke_attestation(subject, attestation_id, signature).await
+    }
+
+    pub async fn get_attestations(&self, subject: &str, include_revoked: bool) -> IcnResult<Vec<MemberAttestation>> {
+        let node = self.node.read().await;
+        node.get_attestations(subject, include_revoked).await
+    }
+
+    pub async fn seal_identity_attribute(&self, owner: &str, name: &str, value: &str, policy: DisclosurePolicy, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.seal_identity_attribute(owner, name, value, policy, signature).await
+    }
+
+    pub async fn present_identity(&self, subject: &str, verifier: &str, requested_at: i64, signature: &[u8]) -> IcnResult<IdentityPresentation> {
+        let node = self.node.read().await;
+        node.present_identity(subject, verifier, requested_at, signature).await
+    }
+
+    pub async fn get_disclosures(&self, owner: &str) -> IcnResult<Vec<DisclosureRecord>> {
+        let node = self.node.read().await;
+        node.get_disclosures(owner).await
+    }
+
+    pub async fn get_reputation_history(&self, id: &str) -> IcnResult<Vec<ReputationChange>> {
+        let node = self.node.read().await;
+        node.get_reputation_history(id).await
+    }
+
+    pub async fn get_trailing_reputation(&self, id: &str, window_secs: i64) -> IcnResult<f64> {
+        let node = self.node.read().await;
+        node.get_trailing_reputation(id, window_secs).await
+    }
+
+    pub async fn get_activity_feed(&self, id: &str, limit: usize) -> Vec<icn_core::ActivityItem> {
+        let node = self.node.read().await;
+        node.get_activity_feed(id, limit).await
+    }
+
+    pub async fn get_notification_preferences(&self, id: &str) -> IcnResult<NotificationPreferences> {
+        let node = self.node.read().await;
+        node.get_notification_preferences(id).await
+    }
+
+    pub async fn set_notification_preferences(&self, id: &str, preferences: NotificationPreferences) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.set_notification_preferences(id, preferences).await
+    }
+
+    pub async fn post_dao_announcement(&self, dao_id: &str, author: &str, message: &str) -> IcnResult<usize> {
+        let node = self.node.read().await;
+        node.post_dao_announcement(dao_id, author, message).await
+    }
+
+    pub async fn allocate_resource(&self, resource_type: &str, amount: u64) -> IcnResult<()> {
+        let node = self.node.write().await;
+        node.allocate_resource(resource_type, amount).await
+    }
+
+    pub async fn get_network_stats(&self) -> IcnResult<icn_common::NetworkStats> {
+        let node = self.node.read().await;
+        node.get_network_stats().await
+    }
+
+    pub async fn get_peer_latencies(&self) -> Vec<(std::net::SocketAddr, PeerLatency)> {
+        let node = self.node.read().await;
+        node.get_peer_latencies().await
+    }
+
+    pub async fn get_network_time(&self) -> (DateTime<Utc>, i64, Vec<(std::net::SocketAddr, icn_network::ClockSample)>) {
+        let node = self.node.read().await;
+        node.get_network_time().await
+    }
+
+    pub async fn get_propagation_stats(&self) -> std::collections::BTreeMap<String, PropagationStats> {
+        let node = self.node.read().await;
+        node.get_propagation_stats().await
+    }
+
+    pub async fn ban_peer(&self, peer: &str, duration_secs: u64) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.ban_peer(peer, duration_secs).await
+    }
+
+    pub async fn unban_peer(&self, peer: &str) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.unban_peer(peer).await
+    }
+
+    pub async fn get_banned_peers(&self) -> Vec<(std::net::SocketAddr, std::time::Duration)> {
+        let node = self.node.read().await;
+        node.get_banned_peers().await
+    }
+
+    // New method to get proposal status
+    pub async fn get_vote_receipt(&self, proposal_id: &str, voter: &str) -> IcnResult<icn_governance::VoteReceipt> {
+        let node = self.node.read().await;
+        node.get_vote_receipt(proposal_id, voter).await
+    }
+
+    pub async fn cast_ballot(&self, proposal_id: &str, voter: String, choices: Vec<String>, timestamp: i64, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.cast_ballot(proposal_id, voter, choices, timestamp, signature).await
+    }
+
+    pub async fn get_option_tally(&self, proposal_id: &str) -> IcnResult<icn_governance::OptionTally> {
+        let node = self.node.read().await;
+        node.get_option_tally(proposal_id).await
+    }
+
+    pub async fn get_voter_snapshot(&self, proposal_id: &str) -> IcnResult<icn_governance::VoterSnapshot> {
+        let node = self.node.read().await;
+        node.get_voter_snapshot(proposal_id).await?
+            .ok_or_else(|| IcnError::Governance(format!("Proposal {} has no voter snapshot", proposal_id)))
+    }
+
+    pub async fn list_proposal_votes(&self, proposal_id: &str, filter: &icn_governance::VoteFilter, offset: usize, limit: usize) -> IcnResult<icn_governance::VotePage> {
+        let node = self.node.read().await;
+        node.list_proposal_votes(proposal_id, filter, offset, limit).await
+    }
+
+    pub async fn simulate_proposal_outcome(&self, proposal_id: &str) -> IcnResult<icn_governance::OutcomeProjection> {
+        let node = self.node.read().await;
+        node.simulate_proposal_outcome(proposal_id).await
+    }
+
+    pub async fn get_proposal_status(&self, proposal_id: &str) -> IcnResult<ProposalStatus> {
+        let node = self.node.read().await;
+        node.get_proposal_status(proposal_id).await
+    }
+
+    pub async fn get_proposal_timelock(&self, proposal_id: &str) -> IcnResult<Option<icn_governance::ProposalTimelock>> {
+        let node = self.node.read().await;
+        node.get_proposal_timelock(proposal_id).await
+    }
+
+    pub async fn get_pending_vetoes(&self, proposal_id: &str) -> IcnResult<Vec<String>> {
+        let node = self.node.read().await;
+        node.get_pending_vetoes(proposal_id).await
+    }
+
+    // New method to get block information
+    pub async fn get_block_info(&self, identifier: &str) -> IcnResult<icn_blockchain::Block> {
+        let node = self.node.read().await;
+        if let Ok(height) = identifier.parse::<u64>() {
+            node.get_block_by_height(height).await
+        } else {
+            node.get_block_by_hash(identifier).await
+        }
+    }
+
+    // New method to get current network difficulty
+    pub async fn get_network_difficulty(&self) -> IcnResult<f64> {
+        let node = self.node.read().await;
+        node.get_network_difficulty().await
+    }
+
+    // New method to submit a new smart contract
+    pub async fn submit_smart_contract(&self, deployer: &str, code: String, init_args: Vec<icn_vm::Value>) -> IcnResult<String> {
+        let node = self.node.write().await;
+        node.deploy_contract(deployer, code, init_args).await
+    }
+
+    pub async fn validate_contract(&self, code: &str) -> IcnResult<icn_smart_contracts::AnalysisReport> {
+        let node = self.node.read().await;
+        node.validate_contract(code).await
+    }
+
+    pub async fn get_contract_abi(&self, contract_id: &str) -> IcnResult<icn_smart_contracts::ContractABI> {
+        let node = self.node.read().await;
+        node.get_contract_abi(contract_id).await
+    }
+
+    // New method to execute a smart contract
+    pub async fn execute_smart_contract(&self, caller: &str, contract_id: &str, function: &str, args: Vec<icn_vm::Value>, nonce: u64, signature: &[u8]) -> IcnResult<Option<icn_vm::Value>> {
+        let node = self.node.write().await;
+        node.execute_smart_contract(caller, contract_id, function, args, nonce, signature).await
+    }
+
+    pub async fn run_script(&self, caller: &str, source: &str) -> IcnResult<Vec<icn_core::HostCall>> {
+        let node = self.node.read().await;
+        node.run_script(caller, source).await
+    }
+
+    pub async fn set_contract_permissions(&self, caller: &str, contract_id: &str, function: &str, roles: Option<Vec<String>>, nonce: u64, signature: &[u8]) -> IcnResult<()> {
+        let node = self.node.read().await;
+        node.set_contract_permissions(caller, contract_id, function, roles, nonce, signature).await
+    }
+
+    pub async fn get_contract_permissions(&self, contract_id: &str) -> IcnResult<icn_smart_contracts::AccessPolicy> {
+        let node = self.node.read().await;
+        node.get_contract_permissions(contract_id).await
+    }
+}
+
+// Request and response structs
+// The proposer picks the id and creation time, since the signature covers them.
+#[derive(Deserialize)]
+struct CreateProposalRequest {
+    id: String,
+    created_at: DateTime<Utc>,
+    /// Hex-encoded signature by the proposer over `Proposal::signing_bytes`;
+    /// left out when preparing the proposal.
+    #[serde(default)]
+    signature: String,
+    title: String,
+    description: String,
+    proposer: String,
+    proposal_type: ProposalType,
+    category: ProposalCategory,
+    #[serde(default)]
+    weight_mode: VotingWeightMode,
+    #[serde(default)]
+    actions: Vec<ProposalAction>,
+    #[serde(default)]
+    scope: ProposalScope,
+    #[serde(default)]
+    ballot: BallotKind,
+    #[serde(default)]
+    depends_on: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CreateTemplateProposalRequest {
+    template_id: String,
+    proposal_id: String,
+    created_at: DateTime<Utc>,
+    /// Hex-encoded signature by the proposer; left out when preparing.
+    #[serde(default)]
+    signature: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+    proposer: String,
+    arguments: icn_governance::TemplateArguments,
+}
+
+#[derive(Serialize)]
+struct CreateProposalResponse {
+    proposal_id: String,
+}
+
+/// A proposal as the node will store it, for its proposer to sign.
+#[derive(Serialize)]
+struct PreparedProposal {
+    proposal: Proposal,
+    /// Hex encoding of the exact bytes the signature must cover.
+    signing_bytes: String,
+}
+
+// Members are signed with `bundle` set, so the bundle id is the client's too.
+#[derive(Deserialize)]
+struct CreateBundleRequest {
+    bundle_id: String,
+    /// In execution order.
+    proposals: Vec<CreateProposalRequest>,
+}
+
+#[derive(Serialize)]
+struct CreateBundleResponse {
+    bundle_id: String,
+    proposal_ids: Vec<String>,
+}
+
+// Vote weight is derived by the node, so a client-supplied `weight` is rejected.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct VoteOnProposalRequest {
+    proposal_id: String,
+    voter: String,
+    in_favor: bool,
+    timestamp: i64,
+    /// Hex-encoded signature by the voter over `Vote::signing_bytes`.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PrepareVoteRequest {
+    proposal_id: String,
+    voter: String,
+    in_favor: bool,
+    timestamp: i64,
+}
+
+#[derive(Deserialize)]
+struct CastBallotRequest {
+    voter: String,
+    choices: Vec<String>,
+    timestamp: i64,
+    /// Hex-encoded signature by the voter over `SignedBallot::signing_bytes`.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PrepareBallotRequest {
+    voter: String,
+    choices: Vec<String>,
+    timestamp: i64,
+}
+
+#[derive(Deserialize)]
+struct GetProposalStatusRequest {
+    proposal_id: String,
+}
+
+#[derive(Serialize)]
+struct GetProposalStatusResponse {
+    status: ProposalStatus,
+}
+
+#[derive(Serialize)]
+struct ProposalTimelockResponse {
+    status: ProposalStatus,
+    timelock: Option<icn_governance::ProposalTimelock>,
+    pending_vetoes: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct GetBlockInfoRequest {
+    identifier: String,
+}
+
+#[derive(Serialize)]
+struct GetBlockInfoResponse {
+    block: icn_blockchain::Block,
+}
+
+#[derive(Serialize)]
+struct GetNetworkDifficultyResponse {
+    difficulty: f64,
+}
+
+#[derive(Deserialize)]
+struct SubmitSmartContractRequest {
+    /// DID of the identity deploying the contract, which becomes its owner.
+    deployer: String,
+    code: String,
+    /// Arguments for the contract's `init` constructor, if it has one.
+    #[serde(default)]
+    init_args: Vec<icn_vm::Value>,
+}
+
+#[derive(Serialize)]
+struct SubmitSmartContractResponse {
+    contract_id: String,
+}
+
+#[derive(Deserialize)]
+struct ValidateContractRequest {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RunScriptRequest {
+    caller: String,
+    /// icn_language source, such as `vote-on-proposal("p1", true)`.
+    source: String,
+}
+
+#[derive(Deserialize)]
+struct ExecuteSmartContractRequest {
+    /// DID of the identity making the call; its roles are checked against
+    /// the contract's permissions.
+    caller: String,
+    contract_id: String,
+    function: String,
+    args: Vec<icn_vm::Value>,
+    nonce: u64,
+    /// Hex-encoded signature by the caller over the `call-contract` request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct SetContractPermissionsRequest {
+    caller: String,
+    function: String,
+    /// Roles allowed to call the function; `null` opens it to everyone.
+    roles: Option<Vec<String>>,
+    nonce: u64,
+    /// Hex-encoded signature by the owner over the
+    /// `set-contract-permissions` request.
+    signature: String,
+}
+
+#[derive(Serialize)]
+struct ExecuteSmartContractResponse {
+    result: Option<icn_vm::Value>,
+}
+
+#[derive(Deserialize)]
+struct ExchangeCurrencyRequest {
+    from: String,
+    source_currency: CurrencyType,
+    target_currency: CurrencyType,
+    amount: Amount,
+    #[serde(default)]
+    min_amount_out: Amount,
+}
+
+#[derive(Serialize)]
+struct ExchangeCurrencyResponse {
+    amount_received: Amount,
+}
+
+#[derive(Deserialize)]
+struct AddLiquidityRequest {
+    provider: String,
+    pool_id: String,
+    amount_a: Amount,
+    amount_b: Amount,
+}
+
+#[derive(Deserialize)]
+struct RemoveLiquidityRequest {
+    provider: String,
+    pool_id: String,
+    shares: Amount,
+}
+
+#[derive(Deserialize)]
+pub struct VestedTransferRequest {
+    from: String,
+    to: String,
+    amount: Amount,
+    currency_type: CurrencyType,
+    #[serde(default)]
+    cliff_secs: i64,
+    duration_secs: i64,
+}
+
+#[derive(Deserialize)]
+struct IssueBondRequest {
+    issuer: String,
+    terms: icn_currency::BondTerms,
+    units: f64,
+}
+
+#[derive(Deserialize)]
+struct RedeemBondRequest {
+    holder: String,
+}
+
+#[derive(Deserialize)]
+struct IssueAssetTokenRequest {
+    issuer: String,
+    terms: icn_currency::AssetTokenTerms,
+    initial_supply: Amount,
+}
+
+#[derive(Deserialize)]
+struct MintAssetTokensRequest {
+    caller: String,
+    amount: Amount,
+}
+
+#[derive(Deserialize)]
+struct RedeemAssetTokensRequest {
+    holder: String,
+    amount: Amount,
+}
+
+#[derive(Deserialize)]
+struct GuardianPauseRequest {
+    paused: bool,
+    reason: Option<String>,
+    signatures: Vec<icn_core::GuardianSignature>,
+}
+
+#[derive(Deserialize)]
+struct MaintenanceRequest {
+    enabled: bool,
+    reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PrepareTransactionRequest {
+    from: String,
+    to: String,
+    amount: f64,
+    currency_type: CurrencyType,
+    #[serde(default)]
+    memo: Option<TransactionMemo>,
+}
+
+#[derive(Deserialize)]
+struct StoreMemoRequest {
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct TransactionSignatureRequest {
+    /// Hex-encoded signature over the prepared transaction's signing bytes.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct DelegatedTransferRequest {
+    to: String,
+    amount: f64,
+    currency_type: CurrencyType,
+    timestamp: i64,
+    /// Hex-encoded signature by the token's delegate over the transfer's
+    /// `Transaction::signing_bytes`.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct RevokeCapabilityRequest {
+    issuer: String,
+    token_id: String,
+    /// Hex-encoded signature by the issuer over `revoke-capability:<token_id>`.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct StoreDataRequest {
+    /// Hex-encoded data.
+    data: String,
+    /// Seconds until the data is deleted; kept until removed if absent.
+    ttl_secs: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct EstimateFeeQuery {
+    #[serde(default)]
+    amount: Amount,
+}
+
+#[derive(Deserialize)]
+struct MempoolQuery {
+    address: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ContractEventsQuery {
+    contract: Option<String>,
+    event: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ImportIdentitiesRequest {
+    format: RosterFormat,
+    data: String,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Deserialize)]
+struct ExportIdentitiesQuery {
+    format: Option<RosterFormat>,
+}
+
+#[derive(Deserialize)]
+struct RegisterKeyRequest {
+    key: SchemePublicKey,
+    authorization: Vec<u8>,
+    proof_of_possession: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct AttestationRequest {
+    issuer: String,
+    claim: String,
+    issued_at: i64,
+    signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct AttestationsQuery {
+    #[serde(default)]
+    include_revoked: bool,
+}
+
+#[derive(Deserialize)]
+struct ReputationHistoryQuery {
+    window_secs: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct RevokeAttestationRequest {
+    signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct SealAttributeRequest {
+    name: String,
+    value: String,
+    policy: DisclosurePolicy,
+    signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct PresentationRequest {
+    verifier: String,
+    requested_at: i64,
+    signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct BanPeerRequest {
+    peer: String,
+    duration_secs: u64,
+}
+
+#[derive(Deserialize)]
+struct MemoSearchQuery {
+    memo: String,
+}
+
+#[derive(Deserialize)]
+struct TransactionSearchQuery {
+    address: Option<String>,
+    currency_type: Option<CurrencyType>,
+    from_time: Option<i64>,
+    to_time: Option<i64>,
+    min_amount: Option<f64>,
+    max_amount: Option<f64>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    50
+}
+
+#[derive(Deserialize)]
+struct ProposalVotesQuery {
+    in_favor: Option<bool>,
+    min_weight: Option<f64>,
+    max_weight: Option<f64>,
+    from_time: Option<DateTime<Utc>>,
+    to_time: Option<DateTime<Utc>>,
+    #[serde(default)]
+    offset: usize,
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct ConfidentialAccountRequest {
+    address: String,
+    /// Hex-encoded public viewing key.
+    viewing_key: String,
+}
+
+#[derive(Deserialize)]
+struct ConfidentialBalanceQuery {
+    address: String,
+    currency_type: CurrencyType,
+}
+
+#[derive(Deserialize)]
+struct DaoProposalRequest {
+    title: String,
+    description: String,
+    proposer: String,
+    duration_secs: i64,
+    contract_call: Option<ContractCall>,
+}
+
+#[derive(Deserialize)]
+struct DaoAnnouncementRequest {
+    author: String,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct DaoVoteRequest {
+    member: String,
+    in_favor: bool,
+}
+
+#[derive(Deserialize)]
+struct ContributionsQuery {
+    contributor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MilestoneRequest {
+    description: String,
+    amount: Amount,
+    due: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct GrantApplicationRequest {
+    applicant: String,
+    title: String,
+    #[serde(default)]
+    description: String,
+    milestones: Vec<MilestoneRequest>,
+}
+
+#[derive(Deserialize)]
+struct GrantVoteRequest {
+    member: String,
+    #[serde(default)]
+    amount: Amount,
+    nonce: u64,
+    /// Hex-encoded signature by the member over the `grant-vote` request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct MilestoneSettlementRequest {
+    steward: String,
+    delivered: bool,
+    nonce: u64,
+    /// Hex-encoded signature by the steward over the `settle-milestone` request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PostListingRequest {
+    seller: String,
+    kind: ListingKind,
+    title: String,
+    #[serde(default)]
+    description: String,
+    unit_price: Amount,
+    currency_type: CurrencyType,
+    quantity: u32,
+}
+
+#[derive(Deserialize)]
+struct ListingsQuery {
+    kind: Option<ListingKind>,
+}
+
+#[derive(Deserialize)]
+struct SellerRequest {
+    seller: String,
+    nonce: u64,
+    /// Hex-encoded signature by the seller over the request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct PlaceOrderRequest {
+    buyer: String,
+    quantity: u32,
+    nonce: u64,
+    /// Hex-encoded signature by the buyer over the `place-order` request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct BuyerRequest {
+    buyer: String,
+    nonce: u64,
+    /// Hex-encoded signature by the buyer over the request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct DisputeOrderRequest {
+    claimant: String,
+    description: String,
+}
+
+#[derive(Deserialize)]
+struct ProfitDistributionRequest {
+    steward: String,
+    total_profit: f64,
+    currency_type: CurrencyType,
+}
+
+#[derive(Deserialize)]
+struct TenantCooperativeRequest {
+    name: String,
+    business_type: String,
+    quorum: f64,
+    majority: f64,
+}
+
+#[derive(Deserialize)]
+struct TenantCurrencyRequest {
+    name: String,
+    #[serde(default)]
+    initial_supply: f64,
+    #[serde(default)]
+    issuance_rate: f64,
+}
+
+#[derive(Deserialize)]
+struct TenantMintRequest {
+    to: String,
+    amount: f64,
+}
+
+#[derive(Deserialize)]
+struct TenantStoreRequest {
+    /// Hex-encoded data.
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct BridgeDepositRequest {
+    deposit: ExternalDeposit,
+    attestation: Attestation,
+}
+
+#[derive(Deserialize)]
+struct BridgeWithdrawalRequest {
+    holder: String,
+    asset_id: String,
+    amount: Amount,
+    external_recipient: String,
+    nonce: u64,
+    /// Hex-encoded signature by the holder over the `bridge-withdrawal`
+    /// request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct BridgeChallengeRequest {
+    relayer: String,
+    reason: String,
+    signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct BlockSignature {
+    validator: String,
+    signature: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct CertifyBlockRequest {
+    approvals: Vec<BlockSignature>,
+}
+
+#[derive(Deserialize)]
+struct FraudChallengeRequest {
+    challenger: String,
+    block_index: u64,
+}
+
+#[derive(Deserialize)]
+struct DoubleSignEvidenceRequest {
+    reporter: String,
+    evidence: icn_common::DoubleSignEvidence,
+}
+
+#[derive(Deserialize)]
+struct BlockHeadersQuery {
+    #[serde(default)]
+    from: u64,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SubscribeHeadersQuery {
+    token: Option<String>,
+    limit: Option<usize>,
+    #[serde(default)]
+    wait_secs: u64,
+}
+
+#[derive(Deserialize)]
+struct LedgerExportRequest {
+    format: icn_currency::LedgerFormat,
+    #[serde(default)]
+    chart: icn_currency::ChartOfAccounts,
+    from: Option<DateTime<Utc>>,
+    /// Exclusive.
+    to: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize)]
+struct BlocksQuery {
+    #[serde(default)]
+    from: u64,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct BlockExportQuery {
+    #[serde(default)]
+    from: u64,
+    /// Exclusive; the export runs to the tip when missing.
+    to: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct RateHistoryQuery {
+    base: Option<CurrencyType>,
+    quote: Option<CurrencyType>,
+}
+
+#[derive(Deserialize)]
+struct ConvertCurrencyQuery {
+    amount: f64,
+    from: CurrencyType,
+    to: CurrencyType,
+}
+
+#[derive(Serialize)]
+struct ConvertCurrencyResponse {
+    amount: f64,
+    rate: f64,
+}
+
+#[derive(Deserialize)]
+struct ParameterHistoryQuery {
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GetAlertsQuery {
+    #[serde(default = "default_alert_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct ReserveInclusionQuery {
+    address: String,
+    currency_type: CurrencyType,
+    nonce: u64,
+    /// Hex-encoded signature by the address over the `reserve-inclusion`
+    /// request.
+    signature: String,
+}
+
+#[derive(Deserialize)]
+struct ConfigAuditQuery {
+    #[serde(default = "default_config_audit_limit")]
+    limit: usize,
+}
+
+#[derive(Deserialize)]
+struct ActivityFeedQuery {
+    #[serde(default = "default_activity_limit")]
+    limit: usize,
+}
+
+fn default_activity_limit() -> usize {
+    50
+}
+
+fn default_alert_limit() -> usize {
+    100
+}
+
+fn default_config_audit_limit() -> usize {
+    50
+}
+
+// Helper function to convert IcnError to warp::Rejection
+fn icn_error_to_rejection(error: IcnError) -> warp::Rejection {
+    warp::reject::custom(ApiError::from(error))
+}
+
+/// Decodes the hex signature on a request a member signs.
+fn decode_signature(signature: &str) -> Result<Vec<u8>, Rejection> {
+    hex::decode(signature)
+        .map_err(|e| icn_error_to_rejection(IcnError::Validation(format!("Invalid signature encoding: {}", e))))
+}
+
+/// Reads the token from an `Authorization: Capability <token>` header.
+fn parse_capability_header(authorization: &str) -> IcnResult<CapabilityToken> {
+    let encoded = authorization.strip_prefix("Capability ")
+        .ok_or_else(|| IcnError::Identity("Expected an Authorization header of the form \"Capability <token>\"".into()))?;
+    CapabilityToken::decode(encoded)
+}
+
+// API routes. Every error is answered with an RFC 7807 problem+json body.
+pub fn api_routes(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> impl Filter<Extract = (impl Reply,), Error = Infallible> + Clone {
+    #[cfg(feature = "fault_injection")]
+    let faults = fault_routes(api_layer.clone());
+    let api_layer = warp::any().map(move || api_layer.clone());
+
+    let submit_transaction = warp::post()
+        .and(warp::path("transaction"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_submit_transaction);
+
+    let prepare_transaction = warp::post()
+        .and(warp::path!("transaction" / "prepare"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_prepare_transaction);
+
+    let submit_transaction_signature = warp::post()
+        .and(warp::path!("transaction" / String / "signature"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_submit_transaction_signature);
+
+    let get_receipt = warp::get()
+        .and(warp::path!("receipt" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_receipt);
+
+    let get_transaction_availability = warp::get()
+        .and(warp::path!("transactions" / String / "availability"))
+        .and(api_layer.clone())
+        .and_then(handle_get_transaction_availability);
+
+    let get_shard_history = warp::get()
+        .and(warp::path!("shards" / u64 / "history"))
+        .and(api_layer.clone())
+        .and_then(handle_get_shard_history);
+
+    let create_proposal = warp::post()
+        .and(warp::path("proposal"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_create_proposal);
+
+    let list_proposal_templates = warp::get()
+        .and(warp::path!("proposal" / "templates"))
+        .and(api_layer.clone())
+        .and_then(handle_list_proposal_templates);
+
+    let prepare_proposal = warp::post()
+        .and(warp::path!("proposal" / "prepare"))
+        .and(warp::body::json())
+        .and_then(handle_prepare_proposal);
+
+    let create_proposal_bundle = warp::post()
+        .and(warp::path!("proposal" / "bundle"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_create_proposal_bundle);
+
+    let prepare_proposal_bundle = warp::post()
+        .and(warp::path!("proposal" / "bundle" / "prepare"))
+        .and(warp::body::json())
+        .and_then(handle_prepare_proposal_bundle);
+
+    let create_template_proposal = warp::post()
+        .and(warp::path!("proposal" / "template"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_create_template_proposal);
+
+    let prepare_template_proposal = warp::post()
+        .and(warp::path!("proposal" / "template" / "prepare"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_prepare_template_proposal);
+
+    let vote_on_proposal = warp::post()
+        .and(warp::path("vote"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_vote_on_proposal);
+
+    let prepare_vote = warp::post()
+        .and(warp::path!("vote" / "prepare"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_prepare_vote);
+
+    let delegated_transfer = warp::post()
+        .and(warp::path!("delegated" / "transfer"))
+        .and(warp::header::<String>("authorization"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_delegated_transfer);
+
+    let revoke_capability = warp::post()
+        .and(warp::path!("capabilities" / "revoke"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_revoke_capability);
+
+    let list_revoked_capabilities = warp::get()
+        .and(warp::path!("capabilities" / "revoked"))
+        .and(api_layer.clone())
+        .and_then(handle_list_revoked_capabilities);
+
+    let get_balance = warp::get()
+        .and(warp::path("balance"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_balance);
+
+    let transfer_vested = warp::post()
+        .and(warp::path!("transfer" / "vested"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_transfer_vested);
+
+    let mint_currency = warp::post()
+        .and(warp::path("mint"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_mint_currency);
+
+    let create_identity = warp::post()
+        .and(warp::path("identity"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_create_identity);
+
+    let import_identities = warp::post()
+        .and(warp::path!("identities" / "import"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_import_identities);
+
+    let export_identities = warp::get()
+        .and(warp::path!("identities" / "export"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_export_identities);
+
+    let register_identity_key = warp::post()
+        .and(warp::path!("identities" / String / "keys"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_register_identity_key);
+
+    let get_identity_keys = warp::get()
+        .and(warp::path!("identities" / String / "keys"))
+        .and(api_layer.clone())
+        .and_then(handle_get_identity_keys);
+
+    let get_request_nonce = warp::get()
+        .and(warp::path!("identities" / String / "nonce"))
+        .and(api_layer.clone())
+        .and_then(handle_get_request_nonce);
+
+    let add_attestation = warp::post()
+        .and(warp::path!("identities" / String / "attestations"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_add_attestation);
+
+    let get_attestations = warp::get()
+        .and(warp::path!("identities" / String / "attestations"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_attestations);
+
+    let revoke_attestation = warp::post()
+        .and(warp::path!("identities" / String / "attestations" / String / "revoke"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_revoke_attestation);
+
+    let seal_identity_attribute = warp::post()
+        .and(warp::path!("identities" / String / "sealed"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_seal_identity_attribute);
+
+    let present_identity = warp::post()
+        .and(warp::path!("identities" / String / "presentation"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_present_identity);
+
+    let get_disclosures = warp::get()
+        .and(warp::path!("identities" / String / "disclosures"))
+        .and(api_layer.clone())
+        .and_then(handle_get_disclosures);
+
+    let get_reputation_history = warp::get()
+        .and(warp::path!("identities" / String / "reputation" / "history"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_reputation_history);
+
+    let get_activity_feed = warp::get()
+        .and(warp::path!("identities" / String / "activity"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_activity_feed);
+
+    let get_notification_preferences = warp::get()
+        .and(warp::path!("identities" / String / "notifications"))
+        .and(api_layer.clone())
+        .and_then(handle_get_notification_preferences);
+
+    let set_notification_preferences = warp::put()
+        .and(warp::path!("identities" / String / "notifications"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_set_notification_preferences);
+
+    let allocate_resource = warp::post()
+        .and(warp::path("allocate"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_allocate_resource);
+
+    let get_network_stats = warp::get()
+        .and(warp::path("stats"))
+        .and(api_layer.clone())
+        .and_then(handle_get_network_stats);
+
+    let get_peer_latencies = warp::get()
+        .and(warp::path!("network" / "latency"))
+        .and(api_layer.clone())
+        .and_then(handle_get_peer_latencies);
+
+    let get_network_time = warp::get()
+        .and(warp::path!("network" / "time"))
+        .and(api_layer.clone())
+        .and_then(handle_get_network_time);
+
+    let get_propagation_stats = warp::get()
+        .and(warp::path!("network" / "propagation"))
+        .and(api_layer.clone())
+        .and_then(handle_get_propagation_stats);
+
+    let get_banned_peers = warp::get()
+        .and(warp::path!("network" / "bans"))
+        .and(api_layer.clone())
+        .and_then(handle_get_banned_peers);
+
+    let ban_peer = warp::post()
+        .and(warp::path!("network" / "bans"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_ban_peer);
+
+    let unban_peer = warp::delete()
+        .and(warp::path!("network" / "bans" / String))
+        .and(api_layer.clone())
+        .and_then(handle_unban_peer);
+
+    let get_vote_receipt = warp::get()
+        .and(warp::path!("vote" / String / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_vote_receipt);
+
+    let cast_ballot = warp::post()
+        .and(warp::path!("proposal" / String / "ballot"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_cast_ballot);
+
+    let prepare_ballot = warp::post()
+        .and(warp::path!("proposal" / String / "ballot" / "prepare"))
+        .and(warp::body::json())
+        .and_then(handle_prepare_ballot);
+
+    let get_option_tally = warp::get()
+        .and(warp::path!("proposal" / String / "tally"))
+        .and(api_layer.clone())
+        .and_then(handle_get_option_tally);
+
+    let get_voter_snapshot = warp::get()
+        .and(warp::path!("proposal" / String / "snapshot"))
+        .and(api_layer.clone())
+        .and_then(handle_get_voter_snapshot);
+
+    let list_proposal_votes = warp::get()
+        .and(warp::path!("proposal" / String / "votes"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_list_proposal_votes);
+
+    let simulate_proposal = warp::get()
+        .and(warp::path!("proposal" / String / "simulation"))
+        .and(api_layer.clone())
+        .and_then(handle_simulate_proposal);
+
+    let get_proposal_timelock = warp::get()
+        .and(warp::path!("proposal" / String / "timelock"))
+        .and(api_layer.clone())
+        .and_then(handle_get_proposal_timelock);
+
+    let get_proposal_status = warp::get()
+        .and(warp::path("proposal"))
+        .and(warp::path("status"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_proposal_status);
+
+    let get_block_info = warp::get()
+        .and(warp::path("block"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_block_info);
+
+    let get_network_difficulty = warp::get()
+        .and(warp::path("difficulty"))
+        .and(api_layer.clone())
+        .and_then(handle_get_network_difficulty);
+
+    let submit_smart_contract = warp::post()
+        .and(warp::path("contract"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_submit_smart_contract);
+
+    let execute_smart_contract = warp::post()
+        .and(warp::path("contract"))
+        .and(warp::path("execute"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_execute_smart_contract);
+
+    let run_script = warp::post()
+        .and(warp::path("script"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_run_script);
+
+    let exchange_currency = warp::post()
+        .and(warp::path("exchange"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_exchange_currency);
+
+    let list_liquidity_pools = warp::get()
+        .and(warp::path("pools"))
+        .and(api_layer.clone())
+        .and_then(handle_list_liquidity_pools);
+
+    let add_liquidity = warp::post()
+        .and(warp::path("liquidity"))
+        .and(warp::path("add"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_add_liquidity);
+
+    let remove_liquidity = warp::post()
+        .and(warp::path("liquidity"))
+        .and(warp::path("remove"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_remove_liquidity);
+
+    let issue_bond = warp::post()
+        .and(warp::path("bonds"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_issue_bond);
+
+    let get_bond = warp::get()
+        .and(warp::path!("bonds" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_bond);
+
+    let pay_bond_coupons = warp::post()
+        .and(warp::path!("bonds" / String / "coupons"))
+        .and(api_layer.clone())
+        .and_then(handle_pay_bond_coupons);
+
+    let redeem_bond = warp::post()
+        .and(warp::path!("bonds" / String / "redeem"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_redeem_bond);
+
+    let issue_asset_token = warp::post()
+        .and(warp::path("assets"))
+        .and(warp::path::end())
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_issue_asset_token);
+
+    let get_asset_token = warp::get()
+        .and(warp::path!("assets" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_asset_token);
+
+    let mint_asset_tokens = warp::post()
+        .and(warp::path!("assets" / String / "mint"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_mint_asset_tokens);
+
+    let redeem_asset_tokens = warp::post()
+        .and(warp::path!("assets" / String / "redeem"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_redeem_asset_tokens);
+
+    let validate_contract = warp::post()
+        .and(warp::path!("contract" / "validate"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_validate_contract);
+
+    let get_contract_abi = warp::get()
+        .and(warp::path!("contract" / String / "abi"))
+        .and(api_layer.clone())
+        .and_then(handle_get_contract_abi);
+
+    let get_contract_permissions = warp::get()
+        .and(warp::path!("contract" / String / "permissions"))
+        .and(api_layer.clone())
+        .and_then(handle_get_contract_permissions);
+
+    let set_contract_permissions = warp::post()
+        .and(warp::path!("contract" / String / "permissions"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_set_contract_permissions);
+
+    let get_storage_usage = warp::get()
+        .and(warp::path!("storage" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_storage_usage);
+
+    let store_data = warp::post()
+        .and(warp::path!("storage" / String / String))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_store_data);
+
+    let get_storage_gc_stats = warp::get()
+        .and(warp::path!("admin" / "storage" / "gc"))
+        .and(api_layer.clone())
+        .and_then(handle_get_storage_gc_stats);
+
+    let audit_supply = warp::post()
+        .and(warp::path!("admin" / "audit"))
+        .and(api_layer.clone())
+        .and_then(handle_audit_supply);
+
+    let resume_minting = warp::post()
+        .and(warp::path!("admin" / "audit" / "resume-minting"))
+        .and(api_layer.clone())
+        .and_then(handle_resume_minting);
+
+    let publish_reserve_proofs = warp::post()
+        .and(warp::path!("admin" / "reserves"))
+        .and(api_layer.clone())
+        .and_then(handle_publish_reserve_proofs);
+
+    let get_reserve_proofs = warp::get()
+        .and(warp::path!("reserves"))
+        .and(api_layer.clone())
+        .and_then(handle_get_reserve_proofs);
+
+    let verify_reserve_proof = warp::post()
+        .and(warp::path!("reserves" / "verify"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_verify_reserve_proof);
+
+    let get_reserve_inclusion = warp::get()
+        .and(warp::path!("reserves" / "inclusion"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_reserve_inclusion);
+
+    let get_maintenance_status = warp::get()
+        .and(warp::path!("admin" / "maintenance"))
+        .and(api_layer.clone())
+        .and_then(handle_get_maintenance_status);
+
+    let set_maintenance_mode = warp::post()
+        .and(warp::path!("admin" / "maintenance"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_set_maintenance_mode);
+
+    let get_runtime_settings = warp::get()
+        .and(warp::path!("admin" / "config"))
+        .and(api_layer.clone())
+        .and_then(handle_get_runtime_settings);
+
+    let update_runtime_settings = warp::patch()
+        .and(warp::path!("admin" / "config"))
+        .and(warp::header::<String>("authorization"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_update_runtime_settings);
+
+    let get_config_audit = warp::get()
+        .and(warp::path!("admin" / "config" / "audit"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_config_audit);
+
+    let list_archived_proposals = warp::get()
+        .and(warp::path!("admin" / "archive"))
+        .and(api_layer.clone())
+        .and_then(handle_list_archived_proposals);
+
+    let restore_archived_proposal = warp::post()
+        .and(warp::path!("admin" / "archive" / String / "restore"))
+        .and(api_layer.clone())
+        .and_then(handle_restore_archived_proposal);
+
+    let purge_archived_proposal = warp::delete()
+        .and(warp::path!("admin" / "archive" / String))
+        .and(api_layer.clone())
+        .and_then(handle_purge_archived_proposal);
+
+    let list_archived_dao_proposals = warp::get()
+        .and(warp::path!("admin" / "archive" / "cooperatives" / String))
+        .and(api_layer.clone())
+        .and_then(handle_list_archived_dao_proposals);
+
+    let restore_archived_dao_proposal = warp::post()
+        .and(warp::path!("admin" / "archive" / "cooperatives" / String / String / "restore"))
+        .and(api_layer.clone())
+        .and_then(handle_restore_archived_dao_proposal);
+
+    let purge_archived_dao_proposal = warp::delete()
+        .and(warp::path!("admin" / "archive" / "cooperatives" / String / String))
+        .and(api_layer.clone())
+        .and_then(handle_purge_archived_dao_proposal);
+
+    let get_validators = warp::get()
+        .and(warp::path!("validators"))
+        .and(api_layer.clone())
+        .and_then(handle_get_validators);
+
+    let get_validator = warp::get()
+        .and(warp::path!("validators" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_validator);
+
+    let list_account_freezes = warp::get()
+        .and(warp::path!("freezes"))
+        .and(api_layer.clone())
+        .and_then(handle_list_account_freezes);
+
+    let get_account_freeze = warp::get()
+        .and(warp::path!("freezes" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_account_freeze);
+
+    let register_validator_bls_key = warp::post()
+        .and(warp::path!("validators" / String / "bls-key"))
+        .and(api_layer.clone())
+        .and_then(handle_register_validator_bls_key);
+
+    let certify_block = warp::post()
+        .and(warp::path!("committee" / u64 / "certificate"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_certify_block);
+
+    let get_committee = warp::get()
+        .and(warp::path!("committee" / u64))
+        .and(api_layer.clone())
+        .and_then(handle_get_committee);
+
+    let audit_committee = warp::get()
+        .and(warp::path!("committee" / u64 / "audit"))
+        .and(api_layer.clone())
+        .and_then(handle_audit_committee);
+
+    let audit_dispute_jury = warp::get()
+        .and(warp::path!("disputes" / String / "jury" / "audit"))
+        .and(api_layer.clone())
+        .and_then(handle_audit_dispute_jury);
+
+    let register_confidential_account = warp::post()
+        .and(warp::path!("confidential" / "accounts"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_register_confidential_account);
+
+    let shield_funds = warp::post()
+        .and(warp::path!("confidential" / "shield"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_shield_funds);
+
+    let submit_confidential_transfer = warp::post()
+        .and(warp::path!("confidential" / "transfers"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_submit_confidential_transfer);
+
+    let unshield_funds = warp::post()
+        .and(warp::path!("confidential" / "unshield"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_unshield_funds);
+
+    let get_confidential_balance = warp::get()
+        .and(warp::path!("confidential" / "balance"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_confidential_balance);
+
+    let get_confidential_transfers = warp::get()
+        .and(warp::path!("confidential" / "transfers" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_confidential_transfers);
+
+    let create_dao_proposal = warp::post()
+        .and(warp::path!("cooperatives" / String / "proposals"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_create_dao_proposal);
+
+    let post_dao_announcement = warp::post()
+        .and(warp::path!("cooperatives" / String / "announcements"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_post_dao_announcement);
+
+    let vote_on_dao_proposal = warp::post()
+        .and(warp::path!("cooperatives" / String / "proposals" / String / "votes"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_vote_on_dao_proposal);
+
+    let finalize_dao_proposal = warp::post()
+        .and(warp::path!("cooperatives" / String / "proposals" / String / "finalize"))
+        .and(api_layer.clone())
+        .and_then(handle_finalize_dao_proposal);
+
+    let get_dao_proposal = warp::get()
+        .and(warp::path!("cooperatives" / String / "proposals" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_dao_proposal);
+
+    let record_contribution = warp::post()
+        .and(warp::path!("cooperatives" / String / "contributions"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_record_contribution);
+
+    let list_bridge_pegs = warp::get()
+        .and(warp::path!("bridge" / "pegs"))
+        .and(api_layer.clone())
+        .and_then(handle_list_bridge_pegs);
+
+    let attest_bridge_deposit = warp::post()
+        .and(warp::path!("bridge" / "deposits"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_attest_bridge_deposit);
+
+    let get_bridge_deposit = warp::get()
+        .and(warp::path!("bridge" / "deposits" / String / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_bridge_deposit);
+
+    let request_bridge_withdrawal = warp::post()
+        .and(warp::path!("bridge" / "withdrawals"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_request_bridge_withdrawal);
+
+    let challenge_bridge_withdrawal = warp::post()
+        .and(warp::path!("bridge" / "withdrawals" / String / "challenge"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_challenge_bridge_withdrawal);
+
+    let release_bridge_withdrawals = warp::post()
+        .and(warp::path!("bridge" / "withdrawals" / "release"))
+        .and(api_layer.clone())
+        .and_then(handle_release_bridge_withdrawals);
+
+    let get_bridge_withdrawal = warp::get()
+        .and(warp::path!("bridge" / "withdrawals" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_bridge_withdrawal);
+
+    let get_contributions = warp::get()
+        .and(warp::path!("cooperatives" / String / "contributions"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_contributions);
+
+    let apply_for_grant = warp::post()
+        .and(warp::path!("cooperatives" / String / "grants" / String / "applications"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_apply_for_grant);
+
+    let vote_for_grant = warp::post()
+        .and(warp::path!("cooperatives" / String / "grants" / String / "applications" / String / "votes"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_vote_for_grant);
+
+    let finalize_grant_round = warp::post()
+        .and(warp::path!("cooperatives" / String / "grants" / String / "finalize"))
+        .and(api_layer.clone())
+        .and_then(handle_finalize_grant_round);
+
+    let settle_grant_milestone = warp::post()
+        .and(warp::path!("cooperatives" / String / "grants" / String / "applications" / String / "milestones"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_settle_grant_milestone);
+
+    let post_listing = warp::post()
+        .and(warp::path!("marketplace" / "listings"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_post_listing);
+
+    let list_listings = warp::get()
+        .and(warp::path!("marketplace" / "listings"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_list_listings);
+
+    let get_listing = warp::get()
+        .and(warp::path!("marketplace" / "listings" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_listing);
+
+    let withdraw_listing = warp::post()
+        .and(warp::path!("marketplace" / "listings" / String / "withdraw"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_withdraw_listing);
+
+    let place_order = warp::post()
+        .and(warp::path!("marketplace" / "listings" / String / "orders"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_place_order);
+
+    let get_order = warp::get()
+        .and(warp::path!("marketplace" / "orders" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_order);
+
+    let list_member_orders = warp::get()
+        .and(warp::path!("marketplace" / "members" / String / "orders"))
+        .and(api_layer.clone())
+        .and_then(handle_list_member_orders);
+
+    let confirm_order_fulfillment = warp::post()
+        .and(warp::path!("marketplace" / "orders" / String / "fulfill"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_confirm_order_fulfillment);
+
+    let cancel_order = warp::post()
+        .and(warp::path!("marketplace" / "orders" / String / "cancel"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_cancel_order);
+
+    let dispute_order = warp::post()
+        .and(warp::path!("marketplace" / "orders" / String / "dispute"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_dispute_order);
+
+    let get_grant_round = warp::get()
+        .and(warp::path!("cooperatives" / String / "grants" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_grant_round);
+
+    let distribute_profits = warp::post()
+        .and(warp::path!("cooperatives" / String / "profits"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_distribute_profits);
+
+    let get_profit_distributions = warp::get()
+        .and(warp::path!("cooperatives" / String / "profits"))
+        .and(api_layer.clone())
+        .and_then(handle_get_profit_distributions);
+
+    let get_tenant = warp::get()
+        .and(warp::path!("tenant"))
+        .and(warp::header::<String>("x-api-key"))
+        .and(api_layer.clone())
+        .and_then(handle_get_tenant);
+
+    let create_tenant_identity = warp::post()
+        .and(warp::path!("tenant" / "identities"))
+        .and(warp::header::<String>("x-api-key"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_create_tenant_identity);
+
+    let register_tenant_cooperative = warp::post()
+        .and(warp::path!("tenant" / "cooperatives"))
+        .and(warp::header::<String>("x-api-key"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_register_tenant_cooperative);
+
+    let list_tenant_cooperatives = warp::get()
+        .and(warp::path!("tenant" / "cooperatives"))
+        .and(warp::header::<String>("x-api-key"))
+        .and(api_layer.clone())
+        .and_then(handle_list_tenant_cooperatives);
+
+    let add_tenant_currency = warp::post()
+        .and(warp::path!("tenant" / "currencies"))
+        .and(warp::header::<String>("x-api-key"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_add_tenant_currency);
+
+    let mint_tenant_currency = warp::post()
+        .and(warp::path!("tenant" / "currencies" / String / "mint"))
+        .and(warp::header::<String>("x-api-key"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_mint_tenant_currency);
+
+    let store_tenant_data = warp::post()
+        .and(warp::path!("tenant" / "storage" / String))
+        .and(warp::header::<String>("x-api-key"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_store_tenant_data);
+
+    let retrieve_tenant_data = warp::get()
+        .and(warp::path!("tenant" / "storage" / String))
+        .and(warp::header::<String>("x-api-key"))
+        .and(api_layer.clone())
+        .and_then(handle_retrieve_tenant_data);
+
+    let raise_fraud_challenge = warp::post()
+        .and(warp::path!("challenges"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_raise_fraud_challenge);
+
+    let get_fraud_challenges = warp::get()
+        .and(warp::path!("challenges"))
+        .and(api_layer.clone())
+        .and_then(handle_get_fraud_challenges);
+
+    let submit_double_sign_evidence = warp::post()
+        .and(warp::path!("consensus" / "evidence"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_submit_double_sign_evidence);
+
+    let get_slashings = warp::get()
+        .and(warp::path!("consensus" / "slashings"))
+        .and(api_layer.clone())
+        .and_then(handle_get_slashings);
+
+    let get_trace = warp::get()
+        .and(warp::path!("trace" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_trace);
+
+    let get_pause_status = warp::get()
+        .and(warp::path("pause"))
+        .and(warp::path::end())
+        .and(api_layer.clone())
+        .and_then(handle_get_pause_status);
+
+    let guardian_pause = warp::post()
+        .and(warp::path("pause"))
+        .and(warp::path("guardian"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_guardian_pause);
+
+    let estimate_fee = warp::get()
+        .and(warp::path!("fees" / "estimate"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_estimate_fee);
+
+    let get_mempool = warp::get()
+        .and(warp::path!("mempool"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_mempool);
+
+    let stream_contract_events = warp::path!("events" / "ws")
+        .and(warp::ws())
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_stream_contract_events);
+
+    let stream_mempool = warp::path!("mempool" / "ws")
+        .and(warp::ws())
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_stream_mempool);
+
+    let store_memo = warp::post()
+        .and(warp::path!("memo"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_store_memo);
+
+    let search_transactions = warp::get()
+        .and(warp::path!("transactions" / "search"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_search_transactions);
+
+    let search_indexed_transactions = warp::get()
+        .and(warp::path!("search" / "transactions"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_search_indexed_transactions);
+
+    let get_block_headers = warp::get()
+        .and(warp::path!("headers"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_block_headers);
+
+    let subscribe_headers = warp::get()
+        .and(warp::path!("headers" / "subscribe"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_subscribe_headers);
+
+    let export_ledger = warp::post()
+        .and(warp::path!("ledger" / "export"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_export_ledger);
+
+    let get_blocks = warp::get()
+        .and(warp::path!("blocks"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_blocks);
+
+    let export_blocks = warp::get()
+        .and(warp::path!("blocks" / "export"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_export_blocks);
+
+    let get_transaction_proof = warp::get()
+        .and(warp::path!("proof" / String))
+        .and(api_layer.clone())
+        .and_then(handle_get_transaction_proof);
+
+    let get_exchange_rates = warp::get()
+        .and(warp::path!("rates"))
+        .and(api_layer.clone())
+        .and_then(handle_get_exchange_rates);
+
+    let get_rate_history = warp::get()
+        .and(warp::path!("rates" / "history"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_rate_history);
+
+    let convert_currency = warp::get()
+        .and(warp::path!("rates" / "convert"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_convert_currency);
+
+    let get_rate_oracles = warp::get()
+        .and(warp::path!("rates" / "oracles"))
+        .and(api_layer.clone())
+        .and_then(handle_get_rate_oracles);
+
+    let submit_oracle_quote = warp::post()
+        .and(warp::path!("rates" / "oracle"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_submit_oracle_quote);
+
+    let get_parameters = warp::get()
+        .and(warp::path!("parameters"))
+        .and(api_layer.clone())
+        .and_then(handle_get_parameters);
+
+    let get_parameter_history = warp::get()
+        .and(warp::path!("parameters" / "history"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_parameter_history);
+
+    let get_alerts = warp::get()
+        .and(warp::path!("alerts"))
+        .and(warp::query())
+        .and(api_layer.clone())
+        .and_then(handle_get_alerts);
+
+    let stream_alerts = warp::get()
+        .and(warp::path!("alerts" / "stream"))
+        .and(api_layer.clone())
+        .and_then(handle_stream_alerts);
+
+    let routes = submit_transaction
+        .or(prepare_transaction)
+        .or(submit_transaction_signature)
+        .or(get_receipt)
+        .or(get_transaction_availability)
+        .or(get_shard_history)
+        .or(create_proposal)
+        .or(prepare_proposal)
+        .or(create_proposal_bundle)
+        .or(prepare_proposal_bundle)
+        .or(list_proposal_templates)
+        .or(create_template_proposal)
+        .or(prepare_template_proposal)
+        .or(vote_on_proposal)
+        .or(prepare_vote)
+        .or(delegated_transfer)
+        .or(revoke_capability)
+        .or(list_revoked_capabilities)
+        .or(get_balance)
+        .or(transfer_vested)
+        .or(estimate_fee)
+        .or(get_mempool)
+        .or(stream_mempool)
+        .or(stream_contract_events)
+        .or(store_memo)
+        .or(search_transactions)
+        .or(search_indexed_transactions)
+        .or(get_block_headers)
+        .or(subscribe_headers)
+        .or(get_blocks)
+        .or(export_ledger)
+        .or(export_blocks)
+        .or(get_transaction_proof)
+        .or(get_exchange_rates)
+        .or(get_rate_history)
+        .or(convert_currency)
+        .or(get_rate_oracles)
+        .or(submit_oracle_quote)
+        .or(get_parameters)
+        .or(get_parameter_history)
+        .or(mint_currency)
+        .or(create_identity)
+        .or(import_identities)
+        .or(export_identities)
+        .or(register_identity_key)
+        .or(get_identity_keys)
+        .or(get_request_nonce)
+        .or(add_attestation)
+        .or(get_attestations)
+        .or(get_reputation_history)
+        .or(revoke_attestation)
+        .or(seal_identity_attribute)
+        .or(present_identity)
+        .or(get_disclosures)
+        .or(get_activity_feed)
+        .or(get_notification_preferences)
+        .or(set_notification_preferences)
+        .or(allocate_resource)
+        .or(get_network_stats)
+        .or(get_peer_latencies)
+        .or(get_network_time)
+        .or(get_propagation_stats)
+        .or(get_banned_peers)
+        .or(ban_peer)
+        .or(unban_peer)
+        .or(get_vote_receipt)
+        .or(cast_ballot)
+        .or(prepare_ballot)
+        .or(get_option_tally)
+        .or(get_voter_snapshot)
+        .or(list_proposal_votes)
+        .or(simulate_proposal)
+        .or(get_proposal_timelock)
+        .or(get_proposal_status)
+        .or(get_block_info)
+        .or(get_network_difficulty)
+        .or(submit_smart_contract)
+        .or(execute_smart_contract)
+        .or(run_script)
+        .or(validate_contract)
+        .or(get_contract_abi)
+        .or(get_contract_permissions)
+        .or(set_contract_permissions)
+        .or(exchange_currency)
+        .or(list_liquidity_pools)
+        .or(add_liquidity)
+        .or(remove_liquidity)
+        .or(issue_bond)
+        .or(get_bond)
+        .or(pay_bond_coupons)
+        .or(redeem_bond)
+        .or(issue_asset_token)
+        .or(get_asset_token)
+        .or(mint_asset_tokens)
+        .or(redeem_asset_tokens)
+        .or(get_storage_usage)
+        .or(store_data)
+        .or(get_storage_gc_stats)
+        .or(audit_supply)
+        .or(resume_minting)
+        .or(publish_reserve_proofs)
+        .or(get_reserve_proofs)
+        .or(verify_reserve_proof)
+        .or(get_reserve_inclusion)
+        .or(list_archived_proposals)
+        .or(restore_archived_proposal)
+        .or(purge_archived_proposal)
+        .or(list_archived_dao_proposals)
+        .or(restore_archived_dao_proposal)
+        .or(purge_archived_dao_proposal)
+        .or(get_validators)
+        .or(get_committee)
+        .or(audit_committee)
+        .or(audit_dispute_jury)
+        .or(register_validator_bls_key)
+        .or(certify_block)
+        .or(list_account_freezes)
+        .or(create_dao_proposal)
+        .or(post_dao_announcement)
+        .or(vote_on_dao_proposal)
+        .or(finalize_dao_proposal)
+        .or(get_dao_proposal)
+        .or(record_contribution)
+        .or(get_contributions)
+        .or(apply_for_grant)
+        .or(vote_for_grant)
+        .or(finalize_grant_round)
+        .or(settle_grant_milestone)
+        .or(post_listing)
+        .or(list_listings)
+        .or(get_listing)
+        .or(withdraw_listing)
+        .or(place_order)
+        .or(get_order)
+        .or(list_member_orders)
+        .or(confirm_order_fulfillment)
+        .or(cancel_order)
+        .or(dispute_order)
+        .or(get_grant_round)
+        .or(distribute_profits)
+        .or(get_profit_distributions)
+        .or(get_tenant)
+        .or(create_tenant_identity)
+        .or(register_tenant_cooperative)
+        .or(list_tenant_cooperatives)
+        .or(add_tenant_currency)
+        .or(mint_tenant_currency)
+        .or(store_tenant_data)
+        .or(retrieve_tenant_data)
+        .or(list_bridge_pegs)
+        .or(attest_bridge_deposit)
+        .or(get_bridge_deposit)
+        .or(request_bridge_withdrawal)
+        .or(challenge_bridge_withdrawal)
+        .or(release_bridge_withdrawals)
+        .or(get_bridge_withdrawal)
+        .or(register_confidential_account)
+        .or(shield_funds)
+        .or(submit_confidential_transfer)
+        .or(unshield_funds)
+        .or(get_confidential_balance)
+        .or(get_confidential_transfers)
+        .or(get_account_freeze)
+        .or(raise_fraud_challenge)
+        .or(get_fraud_challenges)
+        .or(submit_double_sign_evidence)
+        .or(get_slashings)
+        .or(get_validator)
+        .or(get_pause_status)
+        .or(guardian_pause)
+        .or(get_maintenance_status)
+        .or(set_maintenance_mode)
+        .or(get_runtime_settings)
+        .or(update_runtime_settings)
+        .or(get_config_audit)
+        .or(get_trace)
+        .or(get_alerts)
+        .or(stream_alerts);
+
+    #[cfg(feature = "fault_injection")]
+    let routes = routes.or(faults);
+
+    routes.recover(handle_rejection)
+}
+
+/// Routes for arming faults on a test or staging node.
+#[cfg(feature = "fault_injection")]
+fn fault_routes(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
+    let api_layer = warp::any().map(move || api_layer.clone());
+
+    let inject_fault = warp::post()
+        .and(warp::path!("faults"))
+        .and(warp::body::json())
+        .and(api_layer.clone())
+        .and_then(handle_inject_fault);
+
+    let clear_faults = warp::delete()
+        .and(warp::path!("faults"))
+        .and(api_layer.clone())
+        .and_then(handle_clear_faults);
+
+    let get_fault_stats = warp::get()
+        .and(warp::path!("faults"))
+        .and(api_layer)
+        .and_then(handle_get_fault_stats);
+
+    inject_fault.or(clear_faults).or(get_fault_stats)
+}
+
+#[cfg(feature = "fault_injection")]
+async fn handle_inject_fault(
+    fault: Fault,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .inject_fault(fault)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "armed"})))
+        .map_err(icn_error_to_rejection)
+}
+
+#[cfg(feature = "fault_injection")]
+async fn handle_clear_faults(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.clear_faults().await;
+    Ok(warp::reply::json(&json!({"status": "cleared"})))
+}
+
+#[cfg(feature = "fault_injection")]
+async fn handle_get_fault_stats(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    Ok(warp::reply::json(&api_layer.fault_stats().await))
+}
+
+// Handler functions
+async fn handle_submit_transaction(
+    transaction: Transaction,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .submit_transaction(transaction)
+        .await
+        .map(|receipt| warp::reply::json(&json!({"status": "success", "receipt": receipt})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_prepare_transaction(
+    request: PrepareTransactionRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .prepare_transaction(&request.from, &request.to, request.amount, request.currency_type, request.memo)
+        .await
+        .map(|prepared| warp::reply::json(&prepared))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_submit_transaction_signature(
+    hash: String,
+    request: TransactionSignatureRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = hex::decode(&request.signature)
+        .map_err(|e| icn_error_to_rejection(IcnError::Validation(format!("Invalid signature encoding: {}", e))))?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .submit_transaction_signature(&hash, signature)
+        .await
+        .map(|receipt| warp::reply::json(&json!({"status": "success", "receipt": receipt})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_receipt(
+    tx_hash: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_receipt(&tx_hash)
+        .await
+        .map(|receipt| warp::reply::json(&receipt))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_transaction_availability(
+    tx_hash: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_transaction_availability(&tx_hash)
+        .await
+        .map(|availability| warp::reply::json(&json!({"hash": tx_hash, "availability": availability})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_shard_history(
+    shard_id: u64,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_shard_history(shard_id)
+        .await
+        .map(|history| warp::reply::json(&history))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_create_proposal(
+    proposal_request: CreateProposalRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&proposal_request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .create_proposal(proposal_from_request(proposal_request), &signature)
+        .await
+        .map(|proposal_id| warp::reply::json(&CreateProposalResponse { proposal_id }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_prepare_proposal(
+    proposal_request: CreateProposalRequest,
+) -> Result<impl Reply, Rejection> {
+    Ok(warp::reply::json(&prepared_proposal(proposal_from_request(proposal_request))))
+}
+
+async fn handle_create_proposal_bundle(
+    request: CreateBundleRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let bundle_id = request.bundle_id;
+    let proposals = request.proposals.into_iter()
+        .map(|proposal_request| {
+            let signature = decode_signature(&proposal_request.signature)?;
+            Ok((proposal_from_request(proposal_request), signature))
+        })
+        .collect::<Result<Vec<_>, Rejection>>()?;
+    api_layer
+        .create_proposal_bundle(&bundle_id, proposals)
+        .await
+        .map(|proposal_ids| warp::reply::json(&CreateBundleResponse { bundle_id: bundle_id.clone(), proposal_ids }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_prepare_proposal_bundle(
+    request: CreateBundleRequest,
+) -> Result<impl Reply, Rejection> {
+    let proposals: Vec<Proposal> = request.proposals.into_iter().map(proposal_from_request).collect();
+    let bundle = icn_common::ProposalBundle {
+        id: request.bundle_id,
+        members: proposals.iter().map(|proposal| proposal.id.clone()).collect(),
+    };
+    let prepared: Vec<PreparedProposal> = proposals.into_iter()
+        .map(|proposal| prepared_proposal(Proposal { bundle: Some(bundle.clone()), ..proposal }))
+        .collect();
+    Ok(warp::reply::json(&prepared))
+}
+
+fn prepared_proposal(proposal: Proposal) -> PreparedProposal {
+    let signing_bytes = hex::encode(proposal.signing_bytes());
+    PreparedProposal { proposal, signing_bytes }
+}
+
+fn proposal_from_request(proposal_request: CreateProposalRequest) -> Proposal {
+    Proposal {
+        id: proposal_request.id,
+        title: proposal_request.title,
+        description: proposal_request.description,
+        proposer: proposal_request.proposer,
+        created_at: proposal_request.created_at,
+        // Voting period and quorum are replaced by the governance policy for this proposal type
+        voting_ends_at: Utc::now() + Duration::days(7),
+        status: ProposalStatus::Active,
+        proposal_type: proposal_request.proposal_type,
+        category: proposal_request.category,
+        required_quorum: 0.51,
+        execution_timestamp: None,
+        weight_mode: proposal_request.weight_mode,
+        actions: proposal_request.actions,
+        scope: proposal_request.scope,
+        ballot: proposal_request.ballot,
+        depends_on: proposal_request.depends_on,
+        bundle: None,
+    }
+}
+
+async fn handle_list_proposal_templates(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    Ok(warp::reply::json(&api_layer.list_proposal_templates().await))
+}
+
+async fn handle_create_template_proposal(
+    request: CreateTemplateProposalRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let created_at = request.created_at;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .create_proposal_from_template(template_proposal_from_request(request), created_at, &signature)
+        .await
+        .map(|proposal_id| warp::reply::json(&CreateProposalResponse { proposal_id }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_prepare_template_proposal(
+    request: CreateTemplateProposalRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let created_at = request.created_at;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .prepare_template_proposal(&template_proposal_from_request(request), created_at)
+        .await
+        .map(|proposal| warp::reply::json(&prepared_proposal(proposal)))
+        .map_err(icn_error_to_rejection)
+}
+
+fn template_proposal_from_request(request: CreateTemplateProposalRequest) -> icn_governance::TemplateProposal {
+    icn_governance::TemplateProposal {
+        template_id: request.template_id,
+        proposal_id: request.proposal_id,
+        proposer: request.proposer,
+        title: request.title,
+        description: request.description,
+        arguments: request.arguments,
+    }
+}
+
+async fn handle_vote_on_proposal(
+    vote: VoteOnProposalRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&vote.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .vote_on_proposal(&vote.proposal_id, vote.voter, vote.in_favor, vote.timestamp, &signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_prepare_vote(
+    request: PrepareVoteRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .vote_signing_bytes(&request.proposal_id, &request.voter, request.in_favor, request.timestamp)
+        .await
+        .map(|signing_bytes| warp::reply::json(&json!({"signing_bytes": hex::encode(signing_bytes)})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_delegated_transfer(
+    authorization: String,
+    request: DelegatedTransferRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let token = parse_capability_header(&authorization).map_err(icn_error_to_rejection)?;
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .delegated_transfer(&token, &request.to, request.amount, request.currency_type, request.timestamp, signature)
+        .await
+        .map(|receipt| warp::reply::json(&json!({"status": "success", "receipt": receipt})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_revoke_capability(
+    request: RevokeCapabilityRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = hex::decode(&request.signature)
+        .map_err(|e| icn_error_to_rejection(IcnError::Validation(format!("Invalid signature encoding: {}", e))))?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .revoke_capability(&request.issuer, &request.token_id, &signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_revoked_capabilities(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    Ok(warp::reply::json(&api_layer.list_revoked_capabilities().await))
+}
+
+async fn handle_get_balance(
+    query: GetBalanceQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_balance(&query.address, &query.currency_type)
+        .await
+        .map(|balance| warp::reply::json(&json!({"balance": balance.total, "locked": balance.locked, "available": balance.available})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_mint_currency(
+    request: MintCurrencyRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .mint_currency(&request.address, &request.currency_type, request.amount)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_create_identity(
+    attributes: HashMap<String, String>,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .create_identity(attributes)
+        .await
+        .map(|id| warp::reply::json(&json!({"identity_id": id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_allocate_resource(
+    request: AllocateResourceRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .allocate_resource(&request.resource_type, request.amount)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_import_identities(
+    request: ImportIdentitiesRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .import_identities(&request.data, request.format, request.dry_run)
+        .await
+        .map(|report| warp::reply::json(&report))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_export_identities(
+    query: ExportIdentitiesQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<warp::reply::Response, Rejection> {
+    let identities = api_layer.read().await.export_identities().await;
+    match query.format.unwrap_or(RosterFormat::Json) {
+        RosterFormat::Json => Ok(warp::reply::json(&identities).into_response()),
+        RosterFormat::Csv => icn_identity::roster_to_csv(&identities)
+            .map(|csv| warp::reply::with_header(csv, "content-type", "text/csv").into_response())
+            .map_err(icn_error_to_rejection),
+    }
+}
+
+async fn handle_register_identity_key(
+    id: String,
+    request: RegisterKeyRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .register_identity_key(&id, request.key, &request.authorization, &request.proof_of_possession)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_identity_keys(
+    id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_identity_keys(&id)
+        .await
+        .map(|keys| warp::reply::json(&keys))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_request_nonce(
+    id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_request_nonce(&id)
+        .await
+        .map(|nonce| warp::reply::json(&json!({"nonce": nonce})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_add_attestation(
+    subject: String,
+    request: AttestationRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .add_attestation(&request.issuer, &subject, &request.claim, request.issued_at, &request.signature)
+        .await
+        .map(|attestation| warp::reply::json(&attestation))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_attestations(
+    subject: String,
+    query: AttestationsQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_attestations(&subject, query.include_revoked)
+        .await
+        .map(|attestations| warp::reply::json(&attestations))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_seal_identity_attribute(
+    owner: String,
+    request: SealAttributeRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .seal_identity_attribute(&owner, &request.name, &request.value, request.policy, &request.signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_present_identity(
+    subject: String,
+    request: PresentationRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .present_identity(&subject, &request.verifier, request.requested_at, &request.signature)
+        .await
+        .map(|presentation| warp::reply::json(&presentation))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_disclosures(
+    owner: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_disclosures(&owner)
+        .await
+        .map(|disclosures| warp::reply::json(&disclosures))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_reputation_history(
+    id: String,
+    query: ReputationHistoryQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let history = api_layer.get_reputation_history(&id).await.map_err(icn_error_to_rejection)?;
+    let trailing_average = match query.window_secs {
+        Some(window_secs) => Some(api_layer.get_trailing_reputation(&id, window_secs).await.map_err(icn_error_to_rejection)?),
+        None => None,
+    };
+    Ok(warp::reply::json(&json!({"history": history, "trailing_average": trailing_average})))
+}
+
+async fn handle_revoke_attestation(
+    subject: String,
+    attestation_id: String,
+    request: RevokeAttestationRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .revoke_attestation(&subject, &attestation_id, &request.signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_activity_feed(
+    id: String,
+    query: ActivityFeedQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let feed = api_layer.read().await.get_activity_feed(&id, query.limit).await;
+    Ok(warp::reply::json(&feed))
+}
+
+async fn handle_get_notification_preferences(
+    id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_notification_preferences(&id)
+        .await
+        .map(|preferences| warp::reply::json(&preferences))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_set_notification_preferences(
+    id: String,
+    preferences: NotificationPreferences,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .set_notification_preferences(&id, preferences)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_network_stats(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_network_stats()
+        .await
+        .map(|stats| warp::reply::json(&stats))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_banned_peers(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let bans: Vec<_> = api_layer.get_banned_peers().await.into_iter()
+        .map(|(peer, remaining)| json!({"peer": peer, "remaining_secs": remaining.as_secs()}))
+        .collect();
+    Ok(warp::reply::json(&bans))
+}
+
+async fn handle_ban_peer(
+    request: BanPeerRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .ban_peer(&request.peer, request.duration_secs)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_unban_peer(
+    peer: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .unban_peer(&peer)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_peer_latencies(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let peers: Vec<_> = api_layer.get_peer_latencies().await.into_iter()
+        .map(|(peer, latency)| json!({
+            "peer": peer,
+            "smoothed_ms": latency.smoothed_ms,
+            "last_ms": latency.last_ms,
+            "samples": latency.samples,
+        }))
+        .collect();
+    Ok(warp::reply::json(&peers))
+}
+
+async fn handle_get_network_time(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let (network_time, offset_ms, peers) = api_layer.get_network_time().await;
+    let peers: Vec<_> = peers.into_iter()
+        .map(|(peer, sample)| json!({
+            "peer": peer,
+            "offset_ms": sample.offset_ms,
+            "rtt_ms": sample.rtt_ms,
+        }))
+        .collect();
+    Ok(warp::reply::json(&json!({
+        "network_time": network_time,
+        "offset_ms": offset_ms,
+        "peers": peers,
+    })))
+}
+
+async fn handle_get_propagation_stats(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let stats: serde_json::Map<_, _> = api_layer.get_propagation_stats().await.into_iter()
+        .map(|(kind, stats)| (kind, json!({
+            "broadcasts": stats.broadcasts,
+            "deliveries": stats.deliveries,
+            "failures": stats.failures,
+            "mean_ms": stats.mean_ms(),
+            "max_ms": stats.max_ms,
+        })))
+        .collect();
+    Ok(warp::reply::json(&stats))
+}
+
+async fn handle_get_vote_receipt(
+    proposal_id: String,
+    voter: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_vote_receipt(&proposal_id, &voter)
+        .await
+        .map(|receipt| warp::reply::json(&receipt))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_cast_ballot(
+    proposal_id: String,
+    request: CastBallotRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .cast_ballot(&proposal_id, request.voter, request.choices, request.timestamp, &signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_prepare_ballot(proposal_id: String, request: PrepareBallotRequest) -> Result<impl Reply, Rejection> {
+    let ballot = SignedBallot {
+        voter: request.voter,
+        proposal_id,
+        choices: request.choices,
+        timestamp: request.timestamp,
+        signature: Vec::new(),
+    };
+    Ok(warp::reply::json(&json!({"signing_bytes": hex::encode(ballot.signing_bytes())})))
+}
+
+async fn handle_get_option_tally(
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_option_tally(&proposal_id)
+        .await
+        .map(|tally| warp::reply::json(&tally))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_proposal_votes(
+    proposal_id: String,
+    query: ProposalVotesQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let filter = icn_governance::VoteFilter {
+        in_favor: query.in_favor,
+        min_weight: query.min_weight,
+        max_weight: query.max_weight,
+        from_time: query.from_time,
+        to_time: query.to_time,
+    };
+    let api_layer = api_layer.read().await;
+    api_layer
+        .list_proposal_votes(&proposal_id, &filter, query.offset, query.limit)
+        .await
+        .map(|page| warp::reply::json(&page))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_voter_snapshot(
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_voter_snapshot(&proposal_id)
+        .await
+        .map(|snapshot| warp::reply::json(&snapshot))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_simulate_proposal(
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .simulate_proposal_outcome(&proposal_id)
+        .await
+        .map(|projection| warp::reply::json(&projection))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_proposal_timelock(
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let status = api_layer.get_proposal_status(&proposal_id).await.map_err(icn_error_to_rejection)?;
+    let timelock = api_layer.get_proposal_timelock(&proposal_id).await.map_err(icn_error_to_rejection)?;
+    let pending_vetoes = api_layer.get_pending_vetoes(&proposal_id).await.map_err(icn_error_to_rejection)?;
+    Ok(warp::reply::json(&ProposalTimelockResponse { status, timelock, pending_vetoes }))
+}
+
+async fn handle_get_proposal_status(
+    query: GetProposalStatusRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_proposal_status(&query.proposal_id)
+        .await
+        .map(|status| warp::reply::json(&GetProposalStatusResponse { status }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_block_info(
+    query: GetBlockInfoRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_block_info(&query.identifier)
+        .await
+        .map(|block| warp::reply::json(&GetBlockInfoResponse { block }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_network_difficulty(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_network_difficulty()
+        .await
+        .map(|difficulty| warp::reply::json(&GetNetworkDifficultyResponse { difficulty }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_submit_smart_contract(
+    request: SubmitSmartContractRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .submit_smart_contract(&request.deployer, request.code, request.init_args)
+        .await
+        .map(|contract_id| warp::reply::json(&SubmitSmartContractResponse { contract_id }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_execute_smart_contract(
+    request: ExecuteSmartContractRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .execute_smart_contract(&request.caller, &request.contract_id, &request.function, request.args, request.nonce, &signature)
+        .await
+        .map(|result| warp::reply::json(&ExecuteSmartContractResponse { result }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_run_script(
+    request: RunScriptRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .run_script(&request.caller, &request.source)
+        .await
+        .map(|calls| warp::reply::json(&calls))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_exchange_currency(
+    request: ExchangeCurrencyRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .exchange_currency(&request.from, &request.source_currency, &request.target_currency, request.amount, request.min_amount_out)
+        .await
+        .map(|amount_received| warp::reply::json(&ExchangeCurrencyResponse { amount_received }))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_liquidity_pools(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .list_liquidity_pools()
+        .await
+        .map(|pools| warp::reply::json(&pools))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_add_liquidity(
+    request: AddLiquidityRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .add_liquidity(&request.provider, &request.pool_id, request.amount_a, request.amount_b)
+        .await
+        .map(|shares| warp::reply::json(&json!({"shares": shares})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_remove_liquidity(
+    request: RemoveLiquidityRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .remove_liquidity(&request.provider, &request.pool_id, request.shares)
+        .await
+        .map(|(amount_a, amount_b)| warp::reply::json(&json!({"amount_a": amount_a, "amount_b": amount_b})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_issue_bond(
+    request: IssueBondRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .issue_bond(&request.issuer, request.terms, request.units)
+        .await
+        .map(|bond_id| warp::reply::json(&json!({"bond_id": bond_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_bond(
+    bond_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_bond(&bond_id)
+        .await
+        .map(|bond| warp::reply::json(&bond))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_validate_contract(
+    request: ValidateContractRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .validate_contract(&request.code)
+        .await
+        .map(|report| warp::reply::json(&report))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_contract_abi(
+    contract_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_contract_abi(&contract_id)
+        .await
+        .map(|abi| warp::reply::json(&abi))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_contract_permissions(
+    contract_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_contract_permissions(&contract_id)
+        .await
+        .map(|policy| warp::reply::json(&policy))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_set_contract_permissions(
+    contract_id: String,
+    request: SetContractPermissionsRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .set_contract_permissions(&request.caller, &contract_id, &request.function, request.roles, request.nonce, &signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_transfer_vested(
+    request: VestedTransferRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .transfer_vested(request)
+        .await
+        .map(|schedule| warp::reply::json(&json!({"status": "success", "schedule": schedule})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_archived_proposals(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let archived = api_layer.list_archived_proposals().await;
+    Ok(warp::reply::json(&archived))
+}
+
+async fn handle_restore_archived_proposal(
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .restore_archived_proposal(&proposal_id)
+        .await
+        .map(|proposal| warp::reply::json(&json!({"status": "success", "proposal": proposal})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_purge_archived_proposal(
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .purge_archived_proposal(&proposal_id)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success", "proposal_id": proposal_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_archived_dao_proposals(
+    dao_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .list_archived_dao_proposals(&dao_id)
+        .await
+        .map(|archived| warp::reply::json(&archived))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_restore_archived_dao_proposal(
+    dao_id: String,
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .restore_archived_dao_proposal(&dao_id, &proposal_id)
+        .await
+        .map(|proposal| warp::reply::json(&json!({"status": "success", "proposal": proposal})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_purge_archived_dao_proposal(
+    dao_id: String,
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .purge_archived_dao_proposal(&dao_id, &proposal_id)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success", "proposal_id": proposal_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_validators(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let validators = api_layer.get_validators().await;
+    Ok(warp::reply::json(&validators))
+}
+
+async fn handle_get_validator(
+    id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_validator(&id)
+        .await
+        .map(|validator| warp::reply::json(&validator))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_account_freezes(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let freezes = api_layer.read().await.list_account_freezes().await;
+    Ok(warp::reply::json(&freezes))
+}
+
+async fn handle_get_account_freeze(
+    address: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let freeze = api_layer.read().await.get_account_freeze(&address).await;
+    Ok(warp::reply::json(&json!({"address": address, "frozen": freeze.is_some(), "freeze": freeze})))
+}
+
+async fn handle_register_validator_bls_key(
+    id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .register_validator_bls_key(&id)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_certify_block(
+    block_index: u64,
+    request: CertifyBlockRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let approvals = request.approvals.into_iter().map(|approval| (approval.validator, approval.signature)).collect();
+    let api_layer = api_layer.read().await;
+    api_layer
+        .certify_block(block_index, approvals)
+        .await
+        .map(|certificate| warp::reply::json(&certificate))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_committee(
+    block_index: u64,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_committee(block_index)
+        .await
+        .map(|committee| warp::reply::json(&committee))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_audit_committee(
+    block_index: u64,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .audit_committee(block_index)
+        .await
+        .map(|audit| warp::reply::json(&audit))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_audit_dispute_jury(
+    dispute_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .audit_dispute_jury(&dispute_id)
+        .await
+        .map(|audit| warp::reply::json(&audit))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_register_confidential_account(
+    request: ConfidentialAccountRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let viewing_key = hex::decode(&request.viewing_key)
+        .map_err(|e| icn_error_to_rejection(IcnError::Zkp(format!("Invalid viewing key: {}", e))))?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .register_confidential_account(&request.address, viewing_key)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_shield_funds(
+    transaction: Transaction,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .shield_funds(transaction)
+        .await
+        .map(|receipt| warp::reply::json(&json!({"status": "success", "receipt": receipt})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_submit_confidential_transfer(
+    transfer: ConfidentialTransfer,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .submit_confidential_transfer(transfer)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_unshield_funds(
+    withdrawal: ConfidentialWithdrawal,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .unshield_funds(withdrawal)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_confidential_balance(
+    query: ConfidentialBalanceQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let balance = api_layer.read().await.get_confidential_balance(&query.address, &query.currency_type).await;
+    Ok(warp::reply::json(&json!({
+        "address": query.address,
+        "commitment": hex::encode(balance.commitment),
+        "handle": hex::encode(balance.handle),
+    })))
+}
+
+async fn handle_get_confidential_transfers(
+    address: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let transfers = api_layer.read().await.get_confidential_transfers(&address).await;
+    Ok(warp::reply::json(&transfers))
+}
+
+async fn handle_search_indexed_transactions(
+    query: TransactionSearchQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let filter = TransactionFilter {
+        address: query.address,
+        currency_type: query.currency_type,
+        from_time: query.from_time,
+        to_time: query.to_time,
+        min_amount: query.min_amount,
+        max_amount: query.max_amount,
+    };
+    let api_layer = api_layer.read().await;
+    api_layer
+        .search_transactions(&filter, query.offset, query.limit)
+        .await
+        .map(|page| warp::reply::json(&page))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_bridge_pegs(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    Ok(warp::reply::json(&api_layer.list_bridge_pegs().await))
+}
+
+async fn handle_attest_bridge_deposit(
+    request: BridgeDepositRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .attest_bridge_deposit(request.deposit, request.attestation)
+        .await
+        .map(|record| warp::reply::json(&record))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_bridge_deposit(
+    chain: String,
+    tx_hash: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_bridge_deposit(&chain, &tx_hash)
+        .await
+        .map(|record| warp::reply::json(&record))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_request_bridge_withdrawal(
+    request: BridgeWithdrawalRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .request_bridge_withdrawal(&request.holder, &request.asset_id, request.amount, request.external_recipient, request.nonce, &signature)
+        .await
+        .map(|withdrawal| warp::reply::json(&withdrawal))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_challenge_bridge_withdrawal(
+    withdrawal_id: String,
+    request: BridgeChallengeRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .challenge_bridge_withdrawal(&withdrawal_id, &request.relayer, request.reason, &request.signature)
+        .await
+        .map(|withdrawal| warp::reply::json(&withdrawal))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_release_bridge_withdrawals(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .release_bridge_withdrawals()
+        .await
+        .map(|released| warp::reply::json(&released))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_bridge_withdrawal(
+    withdrawal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_bridge_withdrawal(&withdrawal_id)
+        .await
+        .map(|withdrawal| warp::reply::json(&withdrawal))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_post_dao_announcement(
+    dao_id: String,
+    request: DaoAnnouncementRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .post_dao_announcement(&dao_id, &request.author, &request.message)
+        .await
+        .map(|notified| warp::reply::json(&json!({"status": "success", "notified": notified})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_create_dao_proposal(
+    dao_id: String,
+    request: DaoProposalRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .create_dao_proposal(&dao_id, request.title, request.description, request.proposer, Duration::seconds(request.duration_secs), request.contract_call)
+        .await
+        .map(|proposal_id| warp::reply::json(&json!({"proposal_id": proposal_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_vote_on_dao_proposal(
+    dao_id: String,
+    proposal_id: String,
+    request: DaoVoteRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .vote_on_dao_proposal(&dao_id, &proposal_id, &request.member, request.in_favor)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_finalize_dao_proposal(
+    dao_id: String,
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .finalize_dao_proposal(&dao_id, &proposal_id)
+        .await
+        .map(|status| warp::reply::json(&json!({"status": status})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_dao_proposal(
+    dao_id: String,
+    proposal_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .get_dao_proposal(&dao_id, &proposal_id)
+        .await
+        .map(|proposal| warp::reply::json(&proposal))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_record_contribution(
+    dao_id: String,
+    record: ContributionRecord,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .record_contribution(&dao_id, record)
+        .await
+        .map(|mint| warp::reply::json(&mint))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_contributions(
+    dao_id: String,
+    query: ContributionsQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .get_contributions(&dao_id, query.contributor.as_deref())
+        .await
+        .map(|contributions| warp::reply::json(&contributions))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_apply_for_grant(
+    dao_id: String,
+    round_id: String,
+    request: GrantApplicationRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let milestones = request.milestones.iter()
+        .map(|milestone| Milestone::new(&milestone.description, milestone.amount, milestone.due))
+        .collect();
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .apply_for_grant(&dao_id, &round_id, &request.applicant, &request.title, &request.description, milestones)
+        .await
+        .map(|application_id| warp::reply::json(&json!({"application_id": application_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_vote_for_grant(
+    dao_id: String,
+    round_id: String,
+    application_id: String,
+    request: GrantVoteRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .vote_for_grant(&dao_id, &round_id, &application_id, &request.member, request.amount, request.nonce, &signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_finalize_grant_round(
+    dao_id: String,
+    round_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .finalize_grant_round(&dao_id, &round_id)
+        .await
+        .map(|awards| warp::reply::json(&json!({"awards": awards})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_settle_grant_milestone(
+    dao_id: String,
+    round_id: String,
+    application_id: String,
+    request: MilestoneSettlementRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .settle_grant_milestone(&dao_id, &round_id, &application_id, &request.steward, request.delivered, request.nonce, &signature)
+        .await
+        .map(|amount| warp::reply::json(&json!({"amount": amount})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_post_listing(
+    request: PostListingRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .post_listing(&request.seller, request.kind, &request.title, &request.description, request.unit_price, request.currency_type, request.quantity)
+        .await
+        .map(|listing_id| warp::reply::json(&json!({"listing_id": listing_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_listings(
+    query: ListingsQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    Ok(warp::reply::json(&api_layer.list_listings(query.kind).await))
+}
+
+async fn handle_get_listing(
+    listing_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_listing(&listing_id)
+        .await
+        .map(|listing| warp::reply::json(&listing))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_withdraw_listing(
+    listing_id: String,
+    request: SellerRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .withdraw_listing(&listing_id, &request.seller, request.nonce, &signature)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_place_order(
+    listing_id: String,
+    request: PlaceOrderRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .place_order(&listing_id, &request.buyer, request.quantity, request.nonce, &signature)
+        .await
+        .map(|order_id| warp::reply::json(&json!({"order_id": order_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_order(
+    order_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_order(&order_id)
+        .await
+        .map(|order| warp::reply::json(&order))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_member_orders(
+    member: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    Ok(warp::reply::json(&api_layer.list_orders_for(&member).await))
+}
+
+async fn handle_confirm_order_fulfillment(
+    order_id: String,
+    request: BuyerRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .confirm_order_fulfillment(&order_id, &request.buyer, request.nonce, &signature)
+        .await
+        .map(|amount| warp::reply::json(&json!({"amount": amount})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_cancel_order(
+    order_id: String,
+    request: SellerRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&request.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .cancel_order(&order_id, &request.seller, request.nonce, &signature)
+        .await
+        .map(|amount| warp::reply::json(&json!({"amount": amount})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_dispute_order(
+    order_id: String,
+    request: DisputeOrderRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .dispute_order(&order_id, &request.claimant, request.description)
+        .await
+        .map(|dispute_id| warp::reply::json(&json!({"dispute_id": dispute_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_grant_round(
+    dao_id: String,
+    round_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .get_grant_round(&dao_id, &round_id)
+        .await
+        .map(|round| warp::reply::json(&round))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_distribute_profits(
+    dao_id: String,
+    request: ProfitDistributionRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .distribute_profits(&dao_id, &request.steward, request.total_profit, request.currency_type)
+        .await
+        .map(|distribution| warp::reply::json(&distribution))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_profit_distributions(
+    dao_id: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.ensure_shared_cooperative(&dao_id).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .get_profit_distributions(&dao_id)
+        .await
+        .map(|distributions| warp::reply::json(&distributions))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_tenant(
+    api_key: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .get_tenant_usage(&tenant.id)
+        .await
+        .map(|usage| warp::reply::json(&json!({"id": tenant.id, "name": tenant.name, "quotas": tenant.quotas, "usage": usage})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_create_tenant_identity(
+    api_key: String,
+    attributes: HashMap<String, String>,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .create_tenant_identity(&tenant.id, attributes)
+        .await
+        .map(|id| warp::reply::json(&json!({"identity_id": id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_register_tenant_cooperative(
+    api_key: String,
+    request: TenantCooperativeRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    let cooperative = Cooperative::new(request.name, request.business_type, request.quorum, request.majority);
+    api_layer
+        .register_tenant_cooperative(&tenant.id, cooperative)
+        .await
+        .map(|dao_id| warp::reply::json(&json!({"dao_id": dao_id})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_list_tenant_cooperatives(
+    api_key: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .list_tenant_cooperatives(&tenant.id)
+        .await
+        .map(|cooperatives| warp::reply::json(&cooperatives))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_add_tenant_currency(
+    api_key: String,
+    request: TenantCurrencyRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .add_tenant_currency(&tenant.id, &request.name, request.initial_supply, request.issuance_rate)
+        .await
+        .map(|currency_type| warp::reply::json(&json!({"currency_type": currency_type})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_mint_tenant_currency(
+    name: String,
+    api_key: String,
+    request: TenantMintRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .mint_tenant_currency(&tenant.id, &name, &request.to, request.amount)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_store_tenant_data(
+    key: String,
+    api_key: String,
+    request: TenantStoreRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let value = hex::decode(&request.data)
+        .map_err(|e| icn_error_to_rejection(IcnError::Validation(format!("Invalid data encoding: {}", e))))?;
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .store_tenant_data(&tenant.id, &key, value)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_retrieve_tenant_data(
+    key: String,
+    api_key: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let tenant = api_layer.authenticate_tenant(&api_key).await.map_err(icn_error_to_rejection)?;
+    api_layer
+        .retrieve_tenant_data(&tenant.id, &key)
+        .await
+        .map(|data| warp::reply::json(&json!({"key": key, "data": hex::encode(data)})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_raise_fraud_challenge(
+    request: FraudChallengeRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .raise_fraud_challenge(&request.challenger, request.block_index)
+        .await
+        .map(|challenge| warp::reply::json(&challenge))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_fraud_challenges(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let challenges = api_layer.read().await.get_fraud_challenges().await;
+    Ok(warp::reply::json(&challenges))
+}
+
+async fn handle_submit_double_sign_evidence(
+    request: DoubleSignEvidenceRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .submit_double_sign_evidence(&request.reporter, request.evidence)
+        .await
+        .map(|record| warp::reply::json(&record))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_slashings(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let slashings = api_layer.read().await.get_slashings().await;
+    Ok(warp::reply::json(&slashings))
+}
+
+async fn handle_get_storage_usage(
+    owner: String,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_storage_usage(&owner)
+        .await
+        .map(|(used_bytes, quota_bytes)| warp::reply::json(&json!({"owner": owner, "used_bytes": used_bytes, "quota_bytes": quota_bytes})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_store_data(
+    owner: String,
+    key: String,
+    request: StoreDataRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let value = hex::decode(&request.data)
+        .map_err(|e| icn_error_to_rejection(IcnError::Validation(format!("Invalid data encoding: {}", e))))?;
+    let ttl = match request.ttl_secs {
+        Some(secs) if secs <= 0 => {
+            return Err(icn_error_to_rejection(IcnError::Validation("ttl_secs must be positive".into())));
+        }
+        Some(secs) => Some(Duration::seconds(secs)),
+        None => None,
+    };
+    let api_layer = api_layer.read().await;
+    api_layer
+        .store_data(&owner, &key, value, ttl)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_audit_supply(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .audit_supply()
+        .await
+        .map(|alerts| warp::reply::json(&alerts))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_resume_minting(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .resume_minting()
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map_err(icn_error_to_rejection)
+}
 
-    let mint_currency = warp::post()
-        .and(warp::path("mint"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_mint_currency);
+async fn handle_publish_reserve_proofs(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let proofs = api_layer.publish_reserve_proofs().await;
+    Ok(warp::reply::json(&proofs))
+}
 
-    let create_identity = warp::post()
-        .and(warp::path("identity"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_create_identity);
+async fn handle_get_reserve_proofs(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let proofs = api_layer.get_reserve_proofs().await;
+    Ok(warp::reply::json(&proofs))
+}
 
-    let allocate_resource = warp::post()
-        .and(warp::path("allocate"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_allocate_resource);
+async fn handle_verify_reserve_proof(
+    proof: ReserveProof,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .verify_reserve_proof(&proof)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success", "valid": true})))
+        .map_err(icn_error_to_rejection)
+}
 
-    let get_network_stats = warp::get()
-        .and(warp::path("stats"))
-        .and(api_layer.clone())
-        .and_then(handle_get_network_stats);
+async fn handle_get_reserve_inclusion(
+    query: ReserveInclusionQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let signature = decode_signature(&query.signature)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_reserve_inclusion(&query.address, &query.currency_type, query.nonce, &signature)
+        .await
+        .map(|inclusion| warp::reply::json(&inclusion))
+        .map_err(icn_error_to_rejection)
+}
 
-    let get_proposal_status = warp::get()
-        .and(warp::path("proposal"))
-        .and(warp::path("status"))
-        .and(warp::query())
-        .and(api_layer.clone())
-        .and_then(handle_get_proposal_status);
+async fn handle_get_maintenance_status(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let status = api_layer.get_maintenance_status().await;
+    Ok(warp::reply::json(&status))
+}
 
-    let get_block_info = warp::get()
-        .and(warp::path("block"))
-        .and(warp::query())
-        .and(api_layer.clone())
-        .and_then(handle_get_block_info);
+async fn handle_set_maintenance_mode(
+    request: MaintenanceRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer.set_maintenance_mode(request.enabled, request.reason).await;
+    Ok(warp::reply::json(&json!({"status": "success", "maintenance": request.enabled})))
+}
 
-    let get_network_difficulty = warp::get()
-        .and(warp::path("difficulty"))
-        .and(api_layer.clone())
-        .and_then(handle_get_network_difficulty);
+async fn handle_get_runtime_settings(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let settings = api_layer.get_runtime_settings().await;
+    Ok(warp::reply::json(&settings))
+}
 
-    let submit_smart_contract = warp::post()
-        .and(warp::path("contract"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_submit_smart_contract);
+async fn handle_update_runtime_settings(
+    authorization: String,
+    change: icn_core::SettingsChange,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let token = parse_capability_header(&authorization).map_err(icn_error_to_rejection)?;
+    let api_layer = api_layer.read().await;
+    api_layer
+        .update_runtime_settings(&token, change)
+        .await
+        .map(|entry| warp::reply::json(&entry))
+        .map_err(icn_error_to_rejection)
+}
 
-    let execute_smart_contract = warp::post()
-        .and(warp::path("contract"))
-        .and(warp::path("execute"))
-        .and(warp::body::json())
-        .and(api_layer.clone())
-        .and_then(handle_execute_smart_contract);
+async fn handle_get_config_audit(
+    query: ConfigAuditQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let audit = api_layer.get_config_audit(query.limit).await;
+    Ok(warp::reply::json(&audit))
+}
 
-    submit_transaction
-        .or(create_proposal)
-        .or(vote_on_proposal)
-        .or(get_balance)
-        .or(mint_currency)
-        .or(create_identity)
-        .or(allocate_resource)
-        .or(get_network_stats)
-        .or(get_proposal_status)
-        .or(get_block_info)
-        .or(get_network_difficulty)
-        .or(submit_smart_contract)
-        .or(execute_smart_contract)
+async fn handle_get_storage_gc_stats(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .get_storage_gc_stats()
+        .await
+        .map(|stats| warp::reply::json(&stats))
+        .map_err(icn_error_to_rejection)
 }
 
-// Handler functions
-async fn handle_submit_transaction(
-    transaction: Transaction,
+async fn handle_pay_bond_coupons(
+    bond_id: String,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .submit_transaction(transaction)
+        .pay_bond_coupons(&bond_id)
         .await
-        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map(|amount_paid| warp::reply::json(&json!({"amount_paid": amount_paid})))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_create_proposal(
-    proposal_request: CreateProposalRequest,
+async fn handle_redeem_bond(
+    bond_id: String,
+    request: RedeemBondRequest,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
-    let proposal = Proposal {
-        id: Uuid::new_v4().to_string(),
-        title: proposal_request.title,
-        description: proposal_request.description,
-        proposer: proposal_request.proposer,
-        created_at: Utc::now(),
-        voting_ends_at: Utc::now() + Duration::days(7), // Set voting period to 7 days
-        status: ProposalStatus::Active,
-        proposal_type: proposal_request.proposal_type,
-        category: proposal_request.category,
-        required_quorum: 0.51, // Set a default quorum, can be made configurable
-        execution_timestamp: None,
-    };
     api_layer
-        .create_proposal(proposal)
+        .redeem_bond(&bond_id, &request.holder)
         .await
-        .map(|proposal_id| warp::reply::json(&CreateProposalResponse { proposal_id }))
+        .map(|amount_received| warp::reply::json(&json!({"amount_received": amount_received})))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_vote_on_proposal(
-    vote: Vote,
+async fn handle_issue_asset_token(
+    request: IssueAssetTokenRequest,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .vote_on_proposal(&vote.proposal_id, vote.voter, vote.in_favor, vote.weight)
+        .issue_asset_token(&request.issuer, request.terms, request.initial_supply)
         .await
-        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map(|asset_id| warp::reply::json(&json!({"asset_id": asset_id})))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_get_balance(
-    query: GetBalanceQuery,
+async fn handle_get_asset_token(
+    asset_id: String,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .get_balance(&query.address, &query.currency_type)
+        .get_asset_token(&asset_id)
         .await
-        .map(|balance| warp::reply::json(&json!({"balance": balance})))
+        .map(|token| warp::reply::json(&token))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_mint_currency(
-    request: MintCurrencyRequest,
+async fn handle_mint_asset_tokens(
+    asset_id: String,
+    request: MintAssetTokensRequest,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .mint_currency(&request.address, &request.currency_type, request.amount)
+        .mint_asset_tokens(&asset_id, &request.caller, request.amount)
         .await
         .map(|_| warp::reply::json(&json!({"status": "success"})))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_create_identity(
-    attributes: HashMap<String, String>,
+async fn handle_redeem_asset_tokens(
+    asset_id: String,
+    request: RedeemAssetTokensRequest,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .create_identity(attributes)
+        .redeem_asset_tokens(&asset_id, &request.holder, request.amount)
         .await
-        .map(|id| warp::reply::json(&json!({"identity_id": id})))
+        .map(|amount_received| warp::reply::json(&json!({"amount_received": amount_received})))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_allocate_resource(
-    request: AllocateResourceRequest,
+async fn handle_get_trace(
+    correlation_id: String,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .allocate_resource(&request.resource_type, request.amount)
+        .get_trace(&correlation_id)
         .await
-        .map(|_| warp::reply::json(&json!({"status": "success"})))
+        .map(|trace| warp::reply::json(&trace))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_get_network_stats(
+async fn handle_estimate_fee(
+    query: EstimateFeeQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let estimate = api_layer.estimate_fee(query.amount).await;
+    Ok(warp::reply::json(&estimate))
+}
+
+async fn handle_get_mempool(
+    query: MempoolQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let entries = api_layer.get_mempool(query.address.as_deref()).await;
+    Ok(warp::reply::json(&entries))
+}
+
+async fn handle_store_memo(
+    request: StoreMemoRequest,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .get_network_stats()
+        .store_memo(&request.content)
         .await
-        .map(|stats| warp::reply::json(&stats))
+        .map(|memo| warp::reply::json(&json!({"memo": memo})))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_get_proposal_status(
-    query: GetProposalStatusRequest,
+async fn handle_search_transactions(
+    query: MemoSearchQuery,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .get_proposal_status(&query.proposal_id)
+        .search_transactions_by_memo(&query.memo)
         .await
-        .map(|status| warp::reply::json(&GetProposalStatusResponse { status }))
+        .map(|matches| warp::reply::json(&matches))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_get_block_info(
-    query: GetBlockInfoRequest,
+async fn handle_get_block_headers(
+    query: BlockHeadersQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let limit = query.limit.unwrap_or(MAX_HEADERS_PER_REQUEST).min(MAX_HEADERS_PER_REQUEST);
+    let headers = api_layer.read().await.get_block_headers(query.from, limit).await;
+    Ok(warp::reply::json(&headers))
+}
+
+async fn handle_subscribe_headers(
+    query: SubscribeHeadersQuery,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
+    let limit = query.limit.unwrap_or(MAX_HEADERS_PER_REQUEST).clamp(1, MAX_HEADERS_PER_REQUEST);
+    let wait = std::time::Duration::from_secs(query.wait_secs);
     let api_layer = api_layer.read().await;
     api_layer
-        .get_block_info(&query.identifier)
+        .subscribe_headers(query.token.as_deref(), limit, wait)
         .await
-        .map(|block| warp::reply::json(&GetBlockInfoResponse { block }))
+        .map(|batch| warp::reply::json(&batch))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_get_network_difficulty(
+async fn handle_export_ledger(
+    request: LedgerExportRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let journal = api_layer.read().await
+        .export_ledger(&request.chart, request.format, request.from, request.to)
+        .await;
+    Ok(warp::reply::with_header(journal, "content-type", request.format.content_type()))
+}
+
+async fn handle_get_blocks(
+    query: BlocksQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let limit = query.limit.unwrap_or(MAX_BLOCKS_PER_REQUEST).min(MAX_BLOCKS_PER_REQUEST);
+    let blocks = api_layer.read().await.get_blocks(query.from, limit).await;
+    Ok(warp::reply::json(&blocks))
+}
+
+/// Streams blocks as newline-delimited JSON, one block per line, without
+/// holding more than a batch of them in memory.
+async fn handle_export_blocks(
+    query: BlockExportQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let blocks = api_layer.read().await.stream_blocks(query.from, query.to).await;
+    let lines = blocks.map(|block| {
+        serde_json::to_vec(&block).map(|mut line| {
+            line.push(b'\n');
+            line
+        })
+    });
+    Ok(warp::reply::with_header(
+        warp::reply::Response::new(warp::hyper::Body::wrap_stream(lines)),
+        "content-type",
+        "application/x-ndjson",
+    ))
+}
+
+async fn handle_get_transaction_proof(
+    hash: String,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .get_network_difficulty()
+        .get_transaction_proof(&hash)
         .await
-        .map(|difficulty| warp::reply::json(&GetNetworkDifficultyResponse { difficulty }))
+        .map(|proof| warp::reply::json(&proof))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_submit_smart_contract(
-    request: SubmitSmartContractRequest,
+/// Sends mempool additions and removals over a WebSocket as JSON text
+/// frames, optionally only those for transactions touching one address.
+async fn handle_stream_mempool(
+    ws: warp::ws::Ws,
+    query: MempoolQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let receiver = api_layer.read().await.subscribe_events().await;
+    // Removals only carry the hash, so remember which hashes the client cares about
+    let mut watched = std::collections::HashSet::new();
+    let mut events = BroadcastStream::new(receiver).filter_map(move |event| {
+        let Ok(icn_core::NodeEvent::Mempool(event)) = event else {
+            return None;
+        };
+        if let Some(address) = &query.address {
+            match &event {
+                icn_core::MempoolEvent::Added { hash, transaction } => {
+                    if &transaction.from != address && &transaction.to != address {
+                        return None;
+                    }
+                    watched.insert(hash.clone());
+                }
+                icn_core::MempoolEvent::Removed { hash, .. } => {
+                    if !watched.remove(hash) {
+                        return None;
+                    }
+                }
+            }
+        }
+        serde_json::to_string(&event).ok()
+    });
+
+    Ok(ws.on_upgrade(|mut socket| async move {
+        while let Some(text) = events.next().await {
+            if socket.send(warp::ws::Message::text(text)).await.is_err() {
+                break;
+            }
+        }
+    }))
+}
+
+/// Sends contract events over a WebSocket, filtered on the node. The query
+/// sets the first filter; a text frame holding a JSON list of
+/// `EventFilter`s replaces the filters, and an empty list pauses the stream.
+async fn handle_stream_contract_events(
+    ws: warp::ws::Ws,
+    query: ContractEventsQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let receiver = api_layer.read().await.subscribe_events().await;
+    let mut filters = vec![icn_core::EventFilter { contract_id: query.contract, event: query.event, ..Default::default() }];
+
+    Ok(ws.on_upgrade(|socket| async move {
+        let (mut sender, mut incoming) = futures_util::StreamExt::split(socket);
+        let mut events = BroadcastStream::new(receiver);
+        loop {
+            tokio::select! {
+                message = incoming.next() => {
+                    let Some(Ok(message)) = message else { break };
+                    if message.is_close() {
+                        break;
+                    }
+                    let Ok(text) = message.to_str() else { continue };
+                    match serde_json::from_str::<Vec<icn_core::EventFilter>>(text) {
+                        Ok(new_filters) => filters = new_filters,
+                        Err(e) => {
+                            let reply = json!({"error": format!("Invalid event filters: {}", e)}).to_string();
+                            if sender.send(warp::ws::Message::text(reply)).await.is_err() {
+                                break;
+                            }
+                        }
+                    }
+                }
+                event = events.next() => {
+                    let Some(event) = event else { break };
+                    // A lagging subscriber skips the events it missed
+                    let Ok(event) = event else { continue };
+                    if !icn_core::EventFilter::any_match(&filters, &event) {
+                        continue;
+                    }
+                    let Ok(text) = serde_json::to_string(&event) else { continue };
+                    if sender.send(warp::ws::Message::text(text)).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    }))
+}
+
+async fn handle_get_exchange_rates(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let rates = api_layer.get_exchange_rates().await;
+    Ok(warp::reply::json(&rates))
+}
+
+async fn handle_get_rate_history(
+    query: RateHistoryQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let pair = match (&query.base, &query.quote) {
+        (Some(base), Some(quote)) => Some((base, quote)),
+        (None, None) => None,
+        _ => return Err(icn_error_to_rejection(IcnError::Validation("Give both base and quote, or neither".into()))),
+    };
+    let api_layer = api_layer.read().await;
+    let history = api_layer.get_rate_history(pair).await;
+    Ok(warp::reply::json(&history))
+}
+
+async fn handle_convert_currency(
+    query: ConvertCurrencyQuery,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .submit_smart_contract(request.code)
+        .convert_currency(query.amount, &query.from, &query.to)
         .await
-        .map(|contract_id| warp::reply::json(&SubmitSmartContractResponse { contract_id }))
+        .map(|(amount, rate)| warp::reply::json(&ConvertCurrencyResponse { amount, rate }))
         .map_err(icn_error_to_rejection)
 }
 
-async fn handle_execute_smart_contract(
-    request: ExecuteSmartContractRequest,
+async fn handle_get_rate_oracles(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let oracles = api_layer.get_rate_oracles().await;
+    Ok(warp::reply::json(&oracles))
+}
+
+async fn handle_submit_oracle_quote(
+    quote: icn_currency::OracleQuote,
     api_layer: Arc<RwLock<ApiLayer>>,
 ) -> Result<impl Reply, Rejection> {
     let api_layer = api_layer.read().await;
     api_layer
-        .execute_smart_contract(&request.contract_id, &request.function, request.args)
+        .submit_oracle_quote(quote)
         .await
-        .map(|result| warp::reply::json(&ExecuteSmartContractResponse { result }))
+        .map(|rate| warp::reply::json(&rate))
+        .map_err(icn_error_to_rejection)
+}
+
+async fn handle_get_parameters(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let parameters = api_layer.get_parameters().await;
+    Ok(warp::reply::json(&parameters))
+}
+
+async fn handle_get_parameter_history(
+    query: ParameterHistoryQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let history = api_layer.get_parameter_history(query.name.as_deref()).await;
+    Ok(warp::reply::json(&history))
+}
+
+async fn handle_get_alerts(
+    query: GetAlertsQuery,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let alerts = api_layer.get_alerts(query.limit).await;
+    Ok(warp::reply::json(&alerts))
+}
+
+/// Streams alerts as server-sent events. Alerts a slow client falls behind
+/// on are skipped rather than ending the stream.
+async fn handle_stream_alerts(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let receiver = api_layer.read().await.subscribe_alerts().await;
+    let events = BroadcastStream::new(receiver).filter_map(|alert| {
+        let alert = alert.ok()?;
+        let event = warp::sse::Event::default()
+            .event("alert")
+            .id(alert.id.to_string())
+            .json_data(&alert)
+            .ok()?;
+        Some(Ok::<_, Infallible>(event))
+    });
+    Ok(warp::sse::reply(warp::sse::keep_alive().stream(events)))
+}
+
+async fn handle_get_pause_status(
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    let status = api_layer.get_pause_status().await;
+    Ok(warp::reply::json(&status))
+}
+
+async fn handle_guardian_pause(
+    request: GuardianPauseRequest,
+    api_layer: Arc<RwLock<ApiLayer>>,
+) -> Result<impl Reply, Rejection> {
+    let api_layer = api_layer.read().await;
+    api_layer
+        .apply_guardian_signatures(request.paused, request.reason, request.signatures)
+        .await
+        .map(|_| warp::reply::json(&json!({"status": "success", "paused": request.paused})))
         .map_err(icn_error_to_rejection)
 }
 
@@ -438,6 +5331,7 @@ mod tests {
     use super::*;
     use icn_core::Config;
     use std::net::SocketAddr;
+    use icn_common::{Amount, SignatureScheme};
 
     async fn setup_test_env() -> (Arc<RwLock<ApiLayer>>, Arc<RwLock<icn_core::IcnNode>>) {
         let config = Config {
@@ -457,14 +5351,23 @@ mod tests {
         let transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 100.0,
+            amount: Amount::from(100.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: chrono::Utc::now().timestamp(),
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
-        let result = handle_submit_transaction(transaction, api_layer).await;
+        let tx_hash = transaction.hash();
+        let result = handle_submit_transaction(transaction, api_layer.clone()).await;
         assert!(result.is_ok());
+
+        let receipt = handle_get_receipt(tx_hash, api_layer.clone()).await;
+        assert!(receipt.is_ok());
+        assert!(handle_get_receipt("unknown".to_string(), api_layer).await.is_err());
     }
 
     #[tokio::test]
@@ -476,12 +5379,26 @@ mod tests {
             proposer: "Alice".to_string(),
             proposal_type: ProposalType::Constitutional,
             category: ProposalCategory::Economic,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
         };
 
         let result = handle_create_proposal(proposal_request, api_layer).await;
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_vote_request_rejects_client_weight() {
+        let valid = json!({"proposal_id": "p1", "voter": "did:icn:alice", "in_favor": true});
+        assert!(serde_json::from_value::<VoteOnProposalRequest>(valid).is_ok());
+
+        let weighted = json!({"proposal_id": "p1", "voter": "did:icn:alice", "in_favor": true, "weight": 100.0});
+        assert!(serde_json::from_value::<VoteOnProposalRequest>(weighted).is_err());
+    }
+
     #[tokio::test]
     async fn test_get_block_info() {
         let (api_layer, node) = setup_test_env().await;
@@ -536,11 +5453,18 @@ mod tests {
             let mut node = node.write().await;
             node.deploy_smart_contract("contract TestContract { function test() -> int { return 42; } }".to_string()).await.unwrap()
         };
+        let keypair = ed25519_dalek::Keypair::generate(&mut rand::rngs::OsRng {});
+        let caller = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        node.read().await.import_identities(&format!("did\n{}\n", caller), RosterFormat::Csv, false, None).await.unwrap();
+        let message = icn_identity::signed_request_message("call-contract", &caller, &[&contract_id, "test", "[]"], 1);
 
         let request = ExecuteSmartContractRequest {
+            caller,
             contract_id: contract_id.clone(),
             function: "test".to_string(),
             args: vec![],
+            nonce: 1,
+            signature: hex::encode(ed25519_dalek::Signer::sign(&keypair, &message).to_bytes()),
         };
 
         let result = handle_execute_smart_contract(request, api_layer).await;
@@ -0,0 +1,294 @@
+// File: crates/icn_api/src/replica.rs
+
+use crate::errors::{problem_reply, ApiError};
+use crate::{api_routes, ApiLayer, MAX_HEADERS_PER_REQUEST};
+use icn_common::{BlockHeader, IcnError, IcnResult};
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use warp::http::header::{HeaderValue, ACCEPT, AUTHORIZATION, CACHE_CONTROL, CONTENT_TYPE, UPGRADE};
+use warp::http::{HeaderMap, Method, StatusCode};
+use warp::hyper::body::Bytes;
+use warp::path::FullPath;
+use warp::reply::Response;
+use warp::{Filter, Reply};
+
+/// Request headers passed on to the validator with a proxied request.
+const FORWARDED_HEADERS: [warp::http::header::HeaderName; 3] = [CONTENT_TYPE, ACCEPT, AUTHORIZATION];
+
+/// Response headers passed back from the validator.
+const RELAYED_HEADERS: [warp::http::header::HeaderName; 2] = [CONTENT_TYPE, CACHE_CONTROL];
+
+/// How a read replica decides between answering from its own state and
+/// proxying to the validator it follows.
+#[derive(Debug, Clone)]
+pub struct ReplicaConfig {
+    /// Base URL of the validator's API, such as `http://validator:3030`.
+    pub validator_url: String,
+    /// Reads are proxied while this node is more blocks behind the validator.
+    pub max_lag_blocks: u64,
+    /// Reads are also proxied when the validator hasn't been reached for
+    /// this long, since the lag is then unknown.
+    pub max_staleness: Duration,
+    /// How often the validator's height is checked.
+    pub check_interval: Duration,
+    /// `max-age` of the reads this node answers itself, in seconds.
+    pub cache_max_age: u64,
+}
+
+impl ReplicaConfig {
+    pub fn new(validator_url: impl Into<String>) -> Self {
+        ReplicaConfig {
+            validator_url: validator_url.into().trim_end_matches('/').to_string(),
+            max_lag_blocks: 2,
+            max_staleness: Duration::from_secs(30),
+            check_interval: Duration::from_secs(5),
+            cache_max_age: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LagCheck {
+    lag: u64,
+    at: Instant,
+}
+
+/// A node serving the API read-only: reads come from its local state while
+/// it keeps up with the validator, and everything else goes to the
+/// validator.
+pub struct Replica {
+    config: ReplicaConfig,
+    client: reqwest::Client,
+    last_check: RwLock<Option<LagCheck>>,
+}
+
+impl Replica {
+    pub fn new(config: ReplicaConfig) -> Self {
+        Replica {
+            config,
+            client: reqwest::Client::new(),
+            last_check: RwLock::new(None),
+        }
+    }
+
+    pub fn config(&self) -> &ReplicaConfig {
+        &self.config
+    }
+
+    /// How many blocks behind the validator this node was at the last
+    /// check, or `None` if that check is too old to go by.
+    pub async fn lag(&self) -> Option<u64> {
+        self.last_check.read().await
+            .filter(|check| check.at.elapsed() <= self.config.max_staleness)
+            .map(|check| check.lag)
+    }
+
+    /// Whether reads can be answered from local state.
+    pub async fn serves_reads(&self) -> bool {
+        matches!(self.lag().await, Some(lag) if lag <= self.config.max_lag_blocks)
+    }
+
+    pub async fn record_lag(&self, lag: u64) {
+        *self.last_check.write().await = Some(LagCheck { lag, at: Instant::now() });
+    }
+
+    /// Asks the validator for the headers past `local_height` and records
+    /// how many there were. Lag beyond one page of headers is counted as a
+    /// full page, which is past any sensible bound anyway.
+    pub async fn check_lag(&self, local_height: u64) -> IcnResult<u64> {
+        let url = format!("{}/headers?from={}&limit={}", self.config.validator_url, local_height, MAX_HEADERS_PER_REQUEST);
+        let response = self.client.get(&url).send().await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| IcnError::Network(format!("Failed to reach validator: {}", e)))?;
+        let headers: Vec<BlockHeader> = response.json().await
+            .map_err(|e| IcnError::Network(format!("Invalid headers from validator: {}", e)))?;
+        let lag = headers.len() as u64;
+        self.record_lag(lag).await;
+        Ok(lag)
+    }
+
+    /// Sends the request on to the validator and relays its answer. A
+    /// validator that can't be reached is a `502`.
+    async fn forward(&self, method: Method, path: FullPath, query: String, headers: HeaderMap, body: Bytes) -> Response {
+        let mut url = format!("{}{}", self.config.validator_url, path.as_str());
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(&query);
+        }
+        let mut request = self.client.request(method, &url).body(body);
+        for name in &FORWARDED_HEADERS {
+            if let Some(value) = headers.get(name) {
+                request = request.header(name, value.clone());
+            }
+        }
+
+        let upstream = match request.send().await {
+            Ok(upstream) => upstream,
+            Err(e) => return validator_unavailable(e),
+        };
+        let status = upstream.status();
+        let mut relayed = HeaderMap::new();
+        for name in &RELAYED_HEADERS {
+            if let Some(value) = upstream.headers().get(name) {
+                relayed.insert(name, value.clone());
+            }
+        }
+        let body = match upstream.bytes().await {
+            Ok(body) => body,
+            Err(e) => return validator_unavailable(e),
+        };
+
+        let mut response = Response::new(body.into());
+        *response.status_mut() = status;
+        *response.headers_mut() = relayed;
+        response
+    }
+}
+
+fn validator_unavailable(error: reqwest::Error) -> Response {
+    let error = ApiError::new(StatusCode::BAD_GATEWAY, "validator_unavailable", format!("Failed to reach validator: {}", error));
+    problem_reply(&error).into_response()
+}
+
+/// Whether a request may be answered locally. Only reads qualify, and only
+/// while the replica keeps up; websocket upgrades can't be proxied, so they
+/// always stay here.
+async fn answers_locally(replica: &Replica, method: &Method, headers: &HeaderMap) -> bool {
+    if headers.contains_key(UPGRADE) {
+        return true;
+    }
+    (method == Method::GET || method == Method::HEAD) && replica.serves_reads().await
+}
+
+/// Checks the validator's height every `check_interval`. A failed check
+/// leaves the last one to go stale, after which reads are proxied too.
+pub fn spawn_lag_checks(replica: Arc<Replica>, api_layer: Arc<RwLock<ApiLayer>>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(replica.config.check_interval);
+        loop {
+            interval.tick().await;
+            let local_height = api_layer.read().await.get_chain_height().await;
+            let _ = replica.check_lag(local_height).await;
+        }
+    })
+}
+
+/// The API routes of a read replica. Start [`spawn_lag_checks`] alongside
+/// them, or every read is proxied.
+pub fn replica_routes(
+    api_layer: Arc<RwLock<ApiLayer>>,
+    replica: Arc<Replica>,
+) -> impl Filter<Extract = impl Reply, Error = Infallible> + Clone {
+    serve_as_replica(api_routes(api_layer), replica)
+}
+
+/// Puts `local` behind the replica: writes, and reads while the replica is
+/// behind, go to the validator. Successful reads answered locally are
+/// marked cacheable for `cache_max_age`.
+pub fn serve_as_replica<F, R>(
+    local: F,
+    replica: Arc<Replica>,
+) -> impl Filter<Extract = (Response,), Error = Infallible> + Clone
+where
+    F: Filter<Extract = (R,), Error = Infallible> + Clone + Send + Sync + 'static,
+    R: Reply,
+{
+    let cache_control = HeaderValue::from_str(&format!("public, max-age={}", replica.config.cache_max_age))
+        .expect("cache-control value is ASCII");
+    let local = local.map(move |reply: R| {
+        let mut response = reply.into_response();
+        if response.status().is_success() && !response.headers().contains_key(CACHE_CONTROL) {
+            response.headers_mut().insert(CACHE_CONTROL, cache_control.clone());
+        }
+        response
+    });
+
+    let replica = warp::any().map(move || replica.clone());
+    let proxied = warp::method()
+        .and(warp::header::headers_cloned())
+        .and(replica.clone())
+        .and_then(|method: Method, headers: HeaderMap, replica: Arc<Replica>| async move {
+            if answers_locally(&replica, &method, &headers).await {
+                Err(warp::reject())
+            } else {
+                Ok(())
+            }
+        })
+        .untuple_one()
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .and(replica)
+        .then(|method, path, query, headers, body, replica: Arc<Replica>| async move {
+            replica.forward(method, path, query, headers, body).await
+        });
+
+    proxied.or(local).unify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A validator that answers every request with its method, path and body.
+    fn start_validator() -> String {
+        let echo = warp::method()
+            .and(warp::path::full())
+            .and(warp::body::bytes())
+            .map(|method: Method, path: FullPath, body: Bytes| {
+                format!("validator {} {} {}", method, path.as_str(), String::from_utf8_lossy(&body))
+            });
+        let (address, server) = warp::serve(echo).bind_ephemeral(([127, 0, 0, 1], 0));
+        tokio::spawn(server);
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_replica_routes_reads_and_writes() {
+        let replica = Arc::new(Replica::new(ReplicaConfig::new(start_validator())));
+        let local = warp::any().map(|| "local");
+        let routes = serve_as_replica(local, replica.clone());
+
+        // Writes always go to the validator
+        replica.record_lag(0).await;
+        let response = warp::test::request().method("POST").path("/transaction?x=1").body("tx").reply(&routes).await;
+        assert_eq!(response.body(), "validator POST /transaction tx");
+        assert!(!response.headers().contains_key(CACHE_CONTROL));
+
+        // Reads are local and cacheable while the replica keeps up
+        let response = warp::test::request().path("/balance/alice").reply(&routes).await;
+        assert_eq!(response.body(), "local");
+        assert_eq!(response.headers()[CACHE_CONTROL], "public, max-age=5");
+
+        // ...and proxied once it falls behind
+        replica.record_lag(3).await;
+        let response = warp::test::request().path("/balance/alice").reply(&routes).await;
+        assert_eq!(response.body(), "validator GET /balance/alice ");
+    }
+
+    #[tokio::test]
+    async fn test_stale_checks_and_unreachable_validator() {
+        let mut config = ReplicaConfig::new("http://127.0.0.1:1/");
+        config.max_staleness = Duration::from_millis(10);
+        let replica = Arc::new(Replica::new(config));
+        assert_eq!(replica.config().validator_url, "http://127.0.0.1:1");
+        assert!(!replica.serves_reads().await);
+
+        replica.record_lag(1).await;
+        assert!(replica.serves_reads().await);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(replica.lag().await, None);
+        assert!(replica.check_lag(0).await.is_err());
+
+        let routes = serve_as_replica(warp::any().map(|| "local"), replica);
+        let response = warp::test::request().path("/balance/alice").reply(&routes).await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let problem: crate::errors::Problem = serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(problem.code, "validator_unavailable");
+    }
+}
@@ -80,9 +80,18 @@ async fn handle_transaction(tx: Transaction, api: Arc<RwLock<ApiLayer>>) -> Resu
     }
 }
 
-async fn handle_proposal(proposal: Proposal, api: Arc<RwLock<ApiLayer>>) -> Result<impl warp::Reply, warp::Rejection> {
+/// A proposal with its proposer's hex-encoded signature over
+/// `Proposal::signing_bytes`.
+#[derive(serde::Deserialize)]
+struct SignedProposal {
+    proposal: Proposal,
+    signature: String,
+}
+
+async fn handle_proposal(request: SignedProposal, api: Arc<RwLock<ApiLayer>>) -> Result<impl warp::Reply, warp::Rejection> {
+    let signature = hex::decode(&request.signature).unwrap_or_default();
     let api = api.read().await;
-    match api.create_proposal(proposal).await {
+    match api.create_proposal(request.proposal, &signature).await {
         Ok(response) => Ok(warp::reply::json(&response)),
         Err(e) => {
             eprintln!("Error creating proposal: {}", e);
@@ -136,8 +145,9 @@ async fn handle_submit_contract(contract: crate::SubmitSmartContractRequest, api
 }
 
 async fn handle_execute_contract(request: crate::ExecuteSmartContractRequest, api: Arc<RwLock<ApiLayer>>) -> Result<impl warp::Reply, warp::Rejection> {
+    let signature = crate::decode_signature(&request.signature)?;
     let api = api.read().await;
-    match api.execute_smart_contract(&request.contract_id, &request.function, request.args).await {
+    match api.execute_smart_contract(&request.caller, &request.contract_id, &request.function, request.args, request.nonce, &signature).await {
         Ok(result) => Ok(warp::reply::json(&result)),
         Err(e) => {
             eprintln!("Error executing smart contract: {}", e);
@@ -149,6 +159,7 @@ async fn handle_execute_contract(request: crate::ExecuteSmartContractRequest, ap
 #[cfg(test)]
 mod tests {
     use super::*;
+    use icn_common::SignatureScheme;
     use icn_core::Config;
     use std::net::SocketAddr;
     use warp::test::request;
@@ -171,10 +182,14 @@ mod tests {
         let transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 100.0,
+            amount: icn_common::Amount::from(100.0),
             currency_type: icn_common::CurrencyType::BasicNeeds,
             timestamp: chrono::Utc::now().timestamp(),
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         let response = request()
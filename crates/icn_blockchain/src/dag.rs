@@ -0,0 +1,322 @@
+// File: crates/icn_blockchain/src/dag.rs
+
+use crate::{Block, Blockchain, Transaction};
+use icn_common::{evidence_root, Amount, CurrencyType, IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Most blocks a DAG block may build on.
+pub const MAX_DAG_PARENTS: usize = 8;
+
+/// Levels a DAG block must be built over before it joins the chain. Blocks
+/// arriving for a level that has already joined are refused as stale.
+pub const DAG_CONFIRMATION_DEPTH: u64 = 2;
+
+/// A block in DAG mode, built on every tip its producer knew of rather than
+/// a single predecessor. The inner block's `previous_hash` commits to the
+/// parents and its `index` is its depth, one more than its deepest parent.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DagBlock {
+    pub parents: Vec<String>,
+    pub block: Block,
+}
+
+impl DagBlock {
+    pub fn new(depth: u64, mut parents: Vec<String>, transactions: Vec<Transaction>, timestamp: i64) -> Self {
+        parents.sort();
+        parents.dedup();
+        let block = Block::new(depth, transactions, &parents_digest(&parents)).with_timestamp(timestamp);
+        DagBlock { parents, block }
+    }
+
+    pub fn hash(&self) -> &str {
+        &self.block.hash
+    }
+}
+
+/// What a DAG block's `previous_hash` must be for the given sorted parents.
+pub fn parents_digest(parents: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    for parent in parents {
+        hasher.update(parent.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// An earlier block in the linear order already carried it.
+    Duplicate,
+    /// Blocks ordered before it spent the funds it needed.
+    InsufficientFunds,
+}
+
+/// A transaction dropped while linearizing because it conflicted with one
+/// ordered before it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DagConflict {
+    pub dag_block: String,
+    pub transaction: String,
+    pub reason: ConflictReason,
+}
+
+/// Blocks received in DAG mode and how far they have been linearized.
+///
+/// Confirmed blocks are linearized level by level, and within a level by
+/// hash, so every node holding the same blocks orders them the same way.
+/// Each level's blocks only depend on shallower ones, so that order never
+/// puts a block before its parents.
+#[derive(Debug, Clone)]
+pub struct BlockDag {
+    blocks: HashMap<String, DagBlock>,
+    depths: HashMap<String, u64>,
+    tips: BTreeSet<String>,
+    pending: BTreeSet<(u64, String)>,
+    linearized_depth: u64,
+    linearized_transactions: HashSet<String>,
+    /// The DAG block each linearized chain block came from, by chain index.
+    origins: BTreeMap<u64, String>,
+}
+
+impl BlockDag {
+    /// Starts a DAG rooted at `root`, the last block of the linear chain.
+    pub fn new(root: &Block) -> Self {
+        BlockDag {
+            blocks: HashMap::new(),
+            depths: HashMap::from([(root.hash.clone(), 0)]),
+            tips: BTreeSet::from([root.hash.clone()]),
+            pending: BTreeSet::new(),
+            linearized_depth: 0,
+            linearized_transactions: HashSet::new(),
+            origins: BTreeMap::new(),
+        }
+    }
+
+    /// Blocks nothing builds on yet, sorted by hash.
+    pub fn tips(&self) -> Vec<String> {
+        self.tips.iter().cloned().collect()
+    }
+
+    pub fn depth_of(&self, hash: &str) -> Option<u64> {
+        self.depths.get(hash).copied()
+    }
+
+    /// Depth of the deepest block.
+    pub fn depth(&self) -> u64 {
+        self.depths.values().copied().max().unwrap_or(0)
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&DagBlock> {
+        self.blocks.get(hash)
+    }
+
+    /// Hash of the DAG block the chain block at `index` was linearized from.
+    pub fn origin(&self, index: u64) -> Option<&str> {
+        self.origins.get(&index).map(String::as_str)
+    }
+
+    /// Adds a block whose parents are all known. Its transactions are not
+    /// checked against balances here; conflicts are settled when it is
+    /// linearized.
+    pub fn insert(&mut self, dag_block: DagBlock) -> IcnResult<()> {
+        let hash = dag_block.block.hash.clone();
+        if self.depths.contains_key(&hash) {
+            return Err(IcnError::Blockchain(format!("DAG block {} is already known", hash)));
+        }
+        if dag_block.parents.is_empty() || dag_block.parents.len() > MAX_DAG_PARENTS {
+            return Err(IcnError::Blockchain(format!("A DAG block needs between 1 and {} parents", MAX_DAG_PARENTS)));
+        }
+        if dag_block.parents.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(IcnError::Blockchain("DAG block parents must be sorted and distinct".into()));
+        }
+        if dag_block.block.previous_hash != parents_digest(&dag_block.parents) {
+            return Err(IcnError::Blockchain("DAG block does not commit to its parents".into()));
+        }
+        if dag_block.block.hash != dag_block.block.calculate_hash() {
+            return Err(IcnError::Blockchain("Invalid DAG block hash".into()));
+        }
+
+        let mut depth = 0;
+        for parent in &dag_block.parents {
+            let parent_depth = self.depth_of(parent)
+                .ok_or_else(|| IcnError::Blockchain(format!("Unknown DAG parent {}", parent)))?;
+            depth = depth.max(parent_depth + 1);
+        }
+        if depth <= self.linearized_depth {
+            return Err(IcnError::Blockchain(format!("DAG block at depth {} arrived after that level was linearized", depth)));
+        }
+        if dag_block.block.index != depth {
+            return Err(IcnError::Blockchain(format!("DAG block claims depth {} but its parents put it at {}", dag_block.block.index, depth)));
+        }
+
+        for parent in &dag_block.parents {
+            self.tips.remove(parent);
+        }
+        self.tips.insert(hash.clone());
+        self.depths.insert(hash.clone(), depth);
+        self.pending.insert((depth, hash.clone()));
+        self.blocks.insert(hash, dag_block);
+        Ok(())
+    }
+
+    /// Takes the blocks now `DAG_CONFIRMATION_DEPTH` levels deep, in
+    /// linear order.
+    pub fn take_confirmed(&mut self) -> Vec<DagBlock> {
+        let confirmed_depth = self.depth().saturating_sub(DAG_CONFIRMATION_DEPTH);
+        if confirmed_depth <= self.linearized_depth {
+            return Vec::new();
+        }
+        let later = self.pending.split_off(&(confirmed_depth + 1, String::new()));
+        let confirmed = std::mem::replace(&mut self.pending, later);
+        self.linearized_depth = confirmed_depth;
+        confirmed.into_iter().filter_map(|(_, hash)| self.blocks.get(&hash).cloned()).collect()
+    }
+}
+
+impl Blockchain {
+    /// Experimental. From now on blocks may build on several parents, and
+    /// are appended to the chain in a deterministic order once confirmed,
+    /// so everything reading the chain still sees a single line of blocks.
+    pub fn enable_dag_mode(&mut self) {
+        if self.dag.is_none() {
+            self.dag = Some(BlockDag::new(self.get_latest_block()));
+        }
+    }
+
+    pub fn dag(&self) -> Option<&BlockDag> {
+        self.dag.as_ref()
+    }
+
+    /// A DAG block of `transactions` built on the current tips, deepest
+    /// first when there are more than a block may reference.
+    pub fn new_dag_block(&self, transactions: Vec<Transaction>) -> IcnResult<DagBlock> {
+        let dag = self.dag.as_ref().ok_or_else(|| IcnError::Blockchain("DAG mode is not enabled".into()))?;
+        let mut tips: Vec<(u64, String)> = dag.tips().into_iter()
+            .map(|hash| (dag.depth_of(&hash).unwrap_or(0), hash))
+            .collect();
+        tips.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        tips.truncate(MAX_DAG_PARENTS);
+        let depth = tips.first().map_or(0, |(depth, _)| depth + 1);
+        let parents = tips.into_iter().map(|(_, hash)| hash).collect();
+        Ok(DagBlock::new(depth, parents, transactions, self.clock.now().timestamp()))
+    }
+
+    /// Adds a block to the DAG and appends whatever that confirms to the
+    /// chain. Returns the transactions dropped as conflicting on the way.
+    pub fn add_dag_block(&mut self, dag_block: DagBlock) -> IcnResult<Vec<DagConflict>> {
+        let block = &dag_block.block;
+        if block.timestamp > self.clock.now().timestamp() {
+            return Err(IcnError::Blockchain("Block timestamp is in the future".into()));
+        }
+        self.limits.check_block(block, block.transactions.len())?;
+        for transaction in &block.transactions {
+            self.limits.check_transaction(transaction)?;
+            if transaction.is_expired(block.timestamp) {
+                return Err(IcnError::Blockchain(format!("Transaction {} expired before the block", transaction.hash())));
+            }
+        }
+        if block.merkle_root != block.calculate_merkle_root() {
+            return Err(IcnError::Blockchain("Invalid Merkle root".into()));
+        }
+        if block.evidence_root != evidence_root(&block.evidence) {
+            return Err(IcnError::Blockchain("Invalid evidence root".into()));
+        }
+
+        let dag = self.dag.as_mut().ok_or_else(|| IcnError::Blockchain("DAG mode is not enabled".into()))?;
+        dag.insert(dag_block)?;
+        let mut conflicts = Vec::new();
+        for confirmed in dag.take_confirmed() {
+            conflicts.extend(self.linearize(confirmed)?);
+        }
+        Ok(conflicts)
+    }
+
+    /// Appends a confirmed DAG block to the chain, keeping the transactions
+    /// that still apply after everything ordered before them.
+    fn linearize(&mut self, dag_block: DagBlock) -> IcnResult<Vec<DagConflict>> {
+        let dag = self.dag.as_mut().ok_or_else(|| IcnError::Blockchain("DAG mode is not enabled".into()))?;
+        let mut deltas: HashMap<(String, CurrencyType), Amount> = HashMap::new();
+        let mut accepted = Vec::new();
+        let mut conflicts = Vec::new();
+        for transaction in dag_block.block.transactions {
+            let hash = transaction.hash();
+            let reason = if dag.linearized_transactions.contains(&hash) {
+                Some(ConflictReason::Duplicate)
+            } else if transaction.from == "Network" {
+                None
+            } else {
+                let key = (transaction.from.clone(), transaction.currency_type.clone());
+                let balance = self.currency_system.get_balance_amount(&transaction.from, &transaction.currency_type)?
+                    + deltas.get(&key).copied().unwrap_or_default();
+                (balance < transaction.amount).then_some(ConflictReason::InsufficientFunds)
+            };
+            if let Some(reason) = reason {
+                conflicts.push(DagConflict { dag_block: dag_block.block.hash.clone(), transaction: hash, reason });
+                continue;
+            }
+            if transaction.from != "Network" {
+                *deltas.entry((transaction.from.clone(), transaction.currency_type.clone())).or_default() -= transaction.amount;
+            }
+            *deltas.entry((transaction.to.clone(), transaction.currency_type.clone())).or_default() += transaction.amount;
+            dag.linearized_transactions.insert(hash);
+            accepted.push(transaction);
+        }
+
+        let index = self.chain.len() as u64;
+        dag.origins.insert(index, dag_block.block.hash);
+        let mut block = Block::new(index, accepted, &self.get_latest_block().hash)
+            .with_timestamp(dag_block.block.timestamp)
+            .with_evidence(dag_block.block.evidence);
+        block.mine(self.difficulty);
+        self.index.index_block(&block);
+        self.chain.push(block);
+        self.update_balances()?;
+        Ok(conflicts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrent_blocks_are_linearized_deterministically() {
+        let mut blockchain = Blockchain::new(1);
+        blockchain.allocate_genesis("Alice", &CurrencyType::BasicNeeds, 10.0).unwrap();
+        blockchain.enable_dag_mode();
+        let now = blockchain.now().timestamp();
+        let transfer = |to: &str, amount: f64| Transaction::new("Alice".to_string(), to.to_string(), amount, CurrencyType::BasicNeeds, now);
+
+        // Two producers spend the same funds at once, and both carry a shared transfer
+        let shared = transfer("Dave", 1.0);
+        let to_bob = blockchain.new_dag_block(vec![shared.clone(), transfer("Bob", 8.0)]).unwrap();
+        let to_carol = blockchain.new_dag_block(vec![shared.clone(), transfer("Carol", 8.0)]).unwrap();
+        assert_eq!(to_bob.parents, to_carol.parents);
+        blockchain.add_dag_block(to_bob.clone()).unwrap();
+        blockchain.add_dag_block(to_carol.clone()).unwrap();
+        assert_eq!(blockchain.dag().unwrap().tips().len(), 2);
+
+        let merge = blockchain.new_dag_block(Vec::new()).unwrap();
+        assert_eq!(merge.parents.len(), 2);
+        assert!(blockchain.add_dag_block(merge).unwrap().is_empty());
+        assert_eq!(blockchain.chain.len(), 1);
+
+        // A third level confirms the first, ordered by hash
+        let conflicts = blockchain.add_dag_block(blockchain.new_dag_block(Vec::new()).unwrap()).unwrap();
+        let (first, second) = if to_bob.hash() < to_carol.hash() { (&to_bob, &to_carol) } else { (&to_carol, &to_bob) };
+        assert_eq!(conflicts.iter().map(|c| (c.dag_block.as_str(), c.reason)).collect::<Vec<_>>(), vec![
+            (second.hash(), ConflictReason::Duplicate),
+            (second.hash(), ConflictReason::InsufficientFunds),
+        ]);
+        assert_eq!(blockchain.chain.len(), 3);
+        assert_eq!(blockchain.chain[2].previous_hash, blockchain.chain[1].hash);
+        assert_eq!(blockchain.dag().unwrap().origin(1), Some(first.hash()));
+        assert_eq!(blockchain.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 1.0);
+
+        // A block for a level already linearized comes too late
+        let late = DagBlock::new(1, to_bob.parents.clone(), vec![transfer("Erin", 1.0)], now);
+        assert!(blockchain.add_dag_block(late).is_err());
+    }
+}
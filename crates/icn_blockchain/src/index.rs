@@ -0,0 +1,216 @@
+// File: crates/icn_blockchain/src/index.rs
+
+use crate::{Block, Transaction};
+use icn_common::{IcnResult, IcnError, CurrencyType};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Width of the time buckets transactions are indexed under.
+pub const TIME_BUCKET_SECS: i64 = 3600;
+
+/// Most results a single search page may hold.
+pub const MAX_SEARCH_LIMIT: usize = 500;
+
+/// Where a committed transaction sits: its block index and its position in
+/// that block. Ordering by location is chain order.
+pub type TransactionLocation = (u64, usize);
+
+/// Criteria for a transaction search. Every criterion that is set must
+/// match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TransactionFilter {
+    /// Sender or recipient.
+    pub address: Option<String>,
+    pub currency_type: Option<CurrencyType>,
+    /// Inclusive lower bound on the transaction timestamp.
+    pub from_time: Option<i64>,
+    /// Exclusive upper bound on the transaction timestamp.
+    pub to_time: Option<i64>,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+}
+
+/// A committed transaction found by a search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedTransaction {
+    pub block_index: u64,
+    pub transaction: Transaction,
+}
+
+/// One page of search results, in chain order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionPage {
+    /// Matches across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub transactions: Vec<IndexedTransaction>,
+}
+
+/// Lookup tables over the committed chain, kept up to date as blocks are
+/// added so searches don't scan every block.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionIndex {
+    by_address: HashMap<String, BTreeSet<TransactionLocation>>,
+    by_currency: HashMap<CurrencyType, BTreeSet<TransactionLocation>>,
+    by_time_bucket: BTreeMap<i64, BTreeSet<TransactionLocation>>,
+    /// Keyed by `amount_key`, which sorts like the amounts themselves.
+    by_amount: BTreeMap<u64, BTreeSet<TransactionLocation>>,
+    all: BTreeSet<TransactionLocation>,
+}
+
+/// Orders non-negative amounts the way `f64` does, so they can key a
+/// `BTreeMap`. Negative amounts are never valid and sort as zero.
+fn amount_key(amount: f64) -> u64 {
+    amount.max(0.0).to_bits()
+}
+
+impl TransactionIndex {
+    pub fn index_block(&mut self, block: &Block) {
+        for (position, transaction) in block.transactions.iter().enumerate() {
+            let location = (block.index, position);
+            self.by_address.entry(transaction.from.clone()).or_default().insert(location);
+            self.by_address.entry(transaction.to.clone()).or_default().insert(location);
+            self.by_currency.entry(transaction.currency_type.clone()).or_default().insert(location);
+            self.by_time_bucket.entry(transaction.timestamp.div_euclid(TIME_BUCKET_SECS)).or_default().insert(location);
+            self.by_amount.entry(amount_key(transaction.amount.to_f64())).or_default().insert(location);
+            self.all.insert(location);
+        }
+    }
+
+    /// Replaces the index with one over `chain`, after a fork swaps blocks out.
+    pub fn rebuild(&mut self, chain: &[Block]) {
+        *self = TransactionIndex::default();
+        for block in chain {
+            self.index_block(block);
+        }
+    }
+
+    /// Locations of every transaction touching `address`, in chain order.
+    pub fn locations_for_address(&self, address: &str) -> impl Iterator<Item = &TransactionLocation> {
+        self.by_address.get(address).into_iter().flatten()
+    }
+
+    /// Locations matching every criterion in `filter`, in chain order.
+    /// Amount and time bounds narrow the candidates through their indexes;
+    /// the bucket edges are then checked exactly against `chain`.
+    pub fn search(&self, chain: &[Block], filter: &TransactionFilter) -> IcnResult<Vec<TransactionLocation>> {
+        if let (Some(min), Some(max)) = (filter.min_amount, filter.max_amount) {
+            if min > max {
+                return Err(IcnError::Validation("min_amount cannot exceed max_amount".into()));
+            }
+        }
+        if let (Some(from), Some(to)) = (filter.from_time, filter.to_time) {
+            if from >= to {
+                return Err(IcnError::Validation("from_time must be before to_time".into()));
+            }
+        }
+
+        let mut candidate_sets: Vec<BTreeSet<TransactionLocation>> = Vec::new();
+        if let Some(address) = &filter.address {
+            candidate_sets.push(self.by_address.get(address).cloned().unwrap_or_default());
+        }
+        if let Some(currency_type) = &filter.currency_type {
+            candidate_sets.push(self.by_currency.get(currency_type).cloned().unwrap_or_default());
+        }
+        if filter.from_time.is_some() || filter.to_time.is_some() {
+            let first = filter.from_time.map_or(i64::MIN, |from| from.div_euclid(TIME_BUCKET_SECS));
+            let last = filter.to_time.map_or(i64::MAX, |to| (to - 1).div_euclid(TIME_BUCKET_SECS));
+            candidate_sets.push(self.by_time_bucket.range(first..=last).flat_map(|(_, set)| set.iter().copied()).collect());
+        }
+        if filter.min_amount.is_some() || filter.max_amount.is_some() {
+            let low = filter.min_amount.map_or(0, amount_key);
+            let high = filter.max_amount.map_or(u64::MAX, amount_key);
+            candidate_sets.push(self.by_amount.range(low..=high).flat_map(|(_, set)| set.iter().copied()).collect());
+        }
+
+        // Intersect starting from the smallest set
+        candidate_sets.sort_by_key(BTreeSet::len);
+        let mut candidates = match candidate_sets.split_first() {
+            Some((smallest, rest)) => smallest.iter()
+                .filter(|location| rest.iter().all(|set| set.contains(location)))
+                .copied()
+                .collect::<Vec<_>>(),
+            None => self.all.iter().copied().collect(),
+        };
+
+        candidates.retain(|&(block_index, position)| {
+            chain.get(block_index as usize)
+                .and_then(|block| block.transactions.get(position))
+                .is_some_and(|transaction| {
+                    filter.from_time.is_none_or(|from| transaction.timestamp >= from)
+                        && filter.to_time.is_none_or(|to| transaction.timestamp < to)
+                })
+        });
+        Ok(candidates)
+    }
+
+    /// Runs `filter` and returns up to `limit` matches after skipping `offset`.
+    pub fn search_page(&self, chain: &[Block], filter: &TransactionFilter, offset: usize, limit: usize) -> IcnResult<TransactionPage> {
+        if limit == 0 || limit > MAX_SEARCH_LIMIT {
+            return Err(IcnError::Validation(format!("Search limit must be between 1 and {}", MAX_SEARCH_LIMIT)));
+        }
+        let locations = self.search(chain, filter)?;
+        let transactions = locations.iter()
+            .skip(offset)
+            .take(limit)
+            .map(|&(block_index, position)| IndexedTransaction {
+                block_index,
+                transaction: chain[block_index as usize].transactions[position].clone(),
+            })
+            .collect();
+        Ok(TransactionPage { total: locations.len(), offset, transactions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(from: &str, to: &str, amount: f64, currency_type: CurrencyType, timestamp: i64) -> Transaction {
+        Transaction::new(from.to_string(), to.to_string(), amount, currency_type, timestamp)
+    }
+
+    #[test]
+    fn test_search_combines_filters() {
+        let genesis = Block::new(0, Vec::new(), "0");
+        let first = Block::new(1, vec![
+            transaction("Alice", "Bob", 10.0, CurrencyType::BasicNeeds, 1_000),
+            transaction("Bob", "Carol", 25.0, CurrencyType::Education, 1_100),
+        ], &genesis.hash);
+        let second = Block::new(2, vec![
+            transaction("Alice", "Carol", 40.0, CurrencyType::BasicNeeds, 5_000),
+            transaction("Carol", "Alice", 5.0, CurrencyType::BasicNeeds, 9_000),
+        ], &first.hash);
+        let chain = vec![genesis, first, second];
+
+        let mut index = TransactionIndex::default();
+        for block in &chain {
+            index.index_block(block);
+        }
+
+        let alice = TransactionFilter { address: Some("Alice".to_string()), ..Default::default() };
+        assert_eq!(index.search(&chain, &alice).unwrap(), vec![(1, 0), (2, 0), (2, 1)]);
+
+        let filter = TransactionFilter {
+            address: Some("Alice".to_string()),
+            currency_type: Some(CurrencyType::BasicNeeds),
+            min_amount: Some(8.0),
+            from_time: Some(1_000),
+            to_time: Some(5_000),
+            ..Default::default()
+        };
+        assert_eq!(index.search(&chain, &filter).unwrap(), vec![(1, 0)]);
+
+        let page = index.search_page(&chain, &TransactionFilter::default(), 1, 2).unwrap();
+        assert_eq!(page.total, 4);
+        assert_eq!(page.transactions[0].transaction.from, "Bob");
+        assert_eq!(page.transactions[1].block_index, 2);
+
+        let bad = TransactionFilter { min_amount: Some(5.0), max_amount: Some(1.0), ..Default::default() };
+        assert!(index.search(&chain, &bad).is_err());
+        assert!(index.search_page(&chain, &alice, 0, 0).is_err());
+
+        index.rebuild(&chain[..2]);
+        assert_eq!(index.search(&chain, &alice).unwrap(), vec![(1, 0)]);
+    }
+}
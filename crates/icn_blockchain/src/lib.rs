@@ -1,11 +1,24 @@
 // File: crates/icn_blockchain/src/blockchain.rs
 
 use chrono::{DateTime, Utc};
-use icn_common::{IcnResult, IcnError, CurrencyType};
+use icn_common::{system_clock, IcnResult, IcnError, BlockHeader, CurrencyType, DoubleSignEvidence, SharedClock, SizeLimits, evidence_root};
+use icn_common::merkle::{merkle_root, transaction_leaf};
 use icn_currency::CurrencySystem;
 use serde::{Serialize, Deserialize};
-use sha2::{Sha256, Digest};
-use std::collections::HashMap;
+use std::ops::{Bound, RangeBounds};
+
+pub mod dag;
+pub mod index;
+
+pub use icn_common::Transaction;
+pub use dag::{BlockDag, ConflictReason, DagBlock, DagConflict, DAG_CONFIRMATION_DEPTH, MAX_DAG_PARENTS};
+pub use index::{IndexedTransaction, TransactionFilter, TransactionIndex, TransactionPage, MAX_SEARCH_LIMIT};
+
+/// Upper bound on the encoding of a block without its transactions.
+const BLOCK_OVERHEAD_BYTES: usize = 1024;
+
+/// Pending transactions the mempool holds before turning new ones away.
+pub const DEFAULT_MEMPOOL_CAPACITY: usize = 10_000;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
@@ -16,6 +29,20 @@ pub struct Block {
     pub hash: String,
     pub nonce: u64,
     pub merkle_root: String,
+    /// Commitment to every contract's storage after this block.
+    #[serde(default)]
+    pub state_root: String,
+    /// Validators caught approving conflicting blocks, recorded so every
+    /// node slashes them.
+    #[serde(default)]
+    pub evidence: Vec<DoubleSignEvidence>,
+    #[serde(default)]
+    pub evidence_root: String,
+    /// The validator that produced the block, if it went through rounds.
+    #[serde(default)]
+    pub producer: String,
+    #[serde(default)]
+    pub producer_signature: Vec<u8>,
 }
 
 impl Block {
@@ -28,99 +55,171 @@ impl Block {
             hash: String::new(),
             nonce: 0,
             merkle_root: String::new(),
+            state_root: String::new(),
+            evidence: Vec::new(),
+            evidence_root: String::new(),
+            producer: String::new(),
+            producer_signature: Vec::new(),
         };
         block.merkle_root = block.calculate_merkle_root();
         block.hash = block.calculate_hash();
         block
     }
 
+    pub fn with_timestamp(mut self, timestamp: i64) -> Self {
+        self.timestamp = timestamp;
+        self.hash = self.calculate_hash();
+        self
+    }
+
+    pub fn with_state_root(mut self, state_root: String) -> Self {
+        self.state_root = state_root;
+        self.hash = self.calculate_hash();
+        self
+    }
+
+    pub fn with_evidence(mut self, evidence: Vec<DoubleSignEvidence>) -> Self {
+        self.evidence_root = evidence_root(&evidence);
+        self.evidence = evidence;
+        self.hash = self.calculate_hash();
+        self
+    }
+
+    /// Hashes the block's header, so light clients holding only headers
+    /// arrive at the same hash.
     pub fn calculate_hash(&self) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(self.index.to_string());
-        hasher.update(&self.timestamp.to_string());
-        hasher.update(&self.merkle_root);
-        hasher.update(&self.previous_hash);
-        hasher.update(&self.nonce.to_string());
-        format!("{:x}", hasher.finalize())
+        self.header().calculate_hash()
     }
 
     pub fn calculate_merkle_root(&self) -> String {
-        let transaction_hashes: Vec<String> = self.transactions
-            .iter()
-            .map(|tx| {
-                let mut hasher = Sha256::new();
-                hasher.update(serde_json::to_string(tx).unwrap().as_bytes());
-                format!("{:x}", hasher.finalize())
-            })
-            .collect();
-
-        if transaction_hashes.is_empty() {
-            return String::from("0000000000000000000000000000000000000000000000000000000000000000");
-        }
-
-        let mut merkle_tree = transaction_hashes;
-        while merkle_tree.len() > 1 {
-            let mut new_level = Vec::new();
-            for chunk in merkle_tree.chunks(2) {
-                let mut hasher = Sha256::new();
-                hasher.update(chunk[0].as_bytes());
-                if chunk.len() > 1 {
-                    hasher.update(chunk[1].as_bytes());
-                } else {
-                    hasher.update(chunk[0].as_bytes());
-                }
-                new_level.push(format!("{:x}", hasher.finalize()));
-            }
-            merkle_tree = new_level;
-        }
+        merkle_root(&self.transactions.iter().map(transaction_leaf).collect::<Vec<_>>())
+    }
 
-        merkle_tree[0].clone()
+    /// The block without its transactions, for light clients.
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            index: self.index,
+            timestamp: self.timestamp,
+            previous_hash: self.previous_hash.clone(),
+            hash: self.hash.clone(),
+            nonce: self.nonce,
+            merkle_root: self.merkle_root.clone(),
+            state_root: self.state_root.clone(),
+            evidence_root: self.evidence_root.clone(),
+            producer: self.producer.clone(),
+            producer_signature: self.producer_signature.clone(),
+        }
     }
 
     pub fn mine(&mut self, difficulty: usize) {
         let target = "0".repeat(difficulty);
-        while &self.hash[..difficulty] != target {
+        while self.hash[..difficulty] != target {
             self.nonce += 1;
             self.hash = self.calculate_hash();
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Transaction {
-    pub from: String,
-    pub to: String,
-    pub amount: f64,
-    pub currency_type: CurrencyType,
-    pub timestamp: i64,
-    pub signature: Option<Vec<u8>>,
-}
-
 pub struct Blockchain {
     pub chain: Vec<Block>,
     pub pending_transactions: Vec<Transaction>,
     pub difficulty: usize,
     currency_system: CurrencySystem,
+    limits: SizeLimits,
+    mempool_capacity: usize,
+    index: TransactionIndex,
+    pending_evidence: Vec<DoubleSignEvidence>,
+    clock: SharedClock,
+    /// Set once experimental DAG mode is enabled.
+    dag: Option<BlockDag>,
 }
 
 impl Blockchain {
     pub fn new(difficulty: usize) -> Self {
-        let mut blockchain = Blockchain {
+        let mut blockchain = Self::empty(difficulty);
+        let genesis_block = Block::new(0, Vec::new(), "0");
+        blockchain.push_genesis_block(genesis_block, 0);
+        blockchain
+    }
+
+    /// Starts a chain whose genesis block commits to the hash of the
+    /// network's genesis configuration.
+    pub fn with_genesis(difficulty: usize, timestamp: i64, genesis_config_hash: &str) -> Self {
+        let mut blockchain = Self::empty(difficulty);
+        let mut genesis_block = Block::new(0, Vec::new(), "0");
+        genesis_block.merkle_root = genesis_config_hash.to_string();
+        blockchain.push_genesis_block(genesis_block, timestamp);
+        blockchain
+    }
+
+    fn empty(difficulty: usize) -> Self {
+        Blockchain {
             chain: Vec::new(),
             pending_transactions: Vec::new(),
             difficulty,
             currency_system: CurrencySystem::new(),
-        };
-        blockchain.create_genesis_block();
-        blockchain
+            limits: SizeLimits::default(),
+            mempool_capacity: DEFAULT_MEMPOOL_CAPACITY,
+            index: TransactionIndex::default(),
+            pending_evidence: Vec::new(),
+            clock: system_clock(),
+            dag: None,
+        }
     }
 
-    fn create_genesis_block(&mut self) {
-        let genesis_block = Block::new(0, Vec::new(), "0");
+    /// Dates mined blocks and judges future timestamps against `clock`,
+    /// which the chain's currency system shares.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.currency_system = std::mem::replace(&mut self.currency_system, CurrencySystem::new()).with_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
+
+    fn push_genesis_block(&mut self, mut genesis_block: Block, timestamp: i64) {
+        // The genesis hash identifies the chain during peer handshakes, so it
+        // must not depend on when the node started.
+        genesis_block.timestamp = timestamp;
+        genesis_block.hash = genesis_block.calculate_hash();
         self.chain.push(genesis_block);
     }
 
+    pub fn set_limits(&mut self, limits: SizeLimits) -> IcnResult<()> {
+        limits.validate()?;
+        self.limits = limits;
+        Ok(())
+    }
+
+    pub fn limits(&self) -> &SizeLimits {
+        &self.limits
+    }
+
+    /// Caps the pending transactions accepted from now on. Transactions
+    /// already queued beyond a lowered cap stay queued.
+    pub fn set_mempool_capacity(&mut self, capacity: usize) -> IcnResult<()> {
+        if capacity == 0 {
+            return Err(IcnError::Config("Mempool capacity must be greater than zero".into()));
+        }
+        self.mempool_capacity = capacity;
+        Ok(())
+    }
+
+    pub fn mempool_capacity(&self) -> usize {
+        self.mempool_capacity
+    }
+
+    /// The current time on the chain's clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
     pub fn add_transaction(&mut self, transaction: Transaction) -> IcnResult<()> {
+        self.limits.check_transaction(&transaction)?;
+        if transaction.is_expired(self.clock.now().timestamp()) {
+            return Err(IcnError::Blockchain("Transaction has expired".into()));
+        }
+        if self.pending_transactions.len() >= self.mempool_capacity {
+            return Err(IcnError::Blockchain(format!("Mempool is full at {} transactions", self.mempool_capacity)));
+        }
         if self.validate_transaction(&transaction)? {
             self.pending_transactions.push(transaction);
             Ok(())
@@ -129,22 +228,76 @@ impl Blockchain {
         }
     }
 
+    /// Removes up to `max_transactions` from the front of the mempool so they can be packed into a block.
+    /// The batch also stays within the block size limits.
+    pub fn take_pending_batch(&mut self, max_transactions: usize) -> Vec<Transaction> {
+        let max_transactions = max_transactions.min(self.limits.max_transactions_per_block);
+        // Leave room for the block header around the transactions
+        let mut remaining_bytes = self.limits.max_block_bytes.saturating_sub(BLOCK_OVERHEAD_BYTES);
+        let mut count = 0;
+        for transaction in self.pending_transactions.iter().take(max_transactions) {
+            let size = icn_common::limits::encoded_size(transaction).unwrap_or(usize::MAX).saturating_add(1);
+            if size > remaining_bytes {
+                break;
+            }
+            remaining_bytes -= size;
+            count += 1;
+        }
+        self.pending_transactions.drain(..count).collect()
+    }
+
+    /// Removes pending transactions whose validity has ended and returns
+    /// them, oldest first.
+    pub fn evict_expired(&mut self) -> Vec<Transaction> {
+        let now = self.clock.now().timestamp();
+        let (expired, pending) = std::mem::take(&mut self.pending_transactions)
+            .into_iter()
+            .partition(|transaction| transaction.is_expired(now));
+        self.pending_transactions = pending;
+        expired
+    }
+
+    /// Puts transactions from a rejected block back at the front of the mempool.
+    pub fn requeue_transactions(&mut self, transactions: Vec<Transaction>) {
+        self.pending_transactions.splice(0..0, transactions);
+    }
+
+    /// Queues evidence for the next block, unless it is already queued or
+    /// on chain.
+    pub fn add_evidence(&mut self, evidence: DoubleSignEvidence) -> bool {
+        let id = evidence.id();
+        let known = self.pending_evidence.iter()
+            .chain(self.chain.iter().flat_map(|block| &block.evidence))
+            .any(|e| e.id() == id);
+        if !known {
+            self.pending_evidence.push(evidence);
+        }
+        !known
+    }
+
+    pub fn take_pending_evidence(&mut self) -> Vec<DoubleSignEvidence> {
+        std::mem::take(&mut self.pending_evidence)
+    }
+
+    /// Puts evidence from a rejected block back in the queue.
+    pub fn requeue_evidence(&mut self, evidence: Vec<DoubleSignEvidence>) {
+        self.pending_evidence.splice(0..0, evidence);
+    }
+
+    pub fn pending_transaction_count(&self) -> usize {
+        self.pending_transactions.len()
+    }
+
     pub fn mine_pending_transactions(&mut self, miner_address: &str) -> IcnResult<()> {
-        let reward_transaction = Transaction {
-            from: "Network".to_string(),
-            to: miner_address.to_string(),
-            amount: 1.0, // Mining reward
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let now = self.clock.now().timestamp();
+        let reward_transaction = Transaction::new("Network".to_string(), miner_address.to_string(), 1.0, CurrencyType::BasicNeeds, now);
         self.pending_transactions.push(reward_transaction);
 
         let new_block = Block::new(
             self.chain.len() as u64,
             self.pending_transactions.clone(),
             &self.get_latest_block().hash,
-        );
+        ).with_timestamp(now);
         self.add_block(new_block)?;
 
         self.pending_transactions.clear();
@@ -153,13 +306,21 @@ impl Blockchain {
 
     pub fn add_block(&mut self, mut block: Block) -> IcnResult<()> {
         // Check that the block's timestamp is not in the future
-        let current_time = Utc::now().timestamp();
+        let current_time = self.clock.now().timestamp();
         if block.timestamp > current_time {
             return Err(IcnError::Blockchain("Block timestamp is in the future".into()));
         }
 
+        self.limits.check_block(&block, block.transactions.len())?;
+        for transaction in &block.transactions {
+            self.limits.check_transaction(transaction)?;
+        }
+
         // Verify all transactions in the block
         for transaction in &block.transactions {
+            if transaction.is_expired(block.timestamp) {
+                return Err(IcnError::Blockchain(format!("Transaction {} expired before the block", transaction.hash())));
+            }
             if !self.validate_transaction(transaction)? {
                 return Err(IcnError::Blockchain("Invalid transaction in block".into()));
             }
@@ -170,8 +331,12 @@ impl Blockchain {
         if block.merkle_root != calculated_merkle_root {
             return Err(IcnError::Blockchain("Invalid Merkle root".into()));
         }
+        if block.evidence_root != evidence_root(&block.evidence) {
+            return Err(IcnError::Blockchain("Invalid evidence root".into()));
+        }
 
         block.mine(self.difficulty);
+        self.index.index_block(&block);
         self.chain.push(block);
         self.update_balances()?;
         Ok(())
@@ -219,13 +384,13 @@ impl Blockchain {
             return Ok(true); // Allow mining rewards
         }
 
-        let sender_balance = self.currency_system.get_balance(&transaction.from, &transaction.currency_type)?;
+        let sender_balance = self.currency_system.get_balance_amount(&transaction.from, &transaction.currency_type)?;
         if sender_balance < transaction.amount {
             return Ok(false);
         }
 
         // Additional validation logic can be added here (e.g., signature verification)
-        if let Some(signature) = &transaction.signature {
+        if let Some(_signature) = &transaction.signature {
             // Implement signature verification logic here
             // For now, we'll assume all signatures are valid
             // In a real implementation, you would verify the signature against the transaction data
@@ -236,24 +401,47 @@ impl Blockchain {
     }
 
     fn update_balances(&mut self) -> IcnResult<()> {
-        for transaction in &self.get_latest_block().transactions {
-            self.currency_system.process_transaction(transaction)?;
+        let transactions = self.get_latest_block().transactions.clone();
+        for transaction in &transactions {
+            self.process_transaction(transaction)?;
         }
         Ok(())
     }
 
+    /// Applies a transaction to balances. Transactions from the network,
+    /// such as mining rewards, create the currency they pay.
+    fn process_transaction(&mut self, transaction: &Transaction) -> IcnResult<()> {
+        if transaction.from == "Network" {
+            return self.currency_system.allocate(&transaction.to, &transaction.currency_type, transaction.amount.to_f64());
+        }
+        self.currency_system.process_transaction(transaction)
+    }
+
+    /// Credits a genesis allocation. Only allowed before any block is added.
+    pub fn allocate_genesis(&mut self, address: &str, currency_type: &CurrencyType, amount: f64) -> IcnResult<()> {
+        if self.chain.len() != 1 {
+            return Err(IcnError::Blockchain("Genesis allocations must be made before the first block".into()));
+        }
+        self.currency_system.allocate(address, currency_type, amount)
+    }
+
     pub fn get_balance(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<f64> {
         self.currency_system.get_balance(address, currency_type)
     }
 
     pub fn get_transactions(&self, address: &str) -> Vec<&Transaction> {
-        self.chain
-            .iter()
-            .flat_map(|block| block.transactions.iter())
-            .filter(|tx| tx.from == address || tx.to == address)
+        self.index
+            .locations_for_address(address)
+            .map(|&(block_index, position)| &self.chain[block_index as usize].transactions[position])
             .collect()
     }
 
+    /// Committed transactions matching `filter`, in chain order, paginated
+    /// by `offset` and `limit`.
+    pub fn search_transactions(&self, filter: &TransactionFilter, offset: usize, limit: usize) -> IcnResult<TransactionPage> {
+        self.index.search_page(&self.chain, filter, offset, limit)
+    }
+
     pub fn get_block_by_hash(&self, hash: &str) -> Option<&Block> {
         self.chain.iter().find(|block| block.hash == hash)
     }
@@ -262,15 +450,34 @@ impl Blockchain {
         self.chain.get(index as usize)
     }
 
+    /// Blocks whose index is in `range`, borrowed in chain order. Out of
+    /// range bounds are clamped to the chain, so `iter_blocks(10..)` on a
+    /// shorter chain is simply empty.
+    pub fn iter_blocks(&self, range: impl RangeBounds<u64>) -> std::slice::Iter<'_, Block> {
+        let len = self.chain.len();
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start as usize,
+            Bound::Excluded(&start) => (start as usize).saturating_add(1),
+            Bound::Unbounded => 0,
+        }.min(len);
+        let end = match range.end_bound() {
+            Bound::Included(&end) => (end as usize).saturating_add(1),
+            Bound::Excluded(&end) => end as usize,
+            Bound::Unbounded => len,
+        }.clamp(start, len);
+        self.chain[start..end].iter()
+    }
+
     pub fn handle_fork(&mut self, new_chain: Vec<Block>) -> IcnResult<()> {
         if new_chain.len() <= self.chain.len() || !self.is_valid_chain(&new_chain) {
             return Err(IcnError::Blockchain("Invalid fork chain".into()));
         }
 
-        let fork_point = self.find_fork_point(&new_chain)?;
+        let fork_point = self.find_fork_point(&new_chain);
 
         // Roll back transactions from the current chain
-        for block in self.chain.iter().rev().take(self.chain.len() - fork_point) {
+        let abandoned: Vec<Block> = self.chain.drain(fork_point..).rev().collect();
+        for block in &abandoned {
             self.rollback_transactions(block)?;
         }
 
@@ -281,6 +488,7 @@ impl Blockchain {
 
         // Replace the current chain with the new chain
         self.chain = new_chain;
+        self.index.rebuild(&self.chain);
 
         Ok(())
     }
@@ -309,25 +517,29 @@ impl Blockchain {
         true
     }
 
-    fn find_fork_point(&self, new_chain: &[Block]) -> IcnResult<usize> {
+    fn find_fork_point(&self, new_chain: &[Block]) -> usize {
         for (i, (old_block, new_block)) in self.chain.iter().zip(new_chain.iter()).enumerate() {
             if old_block.hash != new_block.hash {
-                return Ok(i);
+                return i;
             }
         }
-        Err(IcnError::Blockchain("No fork point found".into()))
+        // The new chain extends ours
+        self.chain.len().min(new_chain.len())
     }
 
     fn rollback_transactions(&mut self, block: &Block) -> IcnResult<()> {
-        for transaction in &block.transactions {
-            self.currency_system.reverse_transaction(transaction)?;
+        for transaction in block.transactions.iter().rev() {
+            self.currency_system.transfer_amount(&transaction.to, &transaction.from, &transaction.currency_type, transaction.amount)?;
+            if transaction.from == "Network" {
+                self.currency_system.burn(&transaction.currency_type, transaction.amount.to_f64())?;
+            }
         }
         Ok(())
     }
 
     fn apply_transactions(&mut self, block: &Block) -> IcnResult<()> {
         for transaction in &block.transactions {
-            self.currency_system.process_transaction(transaction)?;
+            self.process_transaction(transaction)?;
         }
         Ok(())
     }
@@ -344,20 +556,71 @@ mod tests {
         assert_eq!(blockchain.chain[0].index, 0);
     }
 
+    #[test]
+    fn test_state_root_is_hashed() {
+        let block = Block::new(1, Vec::new(), "previous");
+        let committed = block.clone().with_state_root("abc".to_string());
+        assert_eq!(committed.state_root, "abc");
+        assert_ne!(committed.hash, block.hash);
+        assert_eq!(committed.hash, committed.calculate_hash());
+    }
+
+    #[test]
+    fn test_future_blocks_are_judged_by_the_chain_clock() {
+        use icn_common::{Clock, ManualClock};
+        let clock = ManualClock::new(Utc::now());
+        let mut blockchain = Blockchain::new(2).with_clock(clock.shared());
+        let later = clock.now().timestamp() + 3600;
+        let block = Block::new(1, Vec::new(), &blockchain.get_latest_block().hash).with_timestamp(later);
+        assert!(blockchain.add_block(block.clone()).is_err());
+
+        clock.advance(chrono::Duration::hours(1));
+        blockchain.add_block(block).unwrap();
+        assert_eq!(blockchain.get_latest_block().timestamp, later);
+    }
+
+    #[test]
+    fn test_expired_transactions_are_refused_and_evicted() {
+        use icn_common::{Clock, ManualClock};
+        let clock = ManualClock::new(Utc::now());
+        let mut blockchain = Blockchain::new(2).with_clock(clock.shared());
+        let now = clock.now().timestamp();
+        let transfer = |to: &str, valid_until: i64| {
+            Transaction::new("Network".to_string(), to.to_string(), 1.0, CurrencyType::BasicNeeds, now).with_valid_until(valid_until)
+        };
+        assert!(blockchain.add_transaction(transfer("Alice", now - 1)).is_err());
+        blockchain.add_transaction(transfer("Alice", now + 60)).unwrap();
+        blockchain.add_transaction(transfer("Bob", now + 3600)).unwrap();
+
+        clock.advance(chrono::Duration::minutes(2));
+        let expired = blockchain.evict_expired();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].to, "Alice");
+        assert_eq!(blockchain.pending_transaction_count(), 1);
+
+        // Nor may a block carry a transaction past its expiry
+        let block = Block::new(1, expired, &blockchain.get_latest_block().hash).with_timestamp(clock.now().timestamp());
+        assert!(blockchain.add_block(block).is_err());
+    }
+
+    #[test]
+    fn test_genesis_commits_to_config_hash() {
+        let mut blockchain = Blockchain::with_genesis(2, 1_700_000_000, "abc123");
+        assert_eq!(blockchain.chain[0].merkle_root, "abc123");
+        assert_eq!(blockchain.chain[0].hash, blockchain.chain[0].calculate_hash());
+        assert_ne!(blockchain.chain[0].hash, Blockchain::with_genesis(2, 1_700_000_000, "def456").chain[0].hash);
+
+        blockchain.allocate_genesis("Alice", &CurrencyType::BasicNeeds, 50.0).unwrap();
+        assert_eq!(blockchain.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 50.0);
+    }
+
     #[test]
     fn test_add_transaction_and_mine() {
         let mut blockchain = Blockchain::new(2);
-        let transaction = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
         // Initialize Alice's balance
-        blockchain.currency_system.mint("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        blockchain.currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
 
         assert!(blockchain.add_transaction(transaction).is_ok());
         assert_eq!(blockchain.pending_transactions.len(), 1);
@@ -372,19 +635,73 @@ mod tests {
         assert_eq!(blockchain.get_balance("Miner", &CurrencyType::BasicNeeds).unwrap(), 1.0);
     }
 
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_iter_blocks() {
+        let mut blockchain = Blockchain::new(2);
+        for index in 1..5 {
+            let previous_hash = blockchain.get_latest_block().hash.clone();
+            blockchain.chain.push(Block::new(index, Vec::new(), &previous_hash));
+        }
+
+        let indexes = |blocks: std::slice::Iter<'_, Block>| blocks.map(|block| block.index).collect::<Vec<_>>();
+        assert_eq!(indexes(blockchain.iter_blocks(..)), vec![0, 1, 2, 3, 4]);
+        assert_eq!(indexes(blockchain.iter_blocks(1..3)), vec![1, 2]);
+        assert_eq!(indexes(blockchain.iter_blocks(3..=10)), vec![3, 4]);
+        assert!(blockchain.iter_blocks(7..).next().is_none());
+        assert!(blockchain.iter_blocks(3..1).next().is_none());
+    }
+
+    #[test]
+    fn test_take_and_requeue_pending_batch() {
+        let mut blockchain = Blockchain::new(2);
+        for i in 0..5 {
+            blockchain.add_transaction(Transaction::new("Network".to_string(), format!("Member{}", i), 1.0, CurrencyType::BasicNeeds, Utc::now().timestamp())).unwrap();
+        }
+
+        let batch = blockchain.take_pending_batch(3);
+        assert_eq!(batch.len(), 3);
+        assert_eq!(blockchain.pending_transaction_count(), 2);
+
+        blockchain.requeue_transactions(batch);
+        assert_eq!(blockchain.pending_transaction_count(), 5);
+        assert_eq!(blockchain.pending_transactions[0].to, "Member0");
+        assert_eq!(blockchain.take_pending_batch(10).len(), 5);
+    }
+
+    #[test]
+    fn test_size_limits() {
+        let mut blockchain = Blockchain::new(2);
+        blockchain.set_limits(SizeLimits {
+            max_transaction_bytes: 256,
+            max_transactions_per_block: 3,
+            max_block_bytes: 4096,
+        }).unwrap();
+        let transaction = |to: String| Transaction::new("Network".to_string(), to, 1.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
+
+        assert!(blockchain.add_transaction(transaction("x".repeat(300))).is_err());
+        for i in 0..5 {
+            blockchain.add_transaction(transaction(format!("Member{}", i))).unwrap();
+        }
+        assert_eq!(blockchain.take_pending_batch(10).len(), 3);
+
+        // A full mempool turns new transactions away
+        blockchain.set_mempool_capacity(3).unwrap();
+        blockchain.add_transaction(transaction("Member5".to_string())).unwrap();
+        assert!(blockchain.add_transaction(transaction("Member6".to_string())).is_err());
+        assert!(blockchain.set_mempool_capacity(0).is_err());
+
+        let oversized = Block::new(1, (0..4).map(|i| transaction(format!("Member{}", i))).collect(), &blockchain.get_latest_block().hash);
+        assert!(blockchain.add_block(oversized).is_err());
+        assert!(blockchain.set_limits(SizeLimits { max_block_bytes: 100, ..SizeLimits::default() }).is_err());
+    }
+
     #[test]
     fn test_blockchain_validity() {
         let mut blockchain = Blockchain::new(2);
-        let transaction = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
-        blockchain.currency_system.mint("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        blockchain.currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
 
         assert!(blockchain.add_transaction(transaction).is_ok());
         assert!(blockchain.mine_pending_transactions("Miner").is_ok());
@@ -392,32 +709,18 @@ mod tests {
         assert!(blockchain.is_chain_valid());
 
         // Tamper with a block to test invalid chain
-        blockchain.chain[1].transactions[0].amount = 100.0;
+        blockchain.chain[1].transactions[0].amount = 100.0.into();
         assert!(!blockchain.is_chain_valid());
     }
 
     #[test]
     fn test_get_transactions() {
         let mut blockchain = Blockchain::new(2);
-        let transaction1 = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
-        let transaction2 = Transaction {
-            from: "Bob".to_string(),
-            to: "Charlie".to_string(),
-            amount: 25.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction1 = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
+        let transaction2 = Transaction::new("Bob".to_string(), "Charlie".to_string(), 25.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
-        blockchain.currency_system.mint("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
-        blockchain.currency_system.mint("Bob", &CurrencyType::BasicNeeds, 50.0).unwrap();
+        blockchain.currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        blockchain.currency_system.allocate("Bob", &CurrencyType::BasicNeeds, 50.0).unwrap();
 
         assert!(blockchain.add_transaction(transaction1).is_ok());
         assert!(blockchain.add_transaction(transaction2).is_ok());
@@ -434,16 +737,9 @@ mod tests {
     #[test]
     fn test_get_block_by_hash_and_index() {
         let mut blockchain = Blockchain::new(2);
-        let transaction = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
-        blockchain.currency_system.mint("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        blockchain.currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
         assert!(blockchain.add_transaction(transaction).is_ok());
         assert!(blockchain.mine_pending_transactions("Miner").is_ok());
 
@@ -462,38 +758,19 @@ mod tests {
         let mut blockchain = Blockchain::new(2);
         
         // Create the original chain
-        let transaction1 = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction1 = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
-        blockchain.currency_system.mint("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        blockchain.currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        blockchain.currency_system.allocate("Charlie", &CurrencyType::BasicNeeds, 30.0).unwrap();
+        blockchain.currency_system.allocate("Eve", &CurrencyType::BasicNeeds, 20.0).unwrap();
         assert!(blockchain.add_transaction(transaction1).is_ok());
         assert!(blockchain.mine_pending_transactions("Miner").is_ok());
 
         // Create a forked chain
         let mut forked_chain = blockchain.chain.clone();
-        let transaction2 = Transaction {
-            from: "Charlie".to_string(),
-            to: "David".to_string(),
-            amount: 30.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction2 = Transaction::new("Charlie".to_string(), "David".to_string(), 30.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
-        let transaction3 = Transaction {
-            from: "Eve".to_string(),
-            to: "Frank".to_string(),
-            amount: 20.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: Utc::now().timestamp(),
-            signature: None,
-        };
+        let transaction3 = Transaction::new("Eve".to_string(), "Frank".to_string(), 20.0, CurrencyType::BasicNeeds, Utc::now().timestamp());
 
         let mut new_block = Block::new(2, vec![transaction2, transaction3], &forked_chain.last().unwrap().hash);
         new_block.mine(blockchain.difficulty);
@@ -2,6 +2,7 @@
 
 use icn_common::error::{IcnError, IcnResult};
 use crate::{Transaction, Blockchain};
+use icn_currency::FeeMarket;
 
 /// A struct for validating transactions.
 pub struct TransactionValidator;
@@ -13,16 +14,17 @@ impl TransactionValidator {
     ///
     /// * `transaction` - The transaction to validate.
     /// * `blockchain` - The blockchain context.
+    /// * `fee_market` - Quotes the fee the sender must cover on top of the amount.
     ///
     /// # Returns
     ///
     /// `IcnResult<()>` indicating whether the transaction is valid.
-    pub fn validate_transaction(transaction: &Transaction, blockchain: &Blockchain) -> IcnResult<()> {
+    pub fn validate_transaction(transaction: &Transaction, blockchain: &Blockchain, fee_market: &FeeMarket) -> IcnResult<()> {
         if Self::is_double_spend(transaction, blockchain)? {
             return Err(IcnError::Currency("Double spend detected".to_string()));
         }
         Self::validate_currency_and_amount(transaction)?;
-        Self::check_sufficient_balance(transaction, blockchain)?;
+        Self::check_sufficient_balance(transaction, blockchain, fee_market)?;
         Self::validate_signature(transaction)?;
         Ok(())
     }
@@ -45,9 +47,11 @@ impl TransactionValidator {
         Ok(())
     }
 
-    fn check_sufficient_balance(transaction: &Transaction, blockchain: &Blockchain) -> IcnResult<()> {
+    fn check_sufficient_balance(transaction: &Transaction, blockchain: &Blockchain, fee_market: &FeeMarket) -> IcnResult<()> {
         let balance = blockchain.get_balance(&transaction.from, &transaction.currency_type)?;
-        if balance < transaction.amount + transaction.get_fee() {
+        let needed = transaction.amount.checked_add(fee_market.estimate(transaction.amount))
+            .ok_or_else(|| IcnError::Currency("Amount and fee overflow".to_string()))?;
+        if balance < needed.to_f64() {
             return Err(IcnError::Currency("Insufficient balance".to_string()));
         }
         Ok(())
@@ -91,7 +95,7 @@ mod tests {
         blockchain.add_transaction(create_signed_transaction("Genesis", "Alice", 100.0)).unwrap();
         blockchain.create_block().unwrap();
 
-        assert!(TransactionValidator::validate_transaction(&tx, &blockchain).is_ok());
+        assert!(TransactionValidator::validate_transaction(&tx, &blockchain, &FeeMarket::default()).is_ok());
     }
 
     #[test]
@@ -102,7 +106,7 @@ mod tests {
         blockchain.add_transaction(create_signed_transaction("Genesis", "Alice", 100.0)).unwrap();
         blockchain.create_block().unwrap();
 
-        assert!(TransactionValidator::validate_transaction(&tx, &blockchain).is_err());
+        assert!(TransactionValidator::validate_transaction(&tx, &blockchain, &FeeMarket::default()).is_err());
     }
 
     #[test]
@@ -116,6 +120,6 @@ mod tests {
         blockchain.add_transaction(tx.clone()).unwrap();
         blockchain.create_block().unwrap();
 
-        assert!(TransactionValidator::validate_transaction(&tx, &blockchain).is_err());
+        assert!(TransactionValidator::validate_transaction(&tx, &blockchain, &FeeMarket::default()).is_err());
     }
 }
@@ -0,0 +1,530 @@
+// File: crates/icn_bridge/src/lib.rs
+
+//! Pegs assets held on external chains to asset tokens on the network.
+//!
+//! Relayers watch the external chain. When enough of them attest to a
+//! deposit into the bridge's custody there, the pegged token is minted to
+//! the recipient here. Holders burn pegged tokens to have the asset
+//! released back on the external chain, after a challenge period in which
+//! any relayer can stop a withdrawal that shouldn't go through. The node
+//! checks that withdrawals are signed by the holder before they get here.
+
+use icn_common::{IcnResult, IcnError, Amount, CurrencyType};
+use icn_currency::{AssetTokenTerms, CurrencySystem, TransferRestriction};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+
+/// Account that issues pegged tokens and holds them while withdrawals wait
+/// out their challenge period.
+pub const BRIDGE_ACCOUNT: &str = "bridge";
+
+/// How long a withdrawal can be challenged, unless configured otherwise.
+pub const DEFAULT_CHALLENGE_PERIOD_SECS: i64 = 24 * 60 * 60;
+
+/// An asset on an external chain to be represented on the network.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PegDefinition {
+    pub chain: String,
+    /// The asset's address or symbol on that chain.
+    pub external_asset: String,
+    pub name: String,
+}
+
+/// Who may attest to deposits and what the bridge carries.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BridgeConfig {
+    /// Relayer DIDs, of the form `did:icn:<hex public key>`.
+    pub relayers: BTreeSet<String>,
+    /// Distinct relayer attestations a deposit needs before it is minted.
+    pub threshold: usize,
+    pub challenge_period_secs: i64,
+    pub pegs: Vec<PegDefinition>,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        BridgeConfig {
+            relayers: BTreeSet::new(),
+            threshold: 1,
+            challenge_period_secs: DEFAULT_CHALLENGE_PERIOD_SECS,
+            pegs: Vec::new(),
+        }
+    }
+}
+
+/// An external asset and the asset token that stands for it here.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeggedAsset {
+    pub chain: String,
+    pub external_asset: String,
+    pub asset_id: String,
+}
+
+/// A deposit into the bridge's custody on an external chain, as seen by a
+/// relayer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExternalDeposit {
+    pub chain: String,
+    pub external_asset: String,
+    /// The deposit transaction on the external chain. Each is minted once.
+    pub tx_hash: String,
+    pub amount: Amount,
+    /// Account on this network to mint the pegged token to.
+    pub recipient: String,
+}
+
+impl ExternalDeposit {
+    pub fn id(&self) -> String {
+        format!("{}:{}", self.chain, self.tx_hash)
+    }
+
+    /// What relayers sign to attest to the deposit.
+    pub fn message(&self) -> Vec<u8> {
+        format!(
+            "icn-bridge-deposit:{}:{}:{}:{}:{}",
+            self.chain, self.external_asset, self.tx_hash, self.amount, self.recipient,
+        ).into_bytes()
+    }
+
+    /// Hex SHA-256 of `message`, telling apart versions of one deposit
+    /// that relayers disagree about.
+    pub fn message_hash(&self) -> String {
+        hex::encode(Sha256::digest(&self.message()))
+    }
+}
+
+/// A relayer's signature over `ExternalDeposit::message`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attestation {
+    pub relayer: String,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DepositStatus {
+    /// Waiting for more relayers to attest.
+    Pending,
+    /// `variant` is the message hash of the version that was minted.
+    Minted { asset_id: String, variant: String, minted_at: DateTime<Utc> },
+}
+
+/// One version of a deposit and the relayers that attested to it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DepositVariant {
+    pub deposit: ExternalDeposit,
+    pub attested_by: BTreeSet<String>,
+}
+
+/// Attestations to one external deposit. Relayers that disagree about its
+/// amount or recipient attest to different variants, and the first
+/// variant to reach the threshold is minted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DepositRecord {
+    /// The deposit's `ExternalDeposit::id`.
+    pub id: String,
+    /// Keyed by `ExternalDeposit::message_hash`.
+    pub variants: BTreeMap<String, DepositVariant>,
+    pub status: DepositStatus,
+}
+
+impl DepositRecord {
+    /// The version of the deposit that was minted, if any was.
+    pub fn minted_deposit(&self) -> Option<&ExternalDeposit> {
+        match &self.status {
+            DepositStatus::Minted { variant, .. } => self.variants.get(variant).map(|variant| &variant.deposit),
+            DepositStatus::Pending => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WithdrawalStatus {
+    /// Tokens are held by the bridge until the challenge period ends.
+    Pending,
+    /// A relayer stopped the withdrawal and the tokens went back to the holder.
+    Challenged { relayer: String, reason: String },
+    /// Tokens were burned; relayers release the asset on the external chain.
+    Released { released_at: DateTime<Utc> },
+}
+
+/// A request to move pegged tokens back to the external chain.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Withdrawal {
+    pub id: String,
+    pub holder: String,
+    pub asset_id: String,
+    pub amount: Amount,
+    /// Where the asset is released to on the external chain.
+    pub external_recipient: String,
+    pub requested_at: DateTime<Utc>,
+    pub challenge_ends_at: DateTime<Utc>,
+    pub status: WithdrawalStatus,
+}
+
+impl Withdrawal {
+    /// What a relayer signs to challenge the withdrawal.
+    pub fn challenge_message(&self, reason: &str) -> Vec<u8> {
+        format!("icn-bridge-challenge:{}:{}", self.id, reason).into_bytes()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct BridgeSystem {
+    config: BridgeConfig,
+    // Keyed by (chain, external asset)
+    pegs: HashMap<(String, String), PeggedAsset>,
+    deposits: HashMap<String, DepositRecord>,
+    withdrawals: HashMap<String, Withdrawal>,
+}
+
+impl BridgeSystem {
+    /// Sets up the bridge, issuing an asset token for each peg in `config`.
+    pub fn new(config: BridgeConfig, currency_system: &mut CurrencySystem) -> IcnResult<Self> {
+        if config.threshold == 0 || config.threshold > config.relayers.len() {
+            return Err(IcnError::Config("Bridge threshold must be between 1 and the number of relayers".into()));
+        }
+        if config.challenge_period_secs < 0 {
+            return Err(IcnError::Config("Bridge challenge period cannot be negative".into()));
+        }
+        for relayer in &config.relayers {
+            relayer_public_key(relayer)?;
+        }
+
+        let mut pegs = HashMap::new();
+        for peg in &config.pegs {
+            let key = (peg.chain.clone(), peg.external_asset.clone());
+            if pegs.contains_key(&key) {
+                return Err(IcnError::Config(format!("{} on {} is pegged twice", peg.external_asset, peg.chain)));
+            }
+            // Pegged tokens are redeemed on the external chain, so they
+            // carry no value in a local settlement currency
+            let asset_id = currency_system.issue_asset_token(BRIDGE_ACCOUNT, AssetTokenTerms {
+                name: peg.name.clone(),
+                description: format!("{} bridged from {}", peg.external_asset, peg.chain),
+                unit_value: 0.0,
+                supply_cap: None,
+                settlement_currency: CurrencyType::BasicNeeds,
+                restriction: TransferRestriction::Unrestricted,
+            }, Amount::ZERO)?;
+            pegs.insert(key, PeggedAsset {
+                chain: peg.chain.clone(),
+                external_asset: peg.external_asset.clone(),
+                asset_id,
+            });
+        }
+
+        Ok(BridgeSystem {
+            config,
+            pegs,
+            deposits: HashMap::new(),
+            withdrawals: HashMap::new(),
+        })
+    }
+
+    pub fn config(&self) -> &BridgeConfig {
+        &self.config
+    }
+
+    pub fn list_pegs(&self) -> Vec<&PeggedAsset> {
+        let mut pegs: Vec<_> = self.pegs.values().collect();
+        pegs.sort_by(|a, b| a.asset_id.cmp(&b.asset_id));
+        pegs
+    }
+
+    /// Records a relayer's attestation to a deposit. Once `threshold`
+    /// relayers agree on every detail of it, the pegged token is minted to
+    /// the recipient. A relayer attests to one version of a deposit, and
+    /// attestations to a deposit already minted are ignored.
+    pub fn attest_deposit(&mut self, deposit: ExternalDeposit, attestation: &Attestation, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<&DepositRecord> {
+        if !deposit.amount.is_positive() {
+            return Err(IcnError::Bridge("Deposit amount must be positive".into()));
+        }
+        if deposit.recipient.trim().is_empty() {
+            return Err(IcnError::Bridge("Deposits must name a recipient".into()));
+        }
+        let asset_id = self.pegs.get(&(deposit.chain.clone(), deposit.external_asset.clone()))
+            .map(|peg| peg.asset_id.clone())
+            .ok_or_else(|| IcnError::Bridge(format!("{} on {} is not pegged", deposit.external_asset, deposit.chain)))?;
+        self.verify_relayer(&attestation.relayer, &deposit.message(), &attestation.signature)?;
+
+        let threshold = self.config.threshold;
+        let hash = deposit.message_hash();
+        let record = self.deposits.entry(deposit.id()).or_insert_with(|| DepositRecord {
+            id: deposit.id(),
+            variants: BTreeMap::new(),
+            status: DepositStatus::Pending,
+        });
+        if record.status != DepositStatus::Pending {
+            return Ok(record);
+        }
+        if record.variants.iter().any(|(other, variant)| *other != hash && variant.attested_by.contains(&attestation.relayer)) {
+            return Err(IcnError::Bridge(format!("{} already attested to another version of deposit {}", attestation.relayer, record.id)));
+        }
+
+        let variant = record.variants.entry(hash.clone()).or_insert_with(|| DepositVariant {
+            deposit: deposit.clone(),
+            attested_by: BTreeSet::new(),
+        });
+        variant.attested_by.insert(attestation.relayer.clone());
+        if variant.attested_by.len() >= threshold {
+            currency_system.mint_asset_tokens(&asset_id, BRIDGE_ACCOUNT, deposit.amount)?;
+            currency_system.transfer_amount(BRIDGE_ACCOUNT, &deposit.recipient, &CurrencyType::AssetToken(asset_id.clone()), deposit.amount)?;
+            record.status = DepositStatus::Minted { asset_id, variant: hash, minted_at: now };
+        }
+        Ok(record)
+    }
+
+    pub fn get_deposit(&self, chain: &str, tx_hash: &str) -> IcnResult<&DepositRecord> {
+        self.deposits.get(&format!("{}:{}", chain, tx_hash))
+            .ok_or_else(|| IcnError::Bridge("Deposit not found".into()))
+    }
+
+    /// Moves `amount` of a pegged token from the holder into the bridge's
+    /// custody, to be burned and released on the external chain once the
+    /// challenge period ends. The caller must have checked that the holder
+    /// asked for this.
+    pub fn request_withdrawal(&mut self, holder: &str, asset_id: &str, amount: Amount, external_recipient: String, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<&Withdrawal> {
+        if !self.pegs.values().any(|peg| peg.asset_id == asset_id) {
+            return Err(IcnError::Bridge(format!("{} is not a pegged asset", asset_id)));
+        }
+        if !amount.is_positive() {
+            return Err(IcnError::Bridge("Withdrawal amount must be positive".into()));
+        }
+        if external_recipient.trim().is_empty() {
+            return Err(IcnError::Bridge("Withdrawals must name a recipient on the external chain".into()));
+        }
+        currency_system.transfer_amount(holder, BRIDGE_ACCOUNT, &CurrencyType::AssetToken(asset_id.to_string()), amount)?;
+
+        let id = Uuid::new_v4().to_string();
+        let withdrawal = Withdrawal {
+            id: id.clone(),
+            holder: holder.to_string(),
+            asset_id: asset_id.to_string(),
+            amount,
+            external_recipient,
+            requested_at: now,
+            challenge_ends_at: now + Duration::seconds(self.config.challenge_period_secs),
+            status: WithdrawalStatus::Pending,
+        };
+        Ok(self.withdrawals.entry(id).or_insert(withdrawal))
+    }
+
+    /// Stops a pending withdrawal within its challenge period, returning the
+    /// tokens to the holder. Only relayers can challenge.
+    pub fn challenge_withdrawal(&mut self, withdrawal_id: &str, relayer: &str, reason: String, signature: &[u8], currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<&Withdrawal> {
+        let withdrawal = self.get_withdrawal(withdrawal_id)?;
+        if withdrawal.status != WithdrawalStatus::Pending {
+            return Err(IcnError::Bridge("Only pending withdrawals can be challenged".into()));
+        }
+        if now >= withdrawal.challenge_ends_at {
+            return Err(IcnError::Bridge("The challenge period has ended".into()));
+        }
+        if reason.trim().is_empty() {
+            return Err(IcnError::Bridge("Challenges must give a reason".into()));
+        }
+        self.verify_relayer(relayer, &withdrawal.challenge_message(&reason), signature)?;
+
+        let currency_type = CurrencyType::AssetToken(withdrawal.asset_id.clone());
+        currency_system.transfer_amount(BRIDGE_ACCOUNT, &withdrawal.holder, &currency_type, withdrawal.amount)?;
+        let withdrawal = self.withdrawals.get_mut(withdrawal_id)
+            .ok_or_else(|| IcnError::Bridge("Withdrawal not found".into()))?;
+        withdrawal.status = WithdrawalStatus::Challenged { relayer: relayer.to_string(), reason };
+        Ok(withdrawal)
+    }
+
+    /// Burns the tokens of every pending withdrawal whose challenge period
+    /// has ended and returns them, oldest first, for relayers to release on
+    /// the external chain.
+    pub fn release_due_withdrawals(&mut self, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Vec<Withdrawal>> {
+        let mut due: Vec<_> = self.withdrawals.values()
+            .filter(|withdrawal| withdrawal.status == WithdrawalStatus::Pending && withdrawal.challenge_ends_at <= now)
+            .map(|withdrawal| (withdrawal.requested_at, withdrawal.id.clone()))
+            .collect();
+        due.sort();
+
+        let mut released = Vec::new();
+        for (_, id) in due {
+            let withdrawal = self.withdrawals.get_mut(&id)
+                .ok_or_else(|| IcnError::Bridge("Withdrawal not found".into()))?;
+            // The bridge issues pegged tokens, so redeeming them burns them
+            // without paying anything out
+            currency_system.redeem_asset_tokens(&withdrawal.asset_id, BRIDGE_ACCOUNT, withdrawal.amount)?;
+            withdrawal.status = WithdrawalStatus::Released { released_at: now };
+            released.push(withdrawal.clone());
+        }
+        Ok(released)
+    }
+
+    pub fn get_withdrawal(&self, withdrawal_id: &str) -> IcnResult<&Withdrawal> {
+        self.withdrawals.get(withdrawal_id)
+            .ok_or_else(|| IcnError::Bridge("Withdrawal not found".into()))
+    }
+
+    /// Withdrawals requested by `holder`, newest first.
+    pub fn list_withdrawals(&self, holder: &str) -> Vec<&Withdrawal> {
+        let mut withdrawals: Vec<_> = self.withdrawals.values()
+            .filter(|withdrawal| withdrawal.holder == holder)
+            .collect();
+        withdrawals.sort_by(|a, b| b.requested_at.cmp(&a.requested_at).then_with(|| a.id.cmp(&b.id)));
+        withdrawals
+    }
+
+    /// Amount of each pegged token in circulation, by asset id. Tokens
+    /// held for pending withdrawals still count.
+    pub fn pegged_supply(&self, currency_system: &CurrencySystem) -> BTreeMap<String, Amount> {
+        self.pegs.values()
+            .filter_map(|peg| {
+                let token = currency_system.get_asset_token(&peg.asset_id).ok()?;
+                Some((peg.asset_id.clone(), token.total_supply))
+            })
+            .collect()
+    }
+
+    fn verify_relayer(&self, relayer: &str, message: &[u8], signature: &[u8]) -> IcnResult<()> {
+        if !self.config.relayers.contains(relayer) {
+            return Err(IcnError::Bridge(format!("{} is not a bridge relayer", relayer)));
+        }
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Bridge(format!("Invalid relayer signature: {}", e)))?;
+        relayer_public_key(relayer)?
+            .verify(message, &signature)
+            .map_err(|_| IcnError::Bridge(format!("Signature from {} does not verify", relayer)))
+    }
+}
+
+fn relayer_public_key(did: &str) -> IcnResult<PublicKey> {
+    let key_hex = did.strip_prefix("did:icn:")
+        .ok_or_else(|| IcnError::Bridge(format!("Invalid relayer DID: {}", did)))?;
+    let key_bytes = hex::decode(key_hex)
+        .map_err(|e| IcnError::Bridge(format!("Invalid relayer DID {}: {}", did, e)))?;
+    PublicKey::from_bytes(&key_bytes)
+        .map_err(|e| IcnError::Bridge(format!("Invalid relayer key {}: {}", did, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    fn relayer() -> (String, Keypair) {
+        let keypair = Keypair::generate(&mut OsRng {});
+        (format!("did:icn:{}", hex::encode(keypair.public.to_bytes())), keypair)
+    }
+
+    fn attest(relayer: &(String, Keypair), deposit: &ExternalDeposit) -> Attestation {
+        Attestation {
+            relayer: relayer.0.clone(),
+            signature: relayer.1.sign(&deposit.message()).to_bytes().to_vec(),
+        }
+    }
+
+    fn setup() -> (BridgeSystem, CurrencySystem, Vec<(String, Keypair)>) {
+        let relayers: Vec<_> = (0..3).map(|_| relayer()).collect();
+        let config = BridgeConfig {
+            relayers: relayers.iter().map(|(did, _)| did.clone()).collect(),
+            threshold: 2,
+            challenge_period_secs: 3600,
+            pegs: vec![PegDefinition {
+                chain: "ethereum".to_string(),
+                external_asset: "USDC".to_string(),
+                name: "Bridged USDC".to_string(),
+            }],
+        };
+        let mut currency_system = CurrencySystem::new();
+        let bridge = BridgeSystem::new(config, &mut currency_system).unwrap();
+        (bridge, currency_system, relayers)
+    }
+
+    fn deposit(amount: f64) -> ExternalDeposit {
+        ExternalDeposit {
+            chain: "ethereum".to_string(),
+            external_asset: "USDC".to_string(),
+            tx_hash: "0xabc".to_string(),
+            amount: Amount::from(amount),
+            recipient: "Alice".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_deposit_needs_threshold_of_relayers() {
+        let (mut bridge, mut currency_system, relayers) = setup();
+        let asset_id = bridge.list_pegs()[0].asset_id.clone();
+        let currency_type = CurrencyType::AssetToken(asset_id.clone());
+        let now = Utc::now();
+
+        let record = bridge.attest_deposit(deposit(100.0), &attest(&relayers[0], &deposit(100.0)), &mut currency_system, now).unwrap();
+        assert_eq!(record.status, DepositStatus::Pending);
+        // The same relayer twice doesn't count twice
+        bridge.attest_deposit(deposit(100.0), &attest(&relayers[0], &deposit(100.0)), &mut currency_system, now).unwrap();
+        assert_eq!(currency_system.get_balance("Alice", &currency_type).unwrap(), 0.0);
+
+        // A relayer can't back two versions of the same deposit
+        assert!(bridge.attest_deposit(deposit(500.0), &attest(&relayers[0], &deposit(500.0)), &mut currency_system, now).is_err());
+        let outsider = relayer();
+        assert!(bridge.attest_deposit(deposit(100.0), &attest(&outsider, &deposit(100.0)), &mut currency_system, now).is_err());
+
+        let record = bridge.attest_deposit(deposit(100.0), &attest(&relayers[1], &deposit(100.0)), &mut currency_system, now).unwrap();
+        assert!(matches!(record.status, DepositStatus::Minted { .. }));
+        bridge.attest_deposit(deposit(100.0), &attest(&relayers[2], &deposit(100.0)), &mut currency_system, now).unwrap();
+        assert_eq!(currency_system.get_balance("Alice", &currency_type).unwrap(), 100.0);
+        assert_eq!(bridge.pegged_supply(&currency_system)[&asset_id], Amount::from(100.0));
+    }
+
+    #[test]
+    fn test_wrong_first_attestation_does_not_block_deposit() {
+        let (mut bridge, mut currency_system, relayers) = setup();
+        let currency_type = CurrencyType::AssetToken(bridge.list_pegs()[0].asset_id.clone());
+        let now = Utc::now();
+
+        // A relayer front-runs the others with the wrong amount
+        let record = bridge.attest_deposit(deposit(500.0), &attest(&relayers[0], &deposit(500.0)), &mut currency_system, now).unwrap();
+        assert_eq!(record.variants.len(), 1);
+
+        bridge.attest_deposit(deposit(100.0), &attest(&relayers[1], &deposit(100.0)), &mut currency_system, now).unwrap();
+        let record = bridge.attest_deposit(deposit(100.0), &attest(&relayers[2], &deposit(100.0)), &mut currency_system, now).unwrap();
+        assert_eq!(record.minted_deposit(), Some(&deposit(100.0)));
+        assert_eq!(record.variants.len(), 2);
+        assert_eq!(currency_system.get_balance("Alice", &currency_type).unwrap(), 100.0);
+
+        // The losing variant can't mint afterwards
+        bridge.attest_deposit(deposit(500.0), &attest(&relayers[1], &deposit(500.0)), &mut currency_system, now).unwrap();
+        assert_eq!(currency_system.get_balance("Alice", &currency_type).unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_withdrawals_wait_out_challenge_period() {
+        let (mut bridge, mut currency_system, relayers) = setup();
+        let asset_id = bridge.list_pegs()[0].asset_id.clone();
+        let currency_type = CurrencyType::AssetToken(asset_id.clone());
+        let now = Utc::now();
+        for relayer in &relayers[..2] {
+            bridge.attest_deposit(deposit(100.0), &attest(relayer, &deposit(100.0)), &mut currency_system, now).unwrap();
+        }
+
+        let first = bridge.request_withdrawal("Alice", &asset_id, Amount::from(60.0), "0xalice".to_string(), &mut currency_system, now).unwrap().id.clone();
+        let second = bridge.request_withdrawal("Alice", &asset_id, Amount::from(40.0), "0xalice".to_string(), &mut currency_system, now).unwrap().id.clone();
+        assert!(bridge.request_withdrawal("Alice", &asset_id, Amount::from(1.0), "0xalice".to_string(), &mut currency_system, now).is_err());
+        assert_eq!(currency_system.get_balance("Alice", &currency_type).unwrap(), 0.0);
+
+        let reason = "No matching burn observed".to_string();
+        let signature = relayers[2].1.sign(&bridge.get_withdrawal(&second).unwrap().challenge_message(&reason)).to_bytes().to_vec();
+        bridge.challenge_withdrawal(&second, &relayers[2].0, reason, &signature, &mut currency_system, now).unwrap();
+        assert_eq!(currency_system.get_balance("Alice", &currency_type).unwrap(), 40.0);
+
+        assert!(bridge.release_due_withdrawals(&mut currency_system, now).unwrap().is_empty());
+        let released = bridge.release_due_withdrawals(&mut currency_system, now + Duration::hours(1)).unwrap();
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].id, first);
+        assert_eq!(bridge.pegged_supply(&currency_system)[&asset_id], Amount::from(40.0));
+
+        // Too late to challenge now
+        let reason = "Late".to_string();
+        let signature = relayers[0].1.sign(&bridge.get_withdrawal(&first).unwrap().challenge_message(&reason)).to_bytes().to_vec();
+        assert!(bridge.challenge_withdrawal(&first, &relayers[0].0, reason, &signature, &mut currency_system, now).is_err());
+    }
+}
@@ -0,0 +1,317 @@
+// File: crates/icn_common/src/amount.rs
+
+use crate::{CurrencyType, IcnError, IcnResult};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// Decimal places every ledger keeps amounts to. No currency can be more
+/// precise than this.
+pub const LEDGER_DECIMALS: u32 = 6;
+
+const UNITS_PER_WHOLE: i64 = 10i64.pow(LEDGER_DECIMALS);
+
+/// A currency amount as a whole number of millionths.
+///
+/// Balances kept as `f64` drift as rounding errors pile up over many
+/// transfers; integer units add and subtract exactly. For compatibility
+/// with existing clients amounts are written to JSON as plain numbers, and
+/// read from numbers or decimal strings. Binary formats carry the units.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Amount(i64);
+
+impl Amount {
+    pub const ZERO: Amount = Amount(0);
+
+    pub const fn from_units(units: i64) -> Self {
+        Amount(units)
+    }
+
+    /// The amount in millionths.
+    pub const fn units(self) -> i64 {
+        self.0
+    }
+
+    /// Converts a decimal value, rounding to the nearest millionth. Fails for
+    /// values that are not finite or too large to represent.
+    pub fn from_f64(value: f64) -> IcnResult<Self> {
+        let units = (value * UNITS_PER_WHOLE as f64).round();
+        if !units.is_finite() || units.abs() >= i64::MAX as f64 {
+            return Err(IcnError::Validation(format!("Amount {} cannot be represented", value)));
+        }
+        Ok(Amount(units as i64))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / UNITS_PER_WHOLE as f64
+    }
+
+    /// Parses a decimal string such as `"12.345"` without going through
+    /// floating point.
+    pub fn parse(text: &str) -> IcnResult<Self> {
+        let invalid = || IcnError::Validation(format!("Invalid amount: {}", text));
+        let (negative, digits) = match text.trim().strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.trim()),
+        };
+        let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        if (whole.is_empty() && fraction.is_empty())
+            || fraction.len() > LEDGER_DECIMALS as usize
+            || !whole.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+
+        let whole: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+        let fraction: i64 = format!("{:0<width$}", fraction, width = LEDGER_DECIMALS as usize).parse().map_err(|_| invalid())?;
+        let units = whole.checked_mul(UNITS_PER_WHOLE)
+            .and_then(|units| units.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        Ok(Amount(if negative { -units } else { units }))
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.0 < 0
+    }
+
+    pub fn checked_add(self, other: Amount) -> Option<Amount> {
+        self.0.checked_add(other.0).map(Amount)
+    }
+
+    pub fn checked_sub(self, other: Amount) -> Option<Amount> {
+        self.0.checked_sub(other.0).map(Amount)
+    }
+
+    /// The amount `count` times over, e.g. the price of `count` units.
+    pub fn checked_mul(self, count: i64) -> Option<Amount> {
+        self.0.checked_mul(count).map(Amount)
+    }
+
+    /// `self * numerator / denominator`, rounded toward zero, for exact
+    /// pro-rata shares. `None` for a zero denominator or a result too
+    /// large to represent.
+    pub fn mul_div(self, numerator: i64, denominator: i64) -> Option<Amount> {
+        if denominator == 0 {
+            return None;
+        }
+        let units = self.0 as i128 * numerator as i128 / denominator as i128;
+        i64::try_from(units).ok().map(Amount)
+    }
+
+    /// Whether the amount can be written with at most `decimals` decimal places.
+    pub fn fits_decimals(self, decimals: u32) -> bool {
+        decimals >= LEDGER_DECIMALS || self.0 % 10i64.pow(LEDGER_DECIMALS - decimals) == 0
+    }
+
+    /// Rounds half away from zero to `decimals` decimal places.
+    pub fn round_to(self, decimals: u32) -> Amount {
+        if decimals >= LEDGER_DECIMALS {
+            return self;
+        }
+        let step = 10i64.pow(LEDGER_DECIMALS - decimals);
+        let remainder = self.0 % step;
+        let rounded = self.0 - remainder;
+        if remainder.abs() * 2 >= step {
+            Amount(rounded + step * self.0.signum())
+        } else {
+            Amount(rounded)
+        }
+    }
+
+    /// Checks that the amount is no more precise than the currency allows.
+    pub fn check_precision(self, currency_type: &CurrencyType) -> IcnResult<()> {
+        let decimals = currency_type.decimals();
+        if !self.fits_decimals(decimals) {
+            return Err(IcnError::Validation(format!(
+                "{:?} amounts have at most {} decimal places, got {}", currency_type, decimals, self
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl CurrencyType {
+    /// Decimal places amounts of this currency are given in.
+    ///
+    /// Storage is priced per byte and bond and asset token units can be
+    /// split finely, so they use the full ledger precision.
+    pub fn decimals(&self) -> u32 {
+        match self {
+            CurrencyType::BasicNeeds
+            | CurrencyType::Education
+            | CurrencyType::Environmental
+            | CurrencyType::Community
+            | CurrencyType::Volunteer
+            | CurrencyType::Service
+            | CurrencyType::Custom(_) => 2,
+            CurrencyType::Storage | CurrencyType::Bond(_) | CurrencyType::AssetToken(_) => LEDGER_DECIMALS,
+        }
+    }
+}
+
+/// Rounds to the nearest millionth. Values too large to represent saturate
+/// and NaN becomes zero; use `Amount::from_f64` to reject them instead.
+impl From<f64> for Amount {
+    fn from(value: f64) -> Self {
+        Amount((value * UNITS_PER_WHOLE as f64).round() as i64)
+    }
+}
+
+/// Writes the amount the way `f64` would print the same value, so
+/// signatures and hashes over transactions made before amounts were
+/// fixed-point still match.
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let units = self.0.unsigned_abs();
+        let whole = units / UNITS_PER_WHOLE as u64;
+        let fraction = units % UNITS_PER_WHOLE as u64;
+        if fraction == 0 {
+            return write!(f, "{}{}", sign, whole);
+        }
+        let fraction = format!("{:0width$}", fraction, width = LEDGER_DECIMALS as usize);
+        write!(f, "{}{}.{}", sign, whole, fraction.trim_end_matches('0'))
+    }
+}
+
+// The operators overflow like `i64`. Ledgers adding up balances they
+// don't control use `checked_add` and `checked_sub` instead.
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, other: Amount) -> Amount {
+        Amount(self.0 + other.0)
+    }
+}
+
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, other: Amount) -> Amount {
+        Amount(self.0 - other.0)
+    }
+}
+
+impl Neg for Amount {
+    type Output = Amount;
+
+    fn neg(self) -> Amount {
+        Amount(-self.0)
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, other: Amount) {
+        self.0 += other.0;
+    }
+}
+
+impl SubAssign for Amount {
+    fn sub_assign(&mut self, other: Amount) {
+        self.0 -= other.0;
+    }
+}
+
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Amount {
+        iter.fold(Amount::ZERO, Add::add)
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_f64(self.to_f64())
+        } else {
+            serializer.serialize_i64(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct AmountVisitor;
+
+        impl<'de> Visitor<'de> for AmountVisitor {
+            type Value = Amount;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a number or a decimal string")
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Amount, E> {
+                Amount::from_f64(value).map_err(E::custom)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Amount, E> {
+                value.checked_mul(UNITS_PER_WHOLE).map(Amount).ok_or_else(|| E::custom("amount is too large"))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Amount, E> {
+                i64::try_from(value).map_err(E::custom).and_then(|value| self.visit_i64(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Amount, E> {
+                Amount::parse(value).map_err(E::custom)
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(AmountVisitor)
+        } else {
+            i64::deserialize(deserializer).map(Amount)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_amounts_are_exact() {
+        let mut total = Amount::ZERO;
+        for _ in 0..10 {
+            total += Amount::from(0.1);
+        }
+        assert_eq!(total, Amount::from(1.0));
+        assert_eq!(Amount::parse("12.345").unwrap(), Amount::from_units(12_345_000));
+        assert!(Amount::parse("0.0000001").is_err());
+        assert!(Amount::from_f64(f64::NAN).is_err());
+
+        // Printed like f64 so existing signatures still verify
+        for value in [5.0, 0.1, 12.345, -3.25, 1_000_000.0] {
+            assert_eq!(Amount::from(value).to_string(), value.to_string());
+        }
+
+        assert!(Amount::from(1.25).fits_decimals(2));
+        assert!(!Amount::from(1.255).fits_decimals(2));
+        assert_eq!(Amount::from(1.255).round_to(2), Amount::from(1.26));
+        assert!(Amount::from(0.005).check_precision(&CurrencyType::BasicNeeds).is_err());
+        assert!(Amount::from(0.005).check_precision(&CurrencyType::Storage).is_ok());
+
+        assert_eq!(Amount::from(5.0).checked_mul(4), Some(Amount::from(20.0)));
+        assert_eq!(Amount::from_units(i64::MAX).checked_add(Amount::from_units(1)), None);
+        assert_eq!(Amount::from(100.0).mul_div(1, 3), Some(Amount::from_units(33_333_333)));
+        assert_eq!(Amount::from(100.0).mul_div(1, 0), None);
+    }
+
+    #[test]
+    fn test_amount_serde_compatibility() {
+        assert_eq!(serde_json::to_string(&Amount::from(10.5)).unwrap(), "10.5");
+        assert_eq!(serde_json::from_str::<Amount>("10.5").unwrap(), Amount::from(10.5));
+        assert_eq!(serde_json::from_str::<Amount>("7").unwrap(), Amount::from(7.0));
+        assert_eq!(serde_json::from_str::<Amount>("\"0.07\"").unwrap(), Amount::from(0.07));
+        assert!(serde_json::from_str::<Amount>("\"lots\"").is_err());
+
+        let encoded = bincode::serialize(&Amount::from_units(1)).unwrap();
+        assert_eq!(bincode::deserialize::<Amount>(&encoded).unwrap(), Amount::from_units(1));
+    }
+}
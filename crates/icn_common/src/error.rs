@@ -35,6 +35,33 @@ pub enum IcnError {
     #[error("VM error: {0}")]
     Vm(String),
 
+    #[error("VM out of gas: {0}")]
+    VmOutOfGas(String),
+
+    #[error("VM out of memory: {0}")]
+    VmOutOfMemory(String),
+
+    #[error("VM execution timed out: {0}")]
+    VmTimeout(String),
+
+    #[error("Smart contract error: {0}")]
+    SmartContract(String),
+
+    #[error("DAO error: {0}")]
+    Dao(String),
+
+    #[error("Dispute error: {0}")]
+    Dispute(String),
+
+    #[error("Bridge error: {0}")]
+    Bridge(String),
+
+    #[error("Keystore error: {0}")]
+    Keystore(String),
+
+    #[error("Marketplace error: {0}")]
+    Marketplace(String),
+
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -0,0 +1,100 @@
+// File: crates/icn_common/src/evidence.rs
+
+use crate::merkle::{merkle_root, transaction_leaf};
+use serde::{Serialize, Deserialize};
+
+/// A validator's signed approval of the block with `block_hash` at
+/// `block_index`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockApproval {
+    pub validator: String,
+    pub block_index: u64,
+    pub block_hash: String,
+    /// Signature over `approval_message(block_index, block_hash)`.
+    pub signature: Vec<u8>,
+}
+
+impl BlockApproval {
+    pub fn message(&self) -> Vec<u8> {
+        approval_message(self.block_index, &self.block_hash)
+    }
+}
+
+/// The bytes a validator signs to approve a block.
+pub fn approval_message(block_index: u64, block_hash: &str) -> Vec<u8> {
+    format!("icn-block-approval:{}:{}", block_index, block_hash).into_bytes()
+}
+
+/// Proof that a validator approved two different blocks at one height.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DoubleSignEvidence {
+    pub first: BlockApproval,
+    pub second: BlockApproval,
+}
+
+impl DoubleSignEvidence {
+    pub fn validator(&self) -> &str {
+        &self.first.validator
+    }
+
+    pub fn block_index(&self) -> u64 {
+        self.first.block_index
+    }
+
+    /// Whether the two approvals conflict: same validator, same height,
+    /// different blocks. Signatures are checked by consensus.
+    pub fn is_conflicting(&self) -> bool {
+        self.first.validator == self.second.validator
+            && self.first.block_index == self.second.block_index
+            && self.first.block_hash != self.second.block_hash
+    }
+
+    /// Identifies the offence rather than the submission, so the same two
+    /// approvals in either order are one piece of evidence.
+    pub fn id(&self) -> String {
+        let (a, b) = if self.first.block_hash <= self.second.block_hash {
+            (&self.first.block_hash, &self.second.block_hash)
+        } else {
+            (&self.second.block_hash, &self.first.block_hash)
+        };
+        transaction_leaf(&(self.validator(), self.block_index(), a, b))
+    }
+}
+
+/// Root a block's header commits to for the evidence it carries. Blocks
+/// without evidence have an empty root, which leaves their hash unchanged.
+pub fn evidence_root(evidence: &[DoubleSignEvidence]) -> String {
+    if evidence.is_empty() {
+        return String::new();
+    }
+    merkle_root(&evidence.iter().map(|e| e.id()).collect::<Vec<_>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval(validator: &str, block_index: u64, block_hash: &str) -> BlockApproval {
+        BlockApproval {
+            validator: validator.to_string(),
+            block_index,
+            block_hash: block_hash.to_string(),
+            signature: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_conflicting_approvals() {
+        let evidence = DoubleSignEvidence { first: approval("v1", 4, "aa"), second: approval("v1", 4, "bb") };
+        assert!(evidence.is_conflicting());
+        let swapped = DoubleSignEvidence { first: evidence.second.clone(), second: evidence.first.clone() };
+        assert_eq!(evidence.id(), swapped.id());
+
+        assert!(!DoubleSignEvidence { first: approval("v1", 4, "aa"), second: approval("v1", 4, "aa") }.is_conflicting());
+        assert!(!DoubleSignEvidence { first: approval("v1", 4, "aa"), second: approval("v1", 5, "bb") }.is_conflicting());
+        assert!(!DoubleSignEvidence { first: approval("v1", 4, "aa"), second: approval("v2", 4, "bb") }.is_conflicting());
+
+        assert_eq!(evidence_root(&[]), "");
+        assert_ne!(evidence_root(&[evidence]), "");
+    }
+}
@@ -0,0 +1,159 @@
+// File: crates/icn_common/src/faults.rs
+
+//! Fault injection for resilience testing. Only built with the
+//! `fault_injection` feature, which production builds must leave off.
+
+use crate::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// A fault to inject into a running node.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Fault {
+    /// Drops the next `count` outgoing network messages.
+    DropMessages { count: usize },
+    /// Sleeps for `millis` before each of the next `count` shard lock
+    /// acquisitions.
+    DelayShardLocks { millis: u64, count: usize },
+    /// Overwrites the `replica`th copy of `key` with garbage.
+    CorruptReplica { key: String, replica: usize },
+    /// Aborts the next consensus round after votes are collected but
+    /// before the block is committed.
+    CrashConsensusRound,
+}
+
+/// How many injected faults have fired so far.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FaultStats {
+    pub dropped_messages: usize,
+    pub delayed_lock_acquisitions: usize,
+    pub corrupted_replicas: usize,
+    pub crashed_rounds: usize,
+}
+
+#[derive(Debug, Default)]
+struct FaultState {
+    messages_to_drop: usize,
+    lock_delay: Duration,
+    delayed_locks_left: usize,
+    crash_next_round: bool,
+    stats: FaultStats,
+}
+
+/// Shared handle to the faults armed for a node. Clones share state, so
+/// one handle given to each component lets a test arm faults everywhere.
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl FaultInjector {
+    /// Arms `fault`. Replica corruption is carried out by the storage
+    /// manager holding the data, not armed here.
+    pub fn inject(&self, fault: Fault) -> IcnResult<()> {
+        let mut state = self.lock();
+        match fault {
+            Fault::DropMessages { count } => state.messages_to_drop += count,
+            Fault::DelayShardLocks { millis, count } => {
+                state.lock_delay = Duration::from_millis(millis);
+                state.delayed_locks_left = count;
+            }
+            Fault::CrashConsensusRound => state.crash_next_round = true,
+            Fault::CorruptReplica { .. } => {
+                return Err(IcnError::Validation("Replica corruption is applied by the storage manager".into()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Disarms every pending fault. Stats are kept.
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        let stats = std::mem::take(&mut state.stats);
+        *state = FaultState { stats, ..FaultState::default() };
+    }
+
+    /// Whether the message about to be sent should be dropped instead.
+    pub fn should_drop_message(&self) -> bool {
+        let mut state = self.lock();
+        if state.messages_to_drop == 0 {
+            return false;
+        }
+        state.messages_to_drop -= 1;
+        state.stats.dropped_messages += 1;
+        true
+    }
+
+    /// How long to stall before taking a shard lock, if at all.
+    pub fn shard_lock_delay(&self) -> Option<Duration> {
+        let mut state = self.lock();
+        if state.delayed_locks_left == 0 {
+            return None;
+        }
+        state.delayed_locks_left -= 1;
+        state.stats.delayed_lock_acquisitions += 1;
+        Some(state.lock_delay)
+    }
+
+    /// Whether the current consensus round should crash. Fires once per
+    /// `CrashConsensusRound`.
+    pub fn take_consensus_crash(&self) -> bool {
+        let mut state = self.lock();
+        let crash = std::mem::take(&mut state.crash_next_round);
+        if crash {
+            state.stats.crashed_rounds += 1;
+        }
+        crash
+    }
+
+    /// Counts a replica the storage manager corrupted.
+    pub fn record_corrupted_replica(&self) {
+        self.lock().stats.corrupted_replicas += 1;
+    }
+
+    pub fn stats(&self) -> FaultStats {
+        self.lock().stats.clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, FaultState> {
+        // A test panicking while holding the lock shouldn't disarm faults
+        // for the tests that follow.
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_fire_as_armed() {
+        let injector = FaultInjector::default();
+        let shared = injector.clone();
+        assert!(!injector.should_drop_message());
+
+        shared.inject(Fault::DropMessages { count: 2 }).unwrap();
+        shared.inject(Fault::DelayShardLocks { millis: 5, count: 1 }).unwrap();
+        shared.inject(Fault::CrashConsensusRound).unwrap();
+        assert!(shared.inject(Fault::CorruptReplica { key: "k".to_string(), replica: 0 }).is_err());
+
+        assert!(injector.should_drop_message());
+        assert!(injector.should_drop_message());
+        assert!(!injector.should_drop_message());
+        assert_eq!(injector.shard_lock_delay(), Some(Duration::from_millis(5)));
+        assert_eq!(injector.shard_lock_delay(), None);
+        assert!(injector.take_consensus_crash());
+        assert!(!injector.take_consensus_crash());
+
+        injector.inject(Fault::DropMessages { count: 3 }).unwrap();
+        injector.clear();
+        assert!(!injector.should_drop_message());
+        assert_eq!(injector.stats(), FaultStats {
+            dropped_messages: 2,
+            delayed_lock_acquisitions: 1,
+            corrupted_replicas: 0,
+            crashed_rounds: 1,
+        });
+    }
+}
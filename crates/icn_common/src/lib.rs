@@ -2,12 +2,37 @@
 
 pub mod error;
 pub mod bit_utils;
+pub mod vesting;
+pub mod params;
+pub mod limits;
+pub mod amount;
+pub mod memo;
+pub mod merkle;
+pub mod evidence;
+pub mod rounds;
+pub mod sortition;
+pub mod clock;
+#[cfg(feature = "fault_injection")]
+pub mod faults;
 
 pub use crate::error::{IcnError, IcnResult};
+pub use crate::vesting::VestingSchedule;
+pub use crate::params::{ParameterChange, ParameterDefinition, ParameterRegistry};
+pub use crate::limits::SizeLimits;
+pub use crate::amount::Amount;
+pub use crate::memo::{memo_storage_key, TransactionMemo, MAX_MEMO_BYTES, MAX_STORED_MEMO_BYTES};
+pub use crate::merkle::{BlockHeader, HeaderBatch, MerkleProof, ResumeToken, TransactionProof};
+pub use crate::evidence::{approval_message, evidence_root, BlockApproval, DoubleSignEvidence};
+pub use crate::rounds::{block_producer_message, consensus_proposal_message, consensus_vote_message, ConsensusProposal, ConsensusVote, VoteStep};
+pub use crate::sortition::weighted_sample;
+pub use crate::clock::{system_clock, Clock, ManualClock, SharedClock, SystemClock};
+#[cfg(feature = "fault_injection")]
+pub use crate::faults::{Fault, FaultInjector, FaultStats};
 
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
-use std::collections::HashMap;
+use sha2::{Sha256, Digest};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -21,40 +46,137 @@ pub struct Config {
 pub struct Transaction {
     pub from: String,
     pub to: String,
-    pub amount: f64,
+    pub amount: Amount,
     pub currency_type: CurrencyType,
     pub timestamp: i64,
     pub signature: Option<Vec<u8>>,
+    #[serde(default)]
+    pub memo: Option<TransactionMemo>,
+    /// Scheme the signature was made with. Anything but ed25519 is checked
+    /// against the key the sender registered with the identity service.
+    #[serde(default)]
+    pub signature_scheme: SignatureScheme,
+    /// Unix time after which the transaction may no longer be admitted to
+    /// the mempool or included in a block.
+    #[serde(default)]
+    pub valid_until: Option<i64>,
+    /// An encoded capability token authorizing the transfer for `from` in
+    /// place of a signature. Nodes check it against the issuer's key and
+    /// the token's spending limit before applying the transaction.
+    #[serde(default)]
+    pub capability: Option<String>,
+}
+
+/// Signature algorithms identities may register keys for.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SignatureScheme {
+    #[default]
+    Ed25519,
+    /// ECDSA over secp256k1, as used by most external chains' wallets.
+    Secp256k1,
+    /// BLS over BLS12-381. Signatures from many keys on one message
+    /// aggregate into a single signature.
+    Bls12381,
+}
+
+impl SignatureScheme {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SignatureScheme::Ed25519 => "ed25519",
+            SignatureScheme::Secp256k1 => "secp256k1",
+            SignatureScheme::Bls12381 => "bls12-381",
+        }
+    }
 }
 
 impl Transaction {
-    pub fn new(from: String, to: String, amount: f64, currency_type: CurrencyType, timestamp: i64) -> Self {
+    pub fn new(from: String, to: String, amount: impl Into<Amount>, currency_type: CurrencyType, timestamp: i64) -> Self {
         Transaction {
             from,
             to,
-            amount,
+            amount: amount.into(),
             currency_type,
             timestamp,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
+        }
+    }
+
+    pub fn with_memo(mut self, memo: TransactionMemo) -> Self {
+        self.memo = Some(memo);
+        self
+    }
+
+    pub fn with_valid_until(mut self, valid_until: i64) -> Self {
+        self.valid_until = Some(valid_until);
+        self
+    }
+
+    pub fn with_capability(mut self, token: String) -> Self {
+        self.capability = Some(token);
+        self
+    }
+
+    /// Whether the transaction's validity ended before `now`.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.valid_until.is_some_and(|valid_until| now > valid_until)
+    }
+
+    /// Memo digest and expiry appended to the signed and hashed fields,
+    /// empty without either so older transactions keep their hashes.
+    fn optional_suffix(&self) -> String {
+        let memo = self.memo.as_ref().map(TransactionMemo::digest).unwrap_or_default();
+        match self.valid_until {
+            Some(valid_until) => format!("{}|{}", memo, valid_until),
+            None => memo,
         }
     }
 
+    /// The bytes a signature covers. Wallets signing away from the node
+    /// must sign exactly these: the bincode encoding of `SigningPayload`,
+    /// which length-prefixes every string and names the currency and the
+    /// signature scheme, so a signature fits exactly one transaction.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let payload = SigningPayload {
+            domain: TRANSACTION_SIGNING_DOMAIN,
+            from: &self.from,
+            to: &self.to,
+            amount_units: self.amount.units(),
+            currency_type: &self.currency_type,
+            timestamp: self.timestamp,
+            memo_digest: self.memo.as_ref().map(TransactionMemo::digest),
+            valid_until: self.valid_until,
+            signature_scheme: self.signature_scheme,
+        };
+        bincode::serialize(&payload).expect("transaction fields always serialize")
+    }
+
     pub fn sign(&mut self, keypair: &ed25519_dalek::Keypair) -> IcnResult<()> {
-        let message = format!("{}{}{}{}", self.from, self.to, self.amount, self.timestamp);
-        let signature = keypair.sign(message.as_bytes()).to_bytes().to_vec();
+        self.signature_scheme = SignatureScheme::Ed25519;
+        let signature = keypair.sign(&self.signing_bytes()).to_bytes().to_vec();
         self.signature = Some(signature);
         Ok(())
     }
 
+    /// Checks an ed25519 signature against the key in the sender's
+    /// `did:icn:` DID. Other schemes need the sender's registered key and
+    /// are checked by the identity service.
     pub fn verify(&self) -> IcnResult<bool> {
+        if self.signature.is_some() && self.signature_scheme != SignatureScheme::Ed25519 {
+            return Err(IcnError::Identity(format!(
+                "{} signatures are verified against the sender's registered key", self.signature_scheme.name()
+            )));
+        }
         if let Some(signature) = &self.signature {
-            let message = format!("{}{}{}{}", self.from, self.to, self.amount, self.timestamp);
-            let public_key = ed25519_dalek::PublicKey::from_bytes(&self.from.as_bytes())
-                .map_err(|e| IcnError::Identity(format!("PublicKey conversion failed: {}", e)))?;
+            let message = self.signing_bytes();
+            let public_key = public_key_from_did(&self.from)?;
             let signature = ed25519_dalek::Signature::from_bytes(signature)
                 .map_err(|e| IcnError::Identity(format!("Signature conversion failed: {}", e)))?;
             public_key
-                .verify(message.as_bytes(), &signature)
+                .verify(&message, &signature)
                 .map_err(|e| IcnError::Identity(format!("Signature verification failed: {}", e)))?;
             Ok(true)
         } else {
@@ -62,12 +184,72 @@ impl Transaction {
         }
     }
 
-    pub fn get_fee(&self) -> f64 {
-        // Simplified fee calculation; in a real implementation, fees would be more complex
-        0.01
+    /// Returns the hex-encoded SHA-256 hash identifying this transaction.
+    /// The signature is excluded so the hash is stable before and after signing.
+    pub fn hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(format!("{}{}{}{:?}{}{}", self.from, self.to, self.amount, self.currency_type, self.timestamp, self.optional_suffix()).as_bytes());
+        format!("{:x}", hasher.finalize())
     }
 }
 
+/// Separates transaction signatures from signatures over anything else.
+const TRANSACTION_SIGNING_DOMAIN: &str = "icn-transaction-v1";
+
+/// What a transaction signature covers; see `Transaction::signing_bytes`.
+#[derive(Serialize)]
+struct SigningPayload<'a> {
+    domain: &'static str,
+    from: &'a str,
+    to: &'a str,
+    amount_units: i64,
+    currency_type: &'a CurrencyType,
+    timestamp: i64,
+    memo_digest: Option<String>,
+    valid_until: Option<i64>,
+    signature_scheme: SignatureScheme,
+}
+
+/// The public key embedded in a `did:icn:` DID.
+pub fn public_key_from_did(id: &str) -> IcnResult<ed25519_dalek::PublicKey> {
+    let key_hex = id.strip_prefix("did:icn:")
+        .ok_or_else(|| IcnError::Identity(format!("Invalid DID: {}", id)))?;
+    let key_bytes = hex::decode(key_hex)
+        .map_err(|e| IcnError::Identity(format!("Invalid DID {}: {}", id, e)))?;
+    ed25519_dalek::PublicKey::from_bytes(&key_bytes)
+        .map_err(|e| IcnError::Identity(format!("Invalid public key in {}: {}", id, e)))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TransactionStatus {
+    Success,
+    Failed(String),
+    /// Left the mempool unconfirmed once its `valid_until` passed.
+    Expired,
+}
+
+/// An event emitted while a transaction was executed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionEvent {
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+}
+
+/// The outcome of processing a transaction, kept by the node for later lookup.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionReceipt {
+    pub tx_hash: String,
+    /// Index of the block the transaction was queued for.
+    pub block_index: u64,
+    pub status: TransactionStatus,
+    pub gas_used: u64,
+    /// Fee deducted from the sender on top of the amount.
+    #[serde(default)]
+    pub fee: Amount,
+    pub events: Vec<TransactionEvent>,
+    pub shard_id: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Proposal {
     pub id: String,
@@ -81,6 +263,271 @@ pub struct Proposal {
     pub category: ProposalCategory,
     pub required_quorum: f64,
     pub execution_timestamp: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub weight_mode: VotingWeightMode,
+    #[serde(default)]
+    pub actions: Vec<ProposalAction>,
+    #[serde(default)]
+    pub scope: ProposalScope,
+    #[serde(default)]
+    pub ballot: BallotKind,
+    /// Proposals that must have been executed before this one can be.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// The bundle this proposal is decided and executed with, if any.
+    #[serde(default)]
+    pub bundle: Option<ProposalBundle>,
+}
+
+impl Proposal {
+    /// The bytes the proposer signs: the bincode encoding of
+    /// `ProposalSigningPayload`. Status, quorum and the end of voting are
+    /// left out, since every node sets them from its own policies.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let payload = ProposalSigningPayload {
+            domain: PROPOSAL_SIGNING_DOMAIN,
+            id: &self.id,
+            title: &self.title,
+            description: &self.description,
+            proposer: &self.proposer,
+            created_at: self.created_at.timestamp(),
+            proposal_type: &self.proposal_type,
+            category: &self.category,
+            weight_mode: &self.weight_mode,
+            actions: &self.actions,
+            scope: &self.scope,
+            ballot: &self.ballot,
+            depends_on: &self.depends_on,
+            bundle: self.bundle.as_ref(),
+        };
+        bincode::serialize(&payload).expect("proposal fields always serialize")
+    }
+
+    /// What a vote on this proposal is cast on. A vote on one member of a
+    /// bundle counts for all of them, so its signature names the bundle.
+    pub fn vote_subject(&self) -> String {
+        match &self.bundle {
+            Some(bundle) => format!("bundle:{}", bundle.id),
+            None => format!("proposal:{}", self.id),
+        }
+    }
+}
+
+/// Separates proposal signatures from signatures over anything else.
+const PROPOSAL_SIGNING_DOMAIN: &str = "icn-proposal-v1";
+
+/// What a proposer's signature covers; see `Proposal::signing_bytes`.
+#[derive(Serialize)]
+struct ProposalSigningPayload<'a> {
+    domain: &'static str,
+    id: &'a str,
+    title: &'a str,
+    description: &'a str,
+    proposer: &'a str,
+    created_at: i64,
+    proposal_type: &'a ProposalType,
+    category: &'a ProposalCategory,
+    weight_mode: &'a VotingWeightMode,
+    actions: &'a [ProposalAction],
+    scope: &'a ProposalScope,
+    ballot: &'a BallotKind,
+    depends_on: &'a [String],
+    bundle: Option<&'a ProposalBundle>,
+}
+
+/// Proposals that only make sense together. A vote on any of them counts
+/// for all of them, they pass only if every one passes, and they execute
+/// in the order listed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProposalBundle {
+    pub id: String,
+    pub members: Vec<String>,
+}
+
+/// Rules applied to proposals of a given type and category.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GovernancePolicy {
+    /// Minimum total vote weight for a proposal to be decided.
+    pub quorum: f64,
+    /// Share of the vote weight in favor that must be exceeded to pass.
+    pub approval_threshold: f64,
+    pub voting_duration_secs: i64,
+    /// Identities allowed to vote; `None` means every identity may vote.
+    pub eligible_voters: Option<Vec<String>>,
+    /// When set, reputation-weighted votes count the voter's reputation
+    /// averaged over this many seconds rather than its current value, so
+    /// a sudden gain carries little weight.
+    #[serde(default)]
+    pub reputation_window_secs: Option<i64>,
+    /// Withholds who voted how on an open proposal, showing only the
+    /// totals, so early votes don't sway later ones.
+    #[serde(default)]
+    pub hide_voters_until_finalized: bool,
+    /// How long a passed proposal waits before it can be executed, giving
+    /// members time to veto it. Zero executes it as soon as it passes.
+    #[serde(default)]
+    pub timelock_secs: i64,
+}
+
+impl Default for GovernancePolicy {
+    fn default() -> Self {
+        GovernancePolicy {
+            quorum: 0.51,
+            approval_threshold: 0.5,
+            voting_duration_secs: 7 * 24 * 60 * 60,
+            eligible_voters: None,
+            reputation_window_secs: None,
+            hide_voters_until_finalized: false,
+            timelock_secs: 0,
+        }
+    }
+}
+
+/// Limits above which the node's anomaly monitor raises alerts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AlertThresholds {
+    /// Single mint amount that is reported as unusually large.
+    pub large_mint_amount: f64,
+    /// Share of an account's recent peak balance that, once spent within
+    /// `drain_window_secs`, is reported as a drain.
+    pub drain_fraction: f64,
+    pub drain_window_secs: i64,
+    /// A vote weighing more than this multiple of the average weight cast
+    /// on the same proposal is reported as a spike.
+    pub vote_weight_spike_factor: f64,
+}
+
+impl Default for AlertThresholds {
+    fn default() -> Self {
+        AlertThresholds {
+            large_mint_amount: 100_000.0,
+            drain_fraction: 0.5,
+            drain_window_secs: 60 * 60,
+            vote_weight_spike_factor: 10.0,
+        }
+    }
+}
+
+impl AlertThresholds {
+    pub fn validate(&self) -> IcnResult<()> {
+        let finite = [self.large_mint_amount, self.drain_fraction, self.vote_weight_spike_factor]
+            .iter()
+            .all(|value| value.is_finite());
+        if !finite || self.large_mint_amount <= 0.0 || self.vote_weight_spike_factor <= 1.0 {
+            return Err(IcnError::Governance("Mint limit must be positive and the spike factor above 1".into()));
+        }
+        if self.drain_fraction <= 0.0 || self.drain_fraction > 1.0 || self.drain_window_secs <= 0 {
+            return Err(IcnError::Governance("Drain fraction must be in (0, 1] over a positive window".into()));
+        }
+        Ok(())
+    }
+}
+
+/// State changes carried by a proposal and applied when it is executed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ProposalAction {
+    /// Replaces the policy for a proposal type, optionally narrowed to a category.
+    /// Only valid on Constitutional proposals.
+    UpdatePolicy {
+        proposal_type: ProposalType,
+        category: Option<ProposalCategory>,
+        policy: GovernancePolicy,
+    },
+    /// Pauses or resumes transaction processing and contract execution
+    /// network-wide. Requires a supermajority to pass.
+    SetEmergencyPause {
+        paused: bool,
+        reason: String,
+    },
+    /// Approves replacing a governance-owned contract with the code whose
+    /// SHA-256 hash is given.
+    UpgradeContract {
+        contract_id: String,
+        code_hash: String,
+    },
+    /// Replaces the thresholds used by the anomaly monitor.
+    UpdateAlertThresholds {
+        thresholds: AlertThresholds,
+    },
+    /// Changes a parameter in the node's parameter registry. The proposal
+    /// must be of the type the parameter is governed by.
+    SetParameter {
+        name: String,
+        value: f64,
+    },
+    /// Restricts a contract function to identities holding one of `roles`,
+    /// or opens it to everyone when `roles` is `None`.
+    SetContractPermissions {
+        contract_id: String,
+        function: String,
+        roles: Option<Vec<String>>,
+    },
+    /// Sets the rate at which a currency is issued.
+    SetIssuanceRate {
+        currency_type: CurrencyType,
+        rate: f64,
+    },
+    /// Pays `amount` from the community fund to the recipient of a project.
+    FundProject {
+        project: String,
+        recipient: String,
+        amount: f64,
+        currency_type: CurrencyType,
+    },
+    /// Changes the number of shards on every node once the chain reaches
+    /// `activation_height`. Only valid on NetworkUpgrade proposals.
+    ReshardNetwork {
+        shard_count: u64,
+        activation_height: u64,
+    },
+    /// Stops an account from sending transactions or calling contracts for
+    /// `duration_secs`, after which the freeze lapses on its own.
+    FreezeAccount {
+        address: String,
+        duration_secs: i64,
+        reason: String,
+    },
+    /// Lifts a freeze before it expires.
+    UnfreezeAccount {
+        address: String,
+    },
+    /// Adds or changes a membership class of the DAO the proposal is scoped
+    /// to. Only valid on Constitutional proposals.
+    SetMembershipClass {
+        name: String,
+        vote_multiplier: f64,
+        can_propose: bool,
+        profit_share: f64,
+    },
+    /// Opens a grant round in the DAO the proposal is scoped to, with a
+    /// budget taken from the DAO's treasury. Only valid on economic
+    /// adjustment proposals.
+    OpenGrantRound {
+        title: String,
+        currency_type: CurrencyType,
+        budget: f64,
+        quadratic_matching: bool,
+        application_secs: i64,
+        voting_secs: i64,
+    },
+    /// Sets how many units of `quote` one unit of `base` is worth. Only
+    /// valid on economic adjustment proposals.
+    SetExchangeRate {
+        base: CurrencyType,
+        quote: CurrencyType,
+        rate: f64,
+    },
+    /// Lets an oracle feed exchange rates, or stops it. Only valid on
+    /// economic adjustment proposals.
+    SetRateOracle {
+        oracle: String,
+        authorized: bool,
+    },
+    /// Cancels a passed proposal that is still waiting out its timelock.
+    /// Needs a supermajority.
+    VetoProposal {
+        proposal_id: String,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,30 +538,141 @@ pub struct Vote {
     pub weight: f64,
     pub timestamp: i64,
     pub zkp: Option<Vec<u8>>,
+    /// The voter's signature over `signing_bytes`, which lets peers that
+    /// receive the vote second-hand check who cast it and when.
+    #[serde(default)]
+    pub signature: Option<Vec<u8>>,
+}
+
+impl Vote {
+    /// The bytes the voter signs for a vote on the proposal with
+    /// `Proposal::vote_subject` `subject`. The weight is left out, since
+    /// every node derives it for itself.
+    pub fn signing_bytes(&self, subject: &str) -> Vec<u8> {
+        let payload = (VOTE_SIGNING_DOMAIN, subject, &self.voter, self.in_favor, self.timestamp);
+        bincode::serialize(&payload).expect("vote fields always serialize")
+    }
 }
 
+/// Separates vote signatures from signatures over anything else.
+const VOTE_SIGNING_DOMAIN: &str = "icn-vote-v1";
+
+/// A ranked or approval ballot on a multi-option proposal, signed by its
+/// voter so peers can check who cast it. Receivers derive the weight
+/// themselves.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SignedBallot {
+    pub voter: String,
+    pub proposal_id: String,
+    pub choices: Vec<String>,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+impl SignedBallot {
+    /// The bytes the voter signs: the proposal, their choices in order and
+    /// when they cast the ballot.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let payload = (BALLOT_SIGNING_DOMAIN, &self.proposal_id, &self.voter, &self.choices, self.timestamp);
+        bincode::serialize(&payload).expect("ballot fields always serialize")
+    }
+}
+
+/// Separates ballot signatures from signatures over anything else.
+const BALLOT_SIGNING_DOMAIN: &str = "icn-ballot-v1";
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ProposalStatus {
     Active,
     Passed,
     Rejected,
     Executed,
+    /// Passed, then cancelled by a veto before it was executed.
+    Vetoed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ProposalType {
     Constitutional,
     EconomicAdjustment,
     NetworkUpgrade,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum ProposalCategory {
     Economic,
     Technical,
     Social,
 }
 
+/// How the node derives a voter's weight for a proposal. Weights are never
+/// taken from the client.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum VotingWeightMode {
+    /// Weight equals the voter's reputation in the identity service.
+    Reputation,
+    /// Weight equals the voter's shares in the given cooperative.
+    MembershipShares { dao_id: String },
+    /// Every registered identity votes with a weight of 1.
+    #[default]
+    OneMemberOneVote,
+}
+
+/// The part of the network a proposal governs and whose members vote on it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ProposalScope {
+    /// Every identity may vote and any action may be proposed.
+    #[default]
+    Global,
+    /// Decided by the members of a cooperative under its own quorum and
+    /// majority.
+    Dao(String),
+    /// Decided by the identities whose accounts live on a shard.
+    Shard(u64),
+}
+
+impl ProposalScope {
+    pub fn is_global(&self) -> bool {
+        matches!(self, ProposalScope::Global)
+    }
+
+    /// Name a parameter set by a proposal in this scope is stored under,
+    /// so DAOs and shards cannot overwrite each other's settings.
+    pub fn parameter_key(&self, name: &str) -> String {
+        match self {
+            ProposalScope::Global => name.to_string(),
+            ProposalScope::Dao(dao_id) => format!("dao:{}:{}", dao_id, name),
+            ProposalScope::Shard(shard_id) => format!("shard:{}:{}", shard_id, name),
+        }
+    }
+}
+
+/// How votes on a proposal are cast and counted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub enum BallotKind {
+    /// Votes for or against, weighed against the approval threshold.
+    #[default]
+    YesNo,
+    /// Voters rank the options and an instant runoff picks the winner.
+    RankedChoice { options: Vec<String> },
+    /// Voters approve any number of options and the most approved wins.
+    Approval { options: Vec<String> },
+}
+
+impl BallotKind {
+    /// The options to choose between, empty for yes/no proposals.
+    pub fn options(&self) -> &[String] {
+        match self {
+            BallotKind::YesNo => &[],
+            BallotKind::RankedChoice { options } | BallotKind::Approval { options } => options,
+        }
+    }
+
+    pub fn is_multi_option(&self) -> bool {
+        !matches!(self, BallotKind::YesNo)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum CurrencyType {
     BasicNeeds,
@@ -122,6 +680,16 @@ pub enum CurrencyType {
     Environmental,
     Community,
     Custom(String),
+    /// Pays for data kept in the storage network.
+    Storage,
+    /// Units of the bond with the given id.
+    Bond(String),
+    /// Units of the asset token with the given id.
+    AssetToken(String),
+    /// Earned by volunteering, for example by attending community events.
+    Volunteer,
+    /// Earned by verified work done for a cooperative.
+    Service,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -129,6 +697,28 @@ pub struct NetworkStats {
     pub node_count: usize,
     pub total_transactions: usize,
     pub active_proposals: usize,
+    #[serde(default)]
+    pub bytes_sent: u64,
+    #[serde(default)]
+    pub bytes_received: u64,
+    /// Traffic with each connected peer, by address.
+    #[serde(default)]
+    pub peer_traffic: BTreeMap<String, PeerTraffic>,
+}
+
+/// Messages exchanged with one peer. Handshakes are not counted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PeerTraffic {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    /// Inbound messages dropped for arriving over the peer's limits.
+    pub messages_dropped: u64,
+    /// Outbound messages not sent because the peer's send cap was spent.
+    pub messages_deferred: u64,
+    /// Consecutive rate windows the peer has gone over its limits in.
+    pub strikes: u32,
 }
 
 #[cfg(test)]
@@ -140,24 +730,86 @@ mod tests {
         let tx1 = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 50.0,
+            amount: Amount::from(50.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: 0,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         let tx2 = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 50.0,
+            amount: Amount::from(50.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: 0,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         assert_eq!(tx1, tx2);
     }
 
+    #[test]
+    fn test_transaction_hash_ignores_signature() {
+        let mut tx = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, 0);
+        let unsigned_hash = tx.hash();
+        assert_eq!(unsigned_hash.len(), 64);
+
+        tx.signature = Some(vec![1, 2, 3]);
+        assert_eq!(tx.hash(), unsigned_hash);
+
+        tx.amount = Amount::from(51.0);
+        assert_ne!(tx.hash(), unsigned_hash);
+
+        // A memo is covered by the hash, whether carried inline or hashed
+        let with_memo = tx.clone().with_memo(TransactionMemo::Inline("INV-7".to_string()));
+        assert_ne!(with_memo.hash(), tx.hash());
+        let moved_off_chain = tx.clone().with_memo(TransactionMemo::hashed("INV-7").unwrap());
+        assert_eq!(moved_off_chain.hash(), with_memo.hash());
+        assert_eq!(moved_off_chain.signing_bytes(), with_memo.signing_bytes());
+
+        // So is an expiry, which ends after its last valid second
+        let expiring = tx.clone().with_valid_until(100);
+        assert_ne!(expiring.hash(), tx.hash());
+        assert_ne!(expiring.signing_bytes(), tx.signing_bytes());
+        assert!(!expiring.is_expired(100));
+        assert!(expiring.is_expired(101));
+        assert!(!tx.is_expired(i64::MAX));
+    }
+
+    #[test]
+    fn test_signed_transaction_round_trip() {
+        use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+
+        let secret = SecretKey::from_bytes(&[7; 32]).unwrap();
+        let keypair = Keypair { public: PublicKey::from(&secret), secret };
+        let sender = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        let mut tx = Transaction::new(sender, "Bob".to_string(), 5.0, CurrencyType::BasicNeeds, 1_700_000_000);
+        tx.sign(&keypair).unwrap();
+        assert!(tx.verify().unwrap());
+
+        // Every field is covered, including the currency
+        let mut other_currency = tx.clone();
+        other_currency.currency_type = CurrencyType::Education;
+        assert!(other_currency.verify().is_err());
+
+        // Moving characters across a field boundary changes what is signed
+        let shifted = Transaction::new("ab".to_string(), "c".to_string(), 5.0, CurrencyType::BasicNeeds, 0);
+        let unshifted = Transaction::new("a".to_string(), "bc".to_string(), 5.0, CurrencyType::BasicNeeds, 0);
+        assert_ne!(shifted.signing_bytes(), unshifted.signing_bytes());
+
+        let mut not_a_did = Transaction::new("Alice".to_string(), "Bob".to_string(), 5.0, CurrencyType::BasicNeeds, 0);
+        not_a_did.sign(&keypair).unwrap();
+        assert!(not_a_did.verify().is_err());
+    }
+
     #[test]
     fn test_currency_type_equality() {
         assert_eq!(CurrencyType::BasicNeeds, CurrencyType::BasicNeeds);
@@ -177,6 +829,9 @@ mod tests {
             node_count: 5,
             total_transactions: 100,
             active_proposals: 3,
+            bytes_sent: 0,
+            bytes_received: 0,
+            peer_traffic: BTreeMap::new(),
         };
         assert_eq!(stats.node_count, 5);
         assert_eq!(stats.total_transactions, 100);
@@ -0,0 +1,88 @@
+// File: crates/icn_common/src/limits.rs
+
+use crate::{IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+
+/// Caps on how large transactions and blocks may get, so a single oversized
+/// item can't stall block production or peers relaying it.
+///
+/// Sizes are measured on the JSON encoding, the same one block hashes and
+/// merkle roots are computed over.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SizeLimits {
+    pub max_transaction_bytes: usize,
+    pub max_transactions_per_block: usize,
+    pub max_block_bytes: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        SizeLimits {
+            max_transaction_bytes: 64 * 1024,
+            max_transactions_per_block: 1_000,
+            max_block_bytes: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// Length of a value's JSON encoding in bytes.
+pub fn encoded_size<T: Serialize>(value: &T) -> IcnResult<usize> {
+    Ok(serde_json::to_vec(value)?.len())
+}
+
+impl SizeLimits {
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.max_transaction_bytes == 0 || self.max_transactions_per_block == 0 {
+            return Err(IcnError::Config("Size limits must be greater than zero".into()));
+        }
+        if self.max_block_bytes < self.max_transaction_bytes {
+            return Err(IcnError::Config("A block must be able to hold at least one transaction".into()));
+        }
+        Ok(())
+    }
+
+    pub fn check_transaction<T: Serialize>(&self, transaction: &T) -> IcnResult<usize> {
+        let size = encoded_size(transaction)?;
+        if size > self.max_transaction_bytes {
+            return Err(IcnError::Validation(format!(
+                "Transaction of {} bytes exceeds the limit of {} bytes", size, self.max_transaction_bytes
+            )));
+        }
+        Ok(size)
+    }
+
+    /// Checks a block's transaction count and encoded size.
+    pub fn check_block<B: Serialize>(&self, block: &B, transaction_count: usize) -> IcnResult<()> {
+        if transaction_count > self.max_transactions_per_block {
+            return Err(IcnError::Validation(format!(
+                "Block with {} transactions exceeds the limit of {}", transaction_count, self.max_transactions_per_block
+            )));
+        }
+        let size = encoded_size(block)?;
+        if size > self.max_block_bytes {
+            return Err(IcnError::Validation(format!(
+                "Block of {} bytes exceeds the limit of {} bytes", size, self.max_block_bytes
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_limits() {
+        let limits = SizeLimits { max_transaction_bytes: 16, max_transactions_per_block: 2, max_block_bytes: 64 };
+        assert!(limits.validate().is_ok());
+        assert!(limits.check_transaction(&"short").is_ok());
+        assert!(limits.check_transaction(&"a string well over sixteen bytes").is_err());
+
+        assert!(limits.check_block(&vec!["a", "b"], 2).is_ok());
+        assert!(limits.check_block(&vec!["a", "b", "c"], 3).is_err());
+        assert!(limits.check_block(&vec!["x".repeat(40), "y".repeat(40)], 2).is_err());
+
+        assert!(SizeLimits { max_block_bytes: 8, ..limits }.validate().is_err());
+    }
+}
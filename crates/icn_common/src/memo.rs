@@ -0,0 +1,89 @@
+// File: crates/icn_common/src/memo.rs
+
+use crate::{IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+/// Longest memo that may be carried on chain in full.
+pub const MAX_MEMO_BYTES: usize = 256;
+
+/// Longest memo the node keeps in storage when only its hash goes on chain.
+pub const MAX_STORED_MEMO_BYTES: usize = 16 * 1024;
+
+/// Data attached to a transfer, such as an invoice number or a purpose code.
+///
+/// Signatures and transaction hashes cover the memo's digest, which is the
+/// same for both forms, so a memo can be moved off chain without breaking
+/// either.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TransactionMemo {
+    /// The memo itself, at most `MAX_MEMO_BYTES` long.
+    Inline(String),
+    /// Hex SHA-256 of a memo kept in storage under `memo_storage_key`.
+    Hashed(String),
+}
+
+impl TransactionMemo {
+    /// Hashes a memo too large or too private to put on chain. The caller
+    /// stores the content.
+    pub fn hashed(content: &str) -> IcnResult<Self> {
+        if content.len() > MAX_STORED_MEMO_BYTES {
+            return Err(IcnError::Validation(format!(
+                "Memo of {} bytes exceeds the limit of {} bytes", content.len(), MAX_STORED_MEMO_BYTES
+            )));
+        }
+        Ok(TransactionMemo::Hashed(format!("{:x}", Sha256::digest(content.as_bytes()))))
+    }
+
+    /// Hex SHA-256 of the memo's content.
+    pub fn digest(&self) -> String {
+        match self {
+            TransactionMemo::Inline(content) => format!("{:x}", Sha256::digest(content.as_bytes())),
+            TransactionMemo::Hashed(hash) => hash.clone(),
+        }
+    }
+
+    /// The memo's content, if it is carried on chain.
+    pub fn content(&self) -> Option<&str> {
+        match self {
+            TransactionMemo::Inline(content) => Some(content),
+            TransactionMemo::Hashed(_) => None,
+        }
+    }
+
+    pub fn validate(&self) -> IcnResult<()> {
+        match self {
+            TransactionMemo::Inline(content) if content.len() > MAX_MEMO_BYTES => Err(IcnError::Validation(format!(
+                "Memo of {} bytes exceeds the on-chain limit of {} bytes; store it off chain instead", content.len(), MAX_MEMO_BYTES
+            ))),
+            TransactionMemo::Hashed(hash) if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) => {
+                Err(IcnError::Validation("Memo hash must be a hex SHA-256 digest".into()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Storage key the full content of a hashed memo is kept under.
+pub fn memo_storage_key(hash: &str) -> String {
+    format!("memo:{}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memo_limits_and_digest() {
+        let inline = TransactionMemo::Inline("INV-2024-0042".to_string());
+        assert!(inline.validate().is_ok());
+        assert!(TransactionMemo::Inline("x".repeat(MAX_MEMO_BYTES + 1)).validate().is_err());
+        assert!(TransactionMemo::Hashed("not a hash".to_string()).validate().is_err());
+
+        let hashed = TransactionMemo::hashed("INV-2024-0042").unwrap();
+        assert!(hashed.validate().is_ok());
+        assert_eq!(hashed.digest(), inline.digest());
+        assert_eq!(hashed.content(), None);
+        assert!(TransactionMemo::hashed(&"x".repeat(MAX_STORED_MEMO_BYTES + 1)).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+// File: crates/icn_common/src/merkle.rs
+
+use crate::{IcnError, IcnResult, Transaction};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// Merkle root of a block without transactions.
+pub const EMPTY_MERKLE_ROOT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// A block without its transactions: enough to follow the chain and check
+/// merkle proofs against.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockHeader {
+    pub index: u64,
+    pub timestamp: i64,
+    pub previous_hash: String,
+    pub hash: String,
+    pub nonce: u64,
+    pub merkle_root: String,
+    #[serde(default)]
+    pub state_root: String,
+    /// Commitment to the double-sign evidence in the block, empty if none.
+    #[serde(default)]
+    pub evidence_root: String,
+    /// The validator that produced the block, empty for blocks decided
+    /// without consensus rounds.
+    #[serde(default)]
+    pub producer: String,
+    /// The producer's signature over `block_producer_message`.
+    #[serde(default)]
+    pub producer_signature: Vec<u8>,
+}
+
+impl BlockHeader {
+    /// Hashes the header the same way full nodes hash blocks.
+    pub fn calculate_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.index.to_string());
+        hasher.update(self.timestamp.to_string());
+        hasher.update(&self.merkle_root);
+        hasher.update(&self.state_root);
+        hasher.update(&self.evidence_root);
+        hasher.update(&self.previous_hash);
+        hasher.update(self.nonce.to_string());
+        format!("{:x}", hasher.finalize())
+    }
+}
+
+/// Where a client following the chain left off: the last header it holds.
+/// Written as `<index>:<hash>`, so a client that reconnects can pick up
+/// from there and notice if the node's chain no longer has that block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumeToken {
+    pub index: u64,
+    pub hash: String,
+}
+
+impl ResumeToken {
+    pub fn after(header: &BlockHeader) -> Self {
+        ResumeToken { index: header.index, hash: header.hash.clone() }
+    }
+
+    pub fn parse(token: &str) -> IcnResult<Self> {
+        let (index, hash) = token.split_once(':')
+            .ok_or_else(|| IcnError::Validation(format!("Invalid resume token: {}", token)))?;
+        let index = index.parse()
+            .map_err(|_| IcnError::Validation(format!("Invalid resume token: {}", token)))?;
+        Ok(ResumeToken { index, hash: hash.to_string() })
+    }
+}
+
+impl fmt::Display for ResumeToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.index, self.hash)
+    }
+}
+
+/// Headers pushed to a client following the chain, with the token that
+/// resumes after the last of them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HeaderBatch {
+    pub headers: Vec<BlockHeader>,
+    pub resume_token: String,
+    /// Whether the node already has more headers past these.
+    pub more: bool,
+}
+
+/// Leaf a transaction contributes to its block's merkle tree: the SHA-256
+/// of its JSON encoding.
+pub fn transaction_leaf<T: Serialize>(transaction: &T) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_string(transaction).unwrap_or_default().as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Hashes leaves pairwise up to a single root. A level with an odd number
+/// of nodes pairs its last node with itself.
+pub fn merkle_root(leaves: &[String]) -> String {
+    if leaves.is_empty() {
+        return EMPTY_MERKLE_ROOT.to_string();
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level.chunks(2)
+            .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+            .collect();
+    }
+    level.remove(0)
+}
+
+fn hash_pair(left: &str, right: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// The sibling hashes linking one leaf to its merkle root, from the leaf up.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub siblings: Vec<String>,
+}
+
+impl MerkleProof {
+    /// Builds the proof for `leaves[leaf_index]`.
+    pub fn build(leaves: &[String], leaf_index: usize) -> Option<Self> {
+        if leaf_index >= leaves.len() {
+            return None;
+        }
+        let mut siblings = Vec::new();
+        let mut level = leaves.to_vec();
+        let mut index = leaf_index;
+        while level.len() > 1 {
+            let sibling = if index.is_multiple_of(2) { level.get(index + 1).unwrap_or(&level[index]) } else { &level[index - 1] };
+            siblings.push(sibling.clone());
+            level = level.chunks(2)
+                .map(|pair| hash_pair(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            index /= 2;
+        }
+        Some(MerkleProof { leaf_index, siblings })
+    }
+
+    /// Checks that `leaf` sits at this proof's position under `root`.
+    pub fn verify(&self, leaf: &str, root: &str) -> bool {
+        let mut hash = leaf.to_string();
+        let mut index = self.leaf_index;
+        for sibling in &self.siblings {
+            hash = if index.is_multiple_of(2) { hash_pair(&hash, sibling) } else { hash_pair(sibling, &hash) };
+            index /= 2;
+        }
+        hash == root
+    }
+}
+
+/// A transaction with the proof that it is included in a block, as served
+/// to light clients.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionProof {
+    pub transaction: Transaction,
+    pub block_index: u64,
+    pub block_hash: String,
+    pub proof: MerkleProof,
+}
+
+impl TransactionProof {
+    /// Checks the proof against a header the caller already trusts.
+    pub fn verify(&self, header: &BlockHeader) -> bool {
+        header.index == self.block_index
+            && header.hash == self.block_hash
+            && self.proof.verify(&transaction_leaf(&self.transaction), &header.merkle_root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_proofs_verify_for_every_leaf() {
+        for count in 1..=7 {
+            let leaves: Vec<String> = (0..count).map(|i| format!("{:064x}", i)).collect();
+            let root = merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = MerkleProof::build(&leaves, index).unwrap();
+                assert!(proof.verify(leaf, &root));
+                assert!(!proof.verify(&format!("{:064x}", 99), &root));
+            }
+        }
+        assert_eq!(merkle_root(&[]), EMPTY_MERKLE_ROOT);
+        assert!(MerkleProof::build(&[], 0).is_none());
+    }
+
+    #[test]
+    fn test_resume_token_round_trip() {
+        let token = ResumeToken { index: 42, hash: "ab12".to_string() };
+        assert_eq!(token.to_string(), "42:ab12");
+        assert_eq!(ResumeToken::parse("42:ab12").unwrap(), token);
+        assert!(ResumeToken::parse("ab12").is_err());
+        assert!(ResumeToken::parse("tip:ab12").is_err());
+    }
+}
@@ -0,0 +1,281 @@
+// File: crates/icn_common/src/params.rs
+
+use crate::{IcnError, IcnResult, ProposalScope, ProposalType};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// Number of leading zeros a block hash needs.
+pub const PARAM_DIFFICULTY: &str = "difficulty";
+/// Most transactions packed into one block.
+pub const PARAM_MAX_BLOCK_SIZE: &str = "max_block_size";
+/// Voting period of proposals whose type has no policy of its own.
+pub const PARAM_VOTING_DURATION_SECS: &str = "voting_duration_secs";
+/// Floor of the transaction base fee.
+pub const PARAM_MIN_BASE_FEE: &str = "min_base_fee";
+/// Share of a transferred amount charged on top of the base fee.
+pub const PARAM_FEE_AMOUNT_RATE: &str = "fee_amount_rate";
+/// Validators voting on each block, 0 for all of them.
+pub const PARAM_COMMITTEE_SIZE: &str = "committee_size";
+
+// DAOs set these for themselves through proposals scoped to the DAO.
+
+/// Currency minted per hour of verified contribution.
+pub const PARAM_CONTRIBUTION_HOURLY_RATE: &str = "contribution_hourly_rate";
+/// Currency minted per verified task.
+pub const PARAM_CONTRIBUTION_TASK_RATE: &str = "contribution_task_rate";
+/// Most one member can be paid for contributions per period, 0 for no cap.
+pub const PARAM_CONTRIBUTION_PERIOD_CAP: &str = "contribution_period_cap";
+/// Length of the contribution cap period in days.
+pub const PARAM_CONTRIBUTION_PERIOD_DAYS: &str = "contribution_period_days";
+
+/// A network parameter, the range it may take and which kind of proposal
+/// may change it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParameterDefinition {
+    pub name: String,
+    pub description: String,
+    pub value: f64,
+    pub min: f64,
+    pub max: f64,
+    /// Whether the value must be a whole number.
+    pub integer: bool,
+    pub changed_by: ProposalType,
+}
+
+/// One change made to a parameter by an executed proposal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParameterChange {
+    pub name: String,
+    pub old_value: f64,
+    pub new_value: f64,
+    pub proposal_id: String,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// On-chain registry of the parameters subsystems read instead of
+/// hard-coded constants.
+///
+/// Values are seeded from local configuration when a node starts and after
+/// that only change through executed proposals of the type each parameter
+/// names, with every change kept in the history. Proposals scoped to a DAO
+/// or shard set that scope's own parameters instead, which are free-form.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ParameterRegistry {
+    parameters: BTreeMap<String, ParameterDefinition>,
+    history: Vec<ParameterChange>,
+    // Values set by scoped proposals, by `ProposalScope::parameter_key`
+    #[serde(default)]
+    scoped: BTreeMap<String, f64>,
+}
+
+impl ParameterRegistry {
+    pub fn new() -> Self {
+        let mut registry = ParameterRegistry {
+            parameters: BTreeMap::new(),
+            history: Vec::new(),
+            scoped: BTreeMap::new(),
+        };
+        registry.define(PARAM_DIFFICULTY, "Leading zeros required in block hashes", 4.0, 0.0, 32.0, true, ProposalType::NetworkUpgrade);
+        registry.define(PARAM_MAX_BLOCK_SIZE, "Maximum transactions per block", 100.0, 1.0, 10_000.0, true, ProposalType::NetworkUpgrade);
+        registry.define(PARAM_VOTING_DURATION_SECS, "Default proposal voting period in seconds", 7.0 * 24.0 * 60.0 * 60.0, 60.0, 90.0 * 24.0 * 60.0 * 60.0, true, ProposalType::NetworkUpgrade);
+        registry.define(PARAM_MIN_BASE_FEE, "Lowest base fee charged per transaction", 0.01, 0.0, 1_000.0, false, ProposalType::EconomicAdjustment);
+        registry.define(PARAM_FEE_AMOUNT_RATE, "Share of the transferred amount charged as a fee", 0.0, 0.0, 0.1, false, ProposalType::EconomicAdjustment);
+        registry.define(PARAM_COMMITTEE_SIZE, "Validators voting on each block, 0 for all of them", 0.0, 0.0, 1_000.0, true, ProposalType::NetworkUpgrade);
+        registry
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn define(&mut self, name: &str, description: &str, value: f64, min: f64, max: f64, integer: bool, changed_by: ProposalType) {
+        self.parameters.insert(name.to_string(), ParameterDefinition {
+            name: name.to_string(),
+            description: description.to_string(),
+            value,
+            min,
+            max,
+            integer,
+            changed_by,
+        });
+    }
+
+    pub fn get(&self, name: &str) -> IcnResult<f64> {
+        self.definition(name).map(|definition| definition.value)
+    }
+
+    /// Reads a whole-number parameter such as a size or a count.
+    pub fn get_usize(&self, name: &str) -> IcnResult<usize> {
+        self.get(name).map(|value| value as usize)
+    }
+
+    pub fn definition(&self, name: &str) -> IcnResult<&ParameterDefinition> {
+        self.parameters.get(name)
+            .ok_or_else(|| IcnError::Config(format!("Unknown parameter: {}", name)))
+    }
+
+    pub fn list(&self) -> Vec<&ParameterDefinition> {
+        self.parameters.values().collect()
+    }
+
+    /// Changes made so far, oldest first, optionally for a single parameter.
+    pub fn history(&self, name: Option<&str>) -> Vec<&ParameterChange> {
+        self.history.iter()
+            .filter(|change| name.is_none_or(|name| change.name == name))
+            .collect()
+    }
+
+    /// Checks that a proposal of the given type may set `name` to `value`.
+    pub fn validate_change(&self, name: &str, value: f64, proposal_type: &ProposalType) -> IcnResult<()> {
+        let definition = self.definition(name)?;
+        if &definition.changed_by != proposal_type {
+            return Err(IcnError::Governance(format!(
+                "Parameter {} can only be changed by {:?} proposals", name, definition.changed_by
+            )));
+        }
+        definition.check_value(value)
+    }
+
+    /// Sets an initial value without recording a change. Only used while
+    /// the node is being configured.
+    pub fn seed(&mut self, name: &str, value: f64) -> IcnResult<()> {
+        self.definition(name)?.check_value(value)?;
+        self.parameters.get_mut(name).expect("parameter checked above").value = value;
+        Ok(())
+    }
+
+    /// Applies a change made by an executed proposal and records it.
+    pub fn apply_change(&mut self, name: &str, value: f64, proposal_type: &ProposalType, proposal_id: &str, now: DateTime<Utc>) -> IcnResult<ParameterChange> {
+        self.validate_change(name, value, proposal_type)?;
+        let definition = self.parameters.get_mut(name).expect("parameter checked above");
+        let change = ParameterChange {
+            name: name.to_string(),
+            old_value: definition.value,
+            new_value: value,
+            proposal_id: proposal_id.to_string(),
+            changed_at: now,
+        };
+        definition.value = value;
+        self.history.push(change.clone());
+        Ok(change)
+    }
+
+    /// Takes back the changes recorded in a registry saved earlier.
+    /// Parameters no proposal has changed keep their seeded values.
+    pub fn restore(&mut self, saved: ParameterRegistry) {
+        for change in &saved.history {
+            if let Some(definition) = self.parameters.get_mut(&change.name) {
+                definition.value = change.new_value;
+            }
+        }
+        self.history = saved.history;
+        self.scoped = saved.scoped;
+    }
+
+    /// A parameter of a DAO or shard, if one of its proposals has set it.
+    pub fn get_scoped(&self, scope: &ProposalScope, name: &str) -> Option<f64> {
+        self.scoped.get(&scope.parameter_key(name)).copied()
+    }
+
+    /// Checks a change made by a scoped proposal. Such proposals can never
+    /// reach the network parameters.
+    pub fn validate_scoped_change(&self, scope: &ProposalScope, name: &str, value: f64) -> IcnResult<()> {
+        if scope.is_global() {
+            return Err(IcnError::Governance("Global proposals change network parameters".into()));
+        }
+        if name.trim().is_empty() || !value.is_finite() {
+            return Err(IcnError::Config("Scoped parameters need a name and a finite value".into()));
+        }
+        Ok(())
+    }
+
+    /// Applies a change made by an executed scoped proposal. The history
+    /// records it under the scoped key.
+    pub fn apply_scoped_change(&mut self, scope: &ProposalScope, name: &str, value: f64, proposal_id: &str, now: DateTime<Utc>) -> IcnResult<ParameterChange> {
+        self.validate_scoped_change(scope, name, value)?;
+        let key = scope.parameter_key(name);
+        let change = ParameterChange {
+            name: key.clone(),
+            old_value: self.scoped.get(&key).copied().unwrap_or_default(),
+            new_value: value,
+            proposal_id: proposal_id.to_string(),
+            changed_at: now,
+        };
+        self.scoped.insert(key, value);
+        self.history.push(change.clone());
+        Ok(change)
+    }
+}
+
+impl ParameterDefinition {
+    fn check_value(&self, value: f64) -> IcnResult<()> {
+        if !value.is_finite() || value < self.min || value > self.max {
+            return Err(IcnError::Config(format!(
+                "{} must be between {} and {}", self.name, self.min, self.max
+            )));
+        }
+        if self.integer && value.fract() != 0.0 {
+            return Err(IcnError::Config(format!("{} must be a whole number", self.name)));
+        }
+        Ok(())
+    }
+}
+
+impl Default for ParameterRegistry {
+    fn default() -> Self {
+        ParameterRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parameter_changes() {
+        let mut registry = ParameterRegistry::new();
+        registry.seed(PARAM_DIFFICULTY, 2.0).unwrap();
+        assert_eq!(registry.get_usize(PARAM_DIFFICULTY).unwrap(), 2);
+        assert!(registry.history(None).is_empty());
+
+        assert!(registry.apply_change(PARAM_DIFFICULTY, 3.0, &ProposalType::EconomicAdjustment, "p1", Utc::now()).is_err());
+        assert!(registry.apply_change(PARAM_DIFFICULTY, 2.5, &ProposalType::NetworkUpgrade, "p1", Utc::now()).is_err());
+        assert!(registry.apply_change(PARAM_FEE_AMOUNT_RATE, 0.5, &ProposalType::EconomicAdjustment, "p1", Utc::now()).is_err());
+        assert!(registry.apply_change("block_reward", 1.0, &ProposalType::EconomicAdjustment, "p1", Utc::now()).is_err());
+
+        let change = registry.apply_change(PARAM_DIFFICULTY, 3.0, &ProposalType::NetworkUpgrade, "p1", Utc::now()).unwrap();
+        assert_eq!((change.old_value, change.new_value), (2.0, 3.0));
+        registry.apply_change(PARAM_MIN_BASE_FEE, 0.05, &ProposalType::EconomicAdjustment, "p2", Utc::now()).unwrap();
+
+        assert_eq!(registry.get(PARAM_MIN_BASE_FEE).unwrap(), 0.05);
+        assert_eq!(registry.history(None).len(), 2);
+        assert_eq!(registry.history(Some(PARAM_DIFFICULTY))[0].proposal_id, "p1");
+    }
+
+    #[test]
+    fn test_restore_keeps_governance_changes() {
+        let mut saved = ParameterRegistry::new();
+        saved.apply_change(PARAM_MIN_BASE_FEE, 0.05, &ProposalType::EconomicAdjustment, "p1", Utc::now()).unwrap();
+        saved.apply_scoped_change(&ProposalScope::Dao("bakery".to_string()), "dues", 12.0, "p2", Utc::now()).unwrap();
+        let saved: ParameterRegistry = serde_json::from_slice(&serde_json::to_vec(&saved).unwrap()).unwrap();
+
+        let mut registry = ParameterRegistry::new();
+        registry.seed(PARAM_DIFFICULTY, 2.0).unwrap();
+        registry.restore(saved);
+        assert_eq!(registry.get(PARAM_MIN_BASE_FEE).unwrap(), 0.05);
+        assert_eq!(registry.get(PARAM_DIFFICULTY).unwrap(), 2.0);
+        assert_eq!(registry.get_scoped(&ProposalScope::Dao("bakery".to_string()), "dues"), Some(12.0));
+        assert_eq!(registry.history(None).len(), 2);
+    }
+
+    #[test]
+    fn test_scoped_parameters_kept_apart() {
+        let mut registry = ParameterRegistry::new();
+        let bakery = ProposalScope::Dao("bakery".to_string());
+        assert!(registry.apply_scoped_change(&ProposalScope::Global, PARAM_DIFFICULTY, 8.0, "p1", Utc::now()).is_err());
+
+        registry.apply_scoped_change(&bakery, PARAM_DIFFICULTY, 8.0, "p1", Utc::now()).unwrap();
+        assert_eq!(registry.get_scoped(&bakery, PARAM_DIFFICULTY), Some(8.0));
+        assert_eq!(registry.get_scoped(&ProposalScope::Shard(0), PARAM_DIFFICULTY), None);
+        assert_eq!(registry.get(PARAM_DIFFICULTY).unwrap(), 4.0);
+        assert_eq!(registry.history(Some("dao:bakery:difficulty")).len(), 1);
+    }
+}
@@ -0,0 +1,69 @@
+// File: crates/icn_common/src/rounds.rs
+
+use serde::{Serialize, Deserialize};
+
+/// The two votes validators cast in every round of consensus on a height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VoteStep {
+    /// A first vote on the round's proposal.
+    Prevote,
+    /// A vote to commit, cast once enough of the committee prevoted.
+    Precommit,
+}
+
+/// A validator's signed vote in one round of consensus.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsensusVote {
+    pub validator: String,
+    pub height: u64,
+    pub round: u32,
+    pub step: VoteStep,
+    /// The block voted for, or `None` for no block this round.
+    pub block_hash: Option<String>,
+    /// Signature over `consensus_vote_message`.
+    pub signature: Vec<u8>,
+}
+
+impl ConsensusVote {
+    pub fn message(&self) -> Vec<u8> {
+        consensus_vote_message(self.height, self.round, self.step, self.block_hash.as_deref())
+    }
+}
+
+/// A round's proposer putting forward the block with `block_hash`. The
+/// block itself travels alongside.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConsensusProposal {
+    pub proposer: String,
+    pub height: u64,
+    pub round: u32,
+    pub block_hash: String,
+    /// The earlier round in which the committee prevoted for this block,
+    /// when the proposer is re-proposing it.
+    pub valid_round: Option<u32>,
+    /// Signature over `consensus_proposal_message`.
+    pub signature: Vec<u8>,
+}
+
+impl ConsensusProposal {
+    pub fn message(&self) -> Vec<u8> {
+        consensus_proposal_message(self.height, self.round, &self.block_hash, self.valid_round)
+    }
+}
+
+/// The bytes a validator signs to cast a vote.
+pub fn consensus_vote_message(height: u64, round: u32, step: VoteStep, block_hash: Option<&str>) -> Vec<u8> {
+    format!("icn-consensus-vote:{}:{}:{:?}:{}", height, round, step, block_hash.unwrap_or("nil")).into_bytes()
+}
+
+/// The bytes a proposer signs to propose a block.
+pub fn consensus_proposal_message(height: u64, round: u32, block_hash: &str, valid_round: Option<u32>) -> Vec<u8> {
+    let valid_round = valid_round.map_or_else(|| "none".to_string(), |round| round.to_string());
+    format!("icn-consensus-proposal:{}:{}:{}:{}", height, round, block_hash, valid_round).into_bytes()
+}
+
+/// The bytes a block's producer signs, carried in the block header. The
+/// hash doesn't cover the signature, so signing leaves it unchanged.
+pub fn block_producer_message(height: u64, block_hash: &str) -> Vec<u8> {
+    format!("icn-block-producer:{}:{}", height, block_hash).into_bytes()
+}
@@ -0,0 +1,54 @@
+// File: crates/icn_common/src/sortition.rs
+
+use sha2::{Digest, Sha256};
+
+/// Picks up to `size` candidates with chances in proportion to their
+/// weight, the same way on every node for the same `seed`. `size` 0 picks
+/// every candidate. Candidates without weight are never picked.
+///
+/// Each candidate gets the key `-ln(u) / weight` for a `u` drawn from the
+/// seed and its id, and the lowest keys win, which samples by weight
+/// without replacement. The picked ids are returned sorted.
+pub fn weighted_sample(seed: &[u8], candidates: &[(String, f64)], size: usize) -> Vec<String> {
+    let mut keyed: Vec<(f64, &String)> = candidates.iter()
+        .filter(|(_, weight)| *weight > 0.0)
+        .map(|(id, weight)| (-draw(seed, id).ln() / weight, id))
+        .collect();
+    if size > 0 && size < keyed.len() {
+        keyed.sort_by(|a, b| a.0.total_cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        keyed.truncate(size);
+    }
+    let mut picked: Vec<String> = keyed.into_iter().map(|(_, id)| id.clone()).collect();
+    picked.sort();
+    picked
+}
+
+/// A number in (0, 1] derived from the seed and a candidate id.
+fn draw(seed: &[u8], id: &str) -> f64 {
+    let mut hasher = Sha256::new();
+    hasher.update(seed);
+    hasher.update(id.as_bytes());
+    let digest = hasher.finalize();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    // The top 53 bits fill an f64 mantissa exactly
+    ((u64::from_be_bytes(bytes) >> 11) + 1) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_sample() {
+        let candidates: Vec<(String, f64)> = (0..20).map(|i| (format!("member{}", i), 1.0 + i as f64)).collect();
+        let picked = weighted_sample(b"seed", &candidates, 5);
+        assert_eq!(picked.len(), 5);
+        assert_eq!(picked, weighted_sample(b"seed", &candidates, 5));
+        assert_ne!(picked, weighted_sample(b"other seed", &candidates, 5));
+        assert_eq!(weighted_sample(b"seed", &candidates, 0).len(), 20);
+
+        let with_unweighted = vec![("a".to_string(), 1.0), ("b".to_string(), 0.0)];
+        assert_eq!(weighted_sample(b"seed", &with_unweighted, 2), vec!["a".to_string()]);
+    }
+}
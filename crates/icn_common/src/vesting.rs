@@ -0,0 +1,90 @@
+// File: crates/icn_common/src/vesting.rs
+
+use crate::{Amount, CurrencyType, IcnError, IcnResult};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+
+/// Funds credited to a beneficiary that unlock linearly over time.
+///
+/// Nothing unlocks before the cliff; at the cliff the share accrued since
+/// `start` unlocks at once, and the rest follows linearly until
+/// `start + duration`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VestingSchedule {
+    pub beneficiary: String,
+    pub currency_type: CurrencyType,
+    pub amount: Amount,
+    pub start: DateTime<Utc>,
+    pub cliff_secs: i64,
+    pub duration_secs: i64,
+}
+
+impl VestingSchedule {
+    pub fn new(beneficiary: String, currency_type: CurrencyType, amount: Amount, start: DateTime<Utc>, cliff: Duration, duration: Duration) -> IcnResult<Self> {
+        if !amount.is_positive() {
+            return Err(IcnError::Currency("Vested amount must be positive".into()));
+        }
+        if cliff < Duration::zero() || duration <= Duration::zero() || cliff > duration {
+            return Err(IcnError::Currency("Vesting cliff must lie within a positive duration".into()));
+        }
+        Ok(VestingSchedule {
+            beneficiary,
+            currency_type,
+            amount,
+            start,
+            cliff_secs: cliff.num_seconds(),
+            duration_secs: duration.num_seconds(),
+        })
+    }
+
+    /// What has vested by `now`, rounded down to the ledger's precision
+    /// so the locked part is never understated.
+    pub fn vested_at(&self, now: DateTime<Utc>) -> Amount {
+        let elapsed = (now - self.start).num_seconds();
+        if elapsed < self.cliff_secs {
+            Amount::ZERO
+        } else if elapsed >= self.duration_secs {
+            self.amount
+        } else {
+            // elapsed < duration, so the share is below the amount
+            self.amount.mul_div(elapsed, self.duration_secs).unwrap_or(self.amount)
+        }
+    }
+
+    pub fn locked_at(&self, now: DateTime<Utc>) -> Amount {
+        self.amount - self.vested_at(now)
+    }
+
+    pub fn is_fully_vested(&self, now: DateTime<Utc>) -> bool {
+        (now - self.start).num_seconds() >= self.duration_secs
+    }
+}
+
+/// Total still locked across the schedules of one beneficiary and currency.
+pub fn locked_amount<'a>(schedules: impl IntoIterator<Item = &'a VestingSchedule>, currency_type: &CurrencyType, now: DateTime<Utc>) -> IcnResult<Amount> {
+    schedules.into_iter()
+        .filter(|schedule| schedule.currency_type == *currency_type)
+        .try_fold(Amount::ZERO, |total, schedule| total.checked_add(schedule.locked_at(now))
+            .ok_or_else(|| IcnError::Currency("Locked balance overflows".into())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_vesting_with_cliff() {
+        let start = Utc::now();
+        let schedule = VestingSchedule::new("Alice".to_string(), CurrencyType::BasicNeeds, Amount::from(120.0), start, Duration::days(30), Duration::days(120)).unwrap();
+
+        assert_eq!(schedule.vested_at(start + Duration::days(29)), Amount::ZERO);
+        assert_eq!(schedule.vested_at(start + Duration::days(30)), Amount::from(30.0));
+        assert_eq!(schedule.locked_at(start + Duration::days(60)), Amount::from(60.0));
+        assert_eq!(schedule.vested_at(start + Duration::days(365)), Amount::from(120.0));
+        assert_eq!(locked_amount([&schedule, &schedule], &CurrencyType::BasicNeeds, start + Duration::days(60)).unwrap(), Amount::from(120.0));
+        assert!(schedule.is_fully_vested(start + Duration::days(120)));
+
+        assert!(VestingSchedule::new("Alice".to_string(), CurrencyType::BasicNeeds, Amount::from(1.0), start, Duration::days(2), Duration::days(1)).is_err());
+        assert!(VestingSchedule::new("Alice".to_string(), CurrencyType::BasicNeeds, Amount::ZERO, start, Duration::zero(), Duration::days(1)).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+// File: icn_consensus/src/committee.rs
+
+use serde::{Serialize, Deserialize};
+use icn_common::weighted_sample;
+use icn_identity::VrfSelection;
+use std::collections::HashMap;
+
+/// Number of committees kept for auditing and fraud challenges.
+pub const MAX_COMMITTEE_HISTORY: usize = 100;
+
+/// The validators chosen to vote on one block, and how they voted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Committee {
+    pub block_index: u64,
+    /// Hash of the block before `block_index`, which seeded the selection.
+    pub seed: String,
+    /// Members ordered by id.
+    pub members: Vec<String>,
+    pub votes: HashMap<String, bool>,
+    /// Members' BLS signatures on the block, once collected.
+    #[serde(default)]
+    pub certificate: Option<BlockCertificate>,
+    /// The VRF draw that picked the members, when the node has a VRF key.
+    #[serde(default)]
+    pub selection: Option<VrfSelection>,
+}
+
+impl Committee {
+    pub fn is_member(&self, id: &str) -> bool {
+        self.members.binary_search_by(|member| member.as_str().cmp(id)).is_ok()
+    }
+}
+
+/// Committee members' BLS signatures on a block, aggregated into one
+/// signature that any node can check against their keys.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BlockCertificate {
+    pub block_index: u64,
+    pub block_hash: String,
+    /// Members who signed, ordered by id.
+    pub signers: Vec<String>,
+    /// Aggregate of the signers' signatures over
+    /// `approval_message(block_index, block_hash)`.
+    pub signature: Vec<u8>,
+}
+
+/// A validator's claim that a committed block is invalid, and its outcome.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FraudChallenge {
+    pub challenger: String,
+    pub block_index: u64,
+    /// Whether the block failed the re-check.
+    pub upheld: bool,
+    /// Validators penalized for an invalid vote as a result.
+    pub penalized: Vec<String>,
+    pub timestamp: i64,
+}
+
+/// Picks up to `size` validators to vote on the block at `height`, with
+/// chances in proportion to reputation. `size` 0 selects every validator.
+///
+/// The draw is seeded by `previous_hash` and the height, so every node
+/// picks the same committee but nobody can know it before the previous
+/// block exists. Validators without reputation are never picked.
+pub fn select_committee(validators: &HashMap<String, f64>, size: usize, height: u64, previous_hash: &str) -> Vec<String> {
+    let candidates: Vec<(String, f64)> = validators.iter().map(|(id, reputation)| (id.clone(), *reputation)).collect();
+    weighted_sample(&committee_seed(previous_hash, height), &candidates, size)
+}
+
+fn committee_seed(previous_hash: &str, height: u64) -> Vec<u8> {
+    let mut seed = previous_hash.as_bytes().to_vec();
+    seed.extend_from_slice(&height.to_be_bytes());
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators(count: usize) -> HashMap<String, f64> {
+        (0..count).map(|i| (format!("validator{}", i), 0.5)).collect()
+    }
+
+    #[test]
+    fn test_committee_is_deterministic_per_seed() {
+        let validators = validators(20);
+        let committee = select_committee(&validators, 5, 7, "hash6");
+        assert_eq!(committee.len(), 5);
+        assert_eq!(committee, select_committee(&validators, 5, 7, "hash6"));
+        assert_ne!(committee, select_committee(&validators, 5, 8, "hash7"));
+        assert_eq!(select_committee(&validators, 0, 7, "hash6").len(), 20);
+        assert_eq!(select_committee(&validators, 50, 7, "hash6").len(), 20);
+    }
+
+    #[test]
+    fn test_selection_favours_reputation() {
+        let mut validators = validators(10);
+        validators.insert("trusted".to_string(), 1.0);
+        validators.insert("idle".to_string(), 0.0);
+
+        let mut trusted = 0;
+        for height in 0..200 {
+            let committee = select_committee(&validators, 3, height, "seed");
+            assert!(!committee.contains(&"idle".to_string()));
+            trusted += committee.contains(&"trusted".to_string()) as usize;
+        }
+        // A 0.5 validator sits on about 3 committees in 10
+        assert!(trusted > 80, "trusted validator selected {} times", trusted);
+    }
+}
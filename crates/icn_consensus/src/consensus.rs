@@ -225,6 +225,7 @@ impl PoCConsensus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use icn_common::{Amount, SignatureScheme};
     use tokio::sync::mpsc;
 
     async fn create_test_consensus() -> PoCConsensus {
@@ -256,10 +257,14 @@ mod tests {
         let transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 100.0,
+            amount: Amount::from(100.0),
             currency_type: icn_common::CurrencyType::BasicNeeds,
             timestamp: 12345,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
         consensus.add_pending_transaction(transaction);
 
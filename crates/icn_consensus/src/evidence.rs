@@ -0,0 +1,69 @@
+// File: icn_consensus/src/evidence.rs
+
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use icn_common::{BlockApproval, DoubleSignEvidence, IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+
+/// What a validator loses for approving two blocks at one height.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlashingPolicy {
+    /// Fraction of the validator's reputation taken away. Reputation is
+    /// what validators stake on their votes.
+    pub reputation_slash: f64,
+    /// Blocks the validator is kept off committees for.
+    pub suspension_blocks: u64,
+}
+
+impl Default for SlashingPolicy {
+    fn default() -> Self {
+        SlashingPolicy {
+            reputation_slash: 0.5,
+            suspension_blocks: 1000,
+        }
+    }
+}
+
+/// A validator slashed for double-signing.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlashingRecord {
+    /// `DoubleSignEvidence::id` of the evidence that caused it.
+    pub evidence_id: String,
+    pub validator: String,
+    /// Height the validator signed twice at.
+    pub block_index: u64,
+    /// Who submitted the evidence, or `None` if it arrived in a block.
+    pub reporter: Option<String>,
+    pub reputation_before: f64,
+    pub reputation_after: f64,
+    /// First height the validator may sit on a committee again.
+    pub suspended_until: u64,
+    pub timestamp: i64,
+}
+
+/// Checks that `evidence` shows two conflicting approvals, both signed by
+/// the validator. Only validators identified by `did:icn:<hex public key>`
+/// can be proven to have double-signed.
+pub fn verify_double_sign(evidence: &DoubleSignEvidence) -> IcnResult<()> {
+    if !evidence.is_conflicting() {
+        return Err(IcnError::Consensus("Evidence must show one validator approving two different blocks at the same height".into()));
+    }
+    let key = validator_public_key(evidence.validator())?;
+    verify_approval(&key, &evidence.first)?;
+    verify_approval(&key, &evidence.second)
+}
+
+fn verify_approval(key: &PublicKey, approval: &BlockApproval) -> IcnResult<()> {
+    let signature = Signature::from_bytes(&approval.signature)
+        .map_err(|e| IcnError::Consensus(format!("Invalid approval signature: {}", e)))?;
+    key.verify(&approval.message(), &signature)
+        .map_err(|_| IcnError::Consensus(format!("Approval of block {} does not verify for {}", approval.block_hash, approval.validator)))
+}
+
+pub(crate) fn validator_public_key(did: &str) -> IcnResult<PublicKey> {
+    let key_hex = did.strip_prefix("did:icn:")
+        .ok_or_else(|| IcnError::Consensus(format!("Validator {} has no verifiable key", did)))?;
+    let key_bytes = hex::decode(key_hex)
+        .map_err(|e| IcnError::Consensus(format!("Invalid validator DID {}: {}", did, e)))?;
+    PublicKey::from_bytes(&key_bytes)
+        .map_err(|e| IcnError::Consensus(format!("Invalid validator key {}: {}", did, e)))
+}
@@ -1,10 +1,25 @@
 // File: icn_consensus/src/lib.rs
 
+pub mod committee;
+pub mod evidence;
+pub mod metrics;
+pub mod rounds;
+
+pub use crate::committee::{select_committee, BlockCertificate, Committee, FraudChallenge, MAX_COMMITTEE_HISTORY};
+pub use crate::evidence::{verify_double_sign, SlashingPolicy, SlashingRecord};
+pub use crate::metrics::{PerformanceRecord, ValidatorMetrics, ValidatorPerformance};
+pub use crate::rounds::{RoundAction, RoundStep, RoundTimeout, RoundTimeouts, LEADER_SLOTS_PER_REPUTATION};
+
 use icn_blockchain::Block;
-use icn_common::{IcnResult, IcnError, Transaction, CurrencyType};
-use std::collections::HashMap;
+use icn_common::{IcnResult, IcnError, Transaction, CurrencyType, Amount, DoubleSignEvidence, SignatureScheme, approval_message, evidence_root};
+use icn_identity::{aggregate_bls_signatures, verify_bls_aggregate, SchemePublicKey, SelectionAudit, VrfKey, VrfSelection, COMMITTEE_SELECTION};
+#[cfg(feature = "fault_injection")]
+use icn_common::FaultInjector;
+use icn_reputation::{ReputationEvent, ReputationPolicy};
+use crate::rounds::{HeightState, LocalValidator};
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, RwLock};
-use log::{info, warn, error};
+use tracing::{info, warn, error};
 use chrono::Utc;
 
 /// Struct representing the Proof of Cooperation (PoC) consensus mechanism.
@@ -12,8 +27,23 @@ pub struct PoCConsensus {
     threshold: f64,                // The consensus threshold (e.g., 0.66 for 66% agreement)
     quorum: f64,                   // The required quorum for decision making (e.g., 0.51 for 51% participation)
     validators: HashMap<String, f64>,  // Map of validators and their reputation scores
-    pending_blocks: Vec<Block>,    // Queue of blocks waiting for consensus
+    pending_blocks: Vec<(Block, Option<String>)>,  // Queue of blocks waiting for consensus, with their proposers
     blockchain: Arc<RwLock<Vec<Block>>>,  // Shared blockchain state
+    metrics: HashMap<String, ValidatorMetrics>,  // Per-validator performance counters
+    reputation_policy: ReputationPolicy,  // How performance feeds back into reputation
+    committee_size: usize,  // Validators voting on each block, 0 for all of them
+    committees: VecDeque<Committee>,  // Recent committees and their votes, oldest first
+    fraud_challenges: Vec<FraudChallenge>,  // Challenges raised against committed blocks
+    slashing_policy: SlashingPolicy,  // Penalty for double-signing
+    slashings: Vec<SlashingRecord>,  // Validators slashed for double-signing, oldest first
+    suspended: HashMap<String, u64>,  // Suspended validators and the height their suspension ends
+    bls_keys: HashMap<String, SchemePublicKey>,  // Keys validators sign block certificates with
+    vrf_key: Option<VrfKey>,  // Seeds committee draws when set
+    local_validator: Option<LocalValidator>,  // Key this node votes with in rounds, if it takes part
+    round_timeouts: RoundTimeouts,  // How long each step of a round waits
+    height_state: Option<HeightState>,  // Rounds of the height being decided
+    #[cfg(feature = "fault_injection")]
+    faults: FaultInjector,  // Armed faults, for resilience testing
 }
 
 impl PoCConsensus {
@@ -28,10 +58,86 @@ impl PoCConsensus {
             quorum,
             validators: HashMap::new(),
             pending_blocks: Vec::new(),
-            blockchain: Arc::new(RwLock::new(vec![Block::new(0, Vec::new(), String::from("0"), 4)])),
+            blockchain: Arc::new(RwLock::new(vec![Block::new(0, Vec::new(), "0")])),
+            metrics: HashMap::new(),
+            reputation_policy: ReputationPolicy::default(),
+            committee_size: 0,
+            committees: VecDeque::new(),
+            fraud_challenges: Vec::new(),
+            slashing_policy: SlashingPolicy::default(),
+            slashings: Vec::new(),
+            suspended: HashMap::new(),
+            bls_keys: HashMap::new(),
+            vrf_key: None,
+            local_validator: None,
+            round_timeouts: RoundTimeouts::default(),
+            height_state: None,
+            #[cfg(feature = "fault_injection")]
+            faults: FaultInjector::default(),
         })
     }
 
+    /// Crashes rounds when the injector says to.
+    #[cfg(feature = "fault_injection")]
+    pub fn with_fault_injector(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Replaces the default rewards and penalties applied after each round.
+    pub fn with_reputation_policy(mut self, policy: ReputationPolicy) -> Self {
+        self.reputation_policy = policy;
+        self
+    }
+
+    pub fn with_slashing_policy(mut self, policy: SlashingPolicy) -> Self {
+        self.slashing_policy = policy;
+        self
+    }
+
+    /// Has a committee of `size` validators vote on each block instead of
+    /// every validator. 0 keeps every validator voting.
+    pub fn with_committee_size(mut self, size: usize) -> Self {
+        self.committee_size = size;
+        self
+    }
+
+    /// Seeds each committee draw with this key's VRF output over the
+    /// previous block hash, so the committee can't be worked out in advance
+    /// by whoever produced that block, and records the proof for audits.
+    pub fn with_vrf_key(mut self, key: VrfKey) -> Self {
+        self.vrf_key = Some(key);
+        self
+    }
+
+    pub fn set_committee_size(&mut self, size: usize) {
+        info!("Committee size set to {}", size);
+        self.committee_size = size;
+    }
+
+    pub fn committee_size(&self) -> usize {
+        self.committee_size
+    }
+
+    /// The committee that decided on `block_index`, if it is recent enough
+    /// to still be kept.
+    pub fn get_committee(&self, block_index: u64) -> Option<&Committee> {
+        self.committees.iter().rev().find(|committee| committee.block_index == block_index)
+    }
+
+    pub fn get_fraud_challenges(&self) -> &[FraudChallenge] {
+        &self.fraud_challenges
+    }
+
+    pub fn get_slashings(&self) -> &[SlashingRecord] {
+        &self.slashings
+    }
+
+    /// Whether `id` is barred from committees at `height`.
+    pub fn is_suspended(&self, id: &str, height: u64) -> bool {
+        self.suspended.get(id).is_some_and(|until| height < *until)
+    }
+
     /// Starts the PoC consensus mechanism.
     pub fn start(&self) -> IcnResult<()> {
         info!("PoC Consensus mechanism started");
@@ -53,114 +159,462 @@ impl PoCConsensus {
             warn!("Validator with id {} already exists", id);
             return Err(IcnError::Consensus("Validator already exists".into()));
         }
-        self.validators.insert(id, reputation);
         info!("Added validator {} with reputation {}", id, reputation);
+        self.metrics.insert(id.clone(), ValidatorMetrics::default());
+        self.validators.insert(id, reputation);
         Ok(())
     }
 
+    /// Returns a validator's reputation, or 0 for unknown nodes.
+    pub fn get_node_reputation(&self, id: &str) -> IcnResult<f64> {
+        Ok(self.validators.get(id).copied().unwrap_or(0.0))
+    }
+
+    /// Sets a node's reputation directly, registering it as a validator if needed.
+    pub fn set_node_reputation(&mut self, id: &str, reputation: f64) -> IcnResult<()> {
+        if !(0.0..=1.0).contains(&reputation) {
+            return Err(IcnError::Consensus("Invalid reputation".into()));
+        }
+        self.metrics.entry(id.to_string()).or_default();
+        self.validators.insert(id.to_string(), reputation);
+        Ok(())
+    }
+
+    pub fn get_validator_performance(&self, id: &str) -> IcnResult<ValidatorPerformance> {
+        let reputation = *self.validators.get(id)
+            .ok_or_else(|| IcnError::Consensus(format!("Validator {} not found", id)))?;
+        Ok(ValidatorPerformance {
+            id: id.to_string(),
+            reputation,
+            metrics: self.metrics.get(id).cloned().unwrap_or_default(),
+        })
+    }
+
+    /// Returns every validator's reputation and performance, ordered by id.
+    pub fn get_validators_performance(&self) -> Vec<ValidatorPerformance> {
+        let mut ids: Vec<&String> = self.validators.keys().collect();
+        ids.sort();
+        ids.into_iter()
+            .filter_map(|id| self.get_validator_performance(id).ok())
+            .collect()
+    }
+
     /// Processes a new block by adding it to the pending blocks queue and attempting to reach consensus.
+    /// This decides blocks locally; validators deciding them together use `propose_block`.
     pub fn process_new_block(&mut self, block: Block) -> IcnResult<()> {
-        self.pending_blocks.push(block);
+        self.pending_blocks.push((block, None));
         info!("Added new block to pending queue, attempting to reach consensus");
         self.try_reach_consensus()
     }
 
-    /// Attempts to reach consensus on the pending blocks.
+    /// Like `process_new_block`, for a block `proposer` produced. Proposers
+    /// are only credited for blocks committed in rounds.
+    pub fn process_proposed_block(&mut self, block: Block, proposer: &str) -> IcnResult<()> {
+        self.pending_blocks.push((block, Some(proposer.to_string())));
+        info!("Added block from {} to pending queue, attempting to reach consensus", proposer);
+        self.try_reach_consensus()
+    }
+
+    /// Decides the pending blocks on this node's validation alone.
+    ///
+    /// No validator voted, so the block's committee is recorded without
+    /// votes and nobody's metrics or reputation change. Validator votes
+    /// are only counted in rounds.
     fn try_reach_consensus(&mut self) -> IcnResult<()> {
-        let total_reputation: f64 = self.validators.values().sum();
-        let quorum_reputation = total_reputation * self.quorum;
+        let mut pending: VecDeque<_> = std::mem::take(&mut self.pending_blocks).into();
 
-        let mut blocks_to_add = Vec::new();
+        while let Some((block, proposer)) = pending.pop_front() {
+            let seed = self.last_block_hash()?;
+            let (members, selection) = self.draw_committee(block.index, &seed);
 
-        for block in &self.pending_blocks {
-            let mut votes_for = 0.0;
-            let mut total_votes = 0.0;
+            // Nothing is recorded yet, so the block and those behind it can
+            // be retried as if the decision was never attempted
+            #[cfg(feature = "fault_injection")]
+            if self.faults.take_consensus_crash() {
+                error!("Consensus crashed while deciding block {} (injected fault)", block.index);
+                self.pending_blocks.push((block, proposer));
+                self.pending_blocks.extend(pending);
+                return Err(IcnError::Consensus("Consensus round crashed".into()));
+            }
 
-            for (_, reputation) in &self.validators {
-                if self.validate_block(block)? {
-                    votes_for += reputation;
-                }
-                total_votes += reputation;
-
-                if total_votes >= quorum_reputation {
-                    if votes_for / total_votes >= self.threshold {
-                        blocks_to_add.push(block.clone());
-                        info!("Block {} approved by consensus", block.index);
-                    } else {
-                        warn!("Block {} rejected by consensus", block.index);
-                        return Err(IcnError::Consensus("Block rejected by consensus".into()));
-                    }
-                }
+            if !self.validate_block(&block)? {
+                warn!("Block {} rejected by consensus", block.index);
+                return Err(IcnError::Consensus("Block rejected by consensus".into()));
             }
+            info!("Block {} from {} accepted on local validation", block.index, proposer.as_deref().unwrap_or("this node"));
+            let committee = Committee { block_index: block.index, seed, members, votes: HashMap::new(), certificate: None, selection };
+            self.append_block(block, committee)?;
         }
 
-        for block in blocks_to_add {
-            self.add_block_to_chain(block)?;
+        Ok(())
+    }
+
+    /// Credits the committee's votes, adds the block to the chain and
+    /// applies the double-sign evidence it carries.
+    fn commit_block(&mut self, block: Block, proposer: Option<&str>, committee: Committee) -> IcnResult<()> {
+        self.record_votes(block.index, proposer, &committee.members, &committee.votes, true);
+        self.append_block(block, committee)
+    }
+
+    /// Adds the block to the chain and applies the double-sign evidence it
+    /// carries, without crediting anyone for it.
+    fn append_block(&mut self, block: Block, committee: Committee) -> IcnResult<()> {
+        self.remember_committee(committee);
+        let evidence = block.evidence.clone();
+        self.add_block_to_chain(block)?;
+        for evidence in &evidence {
+            let evidence_id = evidence.id();
+            if self.slashings.iter().any(|record| record.evidence_id == evidence_id) {
+                continue;
+            }
+            if let Err(e) = self.slash(None, evidence) {
+                warn!("Evidence against {} in a committed block was not applied: {}", evidence.validator(), e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Picks the committee for `height`, with the VRF draw it came from if
+    /// the node has a VRF key.
+    fn draw_committee(&self, height: u64, previous_hash: &str) -> (Vec<String>, Option<VrfSelection>) {
+        let eligible = self.eligible_validators(height);
+        match &self.vrf_key {
+            Some(key) => {
+                let mut candidates: Vec<(String, f64)> = eligible.into_iter().collect();
+                candidates.sort_by(|a, b| a.0.cmp(&b.0));
+                let selection = VrfSelection::draw(key, COMMITTEE_SELECTION, &height.to_string(), previous_hash, candidates, self.committee_size);
+                (selection.selected.clone(), Some(selection))
+            }
+            None => (select_committee(&eligible, self.committee_size, height, previous_hash), None),
+        }
+    }
+
+    /// Checks the VRF draw of a recent committee: the proof, that it was
+    /// seeded by the hash of the block before, and that it picks the
+    /// recorded members.
+    pub fn audit_committee(&self, block_index: u64) -> IcnResult<SelectionAudit> {
+        let committee = self.get_committee(block_index)
+            .ok_or_else(|| IcnError::Consensus(format!("No committee recorded for block {}", block_index)))?;
+        let selection = committee.selection.as_ref()
+            .ok_or_else(|| IcnError::Consensus(format!("The committee for block {} was not drawn with a VRF", block_index)))?;
+        let mut audit = selection.audit();
+        if selection.block_hash != committee.seed {
+            audit.problems.push("Draw was not seeded by the previous block".to_string());
+        }
+        if selection.subject != block_index.to_string() {
+            audit.problems.push("Draw was made for another height".to_string());
+        }
+        if selection.selected != committee.members {
+            audit.problems.push("The committee is not the one drawn".to_string());
+        }
+        Ok(audit)
+    }
+
+    fn remember_committee(&mut self, committee: Committee) {
+        if self.committees.len() == MAX_COMMITTEE_HISTORY {
+            self.committees.pop_front();
+        }
+        self.committees.push_back(committee);
+    }
+
+    /// Updates validator metrics and reputation after a round decided on
+    /// `block_index`. Validators missing from `votes` missed the round;
+    /// votes against the outcome count as invalid.
+    pub fn record_round(&mut self, block_index: u64, proposer: Option<&str>, votes: &HashMap<String, bool>, approved: bool) {
+        let validators: Vec<String> = self.eligible_validators(block_index).into_keys().collect();
+        self.record_votes(block_index, proposer, &validators, votes, approved);
+    }
+
+    /// Like `record_round`, but only `members` were asked to vote, so only
+    /// they can miss the round.
+    fn record_votes(&mut self, block_index: u64, proposer: Option<&str>, members: &[String], votes: &HashMap<String, bool>, approved: bool) {
+        if let Some(proposer) = proposer.filter(|_| approved) {
+            self.apply_reputation_event(proposer, block_index, ReputationEvent::BlockProposed);
+        }
+
+        for id in members {
+            let event = match votes.get(id) {
+                None => ReputationEvent::MissedRound,
+                Some(in_favor) if *in_favor != approved => ReputationEvent::InvalidVote,
+                Some(_) => ReputationEvent::VoteCast,
+            };
+            self.apply_reputation_event(id, block_index, event);
+        }
+    }
+
+    /// Lets a validator, typically one outside the block's committee, have
+    /// a committed block re-checked against the chain before it.
+    ///
+    /// If the block fails the check the challenge is upheld and every
+    /// committee member who approved it is penalized for an invalid vote.
+    /// Otherwise the challenger is, so challenges are not free to raise.
+    /// Reverting an invalid block is left to the node operators.
+    pub fn raise_fraud_challenge(&mut self, challenger: &str, block_index: u64) -> IcnResult<FraudChallenge> {
+        if !self.validators.contains_key(challenger) {
+            return Err(IcnError::Consensus(format!("{} is not a validator", challenger)));
+        }
+        if self.fraud_challenges.iter().any(|c| c.challenger == challenger && c.block_index == block_index) {
+            return Err(IcnError::Consensus(format!("{} already challenged block {}", challenger, block_index)));
         }
+        let committee = self.get_committee(block_index).cloned()
+            .ok_or_else(|| IcnError::Consensus(format!("No committee is recorded for block {}", block_index)))?;
 
-        self.pending_blocks.retain(|b| !blocks_to_add.contains(b));
+        let valid = {
+            let blockchain = self.blockchain.read().map_err(|e| {
+                error!("Failed to read blockchain: {}", e);
+                IcnError::Consensus("Failed to read blockchain".into())
+            })?;
+            let position = blockchain.iter().position(|block| block.index == block_index)
+                .ok_or_else(|| IcnError::Consensus(format!("Block {} was not committed", block_index)))?;
+            self.check_block(&blockchain[position], &blockchain[..position])
+        };
 
+        let penalized: Vec<String> = if valid {
+            vec![challenger.to_string()]
+        } else {
+            committee.members.iter()
+                .filter(|id| committee.votes.get(*id) == Some(&true))
+                .cloned()
+                .collect()
+        };
+        for id in &penalized {
+            self.apply_reputation_event(id, block_index, ReputationEvent::InvalidVote);
+        }
+        if valid {
+            info!("Fraud challenge by {} against block {} dismissed", challenger, block_index);
+        } else {
+            warn!("Fraud challenge by {} against block {} upheld, penalized {:?}", challenger, block_index, penalized);
+        }
+
+        let challenge = FraudChallenge {
+            challenger: challenger.to_string(),
+            block_index,
+            upheld: !valid,
+            penalized,
+            timestamp: Utc::now().timestamp(),
+        };
+        self.fraud_challenges.push(challenge.clone());
+        Ok(challenge)
+    }
+
+    /// Sets the BLS key `id` signs block certificates with. The key must
+    /// have proven possession when it was registered, or aggregates could
+    /// be forged with it.
+    pub fn set_validator_bls_key(&mut self, id: &str, key: SchemePublicKey) -> IcnResult<()> {
+        if !self.validators.contains_key(id) {
+            return Err(IcnError::Consensus(format!("{} is not a validator", id)));
+        }
+        if key.scheme != SignatureScheme::Bls12381 {
+            return Err(IcnError::Consensus("Block certificates are signed with BLS keys".into()));
+        }
+        self.bls_keys.insert(id.to_string(), key);
         Ok(())
     }
 
+    /// Aggregates committee members' BLS signatures on a committed block
+    /// into a certificate, kept with the block's committee. Signers must
+    /// have approved the block and together hold at least the consensus
+    /// threshold of the committee's reputation.
+    pub fn certify_block(&mut self, block_index: u64, approvals: Vec<(String, Vec<u8>)>) -> IcnResult<BlockCertificate> {
+        let committee = self.get_committee(block_index).cloned()
+            .ok_or_else(|| IcnError::Consensus(format!("No committee is recorded for block {}", block_index)))?;
+        let block_hash = {
+            let blockchain = self.blockchain.read().map_err(|e| {
+                error!("Failed to read blockchain: {}", e);
+                IcnError::Consensus("Failed to read blockchain".into())
+            })?;
+            blockchain.iter().find(|block| block.index == block_index)
+                .map(|block| block.hash.clone())
+                .ok_or_else(|| IcnError::Consensus(format!("Block {} was not committed", block_index)))?
+        };
+
+        let mut approvals = approvals;
+        approvals.sort_by(|a, b| a.0.cmp(&b.0));
+        approvals.dedup_by(|a, b| a.0 == b.0);
+        for (id, _) in &approvals {
+            if committee.votes.get(id) != Some(&true) {
+                return Err(IcnError::Consensus(format!("{} did not approve block {}", id, block_index)));
+            }
+        }
+        let committee_reputation: f64 = committee.members.iter().filter_map(|id| self.validators.get(id)).sum();
+        let signed_reputation: f64 = approvals.iter().filter_map(|(id, _)| self.validators.get(id)).sum();
+        if approvals.is_empty() || signed_reputation < committee_reputation * self.threshold {
+            return Err(IcnError::Consensus(format!("Too few committee members signed block {}", block_index)));
+        }
+
+        let (signers, signatures): (Vec<String>, Vec<Vec<u8>>) = approvals.into_iter().unzip();
+        let certificate = BlockCertificate {
+            block_index,
+            block_hash,
+            signers,
+            signature: aggregate_bls_signatures(&signatures)?,
+        };
+        self.verify_block_certificate(&certificate)?;
+        if let Some(committee) = self.committees.iter_mut().rev().find(|committee| committee.block_index == block_index) {
+            committee.certificate = Some(certificate.clone());
+        }
+        info!("Block {} certified by {} validators", block_index, certificate.signers.len());
+        Ok(certificate)
+    }
+
+    /// Checks a certificate's aggregate signature against its signers'
+    /// BLS keys.
+    pub fn verify_block_certificate(&self, certificate: &BlockCertificate) -> IcnResult<()> {
+        let keys = certificate.signers.iter()
+            .map(|id| self.bls_keys.get(id).cloned()
+                .ok_or_else(|| IcnError::Consensus(format!("{} has no BLS key", id))))
+            .collect::<IcnResult<Vec<_>>>()?;
+        verify_bls_aggregate(&keys, &approval_message(certificate.block_index, &certificate.block_hash), &certificate.signature)
+            .map_err(|e| IcnError::Consensus(format!("Invalid certificate for block {}: {}", certificate.block_index, e)))
+    }
+
+    /// Slashes and suspends a validator shown to have approved two
+    /// different blocks at one height. Anyone may submit evidence; it only
+    /// has to verify. Each offence is punished once, however often it is
+    /// reported.
+    pub fn submit_double_sign_evidence(&mut self, reporter: &str, evidence: &DoubleSignEvidence) -> IcnResult<SlashingRecord> {
+        self.slash(Some(reporter), evidence)
+    }
+
+    fn slash(&mut self, reporter: Option<&str>, evidence: &DoubleSignEvidence) -> IcnResult<SlashingRecord> {
+        verify_double_sign(evidence)?;
+        let evidence_id = evidence.id();
+        if self.slashings.iter().any(|record| record.evidence_id == evidence_id) {
+            return Err(IcnError::Consensus(format!("{} was already slashed for block {}", evidence.validator(), evidence.block_index())));
+        }
+        let validator = evidence.validator().to_string();
+        let reputation = self.validators.get_mut(&validator)
+            .ok_or_else(|| IcnError::Consensus(format!("{} is not a validator", validator)))?;
+
+        let reputation_before = *reputation;
+        *reputation = (reputation_before * (1.0 - self.slashing_policy.reputation_slash)).max(0.0);
+        let height = self.blockchain.read().map_err(|e| {
+            error!("Failed to read blockchain: {}", e);
+            IcnError::Consensus("Failed to read blockchain".into())
+        })?.len() as u64;
+        let suspended_until = height + self.slashing_policy.suspension_blocks;
+        let until = self.suspended.entry(validator.clone()).or_insert(suspended_until);
+        *until = (*until).max(suspended_until);
+
+        warn!(
+            "Slashed {} for double-signing at height {}: reputation {} -> {}, suspended until block {}",
+            validator, evidence.block_index(), reputation_before, *reputation, suspended_until
+        );
+        let record = SlashingRecord {
+            evidence_id,
+            validator,
+            block_index: evidence.block_index(),
+            reporter: reporter.map(str::to_string),
+            reputation_before,
+            reputation_after: *reputation,
+            suspended_until,
+            timestamp: Utc::now().timestamp(),
+        };
+        self.slashings.push(record.clone());
+        Ok(record)
+    }
+
+    /// Validators allowed on the committee for `height`.
+    fn eligible_validators(&self, height: u64) -> HashMap<String, f64> {
+        self.validators.iter()
+            .filter(|(id, _)| !self.is_suspended(id, height))
+            .map(|(id, reputation)| (id.clone(), *reputation))
+            .collect()
+    }
+
+    fn apply_reputation_event(&mut self, id: &str, block_index: u64, event: ReputationEvent) {
+        let Some(reputation) = self.validators.get_mut(id) else {
+            warn!("Ignoring {:?} for unknown validator {}", event, id);
+            return;
+        };
+        *reputation = self.reputation_policy.apply(*reputation, event);
+        self.metrics.entry(id.to_string()).or_default()
+            .record(block_index, Utc::now().timestamp(), event, *reputation);
+    }
+
+    fn last_block_hash(&self) -> IcnResult<String> {
+        let blockchain = self.blockchain.read().map_err(|e| {
+            error!("Failed to read blockchain: {}", e);
+            IcnError::Consensus("Failed to read blockchain".into())
+        })?;
+        blockchain.last()
+            .map(|block| block.hash.clone())
+            .ok_or_else(|| IcnError::Consensus("Blockchain is empty".into()))
+    }
+
     /// Validates a block according to consensus rules.
     fn validate_block(&self, block: &Block) -> IcnResult<bool> {
-        // Check if the block's previous hash matches the last block in the chain
         let blockchain = self.blockchain.read().map_err(|e| {
             error!("Failed to read blockchain: {}", e);
             IcnError::Consensus("Failed to read blockchain".into())
         })?;
-        
-        let last_block = blockchain.last().ok_or_else(|| {
+        if blockchain.is_empty() {
             error!("Blockchain is empty");
-            IcnError::Consensus("Blockchain is empty".into())
-        })?;
-        
+            return Err(IcnError::Consensus("Blockchain is empty".into()));
+        }
+        Ok(self.check_block(block, &blockchain))
+    }
+
+    /// Checks a block against the blocks before it.
+    fn check_block(&self, block: &Block, history: &[Block]) -> bool {
+        // Check if the block's previous hash matches the last block in the chain
+        let Some(last_block) = history.last() else {
+            warn!("Block validation failed: no block before block {}", block.index);
+            return false;
+        };
+
         if block.previous_hash != last_block.hash {
             warn!("Block validation failed: previous hash does not match last block's hash");
-            return Ok(false);
+            return false;
         }
 
         // Verify block hash
         if block.hash != block.calculate_hash() {
             warn!("Block validation failed: hash does not match calculated hash");
-            return Ok(false);
+            return false;
+        }
+
+        if block.evidence_root != evidence_root(&block.evidence) {
+            warn!("Block validation failed: evidence root does not match its evidence");
+            return false;
+        }
+        for evidence in &block.evidence {
+            if let Err(e) = verify_double_sign(evidence) {
+                warn!("Block validation failed: invalid evidence against {}: {}", evidence.validator(), e);
+                return false;
+            }
         }
 
         // Validate transactions
         for transaction in &block.transactions {
-            if !self.validate_transaction(transaction)? {
+            if !self.validate_transaction(transaction, history) {
                 warn!("Block validation failed: invalid transaction {:?}", transaction);
-                return Ok(false);
+                return false;
             }
         }
 
         info!("Block validation succeeded for block {}", block.index);
-        Ok(true)
+        true
     }
 
     /// Validates a transaction within a block.
-    fn validate_transaction(&self, transaction: &Transaction) -> IcnResult<bool> {
+    fn validate_transaction(&self, transaction: &Transaction, blockchain: &[Block]) -> bool {
         // Check if the transaction amount is positive
-        if transaction.amount <= 0.0 {
+        if !transaction.amount.is_positive() {
             warn!("Transaction validation failed: amount is not positive");
-            return Ok(false);
+            return false;
         }
 
         // Check if the sender has sufficient balance
-        let blockchain = self.blockchain.read().map_err(|e| {
-            error!("Failed to read blockchain: {}", e);
-            IcnError::Consensus("Failed to read blockchain".into())
-        })?;
-        let sender_balance = self.get_balance(&blockchain, &transaction.from, &transaction.currency_type);
+        let sender_balance = self.get_balance(blockchain, &transaction.from, &transaction.currency_type);
         
         if sender_balance < transaction.amount {
             warn!("Transaction validation failed: insufficient balance for {}", transaction.from);
-            return Ok(false);
+            return false;
         }
 
         info!("Transaction validation succeeded for transaction from {}", transaction.from);
-        Ok(true)
+        true
     }
 
     /// Adds a block to the blockchain.
@@ -184,8 +638,8 @@ impl PoCConsensus {
     }
 
     /// Calculates the balance of an address in a specific currency type.
-    fn get_balance(&self, blockchain: &[Block], address: &str, currency_type: &CurrencyType) -> f64 {
-        let mut balance = 0.0;
+    fn get_balance(&self, blockchain: &[Block], address: &str, currency_type: &CurrencyType) -> Amount {
+        let mut balance = Amount::ZERO;
         for block in blockchain {
             for transaction in &block.transactions {
                 if transaction.currency_type == *currency_type {
@@ -205,23 +659,16 @@ impl PoCConsensus {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use icn_common::Block;
     use chrono::Utc;
 
+    /// An empty block on top of `previous_hash`. Nobody holds a balance
+    /// in a fresh chain, so blocks that should pass carry no transfers.
     fn create_test_block(index: u64, previous_hash: &str) -> Block {
-        Block::new(
-            index,
-            vec![Transaction {
-                from: "Alice".to_string(),
-                to: "Bob".to_string(),
-                amount: 100.0,
-                currency_type: CurrencyType::BasicNeeds,
-                timestamp: Utc::now().timestamp(),
-                signature: None,
-            }],
-            previous_hash.to_string(),
-            1,
-        )
+        Block::new(index, Vec::new(), previous_hash)
+    }
+
+    fn genesis_hash(consensus: &PoCConsensus) -> String {
+        consensus.get_blockchain().unwrap()[0].hash.clone()
     }
 
     #[test]
@@ -244,13 +691,23 @@ mod tests {
         consensus.add_validator("validator1".to_string(), 0.8).unwrap();
         consensus.add_validator("validator2".to_string(), 0.7).unwrap();
 
-        let new_block = create_test_block(1, "test_hash_0");
+        let new_block = create_test_block(1, &genesis_hash(&consensus));
         assert!(consensus.process_new_block(new_block).is_ok());
 
         let blockchain = consensus.get_blockchain().unwrap();
         assert_eq!(blockchain.len(), 2);  // Genesis block + 1 new block
     }
 
+    #[test]
+    fn test_blocks_without_validators() {
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap();
+
+        let new_block = create_test_block(1, &genesis_hash(&consensus));
+        consensus.process_new_block(new_block).unwrap();
+        assert!(consensus.process_new_block(create_test_block(2, "invalid_previous_hash")).is_err());
+        assert_eq!(consensus.get_blockchain().unwrap().len(), 2);
+    }
+
     #[test]
     fn test_consensus_threshold() {
         let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap();
@@ -258,7 +715,7 @@ mod tests {
         consensus.add_validator("validator2".to_string(), 0.7).unwrap();
         consensus.add_validator("validator3".to_string(), 0.6).unwrap();
 
-        let new_block = create_test_block(1, "test_hash_0");
+        let new_block = create_test_block(1, &genesis_hash(&consensus));
         assert!(consensus.process_new_block(new_block).is_ok());
 
         // The total reputation is 2.1, and the quorum is 0.51 * 2.1 = 1.071
@@ -295,13 +752,17 @@ mod tests {
         let invalid_transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 1000.0,  // Assume Alice doesn't have this much balance
+            amount: Amount::from(1000.0),  // Assume Alice doesn't have this much balance
             currency_type: CurrencyType::BasicNeeds,
             timestamp: Utc::now().timestamp(),
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
-        let mut invalid_block = Block::new(1, vec![invalid_transaction], "test_hash_0".to_string(), 1);
+        let mut invalid_block = Block::new(1, vec![invalid_transaction], &genesis_hash(&consensus));
         invalid_block.hash = invalid_block.calculate_hash();
 
         assert!(consensus.process_new_block(invalid_block).is_err());
@@ -310,13 +771,248 @@ mod tests {
         assert_eq!(blockchain.len(), 1);  // Only genesis block should remain
     }
 
+    #[test]
+    fn test_round_updates_validator_metrics() {
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap();
+        consensus.add_validator("validator1".to_string(), 0.5).unwrap();
+        consensus.add_validator("validator2".to_string(), 0.5).unwrap();
+        consensus.add_validator("validator3".to_string(), 0.5).unwrap();
+
+        let votes = HashMap::from([
+            ("validator1".to_string(), true),
+            ("validator2".to_string(), false),
+        ]);
+        consensus.record_round(1, Some("validator1"), &votes, true);
+
+        let proposer = consensus.get_validator_performance("validator1").unwrap();
+        assert_eq!(proposer.metrics.blocks_proposed, 1);
+        assert_eq!(proposer.metrics.votes_cast, 1);
+        assert_eq!(proposer.metrics.history.len(), 2);
+        assert!(proposer.reputation > 0.5);
+
+        let dissenter = consensus.get_validator_performance("validator2").unwrap();
+        assert_eq!(dissenter.metrics.invalid_votes, 1);
+        assert!(dissenter.reputation < 0.5);
+
+        let absent = consensus.get_validator_performance("validator3").unwrap();
+        assert_eq!(absent.metrics.missed_rounds, 1);
+        assert_eq!(absent.metrics.history[0].block_index, 1);
+
+        let ids: Vec<String> = consensus.get_validators_performance().into_iter().map(|v| v.id).collect();
+        assert_eq!(ids, vec!["validator1", "validator2", "validator3"]);
+    }
+
+    #[cfg(feature = "fault_injection")]
+    #[test]
+    fn test_crashed_round_is_retried() {
+        let faults = FaultInjector::default();
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap().with_fault_injector(faults.clone());
+        consensus.add_validator("validator1".to_string(), 0.8).unwrap();
+        let genesis_hash = consensus.get_blockchain().unwrap()[0].hash.clone();
+
+        faults.inject(icn_common::Fault::CrashConsensusRound).unwrap();
+        let first = Block::new(1, Vec::new(), &genesis_hash);
+        assert!(consensus.process_new_block(first.clone()).is_err());
+        assert_eq!(consensus.get_blockchain().unwrap().len(), 1);
+        assert_eq!(consensus.get_validator_performance("validator1").unwrap().metrics.votes_cast, 0);
+
+        // The crashed block is retried ahead of the next one
+        consensus.process_new_block(Block::new(2, Vec::new(), &first.hash)).unwrap();
+        assert_eq!(consensus.get_blockchain().unwrap().len(), 3);
+        assert_eq!(faults.stats().crashed_rounds, 1);
+    }
+
+    #[test]
+    fn test_committee_votes_and_fraud_challenges() {
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap().with_committee_size(2);
+        for i in 0..5 {
+            consensus.add_validator(format!("validator{}", i), 0.5).unwrap();
+        }
+        let genesis_hash = consensus.get_blockchain().unwrap()[0].hash.clone();
+        let expected = select_committee(&consensus.validators, 2, 1, &genesis_hash);
+        let block = Block::new(1, Vec::new(), &genesis_hash);
+        consensus.process_new_block(block.clone()).unwrap();
+
+        let committee = consensus.get_committee(1).unwrap().clone();
+        assert_eq!(committee.seed, genesis_hash);
+        assert_eq!(committee.members, expected);
+        // Nobody voted on a block decided locally, so nobody is credited
+        assert!(committee.votes.is_empty());
+        for performance in consensus.get_validators_performance() {
+            assert_eq!(performance.metrics.votes_cast, 0);
+            assert_eq!(performance.metrics.missed_rounds, 0);
+            assert_eq!(performance.reputation, 0.5);
+        }
+
+        // A valid block: the challenger pays for the false alarm
+        let auditor = (0..5).map(|i| format!("validator{}", i)).find(|id| !committee.is_member(id)).unwrap();
+        let challenge = consensus.raise_fraud_challenge(&auditor, 1).unwrap();
+        assert!(!challenge.upheld);
+        assert_eq!(challenge.penalized, vec![auditor.clone()]);
+        assert!(consensus.raise_fraud_challenge(&auditor, 1).is_err());
+        assert!(consensus.raise_fraud_challenge("outsider", 1).is_err());
+
+        // A block the committee should not have approved
+        let mut forged = Block::new(2, Vec::new(), "not_the_previous_hash");
+        forged.hash = forged.calculate_hash();
+        consensus.add_block_to_chain(forged).unwrap();
+        let votes = committee.members.iter().map(|id| (id.clone(), true)).collect();
+        consensus.remember_committee(Committee { block_index: 2, seed: block.hash.clone(), members: committee.members.clone(), votes, certificate: None, selection: None });
+
+        let challenge = consensus.raise_fraud_challenge(&auditor, 2).unwrap();
+        assert!(challenge.upheld);
+        assert_eq!(challenge.penalized, committee.members);
+        for member in &committee.members {
+            assert_eq!(consensus.get_validator_performance(member).unwrap().metrics.invalid_votes, 1);
+        }
+        assert_eq!(consensus.get_fraud_challenges().len(), 2);
+    }
+
+    #[test]
+    fn test_vrf_committee_draw_can_be_audited() {
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap()
+            .with_committee_size(2)
+            .with_vrf_key(VrfKey::generate());
+        for i in 0..5 {
+            consensus.add_validator(format!("validator{}", i), 0.2 + 0.1 * i as f64).unwrap();
+        }
+        let genesis_hash = consensus.get_blockchain().unwrap()[0].hash.clone();
+        consensus.process_new_block(Block::new(1, Vec::new(), &genesis_hash)).unwrap();
+
+        let committee = consensus.get_committee(1).unwrap().clone();
+        assert_eq!(committee.members.len(), 2);
+        let selection = committee.selection.clone().unwrap();
+        assert_eq!(selection.block_hash, genesis_hash);
+        assert_eq!(selection.candidates.len(), 5);
+        let audit = consensus.audit_committee(1).unwrap();
+        assert!(audit.is_valid(), "{:?}", audit.problems);
+        assert_eq!(audit.recomputed, committee.members);
+
+        // Tampering with the recorded members is caught
+        let outsider = (0..5).map(|i| format!("validator{}", i)).find(|id| !committee.is_member(id)).unwrap();
+        let mut members = vec![committee.members[0].clone(), outsider];
+        members.sort();
+        consensus.committees.back_mut().unwrap().members = members;
+        assert!(!consensus.audit_committee(1).unwrap().is_valid());
+        assert!(consensus.audit_committee(7).is_err());
+
+        // Without a VRF key there is no proof to audit
+        let mut plain = PoCConsensus::new(0.66, 0.51).unwrap();
+        plain.add_validator("validator0".to_string(), 0.5).unwrap();
+        let genesis_hash = plain.get_blockchain().unwrap()[0].hash.clone();
+        plain.process_new_block(Block::new(1, Vec::new(), &genesis_hash)).unwrap();
+        assert!(plain.audit_committee(1).is_err());
+    }
+
+    #[test]
+    fn test_double_sign_evidence_slashes_and_suspends() {
+        use ed25519_dalek::{Keypair, PublicKey, SecretKey, Signer};
+        use icn_common::{approval_message, BlockApproval};
+
+        let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let keypair = Keypair { public: PublicKey::from(&secret), secret };
+        let validator = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        let approve = |block_index: u64, block_hash: &str| BlockApproval {
+            validator: validator.clone(),
+            block_index,
+            block_hash: block_hash.to_string(),
+            signature: keypair.sign(&approval_message(block_index, block_hash)).to_bytes().to_vec(),
+        };
+
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap()
+            .with_slashing_policy(SlashingPolicy { reputation_slash: 0.5, suspension_blocks: 10 });
+        consensus.add_validator(validator.clone(), 0.8).unwrap();
+        consensus.add_validator("validator1".to_string(), 0.6).unwrap();
+
+        // Approving the same block twice is not an offence
+        let repeated = DoubleSignEvidence { first: approve(1, "aa"), second: approve(1, "aa") };
+        assert!(consensus.submit_double_sign_evidence("validator1", &repeated).is_err());
+        let mut forged = DoubleSignEvidence { first: approve(1, "aa"), second: approve(1, "bb") };
+        forged.second.signature = approve(1, "cc").signature;
+        assert!(consensus.submit_double_sign_evidence("validator1", &forged).is_err());
+
+        let evidence = DoubleSignEvidence { first: approve(1, "aa"), second: approve(1, "bb") };
+        let record = consensus.submit_double_sign_evidence("validator1", &evidence).unwrap();
+        assert_eq!(record.reporter.as_deref(), Some("validator1"));
+        assert!((record.reputation_after - 0.4).abs() < 1e-9);
+        assert_eq!(record.suspended_until, 11);
+        assert!((consensus.get_node_reputation(&validator).unwrap() - 0.4).abs() < 1e-9);
+
+        // The same offence reported again, in either order, is not punished twice
+        let swapped = DoubleSignEvidence { first: evidence.second.clone(), second: evidence.first.clone() };
+        assert!(consensus.submit_double_sign_evidence("validator2", &swapped).is_err());
+        assert_eq!(consensus.get_slashings().len(), 1);
+
+        // Suspended validators sit out committees until the suspension ends
+        assert!(consensus.is_suspended(&validator, 10));
+        assert!(!consensus.is_suspended(&validator, 11));
+        let genesis_hash = consensus.get_blockchain().unwrap()[0].hash.clone();
+        consensus.process_new_block(Block::new(1, Vec::new(), &genesis_hash)).unwrap();
+        assert_eq!(consensus.get_committee(1).unwrap().members, vec!["validator1".to_string()]);
+
+        // Evidence carried in a committed block slashes too
+        let tip = consensus.get_blockchain().unwrap()[1].hash.clone();
+        let later = DoubleSignEvidence { first: approve(5, "dd"), second: approve(5, "ee") };
+        let block = Block::new(2, Vec::new(), &tip).with_evidence(vec![later, evidence]);
+        consensus.process_new_block(block).unwrap();
+        let slashings = consensus.get_slashings();
+        assert_eq!(slashings.len(), 2);
+        assert_eq!((slashings[1].block_index, slashings[1].reporter.clone()), (5, None));
+        assert!((consensus.get_node_reputation(&validator).unwrap() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bls_block_certificates() {
+        use icn_identity::SchemeKeypair;
+
+        let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap();
+        let mut keypairs = Vec::new();
+        for i in 0..3 {
+            let id = format!("validator{}", i);
+            consensus.add_validator(id.clone(), 0.5).unwrap();
+            let keypair = SchemeKeypair::generate(SignatureScheme::Bls12381);
+            consensus.set_validator_bls_key(&id, keypair.public_key()).unwrap();
+            keypairs.push((id, keypair));
+        }
+        let ed25519 = SchemeKeypair::generate(SignatureScheme::Ed25519).public_key();
+        assert!(consensus.set_validator_bls_key("validator0", ed25519).is_err());
+
+        let genesis_hash = consensus.get_blockchain().unwrap()[0].hash.clone();
+        let block = Block::new(1, Vec::new(), &genesis_hash);
+        consensus.process_new_block(block.clone()).unwrap();
+        let sign = |keypair: &SchemeKeypair| keypair.sign(&approval_message(1, &block.hash));
+        let approvals: Vec<(String, Vec<u8>)> = keypairs.iter().map(|(id, keypair)| (id.clone(), sign(keypair))).collect();
+
+        // Nobody approved a block decided locally
+        assert!(consensus.certify_block(1, approvals.clone()).is_err());
+        consensus.committees.back_mut().unwrap().votes = keypairs.iter().map(|(id, _)| (id.clone(), true)).collect();
+
+        // One of three is short of the threshold
+        assert!(consensus.certify_block(1, approvals[..1].to_vec()).is_err());
+
+        // A signature over the wrong block spoils the aggregate
+        let mut forged = approvals.clone();
+        forged[2].1 = keypairs[2].1.sign(&approval_message(1, "other"));
+        assert!(consensus.certify_block(1, forged).is_err());
+
+        let certificate = consensus.certify_block(1, approvals).unwrap();
+        assert_eq!(certificate.signers.len(), 3);
+        assert_eq!(certificate.signature.len(), 96);
+        consensus.verify_block_certificate(&certificate).unwrap();
+        assert_eq!(consensus.get_committee(1).unwrap().certificate, Some(certificate.clone()));
+
+        let mut tampered = certificate;
+        tampered.block_hash = "other".to_string();
+        assert!(consensus.verify_block_certificate(&tampered).is_err());
+    }
+
     #[test]
     fn test_multiple_blocks() {
         let mut consensus = PoCConsensus::new(0.66, 0.51).unwrap();
         consensus.add_validator("validator1".to_string(), 0.8).unwrap();
         consensus.add_validator("validator2".to_string(), 0.7).unwrap();
 
-        let block1 = create_test_block(1, "test_hash_0");
+        let block1 = create_test_block(1, &genesis_hash(&consensus));
         let block2 = create_test_block(2, &block1.hash);
         let block3 = create_test_block(3, &block2.hash);
 
@@ -0,0 +1,51 @@
+// File: icn_consensus/src/metrics.rs
+
+use icn_reputation::ReputationEvent;
+use serde::{Serialize, Deserialize};
+use std::collections::VecDeque;
+
+/// Number of performance records kept per validator.
+pub const MAX_PERFORMANCE_HISTORY: usize = 100;
+
+/// One reputation adjustment and what caused it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PerformanceRecord {
+    pub block_index: u64,
+    pub timestamp: i64,
+    pub event: ReputationEvent,
+    /// Reputation after the adjustment.
+    pub reputation: f64,
+}
+
+/// Running counters for a validator, plus its most recent adjustments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ValidatorMetrics {
+    pub blocks_proposed: u64,
+    pub votes_cast: u64,
+    pub missed_rounds: u64,
+    pub invalid_votes: u64,
+    pub history: VecDeque<PerformanceRecord>,
+}
+
+impl ValidatorMetrics {
+    pub(crate) fn record(&mut self, block_index: u64, timestamp: i64, event: ReputationEvent, reputation: f64) {
+        match event {
+            ReputationEvent::BlockProposed => self.blocks_proposed += 1,
+            ReputationEvent::VoteCast => self.votes_cast += 1,
+            ReputationEvent::MissedRound => self.missed_rounds += 1,
+            ReputationEvent::InvalidVote => self.invalid_votes += 1,
+        }
+        if self.history.len() == MAX_PERFORMANCE_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(PerformanceRecord { block_index, timestamp, event, reputation });
+    }
+}
+
+/// A validator's current standing, as reported by the `/validators` API.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ValidatorPerformance {
+    pub id: String,
+    pub reputation: f64,
+    pub metrics: ValidatorMetrics,
+}
@@ -0,0 +1,890 @@
+// File: icn_consensus/src/rounds.rs
+
+use crate::committee::Committee;
+use crate::evidence::validator_public_key;
+use crate::PoCConsensus;
+use ed25519_dalek::{Keypair, Signature, Signer, Verifier};
+use icn_blockchain::Block;
+use icn_common::{block_producer_message, consensus_proposal_message, consensus_vote_message, ConsensusProposal, ConsensusVote, IcnError, IcnResult, VoteStep};
+use icn_identity::VrfSelection;
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Leader slots a validator gets per unit of reputation in each rotation.
+/// Every committee member gets at least one.
+pub const LEADER_SLOTS_PER_REPUTATION: f64 = 10.0;
+
+/// How long each step of a round waits for the rest of the committee.
+/// Later rounds wait longer, so a network slower than the timeouts still
+/// gets a round it can finish in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoundTimeouts {
+    pub propose: Duration,
+    pub prevote: Duration,
+    pub precommit: Duration,
+    /// Added to every timeout for each round after the first.
+    pub increment: Duration,
+}
+
+impl Default for RoundTimeouts {
+    fn default() -> Self {
+        RoundTimeouts {
+            propose: Duration::from_secs(3),
+            prevote: Duration::from_secs(1),
+            precommit: Duration::from_secs(1),
+            increment: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RoundTimeouts {
+    pub fn for_step(&self, step: RoundStep, round: u32) -> Duration {
+        let base = match step {
+            RoundStep::Propose => self.propose,
+            RoundStep::Prevote => self.prevote,
+            RoundStep::Precommit => self.precommit,
+        };
+        base + self.increment * round
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RoundStep {
+    /// Waiting for the round's proposal.
+    Propose,
+    /// Prevoted, waiting for the committee's prevotes.
+    Prevote,
+    /// Precommitted, waiting for the committee's precommits.
+    Precommit,
+}
+
+/// A timeout to hand back to `handle_round_timeout` once `after` has
+/// passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundTimeout {
+    pub height: u64,
+    pub round: u32,
+    pub step: RoundStep,
+    pub after: Duration,
+}
+
+/// What the node has to do for consensus to go on.
+#[derive(Debug, Clone)]
+pub enum RoundAction {
+    /// Send the proposal and its block to the other validators.
+    Propose(ConsensusProposal, Block),
+    /// Send the vote to the other validators.
+    Vote(ConsensusVote),
+    ScheduleTimeout(RoundTimeout),
+    /// The committee committed the block, and it is on the consensus chain.
+    Commit(Block),
+}
+
+/// The key this node signs proposals and votes with.
+pub(crate) struct LocalValidator {
+    pub(crate) id: String,
+    pub(crate) keypair: Keypair,
+}
+
+/// Progress on the height being decided.
+pub(crate) struct HeightState {
+    height: u64,
+    round: u32,
+    step: RoundStep,
+    seed: String,
+    /// Ordered by id.
+    members: Vec<String>,
+    /// The members in the order they lead rounds, from round 0.
+    leaders: Vec<String>,
+    selection: Option<VrfSelection>,
+    /// The block this node precommitted and the round it did. It prevotes
+    /// for no other block until the committee prevotes for one in a later
+    /// round.
+    locked: Option<(u32, Block)>,
+    /// The latest block the committee prevoted for, which this node
+    /// re-proposes when it is its turn.
+    valid: Option<(u32, Block)>,
+    /// The block this node proposes when it has nothing better.
+    own_block: Option<Block>,
+    proposals: HashMap<u32, (ConsensusProposal, Block)>,
+    votes: HashMap<(u32, VoteStep), HashMap<String, ConsensusVote>>,
+    scheduled: HashSet<(u32, RoundStep)>,
+}
+
+impl HeightState {
+    fn is_member(&self, id: &str) -> bool {
+        self.members.binary_search_by(|member| member.as_str().cmp(id)).is_ok()
+    }
+
+    fn proposer(&self, round: u32) -> Option<&str> {
+        proposer(&self.leaders, round)
+    }
+}
+
+/// A round whose leader times out falls back to the next candidate, so
+/// every member gets a turn before anyone leads twice.
+fn proposer(leaders: &[String], round: u32) -> Option<&str> {
+    if leaders.is_empty() {
+        return None;
+    }
+    Some(&leaders[round as usize % leaders.len()])
+}
+
+/// Orders `members` to lead the rounds of `height`. Heights walk a smooth
+/// weighted round-robin over the members, so each leads round 0 in
+/// proportion to its weight with its turns spread out; the round-0 leader
+/// is followed by the members that come after it in that rotation.
+fn leader_order(members: &[String], weights: &[u64], height: u64) -> Vec<String> {
+    let total: u64 = weights.iter().sum();
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut credit = vec![0i64; members.len()];
+    let mut slots = Vec::with_capacity(total as usize);
+    for _ in 0..total {
+        for (credit, weight) in credit.iter_mut().zip(weights) {
+            *credit += *weight as i64;
+        }
+        let leader = (0..members.len()).max_by_key(|index| (credit[*index], Reverse(*index))).unwrap();
+        credit[leader] -= total as i64;
+        slots.push(leader);
+    }
+    let start = (height % total) as usize;
+    let mut seen = vec![false; members.len()];
+    slots[start..].iter().chain(&slots[..start])
+        .filter(|index| !std::mem::replace(&mut seen[**index], true))
+        .map(|index| members[*index].clone())
+        .collect()
+}
+
+fn verify_signature(id: &str, message: &[u8], signature: &[u8]) -> IcnResult<()> {
+    let key = validator_public_key(id)?;
+    let signature = Signature::from_bytes(signature)
+        .map_err(|e| IcnError::Consensus(format!("Invalid signature from {}: {}", id, e)))?;
+    key.verify(message, &signature)
+        .map_err(|_| IcnError::Consensus(format!("Signature from {} does not verify", id)))
+}
+
+impl PoCConsensus {
+    /// Takes part in rounds of votes with the other validators as the
+    /// holder of `keypair`, identified by `did:icn:<hex public key>`.
+    /// Without a key, blocks are decided locally.
+    pub fn with_validator_keypair(mut self, keypair: Keypair) -> Self {
+        self.set_validator_keypair(keypair);
+        self
+    }
+
+    /// Takes part in rounds as `did:icn:<hex public key>`, signing with
+    /// `keypair`.
+    pub fn set_validator_keypair(&mut self, keypair: Keypair) {
+        let id = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        self.local_validator = Some(LocalValidator { id, keypair });
+    }
+
+    pub fn with_round_timeouts(mut self, timeouts: RoundTimeouts) -> Self {
+        self.round_timeouts = timeouts;
+        self
+    }
+
+    /// The id this node votes under, if it takes part in rounds.
+    pub fn validator_id(&self) -> Option<&str> {
+        self.local_validator.as_ref().map(|local| local.id.as_str())
+    }
+
+    /// Height and round being decided and the step this node reached.
+    pub fn current_round(&self) -> Option<(u64, u32, RoundStep)> {
+        self.height_state.as_ref().map(|state| (state.height, state.round, state.step))
+    }
+
+    /// Who proposes in `round` at `height`, the next height to decide.
+    pub fn proposer(&self, height: u64, round: u32) -> IcnResult<String> {
+        let leaders = self.committee_leaders(height)?;
+        proposer(&leaders, round)
+            .map(str::to_string)
+            .ok_or_else(|| IcnError::Consensus(format!("No committee for block {}", height)))
+    }
+
+    /// The committee for `height` in the order its members lead rounds,
+    /// weighted by reputation. Round `r` is led by entry `r` modulo the
+    /// committee size.
+    pub fn leader_schedule(&self, height: u64) -> IcnResult<Vec<String>> {
+        self.committee_leaders(height)
+    }
+
+    /// Signs `block` as its producer with this node's validator key.
+    pub fn sign_block(&self, mut block: Block) -> IcnResult<Block> {
+        let local = self.local_validator.as_ref()
+            .ok_or_else(|| IcnError::Consensus("This node has no validator key".into()))?;
+        block.producer = local.id.clone();
+        block.producer_signature = local.keypair.sign(&block_producer_message(block.index, &block.hash)).to_bytes().to_vec();
+        Ok(block)
+    }
+
+    /// Checks that `block` is signed by its producer, who sits on the
+    /// committee for its height.
+    pub fn verify_block_producer(&self, block: &Block) -> IcnResult<()> {
+        if block.producer.is_empty() {
+            return Err(IcnError::Consensus(format!("Block {} has no producer", block.index)));
+        }
+        let members = self.committee_members(block.index)?;
+        if members.binary_search(&block.producer).is_err() {
+            return Err(IcnError::Consensus(format!("{} is not on the committee for block {}", block.producer, block.index)));
+        }
+        verify_signature(&block.producer, &block_producer_message(block.index, &block.hash), &block.producer_signature)
+    }
+
+    /// Offers `block` for the next height. It is proposed when it is this
+    /// node's turn and the committee has no earlier block to settle on;
+    /// the block that ends up committed arrives as `RoundAction::Commit`.
+    pub fn propose_block(&mut self, block: Block) -> IcnResult<Vec<RoundAction>> {
+        if self.local_validator.is_none() {
+            return Err(IcnError::Consensus("This node has no validator key".into()));
+        }
+        let height = self.next_height()?;
+        if block.index != height {
+            return Err(IcnError::Consensus(format!("Block {} can't be proposed at height {}", block.index, height)));
+        }
+        let block = self.sign_block(block)?;
+        self.with_height(|_, state, _| state.own_block = Some(block))
+    }
+
+    pub fn handle_proposal(&mut self, proposal: ConsensusProposal, block: Block) -> IcnResult<Vec<RoundAction>> {
+        let height = self.next_height()?;
+        if proposal.height != height {
+            debug!("Ignoring proposal for block {} at height {}", proposal.height, height);
+            return Ok(Vec::new());
+        }
+        if block.index != height || block.hash != proposal.block_hash {
+            return Err(IcnError::Consensus(format!("Proposal from {} does not match its block", proposal.proposer)));
+        }
+        let leaders = self.committee_leaders(height)?;
+        if proposer(&leaders, proposal.round) != Some(proposal.proposer.as_str()) {
+            return Err(IcnError::Consensus(format!("{} is not the proposer of round {}", proposal.proposer, proposal.round)));
+        }
+        verify_signature(&proposal.proposer, &proposal.message(), &proposal.signature)?;
+        // A re-proposed block keeps the producer of the round it was
+        // first proposed in
+        if proposal.valid_round.is_none() && block.producer != proposal.proposer {
+            return Err(IcnError::Consensus(format!("Block {} was not produced by its proposer {}", height, proposal.proposer)));
+        }
+        self.verify_block_producer(&block)?;
+
+        self.with_height(|_, state, _| {
+            state.proposals.entry(proposal.round).or_insert((proposal, block));
+        })
+    }
+
+    pub fn handle_consensus_vote(&mut self, vote: ConsensusVote) -> IcnResult<Vec<RoundAction>> {
+        let height = self.next_height()?;
+        if vote.height != height {
+            debug!("Ignoring vote for block {} at height {}", vote.height, height);
+            return Ok(Vec::new());
+        }
+        let members = self.committee_members(height)?;
+        if members.binary_search(&vote.validator).is_err() {
+            return Err(IcnError::Consensus(format!("{} is not on the committee for block {}", vote.validator, height)));
+        }
+        verify_signature(&vote.validator, &vote.message(), &vote.signature)?;
+
+        self.with_height(|_, state, _| {
+            let votes = state.votes.entry((vote.round, vote.step)).or_default();
+            match votes.get(&vote.validator) {
+                Some(first) if first.block_hash != vote.block_hash => {
+                    warn!("{} cast conflicting {:?}s in round {} of block {}, keeping the first", vote.validator, vote.step, vote.round, height);
+                }
+                Some(_) => {}
+                None => {
+                    votes.insert(vote.validator.clone(), vote);
+                }
+            }
+        })
+    }
+
+    /// Moves on from a step that waited too long: prevoting or
+    /// precommitting for no block, or starting the next round.
+    pub fn handle_round_timeout(&mut self, timeout: RoundTimeout) -> IcnResult<Vec<RoundAction>> {
+        match &self.height_state {
+            Some(state) if state.height == timeout.height && state.round == timeout.round => {}
+            _ => return Ok(Vec::new()),
+        }
+        self.with_height(|consensus, state, actions| {
+            match (timeout.step, state.step) {
+                (RoundStep::Propose, RoundStep::Propose) => {
+                    consensus.cast(state, VoteStep::Prevote, None, actions);
+                    state.step = RoundStep::Prevote;
+                }
+                (RoundStep::Prevote, RoundStep::Prevote) => {
+                    consensus.cast(state, VoteStep::Precommit, None, actions);
+                    state.step = RoundStep::Precommit;
+                }
+                (RoundStep::Precommit, _) => consensus.start_round(state, state.round + 1, actions),
+                _ => {}
+            }
+        })
+    }
+
+    /// Runs `update` on the height being decided, entering it first if
+    /// needed, then applies whatever rules it made hold.
+    fn with_height(&mut self, update: impl FnOnce(&Self, &mut HeightState, &mut Vec<RoundAction>)) -> IcnResult<Vec<RoundAction>> {
+        let mut actions = Vec::new();
+        let mut state = match self.height_state.take() {
+            Some(state) => state,
+            None => self.enter_height(&mut actions)?,
+        };
+        update(self, &mut state, &mut actions);
+        match self.advance(&mut state, &mut actions) {
+            Ok(true) => {}
+            Ok(false) => self.height_state = Some(state),
+            Err(e) => {
+                self.height_state = Some(state);
+                return Err(e);
+            }
+        }
+        Ok(actions)
+    }
+
+    fn enter_height(&self, actions: &mut Vec<RoundAction>) -> IcnResult<HeightState> {
+        let height = self.next_height()?;
+        let seed = self.last_block_hash()?;
+        let (members, selection) = self.draw_committee(height, &seed);
+        info!("Deciding block {} with a committee of {}", height, members.len());
+        let leaders = self.leader_order(&members, height);
+        let mut state = HeightState {
+            height,
+            round: 0,
+            step: RoundStep::Propose,
+            seed,
+            members,
+            leaders,
+            selection,
+            locked: None,
+            valid: None,
+            own_block: None,
+            proposals: HashMap::new(),
+            votes: HashMap::new(),
+            scheduled: HashSet::new(),
+        };
+        self.start_round(&mut state, 0, actions);
+        Ok(state)
+    }
+
+    fn start_round(&self, state: &mut HeightState, round: u32, actions: &mut Vec<RoundAction>) {
+        if round > 0 {
+            info!("Block {} moves to round {}", state.height, round);
+        }
+        state.round = round;
+        state.step = RoundStep::Propose;
+        self.schedule(state, RoundStep::Propose, actions);
+    }
+
+    fn schedule(&self, state: &mut HeightState, step: RoundStep, actions: &mut Vec<RoundAction>) -> bool {
+        if !state.scheduled.insert((state.round, step)) {
+            return false;
+        }
+        actions.push(RoundAction::ScheduleTimeout(RoundTimeout {
+            height: state.height,
+            round: state.round,
+            step,
+            after: self.round_timeouts.for_step(step, state.round),
+        }));
+        true
+    }
+
+    /// Applies every rule that holds until none does. Returns whether the
+    /// height was committed.
+    fn advance(&mut self, state: &mut HeightState, actions: &mut Vec<RoundAction>) -> IcnResult<bool> {
+        loop {
+            // Precommits from the committee decide the block, whichever
+            // round this node has got to
+            let decided = state.proposals.iter()
+                .find(|(round, (_, block))| self.has_supermajority(state, **round, VoteStep::Precommit, Some(&block.hash)))
+                .map(|(round, (proposal, block))| (*round, proposal.proposer.clone(), block.clone()));
+            if let Some((round, proposer, block)) = decided {
+                self.commit_round(state, round, &proposer, block.clone())?;
+                actions.push(RoundAction::Commit(block));
+                return Ok(true);
+            }
+
+            // Enough of the committee is past this round that it can't
+            // finish without them
+            let later_round = state.votes.keys()
+                .map(|(round, _)| *round)
+                .filter(|round| *round > state.round && self.exceeds_faulty_share(state, *round))
+                .min();
+            if let Some(round) = later_round {
+                self.start_round(state, round, actions);
+                continue;
+            }
+
+            if !self.step(state, actions) {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// Takes the next step of the current round if its conditions hold.
+    fn step(&self, state: &mut HeightState, actions: &mut Vec<RoundAction>) -> bool {
+        let round = state.round;
+        let proposal = state.proposals.get(&round).cloned();
+        let polka = proposal.as_ref()
+            .filter(|(_, block)| self.has_supermajority(state, round, VoteStep::Prevote, Some(&block.hash)))
+            .map(|(_, block)| block.clone());
+
+        match state.step {
+            RoundStep::Propose => {
+                if self.try_propose(state, actions) {
+                    return true;
+                }
+                let Some((proposal, block)) = proposal else {
+                    return false;
+                };
+                let Some(choice) = self.prevote_choice(state, &proposal, &block) else {
+                    return false;
+                };
+                self.cast(state, VoteStep::Prevote, choice, actions);
+                state.step = RoundStep::Prevote;
+                true
+            }
+            RoundStep::Prevote => {
+                if let Some(block) = polka {
+                    self.cast(state, VoteStep::Precommit, Some(block.hash.clone()), actions);
+                    state.locked = Some((round, block.clone()));
+                    state.valid = Some((round, block));
+                    state.step = RoundStep::Precommit;
+                    true
+                } else if self.has_supermajority(state, round, VoteStep::Prevote, None) {
+                    self.cast(state, VoteStep::Precommit, None, actions);
+                    state.step = RoundStep::Precommit;
+                    true
+                } else {
+                    self.has_any_supermajority(state, round, VoteStep::Prevote)
+                        && self.schedule(state, RoundStep::Prevote, actions)
+                }
+            }
+            RoundStep::Precommit => {
+                // Prevotes that arrive after this node precommitted still
+                // tell it which block to re-propose
+                if let Some(block) = polka {
+                    if state.valid.as_ref().is_none_or(|(valid_round, _)| *valid_round < round) {
+                        state.valid = Some((round, block));
+                        return true;
+                    }
+                }
+                self.has_any_supermajority(state, round, VoteStep::Precommit)
+                    && self.schedule(state, RoundStep::Precommit, actions)
+            }
+        }
+    }
+
+    fn try_propose(&self, state: &mut HeightState, actions: &mut Vec<RoundAction>) -> bool {
+        let Some(local) = &self.local_validator else {
+            return false;
+        };
+        if state.proposer(state.round) != Some(local.id.as_str()) || state.proposals.contains_key(&state.round) {
+            return false;
+        }
+        let (block, valid_round) = match (&state.valid, &state.own_block) {
+            (Some((round, block)), _) => (block.clone(), Some(*round)),
+            (None, Some(block)) => (block.clone(), None),
+            (None, None) => return false,
+        };
+        let message = consensus_proposal_message(state.height, state.round, &block.hash, valid_round);
+        let proposal = ConsensusProposal {
+            proposer: local.id.clone(),
+            height: state.height,
+            round: state.round,
+            block_hash: block.hash.clone(),
+            valid_round,
+            signature: local.keypair.sign(&message).to_bytes().to_vec(),
+        };
+        info!("Proposing block {} in round {}", state.height, state.round);
+        state.proposals.insert(state.round, (proposal.clone(), block.clone()));
+        actions.push(RoundAction::Propose(proposal, block));
+        true
+    }
+
+    /// The block to prevote for, `Some(None)` for none, or `None` to wait
+    /// for the prevotes that justify a re-proposal.
+    fn prevote_choice(&self, state: &HeightState, proposal: &ConsensusProposal, block: &Block) -> Option<Option<String>> {
+        let acceptable = self.validate_block(block).unwrap_or(false);
+        let locked_on_block = |locked: &(u32, Block)| locked.1.hash == block.hash;
+        let choice = match proposal.valid_round {
+            None => acceptable && state.locked.as_ref().is_none_or(locked_on_block),
+            Some(valid_round) if valid_round < proposal.round => {
+                if !self.has_supermajority(state, valid_round, VoteStep::Prevote, Some(&block.hash)) {
+                    return None;
+                }
+                acceptable && state.locked.as_ref().is_none_or(|locked| locked.0 <= valid_round || locked_on_block(locked))
+            }
+            Some(_) => false,
+        };
+        Some(choice.then(|| block.hash.clone()))
+    }
+
+    /// Signs and records this node's vote, if it is on the committee.
+    fn cast(&self, state: &mut HeightState, step: VoteStep, block_hash: Option<String>, actions: &mut Vec<RoundAction>) {
+        let Some(local) = &self.local_validator else {
+            return;
+        };
+        if !state.is_member(&local.id) {
+            return;
+        }
+        let message = consensus_vote_message(state.height, state.round, step, block_hash.as_deref());
+        let vote = ConsensusVote {
+            validator: local.id.clone(),
+            height: state.height,
+            round: state.round,
+            step,
+            block_hash,
+            signature: local.keypair.sign(&message).to_bytes().to_vec(),
+        };
+        state.votes.entry((state.round, step)).or_default().insert(local.id.clone(), vote.clone());
+        actions.push(RoundAction::Vote(vote));
+    }
+
+    /// Commits the block the committee precommitted in `round`. Members
+    /// who precommitted for nothing are counted as missing the round.
+    fn commit_round(&mut self, state: &HeightState, round: u32, proposer: &str, block: Block) -> IcnResult<()> {
+        let votes = state.votes.get(&(round, VoteStep::Precommit))
+            .map(|votes| votes.values()
+                .filter_map(|vote| vote.block_hash.as_ref().map(|hash| (vote.validator.clone(), *hash == block.hash)))
+                .collect())
+            .unwrap_or_default();
+        info!("Block {} committed in round {}", block.index, round);
+        let committee = Committee {
+            block_index: block.index,
+            seed: state.seed.clone(),
+            members: state.members.clone(),
+            votes,
+            certificate: None,
+            selection: state.selection.clone(),
+        };
+        self.commit_block(block, Some(proposer), committee)
+    }
+
+    fn committee_members(&self, height: u64) -> IcnResult<Vec<String>> {
+        match &self.height_state {
+            Some(state) if state.height == height => Ok(state.members.clone()),
+            _ => Ok(self.draw_committee(height, &self.last_block_hash()?).0),
+        }
+    }
+
+    fn committee_leaders(&self, height: u64) -> IcnResult<Vec<String>> {
+        match &self.height_state {
+            Some(state) if state.height == height => Ok(state.leaders.clone()),
+            _ => Ok(self.leader_order(&self.committee_members(height)?, height)),
+        }
+    }
+
+    fn leader_order(&self, members: &[String], height: u64) -> Vec<String> {
+        let weights: Vec<u64> = members.iter()
+            .map(|id| self.validators.get(id).map_or(1, |reputation| ((reputation * LEADER_SLOTS_PER_REPUTATION).round() as u64).max(1)))
+            .collect();
+        leader_order(members, &weights, height)
+    }
+
+    fn next_height(&self) -> IcnResult<u64> {
+        let blockchain = self.blockchain.read()
+            .map_err(|_| IcnError::Consensus("Failed to read blockchain".into()))?;
+        Ok(blockchain.len() as u64)
+    }
+
+    fn vote_weight(&self, state: &HeightState, round: u32, step: VoteStep, target: Option<Option<&str>>) -> f64 {
+        state.votes.get(&(round, step))
+            .map(|votes| votes.values()
+                .filter(|vote| target.is_none_or(|hash| vote.block_hash.as_deref() == hash))
+                .filter_map(|vote| self.validators.get(&vote.validator))
+                .sum())
+            .unwrap_or(0.0)
+    }
+
+    fn committee_weight(&self, state: &HeightState) -> f64 {
+        state.members.iter().filter_map(|id| self.validators.get(id)).sum()
+    }
+
+    /// The share of the committee's reputation needed to lock or commit,
+    /// never less than two thirds so that two conflicting blocks can't
+    /// both reach it. Votes for the block also make up the quorum, so a
+    /// quorum above the threshold raises it.
+    fn commit_share(&self) -> f64 {
+        self.threshold.max(self.quorum).max(2.0 / 3.0)
+    }
+
+    fn exceeds_share(weight: f64, total: f64, share: f64) -> bool {
+        if share >= 1.0 {
+            weight >= total
+        } else {
+            weight > total * share
+        }
+    }
+
+    fn has_supermajority(&self, state: &HeightState, round: u32, step: VoteStep, block_hash: Option<&str>) -> bool {
+        let weight = self.vote_weight(state, round, step, Some(block_hash));
+        Self::exceeds_share(weight, self.committee_weight(state), self.commit_share())
+    }
+
+    fn has_any_supermajority(&self, state: &HeightState, round: u32, step: VoteStep) -> bool {
+        let weight = self.vote_weight(state, round, step, None);
+        Self::exceeds_share(weight, self.committee_weight(state), self.commit_share())
+    }
+
+    /// Whether more of the committee voted in `round` than can be faulty.
+    fn exceeds_faulty_share(&self, state: &HeightState, round: u32) -> bool {
+        let mut voters: HashSet<&String> = HashSet::new();
+        for step in [VoteStep::Prevote, VoteStep::Precommit] {
+            voters.extend(state.votes.get(&(round, step)).into_iter().flat_map(|votes| votes.keys()));
+        }
+        let weight: f64 = voters.into_iter().filter_map(|id| self.validators.get(id)).sum();
+        weight > self.committee_weight(state) * (1.0 - self.commit_share())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{PublicKey, SecretKey};
+    use std::collections::VecDeque;
+    use std::sync::{Arc, RwLock};
+
+    fn keypair(seed: u8) -> Keypair {
+        let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+        Keypair { public: PublicKey::from(&secret), secret }
+    }
+
+    /// Validators sharing a genesis block, and the messages between them.
+    struct Validators {
+        nodes: Vec<PoCConsensus>,
+        offline: HashSet<usize>,
+        timeouts: VecDeque<(usize, RoundTimeout)>,
+        commits: Vec<(usize, Block)>,
+    }
+
+    impl Validators {
+        fn new(size: u8) -> Self {
+            let genesis = Block::new(0, Vec::new(), "0");
+            let ids: Vec<String> = (1..=size).map(|seed| format!("did:icn:{}", hex::encode(keypair(seed).public.to_bytes()))).collect();
+            let nodes = (1..=size).map(|seed| {
+                let mut node = PoCConsensus::new(0.66, 0.51).unwrap().with_validator_keypair(keypair(seed));
+                node.blockchain = Arc::new(RwLock::new(vec![genesis.clone()]));
+                for id in &ids {
+                    node.add_validator(id.clone(), 0.5).unwrap();
+                }
+                node
+            }).collect();
+            Validators { nodes, offline: HashSet::new(), timeouts: VecDeque::new(), commits: Vec::new() }
+        }
+
+        fn index_of(&self, id: &str) -> usize {
+            self.nodes.iter().position(|node| node.validator_id() == Some(id)).unwrap()
+        }
+
+        fn next_block(&self, state_root: &str) -> Block {
+            let tip = self.nodes[0].get_blockchain().unwrap().last().unwrap().clone();
+            Block::new(tip.index + 1, Vec::new(), &tip.hash).with_state_root(state_root.to_string())
+        }
+
+        /// Delivers `actions` from node `from` and everything they lead to.
+        fn deliver(&mut self, from: usize, actions: Vec<RoundAction>) {
+            let mut queue: VecDeque<(usize, RoundAction)> = actions.into_iter().map(|action| (from, action)).collect();
+            while let Some((sender, action)) = queue.pop_front() {
+                for receiver in 0..self.nodes.len() {
+                    if self.offline.contains(&receiver) {
+                        continue;
+                    }
+                    let result = match (&action, receiver == sender) {
+                        (RoundAction::Propose(proposal, block), false) => self.nodes[receiver].handle_proposal(proposal.clone(), block.clone()),
+                        (RoundAction::Vote(vote), false) => self.nodes[receiver].handle_consensus_vote(vote.clone()),
+                        (RoundAction::ScheduleTimeout(timeout), true) => {
+                            self.timeouts.push_back((receiver, *timeout));
+                            Ok(Vec::new())
+                        }
+                        (RoundAction::Commit(block), true) => {
+                            self.commits.push((receiver, block.clone()));
+                            Ok(Vec::new())
+                        }
+                        _ => Ok(Vec::new()),
+                    };
+                    queue.extend(result.unwrap().into_iter().map(|action| (receiver, action)));
+                }
+            }
+        }
+
+        /// Lets every scheduled timeout expire in order.
+        fn run_timeouts(&mut self) {
+            while let Some((node, timeout)) = self.timeouts.pop_front() {
+                let actions = self.nodes[node].handle_round_timeout(timeout).unwrap();
+                self.deliver(node, actions);
+            }
+        }
+    }
+
+    #[test]
+    fn test_committee_commits_proposed_block() {
+        let mut committee = Validators::new(4);
+        let block = committee.next_block("a");
+        let proposer = committee.index_of(&committee.nodes[0].proposer(1, 0).unwrap());
+        let actions = committee.nodes[proposer].propose_block(block.clone()).unwrap();
+        assert!(matches!(actions.as_slice(), [RoundAction::ScheduleTimeout(_), RoundAction::Propose(..), RoundAction::Vote(_)]));
+        committee.deliver(proposer, actions);
+
+        assert_eq!(committee.commits.len(), 4);
+        for node in &committee.nodes {
+            let committed = node.get_blockchain().unwrap().last().unwrap().clone();
+            assert_eq!(committed.hash, block.hash);
+            assert_eq!(Some(committed.producer.as_str()), committee.nodes[proposer].validator_id());
+            assert_eq!(committed.header().producer_signature, committed.producer_signature);
+            let recorded = node.get_committee(1).unwrap();
+            assert!(recorded.votes.len() >= 3 && recorded.votes.values().all(|in_favor| *in_favor));
+            assert_eq!(node.current_round(), None);
+        }
+        assert_eq!(committee.nodes[proposer].get_validator_performance(committee.nodes[proposer].validator_id().unwrap()).unwrap().metrics.blocks_proposed, 1);
+
+        // Timeouts left over from the committed height do nothing
+        committee.run_timeouts();
+        assert_eq!(committee.nodes[0].get_blockchain().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_leaders_rotate_by_reputation() {
+        let members = vec!["a".to_string(), "b".to_string()];
+        let round_zero: Vec<String> = (0..8).map(|height| leader_order(&members, &[3, 1], height)[0].clone()).collect();
+        assert_eq!(round_zero, ["a", "a", "b", "a", "a", "a", "b", "a"]);
+        // Whoever leads round 0, the other member is the fallback
+        assert_eq!(leader_order(&members, &[3, 1], 2), ["b", "a"]);
+        assert_eq!(leader_order(&members, &[3, 1], 0), ["a", "b"]);
+        assert!(leader_order(&[], &[], 5).is_empty());
+
+        let mut committee = Validators::new(4);
+        let favoured = committee.nodes[0].validator_id().unwrap().to_string();
+        for node in &mut committee.nodes {
+            node.set_node_reputation(&favoured, 1.0).unwrap();
+        }
+        let schedules: Vec<Vec<String>> = (1..=25).map(|height| committee.nodes[0].leader_schedule(height).unwrap()).collect();
+        assert!(schedules.iter().all(|schedule| schedule.len() == 4));
+        assert_eq!(schedules.iter().filter(|schedule| schedule[0] == favoured).count(), 10);
+        assert_eq!(committee.nodes[0].proposer(3, 1).unwrap(), schedules[2][1]);
+    }
+
+    #[test]
+    fn test_blocks_need_their_producers_signature() {
+        let mut committee = Validators::new(4);
+        let proposer = committee.index_of(&committee.nodes[0].proposer(1, 0).unwrap());
+        let other = (proposer + 1) % 4;
+        let block = committee.next_block("a");
+        assert!(committee.nodes[0].verify_block_producer(&block).is_err());
+
+        let signed = committee.nodes[proposer].sign_block(block.clone()).unwrap();
+        assert_eq!(signed.hash, block.hash);
+        committee.nodes[0].verify_block_producer(&signed).unwrap();
+        let mut forged = signed.clone();
+        forged.producer = committee.nodes[other].validator_id().unwrap().to_string();
+        assert!(committee.nodes[0].verify_block_producer(&forged).is_err());
+
+        // A proposal must carry a block its proposer produced
+        let message = consensus_proposal_message(1, 0, &block.hash, None);
+        let proposal = ConsensusProposal {
+            proposer: committee.nodes[proposer].validator_id().unwrap().to_string(),
+            height: 1,
+            round: 0,
+            block_hash: block.hash.clone(),
+            valid_round: None,
+            signature: keypair(proposer as u8 + 1).sign(&message).to_bytes().to_vec(),
+        };
+        let someone_elses = committee.nodes[other].sign_block(block).unwrap();
+        assert!(committee.nodes[other].handle_proposal(proposal.clone(), someone_elses).is_err());
+        assert!(committee.nodes[other].handle_proposal(proposal, signed).is_ok());
+    }
+
+    #[test]
+    fn test_missing_proposer_moves_to_next_round() {
+        let mut committee = Validators::new(4);
+        let absent = committee.index_of(&committee.nodes[0].proposer(1, 0).unwrap());
+        let next = committee.index_of(&committee.nodes[0].proposer(1, 1).unwrap());
+        committee.offline.insert(absent);
+
+        for node in 0..4 {
+            if node != absent {
+                let block = committee.next_block(&node.to_string());
+                let actions = committee.nodes[node].propose_block(block).unwrap();
+                committee.deliver(node, actions);
+            }
+        }
+        assert!(committee.commits.is_empty());
+        committee.run_timeouts();
+
+        assert_eq!(committee.commits.len(), 3);
+        let committed = &committee.commits[0].1;
+        assert_eq!(committed.state_root, next.to_string());
+        assert!(committee.commits.iter().all(|(_, block)| block.hash == committed.hash));
+        assert_eq!(committee.nodes[next].get_blockchain().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_locked_validator_refuses_other_blocks() {
+        let mut committee = Validators::new(4);
+        let proposer = committee.index_of(&committee.nodes[0].proposer(1, 0).unwrap());
+        let next = committee.index_of(&committee.nodes[0].proposer(1, 1).unwrap());
+        let locked = (0..4).find(|node| *node != proposer && *node != next).unwrap();
+        let block = committee.next_block("a");
+
+        // Everyone prevotes for the block, but only one node sees the prevotes
+        let actions = committee.nodes[proposer].propose_block(block.clone()).unwrap();
+        let (proposal, proposed) = actions.iter().find_map(|action| match action {
+            RoundAction::Propose(proposal, block) => Some((proposal.clone(), block.clone())),
+            _ => None,
+        }).unwrap();
+        let mut prevotes: Vec<ConsensusVote> = actions.iter().filter_map(|action| match action {
+            RoundAction::Vote(vote) => Some(vote.clone()),
+            _ => None,
+        }).collect();
+        for node in (0..4).filter(|node| *node != proposer) {
+            let actions = committee.nodes[node].handle_proposal(proposal.clone(), proposed.clone()).unwrap();
+            prevotes.extend(actions.into_iter().filter_map(|action| match action {
+                RoundAction::Vote(vote) => Some(vote),
+                _ => None,
+            }));
+        }
+        let mut precommit = None;
+        let locked_id = committee.nodes[locked].validator_id().unwrap().to_string();
+        for vote in prevotes.into_iter().filter(|vote| vote.validator != locked_id) {
+            for action in committee.nodes[locked].handle_consensus_vote(vote).unwrap() {
+                if let RoundAction::Vote(vote) = action {
+                    precommit = Some(vote);
+                }
+            }
+        }
+        let precommit = precommit.unwrap();
+        assert_eq!((precommit.step, precommit.block_hash.as_deref()), (VoteStep::Precommit, Some(block.hash.as_str())));
+
+        // In the next round a new block without prevotes behind it is refused
+        committee.nodes[locked].handle_round_timeout(RoundTimeout { height: 1, round: 0, step: RoundStep::Precommit, after: Duration::ZERO }).unwrap();
+        assert_eq!(committee.nodes[locked].current_round(), Some((1, 1, RoundStep::Propose)));
+        let other = committee.nodes[next].sign_block(committee.next_block("b")).unwrap();
+        let message = consensus_proposal_message(1, 1, &other.hash, None);
+        let proposal = ConsensusProposal {
+            proposer: committee.nodes[next].validator_id().unwrap().to_string(),
+            height: 1,
+            round: 1,
+            block_hash: other.hash.clone(),
+            valid_round: None,
+            signature: keypair(next as u8 + 1).sign(&message).to_bytes().to_vec(),
+        };
+        let actions = committee.nodes[locked].handle_proposal(proposal.clone(), other.clone()).unwrap();
+        let prevote = actions.iter().find_map(|action| match action {
+            RoundAction::Vote(vote) => Some(vote.clone()),
+            _ => None,
+        }).unwrap();
+        assert_eq!((prevote.round, prevote.step, prevote.block_hash), (1, VoteStep::Prevote, None));
+
+        // Messages that don't check out are refused
+        let mut forged = proposal;
+        forged.round = 2;
+        assert!(committee.nodes[locked].handle_proposal(forged, other).is_err());
+        let mut tampered = precommit;
+        tampered.block_hash = None;
+        assert!(committee.nodes[proposer].handle_consensus_vote(tampered).is_err());
+    }
+}
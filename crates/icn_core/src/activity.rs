@@ -0,0 +1,118 @@
+// File: crates/icn_core/src/activity.rs
+
+use chrono::{DateTime, Utc};
+use icn_identity::{ActivityCategory, Delivery};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Items kept in each member's feed; older ones fall off.
+pub const MAX_FEED_ITEMS: usize = 200;
+
+/// How often digests of collected notifications are sent.
+pub const DIGEST_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 3600);
+
+/// Something that happened that concerns one member.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActivityItem {
+    pub id: u64,
+    pub identity: String,
+    pub category: ActivityCategory,
+    pub summary: String,
+    /// What the item is about: a transaction hash, a proposal id or a
+    /// `dao_id/proposal_id` pair, or a DAO id for announcements.
+    pub reference: String,
+    pub at: DateTime<Utc>,
+}
+
+/// Notifications collected for a member who asked for a daily digest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActivityDigest {
+    pub identity: String,
+    pub items: Vec<ActivityItem>,
+    pub sent_at: DateTime<Utc>,
+}
+
+/// Every member's recent activity, and the notifications waiting for the
+/// next digest.
+#[derive(Debug, Default)]
+pub struct ActivityFeed {
+    next_id: u64,
+    feeds: HashMap<String, VecDeque<ActivityItem>>,
+    digests: BTreeMap<String, Vec<ActivityItem>>,
+}
+
+impl ActivityFeed {
+    pub fn new() -> Self {
+        ActivityFeed::default()
+    }
+
+    /// Adds an item to the member's feed, queueing it for their digest if
+    /// that is how they want this category delivered.
+    pub fn record(&mut self, identity: &str, category: ActivityCategory, summary: String, reference: &str, delivery: Delivery, at: DateTime<Utc>) -> ActivityItem {
+        self.next_id += 1;
+        let item = ActivityItem {
+            id: self.next_id,
+            identity: identity.to_string(),
+            category,
+            summary,
+            reference: reference.to_string(),
+            at,
+        };
+        let feed = self.feeds.entry(identity.to_string()).or_default();
+        feed.push_back(item.clone());
+        if feed.len() > MAX_FEED_ITEMS {
+            feed.pop_front();
+        }
+        if delivery == Delivery::Digest {
+            self.digests.entry(identity.to_string()).or_default().push(item.clone());
+        }
+        item
+    }
+
+    /// Up to `limit` of the member's items, newest first.
+    pub fn feed(&self, identity: &str, limit: usize) -> Vec<ActivityItem> {
+        self.feeds.get(identity)
+            .map(|feed| feed.iter().rev().take(limit).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Empties the digest queue, one digest per member with anything in it.
+    pub fn take_digests(&mut self, sent_at: DateTime<Utc>) -> Vec<ActivityDigest> {
+        std::mem::take(&mut self.digests).into_iter()
+            .map(|(identity, items)| ActivityDigest { identity, items, sent_at })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feed_and_digests() {
+        let mut activity = ActivityFeed::new();
+        let now = Utc::now();
+        activity.record("alice", ActivityCategory::PaymentReceived, "Received 5".to_string(), "tx1", Delivery::Digest, now);
+        activity.record("alice", ActivityCategory::DaoAnnouncement, "Meeting".to_string(), "dao1", Delivery::Immediate, now);
+        activity.record("bob", ActivityCategory::ProposalNeedsVote, "Vote".to_string(), "p1", Delivery::Muted, now);
+
+        let feed = activity.feed("alice", 10);
+        assert_eq!(feed.iter().map(|item| item.reference.as_str()).collect::<Vec<_>>(), vec!["dao1", "tx1"]);
+        assert_eq!(activity.feed("alice", 1).len(), 1);
+        assert!(activity.feed("carol", 10).is_empty());
+
+        // Only items the member wanted in a digest are collected, and once
+        let digests = activity.take_digests(now);
+        assert_eq!(digests.len(), 1);
+        assert_eq!(digests[0].identity, "alice");
+        assert_eq!(digests[0].items[0].reference, "tx1");
+        assert!(activity.take_digests(now).is_empty());
+
+        for i in 0..MAX_FEED_ITEMS {
+            activity.record("bob", ActivityCategory::PaymentReceived, String::new(), &i.to_string(), Delivery::Immediate, now);
+        }
+        let feed = activity.feed("bob", usize::MAX);
+        assert_eq!(feed.len(), MAX_FEED_ITEMS);
+        assert_eq!(feed.last().unwrap().reference, "0");
+    }
+}
@@ -0,0 +1,290 @@
+// File: crates/icn_core/src/block_producer.rs
+
+use icn_blockchain::{Block, Blockchain};
+use icn_common::{IcnResult, ParameterRegistry, TransactionReceipt, TransactionStatus};
+use icn_common::params::PARAM_MAX_BLOCK_SIZE;
+use icn_consensus::PoCConsensus;
+use icn_currency::{CurrencySystem, FeeMarket};
+use icn_network::NetworkManager;
+use icn_sharding::ShardingManager;
+use icn_storage::StorageManager;
+use crate::contracts::ContractRegistry;
+use crate::events::{EventBus, MempoolEvent, NodeEvent, RemovalReason};
+use crate::receipts::save_receipt;
+use crate::resharding::ReshardSchedule;
+use crate::rounds::{RoundDriver, ROUND_DECISION_TIMEOUT};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
+use tracing::{info, info_span, warn, error, Instrument};
+
+/// Settings for the background block production loop.
+#[derive(Debug, Clone)]
+pub struct BlockProducerConfig {
+    /// How often pending transactions are packed into a block.
+    pub interval: Duration,
+    /// Maximum transactions per block; reaching it triggers production early.
+    pub max_batch_size: usize,
+}
+
+impl Default for BlockProducerConfig {
+    fn default() -> Self {
+        BlockProducerConfig {
+            interval: Duration::from_secs(5),
+            max_batch_size: 100,
+        }
+    }
+}
+
+/// Batches mempool transactions into blocks, gets them approved by consensus
+/// and broadcasts them to peers.
+pub struct BlockProducer {
+    config: BlockProducerConfig,
+    blockchain: Arc<RwLock<Blockchain>>,
+    consensus: Arc<RwLock<PoCConsensus>>,
+    network_manager: Arc<RwLock<NetworkManager>>,
+    contract_registry: Arc<RwLock<ContractRegistry>>,
+    batch_ready: Arc<Notify>,
+    shutdown: Arc<Notify>,
+    fees: Option<(Arc<RwLock<CurrencySystem>>, Arc<RwLock<FeeMarket>>)>,
+    parameters: Option<Arc<RwLock<ParameterRegistry>>>,
+    events: Option<EventBus>,
+    resharding: Option<(Arc<RwLock<ReshardSchedule>>, Arc<RwLock<ShardingManager>>)>,
+    rounds: Option<RoundDriver>,
+    receipts: Option<(Arc<RwLock<HashMap<String, TransactionReceipt>>>, Arc<RwLock<StorageManager>>)>,
+}
+
+impl BlockProducer {
+    pub fn new(
+        config: BlockProducerConfig,
+        blockchain: Arc<RwLock<Blockchain>>,
+        consensus: Arc<RwLock<PoCConsensus>>,
+        network_manager: Arc<RwLock<NetworkManager>>,
+        contract_registry: Arc<RwLock<ContractRegistry>>,
+        batch_ready: Arc<Notify>,
+        shutdown: Arc<Notify>,
+    ) -> Self {
+        BlockProducer {
+            config,
+            blockchain,
+            consensus,
+            network_manager,
+            contract_registry,
+            batch_ready,
+            shutdown,
+            fees: None,
+            parameters: None,
+            events: None,
+            resharding: None,
+            rounds: None,
+            receipts: None,
+        }
+    }
+
+    /// Pays out the fee pool after every committed block and reprices the
+    /// base fee from what is left in the mempool.
+    pub fn with_fee_distribution(mut self, currency_system: Arc<RwLock<CurrencySystem>>, fee_market: Arc<RwLock<FeeMarket>>) -> Self {
+        self.fees = Some((currency_system, fee_market));
+        self
+    }
+
+    /// Caps blocks at the registry's maximum block size in addition to the
+    /// locally configured batch size.
+    pub fn with_parameters(mut self, parameters: Arc<RwLock<ParameterRegistry>>) -> Self {
+        self.parameters = Some(parameters);
+        self
+    }
+
+    /// Publishes a mempool removal for every transaction in a committed block.
+    pub fn with_event_bus(mut self, events: EventBus) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Applies a scheduled shard count change once a committed block
+    /// reaches its activation height.
+    pub fn with_resharding(mut self, schedule: Arc<RwLock<ReshardSchedule>>, sharding_manager: Arc<RwLock<ShardingManager>>) -> Self {
+        self.resharding = Some((schedule, sharding_manager));
+        self
+    }
+
+    /// Marks the receipts of transactions that expire in the mempool, in
+    /// memory and in storage.
+    pub fn with_receipts(mut self, receipts: Arc<RwLock<HashMap<String, TransactionReceipt>>>, storage_manager: Arc<RwLock<StorageManager>>) -> Self {
+        self.receipts = Some((receipts, storage_manager));
+        self
+    }
+
+    /// Has blocks decided in rounds of votes with the other validators
+    /// whenever the node has a validator key.
+    pub fn with_rounds(mut self, rounds: RoundDriver) -> Self {
+        self.rounds = Some(rounds);
+        self
+    }
+
+    /// Runs until the shutdown signal fires, producing a block on every tick
+    /// or as soon as a full batch is waiting.
+    pub async fn run(self) {
+        let mut ticker = tokio::time::interval(self.config.interval);
+        info!("Block producer started with interval {:?}", self.config.interval);
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = self.batch_ready.notified() => {}
+                _ = self.shutdown.notified() => break,
+            }
+
+            if let Err(e) = self.produce_block().await {
+                error!("Block production failed: {}", e);
+            }
+        }
+
+        info!("Block producer stopped");
+    }
+
+    /// Packs the next batch of pending transactions, and any double-sign
+    /// evidence waiting, into a block. Returns `None` when there is neither.
+    pub async fn produce_block(&self) -> IcnResult<Option<Block>> {
+        self.expire_transactions().await;
+        let state_root = self.contract_registry.read().await.state_root();
+        let batch_size = self.batch_size().await;
+        let block = {
+            let mut blockchain = self.blockchain.write().await;
+            let transactions = blockchain.take_pending_batch(batch_size);
+            let evidence = blockchain.take_pending_evidence();
+            if transactions.is_empty() && evidence.is_empty() {
+                return Ok(None);
+            }
+            Block::new(blockchain.chain.len() as u64, transactions, &blockchain.get_latest_block().hash)
+                .with_state_root(state_root)
+                .with_evidence(evidence)
+        };
+
+        let span = info_span!("block", index = block.index);
+        if let Err(e) = self.commit_block(block.clone()).instrument(span).await {
+            warn!("Block {} was not committed, returning transactions to mempool", block.index);
+            let mut blockchain = self.blockchain.write().await;
+            blockchain.requeue_transactions(block.transactions);
+            blockchain.requeue_evidence(block.evidence);
+            return Err(e);
+        }
+
+        if let Some(events) = &self.events {
+            for transaction in &block.transactions {
+                events.publish(NodeEvent::Mempool(MempoolEvent::Removed {
+                    hash: transaction.hash(),
+                    reason: RemovalReason::IncludedInBlock { block_index: block.index },
+                }));
+            }
+        }
+
+        if let Err(e) = self.settle_fees().await {
+            warn!("Failed to distribute fees for block {}: {}", block.index, e);
+        }
+
+        if let Err(e) = self.apply_due_reshard(block.index).await {
+            error!("Failed to reshard at block {}: {}", block.index, e);
+        }
+
+        if let Err(e) = self.network_manager.read().await.broadcast_block(block.clone()).await {
+            warn!("Failed to broadcast block {}: {}", block.index, e);
+        }
+
+        for transaction in &block.transactions {
+            info_span!("transaction", correlation_id = %transaction.hash())
+                .in_scope(|| info!("Included in block {}", block.index));
+        }
+        info!("Produced block {} with {} transactions", block.index, block.transactions.len());
+        Ok(Some(block))
+    }
+
+    /// Drops pending transactions whose validity has ended. The transfers
+    /// they made when admitted are undone, though the fee stays paid.
+    async fn expire_transactions(&self) {
+        let expired = self.blockchain.write().await.evict_expired();
+        for transaction in expired {
+            let hash = transaction.hash();
+            if let Some((currency_system, _)) = &self.fees {
+                let undone = currency_system.write().await
+                    .transfer_amount(&transaction.to, &transaction.from, &transaction.currency_type, transaction.amount);
+                if let Err(e) = undone {
+                    warn!("Could not undo expired transaction {}: {}", hash, e);
+                }
+            }
+            if let Some((receipts, storage_manager)) = &self.receipts {
+                if let Some(receipt) = receipts.write().await.get_mut(&hash) {
+                    receipt.status = TransactionStatus::Expired;
+                    receipt.events.clear();
+                    if let Err(e) = save_receipt(&*storage_manager.read().await, receipt) {
+                        warn!("Could not save the expired receipt of {}: {}", hash, e);
+                    }
+                }
+            }
+            if let Some(events) = &self.events {
+                events.publish(NodeEvent::Mempool(MempoolEvent::Removed { hash: hash.clone(), reason: RemovalReason::Expired }));
+            }
+            info_span!("transaction", correlation_id = %hash)
+                .in_scope(|| info!("Expired before it was included in a block"));
+        }
+    }
+
+    async fn batch_size(&self) -> usize {
+        match &self.parameters {
+            Some(parameters) => parameters.read().await.get_usize(PARAM_MAX_BLOCK_SIZE)
+                .map_or(self.config.max_batch_size, |max_block_size| max_block_size.min(self.config.max_batch_size)),
+            None => self.config.max_batch_size,
+        }
+    }
+
+    async fn apply_due_reshard(&self, height: u64) -> IcnResult<()> {
+        let Some((schedule, sharding_manager)) = &self.resharding else {
+            return Ok(());
+        };
+        let Some(reshard) = schedule.write().await.take_due(height) else {
+            return Ok(());
+        };
+        let moved = sharding_manager.write().await.resize_shards(reshard.shard_count)?;
+        info!(
+            "Block {} activated proposal {}: now {} shards, {} accounts moved",
+            height, reshard.proposal_id, reshard.shard_count, moved
+        );
+        Ok(())
+    }
+
+    async fn settle_fees(&self) -> IcnResult<()> {
+        let Some((currency_system, fee_market)) = &self.fees else {
+            return Ok(());
+        };
+        let pending = self.blockchain.read().await.pending_transaction_count();
+        let split = {
+            let mut fee_market = fee_market.write().await;
+            let base_fee = fee_market.adjust(pending);
+            info!("Base fee is now {} with {} transactions pending", base_fee, pending);
+            fee_market.config().split.clone()
+        };
+
+        let producer = self.network_manager.read().await.node_id().to_string();
+        let validators: Vec<(String, f64)> = self.consensus.read().await.get_validators_performance()
+            .into_iter()
+            .map(|validator| (validator.id, validator.reputation))
+            .collect();
+        for distribution in currency_system.write().await.distribute_fees(&split, &producer, &validators)? {
+            info!("Distributed {} {:?} in fees", distribution.total, distribution.currency_type);
+        }
+        Ok(())
+    }
+
+    async fn commit_block(&self, block: Block) -> IcnResult<()> {
+        if let Some(rounds) = &self.rounds {
+            if self.consensus.read().await.validator_id().is_some() {
+                // The block joins the chain when the committee commits it
+                return rounds.propose_and_wait(block, ROUND_DECISION_TIMEOUT).await;
+            }
+        }
+        let proposer = self.network_manager.read().await.node_id().to_string();
+        self.consensus.write().await.process_proposed_block(block.clone(), &proposer)?;
+        self.blockchain.write().await.add_block(block)
+    }
+}
@@ -0,0 +1,218 @@
+// File: crates/icn_core/src/contracts.rs
+
+use icn_common::{IcnResult, IcnError};
+use icn_vm::EMPTY_STATE_ROOT;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::{BTreeSet, HashMap};
+
+/// Name of the optional function a new contract version can define to
+/// convert the state left behind by the previous version.
+pub const MIGRATE_FUNCTION: &str = "migrate";
+
+/// Name of the optional function run once, with the deployer's arguments,
+/// when a contract is deployed. It can't be called afterwards.
+pub const CONSTRUCTOR_FUNCTION: &str = "init";
+
+/// Who may replace a contract's code.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum UpgradeAuthority {
+    /// Only the given DID may upgrade the contract.
+    Owner(String),
+    /// Upgrades need a passed proposal naming the contract and the new code hash.
+    Governance,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractVersion {
+    pub version: u32,
+    pub code: String,
+    pub code_hash: String,
+    pub deployed_at: DateTime<Utc>,
+}
+
+/// A deployed contract with every version it has gone through.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContractRecord {
+    pub id: String,
+    pub authority: UpgradeAuthority,
+    /// Identity that deployed the contract and owns it. `None` for
+    /// contracts deployed by governance.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// When the constructor ran; `None` if the contract has none.
+    #[serde(default)]
+    pub initialized_at: Option<DateTime<Utc>>,
+    pub versions: Vec<ContractVersion>,
+    /// State keys written by executions, handed to `migrate` on upgrade.
+    pub state_keys: BTreeSet<String>,
+    /// Root of the contract's storage after its last execution.
+    pub state_root: String,
+}
+
+impl ContractRecord {
+    pub fn current(&self) -> &ContractVersion {
+        self.versions.last().expect("contract records always hold at least one version")
+    }
+
+    pub fn version(&self, version: u32) -> Option<&ContractVersion> {
+        self.versions.iter().find(|v| v.version == version)
+    }
+}
+
+/// Tracks deployed contracts, their upgrade authority and code history.
+#[derive(Debug, Default)]
+pub struct ContractRegistry {
+    contracts: HashMap<String, ContractRecord>,
+}
+
+impl ContractRegistry {
+    pub fn new() -> Self {
+        ContractRegistry::default()
+    }
+
+    pub fn register(&mut self, contract_id: &str, code: String, authority: UpgradeAuthority, owner: Option<String>) -> IcnResult<()> {
+        if self.contracts.contains_key(contract_id) {
+            return Err(IcnError::Vm(format!("Contract {} already exists", contract_id)));
+        }
+        self.contracts.insert(contract_id.to_string(), ContractRecord {
+            id: contract_id.to_string(),
+            authority,
+            owner,
+            initialized_at: None,
+            versions: vec![ContractVersion::new(1, code)],
+            state_keys: BTreeSet::new(),
+            state_root: EMPTY_STATE_ROOT.to_string(),
+        });
+        Ok(())
+    }
+
+    pub fn get(&self, contract_id: &str) -> IcnResult<&ContractRecord> {
+        self.contracts.get(contract_id)
+            .ok_or_else(|| IcnError::Vm(format!("Contract {} not found", contract_id)))
+    }
+
+    /// Appends a new version and makes it current, returning its number.
+    pub fn add_version(&mut self, contract_id: &str, code: String) -> IcnResult<u32> {
+        let record = self.get_mut(contract_id)?;
+        let version = record.current().version + 1;
+        record.versions.push(ContractVersion::new(version, code));
+        Ok(version)
+    }
+
+    pub fn mark_initialized(&mut self, contract_id: &str) -> IcnResult<()> {
+        let record = self.get_mut(contract_id)?;
+        if record.initialized_at.is_some() {
+            return Err(IcnError::Vm(format!("Contract {} is already initialized", contract_id)));
+        }
+        record.initialized_at = Some(Utc::now());
+        Ok(())
+    }
+
+    pub fn record_state_key(&mut self, contract_id: &str, key: &str) -> IcnResult<()> {
+        self.get_mut(contract_id)?.state_keys.insert(key.to_string());
+        Ok(())
+    }
+
+    pub fn set_state_root(&mut self, contract_id: &str, state_root: String) -> IcnResult<()> {
+        self.get_mut(contract_id)?.state_root = state_root;
+        Ok(())
+    }
+
+    /// Commits to the storage of every deployed contract. Blocks carry it so
+    /// peers can tell whether their contract state has diverged.
+    pub fn state_root(&self) -> String {
+        let mut ids: Vec<&String> = self.contracts.keys().collect();
+        ids.sort();
+        let mut hasher = Sha256::new();
+        for id in ids {
+            hasher.update(id.as_bytes());
+            hasher.update(self.contracts[id].state_root.as_bytes());
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    pub fn remove(&mut self, contract_id: &str) -> IcnResult<ContractRecord> {
+        self.contracts.remove(contract_id)
+            .ok_or_else(|| IcnError::Vm(format!("Contract {} not found", contract_id)))
+    }
+
+    fn get_mut(&mut self, contract_id: &str) -> IcnResult<&mut ContractRecord> {
+        self.contracts.get_mut(contract_id)
+            .ok_or_else(|| IcnError::Vm(format!("Contract {} not found", contract_id)))
+    }
+}
+
+impl ContractVersion {
+    fn new(version: u32, code: String) -> Self {
+        ContractVersion {
+            version,
+            code_hash: code_hash(&code),
+            code,
+            deployed_at: Utc::now(),
+        }
+    }
+}
+
+/// Hex-encoded SHA-256 of the contract source, used to tie upgrade
+/// proposals to exact code.
+pub fn code_hash(code: &str) -> String {
+    hex::encode(Sha256::digest(code.as_bytes()))
+}
+
+/// Returns true if the source defines a function with the given name.
+pub fn declares_function(code: &str, name: &str) -> bool {
+    ["fn", "function"].iter().any(|keyword| {
+        code.split(&format!("{} {}", keyword, name)).skip(1)
+            .any(|rest| rest.trim_start().starts_with('('))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_are_retained() {
+        let mut registry = ContractRegistry::new();
+        registry.register("counter", "fn get() -> i64 { 1 }".to_string(), UpgradeAuthority::Governance, None).unwrap();
+        assert!(registry.register("counter", String::new(), UpgradeAuthority::Governance, None).is_err());
+
+        let version = registry.add_version("counter", "fn get() -> i64 { 2 }".to_string()).unwrap();
+        assert_eq!(version, 2);
+
+        let record = registry.get("counter").unwrap();
+        assert_eq!(record.current().version, 2);
+        assert_eq!(record.version(1).unwrap().code, "fn get() -> i64 { 1 }");
+        assert_eq!(record.current().code_hash, code_hash("fn get() -> i64 { 2 }"));
+        assert!(registry.add_version("missing", String::new()).is_err());
+
+        registry.mark_initialized("counter").unwrap();
+        assert!(registry.get("counter").unwrap().initialized_at.is_some());
+        assert!(registry.mark_initialized("counter").is_err());
+    }
+
+    #[test]
+    fn test_registry_state_root() {
+        let mut registry = ContractRegistry::new();
+        let empty = registry.state_root();
+        registry.register("counter", String::new(), UpgradeAuthority::Governance, None).unwrap();
+        let deployed = registry.state_root();
+        assert_ne!(deployed, empty);
+
+        registry.set_state_root("counter", "abc".to_string()).unwrap();
+        assert_ne!(registry.state_root(), deployed);
+        assert!(registry.set_state_root("missing", String::new()).is_err());
+    }
+
+    #[test]
+    fn test_declares_function() {
+        assert!(declares_function("fn migrate(state: string) { }", MIGRATE_FUNCTION));
+        assert!(declares_function("function migrate (old_state) { }", MIGRATE_FUNCTION));
+        assert!(!declares_function("fn migrated() { }", MIGRATE_FUNCTION));
+        assert!(!declares_function("fn add(a: i64) { migrate }", MIGRATE_FUNCTION));
+        assert!(declares_function("fn init(supply: i64) { }", CONSTRUCTOR_FUNCTION));
+        assert!(!declares_function("fn initialize() { }", CONSTRUCTOR_FUNCTION));
+    }
+}
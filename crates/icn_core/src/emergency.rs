@@ -0,0 +1,219 @@
+// File: crates/icn_core/src/emergency.rs
+
+use icn_common::{IcnResult, IcnError};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+
+const GUARDIAN_SIGNING_DOMAIN: &str = "icn-emergency-pause-v1";
+
+/// Current state of the network-wide circuit breaker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PauseStatus {
+    pub paused: bool,
+    pub reason: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Incremented on every change so guardian signatures cannot be replayed.
+    pub nonce: u64,
+}
+
+/// A guardian's signature over `EmergencyPause::guardian_message`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardianSignature {
+    pub guardian: String,
+    pub signature: Vec<u8>,
+}
+
+/// Circuit breaker that blocks state-changing operations during an incident.
+///
+/// It can be flipped by a passed governance proposal or by `threshold` of
+/// the configured guardians signing the same pause or resume message.
+#[derive(Debug, Clone, Default)]
+pub struct EmergencyPause {
+    status: PauseStatus,
+    /// Genesis hash of the chain, so signatures for one network cannot be
+    /// replayed on another.
+    network_id: String,
+    guardians: Vec<String>,
+    threshold: usize,
+}
+
+impl EmergencyPause {
+    /// Creates a breaker for the network with the given genesis hash,
+    /// controlled by the given guardian DIDs. With no guardians only
+    /// governance can pause the network.
+    pub fn new(network_id: String, guardians: Vec<String>, threshold: usize) -> IcnResult<Self> {
+        if !guardians.is_empty() && (threshold == 0 || threshold > guardians.len()) {
+            return Err(IcnError::Config("Guardian threshold must be between 1 and the number of guardians".into()));
+        }
+        for guardian in &guardians {
+            guardian_public_key(guardian)?;
+        }
+        Ok(EmergencyPause {
+            status: PauseStatus::default(),
+            network_id,
+            guardians,
+            threshold,
+        })
+    }
+
+    pub fn network_id(&self) -> &str {
+        &self.network_id
+    }
+
+    pub fn status(&self) -> &PauseStatus {
+        &self.status
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.status.paused
+    }
+
+    /// Fails while the network is paused.
+    pub fn ensure_not_paused(&self) -> IcnResult<()> {
+        if self.status.paused {
+            let reason = self.status.reason.as_deref().unwrap_or("no reason given");
+            return Err(IcnError::NodeManagement(format!("Network is paused: {}", reason)));
+        }
+        Ok(())
+    }
+
+    pub fn set_paused(&mut self, paused: bool, reason: Option<String>) {
+        self.status = PauseStatus {
+            paused,
+            reason: if paused { reason } else { None },
+            updated_at: Some(Utc::now()),
+            nonce: self.status.nonce + 1,
+        };
+    }
+
+    /// The message guardians sign to request the given state with the given
+    /// reason. It includes the network and the current nonce, so each
+    /// signature is good for one change on one network only.
+    pub fn guardian_message(&self, paused: bool, reason: Option<&str>) -> Vec<u8> {
+        let payload = (GUARDIAN_SIGNING_DOMAIN, &self.network_id, paused, reason, self.status.nonce);
+        serde_json::to_vec(&payload).expect("guardian message fields always serialize")
+    }
+
+    /// Applies a pause or resume once enough distinct guardians have signed it.
+    pub fn apply_guardian_signatures(&mut self, paused: bool, reason: Option<String>, signatures: &[GuardianSignature]) -> IcnResult<()> {
+        if self.guardians.is_empty() {
+            return Err(IcnError::Governance("No emergency guardians are configured".into()));
+        }
+
+        let message = self.guardian_message(paused, reason.as_deref());
+        let mut signers = HashSet::new();
+        for guardian_signature in signatures {
+            if !self.guardians.contains(&guardian_signature.guardian) {
+                return Err(IcnError::Governance(format!("{} is not an emergency guardian", guardian_signature.guardian)));
+            }
+            let public_key = guardian_public_key(&guardian_signature.guardian)?;
+            let signature = Signature::from_bytes(&guardian_signature.signature)
+                .map_err(|e| IcnError::Governance(format!("Invalid guardian signature: {}", e)))?;
+            public_key.verify(&message, &signature)
+                .map_err(|_| IcnError::Governance(format!("Signature from {} does not verify", guardian_signature.guardian)))?;
+            signers.insert(guardian_signature.guardian.as_str());
+        }
+
+        if signers.len() < self.threshold {
+            return Err(IcnError::Governance(format!(
+                "{} of {} required guardian signatures provided", signers.len(), self.threshold
+            )));
+        }
+
+        self.set_paused(paused, reason);
+        Ok(())
+    }
+}
+
+fn guardian_public_key(did: &str) -> IcnResult<PublicKey> {
+    let key_hex = did.strip_prefix("did:icn:")
+        .ok_or_else(|| IcnError::Identity(format!("Invalid guardian DID: {}", did)))?;
+    let key_bytes = hex::decode(key_hex)
+        .map_err(|e| IcnError::Identity(format!("Invalid guardian DID {}: {}", did, e)))?;
+    PublicKey::from_bytes(&key_bytes)
+        .map_err(|e| IcnError::Identity(format!("Invalid guardian key {}: {}", did, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    fn guardian() -> (String, Keypair) {
+        let keypair = Keypair::generate(&mut OsRng {});
+        (format!("did:icn:{}", hex::encode(keypair.public.to_bytes())), keypair)
+    }
+
+    fn sign(breaker: &EmergencyPause, paused: bool, reason: Option<&str>, guardian: &(String, Keypair)) -> GuardianSignature {
+        GuardianSignature {
+            guardian: guardian.0.clone(),
+            signature: guardian.1.sign(&breaker.guardian_message(paused, reason)).to_bytes().to_vec(),
+        }
+    }
+
+    fn breaker(guardians: &[(String, Keypair)], threshold: usize) -> EmergencyPause {
+        EmergencyPause::new("genesis".to_string(), guardians.iter().map(|g| g.0.clone()).collect(), threshold).unwrap()
+    }
+
+    #[test]
+    fn test_guardian_threshold() {
+        let guardians: Vec<_> = (0..3).map(|_| guardian()).collect();
+        let mut breaker = breaker(&guardians, 2);
+        let incident = Some("incident");
+
+        let one = vec![sign(&breaker, true, incident, &guardians[0])];
+        assert!(breaker.apply_guardian_signatures(true, Some("incident".into()), &one).is_err());
+
+        // The same guardian signing twice still counts once
+        let duplicate = vec![sign(&breaker, true, incident, &guardians[0]), sign(&breaker, true, incident, &guardians[0])];
+        assert!(breaker.apply_guardian_signatures(true, Some("incident".into()), &duplicate).is_err());
+
+        let two = vec![sign(&breaker, true, incident, &guardians[0]), sign(&breaker, true, incident, &guardians[1])];
+        breaker.apply_guardian_signatures(true, Some("incident".into()), &two).unwrap();
+        assert!(breaker.is_paused());
+        assert!(breaker.ensure_not_paused().is_err());
+
+        // Signatures are bound to the nonce, so they cannot be replayed
+        assert!(breaker.apply_guardian_signatures(true, Some("incident".into()), &two).is_err());
+
+        let resume = vec![sign(&breaker, false, None, &guardians[1]), sign(&breaker, false, None, &guardians[2])];
+        breaker.apply_guardian_signatures(false, None, &resume).unwrap();
+        assert!(breaker.ensure_not_paused().is_ok());
+    }
+
+    #[test]
+    fn test_signatures_are_bound_to_reason_and_network() {
+        let guardians: Vec<_> = (0..2).map(|_| guardian()).collect();
+        let mut breaker = breaker(&guardians, 1);
+
+        // A signature cannot be reused with a different reason
+        let signed = vec![sign(&breaker, true, Some("incident"), &guardians[0])];
+        assert!(breaker.apply_guardian_signatures(true, Some("something else".into()), &signed).is_err());
+        assert!(breaker.apply_guardian_signatures(true, None, &signed).is_err());
+
+        // Or on another network whose breaker is at the same nonce
+        let other = EmergencyPause::new("other-genesis".to_string(), guardians.iter().map(|g| g.0.clone()).collect(), 1).unwrap();
+        let foreign = vec![sign(&other, true, Some("incident"), &guardians[0])];
+        assert!(breaker.apply_guardian_signatures(true, Some("incident".into()), &foreign).is_err());
+        assert!(!breaker.is_paused());
+
+        breaker.apply_guardian_signatures(true, Some("incident".into()), &signed).unwrap();
+        assert_eq!(breaker.status().reason.as_deref(), Some("incident"));
+    }
+
+    #[test]
+    fn test_rejects_unknown_guardian_and_bad_threshold() {
+        let guardians: Vec<_> = (0..2).map(|_| guardian()).collect();
+        let outsider = guardian();
+        assert!(EmergencyPause::new("genesis".to_string(), vec![guardians[0].0.clone()], 2).is_err());
+        assert!(EmergencyPause::new("genesis".to_string(), vec!["did:icn:nothex".to_string()], 1).is_err());
+
+        let mut breaker = breaker(&guardians, 1);
+        let forged = vec![sign(&breaker, true, None, &outsider)];
+        assert!(breaker.apply_guardian_signatures(true, None, &forged).is_err());
+        assert!(!breaker.is_paused());
+    }
+}
@@ -0,0 +1,171 @@
+// File: crates/icn_core/src/events.rs
+
+use crate::activity::{ActivityDigest, ActivityItem};
+use icn_common::Transaction;
+use icn_consensus::{FraudChallenge, SlashingRecord};
+use icn_network::PeerStateChange;
+use icn_smart_contracts::EmittedEvent;
+use icn_zkp::ReserveProof;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use tokio::sync::broadcast;
+
+/// Capacity of the event channel; subscribers that fall further behind
+/// miss the oldest events.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// A transaction waiting in the mempool. `position` counts from the front
+/// of the queue, so lower positions go into a block sooner.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MempoolEntry {
+    pub hash: String,
+    pub position: usize,
+    pub transaction: Transaction,
+}
+
+/// A transaction whose memo matched a search. `block_index` is `None`
+/// while the transaction is still in the mempool, and `memo` holds the
+/// memo's content when the node has it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemoMatch {
+    pub hash: String,
+    pub block_index: Option<u64>,
+    pub transaction: Transaction,
+    pub memo: Option<String>,
+}
+
+/// Why a transaction left the mempool.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RemovalReason {
+    IncludedInBlock { block_index: u64 },
+    /// Its `valid_until` passed before it made it into a block.
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum MempoolEvent {
+    Added {
+        hash: String,
+        transaction: Transaction,
+    },
+    Removed {
+        hash: String,
+        reason: RemovalReason,
+    },
+}
+
+impl MempoolEvent {
+    pub fn hash(&self) -> &str {
+        match self {
+            MempoolEvent::Added { hash, .. } | MempoolEvent::Removed { hash, .. } => hash,
+        }
+    }
+}
+
+/// Something that happened inside the node that clients may want to follow.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum NodeEvent {
+    Mempool(MempoolEvent),
+    /// An event emitted by a script run through `IcnNode::run_script`.
+    Script { caller: String, name: String, data: String },
+    /// The outcome of a validator disputing a committed block.
+    FraudChallenge(FraudChallenge),
+    /// A validator slashed for approving two blocks at one height.
+    ValidatorSlashed(SlashingRecord),
+    /// An event emitted by a smart contract call.
+    Contract(EmittedEvent),
+    /// Activity for a member who wants to hear about it right away.
+    Activity(ActivityItem),
+    /// A member's daily digest of collected activity.
+    ActivityDigest(ActivityDigest),
+    /// A peer connected, dropped, is being reconnected to or was banned.
+    PeerState(PeerStateChange),
+    /// A fresh proof that a currency's balances are covered by its supply.
+    ReserveProof(ReserveProof),
+}
+
+/// Selects the contract events a subscriber wants. Unset fields match
+/// anything; `params` must all match the event's named values. Filters
+/// never match events that don't come from contracts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EventFilter {
+    pub contract_id: Option<String>,
+    pub event: Option<String>,
+    #[serde(default)]
+    pub params: BTreeMap<String, String>,
+}
+
+impl EventFilter {
+    pub fn matches(&self, event: &NodeEvent) -> bool {
+        let NodeEvent::Contract(event) = event else {
+            return false;
+        };
+        self.contract_id.as_ref().is_none_or(|id| *id == event.contract_id)
+            && self.event.as_ref().is_none_or(|name| *name == event.name)
+            && self.params.iter().all(|(name, value)| event.params.get(name) == Some(value))
+    }
+
+    /// Whether any of `filters` matches `event`.
+    pub fn any_match(filters: &[EventFilter], event: &NodeEvent) -> bool {
+        filters.iter().any(|filter| filter.matches(event))
+    }
+}
+
+/// Fans node events out to any number of subscribers. Cloning the bus
+/// gives another handle to the same channel.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<NodeEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        EventBus { sender }
+    }
+
+    pub fn publish(&self, event: NodeEvent) {
+        // Nobody listening is not an error
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NodeEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters_match_contract_events() {
+        let transfer = NodeEvent::Contract(EmittedEvent {
+            contract_id: "token".to_string(),
+            name: "Transfer".to_string(),
+            params: BTreeMap::from([("to".to_string(), "Bob".to_string())]),
+            data: "Alice, Bob".to_string(),
+        });
+        let to_bob = EventFilter {
+            contract_id: Some("token".to_string()),
+            event: Some("Transfer".to_string()),
+            params: BTreeMap::from([("to".to_string(), "Bob".to_string())]),
+        };
+        assert!(to_bob.matches(&transfer));
+        assert!(EventFilter::default().matches(&transfer));
+
+        let to_carol = EventFilter { params: BTreeMap::from([("to".to_string(), "Carol".to_string())]), ..to_bob.clone() };
+        let other_contract = EventFilter { contract_id: Some("ledger".to_string()), ..to_bob.clone() };
+        assert!(!EventFilter::any_match(&[to_carol, other_contract], &transfer));
+
+        let script = NodeEvent::Script { caller: "Alice".to_string(), name: "Transfer".to_string(), data: String::new() };
+        assert!(!EventFilter::default().matches(&script));
+    }
+}
@@ -0,0 +1,121 @@
+// File: crates/icn_core/src/freezes.rs
+
+use icn_common::{IcnResult, IcnError};
+use icn_governance::MAX_FREEZE_DURATION_SECS;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// What allowed an account to be frozen.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum FreezeAuthority {
+    /// A passed governance proposal, by id.
+    Proposal(String),
+    /// A resolved dispute, by id, that the account's owner lost.
+    Dispute(String),
+}
+
+/// A frozen account may not send transactions or call contracts until
+/// `expires_at`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AccountFreeze {
+    pub address: String,
+    pub reason: String,
+    pub authority: FreezeAuthority,
+    pub frozen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Accounts currently frozen, at most one freeze per account.
+#[derive(Debug, Clone, Default)]
+pub struct FreezeRegistry {
+    freezes: BTreeMap<String, AccountFreeze>,
+}
+
+impl FreezeRegistry {
+    /// Freezes `address` for `duration_secs` from `now`, replacing any
+    /// freeze already on it.
+    pub fn freeze(&mut self, address: &str, reason: &str, authority: FreezeAuthority, duration_secs: i64, now: DateTime<Utc>) -> IcnResult<AccountFreeze> {
+        Self::validate(address, reason, duration_secs)?;
+        let freeze = AccountFreeze {
+            address: address.to_string(),
+            reason: reason.to_string(),
+            authority,
+            frozen_at: now,
+            expires_at: now + Duration::seconds(duration_secs),
+        };
+        self.freezes.insert(address.to_string(), freeze.clone());
+        Ok(freeze)
+    }
+
+    /// Checks a freeze's terms without placing it.
+    pub fn validate(address: &str, reason: &str, duration_secs: i64) -> IcnResult<()> {
+        if address.trim().is_empty() || reason.trim().is_empty() {
+            return Err(IcnError::Validation("A freeze must name an account and a reason".into()));
+        }
+        if duration_secs <= 0 || duration_secs > MAX_FREEZE_DURATION_SECS {
+            return Err(IcnError::Validation(format!("Freezes must last between 1 and {} seconds", MAX_FREEZE_DURATION_SECS)));
+        }
+        Ok(())
+    }
+
+    /// Lifts the freeze on `address` early, returning it if there was one.
+    pub fn unfreeze(&mut self, address: &str) -> Option<AccountFreeze> {
+        self.freezes.remove(address)
+    }
+
+    /// The freeze on `address` if it has not expired by `now`.
+    pub fn get(&self, address: &str, now: DateTime<Utc>) -> Option<&AccountFreeze> {
+        self.freezes.get(address).filter(|freeze| freeze.expires_at > now)
+    }
+
+    /// Fails if `address` is frozen at `now`.
+    pub fn ensure_not_frozen(&self, address: &str, now: DateTime<Utc>) -> IcnResult<()> {
+        match self.get(address, now) {
+            Some(freeze) => Err(IcnError::Validation(format!(
+                "Account {} is frozen until {}: {}", address, freeze.expires_at, freeze.reason
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Freezes still in force at `now`, ordered by account.
+    pub fn active(&self, now: DateTime<Utc>) -> Vec<&AccountFreeze> {
+        self.freezes.values().filter(|freeze| freeze.expires_at > now).collect()
+    }
+
+    /// Drops and returns the freezes that have expired by `now`.
+    pub fn remove_expired(&mut self, now: DateTime<Utc>) -> Vec<AccountFreeze> {
+        let expired: Vec<String> = self.freezes.values()
+            .filter(|freeze| freeze.expires_at <= now)
+            .map(|freeze| freeze.address.clone())
+            .collect();
+        expired.iter().filter_map(|address| self.freezes.remove(address)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freezes_expire() {
+        let mut registry = FreezeRegistry::default();
+        let now = Utc::now();
+        let authority = FreezeAuthority::Proposal("p1".to_string());
+        assert!(registry.freeze("Mallory", "Stolen funds", authority.clone(), 0, now).is_err());
+        assert!(registry.freeze("Mallory", "Stolen funds", authority.clone(), MAX_FREEZE_DURATION_SECS + 1, now).is_err());
+        assert!(registry.freeze("Mallory", " ", authority.clone(), 60, now).is_err());
+
+        registry.freeze("Mallory", "Stolen funds", authority, 60, now).unwrap();
+        assert!(registry.ensure_not_frozen("Mallory", now).is_err());
+        assert!(registry.ensure_not_frozen("Alice", now).is_ok());
+        assert_eq!(registry.active(now).len(), 1);
+
+        let later = now + Duration::seconds(60);
+        assert!(registry.ensure_not_frozen("Mallory", later).is_ok());
+        assert!(registry.active(later).is_empty());
+        assert_eq!(registry.remove_expired(later)[0].address, "Mallory");
+        assert!(registry.unfreeze("Mallory").is_none());
+    }
+}
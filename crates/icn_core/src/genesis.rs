@@ -0,0 +1,188 @@
+// File: crates/icn_core/src/genesis.rs
+
+use icn_common::{IcnResult, IcnError, CurrencyType, GovernancePolicy, ProposalCategory, ProposalType};
+use serde::{Serialize, Deserialize};
+use sha2::{Sha256, Digest};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+
+/// Initial state of a network, loaded from `genesis.toml`.
+///
+/// Every node on a network must load the same file: its hash is committed
+/// to in the genesis block, so nodes with different genesis state end up
+/// on different chains and refuse each other's handshakes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GenesisConfig {
+    pub chain_id: String,
+    /// Unix timestamp recorded in the genesis block.
+    #[serde(default)]
+    pub timestamp: i64,
+    #[serde(default)]
+    pub identities: Vec<GenesisIdentity>,
+    #[serde(default)]
+    pub allocations: Vec<GenesisAllocation>,
+    #[serde(default)]
+    pub validators: Vec<GenesisValidator>,
+    #[serde(default)]
+    pub policies: Vec<GenesisPolicy>,
+}
+
+/// A founding member, identified by a DID that embeds its public key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisIdentity {
+    pub id: String,
+    #[serde(default = "default_reputation")]
+    pub reputation: f64,
+    #[serde(default)]
+    pub attributes: BTreeMap<String, String>,
+    /// Roles granted at genesis, such as the first treasurer.
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisAllocation {
+    pub address: String,
+    pub currency_type: CurrencyType,
+    pub amount: f64,
+}
+
+/// A founding identity that takes part in consensus from the first block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisValidator {
+    pub id: String,
+    /// Consensus reputation, between 0 and 1.
+    pub reputation: f64,
+}
+
+/// A governance policy in force before any constitutional proposal passes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GenesisPolicy {
+    pub proposal_type: ProposalType,
+    #[serde(default)]
+    pub category: Option<ProposalCategory>,
+    pub policy: GovernancePolicy,
+}
+
+fn default_reputation() -> f64 {
+    1.0
+}
+
+impl GenesisConfig {
+    pub fn load(path: &str) -> IcnResult<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| IcnError::Config(format!("Failed to read genesis file {}: {}", path, e)))?;
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> IcnResult<Self> {
+        let genesis: GenesisConfig = toml::from_str(contents)
+            .map_err(|e| IcnError::Config(format!("Invalid genesis file: {}", e)))?;
+        genesis.validate()?;
+        Ok(genesis)
+    }
+
+    /// Checks that the configuration describes a consistent starting state.
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.chain_id.trim().is_empty() {
+            return Err(IcnError::Config("Genesis chain_id must not be empty".into()));
+        }
+
+        let mut identities = HashSet::new();
+        for identity in &self.identities {
+            if !identities.insert(identity.id.as_str()) {
+                return Err(IcnError::Config(format!("Duplicate genesis identity {}", identity.id)));
+            }
+            if !identity.reputation.is_finite() || identity.reputation < 0.0 {
+                return Err(IcnError::Config(format!("Invalid reputation for {}", identity.id)));
+            }
+        }
+
+        for allocation in &self.allocations {
+            if !allocation.amount.is_finite() || allocation.amount <= 0.0 {
+                return Err(IcnError::Config(format!("Invalid allocation to {}", allocation.address)));
+            }
+            if matches!(allocation.currency_type, CurrencyType::Bond(_) | CurrencyType::AssetToken(_)) {
+                return Err(IcnError::Config("Bonds and asset tokens cannot be allocated at genesis".into()));
+            }
+        }
+
+        let mut validators = HashSet::new();
+        for validator in &self.validators {
+            if !identities.contains(validator.id.as_str()) {
+                return Err(IcnError::Config(format!("Validator {} is not a genesis identity", validator.id)));
+            }
+            if !validators.insert(validator.id.as_str()) {
+                return Err(IcnError::Config(format!("Duplicate genesis validator {}", validator.id)));
+            }
+            if !(0.0..=1.0).contains(&validator.reputation) {
+                return Err(IcnError::Config(format!("Validator {} reputation must be between 0 and 1", validator.id)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Hex-encoded SHA-256 of the configuration, stored as the genesis
+    /// block's merkle root.
+    pub fn hash(&self) -> String {
+        let canonical = serde_json::to_vec(self).expect("genesis configuration is always serializable");
+        hex::encode(Sha256::digest(&canonical))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS: &str = r#"
+        chain_id = "icn-test"
+        timestamp = 1700000000
+
+        [[identities]]
+        id = "did:icn:founder"
+        reputation = 10.0
+        attributes = { name = "Bakery Co-op" }
+
+        [[allocations]]
+        address = "did:icn:founder"
+        currency_type = "BasicNeeds"
+        amount = 500.0
+
+        [[validators]]
+        id = "did:icn:founder"
+        reputation = 0.9
+
+        [[policies]]
+        proposal_type = "Constitutional"
+        [policies.policy]
+        quorum = 0.75
+        approval_threshold = 0.66
+        voting_duration_secs = 86400
+    "#;
+
+    #[test]
+    fn test_parse_and_hash() {
+        let genesis = GenesisConfig::from_toml(GENESIS).unwrap();
+        assert_eq!(genesis.identities[0].attributes["name"], "Bakery Co-op");
+        assert_eq!(genesis.allocations[0].currency_type, CurrencyType::BasicNeeds);
+        assert_eq!(genesis.policies[0].category, None);
+        assert_eq!(genesis.hash(), GenesisConfig::from_toml(GENESIS).unwrap().hash());
+
+        let mut changed = genesis.clone();
+        changed.allocations[0].amount = 501.0;
+        assert_ne!(genesis.hash(), changed.hash());
+    }
+
+    #[test]
+    fn test_rejects_inconsistent_genesis() {
+        let mut genesis = GenesisConfig::from_toml(GENESIS).unwrap();
+        genesis.validators[0].id = "did:icn:stranger".to_string();
+        assert!(genesis.validate().is_err());
+
+        let mut genesis = GenesisConfig::from_toml(GENESIS).unwrap();
+        genesis.allocations[0].amount = -1.0;
+        assert!(genesis.validate().is_err());
+
+        assert!(GenesisConfig::from_toml("chain_id = \"\"").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use log::{info, warn, error, debug};
+use tracing::{info, warn, error, debug};
 
 pub fn log_info(message: &str) {
     info!("{}", message);
@@ -1,22 +1,52 @@
 // icn_core/src/main.rs
 
-use icn_core::{IcnNode, Config};
-use icn_common::{IcnResult, IcnError, Transaction, Proposal, ProposalType, ProposalCategory, CurrencyType, ProposalStatus};
+use icn_core::{IcnNode, Config, GenesisConfig, TraceStore};
+use icn_common::{IcnResult, IcnError, Transaction, SignatureScheme, Proposal, ProposalType, ProposalCategory, CurrencyType, ProposalStatus};
+use icn_keystore::{Keystore, Passphrase, PASSPHRASE_ENV};
 use std::io::{self, Write};
 use chrono::{Duration, Utc};
-use log::{info, warn, error};
+use tracing::{info, warn, error};
 use uuid::Uuid;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::layer::{Layer, SubscriberExt};
+use tracing_subscriber::reload;
+use tracing_subscriber::util::SubscriberInitExt;
 
 fn main() -> IcnResult<()> {
-    env_logger::init();
+    let trace_store = TraceStore::default();
+    // Operators can turn the level up or down through the runtime settings
+    let (log_level, log_level_handle) = reload::Layer::new(LevelFilter::INFO);
+    tracing_subscriber::registry()
+        .with(log_level)
+        .with(tracing_subscriber::fmt::layer().with_filter(tracing_subscriber::EnvFilter::from_default_env()))
+        .with(trace_store.layer())
+        .init();
 
     let config = Config::load("config.json").unwrap_or_else(|_| {
         warn!("Failed to load config.json, using default configuration");
         Config::default()
     });
 
+    let keystore = Keystore::open(std::env::var("ICN_KEYSTORE_DIR").unwrap_or_else(|_| "keystore".to_string()))?;
+
     info!("Starting InterCooperative Network node...");
-    let node = IcnNode::new(config)?;
+    // Every node of a network must start from the same genesis file
+    let node = match std::env::var("ICN_GENESIS_FILE") {
+        Ok(path) => IcnNode::with_genesis(config, &GenesisConfig::load(&path)?)?,
+        Err(_) => {
+            warn!("ICN_GENESIS_FILE is not set, starting from an empty genesis block");
+            IcnNode::new(config)?
+        }
+    };
+    let mut node = node
+        .with_trace_store(trace_store)
+        .with_log_level_control(move |level| {
+            log_level_handle.reload(level).map_err(|e| IcnError::Config(format!("Failed to change the log level: {}", e)))
+        });
+    if let Ok(key_name) = std::env::var("ICN_VALIDATOR_KEY") {
+        node = node.with_validator_key(&keystore, &key_name, &read_passphrase()?)?;
+        info!("Signing blocks with validator key {}", key_name);
+    }
     node.start()?;
 
     info!("Node started successfully. Type 'help' for available commands.");
@@ -35,6 +65,9 @@ fn main() -> IcnResult<()> {
             "transaction" => process_transaction(&node)?,
             "proposal" => create_proposal(&node)?,
             "balance" => check_balance(&node)?,
+            "keys" => list_keys(&keystore)?,
+            "key-generate" => generate_key(&keystore)?,
+            "key-rotate" => rotate_key(&keystore)?,
             _ => println!("Unknown command. Type 'help' for available commands."),
         }
     }
@@ -52,6 +85,9 @@ fn print_help() {
     println!("  transaction - Create a new transaction");
     println!("  proposal    - Create a new proposal");
     println!("  balance     - Check account balance");
+    println!("  keys        - List keys in the keystore");
+    println!("  key-generate - Generate a new encrypted key");
+    println!("  key-rotate  - Replace a key, keeping the old one as retired");
     println!("  exit        - Exit the application");
 }
 
@@ -81,6 +117,10 @@ fn process_transaction(node: &IcnNode) -> IcnResult<()> {
         currency_type: CurrencyType::BasicNeeds,
         timestamp: Utc::now().timestamp(),
         signature: None,
+        memo: None,
+        signature_scheme: SignatureScheme::Ed25519,
+        valid_until: None,
+        capability: None,
     };
 
     node.process_transaction(transaction)?;
@@ -118,6 +158,12 @@ fn create_proposal(node: &IcnNode) -> IcnResult<()> {
         category: ProposalCategory::Economic,
         required_quorum: 0.66,
         execution_timestamp: None,
+        weight_mode: Default::default(),
+        actions: Vec::new(),
+        scope: Default::default(),
+        ballot: Default::default(),
+        depends_on: Vec::new(),
+        bundle: None,
     };
 
     node.create_proposal(proposal)?;
@@ -136,4 +182,50 @@ fn check_balance(node: &IcnNode) -> IcnResult<()> {
     let balance = node.get_balance(address.trim(), &CurrencyType::BasicNeeds)?;
     println!("Balance: {}", balance);
     Ok(())
-}
\ No newline at end of file
+}
+/// Takes the keystore passphrase from the environment for unattended
+/// nodes, and asks for it otherwise.
+fn read_passphrase() -> IcnResult<Passphrase> {
+    if let Ok(passphrase) = Passphrase::from_env(PASSPHRASE_ENV) {
+        return Ok(passphrase);
+    }
+    print!("Keystore passphrase: ");
+    io::stdout().flush().unwrap();
+    let mut passphrase = String::new();
+    io::stdin().read_line(&mut passphrase).unwrap();
+    Passphrase::new(passphrase.trim_end_matches(['\r', '\n']))
+}
+
+fn read_key_name() -> String {
+    print!("Key name: ");
+    io::stdout().flush().unwrap();
+    let mut name = String::new();
+    io::stdin().read_line(&mut name).unwrap();
+    name.trim().to_string()
+}
+
+fn list_keys(keystore: &Keystore) -> IcnResult<()> {
+    let keys = keystore.list()?;
+    if keys.is_empty() {
+        println!("No keys in {}", keystore.dir().display());
+    }
+    for key in keys {
+        println!("{}  {}  created {}", key.name, key.did, key.created_at);
+    }
+    Ok(())
+}
+
+fn generate_key(keystore: &Keystore) -> IcnResult<()> {
+    let name = read_key_name();
+    let key = keystore.generate(&name, &read_passphrase()?)?;
+    info!("Generated key {} for {}", key.name, key.did);
+    Ok(())
+}
+
+fn rotate_key(keystore: &Keystore) -> IcnResult<()> {
+    let name = read_key_name();
+    let key = keystore.rotate(&name, &read_passphrase()?)?;
+    info!("Rotated key {}; it now signs as {}", key.name, key.did);
+    warn!("Register the new key before restarting a validator with it");
+    Ok(())
+}
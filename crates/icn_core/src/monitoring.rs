@@ -0,0 +1,264 @@
+// File: crates/icn_core/src/monitoring.rs
+
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{AlertThresholds, CurrencyType};
+use icn_currency::SupplyViolation;
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::broadcast;
+use tracing::{error, warn};
+
+/// Number of alerts kept for `recent_alerts`.
+pub const MAX_RECENT_ALERTS: usize = 500;
+
+/// Capacity of the subscriber channel; slow subscribers miss older alerts.
+const ALERT_CHANNEL_CAPACITY: usize = 256;
+
+/// Votes already cast on a proposal before spikes are looked for.
+const MIN_VOTES_FOR_SPIKE: usize = 3;
+
+/// Balances seen for an account and currency within the drain window.
+type BalanceHistory = HashMap<(String, CurrencyType), VecDeque<(DateTime<Utc>, f64)>>;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AlertKind {
+    LargeMint,
+    BalanceDrain,
+    VoteWeightSpike,
+    /// A ledger invariant was broken. Minting stays halted until an audit
+    /// passes again.
+    SupplyViolation,
+}
+
+impl AlertKind {
+    /// Critical alerts mean the ledger itself can't be trusted.
+    pub fn is_critical(&self) -> bool {
+        matches!(self, AlertKind::SupplyViolation)
+    }
+}
+
+/// An unusual pattern spotted in treasury, minting or voting activity.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Alert {
+    pub id: u64,
+    pub kind: AlertKind,
+    /// Account or proposal the alert is about.
+    pub subject: String,
+    pub message: String,
+    pub value: f64,
+    pub threshold: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Watches mints, balances and vote weights for anomalies.
+///
+/// Alerts are kept in a bounded history and broadcast to subscribers as they
+/// are raised. Checks never fail the operation being watched.
+pub struct AnomalyMonitor {
+    thresholds: AlertThresholds,
+    balance_history: BalanceHistory,
+    /// Accounts already reported as drained, until their balance recovers.
+    drained: HashMap<(String, CurrencyType), f64>,
+    vote_weights: HashMap<String, Vec<f64>>,
+    recent: VecDeque<Alert>,
+    next_id: u64,
+    sender: broadcast::Sender<Alert>,
+}
+
+impl AnomalyMonitor {
+    pub fn new(thresholds: AlertThresholds) -> Self {
+        let (sender, _) = broadcast::channel(ALERT_CHANNEL_CAPACITY);
+        AnomalyMonitor {
+            thresholds,
+            balance_history: HashMap::new(),
+            drained: HashMap::new(),
+            vote_weights: HashMap::new(),
+            recent: VecDeque::new(),
+            next_id: 1,
+            sender,
+        }
+    }
+
+    pub fn thresholds(&self) -> &AlertThresholds {
+        &self.thresholds
+    }
+
+    pub fn set_thresholds(&mut self, thresholds: AlertThresholds) {
+        self.thresholds = thresholds;
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Alert> {
+        self.sender.subscribe()
+    }
+
+    /// Returns up to `limit` alerts, newest first.
+    pub fn recent_alerts(&self, limit: usize) -> Vec<Alert> {
+        self.recent.iter().rev().take(limit).cloned().collect()
+    }
+
+    pub fn check_mint(&mut self, address: &str, currency_type: &CurrencyType, amount: f64, now: DateTime<Utc>) -> Option<Alert> {
+        let threshold = self.thresholds.large_mint_amount;
+        if amount < threshold {
+            return None;
+        }
+        let message = format!("Minted {} {:?} to {}", amount, currency_type, address);
+        Some(self.raise(AlertKind::LargeMint, address, message, amount, threshold, now))
+    }
+
+    /// Records a new balance and reports a drain when it has fallen by more
+    /// than the configured fraction of its peak within the window.
+    pub fn check_balance(&mut self, address: &str, currency_type: &CurrencyType, balance: f64, now: DateTime<Utc>) -> Option<Alert> {
+        let key = (address.to_string(), currency_type.clone());
+        let window_start = now - Duration::seconds(self.thresholds.drain_window_secs);
+        let history = self.balance_history.entry(key.clone()).or_default();
+        while history.front().is_some_and(|(at, _)| *at < window_start) {
+            history.pop_front();
+        }
+        history.push_back((now, balance));
+
+        let peak = history.iter().map(|(_, b)| *b).fold(f64::MIN, f64::max);
+        if peak <= 0.0 {
+            return None;
+        }
+        let drained = (peak - balance) / peak;
+        let threshold = self.thresholds.drain_fraction;
+        if drained < threshold {
+            self.drained.remove(&key);
+            return None;
+        }
+        // Report each drain once rather than on every further withdrawal
+        if self.drained.get(&key).is_some_and(|reported_peak| *reported_peak == peak) {
+            return None;
+        }
+        self.drained.insert(key, peak);
+
+        let message = format!(
+            "{} {:?} balance fell from {} to {} within {}s",
+            address, currency_type, peak, balance, self.thresholds.drain_window_secs
+        );
+        Some(self.raise(AlertKind::BalanceDrain, address, message, drained, threshold, now))
+    }
+
+    /// Records a vote weight and reports it when it dwarfs the average of the
+    /// votes already cast on the proposal.
+    pub fn check_vote_weight(&mut self, proposal_id: &str, voter: &str, weight: f64, now: DateTime<Utc>) -> Option<Alert> {
+        let weights = self.vote_weights.entry(proposal_id.to_string()).or_default();
+        let previous = weights.len();
+        let mean = weights.iter().sum::<f64>() / previous.max(1) as f64;
+        weights.push(weight);
+
+        if previous < MIN_VOTES_FOR_SPIKE || mean <= 0.0 {
+            return None;
+        }
+        let factor = weight / mean;
+        let threshold = self.thresholds.vote_weight_spike_factor;
+        if factor < threshold {
+            return None;
+        }
+        let message = format!("{} voted on {} with weight {}, {:.1}x the average of {}", voter, proposal_id, weight, factor, mean);
+        Some(self.raise(AlertKind::VoteWeightSpike, proposal_id, message, factor, threshold, now))
+    }
+
+    pub fn report_supply_violation(&mut self, violation: &SupplyViolation, now: DateTime<Utc>) -> Alert {
+        let (subject, message, value, threshold) = match violation {
+            SupplyViolation::SupplyMismatch { currency_type, total_supply, held } => (
+                format!("{:?}", currency_type),
+                format!("{:?} supply is {} but balances and reserves hold {}", currency_type, total_supply, held),
+                *held,
+                *total_supply,
+            ),
+            SupplyViolation::NegativeBalance { address, currency_type, balance } => (
+                address.clone(),
+                format!("{} has a negative {:?} balance of {}", address, currency_type, balance),
+                *balance,
+                0.0,
+            ),
+            SupplyViolation::LedgerMismatch { address, currency_type, shard_balance, chain_balance } => (
+                address.clone(),
+                format!("{} {:?} shard balance is {} but the chain gives {}", address, currency_type, shard_balance, chain_balance),
+                *shard_balance,
+                *chain_balance,
+            ),
+        };
+        self.raise(AlertKind::SupplyViolation, &subject, message, value, threshold, now)
+    }
+
+    /// Forgets the vote weights of a proposal once voting has closed.
+    pub fn clear_proposal(&mut self, proposal_id: &str) {
+        self.vote_weights.remove(proposal_id);
+    }
+
+    fn raise(&mut self, kind: AlertKind, subject: &str, message: String, value: f64, threshold: f64, now: DateTime<Utc>) -> Alert {
+        if kind.is_critical() {
+            error!("{:?} alert: {}", kind, message);
+        } else {
+            warn!("{:?} alert: {}", kind, message);
+        }
+        let alert = Alert {
+            id: self.next_id,
+            kind,
+            subject: subject.to_string(),
+            message,
+            value,
+            threshold,
+            timestamp: now,
+        };
+        self.next_id += 1;
+
+        if self.recent.len() >= MAX_RECENT_ALERTS {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(alert.clone());
+        // No subscribers is not an error
+        let _ = self.sender.send(alert.clone());
+        alert
+    }
+}
+
+impl Default for AnomalyMonitor {
+    fn default() -> Self {
+        AnomalyMonitor::new(AlertThresholds::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detects_anomalies() {
+        let mut monitor = AnomalyMonitor::new(AlertThresholds {
+            large_mint_amount: 1000.0,
+            drain_fraction: 0.5,
+            drain_window_secs: 3600,
+            vote_weight_spike_factor: 5.0,
+        });
+        let mut alerts = monitor.subscribe();
+        let now = Utc::now();
+        let currency = CurrencyType::BasicNeeds;
+
+        assert!(monitor.check_mint("Alice", &currency, 999.0, now).is_none());
+        assert_eq!(monitor.check_mint("Alice", &currency, 1000.0, now).unwrap().kind, AlertKind::LargeMint);
+        assert_eq!(alerts.try_recv().unwrap().kind, AlertKind::LargeMint);
+
+        assert!(monitor.check_balance("Treasury", &currency, 1000.0, now).is_none());
+        assert!(monitor.check_balance("Treasury", &currency, 600.0, now + Duration::minutes(10)).is_none());
+        let drain = monitor.check_balance("Treasury", &currency, 400.0, now + Duration::minutes(20)).unwrap();
+        assert_eq!(drain.kind, AlertKind::BalanceDrain);
+        assert!((drain.value - 0.6).abs() < 1e-9);
+        assert!(monitor.check_balance("Treasury", &currency, 300.0, now + Duration::minutes(30)).is_none());
+        // Once the peak leaves the window the lower balance is the new baseline
+        assert!(monitor.check_balance("Treasury", &currency, 300.0, now + Duration::hours(3)).is_none());
+
+        for voter in ["a", "b", "c"] {
+            assert!(monitor.check_vote_weight("p1", voter, 1.0, now).is_none());
+        }
+        assert!(monitor.check_vote_weight("p1", "d", 4.0, now).is_none());
+        assert_eq!(monitor.check_vote_weight("p1", "e", 20.0, now).unwrap().kind, AlertKind::VoteWeightSpike);
+
+        let recent = monitor.recent_alerts(10);
+        assert_eq!(recent.len(), 3);
+        assert_eq!(recent[0].kind, AlertKind::VoteWeightSpike);
+        assert!(recent[0].id > recent[2].id);
+    }
+}
@@ -0,0 +1,27 @@
+// File: crates/icn_core/src/receipts.rs
+
+use icn_common::{IcnResult, TransactionReceipt};
+use icn_storage::StorageManager;
+
+/// Prefix of the storage keys transaction receipts are saved under.
+pub const RECEIPT_KEY_PREFIX: &str = "receipt:";
+
+pub fn receipt_storage_key(tx_hash: &str) -> String {
+    format!("{}{}", RECEIPT_KEY_PREFIX, tx_hash)
+}
+
+/// Writes a receipt through to storage, replacing any saved for the same
+/// transaction.
+pub fn save_receipt(storage: &StorageManager, receipt: &TransactionReceipt) -> IcnResult<()> {
+    storage.store_data(&receipt_storage_key(&receipt.tx_hash), serde_json::to_vec(receipt)?)
+}
+
+/// Every receipt saved in storage, for filling the node's lookup table on
+/// startup.
+pub fn load_receipts(storage: &StorageManager) -> IcnResult<Vec<TransactionReceipt>> {
+    storage.list_keys()?
+        .into_iter()
+        .filter(|key| key.starts_with(RECEIPT_KEY_PREFIX))
+        .map(|key| Ok(serde_json::from_slice(&storage.retrieve_data(&key)?)?))
+        .collect()
+}
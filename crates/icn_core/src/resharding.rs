@@ -0,0 +1,77 @@
+// File: crates/icn_core/src/resharding.rs
+
+use icn_common::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+
+/// A shard count change approved by governance, waiting for its block.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PendingReshard {
+    pub proposal_id: String,
+    pub shard_count: u64,
+    /// Index of the first block after which the new shard count applies.
+    pub activation_height: u64,
+}
+
+/// Holds the next reshard until the chain reaches its activation height.
+///
+/// Every node executes the same proposal and commits the same blocks, so
+/// tying the switch to a block index lets them all reshard at the same
+/// point without coordinating directly.
+#[derive(Debug, Clone, Default)]
+pub struct ReshardSchedule {
+    pending: Option<PendingReshard>,
+}
+
+impl ReshardSchedule {
+    /// Schedules a reshard, replacing any that has not activated yet. The
+    /// activation height must be above `latest_height`, the index of the
+    /// newest block, since nodes past it could no longer agree on when the
+    /// change applied.
+    pub fn schedule(&mut self, reshard: PendingReshard, latest_height: u64) -> IcnResult<()> {
+        Self::validate(reshard.activation_height, latest_height)?;
+        self.pending = Some(reshard);
+        Ok(())
+    }
+
+    /// Checks that a reshard activating at `activation_height` could still
+    /// be scheduled.
+    pub fn validate(activation_height: u64, latest_height: u64) -> IcnResult<()> {
+        if activation_height <= latest_height {
+            return Err(IcnError::Sharding(format!(
+                "Activation height {} has already been reached at block {}", activation_height, latest_height
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn pending(&self) -> Option<&PendingReshard> {
+        self.pending.as_ref()
+    }
+
+    /// Removes and returns the pending reshard if a block at `height` has
+    /// activated it.
+    pub fn take_due(&mut self, height: u64) -> Option<PendingReshard> {
+        if self.pending.as_ref()?.activation_height > height {
+            return None;
+        }
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reshard_waits_for_activation_height() {
+        let mut schedule = ReshardSchedule::default();
+        let reshard = PendingReshard { proposal_id: "p1".to_string(), shard_count: 8, activation_height: 10 };
+        assert!(schedule.schedule(reshard.clone(), 10).is_err());
+
+        schedule.schedule(reshard.clone(), 9).unwrap();
+        assert_eq!(schedule.take_due(9), None);
+        assert_eq!(schedule.pending(), Some(&reshard));
+        assert_eq!(schedule.take_due(10), Some(reshard));
+        assert_eq!(schedule.pending(), None);
+    }
+}
@@ -0,0 +1,122 @@
+// File: crates/icn_core/src/rounds.rs
+
+use icn_blockchain::{Block, Blockchain};
+use icn_common::{ConsensusProposal, ConsensusVote, IcnError, IcnResult};
+use icn_consensus::{PoCConsensus, RoundAction, RoundTimeout};
+use icn_network::NetworkManager;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tracing::{error, warn};
+
+/// How long a block producer waits for the committee to decide the height
+/// it proposed at.
+pub const ROUND_DECISION_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Does what consensus rounds ask of the node: sends proposals and votes
+/// to peers, times steps out and adds committed blocks to the chain.
+#[derive(Clone)]
+pub struct RoundDriver {
+    consensus: Arc<RwLock<PoCConsensus>>,
+    blockchain: Arc<RwLock<Blockchain>>,
+    network_manager: Arc<RwLock<NetworkManager>>,
+    timeouts: mpsc::UnboundedSender<RoundTimeout>,
+    commits: broadcast::Sender<Block>,
+}
+
+impl RoundDriver {
+    pub fn new(
+        consensus: Arc<RwLock<PoCConsensus>>,
+        blockchain: Arc<RwLock<Blockchain>>,
+        network_manager: Arc<RwLock<NetworkManager>>,
+        timeouts: mpsc::UnboundedSender<RoundTimeout>,
+        commits: broadcast::Sender<Block>,
+    ) -> Self {
+        RoundDriver { consensus, blockchain, network_manager, timeouts, commits }
+    }
+
+    /// Puts `block` up for consensus and waits up to `wait` for its height
+    /// to be decided. Fails if the committee committed another block there.
+    pub async fn propose_and_wait(&self, block: Block, wait: Duration) -> IcnResult<()> {
+        let mut commits = self.commits.subscribe();
+        let actions = self.consensus.write().await.propose_block(block.clone())?;
+        self.execute(actions).await?;
+
+        let decided = tokio::time::timeout(wait, async {
+            loop {
+                match commits.recv().await {
+                    Ok(committed) if committed.index == block.index => return Ok(committed),
+                    Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return Err(IcnError::Consensus("Consensus rounds stopped".into())),
+                }
+            }
+        }).await
+            .map_err(|_| IcnError::Consensus(format!("Block {} was not decided within {:?}", block.index, wait)))??;
+        if decided.hash != block.hash {
+            return Err(IcnError::Consensus(format!("Another block was committed at height {}", block.index)));
+        }
+        Ok(())
+    }
+
+    pub async fn handle_proposal(&self, proposal: ConsensusProposal, block: Block) -> IcnResult<()> {
+        let actions = self.consensus.write().await.handle_proposal(proposal, block)?;
+        self.execute(actions).await
+    }
+
+    pub async fn handle_vote(&self, vote: ConsensusVote) -> IcnResult<()> {
+        let actions = self.consensus.write().await.handle_consensus_vote(vote)?;
+        self.execute(actions).await
+    }
+
+    pub async fn handle_timeout(&self, timeout: RoundTimeout) -> IcnResult<()> {
+        let actions = self.consensus.write().await.handle_round_timeout(timeout)?;
+        self.execute(actions).await
+    }
+
+    async fn execute(&self, actions: Vec<RoundAction>) -> IcnResult<()> {
+        let mut result = Ok(());
+        for action in actions {
+            match action {
+                RoundAction::Propose(proposal, block) => {
+                    if let Err(e) = self.network_manager.read().await.broadcast_consensus_proposal(proposal, block).await {
+                        warn!("Failed to send proposal: {}", e);
+                    }
+                }
+                RoundAction::Vote(vote) => {
+                    if let Err(e) = self.network_manager.read().await.broadcast_consensus_vote(vote).await {
+                        warn!("Failed to send vote: {}", e);
+                    }
+                }
+                RoundAction::ScheduleTimeout(timeout) => {
+                    let timeouts = self.timeouts.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(timeout.after).await;
+                        let _ = timeouts.send(timeout);
+                    });
+                }
+                RoundAction::Commit(block) => {
+                    if let Err(e) = self.blockchain.write().await.add_block(block.clone()) {
+                        error!("Block {} was committed by consensus but rejected by the chain: {}", block.index, e);
+                        result = Err(e);
+                        continue;
+                    }
+                    let _ = self.commits.send(block);
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Hands round timeouts back to consensus as they come due. The receiver
+/// is only held while this runs, so the loop can be started again after
+/// it is aborted.
+pub async fn run_round_timeouts(driver: RoundDriver, receiver: Arc<Mutex<mpsc::UnboundedReceiver<RoundTimeout>>>) {
+    let mut receiver = receiver.lock().await;
+    while let Some(timeout) = receiver.recv().await {
+        if let Err(e) = driver.handle_timeout(timeout).await {
+            warn!("Failed to move round {} of block {} on: {}", timeout.round, timeout.height, e);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+// File: crates/icn_core/src/script_host.rs
+
+use icn_common::{CurrencyType, IcnError, IcnResult, ProposalStatus};
+use icn_vm::HostInterface;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Role allowed to mint currency from a script.
+pub const ROLE_MINTER: &str = "minter";
+/// Role allowed to allocate shard resources from a script.
+pub const ROLE_RESOURCE_MANAGER: &str = "resource_manager";
+/// Role allowed to change other members' reputation from a script.
+pub const ROLE_REPUTATION_MANAGER: &str = "reputation_manager";
+
+/// A node operation a script was allowed to perform. The node carries
+/// these out, in order, once the script has finished.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum HostCall {
+    Mint { currency_type: CurrencyType, amount: f64 },
+    Allocate { resource: String, amount: u64 },
+    UpdateReputation { address: String, change: f64 },
+    AddMember { coop_id: String, member_id: String },
+    Event { name: String, data: String },
+}
+
+/// Runs a script's system opcodes for one caller. The VM is synchronous, so
+/// the host works from a snapshot of what the caller may do and records the
+/// operations for the node to apply afterwards.
+pub struct NodeHost {
+    caller: String,
+    roles: BTreeSet<String>,
+    proposal_statuses: HashMap<String, ProposalStatus>,
+    cooperatives: HashSet<String>,
+    block_time: i64,
+    calls: Arc<Mutex<Vec<HostCall>>>,
+}
+
+impl NodeHost {
+    /// `cooperatives` are the ids of the cooperatives the caller belongs to.
+    pub fn new(
+        caller: &str,
+        roles: BTreeSet<String>,
+        proposal_statuses: HashMap<String, ProposalStatus>,
+        cooperatives: HashSet<String>,
+    ) -> Self {
+        NodeHost {
+            caller: caller.to_string(),
+            roles,
+            proposal_statuses,
+            cooperatives,
+            block_time: chrono::Utc::now().timestamp(),
+            calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Runs the script as part of the block with this timestamp rather
+    /// than at the current time.
+    pub fn with_block_time(mut self, block_time: i64) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// A handle to the recorded operations that outlives the VM.
+    pub fn calls(&self) -> Arc<Mutex<Vec<HostCall>>> {
+        self.calls.clone()
+    }
+
+    fn require_role(&self, role: &str) -> IcnResult<()> {
+        if !self.roles.contains(role) {
+            return Err(IcnError::Vm(format!("{} lacks the {} role", self.caller, role)));
+        }
+        Ok(())
+    }
+
+    fn record(&self, call: HostCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+/// Reads a currency name as used in scripts. Bonds and asset tokens have
+/// their own issuance rules and cannot be named.
+pub fn parse_currency_type(name: &str) -> IcnResult<CurrencyType> {
+    match name {
+        "BasicNeeds" => Ok(CurrencyType::BasicNeeds),
+        "Education" => Ok(CurrencyType::Education),
+        "Environmental" => Ok(CurrencyType::Environmental),
+        "Community" => Ok(CurrencyType::Community),
+        "Storage" => Ok(CurrencyType::Storage),
+        "Volunteer" => Ok(CurrencyType::Volunteer),
+        "Service" => Ok(CurrencyType::Service),
+        "" => Err(IcnError::Vm("Currency name cannot be empty".into())),
+        custom => Ok(CurrencyType::Custom(custom.to_string())),
+    }
+}
+
+impl HostInterface for NodeHost {
+    fn mint_currency(&mut self, currency_type: &str, amount: f64) -> IcnResult<()> {
+        self.require_role(ROLE_MINTER)?;
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(IcnError::Vm("Mint amount must be positive".into()));
+        }
+        let currency_type = parse_currency_type(currency_type)?;
+        self.record(HostCall::Mint { currency_type, amount });
+        Ok(())
+    }
+
+    /// Votes are signed by the member casting them, which a script run on
+    /// their behalf cannot do.
+    fn vote_on_proposal(&mut self, _proposal_id: &str, _in_favor: bool) -> IcnResult<()> {
+        Err(IcnError::Vm("Scripts cannot vote; votes must be signed by the member".into()))
+    }
+
+    fn allocate_resource(&mut self, resource: &str, amount: i64) -> IcnResult<()> {
+        self.require_role(ROLE_RESOURCE_MANAGER)?;
+        let amount = u64::try_from(amount)
+            .map_err(|_| IcnError::Vm("Resource amount cannot be negative".into()))?;
+        self.record(HostCall::Allocate { resource: resource.to_string(), amount });
+        Ok(())
+    }
+
+    fn update_reputation(&mut self, address: &str, change: i64) -> IcnResult<()> {
+        self.require_role(ROLE_REPUTATION_MANAGER)?;
+        if address == self.caller {
+            return Err(IcnError::Vm("Scripts cannot change their caller's own reputation".into()));
+        }
+        self.record(HostCall::UpdateReputation { address: address.to_string(), change: change as f64 });
+        Ok(())
+    }
+
+    /// Proposals are signed by their proposer, like votes.
+    fn create_proposal(&mut self, _title: &str, _description: &str) -> IcnResult<String> {
+        Err(IcnError::Vm("Scripts cannot open proposals; proposals must be signed by the member".into()))
+    }
+
+    fn get_proposal_status(&self, proposal_id: &str) -> IcnResult<String> {
+        self.proposal_statuses.get(proposal_id)
+            .map(|status| format!("{:?}", status))
+            .ok_or_else(|| IcnError::Vm(format!("Proposal {} not found", proposal_id)))
+    }
+
+    fn add_cooperative_member(&mut self, coop_id: &str, member_id: &str) -> IcnResult<()> {
+        if !self.cooperatives.contains(coop_id) {
+            return Err(IcnError::Vm(format!("{} is not a member of cooperative {}", self.caller, coop_id)));
+        }
+        self.record(HostCall::AddMember { coop_id: coop_id.to_string(), member_id: member_id.to_string() });
+        Ok(())
+    }
+
+    fn emit_event(&mut self, name: &str, data: &str) -> IcnResult<()> {
+        self.record(HostCall::Event { name: name.to_string(), data: data.to_string() });
+        Ok(())
+    }
+
+    fn block_time(&self) -> IcnResult<i64> {
+        Ok(self.block_time)
+    }
+}
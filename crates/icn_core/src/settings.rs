@@ -0,0 +1,219 @@
+// File: crates/icn_core/src/settings.rs
+
+use icn_common::{IcnError, IcnResult};
+use icn_blockchain::DEFAULT_MEMPOOL_CAPACITY;
+use icn_network::{BandwidthLimits, DEFAULT_MAX_PEERS};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use tracing::level_filters::LevelFilter;
+
+/// File in the state directory runtime settings and their audit log are kept in.
+pub const RUNTIME_CONFIG_FILE: &str = "runtime_config.json";
+
+/// Role a capability token's issuer needs to change runtime settings.
+pub const ROLE_NODE_OPERATOR: &str = "node_operator";
+
+/// Audit entries kept before the oldest are dropped.
+pub const MAX_CONFIG_AUDIT_ENTRIES: usize = 1_000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    pub fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::ERROR,
+            LogLevel::Warn => LevelFilter::WARN,
+            LogLevel::Info => LevelFilter::INFO,
+            LogLevel::Debug => LevelFilter::DEBUG,
+            LogLevel::Trace => LevelFilter::TRACE,
+        }
+    }
+}
+
+/// The settings an operator can change while the node runs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeSettings {
+    pub log_level: LogLevel,
+    pub max_peers: usize,
+    pub mempool_capacity: usize,
+    /// Per-peer rate limits.
+    pub bandwidth: BandwidthLimits,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        RuntimeSettings {
+            log_level: LogLevel::default(),
+            max_peers: DEFAULT_MAX_PEERS,
+            mempool_capacity: DEFAULT_MEMPOOL_CAPACITY,
+            bandwidth: BandwidthLimits::default(),
+        }
+    }
+}
+
+impl RuntimeSettings {
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.max_peers == 0 {
+            return Err(IcnError::Config("The peer limit must be greater than zero".into()));
+        }
+        if self.mempool_capacity == 0 {
+            return Err(IcnError::Config("The mempool capacity must be greater than zero".into()));
+        }
+        self.bandwidth.validate()
+    }
+}
+
+/// A request to change some settings. Settings left out are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SettingsChange {
+    #[serde(default)]
+    pub log_level: Option<LogLevel>,
+    #[serde(default)]
+    pub max_peers: Option<usize>,
+    #[serde(default)]
+    pub mempool_capacity: Option<usize>,
+    #[serde(default)]
+    pub bandwidth: Option<BandwidthLimits>,
+}
+
+/// One setting's old and new value.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SettingChange {
+    pub setting: String,
+    pub from: Value,
+    pub to: Value,
+}
+
+impl SettingsChange {
+    /// The settings after this change, and what it changed. Fails if the
+    /// result is invalid or nothing would change.
+    pub fn apply_to(&self, current: &RuntimeSettings) -> IcnResult<(RuntimeSettings, Vec<SettingChange>)> {
+        let updated = RuntimeSettings {
+            log_level: self.log_level.unwrap_or(current.log_level),
+            max_peers: self.max_peers.unwrap_or(current.max_peers),
+            mempool_capacity: self.mempool_capacity.unwrap_or(current.mempool_capacity),
+            bandwidth: self.bandwidth.unwrap_or(current.bandwidth),
+        };
+        updated.validate()?;
+
+        let (before, after) = (serde_json::to_value(current)?, serde_json::to_value(updated)?);
+        let changes: Vec<SettingChange> = match (before, after) {
+            (Value::Object(before), Value::Object(after)) => after.into_iter()
+                .filter(|(setting, to)| before.get(setting) != Some(to))
+                .map(|(setting, to)| SettingChange { from: before[&setting].clone(), setting, to })
+                .collect(),
+            _ => Vec::new(),
+        };
+        if changes.is_empty() {
+            return Err(IcnError::Config("The change leaves every setting as it is".into()));
+        }
+        Ok((updated, changes))
+    }
+}
+
+/// Who changed which settings, and when.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfigAuditEntry {
+    pub id: String,
+    /// DID of the operator whose capability token was used.
+    pub changed_by: String,
+    /// Who the token was given to, per the operator's grant.
+    pub delegate: String,
+    pub changed_at: DateTime<Utc>,
+    pub changes: Vec<SettingChange>,
+}
+
+/// The settings last applied and the log of changes, as saved to the
+/// state directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RuntimeConfig {
+    pub settings: RuntimeSettings,
+    #[serde(default)]
+    audit: VecDeque<ConfigAuditEntry>,
+}
+
+impl RuntimeConfig {
+    /// Reads the config saved in `dir`, if there is one.
+    pub fn load(dir: &Path) -> IcnResult<Option<Self>> {
+        let path = dir.join(RUNTIME_CONFIG_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+    }
+
+    pub fn save(&self, dir: &Path) -> IcnResult<()> {
+        fs::create_dir_all(dir)?;
+        let partial = dir.join(format!("{}.partial", RUNTIME_CONFIG_FILE));
+        fs::write(&partial, serde_json::to_vec_pretty(self)?)?;
+        fs::rename(partial, dir.join(RUNTIME_CONFIG_FILE))?;
+        Ok(())
+    }
+
+    /// Takes on `settings` and logs the change.
+    pub fn record(&mut self, settings: RuntimeSettings, entry: ConfigAuditEntry) {
+        self.settings = settings;
+        self.audit.push_back(entry);
+        while self.audit.len() > MAX_CONFIG_AUDIT_ENTRIES {
+            self.audit.pop_front();
+        }
+    }
+
+    /// Up to `limit` audit entries, newest first.
+    pub fn audit(&self, limit: usize) -> Vec<ConfigAuditEntry> {
+        self.audit.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_are_validated_diffed_and_saved() {
+        let current = RuntimeSettings::default();
+        assert!(SettingsChange::default().apply_to(&current).is_err());
+        assert!(SettingsChange { max_peers: Some(0), ..SettingsChange::default() }.apply_to(&current).is_err());
+        let unchanged = SettingsChange { log_level: Some(LogLevel::Info), ..SettingsChange::default() };
+        assert!(unchanged.apply_to(&current).is_err());
+
+        let change = SettingsChange { log_level: Some(LogLevel::Debug), max_peers: Some(8), ..SettingsChange::default() };
+        let (settings, changes) = change.apply_to(&current).unwrap();
+        assert_eq!((settings.log_level, settings.max_peers, settings.mempool_capacity), (LogLevel::Debug, 8, DEFAULT_MEMPOOL_CAPACITY));
+        assert_eq!(changes, vec![
+            SettingChange { setting: "log_level".to_string(), from: "info".into(), to: "debug".into() },
+            SettingChange { setting: "max_peers".to_string(), from: DEFAULT_MAX_PEERS.into(), to: 8.into() },
+        ]);
+
+        let dir = std::env::temp_dir().join(format!("icn-runtime-config-{}", std::process::id()));
+        let mut config = RuntimeConfig::default();
+        for changed_by in ["alice", "bob"] {
+            let entry = ConfigAuditEntry {
+                id: changed_by.to_string(),
+                changed_by: changed_by.to_string(),
+                delegate: "ops-bot".to_string(),
+                changed_at: Utc::now(),
+                changes: changes.clone(),
+            };
+            config.record(settings, entry);
+        }
+        config.save(&dir).unwrap();
+        let loaded = RuntimeConfig::load(&dir).unwrap().unwrap();
+        assert_eq!(loaded, config);
+        assert_eq!(loaded.audit(1).iter().map(|entry| entry.changed_by.as_str()).collect::<Vec<_>>(), vec!["bob"]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,118 @@
+// File: crates/icn_core/src/signing.rs
+
+use icn_common::{IcnResult, IcnError, Amount, Transaction};
+use icn_identity::IdentityService;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// How long a prepared transaction waits for its signature.
+pub const PREPARED_TRANSACTION_TTL_SECS: i64 = 15 * 60;
+
+/// Checks a transaction's signature against the key its sender registered
+/// for the signature's scheme. For ed25519 that is the key behind the
+/// sender's DID.
+pub fn verify_sender_signature(identity_service: &IdentityService, transaction: &Transaction) -> IcnResult<bool> {
+    let signature = transaction.signature.as_ref()
+        .ok_or_else(|| IcnError::Blockchain("Transaction is not signed".into()))?;
+    identity_service.verify_scheme_signature(&transaction.from, transaction.signature_scheme, &transaction.signing_bytes(), signature)
+}
+
+/// An unsigned transaction built by the node for signing elsewhere, such as
+/// on a hardware wallet or an air-gapped machine.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PreparedTransaction {
+    pub hash: String,
+    pub transaction: Transaction,
+    /// Hex encoding of the exact bytes the signature must cover.
+    pub signing_bytes: String,
+    /// Fee quoted when the transaction was prepared; the fee charged is the
+    /// one in force when the signature arrives.
+    pub estimated_fee: Amount,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Prepared transactions waiting for a signature, keyed by hash.
+#[derive(Debug, Clone, Default)]
+pub struct PreparedTransactions {
+    pending: HashMap<String, PreparedTransaction>,
+}
+
+impl PreparedTransactions {
+    pub fn new() -> Self {
+        PreparedTransactions::default()
+    }
+
+    pub fn insert(&mut self, transaction: Transaction, estimated_fee: Amount, now: DateTime<Utc>) -> PreparedTransaction {
+        self.prune(now);
+        let prepared = PreparedTransaction {
+            hash: transaction.hash(),
+            signing_bytes: hex::encode(transaction.signing_bytes()),
+            transaction,
+            estimated_fee,
+            expires_at: now + Duration::seconds(PREPARED_TRANSACTION_TTL_SECS),
+        };
+        self.pending.insert(prepared.hash.clone(), prepared.clone());
+        prepared
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&PreparedTransaction> {
+        self.pending.get(hash)
+    }
+
+    /// Attaches a signature to a prepared transaction and returns it once
+    /// `verify` accepts it. A bad signature leaves the transaction pending
+    /// so the signer can try again.
+    pub fn attach_signature(
+        &mut self,
+        hash: &str,
+        signature: Vec<u8>,
+        now: DateTime<Utc>,
+        verify: impl FnOnce(&Transaction) -> IcnResult<bool>,
+    ) -> IcnResult<Transaction> {
+        self.prune(now);
+        let prepared = self.pending.get(hash)
+            .ok_or_else(|| IcnError::Blockchain(format!("No prepared transaction {} or it has expired", hash)))?;
+        let mut transaction = prepared.transaction.clone();
+        transaction.signature = Some(signature);
+        if !verify(&transaction)? {
+            return Err(IcnError::Blockchain("Invalid transaction signature".into()));
+        }
+        self.pending.remove(hash);
+        Ok(transaction)
+    }
+
+    fn prune(&mut self, now: DateTime<Utc>) {
+        self.pending.retain(|_, prepared| prepared.expires_at > now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_common::CurrencyType;
+
+    #[test]
+    fn test_prepared_transactions() {
+        let mut prepared = PreparedTransactions::new();
+        let now = Utc::now();
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 5.0, CurrencyType::BasicNeeds, now.timestamp());
+        let entry = prepared.insert(transaction.clone(), Amount::from(0.01), now);
+
+        assert_eq!(entry.hash, transaction.hash());
+        assert_eq!(hex::decode(&entry.signing_bytes).unwrap(), transaction.signing_bytes());
+
+        // A bad signature keeps the transaction around for another attempt
+        assert!(prepared.attach_signature(&entry.hash, vec![0; 64], now, |_| Ok(false)).is_err());
+        assert!(prepared.get(&entry.hash).is_some());
+
+        let signed = prepared.attach_signature(&entry.hash, vec![1; 64], now, |tx| Ok(tx.signature == Some(vec![1; 64]))).unwrap();
+        assert_eq!(signed.hash(), entry.hash);
+        assert!(prepared.get(&entry.hash).is_none());
+
+        let entry = prepared.insert(transaction, Amount::from(0.01), now);
+        let later = now + Duration::seconds(PREPARED_TRANSACTION_TTL_SECS + 1);
+        assert!(prepared.attach_signature(&entry.hash, vec![1; 64], later, |_| Ok(true)).is_err());
+        assert!(prepared.get(&entry.hash).is_none());
+    }
+}
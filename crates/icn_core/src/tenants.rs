@@ -0,0 +1,315 @@
+// File: crates/icn_core/src/tenants.rs
+
+use icn_common::{CurrencyType, IcnResult, IcnError};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use uuid::Uuid;
+
+/// Limits on what one tenant may create on a shared node.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TenantQuotas {
+    pub max_identities: usize,
+    pub max_cooperatives: usize,
+    pub max_currencies: usize,
+    /// Bytes the tenant may keep in node storage, across all its keys.
+    pub storage_bytes: usize,
+}
+
+impl Default for TenantQuotas {
+    fn default() -> Self {
+        TenantQuotas {
+            max_identities: 1_000,
+            max_cooperatives: 10,
+            max_currencies: 10,
+            storage_bytes: 100 * 1024 * 1024,
+        }
+    }
+}
+
+/// How much of its quotas a tenant has used.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct TenantUsage {
+    pub identities: usize,
+    pub cooperatives: usize,
+    pub currencies: usize,
+    pub storage_bytes: usize,
+}
+
+/// An API key is only kept as the hash of its secret; the plaintext is
+/// handed out once, when the key is issued.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TenantApiKey {
+    pub key_id: String,
+    pub secret_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+/// One cooperative community hosted on the node. Everything it creates is
+/// recorded here, so it can only reach its own identities, cooperatives,
+/// currencies and storage.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Tenant {
+    pub id: String,
+    pub name: String,
+    pub quotas: TenantQuotas,
+    pub identities: BTreeSet<String>,
+    pub cooperatives: BTreeSet<String>,
+    /// Currency names as the tenant knows them, without the namespace.
+    pub currencies: BTreeSet<String>,
+    pub api_keys: Vec<TenantApiKey>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Tenant {
+    /// The prefix that keeps this tenant's names apart from every other
+    /// tenant's.
+    pub fn namespace(&self) -> String {
+        format!("tenant:{}", self.id)
+    }
+
+    /// The node-wide currency behind the tenant's currency `name`.
+    pub fn currency(&self, name: &str) -> CurrencyType {
+        CurrencyType::Custom(format!("{}/{}", self.namespace(), name))
+    }
+
+    /// The node-wide storage key behind the tenant's key `key`.
+    pub fn storage_key(&self, key: &str) -> String {
+        format!("{}/{}", self.namespace(), key)
+    }
+
+    /// The storage owner the tenant's data is billed and limited under.
+    pub fn storage_owner(&self) -> String {
+        self.namespace()
+    }
+
+    pub fn owns_identity(&self, identity: &str) -> bool {
+        self.identities.contains(identity)
+    }
+
+    pub fn owns_cooperative(&self, dao_id: &str) -> bool {
+        self.cooperatives.contains(dao_id)
+    }
+
+    /// Counts against the quotas, leaving storage for the storage manager
+    /// to fill in.
+    pub fn usage(&self) -> TenantUsage {
+        TenantUsage {
+            identities: self.identities.len(),
+            cooperatives: self.cooperatives.len(),
+            currencies: self.currencies.len(),
+            storage_bytes: 0,
+        }
+    }
+}
+
+fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// The tenants hosted on a node, with the API keys that act for them.
+#[derive(Debug, Clone, Default)]
+pub struct TenantRegistry {
+    tenants: BTreeMap<String, Tenant>,
+    /// Secret hash to (tenant id, key id), for live keys only.
+    keys: HashMap<String, (String, String)>,
+}
+
+impl TenantRegistry {
+    /// Adds a tenant and issues its first API key, returned in plaintext.
+    pub fn create_tenant(&mut self, id: &str, name: &str, quotas: TenantQuotas, now: DateTime<Utc>) -> IcnResult<(Tenant, String)> {
+        if id.trim().is_empty() || name.trim().is_empty() {
+            return Err(IcnError::Validation("A tenant needs an id and a name".into()));
+        }
+        if id.contains(['/', ':', '.']) {
+            return Err(IcnError::Validation("Tenant ids may not contain '/', ':' or '.'".into()));
+        }
+        if self.tenants.contains_key(id) {
+            return Err(IcnError::Validation(format!("Tenant {} already exists", id)));
+        }
+        self.tenants.insert(id.to_string(), Tenant {
+            id: id.to_string(),
+            name: name.to_string(),
+            quotas,
+            identities: BTreeSet::new(),
+            cooperatives: BTreeSet::new(),
+            currencies: BTreeSet::new(),
+            api_keys: Vec::new(),
+            created_at: now,
+        });
+        let api_key = self.issue_api_key(id, now)?;
+        Ok((self.tenants[id].clone(), api_key))
+    }
+
+    pub fn get(&self, tenant_id: &str) -> IcnResult<&Tenant> {
+        self.tenants.get(tenant_id)
+            .ok_or_else(|| IcnError::Validation(format!("Tenant {} not found", tenant_id)))
+    }
+
+    fn get_mut(&mut self, tenant_id: &str) -> IcnResult<&mut Tenant> {
+        self.tenants.get_mut(tenant_id)
+            .ok_or_else(|| IcnError::Validation(format!("Tenant {} not found", tenant_id)))
+    }
+
+    /// Tenants ordered by id.
+    pub fn list(&self) -> Vec<&Tenant> {
+        self.tenants.values().collect()
+    }
+
+    /// Issues another API key for the tenant, as `<key id>.<secret>`.
+    pub fn issue_api_key(&mut self, tenant_id: &str, now: DateTime<Utc>) -> IcnResult<String> {
+        let key_id = Uuid::new_v4().simple().to_string()[..12].to_string();
+        let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let secret_hash = hash_secret(&secret);
+        self.get_mut(tenant_id)?.api_keys.push(TenantApiKey {
+            key_id: key_id.clone(),
+            secret_hash: secret_hash.clone(),
+            created_at: now,
+            revoked_at: None,
+        });
+        self.keys.insert(secret_hash, (tenant_id.to_string(), key_id.clone()));
+        Ok(format!("{}.{}", key_id, secret))
+    }
+
+    pub fn revoke_api_key(&mut self, tenant_id: &str, key_id: &str, now: DateTime<Utc>) -> IcnResult<()> {
+        let key = self.get_mut(tenant_id)?.api_keys.iter_mut()
+            .find(|key| key.key_id == key_id && key.revoked_at.is_none())
+            .ok_or_else(|| IcnError::Validation(format!("No live API key {} for tenant {}", key_id, tenant_id)))?;
+        key.revoked_at = Some(now);
+        let secret_hash = key.secret_hash.clone();
+        self.keys.remove(&secret_hash);
+        Ok(())
+    }
+
+    /// The tenant a plaintext API key acts for.
+    pub fn authenticate(&self, api_key: &str) -> IcnResult<&Tenant> {
+        let (key_id, secret) = api_key.split_once('.')
+            .ok_or_else(|| IcnError::Identity("Malformed API key".into()))?;
+        match self.keys.get(&hash_secret(secret)) {
+            Some((tenant_id, id)) if id == key_id => self.get(tenant_id),
+            _ => Err(IcnError::Identity("Unknown or revoked API key".into())),
+        }
+    }
+
+    /// Fails unless `tenant_id` created the identity.
+    pub fn ensure_owns_identity(&self, tenant_id: &str, identity: &str) -> IcnResult<()> {
+        if self.get(tenant_id)?.owns_identity(identity) {
+            Ok(())
+        } else {
+            Err(IcnError::Validation(format!("Identity {} not found for tenant {}", identity, tenant_id)))
+        }
+    }
+
+    /// The tenant that registered the cooperative, if any did.
+    pub fn cooperative_owner(&self, dao_id: &str) -> Option<&Tenant> {
+        self.tenants.values().find(|tenant| tenant.owns_cooperative(dao_id))
+    }
+
+    /// Fails unless `tenant_id` registered the cooperative.
+    pub fn ensure_owns_cooperative(&self, tenant_id: &str, dao_id: &str) -> IcnResult<()> {
+        if self.get(tenant_id)?.owns_cooperative(dao_id) {
+            Ok(())
+        } else {
+            Err(IcnError::Validation(format!("Cooperative {} not found for tenant {}", dao_id, tenant_id)))
+        }
+    }
+
+    /// Fails if the tenant has no room for another identity.
+    pub fn check_identity_quota(&self, tenant_id: &str) -> IcnResult<()> {
+        let tenant = self.get(tenant_id)?;
+        if tenant.identities.len() >= tenant.quotas.max_identities {
+            return Err(IcnError::Validation(format!("Tenant {} has reached its limit of {} identities", tenant_id, tenant.quotas.max_identities)));
+        }
+        Ok(())
+    }
+
+    /// Fails if the tenant has no room for another cooperative.
+    pub fn check_cooperative_quota(&self, tenant_id: &str) -> IcnResult<()> {
+        let tenant = self.get(tenant_id)?;
+        if tenant.cooperatives.len() >= tenant.quotas.max_cooperatives {
+            return Err(IcnError::Validation(format!("Tenant {} has reached its limit of {} cooperatives", tenant_id, tenant.quotas.max_cooperatives)));
+        }
+        Ok(())
+    }
+
+    /// Fails if the tenant has no room for the currency `name` or already
+    /// has one by that name.
+    pub fn check_currency_quota(&self, tenant_id: &str, name: &str) -> IcnResult<()> {
+        let tenant = self.get(tenant_id)?;
+        if tenant.currencies.contains(name) {
+            return Err(IcnError::Currency(format!("Tenant {} already has a currency named {}", tenant_id, name)));
+        }
+        if tenant.currencies.len() >= tenant.quotas.max_currencies {
+            return Err(IcnError::Validation(format!("Tenant {} has reached its limit of {} currencies", tenant_id, tenant.quotas.max_currencies)));
+        }
+        Ok(())
+    }
+
+    pub fn record_identity(&mut self, tenant_id: &str, identity: &str) -> IcnResult<()> {
+        self.get_mut(tenant_id)?.identities.insert(identity.to_string());
+        Ok(())
+    }
+
+    pub fn record_cooperative(&mut self, tenant_id: &str, dao_id: &str) -> IcnResult<()> {
+        self.get_mut(tenant_id)?.cooperatives.insert(dao_id.to_string());
+        Ok(())
+    }
+
+    pub fn record_currency(&mut self, tenant_id: &str, name: &str) -> IcnResult<()> {
+        self.get_mut(tenant_id)?.currencies.insert(name.to_string());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_keys_act_for_one_tenant() {
+        let mut registry = TenantRegistry::default();
+        let now = Utc::now();
+        let (bakery, bakery_key) = registry.create_tenant("bakery", "Bread Co-op", TenantQuotas::default(), now).unwrap();
+        let (_, farm_key) = registry.create_tenant("farm", "Farm Co-op", TenantQuotas::default(), now).unwrap();
+        assert!(registry.create_tenant("bakery", "Again", TenantQuotas::default(), now).is_err());
+        assert!(registry.create_tenant("a/b", "Slash", TenantQuotas::default(), now).is_err());
+
+        assert_eq!(registry.authenticate(&bakery_key).unwrap().id, "bakery");
+        assert_eq!(registry.authenticate(&farm_key).unwrap().id, "farm");
+        assert!(registry.authenticate("nonsense").is_err());
+        assert_ne!(bakery.currency("Bread"), registry.get("farm").unwrap().currency("Bread"));
+        assert_eq!(bakery.storage_key("menu"), "tenant:bakery/menu");
+
+        let key_id = bakery_key.split_once('.').unwrap().0;
+        registry.revoke_api_key("bakery", key_id, now).unwrap();
+        assert!(registry.authenticate(&bakery_key).is_err());
+        assert!(registry.revoke_api_key("bakery", key_id, now).is_err());
+        let second = registry.issue_api_key("bakery", now).unwrap();
+        assert_eq!(registry.authenticate(&second).unwrap().id, "bakery");
+    }
+
+    #[test]
+    fn test_quotas_and_ownership() {
+        let mut registry = TenantRegistry::default();
+        let quotas = TenantQuotas { max_identities: 1, max_cooperatives: 1, max_currencies: 1, storage_bytes: 10 };
+        registry.create_tenant("bakery", "Bread Co-op", quotas, Utc::now()).unwrap();
+        registry.create_tenant("farm", "Farm Co-op", TenantQuotas::default(), Utc::now()).unwrap();
+
+        registry.check_identity_quota("bakery").unwrap();
+        registry.record_identity("bakery", "did:icn:alice").unwrap();
+        assert!(registry.check_identity_quota("bakery").is_err());
+        registry.ensure_owns_identity("bakery", "did:icn:alice").unwrap();
+        assert!(registry.ensure_owns_identity("farm", "did:icn:alice").is_err());
+
+        registry.record_cooperative("bakery", "dao1").unwrap();
+        assert!(registry.check_cooperative_quota("bakery").is_err());
+        assert!(registry.ensure_owns_cooperative("farm", "dao1").is_err());
+
+        registry.record_currency("bakery", "Bread").unwrap();
+        assert!(registry.check_currency_quota("bakery", "Bread").is_err());
+        registry.check_currency_quota("farm", "Bread").unwrap();
+        assert_eq!(registry.get("bakery").unwrap().usage().identities, 1);
+    }
+}
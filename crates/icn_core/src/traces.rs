@@ -0,0 +1,206 @@
+// File: crates/icn_core/src/traces.rs
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{span, Event, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+/// Span field that ties events to a transaction or proposal.
+pub const CORRELATION_FIELD: &str = "correlation_id";
+
+/// Number of correlation ids kept before the oldest trace is dropped.
+pub const DEFAULT_TRACE_CAPACITY: usize = 1000;
+
+/// A single log event recorded under a correlation id.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TraceEvent {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub span: String,
+    pub message: String,
+}
+
+/// In-memory store of recent traces, keyed by correlation id.
+#[derive(Clone)]
+pub struct TraceStore {
+    inner: Arc<Mutex<TraceBuffer>>,
+}
+
+struct TraceBuffer {
+    traces: HashMap<String, Vec<TraceEvent>>,
+    order: VecDeque<String>,
+    capacity: usize,
+}
+
+impl TraceStore {
+    pub fn new(capacity: usize) -> Self {
+        TraceStore {
+            inner: Arc::new(Mutex::new(TraceBuffer {
+                traces: HashMap::new(),
+                order: VecDeque::new(),
+                capacity,
+            })),
+        }
+    }
+
+    /// Builds a subscriber layer that feeds this store.
+    pub fn layer(&self) -> TraceLayer {
+        TraceLayer { store: self.clone() }
+    }
+
+    /// Returns the events recorded for a correlation id, oldest first.
+    pub fn get(&self, correlation_id: &str) -> Option<Vec<TraceEvent>> {
+        self.inner.lock().unwrap().traces.get(correlation_id).cloned()
+    }
+
+    fn record(&self, correlation_id: String, event: TraceEvent) {
+        let mut buffer = self.inner.lock().unwrap();
+        if !buffer.traces.contains_key(&correlation_id) {
+            if buffer.order.len() >= buffer.capacity {
+                if let Some(oldest) = buffer.order.pop_front() {
+                    buffer.traces.remove(&oldest);
+                }
+            }
+            buffer.order.push_back(correlation_id.clone());
+        }
+        buffer.traces.entry(correlation_id).or_insert_with(Vec::new).push(event);
+    }
+}
+
+impl Default for TraceStore {
+    fn default() -> Self {
+        TraceStore::new(DEFAULT_TRACE_CAPACITY)
+    }
+}
+
+/// Records every event emitted inside a span carrying a `correlation_id`
+/// field, including events from nested spans in other subsystems.
+pub struct TraceLayer {
+    store: TraceStore,
+}
+
+struct CorrelationId(String);
+
+impl<S> Layer<S> for TraceLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::new(CORRELATION_FIELD);
+        attrs.record(&mut visitor);
+        if let (Some(correlation_id), Some(span)) = (visitor.value, ctx.span(id)) {
+            span.extensions_mut().insert(CorrelationId(correlation_id));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let scope = match ctx.event_scope(event) {
+            Some(scope) => scope,
+            None => return,
+        };
+
+        let mut span_name = None;
+        let mut correlation_id = None;
+        for span in scope {
+            span_name.get_or_insert(span.name());
+            if let Some(id) = span.extensions().get::<CorrelationId>() {
+                correlation_id = Some(id.0.clone());
+                break;
+            }
+        }
+        let correlation_id = match correlation_id {
+            Some(id) => id,
+            None => return,
+        };
+
+        let mut visitor = FieldVisitor::new("message");
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        self.store.record(correlation_id, TraceEvent {
+            timestamp: Utc::now(),
+            level: metadata.level().to_string(),
+            target: metadata.target().to_string(),
+            span: span_name.unwrap_or_default().to_string(),
+            message: visitor.value.unwrap_or_default(),
+        });
+    }
+}
+
+/// Extracts the value of a single named field.
+struct FieldVisitor {
+    name: &'static str,
+    value: Option<String>,
+}
+
+impl FieldVisitor {
+    fn new(name: &'static str) -> Self {
+        FieldVisitor { name, value: None }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == self.name {
+            self.value = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == self.name {
+            self.value = Some(format!("{:?}", value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::{info, info_span, warn};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_events_grouped_by_correlation_id() {
+        let store = TraceStore::new(10);
+        let subscriber = tracing_subscriber::registry().with(store.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!("transaction", correlation_id = %"tx1");
+            let _entered = span.enter();
+            info!("verifying transaction");
+            info_span!("sharding").in_scope(|| warn!("routed to shard {}", 0));
+        });
+        tracing::subscriber::with_default(tracing_subscriber::registry().with(store.layer()), || {
+            info!("not correlated");
+        });
+
+        let trace = store.get("tx1").unwrap();
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace[0].message, "verifying transaction");
+        assert_eq!(trace[1].span, "sharding");
+        assert_eq!(trace[1].level, "WARN");
+        assert_eq!(trace[1].message, "routed to shard 0");
+    }
+
+    #[test]
+    fn test_oldest_trace_evicted() {
+        let store = TraceStore::new(2);
+        let subscriber = tracing_subscriber::registry().with(store.layer());
+
+        tracing::subscriber::with_default(subscriber, || {
+            for id in ["a", "b", "c"] {
+                info_span!("proposal", correlation_id = id).in_scope(|| info!("created"));
+            }
+        });
+
+        assert!(store.get("a").is_none());
+        assert!(store.get("b").is_some());
+        assert!(store.get("c").is_some());
+    }
+}
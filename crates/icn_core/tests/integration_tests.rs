@@ -1,7 +1,7 @@
 // File: crates/icn_core/tests/integration_tests.rs
 
 use icn_core::{IcnNode, Config};
-use icn_common::{Transaction, Proposal, CurrencyType, ProposalStatus, ProposalType, ProposalCategory};
+use icn_common::{Amount, Transaction, SignatureScheme, Proposal, CurrencyType, ProposalStatus, ProposalType, ProposalCategory};
 use tokio::test;
 use std::collections::HashMap;
 use chrono::Utc;
@@ -30,10 +30,14 @@ async fn test_node_creation_and_basic_operations() {
     let transaction = Transaction {
         from: "Alice".to_string(),
         to: "Bob".to_string(),
-        amount: 50.0,
+        amount: Amount::from(50.0),
         currency_type: CurrencyType::BasicNeeds,
         timestamp: chrono::Utc::now().timestamp(),
         signature: None,
+        memo: None,
+        signature_scheme: SignatureScheme::Ed25519,
+        valid_until: None,
+        capability: None,
     };
     assert!(node.process_transaction(transaction).await.is_ok());
 
@@ -50,6 +54,12 @@ async fn test_node_creation_and_basic_operations() {
         category: ProposalCategory::Economic,
         required_quorum: 0.66,
         execution_timestamp: None,
+        weight_mode: Default::default(),
+        actions: Vec::new(),
+        scope: Default::default(),
+        ballot: Default::default(),
+        depends_on: Vec::new(),
+        bundle: None,
     };
     assert!(node.create_proposal(proposal).is_ok());
 
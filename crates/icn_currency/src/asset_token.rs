@@ -1,26 +1,135 @@
+// File: crates/icn_currency/src/asset_token.rs
+
+use icn_common::{IcnResult, IcnError, Amount, CurrencyType};
 use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+
+/// Who asset token units may move between.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum TransferRestriction {
+    Unrestricted,
+    /// Both parties must be on the list or be the issuer.
+    Allowlist(BTreeSet<String>),
+    /// Units can only be sent to or received from the issuer.
+    IssuerOnly,
+}
+
+/// Terms chosen by the issuer when an asset token is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssetTokenTerms {
+    pub name: String,
+    pub description: String,
+    /// Amount of the settlement currency the issuer pays per unit on redemption.
+    pub unit_value: f64,
+    pub supply_cap: Option<Amount>,
+    pub settlement_currency: CurrencyType,
+    pub restriction: TransferRestriction,
+}
 
+/// A fungible token backed by a real-world asset. Holdings are tracked as
+/// balances of `CurrencyType::AssetToken(asset_id)` in the currency system.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AssetToken {
     pub asset_id: String,
-    pub name: String,
-    pub description: String,
-    pub owner: String,
-    pub value: f64,
+    pub issuer: String,
+    pub terms: AssetTokenTerms,
+    pub total_supply: Amount,
 }
 
 impl AssetToken {
-    pub fn new(asset_id: String, name: String, description: String, owner: String, value: f64) -> Self {
-        AssetToken {
+    pub fn new(asset_id: String, issuer: String, terms: AssetTokenTerms) -> IcnResult<Self> {
+        if terms.unit_value < 0.0 {
+            return Err(IcnError::Currency("Unit value cannot be negative".into()));
+        }
+        if terms.supply_cap.is_some_and(|cap| !cap.is_positive()) {
+            return Err(IcnError::Currency("Supply cap must be positive".into()));
+        }
+        if matches!(terms.settlement_currency, CurrencyType::Bond(_) | CurrencyType::AssetToken(_)) {
+            return Err(IcnError::Currency("Asset tokens must settle in a currency".into()));
+        }
+        Ok(AssetToken {
             asset_id,
-            name,
-            description,
-            owner,
-            value,
+            issuer,
+            terms,
+            total_supply: Amount::ZERO,
+        })
+    }
+
+    pub fn currency_type(&self) -> CurrencyType {
+        CurrencyType::AssetToken(self.asset_id.clone())
+    }
+
+    /// Increases the supply, refusing to go past the cap.
+    pub fn mint(&mut self, amount: Amount) -> IcnResult<()> {
+        if !amount.is_positive() {
+            return Err(IcnError::Currency("Mint amount must be positive".into()));
         }
+        let total_supply = self.total_supply.checked_add(amount)
+            .ok_or_else(|| IcnError::Currency("Minting would overflow the total supply".into()))?;
+        if let Some(cap) = self.terms.supply_cap {
+            if total_supply > cap {
+                return Err(IcnError::Currency(format!("Minting would exceed the supply cap of {}", cap)));
+            }
+        }
+        self.total_supply = total_supply;
+        Ok(())
+    }
+
+    pub fn burn(&mut self, amount: Amount) -> IcnResult<()> {
+        if !amount.is_positive() || amount > self.total_supply {
+            return Err(IcnError::Currency("Invalid burn amount".into()));
+        }
+        self.total_supply -= amount;
+        Ok(())
     }
 
-    pub fn transfer(&mut self, new_owner: String) {
-        self.owner = new_owner;
+    pub fn can_transfer(&self, from: &str, to: &str) -> bool {
+        match &self.terms.restriction {
+            TransferRestriction::Unrestricted => true,
+            TransferRestriction::Allowlist(allowed) => [from, to].iter()
+                .all(|party| *party == self.issuer || allowed.contains(*party)),
+            TransferRestriction::IssuerOnly => from == self.issuer || to == self.issuer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(supply_cap: Option<Amount>, restriction: TransferRestriction) -> AssetToken {
+        AssetToken::new("asset-1".to_string(), "Coop".to_string(), AssetTokenTerms {
+            name: "Tractor share".to_string(),
+            description: "Fractional ownership of the shared tractor".to_string(),
+            unit_value: 5.0,
+            supply_cap,
+            settlement_currency: CurrencyType::BasicNeeds,
+            restriction,
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_supply_cap() {
+        let mut capped = token(Some(Amount::from(100.0)), TransferRestriction::Unrestricted);
+        capped.mint(Amount::from(60.0)).unwrap();
+        assert!(capped.mint(Amount::from(50.0)).is_err());
+        capped.mint(Amount::from(40.0)).unwrap();
+        capped.burn(Amount::from(30.0)).unwrap();
+        assert_eq!(capped.total_supply, Amount::from(70.0));
+        assert!(capped.burn(Amount::from(100.0)).is_err());
+    }
+
+    #[test]
+    fn test_transfer_restrictions() {
+        assert!(token(None, TransferRestriction::Unrestricted).can_transfer("Alice", "Bob"));
+
+        let issuer_only = token(None, TransferRestriction::IssuerOnly);
+        assert!(issuer_only.can_transfer("Coop", "Alice"));
+        assert!(issuer_only.can_transfer("Alice", "Coop"));
+        assert!(!issuer_only.can_transfer("Alice", "Bob"));
+
+        let allowlist = token(None, TransferRestriction::Allowlist(["Alice".to_string()].into_iter().collect()));
+        assert!(allowlist.can_transfer("Coop", "Alice"));
+        assert!(!allowlist.can_transfer("Alice", "Bob"));
     }
 }
@@ -0,0 +1,142 @@
+// File: crates/icn_currency/src/audit.rs
+
+use crate::CurrencySystem;
+use icn_common::amount::LEDGER_DECIMALS;
+use icn_common::{Amount, CurrencyType, IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// A broken ledger invariant found by an audit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type")]
+pub enum SupplyViolation {
+    /// Balances and pool reserves don't add up to the recorded supply.
+    SupplyMismatch { currency_type: CurrencyType, total_supply: f64, held: f64 },
+    NegativeBalance { address: String, currency_type: CurrencyType, balance: f64 },
+    /// A shard's balance for an account differs from the one derived from
+    /// the blockchain.
+    LedgerMismatch { address: String, currency_type: CurrencyType, shard_balance: f64, chain_balance: f64 },
+}
+
+impl CurrencySystem {
+    /// Checks that every currency, bond and asset token is fully accounted
+    /// for by balances and pool reserves, and that no balance is negative.
+    ///
+    /// The initial supply given to `add_currency` is not credited to any
+    /// account, so it shows up as a mismatch unless it was zero.
+    pub fn audit_supply(&self) -> Vec<SupplyViolation> {
+        // Summed in wider integers so no number of accounts can overflow
+        let mut held: HashMap<&CurrencyType, i128> = HashMap::new();
+        let mut violations = Vec::new();
+        for (address, balances) in &self.balances {
+            for (currency_type, balance) in balances {
+                if balance.is_negative() {
+                    violations.push(SupplyViolation::NegativeBalance {
+                        address: address.clone(),
+                        currency_type: currency_type.clone(),
+                        balance: balance.to_f64(),
+                    });
+                }
+                *held.entry(currency_type).or_default() += balance.units() as i128;
+            }
+        }
+        for pool in self.pools.values() {
+            *held.entry(&pool.currency_a).or_default() += pool.reserve_a.units() as i128;
+            *held.entry(&pool.currency_b).or_default() += pool.reserve_b.units() as i128;
+        }
+
+        let supplies = self.currencies.values()
+            .map(|currency| (currency.currency_type.clone(), currency.total_supply))
+            .chain(self.bonds.values().map(|bond| (bond.currency_type(), bond.units_outstanding)))
+            .chain(self.asset_tokens.values().map(|token| (token.currency_type(), token.total_supply)));
+        for (currency_type, total_supply) in supplies {
+            let held = held.get(&currency_type).copied().unwrap_or(0);
+            if held != total_supply.units() as i128 {
+                let held = held as f64 / 10f64.powi(LEDGER_DECIMALS as i32);
+                violations.push(SupplyViolation::SupplyMismatch { currency_type, total_supply: total_supply.to_f64(), held });
+            }
+        }
+        violations
+    }
+
+    /// Every account's balance of every currency it has held.
+    pub fn list_balances(&self) -> Vec<(String, CurrencyType, Amount)> {
+        self.balances.iter()
+            .flat_map(|(address, balances)| {
+                balances.iter().map(move |(currency_type, balance)| (address.clone(), currency_type.clone(), *balance))
+            })
+            .collect()
+    }
+
+    /// Stops all minting until `resume_minting` is called, for example
+    /// because an audit found the ledger inconsistent.
+    pub fn halt_minting(&mut self, reason: &str) {
+        self.minting_halted = Some(reason.to_string());
+    }
+
+    pub fn resume_minting(&mut self) {
+        self.minting_halted = None;
+    }
+
+    /// Why minting is halted, if it is.
+    pub fn minting_halted(&self) -> Option<&str> {
+        self.minting_halted.as_deref()
+    }
+
+    pub(crate) fn ensure_minting_allowed(&self) -> IcnResult<()> {
+        match &self.minting_halted {
+            Some(reason) => Err(IcnError::Currency(format!("Minting is halted: {}", reason))),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_finds_violations_and_halts_minting() {
+        let mut system = CurrencySystem::new();
+        system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        system.allocate("Bob", &CurrencyType::Education, 50.0).unwrap();
+        system.transfer("Alice", "Bob", &CurrencyType::BasicNeeds, 40.0).unwrap();
+        system.create_pool(CurrencyType::BasicNeeds, CurrencyType::Education, 0.003).unwrap();
+        system.add_liquidity("Bob", "BasicNeeds/Education", Amount::from(20.0), Amount::from(10.0)).unwrap();
+        assert_eq!(system.audit_supply(), Vec::new());
+
+        // Supply minted without a matching balance
+        system.mint(&CurrencyType::BasicNeeds, 5.0).unwrap();
+        system.update_balance("Carol", &CurrencyType::Education, -1.0).unwrap();
+        system.update_balance("Bob", &CurrencyType::Education, 1.0).unwrap();
+        let violations = system.audit_supply();
+        assert_eq!(violations.len(), 2);
+        assert!(violations.contains(&SupplyViolation::SupplyMismatch {
+            currency_type: CurrencyType::BasicNeeds,
+            total_supply: 105.0,
+            held: 100.0,
+        }));
+        assert!(violations.iter().any(|violation| matches!(violation, SupplyViolation::NegativeBalance { address, .. } if address == "Carol")));
+
+        system.halt_minting("Supply audit failed");
+        assert!(system.mint(&CurrencyType::BasicNeeds, 1.0).is_err());
+        assert!(system.allocate("Dave", &CurrencyType::BasicNeeds, 1.0).is_err());
+        system.resume_minting();
+        system.mint(&CurrencyType::BasicNeeds, 1.0).unwrap();
+    }
+
+    #[test]
+    fn test_audit_is_exact() {
+        let mut system = CurrencySystem::new();
+        system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        system.allocate("Alice", &CurrencyType::Education, 100.0).unwrap();
+        system.create_pool(CurrencyType::BasicNeeds, CurrencyType::Education, 0.003).unwrap();
+        system.add_liquidity("Alice", "BasicNeeds/Education", Amount::from(30.0), Amount::from(70.0)).unwrap();
+        system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Education, Amount::from(1.0 / 3.0), Amount::ZERO).unwrap();
+        assert_eq!(system.audit_supply(), Vec::new());
+
+        // A single millionth out of place is caught
+        system.update_balance("Alice", &CurrencyType::Education, Amount::from_units(1)).unwrap();
+        assert_eq!(system.audit_supply().len(), 1);
+    }
+}
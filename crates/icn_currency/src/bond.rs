@@ -1,42 +1,153 @@
+// File: crates/icn_currency/src/bond.rs
+
+use icn_common::{Amount, IcnResult, IcnError, CurrencyType};
 use serde::{Serialize, Deserialize};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Bond {
-    pub bond_id: String,
+/// Terms chosen by the issuer when a bond is created.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BondTerms {
     pub name: String,
     pub description: String,
-    pub issuer: String,
+    /// Amount of the settlement currency each unit is redeemed for at maturity.
     pub face_value: f64,
+    /// Annual coupon rate applied to the face value.
+    pub coupon_rate: f64,
+    pub coupon_interval_days: i64,
     pub maturity_date: DateTime<Utc>,
-    pub interest_rate: f64,
-    pub owner: String,
+    /// Currency coupons and redemptions are paid in.
+    pub settlement_currency: CurrencyType,
+}
+
+/// A bond issued by a member. Holdings are tracked as balances of
+/// `CurrencyType::Bond(bond_id)` in the currency system.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bond {
+    pub bond_id: String,
+    pub issuer: String,
+    pub terms: BondTerms,
+    pub issue_date: DateTime<Utc>,
+    pub units_outstanding: Amount,
+    /// Date up to which coupons have been paid.
+    pub last_coupon_date: DateTime<Utc>,
 }
 
 impl Bond {
-    pub fn new(bond_id: String, name: String, description: String, issuer: String, face_value: f64, maturity_date: DateTime<Utc>, interest_rate: f64, owner: String) -> Self {
-        Bond {
+    pub fn new(bond_id: String, issuer: String, terms: BondTerms, units: f64, issue_date: DateTime<Utc>) -> IcnResult<Self> {
+        if units <= 0.0 || terms.face_value <= 0.0 {
+            return Err(IcnError::Currency("Bond units and face value must be positive".into()));
+        }
+        if terms.coupon_rate < 0.0 || terms.coupon_interval_days <= 0 {
+            return Err(IcnError::Currency("Invalid coupon terms".into()));
+        }
+        if terms.maturity_date <= issue_date {
+            return Err(IcnError::Currency("Maturity date must be in the future".into()));
+        }
+        if matches!(terms.settlement_currency, CurrencyType::Bond(_) | CurrencyType::AssetToken(_)) {
+            return Err(IcnError::Currency("Bonds must settle in a currency".into()));
+        }
+        Ok(Bond {
             bond_id,
-            name,
-            description,
             issuer,
-            face_value,
-            maturity_date,
-            interest_rate,
-            owner,
+            terms,
+            issue_date,
+            units_outstanding: Amount::from_f64(units)?,
+            last_coupon_date: issue_date,
+        })
+    }
+
+    pub fn currency_type(&self) -> CurrencyType {
+        CurrencyType::Bond(self.bond_id.clone())
+    }
+
+    pub fn is_mature(&self, current_date: DateTime<Utc>) -> bool {
+        current_date >= self.terms.maturity_date
+    }
+
+    /// Coupon owed per unit for one interval.
+    pub fn coupon_per_unit(&self) -> f64 {
+        self.terms.face_value * self.terms.coupon_rate * self.terms.coupon_interval_days as f64 / 365.0
+    }
+
+    /// Number of coupon dates that have passed without payment. The final
+    /// period is cut short at maturity and counts as a full coupon.
+    pub fn due_coupons(&self, current_date: DateTime<Utc>) -> u32 {
+        let interval = Duration::days(self.terms.coupon_interval_days);
+        let mut coupon_date = self.last_coupon_date;
+        let mut due = 0;
+        while coupon_date < self.terms.maturity_date {
+            coupon_date = std::cmp::min(coupon_date + interval, self.terms.maturity_date);
+            if coupon_date > current_date {
+                break;
+            }
+            due += 1;
         }
+        due
     }
 
-    pub fn transfer(&mut self, new_owner: String) {
-        self.owner = new_owner;
+    /// Records `periods` coupons as paid.
+    pub fn advance_coupons(&mut self, periods: u32) {
+        let interval = Duration::days(self.terms.coupon_interval_days);
+        for _ in 0..periods {
+            self.last_coupon_date = std::cmp::min(self.last_coupon_date + interval, self.terms.maturity_date);
+        }
     }
 
+    /// Value of one unit, including coupons accrued since the last payment.
     pub fn calculate_current_value(&self, current_date: DateTime<Utc>) -> f64 {
-        if current_date >= self.maturity_date {
-            self.face_value
-        } else {
-            let years_to_maturity = (self.maturity_date - current_date).num_days() as f64 / 365.0;
-            self.face_value * (1.0 + self.interest_rate).powf(years_to_maturity)
+        if self.is_mature(current_date) {
+            return self.terms.face_value;
+        }
+        let accrued_days = (current_date - self.last_coupon_date).num_days().max(0) as f64;
+        self.terms.face_value * (1.0 + self.terms.coupon_rate * accrued_days / 365.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn terms(issue_date: DateTime<Utc>) -> BondTerms {
+        BondTerms {
+            name: "Solar array".to_string(),
+            description: "Community solar financing".to_string(),
+            face_value: 100.0,
+            coupon_rate: 0.073,
+            coupon_interval_days: 100,
+            maturity_date: issue_date + Duration::days(250),
+            settlement_currency: CurrencyType::BasicNeeds,
         }
     }
+
+    #[test]
+    fn test_coupon_schedule() {
+        let issue_date = Utc::now();
+        let mut bond = Bond::new("bond-1".to_string(), "Alice".to_string(), terms(issue_date), 10.0, issue_date).unwrap();
+        assert!((bond.coupon_per_unit() - 2.0).abs() < 1e-9);
+
+        assert_eq!(bond.due_coupons(issue_date + Duration::days(99)), 0);
+        assert_eq!(bond.due_coupons(issue_date + Duration::days(200)), 2);
+        bond.advance_coupons(2);
+        assert_eq!(bond.due_coupons(issue_date + Duration::days(200)), 0);
+
+        // The short final period ends at maturity
+        assert_eq!(bond.due_coupons(issue_date + Duration::days(400)), 1);
+        bond.advance_coupons(1);
+        assert_eq!(bond.last_coupon_date, bond.terms.maturity_date);
+        assert_eq!(bond.due_coupons(issue_date + Duration::days(1000)), 0);
+    }
+
+    #[test]
+    fn test_invalid_terms() {
+        let issue_date = Utc::now();
+        let mut past = terms(issue_date);
+        past.maturity_date = issue_date - Duration::days(1);
+        assert!(Bond::new("b".to_string(), "Alice".to_string(), past, 1.0, issue_date).is_err());
+
+        let mut self_settling = terms(issue_date);
+        self_settling.settlement_currency = CurrencyType::Bond("other".to_string());
+        assert!(Bond::new("b".to_string(), "Alice".to_string(), self_settling, 1.0, issue_date).is_err());
+
+        assert!(Bond::new("b".to_string(), "Alice".to_string(), terms(issue_date), 0.0, issue_date).is_err());
+    }
 }
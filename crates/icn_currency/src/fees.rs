@@ -0,0 +1,293 @@
+// File: crates/icn_currency/src/fees.rs
+
+use crate::CurrencySystem;
+use icn_common::{IcnResult, IcnError, Amount, CurrencyType, Transaction};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// Account transaction fees are held in until a block is produced.
+pub const FEE_POOL_ACCOUNT: &str = "fees:pool";
+
+/// Account receiving the community share of fees.
+pub const COMMUNITY_FUND_ACCOUNT: &str = "fund:community";
+
+/// How collected fees are shared out. The parts must add up to 1.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeSplit {
+    pub producer: f64,
+    /// Shared among validators in proportion to their reputation.
+    pub validators: f64,
+    pub community_fund: f64,
+}
+
+impl Default for FeeSplit {
+    fn default() -> Self {
+        FeeSplit {
+            producer: 0.4,
+            validators: 0.4,
+            community_fund: 0.2,
+        }
+    }
+}
+
+impl FeeSplit {
+    pub fn validate(&self) -> IcnResult<()> {
+        let parts = [self.producer, self.validators, self.community_fund];
+        if parts.iter().any(|part| !part.is_finite() || *part < 0.0) {
+            return Err(IcnError::Currency("Fee split parts must be non-negative".into()));
+        }
+        if (parts.iter().sum::<f64>() - 1.0).abs() > 1e-9 {
+            return Err(IcnError::Currency("Fee split parts must add up to 1".into()));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeConfig {
+    /// The base fee never drops below this.
+    pub min_base_fee: f64,
+    /// Share of the transferred amount charged on top of the base fee.
+    pub amount_rate: f64,
+    /// Mempool size the base fee steers towards.
+    pub target_pending: usize,
+    /// Largest relative change of the base fee per block.
+    pub max_adjustment: f64,
+    pub split: FeeSplit,
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        FeeConfig {
+            min_base_fee: 0.01,
+            amount_rate: 0.0,
+            target_pending: 50,
+            max_adjustment: 0.125,
+            split: FeeSplit::default(),
+        }
+    }
+}
+
+/// Prices transactions with a base fee that rises while the mempool is
+/// above its target size and falls back while it is below.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeeMarket {
+    config: FeeConfig,
+    base_fee: f64,
+}
+
+impl FeeMarket {
+    pub fn new(config: FeeConfig) -> IcnResult<Self> {
+        config.split.validate()?;
+        if config.min_base_fee < 0.0 || config.amount_rate < 0.0 || config.target_pending == 0 {
+            return Err(IcnError::Currency("Invalid fee configuration".into()));
+        }
+        if config.max_adjustment <= 0.0 || config.max_adjustment >= 1.0 {
+            return Err(IcnError::Currency("Base fee adjustment must be between 0 and 1".into()));
+        }
+        Ok(FeeMarket {
+            base_fee: config.min_base_fee,
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &FeeConfig {
+        &self.config
+    }
+
+    pub fn base_fee(&self) -> f64 {
+        self.base_fee
+    }
+
+    /// Replaces the fee floor and amount rate, raising the current base fee
+    /// to the new floor if needed.
+    pub fn set_rates(&mut self, min_base_fee: f64, amount_rate: f64) -> IcnResult<()> {
+        if !min_base_fee.is_finite() || !amount_rate.is_finite() || min_base_fee < 0.0 || amount_rate < 0.0 {
+            return Err(IcnError::Currency("Invalid fee configuration".into()));
+        }
+        self.config.min_base_fee = min_base_fee;
+        self.config.amount_rate = amount_rate;
+        self.base_fee = self.base_fee.max(min_base_fee);
+        Ok(())
+    }
+
+    /// Fee charged for transferring `amount` at the current base fee,
+    /// rounded to the ledger's precision.
+    pub fn estimate(&self, amount: Amount) -> Amount {
+        Amount::from(self.base_fee + amount.to_f64().abs() * self.config.amount_rate)
+    }
+
+    pub fn quote(&self, amount: Amount) -> FeeEstimate {
+        FeeEstimate {
+            base_fee: self.base_fee,
+            fee: self.estimate(amount),
+            split: self.config.split.clone(),
+        }
+    }
+
+    /// Moves the base fee towards the level that keeps the mempool at its
+    /// target, returning the new base fee.
+    pub fn adjust(&mut self, pending: usize) -> f64 {
+        let target = self.config.target_pending as f64;
+        let pressure = ((pending as f64 - target) / target).clamp(-1.0, 1.0);
+        self.base_fee = (self.base_fee * (1.0 + pressure * self.config.max_adjustment))
+            .max(self.config.min_base_fee);
+        self.base_fee
+    }
+}
+
+impl Default for FeeMarket {
+    fn default() -> Self {
+        FeeMarket::new(FeeConfig::default()).expect("default fee configuration is valid")
+    }
+}
+
+/// What a transfer would cost right now.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FeeEstimate {
+    pub base_fee: f64,
+    pub fee: Amount,
+    pub split: FeeSplit,
+}
+
+/// Where the fees collected in one currency went.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FeeDistribution {
+    pub currency_type: Option<CurrencyType>,
+    pub total: Amount,
+    pub producer: Amount,
+    pub validators: HashMap<String, Amount>,
+    pub community_fund: Amount,
+}
+
+impl CurrencySystem {
+    /// Moves a transaction fee from the payer into the fee pool.
+    pub fn collect_fee(&mut self, payer: &str, currency_type: &CurrencyType, fee: Amount) -> IcnResult<()> {
+        if !fee.is_positive() {
+            return Ok(());
+        }
+        self.transfer_amount(payer, FEE_POOL_ACCOUNT, currency_type, fee)
+    }
+
+    /// Applies a transaction and takes its fee as one operation. The sender
+    /// must be able to cover both before anything moves, and the transfer
+    /// is undone if the fee still can't be collected.
+    pub fn process_transaction_with_fee(&mut self, transaction: &Transaction, fee: Amount) -> IcnResult<()> {
+        let needed = transaction.amount.checked_add(fee)
+            .ok_or_else(|| IcnError::Currency("Amount and fee overflow".into()))?;
+        if self.get_available_amount(&transaction.from, &transaction.currency_type)? < needed {
+            return Err(IcnError::Currency("Insufficient unlocked balance to cover amount and fee".into()));
+        }
+        self.process_transaction(transaction)?;
+        if let Err(e) = self.collect_fee(&transaction.from, &transaction.currency_type, fee) {
+            self.update_balance(&transaction.to, &transaction.currency_type, -transaction.amount)?;
+            self.update_balance(&transaction.from, &transaction.currency_type, transaction.amount)?;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Empties the fee pool, paying each currency out according to `split`.
+    /// `validators` pairs validator ids with their reputation; without any
+    /// reputation to share by, the validator part goes to the community fund.
+    pub fn distribute_fees(&mut self, split: &FeeSplit, producer: &str, validators: &[(String, f64)]) -> IcnResult<Vec<FeeDistribution>> {
+        let pooled: Vec<(CurrencyType, Amount)> = self.balances.get(FEE_POOL_ACCOUNT)
+            .map(|balances| {
+                balances.iter()
+                    .filter(|(_, amount)| amount.is_positive())
+                    .map(|(currency_type, amount)| (currency_type.clone(), *amount))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let total_reputation: f64 = validators.iter().map(|(_, reputation)| reputation.max(0.0)).sum();
+        // Shares round down so together they never exceed the pool
+        let share_of = |total: Amount, fraction: f64| Amount::from_units((total.units() as f64 * fraction).floor() as i64);
+
+        let mut distributions = Vec::new();
+        for (currency_type, total) in pooled {
+            let mut distribution = FeeDistribution {
+                currency_type: Some(currency_type.clone()),
+                total,
+                ..Default::default()
+            };
+
+            let producer_share = share_of(total, split.producer);
+            self.transfer_amount(FEE_POOL_ACCOUNT, producer, &currency_type, producer_share)?;
+            distribution.producer = producer_share;
+
+            if total_reputation > 0.0 {
+                for (validator, reputation) in validators {
+                    let share = share_of(total, split.validators * reputation.max(0.0) / total_reputation);
+                    if share.is_positive() {
+                        self.transfer_amount(FEE_POOL_ACCOUNT, validator, &currency_type, share)?;
+                        *distribution.validators.entry(validator.clone()).or_default() += share;
+                    }
+                }
+            }
+
+            // The community fund takes its share plus whatever rounding left behind
+            let remainder = self.get_balance_amount(FEE_POOL_ACCOUNT, &currency_type)?;
+            self.transfer_amount(FEE_POOL_ACCOUNT, COMMUNITY_FUND_ACCOUNT, &currency_type, remainder)?;
+            distribution.community_fund = remainder;
+            distributions.push(distribution);
+        }
+        Ok(distributions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_fee_follows_congestion() {
+        let mut market = FeeMarket::new(FeeConfig { amount_rate: 0.001, ..Default::default() }).unwrap();
+        assert_eq!(market.estimate(Amount::from(100.0)), Amount::from(0.11));
+
+        let raised = market.adjust(100);
+        assert!((raised - 0.01125).abs() < 1e-9);
+        assert_eq!(market.adjust(50), raised);
+        assert_eq!(market.adjust(0), 0.01);
+
+        assert!(FeeMarket::new(FeeConfig {
+            split: FeeSplit { producer: 0.5, validators: 0.5, community_fund: 0.5 },
+            ..Default::default()
+        }).is_err());
+    }
+
+    #[test]
+    fn test_distribute_fees() {
+        let mut currency_system = CurrencySystem::new();
+        currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        currency_system.collect_fee("Alice", &CurrencyType::BasicNeeds, Amount::from(10.0)).unwrap();
+
+        let validators = vec![("v1".to_string(), 0.75), ("v2".to_string(), 0.25)];
+        let distributions = currency_system.distribute_fees(&FeeSplit::default(), "producer", &validators).unwrap();
+        assert_eq!(distributions.len(), 1);
+
+        let balance = |address: &str| currency_system.get_balance(address, &CurrencyType::BasicNeeds).unwrap();
+        assert!((balance("producer") - 4.0).abs() < 1e-9);
+        assert!((balance("v1") - 3.0).abs() < 1e-9);
+        assert!((balance("v2") - 1.0).abs() < 1e-9);
+        assert!((balance(COMMUNITY_FUND_ACCOUNT) - 2.0).abs() < 1e-9);
+        assert_eq!(balance(FEE_POOL_ACCOUNT), 0.0);
+        assert!(currency_system.distribute_fees(&FeeSplit::default(), "producer", &validators).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transfer_and_fee_move_together() {
+        let mut currency_system = CurrencySystem::new();
+        currency_system.allocate("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 100.0, CurrencyType::BasicNeeds, 0);
+
+        // The amount alone fits, but not with the fee on top
+        assert!(currency_system.process_transaction_with_fee(&transaction, Amount::from(1.0)).is_err());
+        assert_eq!(currency_system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 100.0);
+        assert_eq!(currency_system.get_balance("Bob", &CurrencyType::BasicNeeds).unwrap(), 0.0);
+
+        let transaction = Transaction::new("Alice".to_string(), "Bob".to_string(), 90.0, CurrencyType::BasicNeeds, 0);
+        currency_system.process_transaction_with_fee(&transaction, Amount::from(1.0)).unwrap();
+        assert_eq!(currency_system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 9.0);
+        assert_eq!(currency_system.get_balance(FEE_POOL_ACCOUNT, &CurrencyType::BasicNeeds).unwrap(), 1.0);
+    }
+}
@@ -0,0 +1,285 @@
+// File: crates/icn_currency/src/ledger.rs
+
+use crate::fees::FEE_POOL_ACCOUNT;
+use chrono::{DateTime, Utc};
+use icn_common::{Amount, CurrencyType, Transaction, TransactionMemo};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// One side of a journal entry. Debits and credits are kept apart so each
+/// line reads the way a bookkeeper writes it; one of them is always zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalLine {
+    pub account: String,
+    pub currency_type: CurrencyType,
+    pub debit: Amount,
+    pub credit: Amount,
+}
+
+/// A committed transaction as balanced journal lines.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Hash of the transaction.
+    pub id: String,
+    pub date: DateTime<Utc>,
+    pub block_index: u64,
+    pub description: String,
+    pub lines: Vec<JournalLine>,
+}
+
+impl JournalEntry {
+    /// Builds the entry for a transfer: the recipient's account is debited
+    /// and the sender's credited, and a fee paid on top moves from the
+    /// sender to the fee pool.
+    pub fn from_transaction(transaction: &Transaction, fee: Amount, block_index: u64, chart: &ChartOfAccounts) -> Self {
+        let currency_type = &transaction.currency_type;
+        let mut lines = vec![
+            JournalLine::debit(chart.account_for(&transaction.to), currency_type, transaction.amount),
+            JournalLine::credit(chart.account_for(&transaction.from), currency_type, transaction.amount),
+        ];
+        if fee.is_positive() {
+            lines.push(JournalLine::debit(chart.account_for(FEE_POOL_ACCOUNT), currency_type, fee));
+            lines.push(JournalLine::credit(chart.account_for(&transaction.from), currency_type, fee));
+        }
+        let description = match &transaction.memo {
+            Some(TransactionMemo::Inline(memo)) => memo.clone(),
+            _ => format!("Transfer from {} to {}", transaction.from, transaction.to),
+        };
+        JournalEntry {
+            id: transaction.hash(),
+            date: DateTime::from_timestamp(transaction.timestamp, 0).unwrap_or_default(),
+            block_index,
+            description,
+            lines,
+        }
+    }
+
+    /// Whether debits equal credits in every currency.
+    pub fn is_balanced(&self) -> bool {
+        let mut totals: BTreeMap<String, Amount> = BTreeMap::new();
+        for line in &self.lines {
+            *totals.entry(commodity(&line.currency_type)).or_default() += line.debit - line.credit;
+        }
+        totals.values().all(|total| *total == Amount::ZERO)
+    }
+}
+
+impl JournalLine {
+    fn debit(account: String, currency_type: &CurrencyType, amount: Amount) -> Self {
+        JournalLine { account, currency_type: currency_type.clone(), debit: amount, credit: Amount::ZERO }
+    }
+
+    fn credit(account: String, currency_type: &CurrencyType, amount: Amount) -> Self {
+        JournalLine { account, currency_type: currency_type.clone(), debit: Amount::ZERO, credit: amount }
+    }
+
+    /// Debits as positive amounts and credits as negative ones.
+    pub fn signed_amount(&self) -> Amount {
+        self.debit - self.credit
+    }
+}
+
+/// Maps network addresses onto a cooperative's chart of accounts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChartOfAccounts {
+    /// Account names for exact addresses, such as `"fees:pool"` to
+    /// `"Expenses:Network Fees"`.
+    #[serde(default)]
+    pub accounts: BTreeMap<String, String>,
+    /// Account names for address prefixes such as `"dao:"`. The rest of
+    /// the address becomes a sub-account. The longest matching prefix wins.
+    #[serde(default)]
+    pub prefixes: BTreeMap<String, String>,
+    /// Parent account of every address that isn't mapped.
+    #[serde(default = "default_account")]
+    pub default_account: String,
+}
+
+fn default_account() -> String {
+    "Members".to_string()
+}
+
+impl Default for ChartOfAccounts {
+    fn default() -> Self {
+        ChartOfAccounts {
+            accounts: BTreeMap::new(),
+            prefixes: BTreeMap::new(),
+            default_account: default_account(),
+        }
+    }
+}
+
+impl ChartOfAccounts {
+    pub fn account_for(&self, address: &str) -> String {
+        if let Some(account) = self.accounts.get(address) {
+            return account.clone();
+        }
+        let prefix = self.prefixes.iter()
+            .filter(|(prefix, _)| address.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len());
+        match prefix {
+            Some((prefix, account)) => format!("{}:{}", account, &address[prefix.len()..]),
+            None => format!("{}:{}", self.default_account, address),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LedgerFormat {
+    /// One row per journal line.
+    Csv,
+    /// A plain text journal for Ledger-CLI and compatible tools such as
+    /// hledger.
+    Ledger,
+    /// A QuickBooks IIF import file of general journal entries.
+    Iif,
+}
+
+impl LedgerFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            LedgerFormat::Csv => "text/csv",
+            LedgerFormat::Ledger => "text/plain",
+            LedgerFormat::Iif => "application/x-iif",
+        }
+    }
+}
+
+/// Writes journal entries in the given format.
+pub fn export_journal(entries: &[JournalEntry], format: LedgerFormat) -> String {
+    match format {
+        LedgerFormat::Csv => to_csv(entries),
+        LedgerFormat::Ledger => to_ledger(entries),
+        LedgerFormat::Iif => to_iif(entries),
+    }
+}
+
+/// Name of a currency as a commodity in exports.
+pub fn commodity(currency_type: &CurrencyType) -> String {
+    match currency_type {
+        CurrencyType::Custom(name) => name.clone(),
+        CurrencyType::Bond(id) => format!("Bond-{}", id),
+        CurrencyType::AssetToken(id) => format!("Asset-{}", id),
+        other => format!("{:?}", other),
+    }
+}
+
+fn to_csv(entries: &[JournalEntry]) -> String {
+    let mut csv = String::from("date,entry,block,account,commodity,debit,credit,description\n");
+    for entry in entries {
+        for line in &entry.lines {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{}",
+                entry.date.format("%Y-%m-%d"),
+                entry.id,
+                entry.block_index,
+                csv_field(&line.account),
+                csv_field(&commodity(&line.currency_type)),
+                line.debit,
+                line.credit,
+                csv_field(&entry.description),
+            );
+        }
+    }
+    csv
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_ledger(entries: &[JournalEntry]) -> String {
+    let mut journal = String::new();
+    for entry in entries {
+        let _ = writeln!(journal, "{} * {}", entry.date.format("%Y-%m-%d"), entry.description.replace('\n', " "));
+        let _ = writeln!(journal, "    ; entry: {}", entry.id);
+        for line in &entry.lines {
+            // Commodities with anything but letters must be quoted
+            let commodity = commodity(&line.currency_type);
+            let commodity = if commodity.chars().all(char::is_alphabetic) { commodity } else { format!("\"{}\"", commodity) };
+            let _ = writeln!(journal, "    {}  {} {}", line.account, line.signed_amount(), commodity);
+        }
+        journal.push('\n');
+    }
+    journal
+}
+
+fn to_iif(entries: &[JournalEntry]) -> String {
+    let mut iif = String::from("!TRNS\tTRNSID\tTRNSTYPE\tDATE\tACCNT\tAMOUNT\tMEMO\n");
+    iif.push_str("!SPL\tSPLID\tTRNSTYPE\tDATE\tACCNT\tAMOUNT\tMEMO\n");
+    iif.push_str("!ENDTRNS\n");
+    for entry in entries {
+        let date = entry.date.format("%m/%d/%Y");
+        for (i, line) in entry.lines.iter().enumerate() {
+            // QuickBooks has one currency per file, so each line names its own
+            let memo = format!("{} ({})", entry.description, commodity(&line.currency_type)).replace(['\t', '\n'], " ");
+            let kind = if i == 0 { "TRNS" } else { "SPL" };
+            let _ = writeln!(iif, "{}\t\tGENERAL JOURNAL\t{}\t{}\t{}\t{}", kind, date, line.account, line.signed_amount(), memo);
+        }
+        iif.push_str("ENDTRNS\n");
+    }
+    iif
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart() -> ChartOfAccounts {
+        ChartOfAccounts {
+            accounts: BTreeMap::from([(FEE_POOL_ACCOUNT.to_string(), "Expenses:Network Fees".to_string())]),
+            prefixes: BTreeMap::from([("dao:".to_string(), "Assets:Cooperatives".to_string())]),
+            ..Default::default()
+        }
+    }
+
+    fn entry() -> JournalEntry {
+        let mut transaction = Transaction::new("Alice".to_string(), "dao:bakery".to_string(), 10.0, CurrencyType::BasicNeeds, 1_700_000_000);
+        transaction.memo = Some(TransactionMemo::Inline("Bread, weekly".to_string()));
+        JournalEntry::from_transaction(&transaction, Amount::from(0.5), 3, &chart())
+    }
+
+    #[test]
+    fn test_transfers_become_balanced_entries() {
+        assert_eq!(chart().account_for("Alice"), "Members:Alice");
+        assert_eq!(chart().account_for("dao:bakery"), "Assets:Cooperatives:bakery");
+
+        let entry = entry();
+        assert!(entry.is_balanced());
+        let lines: Vec<(&str, f64)> = entry.lines.iter().map(|line| (line.account.as_str(), line.signed_amount().to_f64())).collect();
+        assert_eq!(lines, vec![
+            ("Assets:Cooperatives:bakery", 10.0),
+            ("Members:Alice", -10.0),
+            ("Expenses:Network Fees", 0.5),
+            ("Members:Alice", -0.5),
+        ]);
+
+        let mut unbalanced = entry;
+        unbalanced.lines.pop();
+        assert!(!unbalanced.is_balanced());
+    }
+
+    #[test]
+    fn test_exports() {
+        let entries = vec![entry()];
+        let csv = export_journal(&entries, LedgerFormat::Csv);
+        assert_eq!(csv.lines().count(), 5);
+        assert!(csv.contains(&format!("2023-11-14,{},3,Assets:Cooperatives:bakery,BasicNeeds,10,0,\"Bread, weekly\"", entries[0].id)));
+
+        let ledger = export_journal(&entries, LedgerFormat::Ledger);
+        assert!(ledger.starts_with("2023-11-14 * Bread, weekly\n"));
+        assert!(ledger.contains("    Members:Alice  -0.5 BasicNeeds\n"));
+
+        let iif = export_journal(&entries, LedgerFormat::Iif);
+        assert!(iif.contains("TRNS\t\tGENERAL JOURNAL\t11/14/2023\tAssets:Cooperatives:bakery\t10\tBread, weekly (BasicNeeds)\n"));
+        assert_eq!(iif.matches("\nSPL\t").count(), 3);
+        assert!(iif.ends_with("ENDTRNS\n"));
+    }
+}
@@ -1,15 +1,32 @@
 // File: crates/icn_currency/src/lib.rs
 
-use icn_common::{IcnResult, IcnError, Transaction, CurrencyType};
+pub mod asset_token;
+pub mod audit;
+pub mod bond;
+pub mod fees;
+pub mod ledger;
+pub mod liquidity;
+pub mod rates;
+
+pub use crate::asset_token::{AssetToken, AssetTokenTerms, TransferRestriction};
+pub use crate::audit::SupplyViolation;
+pub use crate::bond::{Bond, BondTerms};
+pub use crate::fees::{FeeConfig, FeeDistribution, FeeEstimate, FeeMarket, FeeSplit, COMMUNITY_FUND_ACCOUNT, FEE_POOL_ACCOUNT};
+pub use crate::ledger::{export_journal, ChartOfAccounts, JournalEntry, JournalLine, LedgerFormat};
+pub use crate::liquidity::LiquidityPool;
+pub use crate::rates::{ExchangeRate, OracleQuote, RateSource, RateTable, MAX_ORACLE_QUOTE_AGE_SECS, MAX_ORACLE_RATE_CHANGE};
+
+use icn_common::{system_clock, IcnResult, IcnError, Amount, Transaction, CurrencyType, SharedClock, VestingSchedule};
+use icn_common::vesting::locked_amount;
 use std::collections::HashMap;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Serialize, Deserialize};
 
 /// Represents a currency in the system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Currency {
     pub currency_type: CurrencyType,
-    pub total_supply: f64,
+    pub total_supply: Amount,
     pub creation_date: DateTime<Utc>,
     pub last_issuance: DateTime<Utc>,
     pub issuance_rate: f64,
@@ -21,7 +38,7 @@ impl Currency {
         let now = Utc::now();
         Currency {
             currency_type,
-            total_supply: initial_supply,
+            total_supply: Amount::from(initial_supply),
             creation_date: now,
             last_issuance: now,
             issuance_rate,
@@ -30,17 +47,20 @@ impl Currency {
 
     /// Mints new currency, increasing the total supply.
     pub fn mint(&mut self, amount: f64) -> IcnResult<()> {
-        if amount < 0.0 {
+        let amount = Amount::from_f64(amount)?;
+        if amount.is_negative() {
             return Err(IcnError::Currency("Cannot mint negative amount".into()));
         }
-        self.total_supply += amount;
+        self.total_supply = self.total_supply.checked_add(amount)
+            .ok_or_else(|| IcnError::Currency("Minting would overflow the total supply".into()))?;
         self.last_issuance = Utc::now();
         Ok(())
     }
 
     /// Burns currency, decreasing the total supply.
     pub fn burn(&mut self, amount: f64) -> IcnResult<()> {
-        if amount < 0.0 {
+        let amount = Amount::from_f64(amount)?;
+        if amount.is_negative() {
             return Err(IcnError::Currency("Cannot burn negative amount".into()));
         }
         if amount > self.total_supply {
@@ -51,10 +71,40 @@ impl Currency {
     }
 }
 
+/// An account's balance split into what it can spend and what is still vesting.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BalanceSummary {
+    pub total: Amount,
+    pub locked: Amount,
+    pub available: Amount,
+}
+
+/// How funds sent with `CurrencySystem::transfer_vested` unlock: nothing
+/// before `start + cliff`, then linearly until `start + duration`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VestingTerms {
+    pub amount: Amount,
+    pub cliff: Duration,
+    pub duration: Duration,
+    pub start: DateTime<Utc>,
+}
+
 /// Manages multiple currencies and their associated balances.
+///
+/// Balances and supplies are kept as fixed-point `Amount`s so repeated
+/// transfers don't accumulate rounding errors. Methods still take and
+/// return `f64` for callers that work in decimals; each value is rounded
+/// to the ledger's precision once on the way in.
 pub struct CurrencySystem {
     pub currencies: HashMap<CurrencyType, Currency>,
-    balances: HashMap<String, HashMap<CurrencyType, f64>>,
+    balances: HashMap<String, HashMap<CurrencyType, Amount>>,
+    pools: HashMap<String, LiquidityPool>,
+    bonds: HashMap<String, Bond>,
+    asset_tokens: HashMap<String, AssetToken>,
+    vesting: HashMap<String, Vec<VestingSchedule>>,
+    /// Set while minting is halted, with the reason.
+    minting_halted: Option<String>,
+    clock: SharedClock,
 }
 
 impl CurrencySystem {
@@ -63,24 +113,42 @@ impl CurrencySystem {
         CurrencySystem {
             currencies: HashMap::new(),
             balances: HashMap::new(),
+            pools: HashMap::new(),
+            bonds: HashMap::new(),
+            asset_tokens: HashMap::new(),
+            vesting: HashMap::new(),
+            minting_halted: None,
+            clock: system_clock(),
         }
     }
 
+    /// Vests balances and dates issuance against `clock` instead of the
+    /// system clock.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Adds a new currency to the system with the specified initial supply and issuance rate.
     pub fn add_currency(&mut self, currency_type: CurrencyType, initial_supply: f64, issuance_rate: f64) -> IcnResult<()> {
         if self.currencies.contains_key(&currency_type) {
             return Err(IcnError::Currency("Currency already exists".into()));
         }
-        let currency = Currency::new(currency_type.clone(), initial_supply, issuance_rate);
+        let mut currency = Currency::new(currency_type.clone(), initial_supply, issuance_rate);
+        currency.creation_date = self.clock.now();
+        currency.last_issuance = currency.creation_date;
         self.currencies.insert(currency_type, currency);
         Ok(())
     }
 
     /// Mints new units of the specified currency.
     pub fn mint(&mut self, currency_type: &CurrencyType, amount: f64) -> IcnResult<()> {
+        self.ensure_minting_allowed()?;
         let currency = self.currencies.get_mut(currency_type)
             .ok_or_else(|| IcnError::Currency("Currency not found".into()))?;
-        currency.mint(amount)
+        currency.mint(amount)?;
+        currency.last_issuance = self.clock.now();
+        Ok(())
     }
 
     /// Burns units of the specified currency.
@@ -90,9 +158,21 @@ impl CurrencySystem {
         currency.burn(amount)
     }
 
+    /// Credits newly created currency to an account, registering the
+    /// currency if needed. Used for genesis allocations.
+    pub fn allocate(&mut self, address: &str, currency_type: &CurrencyType, amount: f64) -> IcnResult<()> {
+        if !self.currencies.contains_key(currency_type) {
+            self.add_currency(currency_type.clone(), 0.0, 0.0)?;
+        }
+        self.mint(currency_type, amount)?;
+        self.update_balance(address, currency_type, amount)
+    }
+
     /// Processes a transaction by transferring currency between two accounts.
+    /// The amount may not be more precise than the currency's decimals.
     pub fn process_transaction(&mut self, transaction: &Transaction) -> IcnResult<()> {
-        self.transfer(
+        transaction.amount.check_precision(&transaction.currency_type)?;
+        self.transfer_amount(
             &transaction.from,
             &transaction.to,
             &transaction.currency_type,
@@ -102,44 +182,105 @@ impl CurrencySystem {
 
     /// Transfers a specified amount of currency from one account to another.
     pub fn transfer(&mut self, from: &str, to: &str, currency_type: &CurrencyType, amount: f64) -> IcnResult<()> {
-        if amount < 0.0 {
+        self.transfer_amount(from, to, currency_type, Amount::from_f64(amount)?)
+    }
+
+    pub fn transfer_amount(&mut self, from: &str, to: &str, currency_type: &CurrencyType, amount: Amount) -> IcnResult<()> {
+        if amount.is_negative() {
             return Err(IcnError::Currency("Cannot transfer negative amount".into()));
         }
 
-        let from_balance = self.get_balance(from, currency_type)?;
-        if from_balance < amount {
-            return Err(IcnError::Currency("Insufficient balance".into()));
+        if let CurrencyType::AssetToken(asset_id) = currency_type {
+            if !self.get_asset_token(asset_id)?.can_transfer(from, to) {
+                return Err(IcnError::Currency(format!("Transfer of asset token {} is restricted", asset_id)));
+            }
+        }
+
+        if self.get_available_amount(from, currency_type)? < amount {
+            return Err(IcnError::Currency("Insufficient unlocked balance".into()));
         }
 
         self.update_balance(from, currency_type, -amount)?;
-        self.update_balance(to, currency_type, amount)?;
+        if let Err(e) = self.update_balance(to, currency_type, amount) {
+            self.update_balance(from, currency_type, amount)?;
+            return Err(e);
+        }
 
         Ok(())
     }
 
+    /// Transfers funds that the recipient can only spend as they vest under
+    /// `terms`.
+    pub fn transfer_vested(&mut self, from: &str, to: &str, currency_type: &CurrencyType, terms: VestingTerms) -> IcnResult<VestingSchedule> {
+        let VestingTerms { amount, cliff, duration, start } = terms;
+        let schedule = VestingSchedule::new(to.to_string(), currency_type.clone(), amount, start, cliff, duration)?;
+        self.transfer_amount(from, to, currency_type, amount)?;
+        let schedules = self.vesting.entry(to.to_string()).or_default();
+        schedules.retain(|existing| !existing.is_fully_vested(start));
+        schedules.push(schedule.clone());
+        Ok(schedule)
+    }
+
+    /// Part of an account's balance that has not vested yet.
+    pub fn get_locked_balance(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<Amount> {
+        self.vesting.get(address)
+            .map_or(Ok(Amount::ZERO), |schedules| locked_amount(schedules, currency_type, self.clock.now()))
+    }
+
+    /// Balance the account can spend right now.
+    pub fn get_available_balance(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<f64> {
+        Ok(self.get_available_amount(address, currency_type)?.to_f64())
+    }
+
+    /// The exact spendable balance, without converting to a decimal.
+    pub fn get_available_amount(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<Amount> {
+        let locked = self.get_locked_balance(address, currency_type)?;
+        Ok(self.get_balance_amount(address, currency_type)?.checked_sub(locked).unwrap_or_default().max(Amount::ZERO))
+    }
+
+    pub fn get_balance_summary(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<BalanceSummary> {
+        Ok(BalanceSummary {
+            total: self.get_balance_amount(address, currency_type)?,
+            locked: self.get_locked_balance(address, currency_type)?,
+            available: self.get_available_amount(address, currency_type)?,
+        })
+    }
+
+    pub fn get_vesting_schedules(&self, address: &str) -> Vec<&VestingSchedule> {
+        self.vesting.get(address).map_or_else(Vec::new, |schedules| schedules.iter().collect())
+    }
+
     /// Retrieves the balance of an account for a specified currency type.
     pub fn get_balance(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<f64> {
-        Ok(*self.balances
+        Ok(self.get_balance_amount(address, currency_type)?.to_f64())
+    }
+
+    /// The exact balance, without converting to a decimal.
+    pub fn get_balance_amount(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<Amount> {
+        Ok(self.balances
             .get(address)
             .and_then(|balances| balances.get(currency_type))
-            .unwrap_or(&0.0))
+            .copied()
+            .unwrap_or_default())
     }
 
-    /// Updates the balance of an account by a specified amount.
-    fn update_balance(&mut self, address: &str, currency_type: &CurrencyType, amount: f64) -> IcnResult<()> {
+    /// Updates the balance of an account by a specified amount. Fails
+    /// rather than wrapping if the balance would overflow.
+    pub(crate) fn update_balance(&mut self, address: &str, currency_type: &CurrencyType, amount: impl Into<Amount>) -> IcnResult<()> {
         let balance = self.balances
             .entry(address.to_string())
             .or_insert_with(HashMap::new)
             .entry(currency_type.clone())
-            .or_insert(0.0);
-        *balance += amount;
+            .or_default();
+        *balance = balance.checked_add(amount.into())
+            .ok_or_else(|| IcnError::Currency(format!("Balance of {} would overflow", address)))?;
         Ok(())
     }
 
     /// Retrieves the total supply of a specified currency.
     pub fn get_total_supply(&self, currency_type: &CurrencyType) -> IcnResult<f64> {
         self.currencies.get(currency_type)
-            .map(|currency| currency.total_supply)
+            .map(|currency| currency.total_supply.to_f64())
             .ok_or_else(|| IcnError::Currency("Currency not found".into()))
     }
 
@@ -170,50 +311,245 @@ impl CurrencySystem {
             .ok_or_else(|| IcnError::Currency("Currency not found".into()))
     }
 
-    /// Exchanges currency from one type to another.
-    pub fn exchange_currency(&mut self, from: &str, source_currency: &CurrencyType, target_currency: &CurrencyType, amount: f64) -> IcnResult<()> {
-        // Check if both currencies exist
-        if !self.currencies.contains_key(source_currency) || !self.currencies.contains_key(target_currency) {
+    /// Creates a liquidity pool for a currency pair. Pools are only created
+    /// through governance, so callers are expected to have authorized this.
+    pub fn create_pool(&mut self, currency_a: CurrencyType, currency_b: CurrencyType, fee_rate: f64) -> IcnResult<String> {
+        if !self.currencies.contains_key(&currency_a) || !self.currencies.contains_key(&currency_b) {
             return Err(IcnError::Currency("Invalid currency type".into()));
         }
+        let pool = LiquidityPool::new(currency_a, currency_b, fee_rate)?;
+        if self.pools.contains_key(&pool.id) {
+            return Err(IcnError::Currency("Pool already exists".into()));
+        }
+        let pool_id = pool.id.clone();
+        self.pools.insert(pool_id.clone(), pool);
+        Ok(pool_id)
+    }
 
-        // Check if the user has sufficient balance
-        let source_balance = self.get_balance(from, source_currency)?;
-        if source_balance < amount {
-            return Err(IcnError::Currency("Insufficient balance for exchange".into()));
+    /// Updates the fee rate of an existing pool.
+    pub fn set_pool_fee_rate(&mut self, pool_id: &str, fee_rate: f64) -> IcnResult<()> {
+        self.get_pool_mut(pool_id)?.set_fee_rate(fee_rate)
+    }
+
+    /// Retrieves a liquidity pool by its identifier.
+    pub fn get_pool(&self, pool_id: &str) -> IcnResult<&LiquidityPool> {
+        self.pools.get(pool_id)
+            .ok_or_else(|| IcnError::Currency("Pool not found".into()))
+    }
+
+    /// Lists all liquidity pools.
+    pub fn list_pools(&self) -> Vec<&LiquidityPool> {
+        self.pools.values().collect()
+    }
+
+    /// Moves a provider's currencies into a pool in exchange for pool shares.
+    pub fn add_liquidity(&mut self, provider: &str, pool_id: &str, amount_a: Amount, amount_b: Amount) -> IcnResult<Amount> {
+        let pool = self.get_pool(pool_id)?;
+        let (currency_a, currency_b) = (pool.currency_a.clone(), pool.currency_b.clone());
+        if self.get_available_amount(provider, &currency_a)? < amount_a || self.get_available_amount(provider, &currency_b)? < amount_b {
+            return Err(IcnError::Currency("Insufficient balance for liquidity".into()));
         }
 
-        // Calculate exchange rate (simplified for demonstration)
-        let exchange_rate = match (source_currency, target_currency) {
-            (CurrencyType::BasicNeeds, CurrencyType::Education) => 1.2,
-            (CurrencyType::Education, CurrencyType::BasicNeeds) => 0.8,
-            (CurrencyType::BasicNeeds, CurrencyType::Environmental) => 1.5,
-            (CurrencyType::Environmental, CurrencyType::BasicNeeds) => 0.6,
-            (CurrencyType::Education, CurrencyType::Environmental) => 1.3,
-            (CurrencyType::Environmental, CurrencyType::Education) => 0.7,
-            _ => 1.0, // Default to 1:1 for other combinations or same currency
-        };
+        let (used_a, used_b, shares) = self.get_pool_mut(pool_id)?.add_liquidity(provider, amount_a, amount_b)?;
+        self.update_balance(provider, &currency_a, -used_a)?;
+        self.update_balance(provider, &currency_b, -used_b)?;
+        Ok(shares)
+    }
 
-        let target_amount = amount * exchange_rate;
+    /// Burns a provider's pool shares and returns their portion of the reserves, including earned fees.
+    pub fn remove_liquidity(&mut self, provider: &str, pool_id: &str, shares: Amount) -> IcnResult<(Amount, Amount)> {
+        let pool = self.get_pool_mut(pool_id)?;
+        let (currency_a, currency_b) = (pool.currency_a.clone(), pool.currency_b.clone());
+        let (amount_a, amount_b) = pool.remove_liquidity(provider, shares)?;
+        self.update_balance(provider, &currency_a, amount_a)?;
+        self.update_balance(provider, &currency_b, amount_b)?;
+        Ok((amount_a, amount_b))
+    }
+
+    /// Exchanges currency from one type to another through the pair's liquidity pool.
+    /// Returns the amount of target currency received.
+    pub fn exchange_currency(&mut self, from: &str, source_currency: &CurrencyType, target_currency: &CurrencyType, amount: Amount, min_amount_out: Amount) -> IcnResult<Amount> {
+        let pool_id = liquidity::pool_id(source_currency, target_currency);
+        if source_currency == target_currency || !self.pools.contains_key(&pool_id) {
+            return Err(IcnError::Currency("No liquidity pool for currency pair".into()));
+        }
 
-        // Perform the exchange
+        let source_balance = self.get_available_amount(from, source_currency)?;
+        if source_balance < amount {
+            return Err(IcnError::Currency("Insufficient balance for exchange".into()));
+        }
+
+        let target_amount = self.get_pool_mut(&pool_id)?.swap(source_currency, amount, min_amount_out)?;
         self.update_balance(from, source_currency, -amount)?;
         self.update_balance(from, target_currency, target_amount)?;
 
-        // Update currency supplies
-        let source_currency = self.currencies.get_mut(source_currency).unwrap();
-        source_currency.burn(amount)?;
+        Ok(target_amount)
+    }
 
-        let target_currency = self.currencies.get_mut(target_currency).unwrap();
-        target_currency.mint(target_amount)?;
+    /// Issues a bond and credits all of its units to the issuer, who can
+    /// then sell them to other members.
+    pub fn issue_bond(&mut self, issuer: &str, terms: BondTerms, units: f64, issue_date: DateTime<Utc>) -> IcnResult<String> {
+        let bond_id = format!("bond-{}", self.bonds.len() + 1);
+        let bond = Bond::new(bond_id.clone(), issuer.to_string(), terms, units, issue_date)?;
+        self.update_balance(issuer, &bond.currency_type(), units)?;
+        self.bonds.insert(bond_id.clone(), bond);
+        Ok(bond_id)
+    }
 
-        Ok(())
+    pub fn get_bond(&self, bond_id: &str) -> IcnResult<&Bond> {
+        self.bonds.get(bond_id)
+            .ok_or_else(|| IcnError::Currency("Bond not found".into()))
+    }
+
+    pub fn list_bonds(&self) -> Vec<&Bond> {
+        self.bonds.values().collect()
+    }
+
+    /// Pays every coupon that has come due to the bond's current holders out
+    /// of the issuer's settlement balance. Returns the total amount paid.
+    pub fn pay_bond_coupons(&mut self, bond_id: &str, current_date: DateTime<Utc>) -> IcnResult<f64> {
+        let bond = self.get_bond(bond_id)?;
+        let periods = bond.due_coupons(current_date);
+        if periods == 0 {
+            return Ok(0.0);
+        }
+
+        let (issuer, settlement_currency) = (bond.issuer.clone(), bond.terms.settlement_currency.clone());
+        let per_unit = bond.coupon_per_unit() * periods as f64;
+        let payments: Vec<(String, f64)> = self.holders_of(&bond.currency_type())
+            .into_iter()
+            .filter(|(holder, _)| *holder != issuer)
+            .map(|(holder, units)| (holder, units * per_unit))
+            .collect();
+
+        let total: f64 = payments.iter().map(|(_, amount)| amount).sum();
+        if self.get_available_balance(&issuer, &settlement_currency)? < total {
+            return Err(IcnError::Currency("Issuer cannot cover coupon payments".into()));
+        }
+        for (holder, amount) in &payments {
+            self.update_balance(&issuer, &settlement_currency, -amount)?;
+            self.update_balance(holder, &settlement_currency, *amount)?;
+        }
+        self.get_bond_mut(bond_id)?.advance_coupons(periods);
+        Ok(total)
+    }
+
+    /// Redeems all of a holder's units at face value once the bond has
+    /// matured and its coupons are paid. Returns the amount received.
+    pub fn redeem_bond(&mut self, bond_id: &str, holder: &str, current_date: DateTime<Utc>) -> IcnResult<f64> {
+        let bond = self.get_bond(bond_id)?;
+        if !bond.is_mature(current_date) {
+            return Err(IcnError::Currency("Bond has not matured".into()));
+        }
+        if bond.due_coupons(current_date) > 0 {
+            return Err(IcnError::Currency("Outstanding coupons must be paid before redemption".into()));
+        }
+
+        let bond_currency = bond.currency_type();
+        let units = self.get_available_amount(holder, &bond_currency)?;
+        if !units.is_positive() {
+            return Err(IcnError::Currency("No bond units to redeem".into()));
+        }
+        let (issuer, settlement_currency) = (bond.issuer.clone(), bond.terms.settlement_currency.clone());
+        let amount = units.to_f64() * bond.terms.face_value;
+        if holder != issuer {
+            if self.get_available_balance(&issuer, &settlement_currency)? < amount {
+                return Err(IcnError::Currency("Issuer cannot cover redemption".into()));
+            }
+            self.update_balance(&issuer, &settlement_currency, -amount)?;
+            self.update_balance(holder, &settlement_currency, amount)?;
+        }
+        self.update_balance(holder, &bond_currency, -units)?;
+        self.get_bond_mut(bond_id)?.units_outstanding -= units;
+        Ok(amount)
+    }
+
+    /// Creates an asset token and mints its initial supply to the issuer.
+    pub fn issue_asset_token(&mut self, issuer: &str, terms: AssetTokenTerms, initial_supply: Amount) -> IcnResult<String> {
+        let asset_id = format!("asset-{}", self.asset_tokens.len() + 1);
+        let mut token = AssetToken::new(asset_id.clone(), issuer.to_string(), terms)?;
+        if initial_supply.is_positive() {
+            token.mint(initial_supply)?;
+            self.update_balance(issuer, &token.currency_type(), initial_supply)?;
+        }
+        self.asset_tokens.insert(asset_id.clone(), token);
+        Ok(asset_id)
+    }
+
+    pub fn get_asset_token(&self, asset_id: &str) -> IcnResult<&AssetToken> {
+        self.asset_tokens.get(asset_id)
+            .ok_or_else(|| IcnError::Currency("Asset token not found".into()))
+    }
+
+    pub fn list_asset_tokens(&self) -> Vec<&AssetToken> {
+        self.asset_tokens.values().collect()
+    }
+
+    /// Mints additional units to the issuer, within the supply cap.
+    pub fn mint_asset_tokens(&mut self, asset_id: &str, caller: &str, amount: Amount) -> IcnResult<()> {
+        self.ensure_minting_allowed()?;
+        let token = self.get_asset_token_mut(asset_id)?;
+        if token.issuer != caller {
+            return Err(IcnError::Currency("Only the issuer can mint asset tokens".into()));
+        }
+        token.mint(amount)?;
+        let currency_type = token.currency_type();
+        self.update_balance(caller, &currency_type, amount)
+    }
+
+    /// Returns units to the issuer, who pays out their value in the
+    /// settlement currency. Returns the amount received.
+    pub fn redeem_asset_tokens(&mut self, asset_id: &str, holder: &str, amount: Amount) -> IcnResult<Amount> {
+        let token = self.get_asset_token(asset_id)?;
+        let currency_type = token.currency_type();
+        if !amount.is_positive() || self.get_available_amount(holder, &currency_type)? < amount {
+            return Err(IcnError::Currency("Insufficient asset token balance".into()));
+        }
+        let (issuer, settlement_currency) = (token.issuer.clone(), token.terms.settlement_currency.clone());
+        let payout = Amount::from_f64(amount.to_f64() * token.terms.unit_value)?;
+        if holder != issuer {
+            if self.get_available_amount(&issuer, &settlement_currency)? < payout {
+                return Err(IcnError::Currency("Issuer cannot cover redemption".into()));
+            }
+            self.update_balance(&issuer, &settlement_currency, -payout)?;
+            self.update_balance(holder, &settlement_currency, payout)?;
+        }
+        self.get_asset_token_mut(asset_id)?.burn(amount)?;
+        self.update_balance(holder, &currency_type, -amount)?;
+        Ok(payout)
+    }
+
+    /// Accounts holding a positive balance of the given currency.
+    fn holders_of(&self, currency_type: &CurrencyType) -> Vec<(String, f64)> {
+        self.balances.iter()
+            .filter_map(|(address, balances)| {
+                balances.get(currency_type)
+                    .filter(|amount| amount.is_positive())
+                    .map(|amount| (address.clone(), amount.to_f64()))
+            })
+            .collect()
+    }
+
+    fn get_bond_mut(&mut self, bond_id: &str) -> IcnResult<&mut Bond> {
+        self.bonds.get_mut(bond_id)
+            .ok_or_else(|| IcnError::Currency("Bond not found".into()))
+    }
+
+    fn get_asset_token_mut(&mut self, asset_id: &str) -> IcnResult<&mut AssetToken> {
+        self.asset_tokens.get_mut(asset_id)
+            .ok_or_else(|| IcnError::Currency("Asset token not found".into()))
+    }
+
+    fn get_pool_mut(&mut self, pool_id: &str) -> IcnResult<&mut LiquidityPool> {
+        self.pools.get_mut(pool_id)
+            .ok_or_else(|| IcnError::Currency("Pool not found".into()))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use icn_common::SignatureScheme;
 
     #[test]
     fn test_currency_system() {
@@ -255,7 +591,7 @@ mod tests {
 
         // Test get currency info
         let basic_needs_info = system.get_currency_info(&CurrencyType::BasicNeeds).unwrap();
-        assert_eq!(basic_needs_info.total_supply, 1050.0);
+        assert_eq!(basic_needs_info.total_supply, Amount::from(1050.0));
         assert_eq!(basic_needs_info.issuance_rate, 0.02);
     }
 
@@ -265,11 +601,11 @@ mod tests {
 
         // Test minting
         assert!(currency.mint(100.0).is_ok());
-        assert_eq!(currency.total_supply, 1100.0);
+        assert_eq!(currency.total_supply, Amount::from(1100.0));
 
         // Test burning
         assert!(currency.burn(50.0).is_ok());
-        assert_eq!(currency.total_supply, 1050.0);
+        assert_eq!(currency.total_supply, Amount::from(1050.0));
 
         // Test minting negative amount
         assert!(currency.mint(-100.0).is_err());
@@ -315,10 +651,14 @@ mod tests {
         let transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 50.0,
+            amount: Amount::from(50.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: Utc::now().timestamp(),
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         assert!(system.process_transaction(&transaction).is_ok());
@@ -329,10 +669,14 @@ mod tests {
         let invalid_transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 100.0, // More than Alice's balance
+            amount: Amount::from(100.0), // More than Alice's balance
             currency_type: CurrencyType::BasicNeeds,
             timestamp: Utc::now().timestamp(),
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         assert!(system.process_transaction(&invalid_transaction).is_err());
@@ -344,15 +688,155 @@ mod tests {
         system.add_currency(CurrencyType::BasicNeeds, 1000.0, 0.01).unwrap();
         system.add_currency(CurrencyType::Education, 1000.0, 0.01).unwrap();
         system.update_balance("Alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        system.update_balance("Pool Provider", &CurrencyType::BasicNeeds, 1000.0).unwrap();
+        system.update_balance("Pool Provider", &CurrencyType::Education, 1000.0).unwrap();
+
+        // No pool yet
+        assert!(system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Education, Amount::from(50.0), Amount::ZERO).is_err());
 
-        assert!(system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Education, 50.0).is_ok());
+        let pool_id = system.create_pool(CurrencyType::BasicNeeds, CurrencyType::Education, 0.0).unwrap();
+        system.add_liquidity("Pool Provider", &pool_id, Amount::from(1000.0), Amount::from(1000.0)).unwrap();
+        assert_eq!(system.get_balance("Pool Provider", &CurrencyType::Education).unwrap(), 0.0);
+
+        let received = system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Education, Amount::from(50.0), Amount::ZERO).unwrap();
         assert_eq!(system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 50.0);
-        assert_eq!(system.get_balance("Alice", &CurrencyType::Education).unwrap(), 60.0); // 50 * 1.2
+        assert_eq!(system.get_balance("Alice", &CurrencyType::Education).unwrap(), received.to_f64());
+        // Rounded down to the ledger's precision of a millionth
+        assert_eq!(received, Amount::from_units(47_619_047));
 
         // Test insufficient balance
-        assert!(system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Education, 100.0).is_err());
+        assert!(system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Education, Amount::from(100.0), Amount::ZERO).is_err());
 
         // Test invalid currency
-        assert!(system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Environmental, 10.0).is_err());
+        assert!(system.exchange_currency("Alice", &CurrencyType::BasicNeeds, &CurrencyType::Environmental, Amount::from(10.0), Amount::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_pool_management() {
+        let mut system = CurrencySystem::new();
+        system.add_currency(CurrencyType::BasicNeeds, 1000.0, 0.01).unwrap();
+        system.add_currency(CurrencyType::Education, 1000.0, 0.01).unwrap();
+        system.update_balance("Alice", &CurrencyType::BasicNeeds, 500.0).unwrap();
+        system.update_balance("Alice", &CurrencyType::Education, 500.0).unwrap();
+
+        let pool_id = system.create_pool(CurrencyType::Education, CurrencyType::BasicNeeds, 0.003).unwrap();
+        assert!(system.create_pool(CurrencyType::BasicNeeds, CurrencyType::Education, 0.003).is_err());
+        assert!(system.create_pool(CurrencyType::BasicNeeds, CurrencyType::Environmental, 0.003).is_err());
+
+        assert!(system.add_liquidity("Alice", &pool_id, Amount::from(1000.0), Amount::from(100.0)).is_err());
+        let shares = system.add_liquidity("Alice", &pool_id, Amount::from(400.0), Amount::from(400.0)).unwrap();
+
+        assert!(system.set_pool_fee_rate(&pool_id, 0.01).is_ok());
+        assert!(system.set_pool_fee_rate(&pool_id, 0.9).is_err());
+        assert_eq!(system.get_pool(&pool_id).unwrap().fee_rate, 0.01);
+        assert_eq!(system.list_pools().len(), 1);
+
+        let (amount_a, amount_b) = system.remove_liquidity("Alice", &pool_id, shares).unwrap();
+        assert_eq!(amount_a + amount_b, Amount::from(800.0));
+        assert_eq!(system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 500.0);
+        assert_eq!(system.get_balance("Alice", &CurrencyType::Education).unwrap(), 500.0);
+    }
+
+    #[test]
+    fn test_bond_lifecycle() {
+        let mut system = CurrencySystem::new();
+        let issue_date = Utc::now();
+        system.update_balance("Coop", &CurrencyType::BasicNeeds, 1000.0).unwrap();
+
+        let bond_id = system.issue_bond("Coop", BondTerms {
+            name: "Bakery expansion".to_string(),
+            description: "Funds a second oven".to_string(),
+            face_value: 100.0,
+            coupon_rate: 0.073,
+            coupon_interval_days: 100,
+            maturity_date: issue_date + chrono::Duration::days(200),
+            settlement_currency: CurrencyType::BasicNeeds,
+        }, 5.0, issue_date).unwrap();
+        let bond_currency = CurrencyType::Bond(bond_id.clone());
+        system.transfer("Coop", "Alice", &bond_currency, 3.0).unwrap();
+
+        // Generic minting cannot create bond units
+        assert!(system.mint(&bond_currency, 10.0).is_err());
+
+        let paid = system.pay_bond_coupons(&bond_id, issue_date + chrono::Duration::days(100)).unwrap();
+        assert!((paid - 6.0).abs() < 1e-9);
+        assert!((system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap() - 6.0).abs() < 1e-9);
+
+        let maturity = issue_date + chrono::Duration::days(200);
+        assert!(system.redeem_bond(&bond_id, "Alice", issue_date + chrono::Duration::days(150)).is_err());
+        assert!(system.redeem_bond(&bond_id, "Alice", maturity).is_err());
+        system.pay_bond_coupons(&bond_id, maturity).unwrap();
+
+        assert_eq!(system.redeem_bond(&bond_id, "Alice", maturity).unwrap(), 300.0);
+        assert_eq!(system.get_balance("Alice", &bond_currency).unwrap(), 0.0);
+        assert!((system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap() - 312.0).abs() < 1e-9);
+        assert_eq!(system.get_bond(&bond_id).unwrap().units_outstanding, Amount::from(2.0));
+    }
+
+    #[test]
+    fn test_asset_token_lifecycle() {
+        let mut system = CurrencySystem::new();
+        system.update_balance("Coop", &CurrencyType::BasicNeeds, 100.0).unwrap();
+
+        let asset_id = system.issue_asset_token("Coop", AssetTokenTerms {
+            name: "Tractor share".to_string(),
+            description: "Fractional ownership of the shared tractor".to_string(),
+            unit_value: 5.0,
+            supply_cap: Some(Amount::from(20.0)),
+            settlement_currency: CurrencyType::BasicNeeds,
+            restriction: TransferRestriction::Allowlist(["Alice".to_string()].into_iter().collect()),
+        }, Amount::from(10.0)).unwrap();
+        let asset_currency = CurrencyType::AssetToken(asset_id.clone());
+
+        assert!(system.mint_asset_tokens(&asset_id, "Alice", Amount::from(5.0)).is_err());
+        system.mint_asset_tokens(&asset_id, "Coop", Amount::from(10.0)).unwrap();
+        assert!(system.mint_asset_tokens(&asset_id, "Coop", Amount::from(1.0)).is_err());
+
+        system.transfer("Coop", "Alice", &asset_currency, 4.0).unwrap();
+        assert!(system.transfer("Alice", "Bob", &asset_currency, 1.0).is_err());
+
+        assert_eq!(system.redeem_asset_tokens(&asset_id, "Alice", Amount::from(4.0)).unwrap(), Amount::from(20.0));
+        assert_eq!(system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 20.0);
+        assert_eq!(system.get_asset_token(&asset_id).unwrap().total_supply, Amount::from(16.0));
+        assert!(system.redeem_asset_tokens(&asset_id, "Alice", Amount::from(1.0)).is_err());
+    }
+
+    #[test]
+    fn test_vested_transfer() {
+        let mut system = CurrencySystem::new();
+        system.allocate("Treasury", &CurrencyType::BasicNeeds, 200.0).unwrap();
+
+        // Halfway through a 120 day schedule with a 30 day cliff
+        let start = Utc::now() - Duration::days(60);
+        let terms = VestingTerms { amount: Amount::from(120.0), cliff: Duration::days(30), duration: Duration::days(120), start };
+        system.transfer_vested("Treasury", "Alice", &CurrencyType::BasicNeeds, terms).unwrap();
+
+        assert_eq!(system.get_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), 120.0);
+        assert!((system.get_locked_balance("Alice", &CurrencyType::BasicNeeds).unwrap().to_f64() - 60.0).abs() < 0.01);
+        assert!(system.transfer("Alice", "Bob", &CurrencyType::BasicNeeds, 70.0).is_err());
+        system.transfer("Alice", "Bob", &CurrencyType::BasicNeeds, 55.0).unwrap();
+
+        // Locked funds cannot leave through the exchange either
+        system.allocate("Alice", &CurrencyType::Education, 10.0).unwrap();
+        let pool_id = system.create_pool(CurrencyType::BasicNeeds, CurrencyType::Education, 0.0).unwrap();
+        assert!(system.add_liquidity("Alice", &pool_id, Amount::from(10.0), Amount::from(10.0)).is_err());
+        assert_eq!(system.get_vesting_schedules("Alice").len(), 1);
+    }
+
+    #[test]
+    fn test_vesting_follows_the_system_clock() {
+        use icn_common::{Clock, ManualClock};
+        let clock = ManualClock::new(Utc::now());
+        let mut system = CurrencySystem::new().with_clock(clock.shared());
+        system.allocate("Treasury", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        let terms = VestingTerms { amount: Amount::from(100.0), cliff: Duration::days(10), duration: Duration::days(100), start: clock.now() };
+        system.transfer_vested("Treasury", "Alice", &CurrencyType::BasicNeeds, terms).unwrap();
+        assert_eq!(system.get_locked_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), Amount::from(100.0));
+
+        clock.advance(Duration::days(50));
+        assert_eq!(system.get_locked_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), Amount::from(50.0));
+        clock.advance(Duration::days(50));
+        assert_eq!(system.get_locked_balance("Alice", &CurrencyType::BasicNeeds).unwrap(), Amount::ZERO);
+        system.transfer("Alice", "Bob", &CurrencyType::BasicNeeds, 100.0).unwrap();
     }
 }
@@ -0,0 +1,288 @@
+// File: crates/icn_currency/src/liquidity.rs
+
+use icn_common::{Amount, IcnResult, IcnError, CurrencyType};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use serde::{Serialize, Deserialize};
+
+/// Upper bound on the swap fee a pool can charge.
+pub const MAX_FEE_RATE: f64 = 0.1;
+
+/// Fee rates are applied as whole parts per million so swaps are exact.
+const FEE_PARTS: i128 = 1_000_000;
+
+/// A constant-product liquidity pool holding reserves of two currencies.
+///
+/// Swap fees stay in the reserves, so liquidity providers earn them
+/// in proportion to their share of the pool. Reserves and shares are
+/// fixed point like balances, rounded in the pool's favour.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityPool {
+    pub id: String,
+    pub currency_a: CurrencyType,
+    pub currency_b: CurrencyType,
+    pub reserve_a: Amount,
+    pub reserve_b: Amount,
+    pub fee_rate: f64,
+    pub total_shares: Amount,
+    pub lp_shares: HashMap<String, Amount>,
+}
+
+impl LiquidityPool {
+    /// Creates an empty pool for the given currency pair.
+    pub fn new(currency_a: CurrencyType, currency_b: CurrencyType, fee_rate: f64) -> IcnResult<Self> {
+        if currency_a == currency_b {
+            return Err(IcnError::Currency("Pool currencies must differ".into()));
+        }
+        validate_fee_rate(fee_rate)?;
+        Ok(LiquidityPool {
+            id: pool_id(&currency_a, &currency_b),
+            currency_a,
+            currency_b,
+            reserve_a: Amount::ZERO,
+            reserve_b: Amount::ZERO,
+            fee_rate,
+            total_shares: Amount::ZERO,
+            lp_shares: HashMap::new(),
+        })
+    }
+
+    /// Returns true if the pool trades the given currency.
+    pub fn contains(&self, currency_type: &CurrencyType) -> bool {
+        &self.currency_a == currency_type || &self.currency_b == currency_type
+    }
+
+    /// Current marginal price of `currency_a` expressed in `currency_b`.
+    pub fn spot_price(&self) -> IcnResult<f64> {
+        if !self.reserve_a.is_positive() || !self.reserve_b.is_positive() {
+            return Err(IcnError::Currency("Pool has no liquidity".into()));
+        }
+        Ok(self.reserve_b.to_f64() / self.reserve_a.to_f64())
+    }
+
+    /// Calculates the output of swapping `amount_in` of `input_currency` without changing the pool.
+    pub fn quote(&self, input_currency: &CurrencyType, amount_in: Amount) -> IcnResult<Amount> {
+        if !amount_in.is_positive() {
+            return Err(IcnError::Currency("Swap amount must be positive".into()));
+        }
+        let (reserve_in, reserve_out) = self.reserves_for(input_currency)?;
+        if !reserve_in.is_positive() || !reserve_out.is_positive() {
+            return Err(IcnError::Currency("Pool has no liquidity".into()));
+        }
+
+        let fee_parts = (self.fee_rate * FEE_PARTS as f64).round() as i128;
+        let amount_in_after_fee = amount_in.units() as i128 * (FEE_PARTS - fee_parts) / FEE_PARTS;
+        let amount_out = reserve_out.units() as i128 * amount_in_after_fee / (reserve_in.units() as i128 + amount_in_after_fee);
+        i64::try_from(amount_out)
+            .map(Amount::from_units)
+            .map_err(|_| IcnError::Currency("Swap amount is too large".into()))
+    }
+
+    /// Swaps `amount_in` of `input_currency` for the other pool currency, keeping `x * y` constant.
+    pub fn swap(&mut self, input_currency: &CurrencyType, amount_in: Amount, min_amount_out: Amount) -> IcnResult<Amount> {
+        let amount_out = self.quote(input_currency, amount_in)?;
+        if amount_out < min_amount_out {
+            return Err(IcnError::Currency("Slippage limit exceeded".into()));
+        }
+
+        let (reserve_in, reserve_out) = if input_currency == &self.currency_a {
+            (&mut self.reserve_a, &mut self.reserve_b)
+        } else {
+            (&mut self.reserve_b, &mut self.reserve_a)
+        };
+        *reserve_in = reserve_in.checked_add(amount_in).ok_or_else(reserve_overflow)?;
+        *reserve_out -= amount_out;
+        Ok(amount_out)
+    }
+
+    /// Deposits liquidity and mints pool shares for the provider.
+    ///
+    /// After the first deposit, only the amounts matching the current reserve
+    /// ratio are taken; the amounts actually used are returned with the shares.
+    pub fn add_liquidity(&mut self, provider: &str, amount_a: Amount, amount_b: Amount) -> IcnResult<(Amount, Amount, Amount)> {
+        if !amount_a.is_positive() || !amount_b.is_positive() {
+            return Err(IcnError::Currency("Liquidity amounts must be positive".into()));
+        }
+
+        let (used_a, used_b, shares) = if self.total_shares == Amount::ZERO {
+            let product = amount_a.units() as i128 * amount_b.units() as i128;
+            let shares = i64::try_from(integer_sqrt(product)).map_err(|_| reserve_overflow())?;
+            (amount_a, amount_b, Amount::from_units(shares))
+        } else if amount_a.units() as i128 * (self.reserve_b.units() as i128) <= amount_b.units() as i128 * self.reserve_a.units() as i128 {
+            // `amount_a` is the scarcer side; take `amount_b` in proportion
+            let used_b = self.reserve_b.mul_div(amount_a.units(), self.reserve_a.units()).ok_or_else(reserve_overflow)?;
+            let shares = self.total_shares.mul_div(amount_a.units(), self.reserve_a.units()).ok_or_else(reserve_overflow)?;
+            (amount_a, used_b, shares)
+        } else {
+            let used_a = self.reserve_a.mul_div(amount_b.units(), self.reserve_b.units()).ok_or_else(reserve_overflow)?;
+            let shares = self.total_shares.mul_div(amount_b.units(), self.reserve_b.units()).ok_or_else(reserve_overflow)?;
+            (used_a, amount_b, shares)
+        };
+        if !shares.is_positive() {
+            return Err(IcnError::Currency("Liquidity is too small to mint any pool shares".into()));
+        }
+
+        let reserve_a = self.reserve_a.checked_add(used_a).ok_or_else(reserve_overflow)?;
+        let reserve_b = self.reserve_b.checked_add(used_b).ok_or_else(reserve_overflow)?;
+        let total_shares = self.total_shares.checked_add(shares).ok_or_else(reserve_overflow)?;
+        self.reserve_a = reserve_a;
+        self.reserve_b = reserve_b;
+        self.total_shares = total_shares;
+        *self.lp_shares.entry(provider.to_string()).or_default() += shares;
+        Ok((used_a, used_b, shares))
+    }
+
+    /// Burns the provider's shares and returns their portion of both reserves.
+    pub fn remove_liquidity(&mut self, provider: &str, shares: Amount) -> IcnResult<(Amount, Amount)> {
+        if !shares.is_positive() {
+            return Err(IcnError::Currency("Shares to remove must be positive".into()));
+        }
+        let provider_shares = self.lp_shares.get_mut(provider)
+            .ok_or_else(|| IcnError::Currency("Provider has no liquidity in pool".into()))?;
+        if *provider_shares < shares {
+            return Err(IcnError::Currency("Insufficient pool shares".into()));
+        }
+
+        let amount_a = self.reserve_a.mul_div(shares.units(), self.total_shares.units()).ok_or_else(reserve_overflow)?;
+        let amount_b = self.reserve_b.mul_div(shares.units(), self.total_shares.units()).ok_or_else(reserve_overflow)?;
+
+        *provider_shares -= shares;
+        if *provider_shares == Amount::ZERO {
+            self.lp_shares.remove(provider);
+        }
+        self.reserve_a -= amount_a;
+        self.reserve_b -= amount_b;
+        self.total_shares -= shares;
+        Ok((amount_a, amount_b))
+    }
+
+    /// Updates the swap fee charged by the pool.
+    pub fn set_fee_rate(&mut self, fee_rate: f64) -> IcnResult<()> {
+        validate_fee_rate(fee_rate)?;
+        self.fee_rate = fee_rate;
+        Ok(())
+    }
+
+    fn reserves_for(&self, input_currency: &CurrencyType) -> IcnResult<(Amount, Amount)> {
+        if input_currency == &self.currency_a {
+            Ok((self.reserve_a, self.reserve_b))
+        } else if input_currency == &self.currency_b {
+            Ok((self.reserve_b, self.reserve_a))
+        } else {
+            Err(IcnError::Currency("Currency is not traded in this pool".into()))
+        }
+    }
+}
+
+/// Builds an order-independent identifier for a currency pair.
+pub fn pool_id(currency_a: &CurrencyType, currency_b: &CurrencyType) -> String {
+    let mut names = [format!("{:?}", currency_a), format!("{:?}", currency_b)];
+    names.sort();
+    format!("{}/{}", names[0], names[1])
+}
+
+fn validate_fee_rate(fee_rate: f64) -> IcnResult<()> {
+    if !(0.0..=MAX_FEE_RATE).contains(&fee_rate) {
+        return Err(IcnError::Currency(format!("Fee rate must be between 0 and {}", MAX_FEE_RATE)));
+    }
+    Ok(())
+}
+
+fn reserve_overflow() -> IcnError {
+    IcnError::Currency("Pool reserves would overflow".into())
+}
+
+/// The largest integer whose square is at most `value`.
+fn integer_sqrt(value: i128) -> i128 {
+    if value < 2 {
+        return value.max(0);
+    }
+    let mut root = (value as f64).sqrt() as i128;
+    while root * root > value {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= value {
+        root += 1;
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(value: f64) -> Amount {
+        Amount::from(value)
+    }
+
+    fn create_test_pool() -> LiquidityPool {
+        let mut pool = LiquidityPool::new(CurrencyType::BasicNeeds, CurrencyType::Education, 0.003).unwrap();
+        pool.add_liquidity("Alice", amount(1000.0), amount(2000.0)).unwrap();
+        pool
+    }
+
+    fn product(pool: &LiquidityPool) -> i128 {
+        pool.reserve_a.units() as i128 * pool.reserve_b.units() as i128
+    }
+
+    #[test]
+    fn test_pool_id_is_order_independent() {
+        assert_eq!(
+            pool_id(&CurrencyType::BasicNeeds, &CurrencyType::Education),
+            pool_id(&CurrencyType::Education, &CurrencyType::BasicNeeds)
+        );
+        assert!(LiquidityPool::new(CurrencyType::BasicNeeds, CurrencyType::BasicNeeds, 0.003).is_err());
+        assert!(LiquidityPool::new(CurrencyType::BasicNeeds, CurrencyType::Education, 0.5).is_err());
+    }
+
+    #[test]
+    fn test_constant_product_swap() {
+        let mut pool = create_test_pool();
+        assert_eq!(pool.spot_price().unwrap(), 2.0);
+
+        let k_before = product(&pool);
+        let out = pool.swap(&CurrencyType::BasicNeeds, amount(100.0), Amount::ZERO).unwrap();
+
+        // Without fees 100 in would return 2000 - 2_000_000 / 1100 ≈ 181.8
+        assert!(out < amount(181.82) && out > amount(180.0));
+        assert_eq!(pool.reserve_a, amount(1100.0));
+        // Fees stay in the pool, so the product can only grow
+        assert!(product(&pool) >= k_before);
+
+        assert!(pool.swap(&CurrencyType::Education, amount(10.0), amount(100.0)).is_err());
+        assert!(pool.swap(&CurrencyType::Environmental, amount(10.0), Amount::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_liquidity_provision_and_fees() {
+        let mut pool = create_test_pool();
+        let (used_a, used_b, shares) = pool.add_liquidity("Bob", amount(100.0), amount(500.0)).unwrap();
+        assert_eq!(used_a, amount(100.0));
+        assert_eq!(used_b, amount(200.0));
+        assert_eq!(shares, pool.total_shares.mul_div(1, 11).unwrap());
+
+        pool.swap(&CurrencyType::BasicNeeds, amount(200.0), Amount::ZERO).unwrap();
+        pool.swap(&CurrencyType::Education, amount(400.0), Amount::ZERO).unwrap();
+
+        let (out_a, out_b) = pool.remove_liquidity("Bob", shares).unwrap();
+        // Bob's position is worth at least what he deposited thanks to swap fees
+        assert!(out_a.to_f64() * 2.0 + out_b.to_f64() >= used_a.to_f64() * 2.0 + used_b.to_f64());
+        assert!(pool.remove_liquidity("Bob", shares).is_err());
+
+        // Reserves and shares add up exactly once everyone has left
+        let alice_shares = pool.lp_shares["Alice"];
+        pool.remove_liquidity("Alice", alice_shares).unwrap();
+        assert_eq!((pool.reserve_a, pool.reserve_b, pool.total_shares), (Amount::ZERO, Amount::ZERO, Amount::ZERO));
+    }
+
+    #[test]
+    fn test_overflowing_reserves_are_rejected() {
+        let mut pool = LiquidityPool::new(CurrencyType::BasicNeeds, CurrencyType::Education, 0.0).unwrap();
+        let huge = Amount::from_units(i64::MAX / 2 + 1);
+        pool.add_liquidity("Alice", huge, huge).unwrap();
+        assert!(pool.add_liquidity("Bob", huge, huge).is_err());
+        assert!(pool.swap(&CurrencyType::BasicNeeds, huge, Amount::ZERO).is_err());
+        assert_eq!(pool.reserve_a, huge);
+        assert_eq!(pool.total_shares, huge);
+    }
+}
@@ -0,0 +1,243 @@
+// File: crates/icn_currency/src/rates.rs
+
+use chrono::{DateTime, Utc};
+use icn_common::{IcnResult, IcnError, CurrencyType};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Largest relative move an oracle may make to a rate in one update.
+/// Bigger moves have to go through governance.
+pub const MAX_ORACLE_RATE_CHANGE: f64 = 0.25;
+
+/// How old an oracle's quote may be when it is submitted, in seconds.
+pub const MAX_ORACLE_QUOTE_AGE_SECS: i64 = 600;
+
+/// Where a rate came from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum RateSource {
+    Governance { proposal_id: String },
+    Oracle { oracle: String },
+}
+
+/// How many units of `quote` one unit of `base` is worth.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExchangeRate {
+    pub base: CurrencyType,
+    pub quote: CurrencyType,
+    pub rate: f64,
+    pub source: RateSource,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A rate published by an oracle, signed with the key in its DID.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OracleQuote {
+    pub oracle: String,
+    pub base: CurrencyType,
+    pub quote: CurrencyType,
+    pub rate: f64,
+    /// When the oracle observed the rate, in seconds since the epoch.
+    pub timestamp: i64,
+    /// Signature over `message`.
+    pub signature: Vec<u8>,
+}
+
+impl OracleQuote {
+    /// What the oracle signs.
+    pub fn message(&self) -> Vec<u8> {
+        format!("icn-oracle-rate:{:?}:{:?}:{}:{}", self.base, self.quote, self.rate, self.timestamp).into_bytes()
+    }
+}
+
+/// Exchange rates between community currencies. Rates are set by economic
+/// adjustment proposals or by the oracles those proposals authorize, and
+/// every change is kept in the history.
+///
+/// Each pair is stored the way it was set; asking for the reverse pair
+/// gives the inverse rate.
+#[derive(Debug, Clone, Default)]
+pub struct RateTable {
+    rates: HashMap<(CurrencyType, CurrencyType), ExchangeRate>,
+    history: Vec<ExchangeRate>,
+    oracles: BTreeSet<String>,
+}
+
+impl RateTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets a rate as decided by a proposal. Governance may move a rate by
+    /// any amount.
+    pub fn set_rate(&mut self, base: CurrencyType, quote: CurrencyType, rate: f64, proposal_id: &str, now: DateTime<Utc>) -> IcnResult<&ExchangeRate> {
+        Self::validate(&base, &quote, rate)?;
+        let source = RateSource::Governance { proposal_id: proposal_id.to_string() };
+        Ok(self.record(ExchangeRate { base, quote, rate, source, updated_at: now }))
+    }
+
+    /// Applies an oracle's quote, whose signature the caller has checked.
+    /// Only authorized oracles are accepted, quotes must be fresh and newer
+    /// than the rate they replace, and an oracle can't move a rate by more
+    /// than `MAX_ORACLE_RATE_CHANGE` at once.
+    pub fn apply_oracle_quote(&mut self, quote: &OracleQuote, now: DateTime<Utc>) -> IcnResult<&ExchangeRate> {
+        if !self.oracles.contains(&quote.oracle) {
+            return Err(IcnError::Currency(format!("{} is not an authorized rate oracle", quote.oracle)));
+        }
+        Self::validate(&quote.base, &quote.quote, quote.rate)?;
+        let observed_at = DateTime::from_timestamp(quote.timestamp, 0)
+            .ok_or_else(|| IcnError::Currency("Invalid quote timestamp".into()))?;
+        if observed_at > now || (now - observed_at).num_seconds() > MAX_ORACLE_QUOTE_AGE_SECS {
+            return Err(IcnError::Currency("Oracle quote is stale or from the future".into()));
+        }
+        if let Some(current) = self.current(&quote.base, &quote.quote) {
+            if observed_at <= current.updated_at {
+                return Err(IcnError::Currency("A newer rate is already recorded for the pair".into()));
+            }
+            let current_rate = self.get_rate(&quote.base, &quote.quote)?;
+            if ((quote.rate - current_rate) / current_rate).abs() > MAX_ORACLE_RATE_CHANGE {
+                return Err(IcnError::Currency(format!(
+                    "Oracles can move a rate by at most {}% at once", MAX_ORACLE_RATE_CHANGE * 100.0
+                )));
+            }
+        }
+        let source = RateSource::Oracle { oracle: quote.oracle.clone() };
+        Ok(self.record(ExchangeRate {
+            base: quote.base.clone(),
+            quote: quote.quote.clone(),
+            rate: quote.rate,
+            source,
+            updated_at: observed_at,
+        }))
+    }
+
+    pub fn authorize_oracle(&mut self, oracle: &str) {
+        self.oracles.insert(oracle.to_string());
+    }
+
+    pub fn revoke_oracle(&mut self, oracle: &str) -> bool {
+        self.oracles.remove(oracle)
+    }
+
+    pub fn oracles(&self) -> Vec<&str> {
+        self.oracles.iter().map(String::as_str).collect()
+    }
+
+    /// The rate from `base` to `quote`, from either direction of the pair.
+    pub fn get_rate(&self, base: &CurrencyType, quote: &CurrencyType) -> IcnResult<f64> {
+        if base == quote {
+            return Ok(1.0);
+        }
+        if let Some(rate) = self.rates.get(&(base.clone(), quote.clone())) {
+            return Ok(rate.rate);
+        }
+        self.rates.get(&(quote.clone(), base.clone()))
+            .map(|rate| 1.0 / rate.rate)
+            .ok_or_else(|| IcnError::Currency(format!("No exchange rate from {:?} to {:?}", base, quote)))
+    }
+
+    /// What `amount` of `from` is worth in `to`.
+    pub fn convert(&self, amount: f64, from: &CurrencyType, to: &CurrencyType) -> IcnResult<f64> {
+        Ok(amount * self.get_rate(from, to)?)
+    }
+
+    /// Current rates, in the direction each was set.
+    pub fn rates(&self) -> Vec<&ExchangeRate> {
+        let mut rates: Vec<&ExchangeRate> = self.rates.values().collect();
+        rates.sort_by(|a, b| (format!("{:?}", a.base), format!("{:?}", a.quote)).cmp(&(format!("{:?}", b.base), format!("{:?}", b.quote))));
+        rates
+    }
+
+    /// Every rate recorded for a pair, in either direction, or for all
+    /// pairs, oldest first.
+    pub fn history(&self, pair: Option<(&CurrencyType, &CurrencyType)>) -> Vec<&ExchangeRate> {
+        self.history.iter()
+            .filter(|rate| pair.is_none_or(|(a, b)| (&rate.base == a && &rate.quote == b) || (&rate.base == b && &rate.quote == a)))
+            .collect()
+    }
+
+    /// The rate recorded for a pair in either direction.
+    fn current(&self, base: &CurrencyType, quote: &CurrencyType) -> Option<&ExchangeRate> {
+        self.rates.get(&(base.clone(), quote.clone()))
+            .or_else(|| self.rates.get(&(quote.clone(), base.clone())))
+    }
+
+    /// Replaces whichever direction of the pair was recorded before.
+    fn record(&mut self, rate: ExchangeRate) -> &ExchangeRate {
+        self.rates.remove(&(rate.quote.clone(), rate.base.clone()));
+        self.history.push(rate.clone());
+        let key = (rate.base.clone(), rate.quote.clone());
+        self.rates.insert(key.clone(), rate);
+        &self.rates[&key]
+    }
+
+    /// Checks that `rate` could be recorded for the pair.
+    pub fn validate(base: &CurrencyType, quote: &CurrencyType, rate: f64) -> IcnResult<()> {
+        if base == quote {
+            return Err(IcnError::Currency("A rate needs two different currencies".into()));
+        }
+        if !rate.is_finite() || rate <= 0.0 {
+            return Err(IcnError::Currency("Exchange rates must be positive".into()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn quote(rate: f64, at: DateTime<Utc>) -> OracleQuote {
+        OracleQuote {
+            oracle: "did:icn:oracle".to_string(),
+            base: CurrencyType::Education,
+            quote: CurrencyType::BasicNeeds,
+            rate,
+            timestamp: at.timestamp(),
+            signature: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_governance_rates_and_conversion() {
+        let mut table = RateTable::new();
+        let now = Utc::now();
+        assert!(table.get_rate(&CurrencyType::BasicNeeds, &CurrencyType::Education).is_err());
+        assert!(table.set_rate(CurrencyType::BasicNeeds, CurrencyType::BasicNeeds, 1.0, "p1", now).is_err());
+        assert!(table.set_rate(CurrencyType::BasicNeeds, CurrencyType::Education, 0.0, "p1", now).is_err());
+
+        table.set_rate(CurrencyType::BasicNeeds, CurrencyType::Education, 2.0, "p1", now).unwrap();
+        assert_eq!(table.convert(10.0, &CurrencyType::BasicNeeds, &CurrencyType::Education).unwrap(), 20.0);
+        assert_eq!(table.get_rate(&CurrencyType::Education, &CurrencyType::BasicNeeds).unwrap(), 0.5);
+
+        // Setting the reverse pair replaces the rate rather than adding one
+        table.set_rate(CurrencyType::Education, CurrencyType::BasicNeeds, 0.25, "p2", now).unwrap();
+        assert_eq!(table.rates().len(), 1);
+        assert_eq!(table.get_rate(&CurrencyType::BasicNeeds, &CurrencyType::Education).unwrap(), 4.0);
+        let history = table.history(Some((&CurrencyType::BasicNeeds, &CurrencyType::Education)));
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].source, RateSource::Governance { proposal_id: "p2".to_string() });
+    }
+
+    #[test]
+    fn test_oracle_quotes_are_bounded() {
+        let mut table = RateTable::new();
+        let now = Utc::now();
+        assert!(table.apply_oracle_quote(&quote(1.0, now), now).is_err());
+
+        table.authorize_oracle("did:icn:oracle");
+        table.set_rate(CurrencyType::BasicNeeds, CurrencyType::Education, 1.0, "p1", now - Duration::minutes(5)).unwrap();
+        table.apply_oracle_quote(&quote(1.1, now - Duration::minutes(1)), now).unwrap();
+        assert!((table.get_rate(&CurrencyType::Education, &CurrencyType::BasicNeeds).unwrap() - 1.1).abs() < 1e-9);
+
+        // Too big a move, a replayed quote and a stale one are all refused
+        assert!(table.apply_oracle_quote(&quote(2.0, now), now).is_err());
+        assert!(table.apply_oracle_quote(&quote(1.15, now - Duration::minutes(1)), now).is_err());
+        assert!(table.apply_oracle_quote(&quote(1.15, now - Duration::hours(1)), now).is_err());
+        table.apply_oracle_quote(&quote(1.2, now), now).unwrap();
+
+        assert!(table.revoke_oracle("did:icn:oracle"));
+        assert!(table.apply_oracle_quote(&quote(1.2, now + Duration::seconds(1)), now + Duration::seconds(1)).is_err());
+        assert_eq!(table.history(None).len(), 3);
+    }
+}
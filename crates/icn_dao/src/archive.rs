@@ -0,0 +1,107 @@
+// crates/icn_dao/src/archive.rs
+
+use crate::{Dao, Proposal, ProposalStatus};
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+
+/// Index entry a DAO keeps for a proposal it has moved to storage.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProposalArchiveEntry {
+    pub proposal_id: String,
+    pub title: String,
+    pub status: ProposalStatus,
+    pub storage_key: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Storage key under which an archived DAO proposal is kept.
+pub fn dao_archive_storage_key(dao_id: &str, proposal_id: &str) -> String {
+    format!("dao:{}:archive:{}", dao_id, proposal_id)
+}
+
+impl Dao {
+    /// Returns the rejected and executed proposals decided more than
+    /// `retention` before `now`. Passed proposals may still be executed.
+    pub fn archivable_proposals(&self, retention: Duration, now: DateTime<Utc>) -> Vec<Proposal> {
+        let cutoff = now - retention;
+        self.proposals.values()
+            .filter(|p| matches!(p.status, ProposalStatus::Rejected | ProposalStatus::Executed))
+            .filter(|p| p.execution.as_ref().map_or(p.expires_at, |execution| execution.executed_at) < cutoff)
+            .cloned()
+            .collect()
+    }
+
+    /// Drops a proposal from memory once it has been written to
+    /// `storage_key`, keeping an index entry in its place.
+    pub fn mark_proposal_archived(&mut self, proposal_id: &str, storage_key: String, now: DateTime<Utc>) -> IcnResult<ProposalArchiveEntry> {
+        let proposal = self.proposals.remove(proposal_id)
+            .ok_or_else(|| IcnError::Dao("Proposal not found".into()))?;
+        let entry = ProposalArchiveEntry {
+            proposal_id: proposal.id,
+            title: proposal.title,
+            status: proposal.status,
+            storage_key,
+            archived_at: now,
+        };
+        self.archived_proposals.insert(proposal_id.to_string(), entry.clone());
+        Ok(entry)
+    }
+
+    pub fn get_proposal_archive_entry(&self, proposal_id: &str) -> Option<&ProposalArchiveEntry> {
+        self.archived_proposals.get(proposal_id)
+    }
+
+    /// Lists archived proposals, most recently archived first.
+    pub fn list_archived_proposals(&self) -> Vec<&ProposalArchiveEntry> {
+        let mut entries: Vec<&ProposalArchiveEntry> = self.archived_proposals.values().collect();
+        entries.sort_by(|a, b| b.archived_at.cmp(&a.archived_at).then_with(|| a.proposal_id.cmp(&b.proposal_id)));
+        entries
+    }
+
+    /// Brings an archived proposal back into memory with its votes.
+    pub fn restore_archived_proposal(&mut self, proposal: Proposal) -> IcnResult<()> {
+        if self.archived_proposals.remove(&proposal.id).is_none() {
+            return Err(IcnError::Dao(format!("Proposal {} is not archived", proposal.id)));
+        }
+        self.proposals.insert(proposal.id.clone(), proposal);
+        Ok(())
+    }
+
+    /// Forgets an archived proposal. The caller removes the stored copy.
+    pub fn purge_archived_proposal(&mut self, proposal_id: &str) -> IcnResult<ProposalArchiveEntry> {
+        self.archived_proposals.remove(proposal_id)
+            .ok_or_else(|| IcnError::Dao(format!("Proposal {} is not archived", proposal_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaoType;
+
+    #[test]
+    fn test_archive_and_restore_dao_proposal() {
+        let mut dao = Dao::new("Test Dao".to_string(), DaoType::Cooperative, 0.5, 0.5);
+        dao.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        let proposal_id = dao.create_proposal("Title".to_string(), "Description".to_string(), "alice".to_string(), Duration::days(7)).unwrap();
+        let now = Utc::now() + Duration::days(30);
+        assert!(dao.archivable_proposals(Duration::days(1), now).is_empty());
+
+        dao.finalize_proposal(&proposal_id).unwrap();
+        assert_eq!(dao.proposals[&proposal_id].status, ProposalStatus::Rejected);
+        assert!(dao.archivable_proposals(Duration::days(30), now).is_empty());
+        let archivable = dao.archivable_proposals(Duration::days(1), now);
+        assert_eq!(archivable.len(), 1);
+
+        let storage_key = dao_archive_storage_key(&dao.id, &proposal_id);
+        dao.mark_proposal_archived(&proposal_id, storage_key, now).unwrap();
+        assert!(dao.proposals.is_empty());
+        assert_eq!(dao.list_archived_proposals()[0].status, ProposalStatus::Rejected);
+
+        dao.restore_archived_proposal(archivable[0].clone()).unwrap();
+        assert!(dao.proposals.contains_key(&proposal_id));
+        assert!(dao.get_proposal_archive_entry(&proposal_id).is_none());
+        assert!(dao.purge_archived_proposal(&proposal_id).is_err());
+    }
+}
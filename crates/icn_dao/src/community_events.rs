@@ -0,0 +1,302 @@
+// crates/icn_dao/src/community_events.rs
+
+use crate::{Dao, DaoEvent};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use icn_common::{IcnResult, IcnError, CurrencyType};
+use icn_currency::CurrencySystem;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+/// What an organizer fills in when announcing an event.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EventDetails {
+    pub name: String,
+    pub description: String,
+    pub location: String,
+    pub starts_at: DateTime<Utc>,
+    /// Most members that can RSVP, if the venue is limited.
+    pub capacity: Option<usize>,
+    /// Volunteer currency paid to each attendee on check-in.
+    pub volunteer_reward: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum EventStatus {
+    Scheduled,
+    Completed,
+    Cancelled,
+}
+
+/// An event organized by members of a DAO.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommunityEvent {
+    pub id: String,
+    pub details: EventDetails,
+    /// Members who can check attendees in. The first is the one who
+    /// created the event.
+    pub organizers: Vec<String>,
+    pub rsvps: BTreeSet<String>,
+    pub attendees: BTreeSet<String>,
+    pub status: EventStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Filters for listing events; unset fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EventQuery {
+    pub status: Option<EventStatus>,
+    pub organizer: Option<String>,
+    pub attendee: Option<String>,
+    /// Only events starting at or after this time.
+    pub starting_after: Option<DateTime<Utc>>,
+}
+
+impl CommunityEvent {
+    pub fn is_full(&self) -> bool {
+        self.details.capacity.is_some_and(|capacity| self.rsvps.len() >= capacity)
+    }
+
+    fn matches(&self, query: &EventQuery) -> bool {
+        query.status.as_ref().is_none_or(|status| &self.status == status)
+            && query.organizer.as_ref().is_none_or(|organizer| self.organizers.contains(organizer))
+            && query.attendee.as_ref().is_none_or(|attendee| self.attendees.contains(attendee))
+            && query.starting_after.is_none_or(|after| self.details.starts_at >= after)
+    }
+}
+
+/// The bytes an organizer signs to vouch that `attendee` showed up.
+pub fn check_in_message(event_id: &str, attendee: &str) -> Vec<u8> {
+    format!("icn-event-check-in:{}:{}", event_id, attendee).into_bytes()
+}
+
+/// The ed25519 key behind a `did:icn:<hex public key>` identifier.
+pub(crate) fn did_public_key(did: &str) -> IcnResult<PublicKey> {
+    let encoded = did.strip_prefix("did:icn:")
+        .ok_or_else(|| IcnError::Dao(format!("{} is not an ICN DID", did)))?;
+    let bytes = hex::decode(encoded)
+        .map_err(|e| IcnError::Dao(format!("Invalid key in DID {}: {}", did, e)))?;
+    PublicKey::from_bytes(&bytes)
+        .map_err(|e| IcnError::Dao(format!("Invalid key in DID {}: {}", did, e)))
+}
+
+impl Dao {
+    /// Announces an event organized by `organizer`, who must be a member.
+    pub fn create_event(&mut self, organizer: &str, details: EventDetails) -> IcnResult<String> {
+        if !self.members.contains_key(organizer) {
+            return Err(IcnError::Dao("Only members can organize events".into()));
+        }
+        if details.name.trim().is_empty() {
+            return Err(IcnError::Dao("Event name cannot be empty".into()));
+        }
+        if details.capacity == Some(0) {
+            return Err(IcnError::Dao("Event capacity must be greater than zero".into()));
+        }
+        if !details.volunteer_reward.is_finite() || details.volunteer_reward < 0.0 {
+            return Err(IcnError::Dao("Volunteer reward must be a non-negative amount".into()));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        let now = self.clock.now();
+        self.community_events.insert(id.clone(), CommunityEvent {
+            id: id.clone(),
+            details,
+            organizers: vec![organizer.to_string()],
+            rsvps: BTreeSet::new(),
+            attendees: BTreeSet::new(),
+            status: EventStatus::Scheduled,
+            created_at: now,
+        });
+        self.events.push(DaoEvent::EventScheduled {
+            event_id: id.clone(),
+            organizer: organizer.to_string(),
+            timestamp: now,
+        });
+        Ok(id)
+    }
+
+    /// Lets an existing organizer bring another member in to help check
+    /// people in.
+    pub fn add_event_organizer(&mut self, event_id: &str, organizer: &str, new_organizer: &str) -> IcnResult<()> {
+        if !self.members.contains_key(new_organizer) {
+            return Err(IcnError::Dao("Only members can organize events".into()));
+        }
+        let event = self.scheduled_event_mut(event_id)?;
+        if !event.organizers.iter().any(|o| o == organizer) {
+            return Err(IcnError::Dao("Only an organizer can add organizers".into()));
+        }
+        if !event.organizers.iter().any(|o| o == new_organizer) {
+            event.organizers.push(new_organizer.to_string());
+        }
+        Ok(())
+    }
+
+    pub fn rsvp(&mut self, event_id: &str, member: &str) -> IcnResult<()> {
+        if !self.members.contains_key(member) {
+            return Err(IcnError::Dao("Only members can RSVP to events".into()));
+        }
+        let event = self.scheduled_event_mut(event_id)?;
+        if event.rsvps.contains(member) {
+            return Err(IcnError::Dao("Member has already RSVPed".into()));
+        }
+        if event.is_full() {
+            return Err(IcnError::Dao("Event is at capacity".into()));
+        }
+        event.rsvps.insert(member.to_string());
+        Ok(())
+    }
+
+    pub fn cancel_rsvp(&mut self, event_id: &str, member: &str) -> IcnResult<()> {
+        let event = self.scheduled_event_mut(event_id)?;
+        if !event.rsvps.remove(member) {
+            return Err(IcnError::Dao("Member has not RSVPed".into()));
+        }
+        Ok(())
+    }
+
+    /// Records that `attendee` came to the event and pays them the event's
+    /// volunteer reward. `signature` is the organizer's signature over
+    /// `check_in_message`, made with the key in their DID, so members can't
+    /// check themselves in.
+    pub fn check_in(
+        &mut self,
+        event_id: &str,
+        attendee: &str,
+        organizer: &str,
+        signature: &[u8],
+        currency_system: &mut CurrencySystem,
+    ) -> IcnResult<()> {
+        if !self.members.contains_key(attendee) {
+            return Err(IcnError::Dao("Only members can be checked in".into()));
+        }
+        let event = self.community_events.get(event_id)
+            .ok_or_else(|| IcnError::Dao("Event not found".into()))?;
+        if event.status != EventStatus::Scheduled {
+            return Err(IcnError::Dao("Event is not open for check-in".into()));
+        }
+        if !event.organizers.iter().any(|o| o == organizer) {
+            return Err(IcnError::Dao("Only an organizer can check attendees in".into()));
+        }
+        if event.attendees.contains(attendee) {
+            return Err(IcnError::Dao("Attendee is already checked in".into()));
+        }
+
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Dao(format!("Invalid check-in signature: {}", e)))?;
+        did_public_key(organizer)?
+            .verify(&check_in_message(event_id, attendee), &signature)
+            .map_err(|_| IcnError::Dao("Check-in signature verification failed".into()))?;
+
+        let reward = event.details.volunteer_reward;
+        if reward > 0.0 {
+            currency_system.allocate(attendee, &CurrencyType::Volunteer, reward)?;
+        }
+
+        let event = self.community_events.get_mut(event_id).expect("event checked above");
+        event.attendees.insert(attendee.to_string());
+        self.events.push(DaoEvent::AttendanceRecorded {
+            event_id: event_id.to_string(),
+            attendee: attendee.to_string(),
+            reward,
+            timestamp: self.clock.now(),
+        });
+        Ok(())
+    }
+
+    /// Closes the event to further check-ins.
+    pub fn complete_event(&mut self, event_id: &str, organizer: &str) -> IcnResult<()> {
+        self.close_event(event_id, organizer, EventStatus::Completed)
+    }
+
+    pub fn cancel_event(&mut self, event_id: &str, organizer: &str) -> IcnResult<()> {
+        self.close_event(event_id, organizer, EventStatus::Cancelled)
+    }
+
+    pub fn get_event(&self, event_id: &str) -> IcnResult<&CommunityEvent> {
+        self.community_events.get(event_id)
+            .ok_or_else(|| IcnError::Dao("Event not found".into()))
+    }
+
+    /// Events matching `query`, soonest first.
+    pub fn list_events(&self, query: &EventQuery) -> Vec<&CommunityEvent> {
+        let mut events: Vec<&CommunityEvent> = self.community_events.values()
+            .filter(|event| event.matches(query))
+            .collect();
+        events.sort_by_key(|event| event.details.starts_at);
+        events
+    }
+
+    fn close_event(&mut self, event_id: &str, organizer: &str, status: EventStatus) -> IcnResult<()> {
+        let event = self.scheduled_event_mut(event_id)?;
+        if !event.organizers.iter().any(|o| o == organizer) {
+            return Err(IcnError::Dao("Only an organizer can close an event".into()));
+        }
+        event.status = status;
+        Ok(())
+    }
+
+    fn scheduled_event_mut(&mut self, event_id: &str) -> IcnResult<&mut CommunityEvent> {
+        let event = self.community_events.get_mut(event_id)
+            .ok_or_else(|| IcnError::Dao("Event not found".into()))?;
+        if event.status != EventStatus::Scheduled {
+            return Err(IcnError::Dao("Event is no longer scheduled".into()));
+        }
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaoType;
+    use chrono::Duration;
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_event_lifecycle() {
+        let keypair = Keypair::generate(&mut OsRng);
+        let organizer = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        let mut dao = Dao::new("Test DAO".to_string(), DaoType::Community, 0.5, 0.5);
+        dao.add_member(organizer.clone(), "Olga".to_string()).unwrap();
+        dao.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        dao.add_member("bob".to_string(), "Bob".to_string()).unwrap();
+        let mut currency_system = CurrencySystem::new();
+
+        let details = EventDetails {
+            name: "Cleanup Day".to_string(),
+            description: "Clean up the park".to_string(),
+            location: "Riverside Park".to_string(),
+            starts_at: Utc::now() + Duration::days(3),
+            capacity: Some(1),
+            volunteer_reward: 5.0,
+        };
+        assert!(dao.create_event("mallory", details.clone()).is_err());
+        let event_id = dao.create_event(&organizer, details).unwrap();
+
+        dao.rsvp(&event_id, "alice").unwrap();
+        assert!(dao.rsvp(&event_id, "bob").is_err());
+        dao.cancel_rsvp(&event_id, "alice").unwrap();
+        dao.rsvp(&event_id, "bob").unwrap();
+
+        // Signed by someone other than the organizer
+        let forged = Keypair::generate(&mut OsRng).sign(&check_in_message(&event_id, "bob"));
+        assert!(dao.check_in(&event_id, "bob", &organizer, &forged.to_bytes(), &mut currency_system).is_err());
+
+        let signature = keypair.sign(&check_in_message(&event_id, "bob"));
+        dao.check_in(&event_id, "bob", &organizer, &signature.to_bytes(), &mut currency_system).unwrap();
+        assert!(dao.check_in(&event_id, "bob", &organizer, &signature.to_bytes(), &mut currency_system).is_err());
+        assert_eq!(currency_system.get_balance("bob", &CurrencyType::Volunteer).unwrap(), 5.0);
+
+        let query = EventQuery { attendee: Some("bob".to_string()), ..Default::default() };
+        assert_eq!(dao.list_events(&query).len(), 1);
+        assert!(dao.list_events(&EventQuery { organizer: Some("alice".to_string()), ..Default::default() }).is_empty());
+
+        assert!(dao.complete_event(&event_id, "alice").is_err());
+        dao.complete_event(&event_id, &organizer).unwrap();
+        let signature = keypair.sign(&check_in_message(&event_id, "alice"));
+        assert!(dao.check_in(&event_id, "alice", &organizer, &signature.to_bytes(), &mut currency_system).is_err());
+        assert_eq!(dao.get_event(&event_id).unwrap().status, EventStatus::Completed);
+    }
+}
@@ -0,0 +1,207 @@
+// crates/icn_dao/src/contributions.rs
+
+use crate::community_events::did_public_key;
+use crate::{Dao, DaoEvent};
+use chrono::{DateTime, Duration, Utc};
+use ed25519_dalek::{Signature, Verifier};
+use icn_common::{IcnResult, IcnError, CurrencyType};
+use icn_currency::CurrencySystem;
+use serde::{Serialize, Deserialize};
+
+/// Cap period used until a DAO sets its own.
+pub const DEFAULT_CONTRIBUTION_PERIOD_DAYS: i64 = 30;
+
+/// Work a member did for a DAO, as vouched for by one of its stewards.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContributionRecord {
+    /// Chosen by the steward; each record can only be paid once.
+    pub id: String,
+    pub contributor: String,
+    /// `Volunteer` for unpaid community work, `Service` for work done for
+    /// the cooperative.
+    pub currency_type: CurrencyType,
+    pub hours: f64,
+    pub tasks: u32,
+    pub description: String,
+    pub performed_at: DateTime<Utc>,
+    pub steward: String,
+    /// The steward's signature over `message()`, made with the key in
+    /// their DID.
+    pub signature: Vec<u8>,
+}
+
+impl ContributionRecord {
+    /// What the steward signs.
+    pub fn message(&self) -> Vec<u8> {
+        format!(
+            "icn-contribution:{}:{}:{:?}:{}:{}:{}:{}",
+            self.id, self.contributor, self.currency_type, self.hours, self.tasks,
+            self.performed_at.timestamp(), self.description,
+        ).into_bytes()
+    }
+}
+
+/// How a DAO pays for contributions. Set by the DAO's own proposals
+/// through its scoped parameters.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ContributionRates {
+    pub hourly_rate: f64,
+    pub task_rate: f64,
+    /// Most one contributor can be paid within a period. Zero means no cap.
+    pub period_cap: f64,
+    pub period_days: i64,
+}
+
+/// A contribution record and what was minted for it, kept as the DAO's
+/// public audit trail.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContributionMint {
+    pub record: ContributionRecord,
+    /// What the rates priced the work at.
+    pub earned: f64,
+    /// What was minted after the period cap.
+    pub minted: f64,
+    pub minted_at: DateTime<Utc>,
+}
+
+impl Dao {
+    pub fn add_steward(&mut self, member_id: &str) -> IcnResult<()> {
+        if !self.members.contains_key(member_id) {
+            return Err(IcnError::Dao("Only members can be stewards".into()));
+        }
+        self.stewards.insert(member_id.to_string());
+        Ok(())
+    }
+
+    pub fn remove_steward(&mut self, member_id: &str) -> IcnResult<()> {
+        if !self.stewards.remove(member_id) {
+            return Err(IcnError::Dao("Member is not a steward".into()));
+        }
+        Ok(())
+    }
+
+    /// Mints currency for a signed contribution record at `rates`, up to
+    /// what the contributor has left under the period cap.
+    pub fn record_contribution(
+        &mut self,
+        record: ContributionRecord,
+        rates: &ContributionRates,
+        currency_system: &mut CurrencySystem,
+        now: DateTime<Utc>,
+    ) -> IcnResult<ContributionMint> {
+        if !matches!(record.currency_type, CurrencyType::Volunteer | CurrencyType::Service) {
+            return Err(IcnError::Dao("Contributions are paid in Volunteer or Service currency".into()));
+        }
+        if !self.stewards.contains(&record.steward) {
+            return Err(IcnError::Dao(format!("{} is not a steward of this DAO", record.steward)));
+        }
+        if !self.members.contains_key(&record.contributor) {
+            return Err(IcnError::Dao("Only members can be paid for contributions".into()));
+        }
+        if record.contributor == record.steward {
+            return Err(IcnError::Dao("Stewards cannot vouch for their own work".into()));
+        }
+        if self.contributions.iter().any(|mint| mint.record.id == record.id) {
+            return Err(IcnError::Dao(format!("Contribution {} has already been paid", record.id)));
+        }
+        if !record.hours.is_finite() || record.hours < 0.0 || record.performed_at > now {
+            return Err(IcnError::Dao("Contributions need non-negative hours and a time in the past".into()));
+        }
+
+        let signature = Signature::from_bytes(&record.signature)
+            .map_err(|e| IcnError::Dao(format!("Invalid contribution signature: {}", e)))?;
+        did_public_key(&record.steward)?
+            .verify(&record.message(), &signature)
+            .map_err(|_| IcnError::Dao("Contribution signature verification failed".into()))?;
+
+        let earned = record.hours * rates.hourly_rate + record.tasks as f64 * rates.task_rate;
+        let minted = if rates.period_cap > 0.0 {
+            let since = now - Duration::days(rates.period_days);
+            let paid: f64 = self.contributions.iter()
+                .filter(|mint| mint.record.contributor == record.contributor && mint.minted_at > since)
+                .map(|mint| mint.minted)
+                .sum();
+            earned.min((rates.period_cap - paid).max(0.0))
+        } else {
+            earned
+        };
+        if minted <= 0.0 {
+            return Err(IcnError::Dao(format!("{} has nothing left to be paid this period", record.contributor)));
+        }
+
+        currency_system.allocate(&record.contributor, &record.currency_type, minted)?;
+        let mint = ContributionMint { record, earned, minted, minted_at: now };
+        self.events.push(DaoEvent::ContributionMinted {
+            record_id: mint.record.id.clone(),
+            contributor: mint.record.contributor.clone(),
+            amount: minted,
+            timestamp: now,
+        });
+        self.contributions.push(mint.clone());
+        Ok(mint)
+    }
+
+    /// Paid contributions, oldest first, optionally for one contributor.
+    pub fn list_contributions(&self, contributor: Option<&str>) -> Vec<&ContributionMint> {
+        self.contributions.iter()
+            .filter(|mint| contributor.is_none_or(|contributor| mint.record.contributor == contributor))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaoType;
+    use ed25519_dalek::{Keypair, Signer};
+    use rand::rngs::OsRng;
+
+    #[test]
+    fn test_contributions_mint_up_to_cap() {
+        let keypair = Keypair::generate(&mut OsRng);
+        let steward = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        let mut dao = Dao::new("Tool Library".to_string(), DaoType::Community, 0.5, 0.5);
+        dao.add_member(steward.clone(), "Sam".to_string()).unwrap();
+        dao.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        let mut currency_system = CurrencySystem::new();
+        let rates = ContributionRates { hourly_rate: 10.0, task_rate: 5.0, period_cap: 100.0, period_days: 30 };
+        let now = Utc::now();
+
+        let sign = |id: &str, hours: f64, tasks: u32| {
+            let mut record = ContributionRecord {
+                id: id.to_string(),
+                contributor: "alice".to_string(),
+                currency_type: CurrencyType::Volunteer,
+                hours,
+                tasks,
+                description: "Repaired bikes".to_string(),
+                performed_at: now - Duration::hours(1),
+                steward: steward.clone(),
+                signature: Vec::new(),
+            };
+            record.signature = keypair.sign(&record.message()).to_bytes().to_vec();
+            record
+        };
+
+        // Stewards must be appointed before their records count
+        assert!(dao.record_contribution(sign("r1", 4.0, 2), &rates, &mut currency_system, now).is_err());
+        dao.add_steward(&steward).unwrap();
+
+        let mut forged = sign("r1", 4.0, 2);
+        forged.hours = 40.0;
+        assert!(dao.record_contribution(forged, &rates, &mut currency_system, now).is_err());
+
+        let mint = dao.record_contribution(sign("r1", 4.0, 2), &rates, &mut currency_system, now).unwrap();
+        assert_eq!(mint.minted, 50.0);
+        assert!(dao.record_contribution(sign("r1", 4.0, 2), &rates, &mut currency_system, now).is_err());
+
+        // The cap leaves 50 for the rest of the period
+        let mint = dao.record_contribution(sign("r2", 8.0, 0), &rates, &mut currency_system, now).unwrap();
+        assert_eq!((mint.earned, mint.minted), (80.0, 50.0));
+        assert!(dao.record_contribution(sign("r3", 1.0, 0), &rates, &mut currency_system, now).is_err());
+        assert_eq!(currency_system.get_balance("alice", &CurrencyType::Volunteer).unwrap(), 100.0);
+
+        assert_eq!(dao.list_contributions(Some("alice")).len(), 2);
+        assert!(dao.list_contributions(Some(&steward)).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+// crates/icn_dao/src/execution.rs
+
+use crate::{Dao, ProposalStatus};
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{IcnResult, IcnError};
+use icn_vm::Value;
+use serde::{Serialize, Deserialize};
+
+/// Time between a proposal passing and its contract call running, unless
+/// the DAO sets its own.
+pub const DEFAULT_EXECUTION_TIMELOCK_SECS: i64 = 2 * 24 * 3600;
+
+pub(crate) fn default_execution_timelock_secs() -> i64 {
+    DEFAULT_EXECUTION_TIMELOCK_SECS
+}
+
+/// A contract function a proposal calls once it passes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContractCall {
+    pub contract_id: String,
+    pub function: String,
+    #[serde(default)]
+    pub args: Vec<Value>,
+}
+
+/// What happened when a proposal's contract call ran.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ContractExecution {
+    pub executed_at: DateTime<Utc>,
+    /// The function's return value, or why the call failed.
+    pub result: Result<Option<Value>, String>,
+}
+
+impl Dao {
+    /// Sets how long passed proposals wait before their contract calls run,
+    /// giving members time to react.
+    pub fn set_execution_timelock(&mut self, secs: i64) -> IcnResult<()> {
+        if secs < 0 {
+            return Err(IcnError::Dao("The execution timelock cannot be negative".into()));
+        }
+        self.execution_timelock_secs = secs;
+        Ok(())
+    }
+
+    /// Creates a proposal that calls `call` once it passes and the
+    /// timelock elapses.
+    pub fn create_proposal_with_call(&mut self, title: String, description: String, proposer: String, duration: Duration, call: ContractCall) -> IcnResult<String> {
+        if call.contract_id.trim().is_empty() || call.function.trim().is_empty() {
+            return Err(IcnError::Dao("A contract call needs a contract and a function".into()));
+        }
+        let id = self.create_proposal(title, description, proposer, duration)?;
+        if let Some(proposal) = self.proposals.get_mut(&id) {
+            proposal.contract_call = Some(call);
+        }
+        Ok(id)
+    }
+
+    /// Passed proposals whose contract calls are due at `now` and have not
+    /// run yet, oldest first.
+    pub fn due_contract_calls(&self, now: DateTime<Utc>) -> Vec<(String, ContractCall)> {
+        let timelock = Duration::seconds(self.execution_timelock_secs);
+        let mut due: Vec<_> = self.proposals.values()
+            .filter(|proposal| proposal.status == ProposalStatus::Passed && proposal.execution.is_none())
+            .filter_map(|proposal| {
+                let passed_at = proposal.passed_at?;
+                let call = proposal.contract_call.as_ref()?;
+                (passed_at + timelock <= now).then(|| (passed_at, proposal.id.clone(), call.clone()))
+            })
+            .collect();
+        due.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(&b.1)));
+        due.into_iter().map(|(_, id, call)| (id, call)).collect()
+    }
+
+    /// Records the outcome of a proposal's contract call. A successful call
+    /// executes the proposal; a failed one leaves it passed with the error
+    /// on record, and it is not retried.
+    pub fn record_execution(&mut self, proposal_id: &str, result: Result<Option<Value>, String>, now: DateTime<Utc>) -> IcnResult<()> {
+        let proposal = self.proposals.get_mut(proposal_id)
+            .ok_or_else(|| IcnError::Dao("Proposal not found".into()))?;
+        if proposal.contract_call.is_none() || proposal.execution.is_some() {
+            return Err(IcnError::Dao("Proposal has no contract call waiting to run".into()));
+        }
+        if result.is_ok() {
+            proposal.status = ProposalStatus::Executed;
+        }
+        proposal.execution = Some(ContractExecution { executed_at: now, result });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaoType;
+
+    #[test]
+    fn test_contract_calls_wait_for_timelock() {
+        let mut dao = Dao::new("Housing".to_string(), DaoType::Cooperative, 0.5, 0.5);
+        dao.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        dao.add_member("bob".to_string(), "Bob".to_string()).unwrap();
+        dao.set_execution_timelock(3600).unwrap();
+
+        let call = ContractCall {
+            contract_id: "budget".to_string(),
+            function: "allocate".to_string(),
+            args: vec![Value::String("repairs".to_string()), Value::Int(500)],
+        };
+        let id = dao.create_proposal_with_call("Repairs".to_string(), "Fund the roof".to_string(), "alice".to_string(), Duration::days(7), call.clone()).unwrap();
+        dao.vote(&id, "alice", true).unwrap();
+        dao.vote(&id, "bob", true).unwrap();
+        assert_eq!(dao.finalize_proposal(&id).unwrap(), ProposalStatus::Passed);
+
+        // The node runs the call, not members
+        assert!(dao.execute_proposal(&id).is_err());
+
+        let passed_at = dao.proposals[&id].passed_at.unwrap();
+        assert!(dao.due_contract_calls(passed_at).is_empty());
+        let now = passed_at + Duration::seconds(3600);
+        assert_eq!(dao.due_contract_calls(now), vec![(id.clone(), call)]);
+
+        dao.record_execution(&id, Ok(Some(Value::Bool(true))), now).unwrap();
+        assert_eq!(dao.proposals[&id].status, ProposalStatus::Executed);
+        assert!(dao.due_contract_calls(now).is_empty());
+        assert!(dao.record_execution(&id, Err("again".to_string()), now).is_err());
+    }
+}
@@ -0,0 +1,569 @@
+// crates/icn_dao/src/grants.rs
+
+use crate::{Dao, DaoEvent};
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{IcnResult, IcnError, Amount, CurrencyType};
+use icn_currency::CurrencySystem;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// What a grant round is opened with, as approved by a DAO proposal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GrantRoundTerms {
+    pub title: String,
+    pub currency_type: CurrencyType,
+    /// Taken from the DAO treasury into the round's escrow when it opens.
+    pub budget: Amount,
+    /// Voters back applications with their own funds and the budget is
+    /// paid out as quadratic funding matches. Otherwise members vote with
+    /// their reputation and the budget funds the most supported requests.
+    pub quadratic_matching: bool,
+    pub application_secs: i64,
+    pub voting_secs: i64,
+}
+
+impl GrantRoundTerms {
+    /// Checks the terms a round would open with, apart from the treasury
+    /// covering its budget.
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.title.trim().is_empty() || !self.budget.is_positive() {
+            return Err(IcnError::Dao("Grant rounds need a title and a positive budget".into()));
+        }
+        if self.application_secs <= 0 || self.voting_secs <= 0 {
+            return Err(IcnError::Dao("Grant rounds need positive application and voting periods".into()));
+        }
+        Ok(())
+    }
+}
+
+/// A funding round: members apply with milestones until applications
+/// close, then vote until voting closes, then the budget is awarded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GrantRound {
+    pub id: String,
+    pub terms: GrantRoundTerms,
+    /// The DAO proposal that opened the round.
+    pub proposal_id: String,
+    pub opened_at: DateTime<Utc>,
+    pub applications_close: DateTime<Utc>,
+    pub voting_closes: DateTime<Utc>,
+    pub applications: BTreeMap<String, GrantApplication>,
+    pub finalized_at: Option<DateTime<Utc>>,
+}
+
+impl GrantRound {
+    /// Account holding the round's budget, backers' contributions and
+    /// awards until milestones release them.
+    pub fn escrow_account(&self) -> String {
+        format!("grants:{}", self.id)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum GrantStatus {
+    Submitted,
+    Funded,
+    Unfunded,
+    Completed,
+    ClawedBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GrantApplication {
+    pub id: String,
+    pub applicant: String,
+    pub title: String,
+    pub description: String,
+    pub milestones: Vec<Milestone>,
+    /// Voters and their reputation-weighted votes.
+    pub votes: BTreeMap<String, f64>,
+    /// What backers contributed under quadratic matching.
+    #[serde(default)]
+    pub contributions: BTreeMap<String, Amount>,
+    /// Set when the round is finalized, including backers' contributions.
+    pub award: Amount,
+    /// What milestones have released to the applicant so far.
+    pub paid: Amount,
+    pub status: GrantStatus,
+}
+
+impl GrantApplication {
+    /// What the milestones add up to. Applications are only accepted if
+    /// this does not overflow.
+    pub fn requested(&self) -> Amount {
+        self.milestones.iter().map(|milestone| milestone.amount).sum()
+    }
+
+    fn support(&self) -> f64 {
+        self.votes.values().sum()
+    }
+
+    fn contributed(&self) -> Amount {
+        self.contributions.values().copied().sum()
+    }
+
+    /// The quadratic funding match before it is scaled to the budget: the
+    /// square of the summed square roots of contributions, less the
+    /// contributions themselves. Summed over pairs of backers so a lone
+    /// backer is matched exactly nothing.
+    fn raw_match(&self) -> f64 {
+        let amounts: Vec<f64> = self.contributions.values().map(|amount| amount.to_f64()).collect();
+        amounts.iter().enumerate()
+            .flat_map(|(i, a)| amounts[i + 1..].iter().map(move |b| 2.0 * (a * b).sqrt()))
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum MilestoneStatus {
+    Pending,
+    Released,
+    Failed,
+}
+
+/// A deliverable and the part of the request paid out when a steward
+/// confirms it. Funded applications are paid in the same proportion of
+/// what they were awarded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Milestone {
+    pub description: String,
+    pub amount: Amount,
+    pub due: DateTime<Utc>,
+    pub status: MilestoneStatus,
+}
+
+impl Milestone {
+    pub fn new(description: &str, amount: Amount, due: DateTime<Utc>) -> Self {
+        Milestone { description: description.to_string(), amount, due, status: MilestoneStatus::Pending }
+    }
+}
+
+impl Dao {
+    /// Account the DAO's grant budgets are drawn from and clawbacks
+    /// return to.
+    pub fn treasury_account(&self) -> String {
+        format!("dao:{}", self.id)
+    }
+
+    /// Opens a grant round, moving its budget from the treasury to escrow.
+    pub fn open_grant_round(
+        &mut self,
+        terms: GrantRoundTerms,
+        proposal_id: &str,
+        currency_system: &mut CurrencySystem,
+        now: DateTime<Utc>,
+    ) -> IcnResult<String> {
+        terms.validate()?;
+
+        let applications_close = now + Duration::seconds(terms.application_secs);
+        let round = GrantRound {
+            id: Uuid::new_v4().to_string(),
+            proposal_id: proposal_id.to_string(),
+            opened_at: now,
+            applications_close,
+            voting_closes: applications_close + Duration::seconds(terms.voting_secs),
+            applications: BTreeMap::new(),
+            finalized_at: None,
+            terms,
+        };
+        currency_system.transfer_amount(&self.treasury_account(), &round.escrow_account(), &round.terms.currency_type, round.terms.budget)?;
+        let round_id = round.id.clone();
+        self.grant_rounds.insert(round_id.clone(), round);
+        Ok(round_id)
+    }
+
+    pub fn apply_for_grant(
+        &mut self,
+        round_id: &str,
+        applicant: &str,
+        title: &str,
+        description: &str,
+        milestones: Vec<Milestone>,
+        now: DateTime<Utc>,
+    ) -> IcnResult<String> {
+        if !self.members.contains_key(applicant) {
+            return Err(IcnError::Dao("Only members can apply for grants".into()));
+        }
+        let round = self.grant_round_mut(round_id)?;
+        if now >= round.applications_close {
+            return Err(IcnError::Dao("Applications for this round have closed".into()));
+        }
+        if title.trim().is_empty() || milestones.is_empty() {
+            return Err(IcnError::Dao("Grant applications need a title and at least one milestone".into()));
+        }
+        if milestones.iter().any(|milestone| !milestone.amount.is_positive()) {
+            return Err(IcnError::Dao("Milestones must be for positive amounts".into()));
+        }
+        if milestones.iter().try_fold(Amount::ZERO, |total, milestone| total.checked_add(milestone.amount)).is_none() {
+            return Err(IcnError::Dao("Milestones add up to more than can be paid".into()));
+        }
+        if milestones.iter().any(|milestone| milestone.due <= round.voting_closes) {
+            return Err(IcnError::Dao("Milestones must fall due after voting closes".into()));
+        }
+
+        let application = GrantApplication {
+            id: Uuid::new_v4().to_string(),
+            applicant: applicant.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            milestones: milestones.into_iter()
+                .map(|milestone| Milestone { status: MilestoneStatus::Pending, ..milestone })
+                .collect(),
+            votes: BTreeMap::new(),
+            contributions: BTreeMap::new(),
+            award: Amount::ZERO,
+            paid: Amount::ZERO,
+            status: GrantStatus::Submitted,
+        };
+        if !round.terms.quadratic_matching && application.requested() > round.terms.budget {
+            return Err(IcnError::Dao("Application asks for more than the round's budget".into()));
+        }
+        let application_id = application.id.clone();
+        round.applications.insert(application_id.clone(), application);
+        Ok(application_id)
+    }
+
+    /// Backs an application while voting is open. Under quadratic matching
+    /// `amount` is moved from the voter to escrow, and can be added to;
+    /// otherwise it is ignored and the vote counts the member's reputation
+    /// once.
+    pub fn vote_for_grant(
+        &mut self,
+        round_id: &str,
+        application_id: &str,
+        member_id: &str,
+        amount: Amount,
+        currency_system: &mut CurrencySystem,
+        now: DateTime<Utc>,
+    ) -> IcnResult<()> {
+        let weight = self.members.get(member_id)
+            .ok_or_else(|| IcnError::Dao("Only members can vote on grants".into()))?
+            .reputation * self.vote_multiplier(member_id);
+        let round = self.grant_round_mut(round_id)?;
+        if now < round.applications_close || now >= round.voting_closes {
+            return Err(IcnError::Dao("Voting on this round is not open".into()));
+        }
+        let (quadratic, currency_type, escrow) = (round.terms.quadratic_matching, round.terms.currency_type.clone(), round.escrow_account());
+        let application = round.applications.get_mut(application_id)
+            .ok_or_else(|| IcnError::Dao("Grant application not found".into()))?;
+        if application.applicant == member_id {
+            return Err(IcnError::Dao("Applicants cannot vote for their own grant".into()));
+        }
+
+        if !quadratic {
+            if application.votes.contains_key(member_id) {
+                return Err(IcnError::Dao("Member has already voted for this application".into()));
+            }
+            application.votes.insert(member_id.to_string(), weight);
+            return Ok(());
+        }
+        if !amount.is_positive() {
+            return Err(IcnError::Dao("Quadratic funding votes must contribute a positive amount".into()));
+        }
+        if application.contributed().checked_add(amount).is_none_or(|total| total > application.requested()) {
+            return Err(IcnError::Dao("Contribution would take the application past what it asked for".into()));
+        }
+        currency_system.transfer_amount(member_id, &escrow, &currency_type, amount)?;
+        *application.contributions.entry(member_id.to_string()).or_default() += amount;
+        Ok(())
+    }
+
+    /// Awards the round once voting has closed and returns what each
+    /// funded application was awarded. Budget that is not awarded goes
+    /// back to the treasury.
+    pub fn finalize_grant_round(&mut self, round_id: &str, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Vec<(String, Amount)>> {
+        let treasury = self.treasury_account();
+        let round = self.grant_round_mut(round_id)?;
+        if round.finalized_at.is_some() {
+            return Err(IcnError::Dao("Grant round has already been finalized".into()));
+        }
+        if now < round.voting_closes {
+            return Err(IcnError::Dao("Voting on this round has not closed".into()));
+        }
+
+        let budget = round.terms.budget;
+        let mut from_budget = Amount::ZERO;
+        if round.terms.quadratic_matching {
+            let total_match: f64 = round.applications.values().map(GrantApplication::raw_match).sum();
+            let scale = if total_match > budget.to_f64() { budget.to_f64() / total_match } else { 1.0 };
+            for application in round.applications.values_mut() {
+                let contributed = application.contributed();
+                if !contributed.is_positive() {
+                    continue;
+                }
+                // Rounding can't take the matches past the budget
+                let matched = Amount::from(application.raw_match() * scale).min(budget - from_budget);
+                application.award = application.requested().min(contributed + matched);
+                from_budget += application.award - contributed;
+            }
+        } else {
+            let mut ranked: Vec<&mut GrantApplication> = round.applications.values_mut()
+                .filter(|application| application.support() > 0.0)
+                .collect();
+            ranked.sort_by(|a, b| b.support().total_cmp(&a.support()).then_with(|| a.id.cmp(&b.id)));
+            for application in ranked {
+                if from_budget + application.requested() <= budget {
+                    application.award = application.requested();
+                    from_budget += application.award;
+                }
+            }
+        }
+
+        let mut awards = Vec::new();
+        for application in round.applications.values_mut() {
+            if application.award.is_positive() {
+                application.status = GrantStatus::Funded;
+                awards.push((application.id.clone(), application.award));
+            } else {
+                application.status = GrantStatus::Unfunded;
+            }
+        }
+        round.finalized_at = Some(now);
+        let leftover = budget - from_budget;
+        if leftover.is_positive() {
+            currency_system.transfer_amount(&round.escrow_account(), &treasury, &round.terms.currency_type, leftover)?;
+        }
+        for (application_id, award) in &awards {
+            self.events.push(DaoEvent::GrantAwarded {
+                round_id: round_id.to_string(),
+                application_id: application_id.clone(),
+                amount: *award,
+                timestamp: now,
+            });
+        }
+        Ok(awards)
+    }
+
+    /// Pays the next milestone of a funded application from escrow, once a
+    /// steward confirms it was delivered.
+    pub fn release_milestone(
+        &mut self,
+        round_id: &str,
+        application_id: &str,
+        steward: &str,
+        currency_system: &mut CurrencySystem,
+        now: DateTime<Utc>,
+    ) -> IcnResult<Amount> {
+        self.check_milestone_steward(round_id, application_id, steward)?;
+        let round = self.grant_round_mut(round_id)?;
+        let (currency_type, escrow) = (round.terms.currency_type.clone(), round.escrow_account());
+        let application = round.applications.get_mut(application_id)
+            .ok_or_else(|| IcnError::Dao("Grant application not found".into()))?;
+        if application.status != GrantStatus::Funded {
+            return Err(IcnError::Dao("Only funded grants have milestones to release".into()));
+        }
+
+        let requested = application.requested();
+        let index = application.milestones.iter().position(|milestone| milestone.status == MilestoneStatus::Pending)
+            .ok_or_else(|| IcnError::Dao("Grant has no pending milestones".into()))?;
+        let last = index + 1 == application.milestones.len();
+        // The last milestone takes what is left so rounding never strands funds
+        let payment = if last {
+            application.award - application.paid
+        } else {
+            application.award.mul_div(application.milestones[index].amount.units(), requested.units())
+                .ok_or_else(|| IcnError::Dao("Milestone payment cannot be represented".into()))?
+        };
+        currency_system.transfer_amount(&escrow, &application.applicant, &currency_type, payment)?;
+        application.milestones[index].status = MilestoneStatus::Released;
+        application.paid += payment;
+        if last {
+            application.status = GrantStatus::Completed;
+        }
+        self.events.push(DaoEvent::MilestoneReleased {
+            round_id: round_id.to_string(),
+            application_id: application_id.to_string(),
+            milestone: index,
+            amount: payment,
+            timestamp: now,
+        });
+        Ok(payment)
+    }
+
+    /// Fails the next milestone of a funded application on a steward's
+    /// word and returns what has not been paid out to the treasury.
+    pub fn fail_milestone(
+        &mut self,
+        round_id: &str,
+        application_id: &str,
+        steward: &str,
+        currency_system: &mut CurrencySystem,
+        now: DateTime<Utc>,
+    ) -> IcnResult<Amount> {
+        self.check_milestone_steward(round_id, application_id, steward)?;
+        self.claw_back(round_id, application_id, currency_system, now)
+    }
+
+    /// Claws back every funded grant with a pending milestone past its due
+    /// date. Returns the round, application and amount of each clawback.
+    pub fn claw_back_overdue_grants(&mut self, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Vec<(String, String, Amount)>> {
+        let overdue: Vec<(String, String)> = self.grant_rounds.values()
+            .flat_map(|round| round.applications.values()
+                .filter(|application| application.status == GrantStatus::Funded)
+                .filter(|application| application.milestones.iter()
+                    .any(|milestone| milestone.status == MilestoneStatus::Pending && milestone.due < now))
+                .map(move |application| (round.id.clone(), application.id.clone())))
+            .collect();
+        let mut clawbacks = Vec::new();
+        for (round_id, application_id) in overdue {
+            let amount = self.claw_back(&round_id, &application_id, currency_system, now)?;
+            clawbacks.push((round_id, application_id, amount));
+        }
+        Ok(clawbacks)
+    }
+
+    pub fn get_grant_round(&self, round_id: &str) -> IcnResult<&GrantRound> {
+        self.grant_rounds.get(round_id)
+            .ok_or_else(|| IcnError::Dao("Grant round not found".into()))
+    }
+
+    fn grant_round_mut(&mut self, round_id: &str) -> IcnResult<&mut GrantRound> {
+        self.grant_rounds.get_mut(round_id)
+            .ok_or_else(|| IcnError::Dao("Grant round not found".into()))
+    }
+
+    fn check_milestone_steward(&self, round_id: &str, application_id: &str, steward: &str) -> IcnResult<()> {
+        if !self.stewards.contains(steward) {
+            return Err(IcnError::Dao(format!("{} is not a steward of this DAO", steward)));
+        }
+        let application = self.get_grant_round(round_id)?.applications.get(application_id)
+            .ok_or_else(|| IcnError::Dao("Grant application not found".into()))?;
+        if application.applicant == steward {
+            return Err(IcnError::Dao("Stewards cannot sign off their own grant".into()));
+        }
+        Ok(())
+    }
+
+    fn claw_back(&mut self, round_id: &str, application_id: &str, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Amount> {
+        let treasury = self.treasury_account();
+        let round = self.grant_round_mut(round_id)?;
+        let (currency_type, escrow) = (round.terms.currency_type.clone(), round.escrow_account());
+        let application = round.applications.get_mut(application_id)
+            .ok_or_else(|| IcnError::Dao("Grant application not found".into()))?;
+        if application.status != GrantStatus::Funded {
+            return Err(IcnError::Dao("Only funded grants can be clawed back".into()));
+        }
+
+        let remaining = application.award - application.paid;
+        if remaining.is_positive() {
+            currency_system.transfer_amount(&escrow, &treasury, &currency_type, remaining)?;
+        }
+        if let Some(milestone) = application.milestones.iter_mut().find(|milestone| milestone.status == MilestoneStatus::Pending) {
+            milestone.status = MilestoneStatus::Failed;
+        }
+        application.status = GrantStatus::ClawedBack;
+        self.events.push(DaoEvent::GrantClawedBack {
+            round_id: round_id.to_string(),
+            application_id: application_id.to_string(),
+            amount: remaining,
+            timestamp: now,
+        });
+        Ok(remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaoType;
+
+    const CURRENCY: CurrencyType = CurrencyType::Service;
+
+    fn setup(quadratic_matching: bool, now: DateTime<Utc>) -> (Dao, CurrencySystem, String) {
+        let mut dao = Dao::new("Makers".to_string(), DaoType::Community, 0.5, 0.5);
+        for member in ["alice", "bob", "carol", "dave", "steward"] {
+            dao.add_member(member.to_string(), member.to_string()).unwrap();
+        }
+        dao.add_steward("steward").unwrap();
+        let mut currency_system = CurrencySystem::new();
+        currency_system.allocate(&dao.treasury_account(), &CURRENCY, 1000.0).unwrap();
+        for member in ["carol", "dave"] {
+            currency_system.allocate(member, &CURRENCY, 100.0).unwrap();
+        }
+        let terms = GrantRoundTerms {
+            title: "Spring round".to_string(),
+            currency_type: CURRENCY,
+            budget: Amount::from(300.0),
+            quadratic_matching,
+            application_secs: 60,
+            voting_secs: 60,
+        };
+        let round_id = dao.open_grant_round(terms, "p1", &mut currency_system, now).unwrap();
+        (dao, currency_system, round_id)
+    }
+
+    fn milestones(now: DateTime<Utc>, amounts: &[f64]) -> Vec<Milestone> {
+        amounts.iter().enumerate()
+            .map(|(i, amount)| Milestone::new(&format!("Step {}", i), Amount::from(*amount), now + Duration::days(i as i64 + 1)))
+            .collect()
+    }
+
+    #[test]
+    fn test_grant_round_funds_and_releases_milestones() {
+        let now = Utc::now();
+        let (mut dao, mut currency_system, round_id) = setup(false, now);
+        let treasury = dao.treasury_account();
+        assert_eq!(currency_system.get_balance(&treasury, &CURRENCY).unwrap(), 700.0);
+
+        let first = dao.apply_for_grant(&round_id, "alice", "Tool shed", "", milestones(now, &[100.0, 100.0]), now).unwrap();
+        let second = dao.apply_for_grant(&round_id, "bob", "Kiln", "", milestones(now, &[150.0]), now).unwrap();
+        assert!(dao.apply_for_grant(&round_id, "carol", "Too big", "", milestones(now, &[400.0]), now).is_err());
+
+        // Voting opens when applications close
+        assert!(dao.vote_for_grant(&round_id, &first, "carol", Amount::ZERO, &mut currency_system, now).is_err());
+        let voting = now + Duration::seconds(90);
+        assert!(dao.apply_for_grant(&round_id, "carol", "Late", "", milestones(now, &[10.0]), voting).is_err());
+        assert!(dao.vote_for_grant(&round_id, &first, "alice", Amount::ZERO, &mut currency_system, voting).is_err());
+        for member in ["carol", "dave"] {
+            dao.vote_for_grant(&round_id, &first, member, Amount::ZERO, &mut currency_system, voting).unwrap();
+        }
+        dao.vote_for_grant(&round_id, &second, "carol", Amount::ZERO, &mut currency_system, voting).unwrap();
+        assert!(dao.vote_for_grant(&round_id, &second, "carol", Amount::ZERO, &mut currency_system, voting).is_err());
+
+        // Only the more supported application fits in the budget
+        assert!(dao.finalize_grant_round(&round_id, &mut currency_system, voting).is_err());
+        let closed = now + Duration::seconds(150);
+        let awards = dao.finalize_grant_round(&round_id, &mut currency_system, closed).unwrap();
+        assert_eq!(awards, vec![(first.clone(), Amount::from(200.0))]);
+        assert_eq!(dao.get_grant_round(&round_id).unwrap().applications[&second].status, GrantStatus::Unfunded);
+        assert_eq!(currency_system.get_balance(&treasury, &CURRENCY).unwrap(), 800.0);
+
+        assert!(dao.release_milestone(&round_id, &first, "bob", &mut currency_system, closed).is_err());
+        assert_eq!(dao.release_milestone(&round_id, &first, "steward", &mut currency_system, closed).unwrap(), Amount::from(100.0));
+
+        // The second milestone is missed and the rest goes back to the treasury
+        assert!(dao.claw_back_overdue_grants(&mut currency_system, closed).unwrap().is_empty());
+        let clawbacks = dao.claw_back_overdue_grants(&mut currency_system, now + Duration::days(3)).unwrap();
+        assert_eq!(clawbacks, vec![(round_id.clone(), first.clone(), Amount::from(100.0))]);
+        let application = &dao.get_grant_round(&round_id).unwrap().applications[&first];
+        assert_eq!(application.status, GrantStatus::ClawedBack);
+        assert_eq!(application.milestones[1].status, MilestoneStatus::Failed);
+        assert_eq!(currency_system.get_balance("alice", &CURRENCY).unwrap(), 100.0);
+        assert_eq!(currency_system.get_balance(&treasury, &CURRENCY).unwrap(), 900.0);
+    }
+
+    #[test]
+    fn test_quadratic_matching_favors_broad_support() {
+        let now = Utc::now();
+        let (mut dao, mut currency_system, round_id) = setup(true, now);
+        let broad = dao.apply_for_grant(&round_id, "alice", "Garden", "", milestones(now, &[500.0]), now).unwrap();
+        let narrow = dao.apply_for_grant(&round_id, "bob", "Studio", "", milestones(now, &[500.0]), now).unwrap();
+
+        let voting = now + Duration::seconds(90);
+        assert!(dao.vote_for_grant(&round_id, &broad, "carol", Amount::ZERO, &mut currency_system, voting).is_err());
+        dao.vote_for_grant(&round_id, &broad, "carol", Amount::from(25.0), &mut currency_system, voting).unwrap();
+        dao.vote_for_grant(&round_id, &broad, "dave", Amount::from(25.0), &mut currency_system, voting).unwrap();
+        dao.vote_for_grant(&round_id, &narrow, "carol", Amount::from(50.0), &mut currency_system, voting).unwrap();
+
+        // Two backers of 25 match 50; one backer of 50 matches nothing
+        let awards = dao.finalize_grant_round(&round_id, &mut currency_system, now + Duration::seconds(150)).unwrap();
+        let awarded: BTreeMap<String, Amount> = awards.into_iter().collect();
+        assert_eq!(awarded[&broad], Amount::from(100.0));
+        assert_eq!(awarded[&narrow], Amount::from(50.0));
+        assert_eq!(currency_system.get_balance(&dao.treasury_account(), &CURRENCY).unwrap(), 950.0);
+
+        assert_eq!(dao.fail_milestone(&round_id, &narrow, "steward", &mut currency_system, now).unwrap(), Amount::from(50.0));
+        assert_eq!(currency_system.get_balance(&dao.treasury_account(), &CURRENCY).unwrap(), 1000.0);
+    }
+}
@@ -1,9 +1,25 @@
 // crates/icn_dao/src/lib.rs
 
-use std::collections::HashMap;
+pub mod archive;
+pub mod onboarding;
+pub mod community_events;
+pub mod contributions;
+pub mod execution;
+pub mod grants;
+pub mod membership;
+
+pub use crate::archive::{dao_archive_storage_key, ProposalArchiveEntry};
+pub use crate::onboarding::{Attestation, JoinRequest, JoinRequestStatus, OnboardingRules, Sponsorship};
+pub use crate::community_events::{CommunityEvent, EventDetails, EventQuery, EventStatus};
+pub use crate::contributions::{ContributionMint, ContributionRates, ContributionRecord, DEFAULT_CONTRIBUTION_PERIOD_DAYS};
+pub use crate::execution::{ContractCall, ContractExecution, DEFAULT_EXECUTION_TIMELOCK_SECS};
+pub use crate::grants::{GrantApplication, GrantRound, GrantRoundTerms, GrantStatus, Milestone, MilestoneStatus};
+pub use crate::membership::{MembershipClass, ProfitDistribution, ProfitPayout};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
-use icn_common::{IcnResult, IcnError};
+use icn_common::{system_clock, IcnResult, IcnError, Amount, SharedClock};
 use uuid::Uuid;
 
 /// Represents a member of a DAO
@@ -13,6 +29,9 @@ pub struct Member {
     pub name: String,
     pub joined_at: DateTime<Utc>,
     pub reputation: f64,
+    /// Name of the member's class, or `None` for full rights.
+    #[serde(default)]
+    pub class: Option<String>,
 }
 
 /// Represents a proposal in a DAO
@@ -26,6 +45,14 @@ pub struct Proposal {
     pub expires_at: DateTime<Utc>,
     pub status: ProposalStatus,
     pub votes: HashMap<String, Vote>,
+    /// Run by the node once the proposal passes and the DAO's timelock
+    /// elapses.
+    #[serde(default)]
+    pub contract_call: Option<ContractCall>,
+    #[serde(default)]
+    pub passed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub execution: Option<ContractExecution>,
 }
 
 /// Represents the status of a proposal
@@ -53,6 +80,52 @@ pub enum DaoType {
     Custom(String),
 }
 
+/// Something that happened in a DAO that members may want to be told about
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DaoEvent {
+    MemberWelcomed {
+        member_id: String,
+        sponsors: Vec<String>,
+        timestamp: DateTime<Utc>,
+    },
+    EventScheduled {
+        event_id: String,
+        organizer: String,
+        timestamp: DateTime<Utc>,
+    },
+    AttendanceRecorded {
+        event_id: String,
+        attendee: String,
+        reward: f64,
+        timestamp: DateTime<Utc>,
+    },
+    ContributionMinted {
+        record_id: String,
+        contributor: String,
+        amount: f64,
+        timestamp: DateTime<Utc>,
+    },
+    GrantAwarded {
+        round_id: String,
+        application_id: String,
+        amount: Amount,
+        timestamp: DateTime<Utc>,
+    },
+    MilestoneReleased {
+        round_id: String,
+        application_id: String,
+        milestone: usize,
+        amount: Amount,
+        timestamp: DateTime<Utc>,
+    },
+    GrantClawedBack {
+        round_id: String,
+        application_id: String,
+        amount: Amount,
+        timestamp: DateTime<Utc>,
+    },
+}
+
 /// Represents a Decentralized Autonomous Organization (DAO)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dao {
@@ -63,6 +136,31 @@ pub struct Dao {
     pub proposals: HashMap<String, Proposal>,
     pub quorum: f64,
     pub majority: f64,
+    #[serde(default)]
+    pub onboarding: OnboardingRules,
+    #[serde(default)]
+    pub join_requests: HashMap<String, JoinRequest>,
+    #[serde(default)]
+    pub events: Vec<DaoEvent>,
+    #[serde(default)]
+    pub community_events: HashMap<String, CommunityEvent>,
+    #[serde(default)]
+    pub membership_classes: HashMap<String, MembershipClass>,
+    /// Members who vouch for contribution records.
+    #[serde(default)]
+    pub stewards: BTreeSet<String>,
+    #[serde(default)]
+    pub contributions: Vec<ContributionMint>,
+    #[serde(default = "execution::default_execution_timelock_secs")]
+    pub execution_timelock_secs: i64,
+    #[serde(default)]
+    pub grant_rounds: BTreeMap<String, GrantRound>,
+    /// Proposals moved to storage, by id.
+    #[serde(default)]
+    pub archived_proposals: BTreeMap<String, ProposalArchiveEntry>,
+    /// Where the DAO reads the time; not persisted.
+    #[serde(skip, default = "system_clock")]
+    clock: SharedClock,
 }
 
 impl Dao {
@@ -76,9 +174,27 @@ impl Dao {
             proposals: HashMap::new(),
             quorum,
             majority,
+            onboarding: OnboardingRules::default(),
+            join_requests: HashMap::new(),
+            events: Vec::new(),
+            community_events: HashMap::new(),
+            membership_classes: HashMap::new(),
+            stewards: BTreeSet::new(),
+            contributions: Vec::new(),
+            execution_timelock_secs: DEFAULT_EXECUTION_TIMELOCK_SECS,
+            grant_rounds: BTreeMap::new(),
+            archived_proposals: BTreeMap::new(),
+            clock: system_clock(),
         }
     }
 
+    /// Dates membership, proposals and events with `clock` instead of
+    /// the system clock.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Adds a new member to the DAO
     pub fn add_member(&mut self, id: String, name: String) -> IcnResult<()> {
         if self.members.contains_key(&id) {
@@ -88,8 +204,9 @@ impl Dao {
         let member = Member {
             id: id.clone(),
             name,
-            joined_at: Utc::now(),
+            joined_at: self.clock.now(),
             reputation: 1.0,
+            class: None,
         };
 
         self.members.insert(id, member);
@@ -101,17 +218,24 @@ impl Dao {
         if !self.members.contains_key(&proposer) {
             return Err(IcnError::Dao("Proposer is not a member of the DAO".into()));
         }
+        if !self.can_propose(&proposer) {
+            return Err(IcnError::Dao("Proposer's membership class cannot create proposals".into()));
+        }
 
         let id = Uuid::new_v4().to_string();
+        let now = self.clock.now();
         let proposal = Proposal {
             id: id.clone(),
             title,
             description,
             proposer,
-            created_at: Utc::now(),
-            expires_at: Utc::now() + duration,
+            created_at: now,
+            expires_at: now + duration,
             status: ProposalStatus::Active,
             votes: HashMap::new(),
+            contract_call: None,
+            passed_at: None,
+            execution: None,
         };
 
         self.proposals.insert(id.clone(), proposal);
@@ -120,6 +244,10 @@ impl Dao {
 
     /// Casts a vote on a proposal
     pub fn vote(&mut self, proposal_id: &str, member_id: &str, in_favor: bool) -> IcnResult<()> {
+        let weight = self.members.get(member_id)
+            .ok_or_else(|| IcnError::Dao("Member not found".into()))?
+            .reputation * self.vote_multiplier(member_id);
+
         let proposal = self.proposals.get_mut(proposal_id)
             .ok_or_else(|| IcnError::Dao("Proposal not found".into()))?;
 
@@ -127,13 +255,10 @@ impl Dao {
             return Err(IcnError::Dao("Proposal is not active".into()));
         }
 
-        let member = self.members.get(member_id)
-            .ok_or_else(|| IcnError::Dao("Member not found".into()))?;
-
         let vote = Vote {
             member: member_id.to_string(),
             in_favor,
-            weight: member.reputation,
+            weight,
         };
 
         proposal.votes.insert(member_id.to_string(), vote);
@@ -142,6 +267,7 @@ impl Dao {
 
     /// Finalizes a proposal, determining if it passed or failed
     pub fn finalize_proposal(&mut self, proposal_id: &str) -> IcnResult<ProposalStatus> {
+        let total_members: f64 = self.members.values().map(|m| m.reputation * self.vote_multiplier(&m.id)).sum();
         let proposal = self.proposals.get_mut(proposal_id)
             .ok_or_else(|| IcnError::Dao("Proposal not found".into()))?;
 
@@ -150,7 +276,6 @@ impl Dao {
         }
 
         let total_votes: f64 = proposal.votes.values().map(|v| v.weight).sum();
-        let total_members: f64 = self.members.values().map(|m| m.reputation).sum();
 
         if total_votes / total_members < self.quorum {
             proposal.status = ProposalStatus::Rejected;
@@ -164,6 +289,7 @@ impl Dao {
 
         if votes_in_favor / total_votes > self.majority {
             proposal.status = ProposalStatus::Passed;
+            proposal.passed_at = Some(self.clock.now());
             Ok(ProposalStatus::Passed)
         } else {
             proposal.status = ProposalStatus::Rejected;
@@ -179,6 +305,9 @@ impl Dao {
         if proposal.status != ProposalStatus::Passed {
             return Err(IcnError::Dao("Proposal has not passed".into()));
         }
+        if proposal.contract_call.is_some() {
+            return Err(IcnError::Dao("Proposals with a contract call are executed by the node once the timelock elapses".into()));
+        }
 
         // Here you would implement the logic to execute the proposal
         // For now, we'll just mark it as executed
@@ -193,6 +322,9 @@ pub struct Cooperative {
     pub dao: Dao,
     pub business_type: String,
     pub member_shares: HashMap<String, f64>,
+    /// Profits paid out to members, oldest first.
+    #[serde(default)]
+    pub profit_distributions: Vec<ProfitDistribution>,
 }
 
 impl Cooperative {
@@ -201,6 +333,7 @@ impl Cooperative {
             dao: Dao::new(name, DaoType::Cooperative, quorum, majority),
             business_type,
             member_shares: HashMap::new(),
+            profit_distributions: Vec::new(),
         }
     }
 
@@ -218,18 +351,6 @@ impl Cooperative {
             .cloned()
             .ok_or_else(|| IcnError::Dao("Member has no shares".into()))
     }
-
-    pub fn distribute_profits(&mut self, total_profit: f64) -> IcnResult<()> {
-        let total_shares: f64 = self.member_shares.values().sum();
-        
-        for (member_id, shares) in &self.member_shares {
-            let profit_share = total_profit * (shares / total_shares);
-            // Here you would typically update the member's balance
-            println!("Member {} receives profit share: {}", member_id, profit_share);
-        }
-
-        Ok(())
-    }
 }
 
 /// Represents a Community, which is another specific type of DAO
@@ -267,11 +388,13 @@ impl Community {
         }
     }
 
-    pub fn organize_event(&self, event_name: &str, event_description: &str) -> IcnResult<()> {
-        // Here you would typically integrate with a calendar or event system
-        println!("Community {} is organizing event: {}", self.dao.name, event_name);
-        println!("Event description: {}", event_description);
-        Ok(())
+    /// Schedules an event, held in the community's own location unless the
+    /// details name another.
+    pub fn organize_event(&mut self, organizer: &str, mut details: EventDetails) -> IcnResult<String> {
+        if details.location.trim().is_empty() {
+            details.location = self.location.clone();
+        }
+        self.dao.create_event(organizer, details)
     }
 }
 
@@ -283,7 +406,7 @@ impl DaoFactory {
         match dao_type {
             DaoType::Cooperative => Box::new(Cooperative::new(name, "General".to_string(), quorum, majority)),
             DaoType::Community => Box::new(Community::new(name, "Global".to_string(), Vec::new(), quorum, majority)),
-            DaoType::Custom(custom_type) => {
+            DaoType::Custom(ref custom_type) => {
                 // Here you could implement logic to create custom DAO types
                 println!("Creating custom DAO of type: {}", custom_type);
                 Box::new(Dao::new(name, dao_type, quorum, majority))
@@ -370,6 +493,21 @@ mod tests {
         coop.execute_proposal(&proposal_id).unwrap();
     }
 
+    #[test]
+    fn test_dao_dates_records_with_its_clock() {
+        use icn_common::{Clock, ManualClock};
+        let clock = ManualClock::new(Utc::now() - chrono::Duration::days(30));
+        let mut dao = Dao::new("Test Dao".to_string(), DaoType::Cooperative, 0.5, 0.5).with_clock(clock.shared());
+        dao.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        assert_eq!(dao.members["alice"].joined_at, clock.now());
+
+        clock.advance(chrono::Duration::days(1));
+        let proposal_id = dao.create_proposal("Title".to_string(), "Description".to_string(), "alice".to_string(), chrono::Duration::days(7)).unwrap();
+        let proposal = &dao.proposals[&proposal_id];
+        assert_eq!(proposal.created_at, clock.now());
+        assert_eq!(proposal.expires_at, clock.now() + chrono::Duration::days(7));
+    }
+
     #[test]
     fn test_community_creation_and_operations() {
         let mut community = Community::new(
@@ -409,7 +547,16 @@ mod tests {
 
         community.execute_proposal(&proposal_id).unwrap();
 
-        community.organize_event("Community Cleanup Day", "Let's clean up our neighborhood!").unwrap();
+        let event_id = community.organize_event("alice", EventDetails {
+            name: "Community Cleanup Day".to_string(),
+            description: "Let's clean up our neighborhood!".to_string(),
+            location: String::new(),
+            starts_at: Utc::now() + chrono::Duration::days(7),
+            capacity: None,
+            volunteer_reward: 2.0,
+        }).unwrap();
+        community.dao.rsvp(&event_id, "bob").unwrap();
+        assert_eq!(community.dao.get_event(&event_id).unwrap().details.location, community.location);
     }
 
     #[test]
@@ -436,7 +583,7 @@ mod tests {
             0.5,
             0.6
         );
-        if let DaoType::Custom(custom_type) = custom_dao.get_dao().dao_type {
+        if let DaoType::Custom(custom_type) = &custom_dao.get_dao().dao_type {
             assert_eq!(custom_type, "CustomType");
         } else {
             panic!("Expected custom DAO type");
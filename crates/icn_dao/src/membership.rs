@@ -0,0 +1,215 @@
+// crates/icn_dao/src/membership.rs
+
+use crate::{Cooperative, Dao};
+use chrono::{DateTime, Utc};
+use icn_common::{CurrencyType, IcnResult, IcnError, Transaction, TransactionMemo, TransactionStatus};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// A kind of member, such as worker, consumer or supporter, and the rights
+/// that come with it. Members without a class have full rights.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MembershipClass {
+    pub name: String,
+    /// Scales a member's reputation when their votes are weighed.
+    pub vote_multiplier: f64,
+    pub can_propose: bool,
+    /// Scales a member's shares when profits are distributed.
+    pub profit_share: f64,
+}
+
+impl MembershipClass {
+    pub fn new(name: &str, vote_multiplier: f64, can_propose: bool, profit_share: f64) -> Self {
+        MembershipClass { name: name.to_string(), vote_multiplier, can_propose, profit_share }
+    }
+
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.name.trim().is_empty() {
+            return Err(IcnError::Dao("Membership classes must have a name".into()));
+        }
+        if !self.vote_multiplier.is_finite() || self.vote_multiplier < 0.0
+            || !self.profit_share.is_finite() || self.profit_share < 0.0
+        {
+            return Err(IcnError::Dao("Vote multipliers and profit shares must be non-negative numbers".into()));
+        }
+        Ok(())
+    }
+}
+
+/// One member's part of a profit distribution.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProfitPayout {
+    pub member_id: String,
+    pub amount: f64,
+    /// The transfer from the treasury that paid it.
+    pub tx_hash: String,
+    pub status: TransactionStatus,
+}
+
+/// Profits paid out of a cooperative's treasury in one go.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProfitDistribution {
+    pub dao_id: String,
+    pub steward: String,
+    pub currency_type: CurrencyType,
+    pub total_profit: f64,
+    /// What actually reached members; less than `total_profit` if some
+    /// transfers failed.
+    pub distributed: f64,
+    pub payouts: Vec<ProfitPayout>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Dao {
+    /// Sets up the DAO's membership classes when it is created.
+    pub fn with_membership_classes(mut self, classes: Vec<MembershipClass>) -> IcnResult<Self> {
+        for class in classes {
+            self.set_membership_class(class)?;
+        }
+        Ok(self)
+    }
+
+    /// Adds a class, or changes the rights of an existing one. Members
+    /// already in the class get the new rights.
+    pub fn set_membership_class(&mut self, class: MembershipClass) -> IcnResult<()> {
+        class.validate()?;
+        self.membership_classes.insert(class.name.clone(), class);
+        Ok(())
+    }
+
+    /// Moves a member into a class, or back to full rights with `None`.
+    pub fn assign_membership_class(&mut self, member_id: &str, class: Option<&str>) -> IcnResult<()> {
+        if let Some(class) = class {
+            if !self.membership_classes.contains_key(class) {
+                return Err(IcnError::Dao(format!("Membership class {} not found", class)));
+            }
+        }
+        let member = self.members.get_mut(member_id)
+            .ok_or_else(|| IcnError::Dao("Member not found".into()))?;
+        member.class = class.map(str::to_string);
+        Ok(())
+    }
+
+    pub fn member_class(&self, member_id: &str) -> Option<&MembershipClass> {
+        let class = self.members.get(member_id)?.class.as_ref()?;
+        self.membership_classes.get(class)
+    }
+
+    pub fn vote_multiplier(&self, member_id: &str) -> f64 {
+        self.member_class(member_id).map_or(1.0, |class| class.vote_multiplier)
+    }
+
+    pub fn can_propose(&self, member_id: &str) -> bool {
+        self.members.contains_key(member_id)
+            && self.member_class(member_id).is_none_or(|class| class.can_propose)
+    }
+}
+
+impl Cooperative {
+    /// Splits `total_profit` in proportion to each member's shares scaled
+    /// by their class's profit share.
+    pub fn profit_split(&self, total_profit: f64) -> HashMap<String, f64> {
+        let weights: HashMap<&String, f64> = self.member_shares.iter()
+            .map(|(member_id, shares)| {
+                let profit_share = self.dao.member_class(member_id).map_or(1.0, |class| class.profit_share);
+                (member_id, shares * profit_share)
+            })
+            .collect();
+        let total_weight: f64 = weights.values().sum();
+        if total_weight <= 0.0 {
+            return HashMap::new();
+        }
+        weights.into_iter()
+            .map(|(member_id, weight)| (member_id.clone(), total_profit * weight / total_weight))
+            .collect()
+    }
+
+    /// The transfers from the treasury that pay `total_profit` out by
+    /// `profit_split`, one per member with something to receive, ordered
+    /// by member. Only stewards can distribute profits.
+    pub fn distribute_profits(&self, steward: &str, total_profit: f64, currency_type: &CurrencyType, timestamp: i64) -> IcnResult<Vec<Transaction>> {
+        if !self.dao.stewards.contains(steward) {
+            return Err(IcnError::Dao(format!("{} is not a steward of {}", steward, self.dao.name)));
+        }
+        if !total_profit.is_finite() || total_profit <= 0.0 {
+            return Err(IcnError::Dao("Profits to distribute must be a positive amount".into()));
+        }
+        let mut split: Vec<(String, f64)> = self.profit_split(total_profit).into_iter()
+            .filter(|(_, amount)| *amount > 0.0)
+            .collect();
+        if split.is_empty() {
+            return Err(IcnError::Dao("No member holds shares that earn profits".into()));
+        }
+        split.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let treasury = self.dao.treasury_account();
+        let memo = format!("Profit distribution from {}", self.dao.name);
+        Ok(split.into_iter()
+            .map(|(member_id, amount)| Transaction::new(treasury.clone(), member_id, amount, currency_type.clone(), timestamp)
+                .with_memo(TransactionMemo::Inline(memo.clone())))
+            .collect())
+    }
+
+    pub fn record_profit_distribution(&mut self, distribution: ProfitDistribution) {
+        self.profit_distributions.push(distribution);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DaoTrait, ProposalStatus};
+
+    #[test]
+    fn test_membership_classes_shape_rights() {
+        let mut coop = Cooperative::new("Grocery".to_string(), "Retail".to_string(), 0.5, 0.5);
+        coop.dao = coop.dao.clone().with_membership_classes(vec![
+            MembershipClass::new("worker", 2.0, true, 1.0),
+            MembershipClass::new("supporter", 0.5, false, 0.0),
+        ]).unwrap();
+        assert!(coop.dao.set_membership_class(MembershipClass::new("consumer", -1.0, true, 1.0)).is_err());
+
+        coop.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        coop.add_member("bob".to_string(), "Bob".to_string()).unwrap();
+        coop.dao.assign_membership_class("alice", Some("worker")).unwrap();
+        coop.dao.assign_membership_class("bob", Some("supporter")).unwrap();
+        assert!(coop.dao.assign_membership_class("bob", Some("consumer")).is_err());
+
+        assert!(coop.create_proposal("Expand".to_string(), "Open a second store".to_string(), "bob".to_string(), chrono::Duration::days(7)).is_err());
+        let proposal_id = coop.create_proposal("Expand".to_string(), "Open a second store".to_string(), "alice".to_string(), chrono::Duration::days(7)).unwrap();
+
+        // Alice's vote counts 2.0 against Bob's 0.5
+        coop.vote(&proposal_id, "alice", true).unwrap();
+        coop.vote(&proposal_id, "bob", false).unwrap();
+        assert_eq!(coop.dao.proposals[&proposal_id].votes["alice"].weight, 2.0);
+        assert_eq!(coop.finalize_proposal(&proposal_id).unwrap(), ProposalStatus::Passed);
+
+        coop.issue_shares("alice", 10.0).unwrap();
+        coop.issue_shares("bob", 10.0).unwrap();
+        let split = coop.profit_split(100.0);
+        assert_eq!(split["alice"], 100.0);
+        assert_eq!(split["bob"], 0.0);
+    }
+
+    #[test]
+    fn test_profits_are_paid_from_the_treasury() {
+        let mut coop = Cooperative::new("Bakery".to_string(), "Food".to_string(), 0.5, 0.5);
+        coop.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        coop.add_member("bob".to_string(), "Bob".to_string()).unwrap();
+        coop.add_member("carol".to_string(), "Carol".to_string()).unwrap();
+        coop.dao.stewards.insert("alice".to_string());
+        coop.issue_shares("bob", 30.0).unwrap();
+        coop.issue_shares("alice", 10.0).unwrap();
+
+        assert!(coop.distribute_profits("bob", 100.0, &CurrencyType::BasicNeeds, 1_700_000_000).is_err());
+        assert!(coop.distribute_profits("alice", -5.0, &CurrencyType::BasicNeeds, 1_700_000_000).is_err());
+
+        // Carol holds no shares and gets nothing
+        let transfers = coop.distribute_profits("alice", 100.0, &CurrencyType::BasicNeeds, 1_700_000_000).unwrap();
+        let paid: Vec<(&str, &str, f64)> = transfers.iter()
+            .map(|transfer| (transfer.from.as_str(), transfer.to.as_str(), transfer.amount.to_f64()))
+            .collect();
+        let treasury = coop.dao.treasury_account();
+        assert_eq!(paid, vec![(treasury.as_str(), "alice", 25.0), (treasury.as_str(), "bob", 75.0)]);
+    }
+}
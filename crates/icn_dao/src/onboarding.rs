@@ -0,0 +1,325 @@
+// crates/icn_dao/src/onboarding.rs
+
+use crate::{Dao, DaoEvent, Member, ProposalStatus};
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+
+/// How a DAO admits new members.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OnboardingRules {
+    pub required_attestations: usize,
+    pub required_sponsors: usize,
+    /// Reputation each sponsor puts at stake. It is returned once the
+    /// applicant is admitted or withdraws, and lost if members vote them down.
+    pub sponsor_stake: f64,
+    /// Reputation a sponsor must keep after staking.
+    pub min_sponsor_reputation: f64,
+    /// Admit applicants as soon as they are fully sponsored instead of
+    /// putting them to a vote.
+    pub auto_approve: bool,
+    pub voting_period_days: i64,
+    pub starter_reputation: f64,
+}
+
+impl Default for OnboardingRules {
+    fn default() -> Self {
+        OnboardingRules {
+            required_attestations: 1,
+            required_sponsors: 2,
+            sponsor_stake: 0.1,
+            min_sponsor_reputation: 0.5,
+            auto_approve: false,
+            voting_period_days: 7,
+            starter_reputation: 0.5,
+        }
+    }
+}
+
+/// A claim about an applicant vouched for by someone outside the DAO, such
+/// as a credential issuer or a partner cooperative.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attestation {
+    pub attester: String,
+    pub claim: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Sponsorship {
+    pub member: String,
+    pub stake: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum JoinRequestStatus {
+    /// Waiting for sponsors.
+    Pending,
+    /// Fully sponsored and put to the members as the given proposal.
+    Voting(String),
+    Approved,
+    Rejected,
+    Withdrawn,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinRequest {
+    pub id: String,
+    pub applicant: String,
+    pub name: String,
+    pub attestations: Vec<Attestation>,
+    pub sponsors: Vec<Sponsorship>,
+    pub status: JoinRequestStatus,
+    pub submitted_at: DateTime<Utc>,
+}
+
+impl Dao {
+    pub fn with_onboarding_rules(mut self, rules: OnboardingRules) -> Self {
+        self.onboarding = rules;
+        self
+    }
+
+    /// Files a request to join. Applicants may only have one open request.
+    pub fn submit_join_request(&mut self, applicant: String, name: String, attestations: Vec<Attestation>) -> IcnResult<String> {
+        if self.members.contains_key(&applicant) {
+            return Err(IcnError::Dao("Applicant is already a member".into()));
+        }
+        if self.join_requests.values().any(|r| r.applicant == applicant && r.is_open()) {
+            return Err(IcnError::Dao("Applicant already has an open join request".into()));
+        }
+        if attestations.len() < self.onboarding.required_attestations {
+            return Err(IcnError::Dao(format!(
+                "Join requests need at least {} attestations", self.onboarding.required_attestations
+            )));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        self.join_requests.insert(id.clone(), JoinRequest {
+            id: id.clone(),
+            applicant,
+            name,
+            attestations,
+            sponsors: Vec::new(),
+            status: JoinRequestStatus::Pending,
+            submitted_at: self.clock.now(),
+        });
+        Ok(id)
+    }
+
+    /// Stakes a member's reputation behind an applicant. Once enough members
+    /// sponsor the request it is either approved outright or put to a vote,
+    /// depending on the onboarding rules. Returns the resulting status.
+    pub fn sponsor_join_request(&mut self, request_id: &str, sponsor: &str) -> IcnResult<JoinRequestStatus> {
+        let rules = self.onboarding.clone();
+        let request = self.join_requests.get(request_id)
+            .ok_or_else(|| IcnError::Dao("Join request not found".into()))?;
+        if request.status != JoinRequestStatus::Pending {
+            return Err(IcnError::Dao("Join request is not awaiting sponsors".into()));
+        }
+        if request.sponsors.iter().any(|s| s.member == sponsor) {
+            return Err(IcnError::Dao("Member already sponsors this request".into()));
+        }
+
+        let member = self.members.get_mut(sponsor)
+            .ok_or_else(|| IcnError::Dao("Sponsor is not a member of the DAO".into()))?;
+        if member.reputation - rules.sponsor_stake < rules.min_sponsor_reputation {
+            return Err(IcnError::Dao("Sponsor does not have enough reputation to stake".into()));
+        }
+        member.reputation -= rules.sponsor_stake;
+
+        let request = self.join_requests.get_mut(request_id).expect("request checked above");
+        request.sponsors.push(Sponsorship { member: sponsor.to_string(), stake: rules.sponsor_stake });
+        if request.sponsors.len() < rules.required_sponsors {
+            return Ok(JoinRequestStatus::Pending);
+        }
+
+        if rules.auto_approve {
+            self.admit_applicant(request_id)?;
+        } else {
+            let (title, description) = (
+                format!("Admit {}", request.name),
+                format!("Join request {} from {}, sponsored by {} members", request.id, request.applicant, request.sponsors.len()),
+            );
+            let proposer = request.sponsors[0].member.clone();
+            let proposal_id = self.create_proposal(title, description, proposer, Duration::days(rules.voting_period_days))?;
+            self.join_requests.get_mut(request_id).expect("request checked above").status = JoinRequestStatus::Voting(proposal_id);
+        }
+        Ok(self.join_requests[request_id].status.clone())
+    }
+
+    /// Settles a request that was put to a vote once its proposal has been
+    /// finalized. Sponsors of a rejected applicant lose their stake.
+    pub fn resolve_join_request(&mut self, request_id: &str) -> IcnResult<JoinRequestStatus> {
+        let request = self.join_requests.get(request_id)
+            .ok_or_else(|| IcnError::Dao("Join request not found".into()))?;
+        let JoinRequestStatus::Voting(proposal_id) = &request.status else {
+            return Err(IcnError::Dao("Join request is not being voted on".into()));
+        };
+        let proposal_status = self.proposals.get(proposal_id)
+            .ok_or_else(|| IcnError::Dao("Proposal not found".into()))?
+            .status.clone();
+
+        match proposal_status {
+            ProposalStatus::Active => Err(IcnError::Dao("Vote on the join request is still open".into())),
+            ProposalStatus::Passed | ProposalStatus::Executed => {
+                self.admit_applicant(request_id)?;
+                Ok(JoinRequestStatus::Approved)
+            }
+            ProposalStatus::Rejected => {
+                let request = self.join_requests.get_mut(request_id).expect("request checked above");
+                request.status = JoinRequestStatus::Rejected;
+                Ok(JoinRequestStatus::Rejected)
+            }
+        }
+    }
+
+    /// Lets an applicant withdraw before being admitted; sponsors get their
+    /// stake back.
+    pub fn withdraw_join_request(&mut self, request_id: &str, applicant: &str) -> IcnResult<()> {
+        let request = self.join_requests.get(request_id)
+            .ok_or_else(|| IcnError::Dao("Join request not found".into()))?;
+        if request.applicant != applicant {
+            return Err(IcnError::Dao("Only the applicant can withdraw a join request".into()));
+        }
+        if !request.is_open() {
+            return Err(IcnError::Dao("Join request is already closed".into()));
+        }
+
+        self.return_stakes(request_id);
+        self.join_requests.get_mut(request_id).expect("request checked above").status = JoinRequestStatus::Withdrawn;
+        Ok(())
+    }
+
+    pub fn get_join_request(&self, request_id: &str) -> IcnResult<&JoinRequest> {
+        self.join_requests.get(request_id)
+            .ok_or_else(|| IcnError::Dao("Join request not found".into()))
+    }
+
+    pub fn list_open_join_requests(&self) -> Vec<&JoinRequest> {
+        let mut requests: Vec<&JoinRequest> = self.join_requests.values().filter(|r| r.is_open()).collect();
+        requests.sort_by_key(|r| r.submitted_at);
+        requests
+    }
+
+    fn admit_applicant(&mut self, request_id: &str) -> IcnResult<()> {
+        let request = self.join_requests.get(request_id)
+            .ok_or_else(|| IcnError::Dao("Join request not found".into()))?;
+        // Checked before any refund, so a failed admission leaves the
+        // stakes for a later withdrawal to return exactly once
+        if self.members.contains_key(&request.applicant) {
+            return Err(IcnError::Dao("Applicant is already a member".into()));
+        }
+        self.return_stakes(request_id);
+        let request = self.join_requests.get_mut(request_id).expect("request checked above");
+        request.status = JoinRequestStatus::Approved;
+
+        let joined_at = self.clock.now();
+        self.members.insert(request.applicant.clone(), Member {
+            id: request.applicant.clone(),
+            name: request.name.clone(),
+            joined_at,
+            reputation: self.onboarding.starter_reputation,
+            class: None,
+        });
+        self.events.push(DaoEvent::MemberWelcomed {
+            member_id: request.applicant.clone(),
+            sponsors: request.sponsors.iter().map(|s| s.member.clone()).collect(),
+            timestamp: joined_at,
+        });
+        Ok(())
+    }
+
+    fn return_stakes(&mut self, request_id: &str) {
+        let Some(request) = self.join_requests.get(request_id) else {
+            return;
+        };
+        for sponsorship in &request.sponsors {
+            if let Some(member) = self.members.get_mut(&sponsorship.member) {
+                member.reputation += sponsorship.stake;
+            }
+        }
+    }
+}
+
+impl JoinRequest {
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, JoinRequestStatus::Pending | JoinRequestStatus::Voting(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DaoType;
+
+    fn dao_with_members(rules: OnboardingRules) -> Dao {
+        let mut dao = Dao::new("Test DAO".to_string(), DaoType::Community, 0.5, 0.5).with_onboarding_rules(rules);
+        dao.add_member("alice".to_string(), "Alice".to_string()).unwrap();
+        dao.add_member("bob".to_string(), "Bob".to_string()).unwrap();
+        dao
+    }
+
+    fn attestation() -> Vec<Attestation> {
+        vec![Attestation { attester: "did:icn:partner".to_string(), claim: "worker".to_string(), issued_at: Utc::now() }]
+    }
+
+    #[test]
+    fn test_auto_approved_onboarding() {
+        let mut dao = dao_with_members(OnboardingRules { auto_approve: true, ..Default::default() });
+        assert!(dao.submit_join_request("carol".to_string(), "Carol".to_string(), Vec::new()).is_err());
+        let request_id = dao.submit_join_request("carol".to_string(), "Carol".to_string(), attestation()).unwrap();
+
+        assert_eq!(dao.sponsor_join_request(&request_id, "alice").unwrap(), JoinRequestStatus::Pending);
+        assert!((dao.members["alice"].reputation - 0.9).abs() < 1e-9);
+        assert!(dao.sponsor_join_request(&request_id, "alice").is_err());
+        assert!(dao.sponsor_join_request(&request_id, "carol").is_err());
+
+        assert_eq!(dao.sponsor_join_request(&request_id, "bob").unwrap(), JoinRequestStatus::Approved);
+        assert_eq!(dao.members["carol"].reputation, 0.5);
+        assert_eq!(dao.members["alice"].reputation, 1.0);
+        assert!(matches!(&dao.events[0], DaoEvent::MemberWelcomed { member_id, sponsors, .. } if member_id == "carol" && sponsors.len() == 2));
+    }
+
+    #[test]
+    fn test_voted_down_applicant_costs_sponsors() {
+        let mut dao = dao_with_members(OnboardingRules::default());
+        let request_id = dao.submit_join_request("carol".to_string(), "Carol".to_string(), attestation()).unwrap();
+        dao.sponsor_join_request(&request_id, "alice").unwrap();
+        let JoinRequestStatus::Voting(proposal_id) = dao.sponsor_join_request(&request_id, "bob").unwrap() else {
+            panic!("expected the request to go to a vote");
+        };
+        assert!(dao.resolve_join_request(&request_id).is_err());
+
+        dao.vote(&proposal_id, "alice", false).unwrap();
+        dao.vote(&proposal_id, "bob", false).unwrap();
+        dao.finalize_proposal(&proposal_id).unwrap();
+
+        assert_eq!(dao.resolve_join_request(&request_id).unwrap(), JoinRequestStatus::Rejected);
+        assert!(!dao.members.contains_key("carol"));
+        assert!((dao.members["alice"].reputation - 0.9).abs() < 1e-9);
+        assert!(dao.list_open_join_requests().is_empty());
+    }
+
+    #[test]
+    fn test_failed_admission_refunds_stakes_once() {
+        let mut dao = dao_with_members(OnboardingRules::default());
+        let request_id = dao.submit_join_request("carol".to_string(), "Carol".to_string(), attestation()).unwrap();
+        dao.sponsor_join_request(&request_id, "alice").unwrap();
+        let JoinRequestStatus::Voting(proposal_id) = dao.sponsor_join_request(&request_id, "bob").unwrap() else {
+            panic!("expected the request to go to a vote");
+        };
+        dao.vote(&proposal_id, "alice", true).unwrap();
+        dao.vote(&proposal_id, "bob", true).unwrap();
+        dao.finalize_proposal(&proposal_id).unwrap();
+
+        // Carol joined some other way while the vote was open
+        dao.add_member("carol".to_string(), "Carol".to_string()).unwrap();
+        assert!(dao.resolve_join_request(&request_id).is_err());
+        assert!((dao.members["alice"].reputation - 0.9).abs() < 1e-9);
+
+        dao.withdraw_join_request(&request_id, "carol").unwrap();
+        assert!((dao.members["alice"].reputation - 1.0).abs() < 1e-9);
+        assert!(dao.withdraw_join_request(&request_id, "carol").is_err());
+    }
+}
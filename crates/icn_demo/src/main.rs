@@ -1,7 +1,7 @@
 // File: icn_demo/src/main.rs
 
 use icn_core::{IcnNode, Config};
-use icn_common::{Transaction, Proposal, ProposalType, ProposalCategory, CurrencyType, ProposalStatus, IcnResult, IcnError};
+use icn_common::{Transaction, SignatureScheme, Proposal, ProposalType, ProposalCategory, CurrencyType, ProposalStatus, IcnResult, IcnError};
 use std::io::{self, Write};
 use chrono::{Duration, Utc};
 use log::{info, warn, error};
@@ -81,6 +81,10 @@ async fn process_transaction(node: &IcnNode) -> IcnResult<()> {
         currency_type,
         timestamp: Utc::now().timestamp(),
         signature: None,
+        memo: None,
+        signature_scheme: SignatureScheme::Ed25519,
+        valid_until: None,
+        capability: None,
     };
 
     node.process_transaction(transaction).await?;
@@ -109,9 +113,18 @@ async fn create_proposal(node: &IcnNode) -> IcnResult<()> {
         category,
         required_quorum: 0.66,
         execution_timestamp: None,
+        weight_mode: Default::default(),
+        actions: Vec::new(),
+        scope: Default::default(),
+        ballot: Default::default(),
+        depends_on: Vec::new(),
+        bundle: None,
     };
 
-    let proposal_id = node.create_proposal(proposal).await?;
+    println!("Sign these bytes with the proposer's key: {}", hex::encode(proposal.signing_bytes()));
+    let signature = hex::decode(get_input("Signature (hex): ")?)
+        .map_err(|e| IcnError::Identity(format!("Invalid signature: {}", e)))?;
+    let proposal_id = node.create_proposal(proposal, &signature).await?;
     println!("Proposal created successfully. ID: {}", proposal_id);
     Ok(())
 }
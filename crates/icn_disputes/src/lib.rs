@@ -0,0 +1,435 @@
+// File: crates/icn_disputes/src/lib.rs
+
+use icn_common::{IcnResult, IcnError, CurrencyType};
+use icn_identity::{SelectionAudit, VrfKey, VrfSelection, JURY_SELECTION};
+use icn_storage::StorageManager;
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// What a dispute is about.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DisputeSubject {
+    /// A processed transaction, by hash.
+    Transaction(String),
+    /// An escrow agreement, by id.
+    Escrow(String),
+}
+
+/// The side a juror or the jury as a whole finds for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Verdict {
+    Claimant,
+    Respondent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum DisputeStatus {
+    /// Accepting evidence, no jury yet.
+    Open,
+    /// A jury has been selected and is voting.
+    Deliberating,
+    Resolved(Verdict),
+}
+
+/// The amount the claimant asks to have refunded.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Claim {
+    pub amount: f64,
+    pub currency_type: CurrencyType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Evidence {
+    pub submitted_by: String,
+    /// Hash of the evidence blob in content-addressed storage.
+    pub content_hash: String,
+    pub submitted_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Dispute {
+    pub id: String,
+    pub claimant: String,
+    pub respondent: String,
+    pub subject: DisputeSubject,
+    pub description: String,
+    pub claim: Option<Claim>,
+    pub evidence: Vec<Evidence>,
+    pub jury: Vec<String>,
+    /// How the jury was drawn, so anyone can check the draw.
+    #[serde(default)]
+    pub jury_selection: Option<VrfSelection>,
+    pub votes: HashMap<String, Verdict>,
+    pub status: DisputeStatus,
+    pub opened_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisputeConfig {
+    pub jury_size: usize,
+    /// Minimum reputation a member needs to be drawn as a juror.
+    pub min_juror_reputation: f64,
+    /// Reputation taken from the losing party.
+    pub losing_party_penalty: f64,
+}
+
+impl Default for DisputeConfig {
+    fn default() -> Self {
+        DisputeConfig {
+            jury_size: 5,
+            min_juror_reputation: 10.0,
+            losing_party_penalty: 1.0,
+        }
+    }
+}
+
+/// A refund the node should apply after a verdict for the claimant.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Refund {
+    pub from: String,
+    pub to: String,
+    pub amount: f64,
+    pub currency_type: CurrencyType,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReputationPenalty {
+    pub member: String,
+    pub amount: f64,
+}
+
+/// The consequences of a verdict, to be applied by the caller.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Resolution {
+    pub dispute_id: String,
+    pub verdict: Verdict,
+    pub refund: Option<Refund>,
+    pub penalties: Vec<ReputationPenalty>,
+}
+
+pub struct DisputeSystem {
+    disputes: HashMap<String, Dispute>,
+    config: DisputeConfig,
+}
+
+impl DisputeSystem {
+    pub fn new(config: DisputeConfig) -> Self {
+        DisputeSystem {
+            disputes: HashMap::new(),
+            config,
+        }
+    }
+
+    pub fn open_dispute(&mut self, claimant: String, respondent: String, subject: DisputeSubject, description: String, claim: Option<Claim>) -> IcnResult<String> {
+        if claimant == respondent {
+            return Err(IcnError::Dispute("A member cannot open a dispute against themselves".into()));
+        }
+        if let Some(claim) = &claim {
+            if claim.amount <= 0.0 {
+                return Err(IcnError::Dispute("Claimed amount must be positive".into()));
+            }
+        }
+
+        let id = Uuid::new_v4().to_string();
+        self.disputes.insert(id.clone(), Dispute {
+            id: id.clone(),
+            claimant,
+            respondent,
+            subject,
+            description,
+            claim,
+            evidence: Vec::new(),
+            jury: Vec::new(),
+            jury_selection: None,
+            votes: HashMap::new(),
+            status: DisputeStatus::Open,
+            opened_at: Utc::now(),
+            resolved_at: None,
+        });
+        Ok(id)
+    }
+
+    pub fn get_dispute(&self, dispute_id: &str) -> IcnResult<&Dispute> {
+        self.disputes.get(dispute_id)
+            .ok_or_else(|| IcnError::Dispute("Dispute not found".into()))
+    }
+
+    /// Lists disputes the member is a party to or sits on the jury of.
+    pub fn list_disputes_for(&self, member: &str) -> Vec<&Dispute> {
+        self.disputes.values()
+            .filter(|d| d.claimant == member || d.respondent == member || d.jury.iter().any(|j| j == member))
+            .collect()
+    }
+
+    /// Stores an evidence blob and attaches its content hash to the dispute.
+    /// Only the parties may submit evidence, and only before a jury is drawn.
+    pub fn submit_evidence(&mut self, dispute_id: &str, submitter: &str, content: Vec<u8>, storage: &StorageManager) -> IcnResult<String> {
+        let dispute = self.get_dispute_mut(dispute_id)?;
+        if dispute.status != DisputeStatus::Open {
+            return Err(IcnError::Dispute("Evidence can only be submitted while the dispute is open".into()));
+        }
+        if submitter != dispute.claimant && submitter != dispute.respondent {
+            return Err(IcnError::Dispute("Only the parties to a dispute can submit evidence".into()));
+        }
+
+        let content_hash = storage.store_content(content)?;
+        dispute.evidence.push(Evidence {
+            submitted_by: submitter.to_string(),
+            content_hash: content_hash.clone(),
+            submitted_at: Utc::now(),
+        });
+        Ok(content_hash)
+    }
+
+    /// Draws the jury from candidates meeting the reputation threshold,
+    /// excluding the parties themselves, with chances in proportion to
+    /// reputation. The draw is seeded by the VRF output of `key` over
+    /// `block_hash` and the dispute id, so it can't be predicted before that
+    /// block exists and can be checked afterwards with `audit_jury`.
+    pub fn select_jury(&mut self, dispute_id: &str, candidates: &[(String, f64)], block_hash: &str, key: &VrfKey) -> IcnResult<Vec<String>> {
+        let jury_size = self.config.jury_size;
+        let min_reputation = self.config.min_juror_reputation;
+        let dispute = self.get_dispute_mut(dispute_id)?;
+        if dispute.status != DisputeStatus::Open {
+            return Err(IcnError::Dispute("Jury has already been selected".into()));
+        }
+
+        let mut eligible: Vec<(String, f64)> = candidates.iter()
+            .filter(|(member, reputation)| {
+                *reputation >= min_reputation && member != &dispute.claimant && member != &dispute.respondent
+            })
+            .cloned()
+            .collect();
+        if eligible.len() < jury_size {
+            return Err(IcnError::Dispute(format!(
+                "Not enough eligible jurors: {} available, {} required", eligible.len(), jury_size
+            )));
+        }
+        eligible.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let selection = VrfSelection::draw(key, JURY_SELECTION, dispute_id, block_hash, eligible, jury_size);
+        dispute.jury = selection.selected.clone();
+        dispute.jury_selection = Some(selection);
+        dispute.status = DisputeStatus::Deliberating;
+        Ok(dispute.jury.clone())
+    }
+
+    /// Checks the VRF proof of a dispute's jury draw and redoes it.
+    pub fn audit_jury(&self, dispute_id: &str) -> IcnResult<SelectionAudit> {
+        let dispute = self.get_dispute(dispute_id)?;
+        let selection = dispute.jury_selection.as_ref()
+            .ok_or_else(|| IcnError::Dispute(format!("No jury has been drawn for dispute {}", dispute_id)))?;
+        let mut audit = selection.audit();
+        if selection.selected != dispute.jury {
+            audit.problems.push("The sitting jury is not the one drawn".to_string());
+        }
+        Ok(audit)
+    }
+
+    pub fn cast_vote(&mut self, dispute_id: &str, juror: &str, verdict: Verdict) -> IcnResult<()> {
+        let dispute = self.get_dispute_mut(dispute_id)?;
+        if dispute.status != DisputeStatus::Deliberating {
+            return Err(IcnError::Dispute("Dispute is not in deliberation".into()));
+        }
+        if !dispute.jury.iter().any(|j| j == juror) {
+            return Err(IcnError::Dispute("Only selected jurors can vote".into()));
+        }
+        if dispute.votes.contains_key(juror) {
+            return Err(IcnError::Dispute("Juror has already voted".into()));
+        }
+        dispute.votes.insert(juror.to_string(), verdict);
+        Ok(())
+    }
+
+    /// Works out the verdict and its consequences without changing the dispute.
+    ///
+    /// A side wins once it holds a majority of the jury. If every juror has
+    /// voted and there is still no majority, the claimant has not made
+    /// their case and the respondent wins.
+    pub fn tally(&self, dispute_id: &str) -> IcnResult<Resolution> {
+        let dispute = self.get_dispute(dispute_id)?;
+        if dispute.status != DisputeStatus::Deliberating {
+            return Err(IcnError::Dispute("Dispute is not in deliberation".into()));
+        }
+
+        let majority = dispute.jury.len() / 2 + 1;
+        let for_claimant = dispute.votes.values().filter(|v| **v == Verdict::Claimant).count();
+        let for_respondent = dispute.votes.len() - for_claimant;
+
+        let verdict = if for_claimant >= majority {
+            Verdict::Claimant
+        } else if for_respondent >= majority || dispute.votes.len() == dispute.jury.len() {
+            Verdict::Respondent
+        } else {
+            return Err(IcnError::Dispute("The jury has not reached a majority yet".into()));
+        };
+
+        let (loser, refund) = match verdict {
+            Verdict::Claimant => (
+                dispute.respondent.clone(),
+                dispute.claim.as_ref().map(|claim| Refund {
+                    from: dispute.respondent.clone(),
+                    to: dispute.claimant.clone(),
+                    amount: claim.amount,
+                    currency_type: claim.currency_type.clone(),
+                }),
+            ),
+            Verdict::Respondent => (dispute.claimant.clone(), None),
+        };
+
+        Ok(Resolution {
+            dispute_id: dispute_id.to_string(),
+            verdict,
+            refund,
+            penalties: vec![ReputationPenalty { member: loser, amount: self.config.losing_party_penalty }],
+        })
+    }
+
+    /// Closes the dispute with the verdict from `tally`, once its
+    /// consequences have been applied.
+    pub fn mark_resolved(&mut self, dispute_id: &str, verdict: Verdict) -> IcnResult<()> {
+        let dispute = self.get_dispute_mut(dispute_id)?;
+        if dispute.status != DisputeStatus::Deliberating {
+            return Err(IcnError::Dispute("Dispute is not in deliberation".into()));
+        }
+        dispute.status = DisputeStatus::Resolved(verdict);
+        dispute.resolved_at = Some(Utc::now());
+        Ok(())
+    }
+
+    fn get_dispute_mut(&mut self, dispute_id: &str) -> IcnResult<&mut Dispute> {
+        self.disputes.get_mut(dispute_id)
+            .ok_or_else(|| IcnError::Dispute("Dispute not found".into()))
+    }
+}
+
+impl Default for DisputeSystem {
+    fn default() -> Self {
+        DisputeSystem::new(DisputeConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_system() -> DisputeSystem {
+        DisputeSystem::new(DisputeConfig {
+            jury_size: 3,
+            min_juror_reputation: 10.0,
+            losing_party_penalty: 2.0,
+        })
+    }
+
+    fn candidates() -> Vec<(String, f64)> {
+        vec![
+            ("Alice".to_string(), 50.0),
+            ("Carol".to_string(), 20.0),
+            ("Dave".to_string(), 15.0),
+            ("Erin".to_string(), 12.0),
+            ("Frank".to_string(), 3.0),
+        ]
+    }
+
+    fn open_test_dispute(system: &mut DisputeSystem) -> String {
+        system.open_dispute(
+            "Alice".to_string(),
+            "Bob".to_string(),
+            DisputeSubject::Transaction("tx1".to_string()),
+            "Goods never delivered".to_string(),
+            Some(Claim { amount: 40.0, currency_type: CurrencyType::BasicNeeds }),
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_evidence_and_jury_selection() {
+        let mut system = create_test_system();
+        let storage = StorageManager::new(1);
+        storage.add_node("node1".to_string()).unwrap();
+        let dispute_id = open_test_dispute(&mut system);
+
+        let hash = system.submit_evidence(&dispute_id, "Alice", b"invoice #42".to_vec(), &storage).unwrap();
+        assert_eq!(storage.retrieve_content(&hash).unwrap(), b"invoice #42".to_vec());
+        assert!(system.submit_evidence(&dispute_id, "Mallory", b"noise".to_vec(), &storage).is_err());
+
+        let key = VrfKey::generate();
+        let jury = system.select_jury(&dispute_id, &candidates(), "blockhash", &key).unwrap();
+        assert_eq!(jury.len(), 3);
+        // Parties and low-reputation members are never drawn
+        assert!(!jury.contains(&"Alice".to_string()));
+        assert!(!jury.contains(&"Frank".to_string()));
+
+        assert!(system.submit_evidence(&dispute_id, "Bob", b"late".to_vec(), &storage).is_err());
+        assert!(system.select_jury(&dispute_id, &candidates(), "blockhash", &key).is_err());
+
+        let audit = system.audit_jury(&dispute_id).unwrap();
+        assert!(audit.is_valid());
+        assert_eq!(audit.recomputed, jury);
+        assert_eq!(audit.selection.block_hash, "blockhash");
+    }
+
+    #[test]
+    fn test_jury_draw_is_verifiable() {
+        let mut system = DisputeSystem::new(DisputeConfig { jury_size: 2, ..create_test_system().config });
+        let key = VrfKey::generate();
+        let first = open_test_dispute(&mut system);
+        let second = open_test_dispute(&mut system);
+        system.select_jury(&first, &candidates(), "blockhash", &key).unwrap();
+        system.select_jury(&second, &candidates(), "blockhash", &key).unwrap();
+
+        // Each dispute gets its own VRF input from the same block
+        let first_vrf = system.audit_jury(&first).unwrap().selection.vrf;
+        let second_vrf = system.audit_jury(&second).unwrap().selection.vrf;
+        assert_ne!(first_vrf.output, second_vrf.output);
+
+        // Swapping a juror after the draw shows up in the audit
+        system.disputes.get_mut(&first).unwrap().jury[0] = "Mallory".to_string();
+        assert!(!system.audit_jury(&first).unwrap().is_valid());
+        assert!(system.audit_jury("missing").is_err());
+    }
+
+    #[test]
+    fn test_verdict_for_claimant() {
+        let mut system = create_test_system();
+        let dispute_id = open_test_dispute(&mut system);
+        let jury = system.select_jury(&dispute_id, &candidates(), "blockhash", &VrfKey::generate()).unwrap();
+
+        assert!(system.cast_vote(&dispute_id, "Frank", Verdict::Claimant).is_err());
+        system.cast_vote(&dispute_id, &jury[0], Verdict::Claimant).unwrap();
+        assert!(system.cast_vote(&dispute_id, &jury[0], Verdict::Respondent).is_err());
+        assert!(system.tally(&dispute_id).is_err());
+
+        system.cast_vote(&dispute_id, &jury[1], Verdict::Claimant).unwrap();
+        let resolution = system.tally(&dispute_id).unwrap();
+        assert_eq!(resolution.verdict, Verdict::Claimant);
+        assert_eq!(resolution.refund, Some(Refund {
+            from: "Bob".to_string(),
+            to: "Alice".to_string(),
+            amount: 40.0,
+            currency_type: CurrencyType::BasicNeeds,
+        }));
+        assert_eq!(resolution.penalties, vec![ReputationPenalty { member: "Bob".to_string(), amount: 2.0 }]);
+
+        system.mark_resolved(&dispute_id, resolution.verdict).unwrap();
+        assert_eq!(system.get_dispute(&dispute_id).unwrap().status, DisputeStatus::Resolved(Verdict::Claimant));
+        assert!(system.cast_vote(&dispute_id, "Erin", Verdict::Respondent).is_err());
+    }
+
+    #[test]
+    fn test_split_jury_favors_respondent() {
+        let mut system = DisputeSystem::new(DisputeConfig { jury_size: 2, ..create_test_system().config });
+        let dispute_id = open_test_dispute(&mut system);
+        let jury = system.select_jury(&dispute_id, &candidates(), "blockhash", &VrfKey::generate()).unwrap();
+
+        system.cast_vote(&dispute_id, &jury[0], Verdict::Claimant).unwrap();
+        system.cast_vote(&dispute_id, &jury[1], Verdict::Respondent).unwrap();
+
+        let resolution = system.tally(&dispute_id).unwrap();
+        assert_eq!(resolution.verdict, Verdict::Respondent);
+        assert_eq!(resolution.refund, None);
+        assert_eq!(resolution.penalties[0].member, "Alice");
+    }
+}
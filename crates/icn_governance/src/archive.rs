@@ -0,0 +1,148 @@
+// File: crates/icn_governance/src/archive.rs
+
+use crate::{Ballot, GovernanceSystem, Proposal, ProposalStatus, Vote, VoterSnapshot};
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{GovernancePolicy, IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+
+/// Everything governance keeps about a proposal, as written to storage when
+/// the proposal is archived.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedProposal {
+    pub proposal: Proposal,
+    pub votes: Vec<Vote>,
+    pub policy: GovernancePolicy,
+    #[serde(default)]
+    pub ballots: Vec<Ballot>,
+    #[serde(default)]
+    pub snapshot: Option<VoterSnapshot>,
+}
+
+impl ArchivedProposal {
+    pub fn to_bytes(&self) -> IcnResult<Vec<u8>> {
+        serde_json::to_vec(self)
+            .map_err(|e| IcnError::Governance(format!("Failed to serialize archived proposal: {}", e)))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IcnResult<Self> {
+        serde_json::from_slice(bytes)
+            .map_err(|e| IcnError::Governance(format!("Failed to deserialize archived proposal: {}", e)))
+    }
+}
+
+/// Index entry left in memory for an archived proposal.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ArchiveEntry {
+    pub proposal_id: String,
+    pub title: String,
+    pub status: ProposalStatus,
+    pub storage_key: String,
+    pub archived_at: DateTime<Utc>,
+}
+
+/// Storage key under which an archived proposal is kept.
+pub fn archive_storage_key(proposal_id: &str) -> String {
+    format!("governance:archive:{}", proposal_id)
+}
+
+/// Storage key of the archive index, so archived proposals can still be
+/// found after a restart.
+pub const ARCHIVE_INDEX_STORAGE_KEY: &str = "governance:archive-index";
+
+impl GovernanceSystem {
+    /// Returns the finalized proposals that were decided more than
+    /// `retention` before `now`. Only rejected and executed proposals are
+    /// archived; passed proposals may still be executed.
+    pub fn archivable_proposals(&self, retention: Duration, now: DateTime<Utc>) -> Vec<ArchivedProposal> {
+        let cutoff = now - retention;
+        self.proposals.values()
+            .filter(|p| matches!(p.status, ProposalStatus::Rejected | ProposalStatus::Executed | ProposalStatus::Vetoed))
+            .filter(|p| p.execution_timestamp.unwrap_or(p.voting_ends_at) < cutoff)
+            .map(|p| ArchivedProposal {
+                proposal: p.clone(),
+                votes: self.votes.get(&p.id).cloned().unwrap_or_default(),
+                policy: self.proposal_policies.get(&p.id).cloned().unwrap_or_else(|| self.default_policy.clone()),
+                ballots: self.ballots.get(&p.id).cloned().unwrap_or_default(),
+                snapshot: self.snapshots.get(&p.id).cloned(),
+            })
+            .collect()
+    }
+
+    /// Drops a proposal from memory once its archive has been written to
+    /// `storage_key`, keeping an index entry in its place.
+    pub fn mark_archived(&mut self, proposal_id: &str, storage_key: String, now: DateTime<Utc>) -> IcnResult<ArchiveEntry> {
+        let proposal = self.proposals.remove(proposal_id)
+            .ok_or_else(|| IcnError::Governance("Proposal not found".into()))?;
+        self.votes.remove(proposal_id);
+        self.ballots.remove(proposal_id);
+        self.proposal_policies.remove(proposal_id);
+        self.snapshots.remove(proposal_id);
+        self.timelocks.remove(proposal_id);
+
+        let entry = ArchiveEntry {
+            proposal_id: proposal.id,
+            title: proposal.title,
+            status: proposal.status,
+            storage_key,
+            archived_at: now,
+        };
+        self.archive_index.insert(proposal_id.to_string(), entry.clone());
+        Ok(entry)
+    }
+
+    pub fn get_archive_entry(&self, proposal_id: &str) -> Option<&ArchiveEntry> {
+        self.archive_index.get(proposal_id)
+    }
+
+    /// Lists archived proposals, most recently archived first.
+    pub fn list_archived(&self) -> Vec<&ArchiveEntry> {
+        let mut entries: Vec<&ArchiveEntry> = self.archive_index.values().collect();
+        entries.sort_by(|a, b| b.archived_at.cmp(&a.archived_at).then_with(|| a.proposal_id.cmp(&b.proposal_id)));
+        entries
+    }
+
+    /// Brings an archived proposal back into memory with its votes and policy.
+    pub fn restore_archived(&mut self, archived: ArchivedProposal) -> IcnResult<()> {
+        let proposal_id = archived.proposal.id.clone();
+        if self.archive_index.remove(&proposal_id).is_none() {
+            return Err(IcnError::Governance(format!("Proposal {} is not archived", proposal_id)));
+        }
+        self.votes.insert(proposal_id.clone(), archived.votes);
+        if !archived.ballots.is_empty() {
+            self.ballots.insert(proposal_id.clone(), archived.ballots);
+        }
+        self.proposal_policies.insert(proposal_id.clone(), archived.policy);
+        if let Some(snapshot) = archived.snapshot {
+            self.snapshots.insert(proposal_id.clone(), snapshot);
+        }
+        self.proposals.insert(proposal_id, archived.proposal);
+        Ok(())
+    }
+
+    /// The archive index as written to `ARCHIVE_INDEX_STORAGE_KEY`.
+    pub fn archive_index_bytes(&self) -> IcnResult<Vec<u8>> {
+        serde_json::to_vec(&self.list_archived())
+            .map_err(|e| IcnError::Governance(format!("Failed to serialize archive index: {}", e)))
+    }
+
+    /// Loads an index written by `archive_index_bytes`, skipping proposals
+    /// that are back in memory. Returns how many entries were loaded.
+    pub fn restore_archive_index(&mut self, bytes: &[u8]) -> IcnResult<usize> {
+        let entries: Vec<ArchiveEntry> = serde_json::from_slice(bytes)
+            .map_err(|e| IcnError::Governance(format!("Failed to deserialize archive index: {}", e)))?;
+        let mut loaded = 0;
+        for entry in entries {
+            if !self.proposals.contains_key(&entry.proposal_id) {
+                self.archive_index.insert(entry.proposal_id.clone(), entry);
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Forgets an archived proposal. The caller removes the stored copy.
+    pub fn purge_archived(&mut self, proposal_id: &str) -> IcnResult<ArchiveEntry> {
+        self.archive_index.remove(proposal_id)
+            .ok_or_else(|| IcnError::Governance(format!("Proposal {} is not archived", proposal_id)))
+    }
+}
@@ -0,0 +1,365 @@
+// File: crates/icn_governance/src/ballots.rs
+
+use crate::{GovernanceSystem, Proposal, ProposalStatus};
+use chrono::{DateTime, Utc};
+use icn_common::{BallotKind, IcnError, IcnResult};
+use icn_zkp::{WeightOpening, WeightRangeProof};
+use log::warn;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A vote on a multi-option proposal. For ranked-choice proposals `choices`
+/// runs from most to least preferred; for approval proposals it lists the
+/// approved options in any order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ballot {
+    pub voter: String,
+    pub proposal_id: String,
+    pub choices: Vec<String>,
+    pub weight: f64,
+    pub timestamp: DateTime<Utc>,
+    pub eligibility_proof: WeightRangeProof,
+    #[serde(default)]
+    pub weight_opening: WeightOpening,
+}
+
+impl Ballot {
+    pub fn is_eligibility_verified(&self) -> bool {
+        self.eligibility_proof.opens(self.weight, &self.weight_opening)
+            && self.eligibility_proof.verify(&self.proposal_id, &self.voter).is_ok()
+    }
+}
+
+/// Weight behind each option still standing in one round of a tally.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TallyRound {
+    pub counts: BTreeMap<String, f64>,
+    /// Weight of ballots that ranked none of the remaining options.
+    pub exhausted: f64,
+    /// Option dropped at the end of the round, if no option had a majority.
+    pub eliminated: Option<String>,
+}
+
+/// The rounds of a multi-option tally and the option that won, if any.
+/// Approval tallies have a single round.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OptionTally {
+    pub proposal_id: String,
+    pub rounds: Vec<TallyRound>,
+    pub winner: Option<String>,
+    pub total_weight: f64,
+}
+
+impl GovernanceSystem {
+    /// Checks the options of a multi-option proposal when it is created.
+    pub(crate) fn validate_ballot_kind(proposal: &Proposal) -> IcnResult<()> {
+        if !proposal.ballot.is_multi_option() {
+            return Ok(());
+        }
+        let options = proposal.ballot.options();
+        if options.len() < 2 {
+            return Err(IcnError::Governance("Multi-option proposals need at least two options".into()));
+        }
+        if options.iter().any(|option| option.trim().is_empty()) {
+            return Err(IcnError::Governance("Options cannot be empty".into()));
+        }
+        if options.iter().collect::<BTreeSet<_>>().len() != options.len() {
+            return Err(IcnError::Governance("Options must be distinct".into()));
+        }
+        if !proposal.actions.is_empty() {
+            return Err(IcnError::Governance("Multi-option proposals cannot carry actions".into()));
+        }
+        Ok(())
+    }
+
+    /// Casts a ranked or approval ballot on a multi-option proposal,
+    /// weighted from the proposal's voter snapshot if it has one.
+    pub fn cast_ballot(&mut self, proposal_id: &str, voter: String, choices: Vec<String>, weight: f64) -> IcnResult<()> {
+        let weight = self.check_ballot(proposal_id, &voter, &choices, weight)?;
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(IcnError::Governance("Proposal is not active".into()));
+        }
+        if self.clock.now() > proposal.voting_ends_at {
+            return Err(IcnError::Governance("Voting period has ended".into()));
+        }
+        if self.get_ballots(proposal_id).iter().any(|ballot| ballot.voter == voter) {
+            return Err(IcnError::Governance("Voter has already voted on this proposal".into()));
+        }
+
+        let ballot = Self::weighted_ballot(proposal_id, voter, choices, weight, self.clock.now())?;
+        self.ballots.entry(proposal_id.to_string()).or_default().push(ballot);
+        Ok(())
+    }
+
+    /// Records a ballot cast on another node. Like remote votes, ballots cast
+    /// after the voting period are rejected by their own timestamp, and
+    /// ballots arriving after this node finalized the proposal are merged
+    /// and the result recalculated. If a voter appears with two different
+    /// ballots the earlier one wins, so every node keeps the same one.
+    /// Returns whether the ballot changed anything.
+    pub fn apply_remote_ballot(&mut self, proposal_id: &str, voter: String, choices: Vec<String>, weight: f64, timestamp: DateTime<Utc>) -> IcnResult<bool> {
+        let weight = self.check_ballot(proposal_id, &voter, &choices, weight)?;
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.status == ProposalStatus::Executed {
+            return Err(IcnError::Governance("Proposal has already been executed".into()));
+        }
+        if timestamp > proposal.voting_ends_at {
+            return Err(IcnError::Governance("Ballot was cast after the voting period".into()));
+        }
+        let existing = self.get_ballots(proposal_id).iter().position(|ballot| ballot.voter == voter);
+        if let Some(index) = existing {
+            let current = &self.get_ballots(proposal_id)[index];
+            if (timestamp, &choices) >= (current.timestamp, &current.choices) {
+                return Ok(false);
+            }
+        }
+
+        let ballot = Self::weighted_ballot(proposal_id, voter, choices, weight, timestamp)?;
+        let ballots = self.ballots.entry(proposal_id.to_string()).or_default();
+        match existing {
+            Some(index) => ballots[index] = ballot,
+            None => ballots.push(ballot),
+        }
+
+        if self.get_proposal(proposal_id)?.status != ProposalStatus::Active {
+            let recalculated = self.decide_options(proposal_id)?;
+            self.set_status(proposal_id, recalculated)?;
+        }
+        Ok(true)
+    }
+
+    /// Checks a ballot's choices and the voter's eligibility, returning the
+    /// weight it counts with.
+    fn check_ballot(&self, proposal_id: &str, voter: &str, choices: &[String], weight: f64) -> IcnResult<f64> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if !proposal.ballot.is_multi_option() {
+            return Err(IcnError::Governance("Proposal takes yes/no votes, not ballots".into()));
+        }
+        if choices.is_empty() {
+            return Err(IcnError::Governance("A ballot must choose at least one option".into()));
+        }
+        if let Some(unknown) = choices.iter().find(|choice| !proposal.ballot.options().contains(choice)) {
+            return Err(IcnError::Governance(format!("{} is not an option on this proposal", unknown)));
+        }
+        if choices.iter().collect::<BTreeSet<_>>().len() != choices.len() {
+            return Err(IcnError::Governance("A ballot cannot list an option twice".into()));
+        }
+        if let Some(eligible_voters) = self.proposal_policies.get(proposal_id).and_then(|p| p.eligible_voters.as_ref()) {
+            if !eligible_voters.iter().any(|eligible| eligible == voter) {
+                return Err(IcnError::Governance("Voter is not eligible for this proposal".into()));
+            }
+        }
+        Ok(self.snapshot_weight(proposal_id, voter)?.unwrap_or(weight))
+    }
+
+    fn weighted_ballot(proposal_id: &str, voter: String, choices: Vec<String>, weight: f64, timestamp: DateTime<Utc>) -> IcnResult<Ballot> {
+        let (eligibility_proof, weight_opening) = WeightRangeProof::prove(proposal_id, &voter, weight)?;
+        Ok(Ballot {
+            voter,
+            proposal_id: proposal_id.to_string(),
+            choices,
+            weight,
+            timestamp,
+            eligibility_proof,
+            weight_opening,
+        })
+    }
+
+    pub fn get_ballots(&self, proposal_id: &str) -> &[Ballot] {
+        self.ballots.get(proposal_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Counts the ballots cast so far on a multi-option proposal.
+    pub fn tally_options(&self, proposal_id: &str) -> IcnResult<OptionTally> {
+        let proposal = self.get_proposal(proposal_id)?;
+        let ballots: Vec<&Ballot> = self.get_ballots(proposal_id).iter()
+            .filter(|ballot| {
+                let verified = ballot.is_eligibility_verified();
+                if !verified {
+                    warn!("Ignoring ballot by {} on {}: eligibility proof failed", ballot.voter, ballot.proposal_id);
+                }
+                verified
+            })
+            .collect();
+        let total_weight = ballots.iter().map(|ballot| ballot.weight).sum();
+
+        let (rounds, winner) = match &proposal.ballot {
+            BallotKind::YesNo => return Err(IcnError::Governance("Proposal takes yes/no votes, not ballots".into())),
+            BallotKind::RankedChoice { options } => instant_runoff(options, &ballots),
+            BallotKind::Approval { options } => approval(options, &ballots),
+        };
+        Ok(OptionTally { proposal_id: proposal_id.to_string(), rounds, winner, total_weight })
+    }
+
+    /// A multi-option proposal passes when quorum is met and an option wins.
+    pub(crate) fn decide_options(&self, proposal_id: &str) -> IcnResult<ProposalStatus> {
+        let proposal = self.get_proposal(proposal_id)?;
+        let tally = self.tally_options(proposal_id)?;
+        if tally.total_weight >= proposal.required_quorum && tally.winner.is_some() {
+            Ok(ProposalStatus::Passed)
+        } else {
+            Ok(ProposalStatus::Rejected)
+        }
+    }
+}
+
+/// Runs rounds until an option holds a majority of the ballots still in
+/// play, dropping the weakest option after each round. Ties for last place
+/// drop the option that sorts last, so every node eliminates the same one.
+fn instant_runoff(options: &[String], ballots: &[&Ballot]) -> (Vec<TallyRound>, Option<String>) {
+    let mut remaining: BTreeSet<&String> = options.iter().collect();
+    let mut rounds = Vec::new();
+    loop {
+        let mut counts: BTreeMap<String, f64> = remaining.iter().map(|option| (option.to_string(), 0.0)).collect();
+        let mut exhausted = 0.0;
+        for ballot in ballots {
+            match ballot.choices.iter().find(|choice| remaining.contains(choice)) {
+                Some(choice) => *counts.entry(choice.clone()).or_default() += ballot.weight,
+                None => exhausted += ballot.weight,
+            }
+        }
+        let active: f64 = counts.values().sum();
+        if active <= 0.0 {
+            rounds.push(TallyRound { counts, exhausted, eliminated: None });
+            return (rounds, None);
+        }
+
+        let (leader, leader_weight) = leading(&counts);
+        if leader_weight * 2.0 > active || remaining.len() == 1 {
+            let winner = leader.clone();
+            rounds.push(TallyRound { counts, exhausted, eliminated: None });
+            return (rounds, Some(winner));
+        }
+
+        let eliminated = counts.iter()
+            .min_by(|a, b| a.1.total_cmp(b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(option, _)| option.clone())
+            .expect("at least two options remain");
+        remaining.retain(|option| **option != eliminated);
+        rounds.push(TallyRound { counts, exhausted, eliminated: Some(eliminated) });
+    }
+}
+
+/// Sums the weight approving each option. A tie for the most approvals
+/// has no winner.
+fn approval(options: &[String], ballots: &[&Ballot]) -> (Vec<TallyRound>, Option<String>) {
+    let mut counts: BTreeMap<String, f64> = options.iter().map(|option| (option.clone(), 0.0)).collect();
+    for ballot in ballots {
+        for choice in &ballot.choices {
+            *counts.entry(choice.clone()).or_default() += ballot.weight;
+        }
+    }
+    let (leader, leader_weight) = leading(&counts);
+    let tied = counts.values().filter(|weight| **weight == leader_weight).count() > 1;
+    let winner = (leader_weight > 0.0 && !tied).then(|| leader.clone());
+    (vec![TallyRound { counts, exhausted: 0.0, eliminated: None }], winner)
+}
+
+/// The option with the most weight, preferring the one that sorts first.
+fn leading(counts: &BTreeMap<String, f64>) -> (&String, f64) {
+    counts.iter()
+        .max_by(|a, b| a.1.total_cmp(b.1).then_with(|| b.0.cmp(a.0)))
+        .map(|(option, weight)| (option, *weight))
+        .expect("multi-option proposals have options")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ProposalCategory, ProposalScope, ProposalType, VotingWeightMode};
+    use chrono::Duration;
+
+    fn proposal(id: &str, ballot: BallotKind) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: "Where should the new workshop go?".to_string(),
+            description: String::new(),
+            proposer: "Alice".to_string(),
+            created_at: Utc::now(),
+            voting_ends_at: Utc::now() + Duration::days(7),
+            status: ProposalStatus::Active,
+            proposal_type: ProposalType::EconomicAdjustment,
+            category: ProposalCategory::Economic,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    fn options() -> Vec<String> {
+        ["north", "south", "east"].iter().map(|option| option.to_string()).collect()
+    }
+
+    fn choices(choices: &[&str]) -> Vec<String> {
+        choices.iter().map(|choice| choice.to_string()).collect()
+    }
+
+    #[test]
+    fn test_instant_runoff() {
+        let mut gov_system = GovernanceSystem::new();
+        gov_system.create_proposal(proposal("site", BallotKind::RankedChoice { options: options() })).unwrap();
+        assert!(gov_system.vote_on_proposal("site", "Alice".to_string(), true, 1.0).is_err());
+        assert!(gov_system.cast_ballot("site", "Alice".to_string(), choices(&["west"]), 1.0).is_err());
+        assert!(gov_system.cast_ballot("site", "Alice".to_string(), choices(&["north", "north"]), 1.0).is_err());
+
+        // North leads the first round, but east's voters prefer south
+        gov_system.cast_ballot("site", "Alice".to_string(), choices(&["north"]), 4.0).unwrap();
+        gov_system.cast_ballot("site", "Bob".to_string(), choices(&["south", "north"]), 3.0).unwrap();
+        gov_system.cast_ballot("site", "Carol".to_string(), choices(&["east", "south"]), 2.0).unwrap();
+        assert!(gov_system.cast_ballot("site", "Carol".to_string(), choices(&["north"]), 2.0).is_err());
+
+        let tally = gov_system.tally_options("site").unwrap();
+        assert_eq!(tally.rounds.len(), 2);
+        assert_eq!(tally.rounds[0].eliminated, Some("east".to_string()));
+        assert_eq!(tally.rounds[1].counts["south"], 5.0);
+        assert_eq!(tally.winner, Some("south".to_string()));
+
+        gov_system.proposals.get_mut("site").unwrap().voting_ends_at = Utc::now() - Duration::hours(1);
+        assert_eq!(gov_system.finalize_proposal("site").unwrap(), ProposalStatus::Passed);
+    }
+
+    #[test]
+    fn test_approval_voting() {
+        let mut gov_system = GovernanceSystem::new();
+        assert!(gov_system.create_proposal(proposal("one", BallotKind::Approval { options: choices(&["north"]) })).is_err());
+
+        gov_system.create_proposal(proposal("site", BallotKind::Approval { options: options() })).unwrap();
+        gov_system.cast_ballot("site", "Alice".to_string(), choices(&["north", "east"]), 1.0).unwrap();
+        gov_system.cast_ballot("site", "Bob".to_string(), choices(&["east"]), 1.0).unwrap();
+        let tally = gov_system.tally_options("site").unwrap();
+        assert_eq!(tally.winner, Some("east".to_string()));
+        assert_eq!(tally.rounds[0].counts["north"], 1.0);
+
+        // A tie for first place decides nothing
+        gov_system.cast_ballot("site", "Carol".to_string(), choices(&["north"]), 1.0).unwrap();
+        assert_eq!(gov_system.tally_options("site").unwrap().winner, None);
+    }
+
+    #[test]
+    fn test_remote_ballots_merge_the_same_way_everywhere() {
+        let mut gov_system = GovernanceSystem::new();
+        gov_system.create_proposal(proposal("site", BallotKind::Approval { options: options() })).unwrap();
+        let cast_at = Utc::now();
+        assert!(gov_system.apply_remote_ballot("site", "Alice".to_string(), choices(&["north"]), 1.0, cast_at).unwrap());
+        assert!(!gov_system.apply_remote_ballot("site", "Alice".to_string(), choices(&["north"]), 1.0, cast_at).unwrap());
+
+        // Of two ballots from one voter the earlier is kept, whichever arrives first
+        assert!(gov_system.apply_remote_ballot("site", "Alice".to_string(), choices(&["east"]), 1.0, cast_at - Duration::minutes(1)).unwrap());
+        assert!(!gov_system.apply_remote_ballot("site", "Alice".to_string(), choices(&["south"]), 1.0, cast_at).unwrap());
+        assert_eq!(gov_system.get_ballots("site")[0].choices, choices(&["east"]));
+        let late = Utc::now() + Duration::days(8);
+        assert!(gov_system.apply_remote_ballot("site", "Bob".to_string(), choices(&["north"]), 1.0, late).is_err());
+
+        // A ballot that arrives after finalization changes the result
+        gov_system.proposals.get_mut("site").unwrap().voting_ends_at = Utc::now() - Duration::hours(1);
+        assert_eq!(gov_system.finalize_proposal("site").unwrap(), ProposalStatus::Passed);
+        gov_system.apply_remote_ballot("site", "Bob".to_string(), choices(&["north"]), 1.0, Utc::now() - Duration::hours(2)).unwrap();
+        assert_eq!(gov_system.get_proposal("site").unwrap().status, ProposalStatus::Rejected);
+    }
+}
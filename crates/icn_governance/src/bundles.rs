@@ -0,0 +1,265 @@
+// File: crates/icn_governance/src/bundles.rs
+
+use crate::{GovernanceSystem, Proposal, ProposalStatus};
+use icn_common::{IcnError, IcnResult, ProposalBundle};
+use std::collections::HashSet;
+
+/// Fewest proposals a bundle can hold.
+pub const MIN_BUNDLE_SIZE: usize = 2;
+
+impl GovernanceSystem {
+    /// Creates proposals that are decided and executed together, in the
+    /// order given. Either all of them are created or none is.
+    pub fn create_bundle(&mut self, bundle_id: &str, mut proposals: Vec<Proposal>) -> IcnResult<Vec<String>> {
+        let bundle = ProposalBundle {
+            id: bundle_id.to_string(),
+            members: proposals.iter().map(|proposal| proposal.id.clone()).collect(),
+        };
+        if bundle_id.trim().is_empty() || self.get_bundle(bundle_id).is_some() {
+            return Err(IcnError::Governance("Bundles need an id that isn't taken".into()));
+        }
+        if let Some(first) = proposals.first() {
+            if proposals.iter().any(|proposal| proposal.scope != first.scope || proposal.weight_mode != first.weight_mode) {
+                return Err(IcnError::Governance("Bundled proposals must share a scope and voting weight mode".into()));
+            }
+        }
+        for proposal in &mut proposals {
+            proposal.bundle = Some(bundle.clone());
+            Self::validate_bundle(proposal)?;
+        }
+
+        let mut created = Vec::new();
+        for proposal in proposals {
+            match self.create_proposal(proposal) {
+                Ok(proposal_id) => created.push(proposal_id),
+                Err(e) => {
+                    for proposal_id in &created {
+                        self.remove_proposal(proposal_id);
+                    }
+                    return Err(e);
+                }
+            }
+        }
+        Ok(created)
+    }
+
+    /// The bundle with the given id, as carried by its proposals.
+    pub fn get_bundle(&self, bundle_id: &str) -> Option<&ProposalBundle> {
+        self.proposals.values()
+            .filter_map(|proposal| proposal.bundle.as_ref())
+            .find(|bundle| bundle.id == bundle_id)
+    }
+
+    /// The proposals decided together with `proposal_id`, itself included,
+    /// in execution order.
+    pub fn bundle_members(&self, proposal_id: &str) -> IcnResult<Vec<String>> {
+        Ok(match &self.get_proposal(proposal_id)?.bundle {
+            Some(bundle) => bundle.members.clone(),
+            None => vec![proposal_id.to_string()],
+        })
+    }
+
+    /// The proposals executing `proposal_id` executes, in order: its whole
+    /// bundle, once every member has passed and is out of its timelock and
+    /// every proposal they depend on outside the bundle has been executed.
+    pub fn execution_order(&self, proposal_id: &str) -> IcnResult<Vec<String>> {
+        let members = self.bundle_members(proposal_id)?;
+        for member in &members {
+            let proposal = self.get_proposal(member)?;
+            if proposal.status != ProposalStatus::Passed {
+                return Err(IcnError::Governance(format!("Proposal {} has not passed", member)));
+            }
+            self.check_timelock(member)?;
+            for dependency in proposal.depends_on.iter().filter(|dependency| !members.contains(dependency)) {
+                if self.known_status(dependency) != Some(ProposalStatus::Executed) {
+                    return Err(IcnError::Governance(format!("Proposal {} waits for {} to be executed", member, dependency)));
+                }
+            }
+        }
+        Ok(members)
+    }
+
+    /// Bundles must list the proposal among two or more distinct members
+    /// and can only be voted on yes or no.
+    pub(crate) fn validate_bundle(proposal: &Proposal) -> IcnResult<()> {
+        let Some(bundle) = &proposal.bundle else {
+            return Ok(());
+        };
+        let distinct: HashSet<&String> = bundle.members.iter().collect();
+        if bundle.members.len() < MIN_BUNDLE_SIZE || distinct.len() != bundle.members.len() || !distinct.contains(&proposal.id) {
+            return Err(IcnError::Governance(format!("Bundles hold at least {} distinct proposals, including each member", MIN_BUNDLE_SIZE)));
+        }
+        if proposal.ballot.is_multi_option() {
+            return Err(IcnError::Governance("Multi-option proposals cannot be bundled".into()));
+        }
+        Ok(())
+    }
+
+    /// A proposal can depend on proposals that exist and weren't rejected
+    /// or vetoed, or on members listed before it in its own bundle, so
+    /// dependencies never form a cycle.
+    pub(crate) fn check_dependencies(&self, proposal: &Proposal) -> IcnResult<()> {
+        let earlier_members: &[String] = match &proposal.bundle {
+            Some(bundle) => {
+                let position = bundle.members.iter().position(|member| *member == proposal.id).unwrap_or(0);
+                &bundle.members[..position]
+            }
+            None => &[],
+        };
+        let mut seen = HashSet::new();
+        for dependency in &proposal.depends_on {
+            if *dependency == proposal.id || !seen.insert(dependency) {
+                return Err(IcnError::Governance("Proposals cannot depend on themselves or on a proposal twice".into()));
+            }
+            if earlier_members.contains(dependency) {
+                continue;
+            }
+            if proposal.bundle.as_ref().is_some_and(|bundle| bundle.members.contains(dependency)) {
+                return Err(IcnError::Governance(format!("{} executes after {} in their bundle", dependency, proposal.id)));
+            }
+            match self.known_status(dependency) {
+                None => return Err(IcnError::Governance(format!("Dependency {} not found", dependency))),
+                Some(ProposalStatus::Rejected | ProposalStatus::Vetoed) => {
+                    return Err(IcnError::Governance(format!("Dependency {} was rejected", dependency)));
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Decides a bundle as one: it passes only if every member would.
+    /// Returns `None` while a member hasn't reached this node.
+    pub(crate) fn decide_members(&self, members: &[String]) -> IcnResult<Option<ProposalStatus>> {
+        let mut status = ProposalStatus::Passed;
+        for member in members {
+            if !self.proposals.contains_key(member) {
+                return Ok(None);
+            }
+            if self.decide(member)? != ProposalStatus::Passed {
+                status = ProposalStatus::Rejected;
+            }
+        }
+        Ok(Some(status))
+    }
+
+    /// Status of a live or archived proposal.
+    fn known_status(&self, proposal_id: &str) -> Option<ProposalStatus> {
+        self.proposals.get(proposal_id)
+            .map(|proposal| proposal.status.clone())
+            .or_else(|| self.archive_index.get(proposal_id).map(|entry| entry.status.clone()))
+    }
+
+    fn remove_proposal(&mut self, proposal_id: &str) {
+        self.proposals.remove(proposal_id);
+        self.votes.remove(proposal_id);
+        self.ballots.remove(proposal_id);
+        self.proposal_policies.remove(proposal_id);
+        self.snapshots.remove(proposal_id);
+        self.timelocks.remove(proposal_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ProposalCategory, ProposalScope, ProposalType, VotingWeightMode};
+    use chrono::{Duration, Utc};
+    use icn_common::{BallotKind, GovernancePolicy, ProposalAction};
+
+    fn proposal(id: &str, proposal_type: ProposalType) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: format!("Proposal {}", id),
+            description: String::new(),
+            proposer: "Alice".to_string(),
+            created_at: Utc::now(),
+            voting_ends_at: Utc::now(),
+            status: ProposalStatus::Active,
+            proposal_type,
+            category: ProposalCategory::Economic,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    fn end_voting(governance: &mut GovernanceSystem, members: &[&str]) {
+        for member in members {
+            governance.proposals.get_mut(*member).unwrap().voting_ends_at = Utc::now() - Duration::seconds(1);
+        }
+    }
+
+    #[test]
+    fn test_bundles_are_voted_and_executed_together() {
+        let mut governance = GovernanceSystem::new();
+        let policy = GovernancePolicy { quorum: 1.0, ..GovernancePolicy::default() };
+        governance.set_initial_policy(ProposalType::Constitutional, None, policy).unwrap();
+
+        let mut fees = proposal("fees", ProposalType::EconomicAdjustment);
+        fees.depends_on = vec!["policy".to_string()];
+        let mut policy = proposal("policy", ProposalType::Constitutional);
+        policy.actions = vec![ProposalAction::UpdatePolicy {
+            proposal_type: ProposalType::EconomicAdjustment,
+            category: None,
+            policy: GovernancePolicy::default(),
+        }];
+
+        // A member can only depend on the members before it
+        assert!(governance.create_bundle("reform", vec![fees.clone(), policy.clone()]).is_err());
+        assert!(governance.proposals.is_empty());
+        assert!(governance.create_bundle("reform", vec![policy.clone()]).is_err());
+
+        governance.create_bundle("reform", vec![policy, fees]).unwrap();
+        assert_eq!(governance.get_bundle("reform").unwrap().members, vec!["policy", "fees"]);
+
+        // One vote counts for both, and the constitutional member's quorum holds the bundle back
+        governance.vote_on_proposal("fees", "Alice".to_string(), true, 0.6).unwrap();
+        assert_eq!(governance.get_votes("policy").unwrap().len(), 1);
+        assert!(governance.vote_on_proposal("policy", "Alice".to_string(), true, 0.6).is_err());
+        end_voting(&mut governance, &["policy", "fees"]);
+        assert!(governance.apply_remote_vote("policy", "Bob".to_string(), true, 0.6, Utc::now() - Duration::seconds(2), None).unwrap());
+
+        assert_eq!(governance.finalize_proposal("fees").unwrap(), ProposalStatus::Passed);
+        assert_eq!(governance.get_proposal("policy").unwrap().status, ProposalStatus::Passed);
+        assert_eq!(governance.execution_order("fees").unwrap(), vec!["policy", "fees"]);
+        governance.execute_proposal("fees").unwrap();
+        assert!(["policy", "fees"].iter().all(|id| governance.get_proposal(id).unwrap().status == ProposalStatus::Executed));
+    }
+
+    #[test]
+    fn test_bundle_fails_as_one_and_blocks_dependents() {
+        let mut governance = GovernanceSystem::new();
+        let policy = GovernancePolicy { quorum: 1.0, ..GovernancePolicy::default() };
+        governance.set_initial_policy(ProposalType::Constitutional, None, policy).unwrap();
+        governance.create_bundle("reform", vec![
+            proposal("policy", ProposalType::Constitutional),
+            proposal("fees", ProposalType::EconomicAdjustment),
+        ]).unwrap();
+
+        let mut follow_up = proposal("follow-up", ProposalType::EconomicAdjustment);
+        follow_up.depends_on = vec!["fees".to_string()];
+        governance.create_proposal(follow_up).unwrap();
+        let mut unknown = proposal("unknown", ProposalType::EconomicAdjustment);
+        unknown.depends_on = vec!["missing".to_string()];
+        assert!(governance.create_proposal(unknown).is_err());
+
+        // Enough for the economic member alone, but not for the constitutional one
+        governance.vote_on_proposal("fees", "Alice".to_string(), true, 0.6).unwrap();
+        governance.vote_on_proposal("follow-up", "Alice".to_string(), true, 0.6).unwrap();
+        end_voting(&mut governance, &["policy", "fees", "follow-up"]);
+        assert_eq!(governance.finalize_proposal("policy").unwrap(), ProposalStatus::Rejected);
+        assert_eq!(governance.get_proposal("fees").unwrap().status, ProposalStatus::Rejected);
+
+        assert_eq!(governance.finalize_proposal("follow-up").unwrap(), ProposalStatus::Passed);
+        assert!(governance.execute_proposal("follow-up").is_err());
+        let mut late = proposal("late", ProposalType::EconomicAdjustment);
+        late.depends_on = vec!["fees".to_string()];
+        assert!(governance.create_proposal(late).is_err());
+    }
+}
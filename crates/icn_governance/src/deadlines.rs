@@ -0,0 +1,164 @@
+// File: crates/icn_governance/src/deadlines.rs
+
+use crate::{GovernanceSystem, ProposalStatus};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashSet};
+
+/// How long before voting closes voters who have not voted are reminded,
+/// in seconds, furthest first.
+pub const VOTING_DEADLINE_REMINDERS: [i64; 2] = [24 * 3600, 3600];
+
+/// A reminder that voting on a proposal closes soon.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DeadlineReminder {
+    pub proposal_id: String,
+    pub title: String,
+    pub closes_at: DateTime<Utc>,
+    /// Which of `VOTING_DEADLINE_REMINDERS` this is.
+    pub remaining_secs: i64,
+    /// Voters in the proposal's snapshot who have not voted yet.
+    pub voters: Vec<String>,
+}
+
+impl GovernanceSystem {
+    /// Reminders that have come due since the last call, each sent once per
+    /// proposal. When several come due together only the closest to the
+    /// deadline is sent, and none is sent for a voting period shorter than
+    /// its lead time, since voters were told of the deadline when it opened.
+    pub fn take_deadline_reminders(&mut self) -> Vec<DeadlineReminder> {
+        let now = self.clock.now();
+        let mut reminders = Vec::new();
+        for proposal in self.proposals.values().filter(|proposal| proposal.status == ProposalStatus::Active) {
+            if now >= proposal.voting_ends_at {
+                continue;
+            }
+            let voting_secs = (proposal.voting_ends_at - proposal.created_at).num_seconds();
+            let sent = self.reminders_sent.get(&proposal.id);
+            let due = VOTING_DEADLINE_REMINDERS.iter().copied()
+                .filter(|&lead| lead < voting_secs && now >= proposal.voting_ends_at - Duration::seconds(lead))
+                .filter(|lead| sent.is_none_or(|sent| !sent.contains(lead)))
+                .min();
+            let Some(remaining_secs) = due else {
+                continue;
+            };
+
+            let voted: HashSet<&str> = self.votes.get(&proposal.id).into_iter().flatten().map(|vote| vote.voter.as_str())
+                .chain(self.ballots.get(&proposal.id).into_iter().flatten().map(|ballot| ballot.voter.as_str()))
+                .collect();
+            let voters = self.snapshots.get(&proposal.id)
+                .map(|snapshot| snapshot.weights.keys().filter(|voter| !voted.contains(voter.as_str())).cloned().collect())
+                .unwrap_or_default();
+            reminders.push(DeadlineReminder {
+                proposal_id: proposal.id.clone(),
+                title: proposal.title.clone(),
+                closes_at: proposal.voting_ends_at,
+                remaining_secs,
+                voters,
+            });
+        }
+
+        for reminder in &reminders {
+            // Leads further out than the one sent are spent as well
+            self.reminders_sent.entry(reminder.proposal_id.clone()).or_default()
+                .extend(VOTING_DEADLINE_REMINDERS.iter().filter(|&&lead| lead >= reminder.remaining_secs));
+        }
+        let proposals = &self.proposals;
+        self.reminders_sent.retain(|id, _| proposals.get(id).is_some_and(|proposal| proposal.status == ProposalStatus::Active));
+        reminders.sort_by(|a, b| a.closes_at.cmp(&b.closes_at).then_with(|| a.proposal_id.cmp(&b.proposal_id)));
+        reminders
+    }
+
+    /// Active proposals whose voting period is over, ready to finalize. A
+    /// bundle is listed once, by its first member.
+    pub fn closed_proposals(&self) -> Vec<String> {
+        let now = self.clock.now();
+        let mut closed: Vec<&String> = self.proposals.values()
+            .filter(|proposal| proposal.status == ProposalStatus::Active && now >= proposal.voting_ends_at)
+            .map(|proposal| &proposal.id)
+            .collect();
+        closed.sort();
+
+        let mut covered = BTreeSet::new();
+        let mut listed = Vec::new();
+        for proposal_id in closed {
+            if covered.contains(proposal_id) {
+                continue;
+            }
+            if let Ok(members) = self.bundle_members(proposal_id) {
+                covered.extend(members);
+            }
+            listed.push(proposal_id.clone());
+        }
+        listed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BallotKind, Proposal, ProposalCategory, ProposalScope, ProposalType, VotingWeightMode};
+    use icn_common::{GovernancePolicy, ManualClock};
+
+    fn proposal(id: &str, now: DateTime<Utc>) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: format!("Proposal {}", id),
+            description: String::new(),
+            proposer: "alice".to_string(),
+            created_at: now,
+            voting_ends_at: now,
+            status: ProposalStatus::Active,
+            proposal_type: ProposalType::EconomicAdjustment,
+            category: ProposalCategory::Economic,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    #[test]
+    fn test_voters_are_reminded_before_voting_closes() {
+        let clock = ManualClock::new(Utc::now());
+        let mut governance = GovernanceSystem::new().with_clock(clock.shared());
+        let policy = GovernancePolicy { quorum: 0.0, voting_duration_secs: 3 * 24 * 3600, ..GovernancePolicy::default() };
+        governance.set_initial_policy(ProposalType::EconomicAdjustment, None, policy).unwrap();
+        governance.create_proposal(proposal("budget", governance.now())).unwrap();
+        let voters = ["alice", "bob", "carol"].map(|voter| (voter.to_string(), 1.0));
+        governance.record_snapshot("budget", voters, governance.now()).unwrap();
+        governance.vote_on_proposal("budget", "alice".to_string(), true, 1.0).unwrap();
+        assert!(governance.take_deadline_reminders().is_empty());
+
+        clock.advance(Duration::days(2));
+        let reminders = governance.take_deadline_reminders();
+        assert_eq!(reminders.len(), 1);
+        assert_eq!(reminders[0].remaining_secs, 24 * 3600);
+        assert_eq!(reminders[0].voters, vec!["bob".to_string(), "carol".to_string()]);
+        assert!(governance.take_deadline_reminders().is_empty());
+
+        governance.create_proposal(proposal("fees", governance.now())).unwrap();
+        clock.advance(Duration::hours(23));
+        governance.vote_on_proposal("budget", "bob".to_string(), true, 1.0).unwrap();
+        let reminders = governance.take_deadline_reminders();
+        assert_eq!(reminders.iter().map(|r| (r.proposal_id.as_str(), r.remaining_secs)).collect::<Vec<_>>(), vec![("budget", 3600)]);
+        assert_eq!(reminders[0].voters, vec!["carol".to_string()]);
+
+        // Checked too late for the first, only the last reminder goes out
+        clock.advance(Duration::days(2) + Duration::minutes(30));
+        let reminders = governance.take_deadline_reminders();
+        assert_eq!(reminders.iter().map(|r| (r.proposal_id.as_str(), r.remaining_secs)).collect::<Vec<_>>(), vec![("fees", 3600)]);
+        assert!(governance.take_deadline_reminders().is_empty());
+
+        // Closed proposals are listed for finalizing until they are
+        assert_eq!(governance.closed_proposals(), vec!["budget".to_string()]);
+        governance.finalize_proposal("budget").unwrap();
+        assert!(governance.closed_proposals().is_empty());
+        clock.advance(Duration::hours(1));
+        assert_eq!(governance.closed_proposals(), vec!["fees".to_string()]);
+    }
+}
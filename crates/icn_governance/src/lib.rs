@@ -1,46 +1,42 @@
 // File: crates/icn_governance/src/lib.rs
 
-use icn_common::{IcnResult, IcnError};
+pub mod archive;
+pub mod ballots;
+pub mod bundles;
+pub mod deadlines;
+pub mod scopes;
+pub mod simulation;
+pub mod snapshots;
+pub mod templates;
+pub mod timelock;
+pub mod votes;
+
+pub use crate::archive::{archive_storage_key, ArchiveEntry, ArchivedProposal, ARCHIVE_INDEX_STORAGE_KEY};
+pub use crate::ballots::{Ballot, OptionTally, TallyRound};
+pub use crate::bundles::MIN_BUNDLE_SIZE;
+pub use crate::deadlines::{DeadlineReminder, VOTING_DEADLINE_REMINDERS};
+pub use crate::scopes::ScopeRules;
+pub use crate::simulation::{OutcomeProjection, DEFAULT_TURNOUT};
+pub use crate::snapshots::VoterSnapshot;
+pub use crate::templates::{builtin_templates, ParameterKind, ProposalTemplate, TemplateArguments, TemplateKind, TemplateParameter, TemplateProposal};
+pub use crate::timelock::{ProposalTimelock, VETO_SUPERMAJORITY};
+pub use crate::votes::{VoteFilter, VotePage, VoteTotals, MAX_VOTE_PAGE_LIMIT};
+
+use icn_common::{system_clock, IcnResult, IcnError, GovernancePolicy, ProposalAction, SharedClock};
+use icn_zkp::{WeightOpening, WeightRangeProof};
 use chrono::{DateTime, Utc, Duration};
+use log::warn;
 use serde::{Serialize, Deserialize};
-use std::collections::HashMap;
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProposalStatus {
-    Active,
-    Passed,
-    Rejected,
-    Executed,
-}
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProposalType {
-    Constitutional,
-    EconomicAdjustment,
-    NetworkUpgrade,
-}
+pub use icn_common::{BallotKind, Proposal, ProposalStatus, ProposalType, ProposalCategory, ProposalScope, VotingWeightMode};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProposalCategory {
-    Economic,
-    Technical,
-    Social,
-}
+/// Most shards a resharding proposal may ask for.
+pub const MAX_SHARD_COUNT: u64 = 1024;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Proposal {
-    pub id: String,
-    pub title: String,
-    pub description: String,
-    pub proposer: String,
-    pub created_at: DateTime<Utc>,
-    pub voting_ends_at: DateTime<Utc>,
-    pub status: ProposalStatus,
-    pub proposal_type: ProposalType,
-    pub category: ProposalCategory,
-    pub required_quorum: f64,
-    pub execution_timestamp: Option<DateTime<Utc>>,
-}
+/// Longest a single account freeze may last. Keeping an account frozen
+/// longer takes a new proposal or ruling.
+pub const MAX_FREEZE_DURATION_SECS: i64 = 90 * 24 * 60 * 60;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Vote {
@@ -49,11 +45,61 @@ pub struct Vote {
     pub in_favor: bool,
     pub weight: f64,
     pub timestamp: DateTime<Utc>,
+    /// Proof that the weight lies in a published range, generated when the vote is cast.
+    pub eligibility_proof: WeightRangeProof,
+    /// Opens the proof's commitment, binding it to `weight`. Receipts leave it out.
+    #[serde(default)]
+    pub weight_opening: WeightOpening,
+    /// The voter's signature over `icn_common::Vote::signing_bytes`, kept
+    /// so the vote can be passed on to peers.
+    #[serde(default)]
+    pub signature: Option<Vec<u8>>,
+}
+
+impl Vote {
+    /// Returns true if the eligibility proof verifies and its commitment
+    /// opens to exactly the weight being counted.
+    pub fn is_eligibility_verified(&self) -> bool {
+        self.eligibility_proof.opens(self.weight, &self.weight_opening)
+            && self.eligibility_proof.verify(&self.proposal_id, &self.voter).is_ok()
+    }
+}
+
+/// What a voter or auditor can see about a vote: the proven weight range, but not the weight.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VoteReceipt {
+    pub voter: String,
+    pub proposal_id: String,
+    pub in_favor: bool,
+    pub timestamp: DateTime<Utc>,
+    pub eligibility_proof: WeightRangeProof,
 }
 
+/// Share of the weighted vote an emergency pause action must exceed.
+pub const EMERGENCY_SUPERMAJORITY: f64 = 2.0 / 3.0;
+
+type PolicyKey = (ProposalType, Option<ProposalCategory>);
+
 pub struct GovernanceSystem {
     proposals: HashMap<String, Proposal>,
     votes: HashMap<String, Vec<Vote>>,
+    // Ranked and approval ballots on multi-option proposals
+    ballots: HashMap<String, Vec<Ballot>>,
+    policies: HashMap<PolicyKey, GovernancePolicy>,
+    default_policy: GovernancePolicy,
+    // Policy in force when each proposal was created
+    proposal_policies: HashMap<String, GovernancePolicy>,
+    // Proposals moved out to storage, by id
+    archive_index: HashMap<String, ArchiveEntry>,
+    templates: BTreeMap<String, ProposalTemplate>,
+    scope_rules: HashMap<ProposalScope, ScopeRules>,
+    // Voter weights fixed when each proposal was opened
+    snapshots: HashMap<String, VoterSnapshot>,
+    // When each passed proposal may be executed
+    timelocks: HashMap<String, ProposalTimelock>,
+    // Deadline reminders already sent for each open proposal, by lead time
+    reminders_sent: HashMap<String, BTreeSet<i64>>,
+    clock: SharedClock,
 }
 
 impl GovernanceSystem {
@@ -61,16 +107,161 @@ impl GovernanceSystem {
         GovernanceSystem {
             proposals: HashMap::new(),
             votes: HashMap::new(),
+            ballots: HashMap::new(),
+            policies: HashMap::new(),
+            default_policy: GovernancePolicy::default(),
+            proposal_policies: HashMap::new(),
+            archive_index: HashMap::new(),
+            templates: builtin_templates().into_iter().map(|template| (template.id.clone(), template)).collect(),
+            scope_rules: HashMap::new(),
+            snapshots: HashMap::new(),
+            timelocks: HashMap::new(),
+            reminders_sent: HashMap::new(),
+            clock: system_clock(),
         }
     }
 
-    pub fn create_proposal(&mut self, proposal: Proposal) -> IcnResult<String> {
-        if self.proposals.contains_key(&proposal.id) {
+    /// Reads deadlines against `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The current time on this system's clock.
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Creates a proposal, taking its quorum and voting period from the
+    /// policy registered for its type and category, narrowed to the
+    /// proposal's scope.
+    pub fn create_proposal(&mut self, mut proposal: Proposal) -> IcnResult<String> {
+        if self.proposals.contains_key(&proposal.id) || self.archive_index.contains_key(&proposal.id) {
             return Err(IcnError::Governance("Proposal ID already exists".into()));
         }
+        if proposal.proposal_type != ProposalType::Constitutional
+            && proposal.actions.iter().any(|a| matches!(a, ProposalAction::UpdatePolicy { .. }))
+        {
+            return Err(IcnError::Governance("Only constitutional proposals can change governance policies".into()));
+        }
+        Self::check_scoped_actions(&proposal)?;
+        Self::validate_ballot_kind(&proposal)?;
+        Self::validate_bundle(&proposal)?;
+        self.check_dependencies(&proposal)?;
+        for action in &proposal.actions {
+            match action {
+                ProposalAction::UpdatePolicy { policy, .. } => Self::validate_policy(policy)?,
+                ProposalAction::SetEmergencyPause { reason, .. } => {
+                    if reason.trim().is_empty() {
+                        return Err(IcnError::Governance("Emergency pause actions must state a reason".into()));
+                    }
+                }
+                ProposalAction::UpgradeContract { contract_id, code_hash } => {
+                    if contract_id.is_empty() || code_hash.len() != 64 {
+                        return Err(IcnError::Governance("Contract upgrades must name a contract and a SHA-256 code hash".into()));
+                    }
+                }
+                ProposalAction::UpdateAlertThresholds { thresholds } => thresholds.validate()?,
+                ProposalAction::SetParameter { name, value } => {
+                    if name.is_empty() || !value.is_finite() {
+                        return Err(IcnError::Governance("Parameter changes must name a parameter and a finite value".into()));
+                    }
+                    if proposal.proposal_type == ProposalType::Constitutional {
+                        return Err(IcnError::Governance("Parameters are changed by economic adjustment or network upgrade proposals".into()));
+                    }
+                }
+                ProposalAction::SetContractPermissions { contract_id, function, roles } => {
+                    if contract_id.is_empty() || function.is_empty() {
+                        return Err(IcnError::Governance("Permission changes must name a contract and a function".into()));
+                    }
+                    if roles.as_ref().is_some_and(|roles| roles.iter().any(|role| role.trim().is_empty())) {
+                        return Err(IcnError::Governance("Role names cannot be empty".into()));
+                    }
+                }
+                ProposalAction::SetIssuanceRate { rate, .. } => {
+                    if !rate.is_finite() || *rate < 0.0 {
+                        return Err(IcnError::Governance("Issuance rate must be a non-negative number".into()));
+                    }
+                }
+                ProposalAction::FundProject { project, recipient, amount, .. } => {
+                    if project.trim().is_empty() || recipient.trim().is_empty() {
+                        return Err(IcnError::Governance("Project funding must name a project and a recipient".into()));
+                    }
+                    if !amount.is_finite() || *amount <= 0.0 {
+                        return Err(IcnError::Governance("Project funding must be a positive amount".into()));
+                    }
+                }
+                ProposalAction::ReshardNetwork { shard_count, .. } => {
+                    if proposal.proposal_type != ProposalType::NetworkUpgrade {
+                        return Err(IcnError::Governance("Only network upgrade proposals can change the shard count".into()));
+                    }
+                    if *shard_count == 0 || *shard_count > MAX_SHARD_COUNT {
+                        return Err(IcnError::Governance(format!("Shard count must be between 1 and {}", MAX_SHARD_COUNT)));
+                    }
+                }
+                ProposalAction::FreezeAccount { address, duration_secs, reason } => {
+                    if address.trim().is_empty() || reason.trim().is_empty() {
+                        return Err(IcnError::Governance("Account freezes must name an account and a reason".into()));
+                    }
+                    if *duration_secs <= 0 || *duration_secs > MAX_FREEZE_DURATION_SECS {
+                        return Err(IcnError::Governance(format!("Freezes must last between 1 and {} seconds", MAX_FREEZE_DURATION_SECS)));
+                    }
+                }
+                ProposalAction::UnfreezeAccount { address } => {
+                    if address.trim().is_empty() {
+                        return Err(IcnError::Governance("Unfreezing must name an account".into()));
+                    }
+                }
+                ProposalAction::SetMembershipClass { name, vote_multiplier, profit_share, .. } => {
+                    if proposal.proposal_type != ProposalType::Constitutional || !matches!(proposal.scope, ProposalScope::Dao(_)) {
+                        return Err(IcnError::Governance("Membership classes are set by constitutional proposals scoped to a DAO".into()));
+                    }
+                    if name.trim().is_empty() {
+                        return Err(IcnError::Governance("Membership classes must have a name".into()));
+                    }
+                    if !vote_multiplier.is_finite() || *vote_multiplier < 0.0 || !profit_share.is_finite() || *profit_share < 0.0 {
+                        return Err(IcnError::Governance("Vote multipliers and profit shares must be non-negative numbers".into()));
+                    }
+                }
+                ProposalAction::OpenGrantRound { title, budget, application_secs, voting_secs, .. } => {
+                    if proposal.proposal_type != ProposalType::EconomicAdjustment || !matches!(proposal.scope, ProposalScope::Dao(_)) {
+                        return Err(IcnError::Governance("Grant rounds are opened by economic adjustment proposals scoped to a DAO".into()));
+                    }
+                    if title.trim().is_empty() || !budget.is_finite() || *budget <= 0.0 {
+                        return Err(IcnError::Governance("Grant rounds need a title and a positive budget".into()));
+                    }
+                    if *application_secs <= 0 || *voting_secs <= 0 {
+                        return Err(IcnError::Governance("Grant rounds need positive application and voting periods".into()));
+                    }
+                }
+                ProposalAction::SetExchangeRate { base, quote, rate } => {
+                    if proposal.proposal_type != ProposalType::EconomicAdjustment {
+                        return Err(IcnError::Governance("Exchange rates are set by economic adjustment proposals".into()));
+                    }
+                    if base == quote || !rate.is_finite() || *rate <= 0.0 {
+                        return Err(IcnError::Governance("Exchange rates need two different currencies and a positive rate".into()));
+                    }
+                }
+                ProposalAction::SetRateOracle { oracle, .. } => {
+                    if proposal.proposal_type != ProposalType::EconomicAdjustment {
+                        return Err(IcnError::Governance("Rate oracles are chosen by economic adjustment proposals".into()));
+                    }
+                    if oracle.trim().is_empty() {
+                        return Err(IcnError::Governance("Rate oracle changes must name an oracle".into()));
+                    }
+                }
+                ProposalAction::VetoProposal { proposal_id } => self.validate_veto(proposal_id)?,
+            }
+        }
+
+        let policy = self.scoped_policy(&proposal, self.get_policy(&proposal.proposal_type, &proposal.category).clone())?;
+        proposal.required_quorum = policy.quorum;
+        proposal.voting_ends_at = proposal.created_at + Duration::seconds(policy.voting_duration_secs);
+
         let proposal_id = proposal.id.clone();
         self.proposals.insert(proposal_id.clone(), proposal);
         self.votes.insert(proposal_id.clone(), Vec::new());
+        self.proposal_policies.insert(proposal_id.clone(), policy);
         Ok(proposal_id)
     }
 
@@ -79,63 +270,308 @@ impl GovernanceSystem {
             .ok_or_else(|| IcnError::Governance("Proposal not found".into()))
     }
 
+    /// The policy a proposal was created under.
+    pub fn get_proposal_policy(&self, proposal_id: &str) -> Option<&GovernancePolicy> {
+        self.proposal_policies.get(proposal_id)
+    }
+
+    /// Returns the policy for a proposal type and category, falling back to
+    /// the type-wide policy and then to the default policy.
+    pub fn get_policy(&self, proposal_type: &ProposalType, category: &ProposalCategory) -> &GovernancePolicy {
+        self.policies.get(&(proposal_type.clone(), Some(category.clone())))
+            .or_else(|| self.policies.get(&(proposal_type.clone(), None)))
+            .unwrap_or(&self.default_policy)
+    }
+
+    /// Sets a policy directly, bypassing governance. Only used to seed the
+    /// policies listed in the genesis file.
+    pub fn set_initial_policy(&mut self, proposal_type: ProposalType, category: Option<ProposalCategory>, policy: GovernancePolicy) -> IcnResult<()> {
+        Self::validate_policy(&policy)?;
+        self.policies.insert((proposal_type, category), policy);
+        Ok(())
+    }
+
+    /// Sets the voting period used by proposals whose type and category
+    /// have no policy of their own.
+    pub fn set_default_voting_duration(&mut self, voting_duration_secs: i64) -> IcnResult<()> {
+        if voting_duration_secs <= 0 {
+            return Err(IcnError::Governance("Voting duration must be positive".into()));
+        }
+        self.default_policy.voting_duration_secs = voting_duration_secs;
+        Ok(())
+    }
+
+    pub fn list_policies(&self) -> Vec<(ProposalType, Option<ProposalCategory>, GovernancePolicy)> {
+        self.policies.iter()
+            .map(|((proposal_type, category), policy)| (proposal_type.clone(), category.clone(), policy.clone()))
+            .collect()
+    }
+
+    /// Records a yes/no vote. If the proposal has a voter snapshot, the
+    /// voter's snapshot weight is counted instead of `weight`. A vote on a
+    /// bundled proposal is cast on every proposal in the bundle.
     pub fn vote_on_proposal(&mut self, proposal_id: &str, voter: String, in_favor: bool, weight: f64) -> IcnResult<()> {
-        let proposal = self.proposals.get_mut(proposal_id)
-            .ok_or_else(|| IcnError::Governance("Proposal not found".into()))?;
+        let timestamp = self.clock.now();
+        self.cast_vote(proposal_id, voter, in_favor, weight, timestamp, None)
+    }
+
+    /// Records a vote the voter signed at `timestamp`, which must fall in
+    /// the voting period, keeping the signature for peers. Signed times
+    /// are whole seconds, so they are compared to the second.
+    pub fn cast_signed_vote(&mut self, proposal_id: &str, voter: String, in_favor: bool, weight: f64, timestamp: DateTime<Utc>, signature: Vec<u8>) -> IcnResult<()> {
+        let proposal = self.get_proposal(proposal_id)?;
+        let signed_at = timestamp.timestamp();
+        if signed_at < proposal.created_at.timestamp() || signed_at > proposal.voting_ends_at.timestamp() {
+            return Err(IcnError::Governance("Vote is timestamped outside the voting period".into()));
+        }
+        self.cast_vote(proposal_id, voter, in_favor, weight, timestamp, Some(signature))
+    }
+
+    fn cast_vote(&mut self, proposal_id: &str, voter: String, in_favor: bool, weight: f64, timestamp: DateTime<Utc>, signature: Option<Vec<u8>>) -> IcnResult<()> {
+        let members = self.bundle_members(proposal_id)?;
+        for member in &members {
+            self.check_vote(member, &voter)?;
+        }
+
+        for member in &members {
+            let weight = self.snapshot_weight(member, &voter)?.unwrap_or(weight);
+            let (eligibility_proof, weight_opening) = WeightRangeProof::prove(member, &voter, weight)?;
+            self.votes.get_mut(member)
+                .ok_or_else(|| IcnError::Governance("Votes not found for proposal".into()))?
+                .push(Vote {
+                    voter: voter.clone(),
+                    proposal_id: member.clone(),
+                    in_favor,
+                    weight,
+                    timestamp,
+                    eligibility_proof,
+                    weight_opening,
+                    signature: signature.clone(),
+                });
+        }
+        Ok(())
+    }
+
+    /// Checks that `voter` may still vote on a proposal.
+    fn check_vote(&self, proposal_id: &str, voter: &str) -> IcnResult<()> {
+        self.snapshot_weight(proposal_id, voter)?;
+        let proposal = self.get_proposal(proposal_id)?;
 
         if proposal.status != ProposalStatus::Active {
             return Err(IcnError::Governance("Proposal is not active".into()));
         }
 
-        if Utc::now() > proposal.voting_ends_at {
+        if proposal.ballot.is_multi_option() {
+            return Err(IcnError::Governance("Multi-option proposals take ballots, not yes/no votes".into()));
+        }
+
+        if self.clock.now() > proposal.voting_ends_at {
             return Err(IcnError::Governance("Voting period has ended".into()));
         }
 
-        let votes = self.votes.get_mut(proposal_id)
+        if let Some(eligible_voters) = self.proposal_policies.get(proposal_id).and_then(|p| p.eligible_voters.as_ref()) {
+            if !eligible_voters.iter().any(|eligible| eligible == voter) {
+                return Err(IcnError::Governance("Voter is not eligible for this proposal".into()));
+            }
+        }
+
+        let votes = self.votes.get(proposal_id)
             .ok_or_else(|| IcnError::Governance("Votes not found for proposal".into()))?;
 
         if votes.iter().any(|v| v.voter == voter) {
             return Err(IcnError::Governance("Voter has already voted on this proposal".into()));
         }
-
-        votes.push(Vote {
-            voter,
-            proposal_id: proposal_id.to_string(),
-            in_favor,
-            weight,
-            timestamp: Utc::now(),
-        });
-
         Ok(())
     }
 
+    /// Decides a proposal once its voting period has ended. A bundle is
+    /// decided as a whole once every proposal in it has reached this node
+    /// and closed.
     pub fn finalize_proposal(&mut self, proposal_id: &str) -> IcnResult<ProposalStatus> {
-        let proposal = self.proposals.get_mut(proposal_id)
-            .ok_or_else(|| IcnError::Governance("Proposal not found".into()))?;
+        let members = self.bundle_members(proposal_id)?;
+        for member in &members {
+            let proposal = self.get_proposal(member)?;
 
-        if proposal.status != ProposalStatus::Active {
-            return Err(IcnError::Governance("Proposal is not active".into()));
+            if proposal.status != ProposalStatus::Active {
+                return Err(IcnError::Governance("Proposal is not active".into()));
+            }
+
+            if self.clock.now() < proposal.voting_ends_at {
+                return Err(IcnError::Governance("Voting period has not ended yet".into()));
+            }
+        }
+
+        let status = self.decide_members(&members)?
+            .ok_or_else(|| IcnError::Governance("Not every proposal in the bundle has arrived".into()))?;
+        for member in &members {
+            self.set_status(member, status.clone())?;
+            if status == ProposalStatus::Passed {
+                self.start_timelock(member)?;
+            }
         }
+        Ok(status)
+    }
 
-        if Utc::now() < proposal.voting_ends_at {
-            return Err(IcnError::Governance("Voting period has not ended yet".into()));
+    /// Adds a proposal received from a peer. Returns true if local state changed.
+    ///
+    /// Two different proposals with the same id are resolved in favor of the
+    /// one created first, with ties broken by proposer and then title, so
+    /// every node keeps the same one. Votes cast on the losing proposal are
+    /// dropped. A proposal that is no longer active is never replaced.
+    pub fn apply_remote_proposal(&mut self, mut proposal: Proposal) -> IcnResult<bool> {
+        proposal.status = ProposalStatus::Active;
+        proposal.execution_timestamp = None;
+
+        let existing = match self.proposals.get(&proposal.id) {
+            Some(existing) => existing,
+            None => {
+                self.create_proposal(proposal)?;
+                return Ok(true);
+            }
+        };
+
+        let incoming_key = proposal_precedence(&proposal);
+        let existing_key = proposal_precedence(existing);
+        if incoming_key >= existing_key {
+            return Ok(false);
+        }
+        if existing.status != ProposalStatus::Active {
+            warn!("Keeping proposal {} despite a conflicting earlier version: it is already {:?}", proposal.id, existing.status);
+            return Ok(false);
         }
 
-        let votes = self.votes.get(proposal_id)
+        let proposal_id = proposal.id.clone();
+        self.proposals.remove(&proposal_id);
+        self.votes.remove(&proposal_id);
+        self.ballots.remove(&proposal_id);
+        self.proposal_policies.remove(&proposal_id);
+        self.snapshots.remove(&proposal_id);
+        self.create_proposal(proposal)?;
+        Ok(true)
+    }
+
+    /// Records a vote received from a peer, with the weight this node derived
+    /// for the voter. Returns true if local state changed.
+    ///
+    /// Votes cast after the voting period are rejected using the vote's own
+    /// timestamp, so late delivery does not matter. If a voter appears with
+    /// two different votes, the earlier one wins, and "against" wins a tie.
+    /// Votes that arrive after this node finalized the proposal are still
+    /// merged and the result is recalculated; executed proposals are final.
+    /// Like local votes, a vote on a bundled proposal counts for the whole
+    /// bundle.
+    pub fn apply_remote_vote(&mut self, proposal_id: &str, voter: String, in_favor: bool, weight: f64, timestamp: DateTime<Utc>, signature: Option<Vec<u8>>) -> IcnResult<bool> {
+        let members: Vec<String> = self.bundle_members(proposal_id)?.into_iter()
+            .filter(|member| self.proposals.contains_key(member))
+            .collect();
+        let mut changed = false;
+        for member in &members {
+            changed |= self.apply_remote_member_vote(member, voter.clone(), in_favor, weight, timestamp, signature.clone())?;
+        }
+
+        if changed && self.get_proposal(proposal_id)?.status != ProposalStatus::Active {
+            if let Some(recalculated) = self.decide_members(&self.bundle_members(proposal_id)?)? {
+                for member in &members {
+                    self.set_status(member, recalculated.clone())?;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    fn apply_remote_member_vote(&mut self, proposal_id: &str, voter: String, in_favor: bool, weight: f64, timestamp: DateTime<Utc>, signature: Option<Vec<u8>>) -> IcnResult<bool> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.status == ProposalStatus::Executed {
+            return Err(IcnError::Governance("Proposal has already been executed".into()));
+        }
+        if timestamp > proposal.voting_ends_at {
+            return Err(IcnError::Governance("Vote was cast after the voting period".into()));
+        }
+        if let Some(eligible_voters) = self.proposal_policies.get(proposal_id).and_then(|p| p.eligible_voters.as_ref()) {
+            if !eligible_voters.contains(&voter) {
+                return Err(IcnError::Governance("Voter is not eligible for this proposal".into()));
+            }
+        }
+        let weight = self.snapshot_weight(proposal_id, &voter)?.unwrap_or(weight);
+
+        let votes = self.votes.get_mut(proposal_id)
             .ok_or_else(|| IcnError::Governance("Votes not found for proposal".into()))?;
+        let existing = votes.iter().position(|v| v.voter == voter);
+        if let Some(index) = existing {
+            if vote_precedence(timestamp, in_favor) >= vote_precedence(votes[index].timestamp, votes[index].in_favor) {
+                return Ok(false);
+            }
+        }
+
+        let (eligibility_proof, weight_opening) = WeightRangeProof::prove(proposal_id, &voter, weight)?;
+        let vote = Vote {
+            voter,
+            proposal_id: proposal_id.to_string(),
+            in_favor,
+            weight,
+            timestamp,
+            eligibility_proof,
+            weight_opening,
+            signature,
+        };
+        match existing {
+            Some(index) => votes[index] = vote,
+            None => votes.push(vote),
+        }
+        Ok(true)
+    }
 
-        let total_votes: f64 = votes.iter().map(|v| v.weight).sum();
-        let votes_in_favor: f64 = votes.iter().filter(|v| v.in_favor).map(|v| v.weight).sum();
+    /// Handles a peer announcing that a proposal was finalized. The proposal
+    /// is finalized here from local votes once the voting period has ended
+    /// locally too; otherwise it stays active until it does.
+    pub fn apply_remote_finalization(&mut self, proposal_id: &str) -> IcnResult<ProposalStatus> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.status == ProposalStatus::Active && self.clock.now() >= proposal.voting_ends_at {
+            return self.finalize_proposal(proposal_id);
+        }
+        Ok(proposal.status.clone())
+    }
+
+    /// Works out whether a proposal passes with the votes recorded so far.
+    fn decide(&self, proposal_id: &str) -> IcnResult<ProposalStatus> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.ballot.is_multi_option() {
+            return self.decide_options(proposal_id);
+        }
+        let approval_threshold = self.approval_threshold(proposal_id)?;
+        let (votes_in_favor, total_votes) = Self::tally(self.get_votes(proposal_id)?);
 
         if total_votes < proposal.required_quorum {
-            proposal.status = ProposalStatus::Rejected;
-        } else if votes_in_favor / total_votes > 0.5 {
-            proposal.status = ProposalStatus::Passed;
+            Ok(ProposalStatus::Rejected)
+        } else if votes_in_favor / total_votes > approval_threshold {
+            Ok(ProposalStatus::Passed)
         } else {
-            proposal.status = ProposalStatus::Rejected;
+            Ok(ProposalStatus::Rejected)
         }
+    }
 
-        Ok(proposal.status.clone())
+    /// Share of the weighted vote a proposal must exceed to pass, raised to a
+    /// supermajority for emergency pauses and vetoes.
+    fn approval_threshold(&self, proposal_id: &str) -> IcnResult<f64> {
+        let proposal = self.get_proposal(proposal_id)?;
+        let approval_threshold = self.proposal_policies.get(proposal_id)
+            .map(|p| p.approval_threshold)
+            .unwrap_or(self.default_policy.approval_threshold);
+
+        if proposal.actions.iter().any(|a| matches!(a, ProposalAction::SetEmergencyPause { .. })) {
+            return Ok(approval_threshold.max(EMERGENCY_SUPERMAJORITY));
+        }
+        if timelock::veto_targets(proposal).next().is_some() {
+            return Ok(approval_threshold.max(VETO_SUPERMAJORITY));
+        }
+        Ok(approval_threshold)
+    }
+
+    fn set_status(&mut self, proposal_id: &str, status: ProposalStatus) -> IcnResult<()> {
+        self.proposals.get_mut(proposal_id)
+            .ok_or_else(|| IcnError::Governance("Proposal not found".into()))?
+            .status = status;
+        Ok(())
     }
 
     pub fn list_active_proposals(&self) -> Vec<&Proposal> {
@@ -144,6 +580,13 @@ impl GovernanceSystem {
             .collect()
     }
 
+    /// The status of every live proposal, keyed by id.
+    pub fn proposal_statuses(&self) -> HashMap<String, ProposalStatus> {
+        self.proposals.iter()
+            .map(|(id, proposal)| (id.clone(), proposal.status.clone()))
+            .collect()
+    }
+
     pub fn mark_as_executed(&mut self, proposal_id: &str) -> IcnResult<()> {
         let proposal = self.proposals.get_mut(proposal_id)
             .ok_or_else(|| IcnError::Governance("Proposal not found".into()))?;
@@ -153,48 +596,90 @@ impl GovernanceSystem {
         }
 
         proposal.status = ProposalStatus::Executed;
-        proposal.execution_timestamp = Some(Utc::now());
+        proposal.execution_timestamp = Some(self.clock.now());
         Ok(())
     }
 
+    /// Executes a passed proposal, or every proposal of its bundle in
+    /// order, once the proposals they depend on have been executed and
+    /// their timelocks have run out.
     pub fn execute_proposal(&mut self, proposal_id: &str) -> IcnResult<()> {
-        let proposal = self.proposals.get_mut(proposal_id)
-            .ok_or_else(|| IcnError::Governance("Proposal not found".into()))?;
-
-        if proposal.status != ProposalStatus::Passed {
-            return Err(IcnError::Governance("Proposal has not passed".into()));
-        }
-
-        match proposal.proposal_type {
-            ProposalType::Constitutional => self.execute_constitutional_proposal(proposal),
-            ProposalType::EconomicAdjustment => self.execute_economic_adjustment_proposal(proposal),
-            ProposalType::NetworkUpgrade => self.execute_network_upgrade_proposal(proposal),
-        }?;
+        for member in self.execution_order(proposal_id)? {
+            let proposal = self.get_proposal(&member)?.clone();
 
-        proposal.status = ProposalStatus::Executed;
-        proposal.execution_timestamp = Some(Utc::now());
+            match proposal.proposal_type {
+                ProposalType::Constitutional => self.execute_constitutional_proposal(&proposal),
+                ProposalType::EconomicAdjustment => self.execute_economic_adjustment_proposal(&proposal),
+                ProposalType::NetworkUpgrade => self.execute_network_upgrade_proposal(&proposal),
+            }?;
+            self.apply_vetoes(&proposal)?;
 
+            self.mark_as_executed(&member)?;
+        }
         Ok(())
     }
 
-    fn execute_constitutional_proposal(&self, proposal: &Proposal) -> IcnResult<()> {
-        // Implementation for constitutional changes
+    fn execute_constitutional_proposal(&mut self, proposal: &Proposal) -> IcnResult<()> {
         println!("Executing constitutional proposal: {}", proposal.title);
-        // Add logic for updating constitutional parameters
+        for action in &proposal.actions {
+            match action {
+                ProposalAction::UpdatePolicy { proposal_type, category, policy } => {
+                    self.policies.insert((proposal_type.clone(), category.clone()), policy.clone());
+                }
+                // These act on node state and are applied by the node
+                ProposalAction::SetEmergencyPause { .. }
+                | ProposalAction::UpgradeContract { .. }
+                | ProposalAction::UpdateAlertThresholds { .. }
+                | ProposalAction::SetParameter { .. }
+                | ProposalAction::SetContractPermissions { .. }
+                | ProposalAction::SetIssuanceRate { .. }
+                | ProposalAction::FundProject { .. }
+                | ProposalAction::ReshardNetwork { .. }
+                | ProposalAction::FreezeAccount { .. }
+                | ProposalAction::UnfreezeAccount { .. }
+                | ProposalAction::SetMembershipClass { .. }
+                | ProposalAction::OpenGrantRound { .. }
+                | ProposalAction::SetExchangeRate { .. }
+                | ProposalAction::SetRateOracle { .. } => {}
+                // Vetoes are applied for every proposal type
+                ProposalAction::VetoProposal { .. } => {}
+            }
+        }
         Ok(())
     }
 
     fn execute_economic_adjustment_proposal(&self, proposal: &Proposal) -> IcnResult<()> {
         // Implementation for economic adjustments
         println!("Executing economic adjustment proposal: {}", proposal.title);
-        // Add logic for adjusting economic parameters
+        // Fee, issuance and contribution settings change when the node
+        // applies the proposal's actions after this returns
         Ok(())
     }
 
     fn execute_network_upgrade_proposal(&self, proposal: &Proposal) -> IcnResult<()> {
         // Implementation for network upgrades
         println!("Executing network upgrade proposal: {}", proposal.title);
-        // Add logic for implementing network upgrades
+        // Difficulty and committee size changes, and any reshard, are left
+        // to the node, which saves the parameter registry to storage
+        Ok(())
+    }
+
+    fn validate_policy(policy: &GovernancePolicy) -> IcnResult<()> {
+        if policy.quorum < 0.0 {
+            return Err(IcnError::Governance("Policy quorum cannot be negative".into()));
+        }
+        if !(0.0..1.0).contains(&policy.approval_threshold) {
+            return Err(IcnError::Governance("Approval threshold must be between 0 and 1".into()));
+        }
+        if policy.voting_duration_secs <= 0 {
+            return Err(IcnError::Governance("Voting duration must be positive".into()));
+        }
+        if policy.reputation_window_secs.is_some_and(|window| window <= 0) {
+            return Err(IcnError::Governance("Reputation window must be positive".into()));
+        }
+        if policy.timelock_secs < 0 {
+            return Err(IcnError::Governance("Timelock cannot be negative".into()));
+        }
         Ok(())
     }
 
@@ -204,13 +689,53 @@ impl GovernanceSystem {
     }
 
     pub fn get_proposal_result(&self, proposal_id: &str) -> IcnResult<(f64, f64)> {
-        let votes = self.get_votes(proposal_id)?;
-        let total_votes: f64 = votes.iter().map(|v| v.weight).sum();
-        let votes_in_favor: f64 = votes.iter().filter(|v| v.in_favor).map(|v| v.weight).sum();
-        Ok((votes_in_favor, total_votes))
+        Ok(Self::tally(self.get_votes(proposal_id)?))
+    }
+
+    pub fn get_vote_receipt(&self, proposal_id: &str, voter: &str) -> IcnResult<VoteReceipt> {
+        let vote = self.get_votes(proposal_id)?
+            .iter()
+            .find(|v| v.voter == voter)
+            .ok_or_else(|| IcnError::Governance("Vote not found".into()))?;
+        Ok(VoteReceipt {
+            voter: vote.voter.clone(),
+            proposal_id: vote.proposal_id.clone(),
+            in_favor: vote.in_favor,
+            timestamp: vote.timestamp,
+            eligibility_proof: vote.eligibility_proof.clone(),
+        })
+    }
+
+    /// Sums weights in favor and in total, leaving out votes whose
+    /// eligibility proof does not verify.
+    fn tally(votes: &[Vote]) -> (f64, f64) {
+        let mut in_favor = 0.0;
+        let mut total = 0.0;
+        for vote in votes {
+            if !vote.is_eligibility_verified() {
+                warn!("Ignoring vote by {} on {}: eligibility proof failed", vote.voter, vote.proposal_id);
+                continue;
+            }
+            total += vote.weight;
+            if vote.in_favor {
+                in_favor += vote.weight;
+            }
+        }
+        (in_favor, total)
     }
 }
 
+/// Ordering used to pick between two proposals that share an id; lower wins.
+fn proposal_precedence(proposal: &Proposal) -> (DateTime<Utc>, &str, &str) {
+    (proposal.created_at, &proposal.proposer, &proposal.title)
+}
+
+/// Ordering used to pick between two votes by the same voter; lower wins.
+/// Timestamps are compared in whole seconds, the precision votes are gossiped with.
+fn vote_precedence(timestamp: DateTime<Utc>, in_favor: bool) -> (i64, bool) {
+    (timestamp.timestamp(), in_favor)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -228,9 +753,20 @@ mod tests {
             category: ProposalCategory::Economic,
             required_quorum: 0.5,
             execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
         }
     }
 
+    fn end_voting(gov_system: &mut GovernanceSystem, proposal_id: &str) {
+        let proposal = gov_system.proposals.get_mut(proposal_id).unwrap();
+        proposal.voting_ends_at = Utc::now() - Duration::hours(1);
+    }
+
     #[test]
     fn test_create_proposal() {
         let mut gov_system = GovernanceSystem::new();
@@ -256,16 +792,31 @@ mod tests {
         assert!(gov_system.vote_on_proposal("non_existent", "Charlie".to_string(), true, 1.0).is_err());
     }
 
+    #[test]
+    fn test_signed_votes_must_fall_in_the_voting_period() {
+        let mut gov_system = GovernanceSystem::new();
+        let proposal = create_test_proposal();
+        let created_at = proposal.created_at;
+        gov_system.create_proposal(proposal).unwrap();
+
+        assert!(gov_system.cast_signed_vote("test_proposal", "Alice".to_string(), true, 1.0, created_at - Duration::hours(1), vec![1]).is_err());
+        assert!(gov_system.cast_signed_vote("test_proposal", "Alice".to_string(), true, 1.0, created_at + Duration::days(8), vec![1]).is_err());
+        gov_system.cast_signed_vote("test_proposal", "Alice".to_string(), true, 1.0, created_at + Duration::hours(1), vec![1]).unwrap();
+        let vote = &gov_system.get_votes("test_proposal").unwrap()[0];
+        assert_eq!(vote.timestamp, created_at + Duration::hours(1));
+        assert_eq!(vote.signature, Some(vec![1]));
+    }
+
     #[test]
     fn test_finalize_proposal() {
         let mut gov_system = GovernanceSystem::new();
-        let mut proposal = create_test_proposal();
-        proposal.voting_ends_at = Utc::now() - Duration::hours(1); // Set voting period to have ended
+        let proposal = create_test_proposal();
         gov_system.create_proposal(proposal).unwrap();
 
         gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), true, 1.0).unwrap();
         gov_system.vote_on_proposal("test_proposal", "Bob".to_string(), true, 1.0).unwrap();
         gov_system.vote_on_proposal("test_proposal", "Charlie".to_string(), false, 1.0).unwrap();
+        end_voting(&mut gov_system, "test_proposal");
 
         let result = gov_system.finalize_proposal("test_proposal").unwrap();
         assert_eq!(result, ProposalStatus::Passed);
@@ -274,6 +825,22 @@ mod tests {
         assert!(gov_system.finalize_proposal("test_proposal").is_err());
     }
 
+    #[test]
+    fn test_manual_clock_runs_out_the_voting_period() {
+        use icn_common::{Clock, ManualClock};
+        let clock = ManualClock::new(Utc::now());
+        let mut gov_system = GovernanceSystem::new().with_clock(clock.shared());
+        gov_system.create_proposal(create_test_proposal()).unwrap();
+        gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), true, 1.0).unwrap();
+        assert!(gov_system.finalize_proposal("test_proposal").is_err());
+
+        clock.advance(Duration::days(8));
+        assert!(gov_system.vote_on_proposal("test_proposal", "Bob".to_string(), false, 1.0).is_err());
+        assert_eq!(gov_system.finalize_proposal("test_proposal").unwrap(), ProposalStatus::Passed);
+        gov_system.mark_as_executed("test_proposal").unwrap();
+        assert_eq!(gov_system.get_proposal("test_proposal").unwrap().execution_timestamp, Some(clock.now()));
+    }
+
     #[test]
     fn test_list_active_proposals() {
         let mut gov_system = GovernanceSystem::new();
@@ -331,13 +898,16 @@ mod tests {
     #[test]
     fn test_proposal_quorum() {
         let mut gov_system = GovernanceSystem::new();
-        let mut proposal = create_test_proposal();
-        proposal.required_quorum = 5.0;
-        proposal.voting_ends_at = Utc::now() - Duration::hours(1);
+        gov_system.policies.insert(
+            (ProposalType::Constitutional, None),
+            GovernancePolicy { quorum: 5.0, ..GovernancePolicy::default() },
+        );
+        let proposal = create_test_proposal();
         gov_system.create_proposal(proposal).unwrap();
 
         gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), true, 2.0).unwrap();
         gov_system.vote_on_proposal("test_proposal", "Bob".to_string(), true, 2.0).unwrap();
+        end_voting(&mut gov_system, "test_proposal");
 
         let result = gov_system.finalize_proposal("test_proposal").unwrap();
         assert_eq!(result, ProposalStatus::Rejected); // Rejected due to not meeting quorum
@@ -345,12 +915,11 @@ mod tests {
         // Test with meeting quorum
         let mut proposal2 = create_test_proposal();
         proposal2.id = "test_proposal_2".to_string();
-        proposal2.required_quorum = 5.0;
-        proposal2.voting_ends_at = Utc::now() - Duration::hours(1);
         gov_system.create_proposal(proposal2).unwrap();
 
         gov_system.vote_on_proposal("test_proposal_2", "Alice".to_string(), true, 3.0).unwrap();
         gov_system.vote_on_proposal("test_proposal_2", "Bob".to_string(), true, 3.0).unwrap();
+        end_voting(&mut gov_system, "test_proposal_2");
 
         let result2 = gov_system.finalize_proposal("test_proposal_2").unwrap();
         assert_eq!(result2, ProposalStatus::Passed); // Passed due to meeting quorum and majority
@@ -384,8 +953,7 @@ mod tests {
         gov_system.vote_on_proposal(&proposal_id, "Bob".to_string(), true, 0.3).unwrap();
 
         // Fast-forward time to end voting period
-        let proposal = gov_system.proposals.get_mut(&proposal_id).unwrap();
-        proposal.voting_ends_at = Utc::now() - Duration::hours(1);
+        end_voting(&mut gov_system, &proposal_id);
 
         // Finalize the proposal
         let status = gov_system.finalize_proposal(&proposal_id).unwrap();
@@ -422,13 +990,13 @@ mod tests {
 
         for proposal_type in proposal_types {
             let mut proposal = create_test_proposal();
+            proposal.id = format!("{:?}", proposal_type);
             proposal.proposal_type = proposal_type.clone();
             let proposal_id = gov_system.create_proposal(proposal).unwrap();
 
             // Simulate voting and passing
             gov_system.vote_on_proposal(&proposal_id, "Alice".to_string(), true, 0.6).unwrap();
-            let proposal = gov_system.proposals.get_mut(&proposal_id).unwrap();
-            proposal.voting_ends_at = Utc::now() - Duration::hours(1);
+            end_voting(&mut gov_system, &proposal_id);
             gov_system.finalize_proposal(&proposal_id).unwrap();
 
             // Execute the proposal
@@ -439,4 +1007,243 @@ mod tests {
             assert_eq!(executed_proposal.status, ProposalStatus::Executed);
         }
     }
+
+    #[test]
+    fn test_policy_update_through_constitutional_proposal() {
+        let mut gov_system = GovernanceSystem::new();
+        let strict_policy = GovernancePolicy {
+            quorum: 2.0,
+            approval_threshold: 0.66,
+            voting_duration_secs: 3 * 24 * 60 * 60,
+            eligible_voters: Some(vec!["Alice".to_string(), "Bob".to_string()]),
+            reputation_window_secs: None,
+            hide_voters_until_finalized: false,
+            timelock_secs: 0,
+        };
+        let update = ProposalAction::UpdatePolicy {
+            proposal_type: ProposalType::NetworkUpgrade,
+            category: Some(ProposalCategory::Technical),
+            policy: strict_policy.clone(),
+        };
+
+        // Policy changes are rejected on non-constitutional proposals
+        let mut economic = create_test_proposal();
+        economic.id = "economic".to_string();
+        economic.proposal_type = ProposalType::EconomicAdjustment;
+        economic.actions = vec![update.clone()];
+        assert!(gov_system.create_proposal(economic).is_err());
+
+        let mut constitutional = create_test_proposal();
+        constitutional.actions = vec![update];
+        let proposal_id = gov_system.create_proposal(constitutional).unwrap();
+        gov_system.vote_on_proposal(&proposal_id, "Alice".to_string(), true, 1.0).unwrap();
+        end_voting(&mut gov_system, &proposal_id);
+        assert_eq!(gov_system.finalize_proposal(&proposal_id).unwrap(), ProposalStatus::Passed);
+        gov_system.execute_proposal(&proposal_id).unwrap();
+
+        assert_eq!(gov_system.get_policy(&ProposalType::NetworkUpgrade, &ProposalCategory::Technical), &strict_policy);
+        assert_eq!(gov_system.get_policy(&ProposalType::NetworkUpgrade, &ProposalCategory::Social), &GovernancePolicy::default());
+
+        // New proposals pick up the updated policy
+        let mut upgrade = create_test_proposal();
+        upgrade.id = "upgrade".to_string();
+        upgrade.proposal_type = ProposalType::NetworkUpgrade;
+        upgrade.category = ProposalCategory::Technical;
+        gov_system.create_proposal(upgrade).unwrap();
+        let upgrade = gov_system.get_proposal("upgrade").unwrap();
+        assert_eq!(upgrade.required_quorum, 2.0);
+        assert_eq!(upgrade.voting_ends_at - upgrade.created_at, Duration::days(3));
+
+        assert!(gov_system.vote_on_proposal("upgrade", "Charlie".to_string(), true, 1.0).is_err());
+        gov_system.vote_on_proposal("upgrade", "Alice".to_string(), true, 1.3).unwrap();
+        gov_system.vote_on_proposal("upgrade", "Bob".to_string(), false, 0.7).unwrap();
+        end_voting(&mut gov_system, "upgrade");
+
+        // 65% in favor does not clear the 66% threshold
+        assert_eq!(gov_system.finalize_proposal("upgrade").unwrap(), ProposalStatus::Rejected);
+    }
+
+    #[test]
+    fn test_emergency_pause_requires_supermajority() {
+        let mut gov_system = GovernanceSystem::new();
+        let pause = ProposalAction::SetEmergencyPause { paused: true, reason: "Exploit in progress".to_string() };
+
+        let mut proposal = create_test_proposal();
+        proposal.actions = vec![ProposalAction::SetEmergencyPause { paused: true, reason: " ".to_string() }];
+        assert!(gov_system.create_proposal(proposal).is_err());
+
+        // 60% in favor passes a normal proposal but not a pause
+        let mut proposal = create_test_proposal();
+        proposal.actions = vec![pause.clone()];
+        let proposal_id = gov_system.create_proposal(proposal).unwrap();
+        gov_system.vote_on_proposal(&proposal_id, "Alice".to_string(), true, 0.6).unwrap();
+        gov_system.vote_on_proposal(&proposal_id, "Bob".to_string(), false, 0.4).unwrap();
+        end_voting(&mut gov_system, &proposal_id);
+        assert_eq!(gov_system.finalize_proposal(&proposal_id).unwrap(), ProposalStatus::Rejected);
+
+        let mut proposal = create_test_proposal();
+        proposal.id = "pause".to_string();
+        proposal.actions = vec![pause];
+        gov_system.create_proposal(proposal).unwrap();
+        gov_system.vote_on_proposal("pause", "Alice".to_string(), true, 0.7).unwrap();
+        gov_system.vote_on_proposal("pause", "Bob".to_string(), false, 0.3).unwrap();
+        end_voting(&mut gov_system, "pause");
+        assert_eq!(gov_system.finalize_proposal("pause").unwrap(), ProposalStatus::Passed);
+    }
+
+    #[test]
+    fn test_reshard_requires_network_upgrade() {
+        let mut gov_system = GovernanceSystem::new();
+        let mut proposal = create_test_proposal();
+        proposal.actions = vec![ProposalAction::ReshardNetwork { shard_count: 8, activation_height: 100 }];
+        assert!(gov_system.create_proposal(proposal.clone()).is_err());
+
+        proposal.proposal_type = ProposalType::NetworkUpgrade;
+        proposal.actions = vec![ProposalAction::ReshardNetwork { shard_count: 0, activation_height: 100 }];
+        assert!(gov_system.create_proposal(proposal.clone()).is_err());
+
+        proposal.actions = vec![ProposalAction::ReshardNetwork { shard_count: 8, activation_height: 100 }];
+        gov_system.create_proposal(proposal).unwrap();
+    }
+
+    #[test]
+    fn test_freeze_actions_are_bounded() {
+        let mut gov_system = GovernanceSystem::new();
+        let mut proposal = create_test_proposal();
+        let freeze = |duration_secs, reason: &str| ProposalAction::FreezeAccount {
+            address: "Mallory".to_string(),
+            duration_secs,
+            reason: reason.to_string(),
+        };
+        for action in [freeze(0, "Fraud"), freeze(MAX_FREEZE_DURATION_SECS + 1, "Fraud"), freeze(3600, "")] {
+            proposal.actions = vec![action];
+            assert!(gov_system.create_proposal(proposal.clone()).is_err());
+        }
+        proposal.actions = vec![freeze(3600, "Fraud")];
+        gov_system.create_proposal(proposal).unwrap();
+    }
+
+    #[test]
+    fn test_votes_with_invalid_eligibility_proof_are_not_counted() {
+        let mut gov_system = GovernanceSystem::new();
+        gov_system.create_proposal(create_test_proposal()).unwrap();
+        gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), true, 3.0).unwrap();
+        gov_system.vote_on_proposal("test_proposal", "Bob".to_string(), false, 1.0).unwrap();
+
+        let receipt = gov_system.get_vote_receipt("test_proposal", "Alice").unwrap();
+        assert!(receipt.eligibility_proof.verify("test_proposal", "Alice").is_ok());
+        assert!(receipt.eligibility_proof.covers(3.0));
+
+        // Inflating a weight beyond its proven range drops the vote from the tally
+        gov_system.votes.get_mut("test_proposal").unwrap()[1].weight = 10.0;
+        assert_eq!(gov_system.get_proposal_result("test_proposal").unwrap(), (3.0, 3.0));
+        // So does changing it within the range, since the commitment is to the exact weight
+        gov_system.votes.get_mut("test_proposal").unwrap()[1].weight = 0.6;
+        assert!(gov_system.votes["test_proposal"][1].eligibility_proof.covers(0.6));
+        assert_eq!(gov_system.get_proposal_result("test_proposal").unwrap(), (3.0, 3.0));
+
+        end_voting(&mut gov_system, "test_proposal");
+        assert_eq!(gov_system.finalize_proposal("test_proposal").unwrap(), ProposalStatus::Passed);
+    }
+
+    #[test]
+    fn test_remote_votes_converge() {
+        let mut node_a = GovernanceSystem::new();
+        let mut node_b = GovernanceSystem::new();
+        let proposal = create_test_proposal();
+        node_a.create_proposal(proposal.clone()).unwrap();
+        assert!(node_b.apply_remote_proposal(proposal.clone()).unwrap());
+        assert!(!node_b.apply_remote_proposal(proposal.clone()).unwrap());
+
+        // Alice's conflicting votes reach the two nodes in opposite orders
+        let earlier = Utc::now() - Duration::hours(3);
+        let later = Utc::now() - Duration::hours(2);
+        node_a.apply_remote_vote("test_proposal", "Alice".to_string(), true, 1.0, later, None).unwrap();
+        node_a.apply_remote_vote("test_proposal", "Alice".to_string(), false, 1.0, earlier, None).unwrap();
+        node_b.apply_remote_vote("test_proposal", "Alice".to_string(), false, 1.0, earlier, None).unwrap();
+        assert!(!node_b.apply_remote_vote("test_proposal", "Alice".to_string(), true, 1.0, later, None).unwrap());
+        assert!(!node_a.get_votes("test_proposal").unwrap()[0].in_favor);
+        assert!(!node_b.get_votes("test_proposal").unwrap()[0].in_favor);
+
+        // A vote that reaches node B only after it finalized still counts
+        node_a.apply_remote_vote("test_proposal", "Bob".to_string(), true, 3.0, later, None).unwrap();
+        end_voting(&mut node_a, "test_proposal");
+        end_voting(&mut node_b, "test_proposal");
+        assert_eq!(node_a.finalize_proposal("test_proposal").unwrap(), ProposalStatus::Passed);
+        assert_eq!(node_b.apply_remote_finalization("test_proposal").unwrap(), ProposalStatus::Rejected);
+        assert!(node_b.apply_remote_vote("test_proposal", "Bob".to_string(), true, 3.0, later, None).unwrap());
+        assert_eq!(node_b.get_proposal("test_proposal").unwrap().status, ProposalStatus::Passed);
+
+        assert!(node_b.apply_remote_vote("test_proposal", "Carol".to_string(), true, 1.0, Utc::now(), None).is_err());
+    }
+
+    #[test]
+    fn test_conflicting_proposals_resolve_to_earliest() {
+        let mut gov_system = GovernanceSystem::new();
+        let mut later = create_test_proposal();
+        later.title = "Later".to_string();
+        let mut earlier = later.clone();
+        earlier.title = "Earlier".to_string();
+        earlier.created_at = later.created_at - Duration::seconds(30);
+
+        gov_system.apply_remote_proposal(later.clone()).unwrap();
+        gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), true, 1.0).unwrap();
+        assert!(gov_system.apply_remote_proposal(earlier).unwrap());
+        assert!(!gov_system.apply_remote_proposal(later).unwrap());
+
+        assert_eq!(gov_system.get_proposal("test_proposal").unwrap().title, "Earlier");
+        assert!(gov_system.get_votes("test_proposal").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_archive_and_restore_proposal() {
+        let mut gov_system = GovernanceSystem::new();
+        gov_system.create_proposal(create_test_proposal()).unwrap();
+        gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), false, 1.0).unwrap();
+        end_voting(&mut gov_system, "test_proposal");
+        assert_eq!(gov_system.finalize_proposal("test_proposal").unwrap(), ProposalStatus::Rejected);
+
+        let now = Utc::now();
+        assert!(gov_system.archivable_proposals(Duration::days(1), now).is_empty());
+        let archivable = gov_system.archivable_proposals(Duration::minutes(30), now);
+        assert_eq!(archivable.len(), 1);
+
+        let bytes = archivable[0].to_bytes().unwrap();
+        gov_system.mark_archived("test_proposal", archive_storage_key("test_proposal"), now).unwrap();
+        assert!(gov_system.get_proposal("test_proposal").is_err());
+        assert_eq!(gov_system.list_archived()[0].status, ProposalStatus::Rejected);
+        assert!(gov_system.create_proposal(create_test_proposal()).is_err());
+
+        // The index survives a restart through storage
+        let mut restarted = GovernanceSystem::new();
+        assert_eq!(restarted.restore_archive_index(&gov_system.archive_index_bytes().unwrap()).unwrap(), 1);
+        assert_eq!(restarted.get_archive_entry("test_proposal"), gov_system.get_archive_entry("test_proposal"));
+
+        gov_system.restore_archived(ArchivedProposal::from_bytes(&bytes).unwrap()).unwrap();
+        assert_eq!(gov_system.get_votes("test_proposal").unwrap().len(), 1);
+        assert!(gov_system.get_archive_entry("test_proposal").is_none());
+        assert!(gov_system.purge_archived("test_proposal").is_err());
+    }
+
+    #[test]
+    fn test_votes_count_with_snapshot_weights() {
+        let mut gov_system = GovernanceSystem::new();
+        let mut proposal = create_test_proposal();
+        proposal.weight_mode = VotingWeightMode::Reputation;
+        gov_system.create_proposal(proposal).unwrap();
+        let weights = vec![("Alice".to_string(), 3.0), ("Bob".to_string(), 2.0), ("Carol".to_string(), 0.0)];
+        gov_system.record_snapshot("test_proposal", weights, Utc::now()).unwrap();
+        assert!(gov_system.record_snapshot("test_proposal", Vec::new(), Utc::now()).is_err());
+
+        // Bob's reputation has since grown, but the snapshot is what counts
+        gov_system.vote_on_proposal("test_proposal", "Alice".to_string(), true, 3.0).unwrap();
+        gov_system.vote_on_proposal("test_proposal", "Bob".to_string(), false, 50.0).unwrap();
+        assert!(gov_system.vote_on_proposal("test_proposal", "Carol".to_string(), false, 5.0).is_err());
+        assert!(gov_system.apply_remote_vote("test_proposal", "Dave".to_string(), false, 5.0, Utc::now(), None).is_err());
+        assert_eq!(gov_system.get_proposal_result("test_proposal").unwrap(), (3.0, 5.0));
+        assert_eq!(gov_system.get_snapshot("test_proposal").unwrap().weights.len(), 2);
+
+        end_voting(&mut gov_system, "test_proposal");
+        assert_eq!(gov_system.finalize_proposal("test_proposal").unwrap(), ProposalStatus::Passed);
+    }
 }
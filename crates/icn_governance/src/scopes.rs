@@ -0,0 +1,144 @@
+// File: crates/icn_governance/src/scopes.rs
+
+use crate::{GovernanceSystem, Proposal};
+use icn_common::{IcnError, IcnResult, GovernancePolicy, ProposalAction, ProposalScope};
+use serde::{Serialize, Deserialize};
+
+/// Who belongs to a DAO or shard and the rules its proposals are decided
+/// by. The node keeps these current from its cooperatives and shard map.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ScopeRules {
+    pub members: Vec<String>,
+    /// Minimum total vote weight, replacing the policy's quorum when set.
+    pub quorum: Option<f64>,
+    /// Replaces the policy's approval threshold when set.
+    pub approval_threshold: Option<f64>,
+}
+
+impl GovernanceSystem {
+    /// Records the membership and rules of a DAO or shard. Proposals
+    /// already open keep the membership they were created with.
+    pub fn set_scope_rules(&mut self, scope: ProposalScope, rules: ScopeRules) -> IcnResult<()> {
+        if scope.is_global() {
+            return Err(IcnError::Governance("The global scope has no membership of its own".into()));
+        }
+        if rules.quorum.is_some_and(|quorum| !quorum.is_finite() || quorum < 0.0) {
+            return Err(IcnError::Governance("Scope quorum must be a non-negative number".into()));
+        }
+        if rules.approval_threshold.is_some_and(|threshold| !(0.0..1.0).contains(&threshold)) {
+            return Err(IcnError::Governance("Approval threshold must be between 0 and 1".into()));
+        }
+        self.scope_rules.insert(scope, rules);
+        Ok(())
+    }
+
+    pub fn get_scope_rules(&self, scope: &ProposalScope) -> Option<&ScopeRules> {
+        self.scope_rules.get(scope)
+    }
+
+    /// Scoped proposals may only set their scope's own parameters, or a
+    /// DAO's membership classes and grant rounds; every other action
+    /// reaches beyond the DAO or shard.
+    pub(crate) fn check_scoped_actions(proposal: &Proposal) -> IcnResult<()> {
+        if proposal.scope.is_global() {
+            return Ok(());
+        }
+        if proposal.actions.iter().any(|action| !matches!(action, ProposalAction::SetParameter { .. } | ProposalAction::SetMembershipClass { .. } | ProposalAction::OpenGrantRound { .. })) {
+            return Err(IcnError::Governance(format!(
+                "Proposals scoped to {:?} can only change that scope's parameters, membership classes and grant rounds", proposal.scope
+            )));
+        }
+        Ok(())
+    }
+
+    /// Narrows a policy to the proposal's scope: only the scope's members
+    /// may vote, and its quorum and threshold apply where it sets them.
+    pub(crate) fn scoped_policy(&self, proposal: &Proposal, mut policy: GovernancePolicy) -> IcnResult<GovernancePolicy> {
+        if proposal.scope.is_global() {
+            return Ok(policy);
+        }
+        let rules = self.scope_rules.get(&proposal.scope)
+            .ok_or_else(|| IcnError::Governance(format!("No membership is known for {:?}", proposal.scope)))?;
+
+        let members = rules.members.iter()
+            .filter(|member| policy.eligible_voters.as_ref().is_none_or(|eligible| eligible.contains(member)))
+            .cloned()
+            .collect();
+        policy.eligible_voters = Some(members);
+        if let Some(quorum) = rules.quorum {
+            policy.quorum = quorum;
+        }
+        if let Some(approval_threshold) = rules.approval_threshold {
+            policy.approval_threshold = approval_threshold;
+        }
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BallotKind, ProposalCategory, ProposalStatus, ProposalType, VotingWeightMode};
+    use chrono::{Duration, Utc};
+
+    fn proposal(id: &str, scope: ProposalScope) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            proposer: "Alice".to_string(),
+            created_at: Utc::now(),
+            voting_ends_at: Utc::now(),
+            status: ProposalStatus::Active,
+            proposal_type: ProposalType::EconomicAdjustment,
+            category: ProposalCategory::Economic,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: vec![ProposalAction::SetParameter { name: "bread_price".to_string(), value: 3.0 }],
+            scope,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    #[test]
+    fn test_scoped_proposal_decided_by_members() {
+        let mut governance = GovernanceSystem::new();
+        let bakery = ProposalScope::Dao("bakery".to_string());
+        assert!(governance.create_proposal(proposal("p1", bakery.clone())).is_err());
+
+        let rules = ScopeRules {
+            members: vec!["Alice".to_string(), "Bob".to_string()],
+            quorum: Some(2.0),
+            approval_threshold: Some(0.6),
+        };
+        governance.set_scope_rules(bakery.clone(), rules).unwrap();
+        governance.create_proposal(proposal("p1", bakery.clone())).unwrap();
+        assert_eq!(governance.get_proposal("p1").unwrap().required_quorum, 2.0);
+
+        assert!(governance.vote_on_proposal("p1", "Carol".to_string(), true, 1.0).is_err());
+        governance.vote_on_proposal("p1", "Alice".to_string(), true, 1.0).unwrap();
+        governance.vote_on_proposal("p1", "Bob".to_string(), true, 1.0).unwrap();
+        governance.proposals.get_mut("p1").unwrap().voting_ends_at = Utc::now() - Duration::seconds(1);
+        assert_eq!(governance.finalize_proposal("p1").unwrap(), ProposalStatus::Passed);
+
+        // Network-wide actions are out of reach of a scoped proposal
+        let mut pause = proposal("p2", bakery.clone());
+        pause.actions = vec![ProposalAction::SetEmergencyPause { paused: true, reason: "Flour shortage".to_string() }];
+        assert!(governance.create_proposal(pause).is_err());
+
+        // Membership classes are the DAO's own, but change its constitution
+        let mut classes = proposal("p3", bakery.clone());
+        classes.actions = vec![ProposalAction::SetMembershipClass {
+            name: "supporter".to_string(), vote_multiplier: 0.5, can_propose: false, profit_share: 0.0,
+        }];
+        assert!(governance.create_proposal(classes.clone()).is_err());
+        classes.proposal_type = ProposalType::Constitutional;
+        governance.create_proposal(classes.clone()).unwrap();
+        classes.id = "p4".to_string();
+        classes.scope = ProposalScope::Global;
+        assert!(governance.create_proposal(classes).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// File: crates/icn_governance/src/simulation.rs
+
+use crate::{GovernanceSystem, ProposalStatus};
+use icn_common::{IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// Share of the electorate's weight assumed to vote when no earlier
+/// proposal has been decided to learn turnout from.
+pub const DEFAULT_TURNOUT: f64 = 0.5;
+
+/// A forecast of how an active proposal will be decided if turnout matches
+/// earlier proposals and the votes still to come split like those cast so far.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutcomeProjection {
+    pub proposal_id: String,
+    /// Voters who could still take part, counting those who already did.
+    pub eligible_voters: usize,
+    pub eligible_weight: f64,
+    pub weight_cast: f64,
+    pub weight_in_favor: f64,
+    /// Share of the electorate's weight expected to vote.
+    pub expected_turnout: f64,
+    /// Decided proposals the expected turnout was averaged over.
+    pub turnout_sample_size: usize,
+    pub projected_weight: f64,
+    pub projected_weight_in_favor: f64,
+    pub required_quorum: f64,
+    pub approval_threshold: f64,
+    pub quorum_met: bool,
+    pub threshold_met: bool,
+    pub projected_status: ProposalStatus,
+}
+
+impl GovernanceSystem {
+    /// Projects the outcome of an active proposal.
+    ///
+    /// `electorate` maps every registered voter to the weight they would
+    /// vote with on this proposal; the caller derives it from the
+    /// proposal's weighting mode. Voters outside the proposal's eligible
+    /// list are ignored.
+    pub fn simulate_proposal_outcome(&self, proposal_id: &str, electorate: &HashMap<String, f64>) -> IcnResult<OutcomeProjection> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if proposal.status != ProposalStatus::Active {
+            return Err(IcnError::Governance("Only active proposals can be simulated".into()));
+        }
+        if proposal.ballot.is_multi_option() {
+            return Err(IcnError::Governance("Only yes/no proposals can be simulated".into()));
+        }
+
+        let eligible: HashMap<&str, f64> = electorate.iter()
+            .filter(|(voter, &weight)| weight > 0.0 && self.is_eligible(proposal_id, voter))
+            .map(|(voter, &weight)| (voter.as_str(), weight))
+            .collect();
+        let eligible_weight: f64 = eligible.values().sum();
+
+        let (weight_in_favor, weight_cast) = Self::tally(self.get_votes(proposal_id)?);
+        let (expected_turnout, turnout_sample_size) = self.historical_turnout(&eligible)
+            .unwrap_or((DEFAULT_TURNOUT, 0));
+
+        // Votes already cast count in full even if turnout is expected to be lower
+        let projected_weight = (eligible_weight * expected_turnout).max(weight_cast);
+        let share_in_favor = if weight_cast > 0.0 { weight_in_favor / weight_cast } else { 0.5 };
+        let projected_weight_in_favor = weight_in_favor + (projected_weight - weight_cast) * share_in_favor;
+
+        let approval_threshold = self.approval_threshold(proposal_id)?;
+        let quorum_met = projected_weight >= proposal.required_quorum;
+        let threshold_met = projected_weight > 0.0 && projected_weight_in_favor / projected_weight > approval_threshold;
+        let projected_status = if quorum_met && threshold_met { ProposalStatus::Passed } else { ProposalStatus::Rejected };
+
+        Ok(OutcomeProjection {
+            proposal_id: proposal_id.to_string(),
+            eligible_voters: eligible.len(),
+            eligible_weight,
+            weight_cast,
+            weight_in_favor,
+            expected_turnout,
+            turnout_sample_size,
+            projected_weight,
+            projected_weight_in_favor,
+            required_quorum: proposal.required_quorum,
+            approval_threshold,
+            quorum_met,
+            threshold_met,
+            projected_status,
+        })
+    }
+
+    /// Average share of `electorate`'s weight that voted on proposals that
+    /// have already been decided, with how many proposals that covers.
+    fn historical_turnout(&self, electorate: &HashMap<&str, f64>) -> Option<(f64, usize)> {
+        let total: f64 = electorate.values().sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let turnouts: Vec<f64> = self.proposals.values()
+            .filter(|p| p.status != ProposalStatus::Active)
+            .filter_map(|p| self.votes.get(&p.id))
+            .map(|votes| {
+                let voted: f64 = votes.iter().filter_map(|vote| electorate.get(vote.voter.as_str())).sum();
+                voted / total
+            })
+            .collect();
+        if turnouts.is_empty() {
+            return None;
+        }
+        Some((turnouts.iter().sum::<f64>() / turnouts.len() as f64, turnouts.len()))
+    }
+
+    fn is_eligible(&self, proposal_id: &str, voter: &str) -> bool {
+        self.proposal_policies.get(proposal_id)
+            .and_then(|policy| policy.eligible_voters.as_ref())
+            .is_none_or(|eligible_voters| eligible_voters.iter().any(|eligible| eligible == voter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BallotKind, Proposal, ProposalCategory, ProposalScope, ProposalType, VotingWeightMode};
+    use chrono::{Duration, Utc};
+
+    fn proposal(id: &str) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: id.to_string(),
+            description: String::new(),
+            proposer: "Alice".to_string(),
+            created_at: Utc::now(),
+            voting_ends_at: Utc::now(),
+            status: ProposalStatus::Active,
+            proposal_type: ProposalType::NetworkUpgrade,
+            category: ProposalCategory::Technical,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    #[test]
+    fn test_projection_uses_historical_turnout() {
+        let mut governance = GovernanceSystem::new();
+        let electorate: HashMap<String, f64> = ["Alice", "Bob", "Carol", "Dave"].iter()
+            .map(|voter| (voter.to_string(), 1.0))
+            .collect();
+
+        // Without history half the electorate is expected to vote
+        governance.create_proposal(proposal("p1")).unwrap();
+        governance.vote_on_proposal("p1", "Alice".to_string(), true, 1.0).unwrap();
+        let projection = governance.simulate_proposal_outcome("p1", &electorate).unwrap();
+        assert_eq!((projection.expected_turnout, projection.turnout_sample_size), (DEFAULT_TURNOUT, 0));
+        assert_eq!(projection.projected_weight, 2.0);
+        assert_eq!(projection.projected_status, ProposalStatus::Passed);
+
+        // Once p1 is decided with three of four voting, that becomes the expectation
+        governance.vote_on_proposal("p1", "Bob".to_string(), false, 1.0).unwrap();
+        governance.vote_on_proposal("p1", "Carol".to_string(), true, 1.0).unwrap();
+        governance.proposals.get_mut("p1").unwrap().voting_ends_at = Utc::now() - Duration::seconds(1);
+        governance.finalize_proposal("p1").unwrap();
+
+        governance.create_proposal(proposal("p2")).unwrap();
+        governance.proposals.get_mut("p2").unwrap().required_quorum = 2.5;
+        governance.vote_on_proposal("p2", "Dave".to_string(), true, 1.0).unwrap();
+        let projection = governance.simulate_proposal_outcome("p2", &electorate).unwrap();
+        assert_eq!((projection.expected_turnout, projection.turnout_sample_size), (0.75, 1));
+        assert_eq!((projection.weight_cast, projection.projected_weight), (1.0, 3.0));
+        assert!(projection.quorum_met && projection.threshold_met);
+        assert_eq!(projection.projected_status, ProposalStatus::Passed);
+
+        assert!(governance.simulate_proposal_outcome("p1", &electorate).is_err());
+    }
+}
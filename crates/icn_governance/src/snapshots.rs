@@ -0,0 +1,59 @@
+// File: crates/icn_governance/src/snapshots.rs
+
+use crate::{GovernanceSystem, VotingWeightMode};
+use chrono::{DateTime, Utc};
+use icn_common::{IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// The weight every eligible voter held when a proposal was opened. Votes
+/// on the proposal count with these weights, so reputation, shares or
+/// membership gained afterwards cannot swing it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VoterSnapshot {
+    pub proposal_id: String,
+    pub weight_mode: VotingWeightMode,
+    pub taken_at: DateTime<Utc>,
+    pub weights: BTreeMap<String, f64>,
+}
+
+impl GovernanceSystem {
+    /// Fixes the voter weights for a proposal. Voters outside the
+    /// proposal's scope and those without weight are left out. A snapshot
+    /// is taken once; the weights cannot be replaced while the proposal is
+    /// open.
+    pub fn record_snapshot(&mut self, proposal_id: &str, weights: impl IntoIterator<Item = (String, f64)>, taken_at: DateTime<Utc>) -> IcnResult<&VoterSnapshot> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if self.snapshots.contains_key(proposal_id) {
+            return Err(IcnError::Governance("Proposal already has a voter snapshot".into()));
+        }
+        let eligible_voters = self.proposal_policies.get(proposal_id).and_then(|policy| policy.eligible_voters.as_ref());
+        let weights = weights.into_iter()
+            .filter(|(voter, weight)| *weight > 0.0 && eligible_voters.is_none_or(|eligible| eligible.contains(voter)))
+            .collect();
+        let snapshot = VoterSnapshot {
+            proposal_id: proposal_id.to_string(),
+            weight_mode: proposal.weight_mode.clone(),
+            taken_at,
+            weights,
+        };
+        Ok(self.snapshots.entry(proposal_id.to_string()).or_insert(snapshot))
+    }
+
+    pub fn get_snapshot(&self, proposal_id: &str) -> Option<&VoterSnapshot> {
+        self.snapshots.get(proposal_id)
+    }
+
+    /// The weight `voter` held in the proposal's snapshot, or `None` if the
+    /// proposal has none and weights are taken as cast. Voters missing from
+    /// the snapshot held no weight and cannot vote.
+    pub fn snapshot_weight(&self, proposal_id: &str, voter: &str) -> IcnResult<Option<f64>> {
+        match self.snapshots.get(proposal_id) {
+            None => Ok(None),
+            Some(snapshot) => snapshot.weights.get(voter)
+                .copied()
+                .map(Some)
+                .ok_or_else(|| IcnError::Governance(format!("{} held no voting weight when the proposal was created", voter))),
+        }
+    }
+}
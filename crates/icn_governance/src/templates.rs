@@ -0,0 +1,335 @@
+// File: crates/icn_governance/src/templates.rs
+
+use crate::GovernanceSystem;
+use icn_common::{IcnResult, IcnError, CurrencyType, ParameterRegistry, Proposal, ProposalAction, ProposalCategory, ProposalStatus, ProposalScope, BallotKind, ProposalType, VotingWeightMode};
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Values supplied for a template's parameters, keyed by parameter name.
+pub type TemplateArguments = BTreeMap<String, Value>;
+
+/// The type a template parameter's value must have.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum ParameterKind {
+    Text { max_length: usize },
+    Number { min: f64, max: f64 },
+    Boolean,
+    /// A currency, given in the same form as in transactions.
+    Currency,
+    /// The DID or address of an account.
+    Account,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateParameter {
+    pub name: String,
+    pub description: String,
+    pub kind: ParameterKind,
+}
+
+/// What a template's proposals do once they pass.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TemplateKind {
+    AdjustIssuanceRate,
+    FundProject,
+    ChangeParameter,
+    EmergencyPause,
+}
+
+/// A kind of proposal with a typed list of parameters. Proposals created
+/// from a template carry actions the node can execute, rather than a
+/// description someone has to act on by hand.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProposalTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub kind: TemplateKind,
+    pub proposal_type: ProposalType,
+    pub category: ProposalCategory,
+    pub parameters: Vec<TemplateParameter>,
+}
+
+/// A request to open a proposal from a template.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateProposal {
+    pub template_id: String,
+    pub proposal_id: String,
+    pub proposer: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub arguments: TemplateArguments,
+}
+
+fn parameter(name: &str, description: &str, kind: ParameterKind) -> TemplateParameter {
+    TemplateParameter {
+        name: name.to_string(),
+        description: description.to_string(),
+        kind,
+    }
+}
+
+/// Templates every node offers.
+pub fn builtin_templates() -> Vec<ProposalTemplate> {
+    vec![
+        ProposalTemplate {
+            id: "adjust_issuance_rate".to_string(),
+            name: "Adjust issuance rate".to_string(),
+            description: "Change how fast a currency is issued".to_string(),
+            kind: TemplateKind::AdjustIssuanceRate,
+            proposal_type: ProposalType::EconomicAdjustment,
+            category: ProposalCategory::Economic,
+            parameters: vec![
+                parameter("currency_type", "Currency whose issuance changes", ParameterKind::Currency),
+                parameter("rate", "New issuance rate", ParameterKind::Number { min: 0.0, max: 1.0 }),
+            ],
+        },
+        ProposalTemplate {
+            id: "fund_project".to_string(),
+            name: "Fund project".to_string(),
+            description: "Pay a project from the community fund".to_string(),
+            kind: TemplateKind::FundProject,
+            proposal_type: ProposalType::EconomicAdjustment,
+            category: ProposalCategory::Social,
+            parameters: vec![
+                parameter("project", "Name of the project", ParameterKind::Text { max_length: 200 }),
+                parameter("recipient", "Account receiving the funds", ParameterKind::Account),
+                parameter("amount", "Amount to pay", ParameterKind::Number { min: f64::MIN_POSITIVE, max: f64::MAX }),
+                parameter("currency_type", "Currency to pay in", ParameterKind::Currency),
+            ],
+        },
+        ProposalTemplate {
+            id: "change_parameter".to_string(),
+            name: "Change network parameter".to_string(),
+            description: "Set a value in the network parameter registry".to_string(),
+            kind: TemplateKind::ChangeParameter,
+            proposal_type: ProposalType::NetworkUpgrade,
+            category: ProposalCategory::Technical,
+            parameters: vec![
+                parameter("name", "Parameter to change", ParameterKind::Text { max_length: 100 }),
+                parameter("value", "New value", ParameterKind::Number { min: f64::MIN, max: f64::MAX }),
+            ],
+        },
+        ProposalTemplate {
+            id: "emergency_pause".to_string(),
+            name: "Emergency pause".to_string(),
+            description: "Pause or resume the network".to_string(),
+            kind: TemplateKind::EmergencyPause,
+            proposal_type: ProposalType::Constitutional,
+            category: ProposalCategory::Technical,
+            parameters: vec![
+                parameter("paused", "Whether the network should be paused", ParameterKind::Boolean),
+                parameter("reason", "Why", ParameterKind::Text { max_length: 500 }),
+            ],
+        },
+    ]
+}
+
+impl ParameterKind {
+    fn check(&self, name: &str, value: &Value) -> IcnResult<()> {
+        let valid = match self {
+            ParameterKind::Text { max_length } => value.as_str()
+                .is_some_and(|text| !text.trim().is_empty() && text.len() <= *max_length),
+            ParameterKind::Number { min, max } => value.as_f64()
+                .is_some_and(|number| number.is_finite() && number >= *min && number <= *max),
+            ParameterKind::Boolean => value.is_boolean(),
+            ParameterKind::Currency => serde_json::from_value::<CurrencyType>(value.clone()).is_ok(),
+            ParameterKind::Account => value.as_str().is_some_and(|account| !account.trim().is_empty()),
+        };
+        if !valid {
+            return Err(IcnError::Governance(format!("Invalid value for parameter {}: expected {:?}", name, self)));
+        }
+        Ok(())
+    }
+}
+
+impl ProposalTemplate {
+    /// Checks that every parameter is given with a value of its type and
+    /// that nothing else is.
+    pub fn validate(&self, arguments: &TemplateArguments) -> IcnResult<()> {
+        for name in arguments.keys() {
+            if !self.parameters.iter().any(|parameter| &parameter.name == name) {
+                return Err(IcnError::Governance(format!("Template {} has no parameter {}", self.id, name)));
+            }
+        }
+        for parameter in &self.parameters {
+            let value = arguments.get(&parameter.name)
+                .ok_or_else(|| IcnError::Governance(format!("Missing parameter {}", parameter.name)))?;
+            parameter.kind.check(&parameter.name, value)?;
+        }
+        Ok(())
+    }
+
+    /// The actions a proposal made from this template executes.
+    pub fn actions(&self, arguments: &TemplateArguments) -> IcnResult<Vec<ProposalAction>> {
+        self.validate(arguments)?;
+        let text = |name: &str| arguments[name].as_str().unwrap_or_default().to_string();
+        let number = |name: &str| arguments[name].as_f64().unwrap_or_default();
+        let currency = |name: &str| serde_json::from_value::<CurrencyType>(arguments[name].clone());
+
+        let action = match self.kind {
+            TemplateKind::AdjustIssuanceRate => ProposalAction::SetIssuanceRate {
+                currency_type: currency("currency_type")?,
+                rate: number("rate"),
+            },
+            TemplateKind::FundProject => ProposalAction::FundProject {
+                project: text("project"),
+                recipient: text("recipient"),
+                amount: number("amount"),
+                currency_type: currency("currency_type")?,
+            },
+            TemplateKind::ChangeParameter => ProposalAction::SetParameter {
+                name: text("name"),
+                value: number("value"),
+            },
+            TemplateKind::EmergencyPause => ProposalAction::SetEmergencyPause {
+                paused: arguments["paused"].as_bool().unwrap_or_default(),
+                reason: text("reason"),
+            },
+        };
+        Ok(vec![action])
+    }
+
+    /// The type and category of a proposal with `actions`. A parameter
+    /// change takes the type the registry requires for that parameter.
+    fn proposal_kind(&self, actions: &[ProposalAction]) -> IcnResult<(ProposalType, ProposalCategory)> {
+        match actions {
+            [ProposalAction::SetParameter { name, .. }] if self.kind == TemplateKind::ChangeParameter => {
+                let proposal_type = ParameterRegistry::new().definition(name)?.changed_by.clone();
+                let category = match proposal_type {
+                    ProposalType::EconomicAdjustment => ProposalCategory::Economic,
+                    _ => self.category.clone(),
+                };
+                Ok((proposal_type, category))
+            }
+            _ => Ok((self.proposal_type.clone(), self.category.clone())),
+        }
+    }
+
+    /// Builds the proposal described by `request`. Its quorum and voting
+    /// period are filled in when it is created.
+    pub fn instantiate(&self, request: &TemplateProposal, now: DateTime<Utc>) -> IcnResult<Proposal> {
+        let actions = self.actions(&request.arguments)?;
+        let (proposal_type, category) = self.proposal_kind(&actions)?;
+        let description = if request.description.trim().is_empty() {
+            self.description.clone()
+        } else {
+            request.description.clone()
+        };
+        Ok(Proposal {
+            id: request.proposal_id.clone(),
+            title: request.title.clone(),
+            description,
+            proposer: request.proposer.clone(),
+            created_at: now,
+            voting_ends_at: now,
+            status: ProposalStatus::Active,
+            proposal_type,
+            category,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::default(),
+            actions,
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        })
+    }
+}
+
+impl GovernanceSystem {
+    pub fn list_templates(&self) -> Vec<&ProposalTemplate> {
+        self.templates.values().collect()
+    }
+
+    pub fn get_template(&self, template_id: &str) -> IcnResult<&ProposalTemplate> {
+        self.templates.get(template_id)
+            .ok_or_else(|| IcnError::Governance(format!("Unknown proposal template: {}", template_id)))
+    }
+
+    /// Validates the request against its template and returns the proposal
+    /// to pass to `create_proposal`.
+    pub fn instantiate_template(&self, request: &TemplateProposal, now: DateTime<Utc>) -> IcnResult<Proposal> {
+        self.get_template(&request.template_id)?.instantiate(request, now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_common::params::{PARAM_DIFFICULTY, PARAM_MIN_BASE_FEE};
+    use serde_json::json;
+
+    fn fund_project() -> ProposalTemplate {
+        builtin_templates().into_iter().find(|template| template.id == "fund_project").unwrap()
+    }
+
+    #[test]
+    fn test_template_arguments_validated() {
+        let template = fund_project();
+        let mut arguments = TemplateArguments::new();
+        arguments.insert("project".to_string(), json!("Community garden"));
+        arguments.insert("recipient".to_string(), json!("did:icn:garden"));
+        arguments.insert("amount".to_string(), json!(250.0));
+        arguments.insert("currency_type".to_string(), json!("Community"));
+
+        let actions = template.actions(&arguments).unwrap();
+        assert_eq!(actions, vec![ProposalAction::FundProject {
+            project: "Community garden".to_string(),
+            recipient: "did:icn:garden".to_string(),
+            amount: 250.0,
+            currency_type: CurrencyType::Community,
+        }]);
+
+        let mut bad = arguments.clone();
+        bad.insert("amount".to_string(), json!(-5.0));
+        assert!(template.validate(&bad).is_err());
+        bad.insert("amount".to_string(), json!("lots"));
+        assert!(template.validate(&bad).is_err());
+
+        let mut extra = arguments.clone();
+        extra.insert("deadline".to_string(), json!("soon"));
+        assert!(template.validate(&extra).is_err());
+
+        let mut governance = GovernanceSystem::new();
+        let request = TemplateProposal {
+            template_id: "fund_project".to_string(),
+            proposal_id: "p1".to_string(),
+            proposer: "Alice".to_string(),
+            title: "Fund the garden".to_string(),
+            description: String::new(),
+            arguments: arguments.clone(),
+        };
+        let proposal = governance.instantiate_template(&request, Utc::now()).unwrap();
+        assert_eq!(proposal.proposal_type, ProposalType::EconomicAdjustment);
+        assert_eq!(proposal.actions, actions);
+        governance.create_proposal(proposal).unwrap();
+        assert!(governance.instantiate_template(&TemplateProposal { template_id: "unknown".to_string(), ..request }, Utc::now()).is_err());
+
+        arguments.remove("recipient");
+        assert!(template.validate(&arguments).is_err());
+    }
+
+    #[test]
+    fn test_parameter_change_takes_the_parameters_proposal_type() {
+        let governance = GovernanceSystem::new();
+        let request = |name: &str| TemplateProposal {
+            template_id: "change_parameter".to_string(),
+            proposal_id: format!("change-{}", name),
+            proposer: "Alice".to_string(),
+            title: format!("Change {}", name),
+            description: String::new(),
+            arguments: TemplateArguments::from([("name".to_string(), json!(name)), ("value".to_string(), json!(2.0))]),
+        };
+
+        let fees = governance.instantiate_template(&request(PARAM_MIN_BASE_FEE), Utc::now()).unwrap();
+        assert_eq!((fees.proposal_type, fees.category), (ProposalType::EconomicAdjustment, ProposalCategory::Economic));
+        let difficulty = governance.instantiate_template(&request(PARAM_DIFFICULTY), Utc::now()).unwrap();
+        assert_eq!((difficulty.proposal_type, difficulty.category), (ProposalType::NetworkUpgrade, ProposalCategory::Technical));
+        assert!(governance.instantiate_template(&request("block_reward"), Utc::now()).is_err());
+    }
+}
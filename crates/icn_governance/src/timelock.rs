@@ -0,0 +1,199 @@
+// File: crates/icn_governance/src/timelock.rs
+
+use crate::{GovernanceSystem, Proposal, ProposalStatus};
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{IcnError, IcnResult, ProposalAction};
+use serde::{Serialize, Deserialize};
+use std::collections::HashSet;
+
+/// Share of the weighted vote a veto must exceed.
+pub const VETO_SUPERMAJORITY: f64 = 2.0 / 3.0;
+
+/// When a passed proposal becomes executable.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ProposalTimelock {
+    pub proposal_id: String,
+    pub passed_at: DateTime<Utc>,
+    pub executable_at: DateTime<Utc>,
+}
+
+/// The proposals `proposal` vetoes.
+pub(crate) fn veto_targets(proposal: &Proposal) -> impl Iterator<Item = &String> {
+    proposal.actions.iter().filter_map(|action| match action {
+        ProposalAction::VetoProposal { proposal_id } => Some(proposal_id),
+        _ => None,
+    })
+}
+
+impl GovernanceSystem {
+    /// Holds a proposal that just passed for its policy's timelock. Vetoes
+    /// aren't held, so they can land while their target still is.
+    pub(crate) fn start_timelock(&mut self, proposal_id: &str) -> IcnResult<()> {
+        let proposal = self.get_proposal(proposal_id)?;
+        if veto_targets(proposal).next().is_some() {
+            return Ok(());
+        }
+        let timelock_secs = self.proposal_policies.get(proposal_id)
+            .map_or(self.default_policy.timelock_secs, |policy| policy.timelock_secs);
+        let passed_at = self.clock.now();
+        self.timelocks.insert(proposal_id.to_string(), ProposalTimelock {
+            proposal_id: proposal_id.to_string(),
+            passed_at,
+            executable_at: passed_at + Duration::seconds(timelock_secs),
+        });
+        Ok(())
+    }
+
+    pub fn get_timelock(&self, proposal_id: &str) -> Option<&ProposalTimelock> {
+        self.timelocks.get(proposal_id)
+    }
+
+    /// Vetoes of `proposal_id` that could still cancel it: open ones, and
+    /// passed ones not yet executed.
+    pub fn pending_vetoes(&self, proposal_id: &str) -> Vec<&str> {
+        let mut vetoes: Vec<&str> = self.proposals.values()
+            .filter(|veto| matches!(veto.status, ProposalStatus::Active | ProposalStatus::Passed))
+            .filter(|veto| veto_targets(veto).any(|target| target == proposal_id))
+            .map(|veto| veto.id.as_str())
+            .collect();
+        vetoes.sort_unstable();
+        vetoes
+    }
+
+    /// Fails while the proposal is timelocked or a veto of it is pending.
+    pub(crate) fn check_timelock(&self, proposal_id: &str) -> IcnResult<()> {
+        if let Some(timelock) = self.timelocks.get(proposal_id) {
+            if self.clock.now() < timelock.executable_at {
+                return Err(IcnError::Governance(format!("Proposal {} is timelocked until {}", proposal_id, timelock.executable_at)));
+            }
+        }
+        if let Some(veto) = self.pending_vetoes(proposal_id).first() {
+            return Err(IcnError::Governance(format!("Proposal {} waits on veto {}", proposal_id, veto)));
+        }
+        Ok(())
+    }
+
+    /// A veto can only target a passed proposal still in its timelock.
+    pub(crate) fn validate_veto(&self, target: &str) -> IcnResult<()> {
+        let proposal = self.get_proposal(target)?;
+        let timelocked = self.timelocks.get(target).is_some_and(|timelock| self.clock.now() < timelock.executable_at);
+        if proposal.status != ProposalStatus::Passed || !timelocked {
+            return Err(IcnError::Governance(format!("Proposal {} is not waiting out a timelock", target)));
+        }
+        Ok(())
+    }
+
+    /// Cancels the proposals a veto names as it is executed.
+    pub(crate) fn apply_vetoes(&mut self, veto: &Proposal) -> IcnResult<()> {
+        for target in veto_targets(veto) {
+            if self.get_proposal(target)?.status != ProposalStatus::Passed {
+                return Err(IcnError::Governance(format!("Proposal {} can no longer be vetoed", target)));
+            }
+        }
+        for target in veto_targets(veto) {
+            self.set_status(target, ProposalStatus::Vetoed)?;
+            self.timelocks.remove(target);
+        }
+        Ok(())
+    }
+
+    /// Passed proposals ready to be executed, soonest executable first.
+    /// A bundle is listed once, by the member that comes first.
+    pub fn due_proposals(&self) -> Vec<String> {
+        let mut passed: Vec<&Proposal> = self.proposals.values()
+            .filter(|proposal| proposal.status == ProposalStatus::Passed)
+            .collect();
+        let executable_at = |proposal: &Proposal| self.timelocks.get(&proposal.id).map(|timelock| timelock.executable_at);
+        passed.sort_by(|a, b| executable_at(a).cmp(&executable_at(b)).then_with(|| a.id.cmp(&b.id)));
+
+        let mut covered = HashSet::new();
+        let mut due = Vec::new();
+        for proposal in passed {
+            if covered.contains(&proposal.id) {
+                continue;
+            }
+            if let Ok(members) = self.execution_order(&proposal.id) {
+                covered.extend(members);
+                due.push(proposal.id.clone());
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BallotKind, ProposalCategory, ProposalScope, ProposalType, VotingWeightMode};
+    use icn_common::{GovernancePolicy, ManualClock};
+
+    fn proposal(id: &str, proposal_type: ProposalType, actions: Vec<ProposalAction>, now: DateTime<Utc>) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: format!("Proposal {}", id),
+            description: String::new(),
+            proposer: "Alice".to_string(),
+            created_at: now,
+            voting_ends_at: now,
+            status: ProposalStatus::Active,
+            proposal_type,
+            category: ProposalCategory::Economic,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions,
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    fn vote(governance: &mut GovernanceSystem, proposal_id: &str, votes: &[bool]) {
+        for (i, in_favor) in votes.iter().enumerate() {
+            governance.vote_on_proposal(proposal_id, format!("member-{}", i), *in_favor, 1.0).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_passed_proposals_wait_out_the_timelock_unless_vetoed() {
+        let clock = ManualClock::new(Utc::now());
+        let mut governance = GovernanceSystem::new().with_clock(clock.shared());
+        let policy = GovernancePolicy { quorum: 0.0, voting_duration_secs: 60, timelock_secs: 3_600, ..GovernancePolicy::default() };
+        governance.set_initial_policy(ProposalType::EconomicAdjustment, None, policy).unwrap();
+        let veto_policy = GovernancePolicy { quorum: 0.0, voting_duration_secs: 1_800, ..GovernancePolicy::default() };
+        governance.set_initial_policy(ProposalType::Constitutional, None, veto_policy).unwrap();
+        for id in ["fees", "rates"] {
+            governance.create_proposal(proposal(id, ProposalType::EconomicAdjustment, Vec::new(), governance.now())).unwrap();
+            vote(&mut governance, id, &[true]);
+        }
+        clock.advance(Duration::seconds(60));
+        governance.finalize_proposal("fees").unwrap();
+        governance.finalize_proposal("rates").unwrap();
+        assert!(governance.execute_proposal("fees").is_err());
+        assert!(governance.due_proposals().is_empty());
+
+        // Vetoes need a passed, timelocked target and a supermajority
+        let veto = |id: &str, target: &str, now| proposal(id, ProposalType::Constitutional, vec![ProposalAction::VetoProposal { proposal_id: target.to_string() }], now);
+        assert!(governance.create_proposal(veto("bogus", "missing", governance.now())).is_err());
+        governance.create_proposal(veto("weak-veto", "rates", governance.now())).unwrap();
+        governance.create_proposal(veto("veto", "fees", governance.now())).unwrap();
+        vote(&mut governance, "weak-veto", &[true, true, false]);
+        vote(&mut governance, "veto", &[true, true, true, false]);
+        assert_eq!(governance.pending_vetoes("fees"), vec!["veto"]);
+
+        clock.advance(Duration::seconds(3_600));
+        assert_eq!(governance.finalize_proposal("weak-veto").unwrap(), ProposalStatus::Rejected);
+        assert_eq!(governance.finalize_proposal("veto").unwrap(), ProposalStatus::Passed);
+        assert!(governance.get_timelock("veto").is_none());
+
+        // The pending veto holds its target past the timelock
+        assert_eq!(governance.due_proposals(), vec!["veto".to_string(), "rates".to_string()]);
+        governance.execute_proposal("veto").unwrap();
+        assert_eq!(governance.get_proposal("fees").unwrap().status, ProposalStatus::Vetoed);
+        assert!(governance.execute_proposal("fees").is_err());
+
+        governance.execute_proposal("rates").unwrap();
+        assert!(governance.due_proposals().is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+// File: crates/icn_governance/src/votes.rs
+
+use crate::{GovernanceSystem, ProposalStatus, Vote};
+use chrono::{DateTime, Utc};
+use icn_common::{IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
+
+/// Most votes one page may hold.
+pub const MAX_VOTE_PAGE_LIMIT: usize = 500;
+
+/// Criteria for listing a proposal's votes. Every criterion that is set
+/// must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VoteFilter {
+    pub in_favor: Option<bool>,
+    pub min_weight: Option<f64>,
+    pub max_weight: Option<f64>,
+    /// Inclusive lower bound on when the vote was cast.
+    pub from_time: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on when the vote was cast.
+    pub to_time: Option<DateTime<Utc>>,
+}
+
+impl VoteFilter {
+    pub fn matches(&self, vote: &Vote) -> bool {
+        self.in_favor.is_none_or(|in_favor| vote.in_favor == in_favor)
+            && self.min_weight.is_none_or(|min| vote.weight >= min)
+            && self.max_weight.is_none_or(|max| vote.weight <= max)
+            && self.from_time.is_none_or(|from| vote.timestamp >= from)
+            && self.to_time.is_none_or(|to| vote.timestamp < to)
+    }
+}
+
+/// Counts and weights on each side of a proposal, over the votes whose
+/// eligibility proof verifies.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct VoteTotals {
+    pub votes_in_favor: usize,
+    pub votes_against: usize,
+    pub weight_in_favor: f64,
+    pub weight_against: f64,
+}
+
+/// One page of a proposal's votes, oldest first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VotePage {
+    pub proposal_id: String,
+    /// Whether individual votes are withheld until the proposal is
+    /// finalized. While they are, `votes` is empty, the filter is ignored
+    /// and `total` counts every vote.
+    pub voters_hidden: bool,
+    /// Matches across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub votes: Vec<Vote>,
+    /// Totals over every vote on the proposal, whatever the filter.
+    pub totals: VoteTotals,
+}
+
+impl GovernanceSystem {
+    /// Whether the proposal's policy withholds who voted how while it is
+    /// still open, so early votes don't sway later ones.
+    pub fn voters_hidden(&self, proposal_id: &str) -> IcnResult<bool> {
+        let proposal = self.get_proposal(proposal_id)?;
+        let hidden = self.get_proposal_policy(proposal_id)
+            .is_some_and(|policy| policy.hide_voters_until_finalized);
+        Ok(hidden && proposal.status == ProposalStatus::Active)
+    }
+
+    /// Returns up to `limit` of the proposal's votes matching `filter`,
+    /// after skipping `offset`.
+    pub fn list_votes(&self, proposal_id: &str, filter: &VoteFilter, offset: usize, limit: usize) -> IcnResult<VotePage> {
+        if limit == 0 || limit > MAX_VOTE_PAGE_LIMIT {
+            return Err(IcnError::Validation(format!("Vote page limit must be between 1 and {}", MAX_VOTE_PAGE_LIMIT)));
+        }
+        let voters_hidden = self.voters_hidden(proposal_id)?;
+        let votes = self.get_votes(proposal_id)?;
+
+        let mut totals = VoteTotals::default();
+        for vote in votes.iter().filter(|vote| vote.is_eligibility_verified()) {
+            if vote.in_favor {
+                totals.votes_in_favor += 1;
+                totals.weight_in_favor += vote.weight;
+            } else {
+                totals.votes_against += 1;
+                totals.weight_against += vote.weight;
+            }
+        }
+
+        if voters_hidden {
+            return Ok(VotePage { proposal_id: proposal_id.to_string(), voters_hidden, total: votes.len(), offset, votes: Vec::new(), totals });
+        }
+        let mut matches: Vec<&Vote> = votes.iter().filter(|vote| filter.matches(vote)).collect();
+        matches.sort_by(|a, b| a.timestamp.cmp(&b.timestamp).then_with(|| a.voter.cmp(&b.voter)));
+        Ok(VotePage {
+            proposal_id: proposal_id.to_string(),
+            voters_hidden,
+            total: matches.len(),
+            offset,
+            votes: matches.into_iter().skip(offset).take(limit).cloned().collect(),
+            totals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BallotKind, Proposal, ProposalCategory, ProposalScope, ProposalType, VotingWeightMode};
+    use chrono::Duration;
+    use icn_common::{GovernancePolicy, ManualClock};
+
+    fn proposal(id: &str, now: DateTime<Utc>) -> Proposal {
+        Proposal {
+            id: id.to_string(),
+            title: format!("Proposal {}", id),
+            description: String::new(),
+            proposer: "Alice".to_string(),
+            created_at: now,
+            voting_ends_at: now,
+            status: ProposalStatus::Active,
+            proposal_type: ProposalType::Constitutional,
+            category: ProposalCategory::Economic,
+            required_quorum: 0.0,
+            execution_timestamp: None,
+            weight_mode: VotingWeightMode::OneMemberOneVote,
+            actions: Vec::new(),
+            scope: ProposalScope::Global,
+            ballot: BallotKind::YesNo,
+            depends_on: Vec::new(),
+            bundle: None,
+        }
+    }
+
+    #[test]
+    fn test_votes_are_paged_filtered_and_hidden_by_policy() {
+        let clock = ManualClock::new(Utc::now());
+        let mut governance = GovernanceSystem::new().with_clock(clock.shared());
+        governance.create_proposal(proposal("open", governance.now())).unwrap();
+        for (voter, in_favor, weight) in [("Alice", true, 1.0), ("Bob", false, 2.0), ("Carol", true, 3.0)] {
+            governance.vote_on_proposal("open", voter.to_string(), in_favor, weight).unwrap();
+        }
+
+        let in_favor = VoteFilter { in_favor: Some(true), ..VoteFilter::default() };
+        let page = governance.list_votes("open", &in_favor, 1, 10).unwrap();
+        assert!(!page.voters_hidden);
+        assert_eq!(page.total, 2);
+        assert_eq!(page.votes.len(), 1);
+        assert_eq!(page.totals.weight_in_favor, 4.0);
+        assert_eq!(page.totals.votes_against, 1);
+        let middling = VoteFilter { min_weight: Some(1.5), max_weight: Some(2.5), ..VoteFilter::default() };
+        assert_eq!(governance.list_votes("open", &middling, 0, 10).unwrap().votes[0].voter, "Bob");
+        assert!(governance.list_votes("open", &middling, 0, MAX_VOTE_PAGE_LIMIT + 1).is_err());
+
+        // A policy that hides voters only shows totals until finalization
+        let policy = GovernancePolicy { quorum: 0.0, hide_voters_until_finalized: true, ..GovernancePolicy::default() };
+        governance.set_initial_policy(ProposalType::Constitutional, Some(ProposalCategory::Economic), policy).unwrap();
+        governance.create_proposal(proposal("secret", governance.now())).unwrap();
+        governance.vote_on_proposal("secret", "Alice".to_string(), true, 1.0).unwrap();
+
+        let page = governance.list_votes("secret", &in_favor, 0, 10).unwrap();
+        assert!(page.voters_hidden);
+        assert!(page.votes.is_empty());
+        assert_eq!((page.total, page.totals.votes_in_favor), (1, 1));
+
+        clock.advance(Duration::days(8));
+        governance.finalize_proposal("secret").unwrap();
+        let page = governance.list_votes("secret", &in_favor, 0, 10).unwrap();
+        assert!(!page.voters_hidden);
+        assert_eq!(page.votes[0].voter, "Alice");
+    }
+}
@@ -0,0 +1,259 @@
+// File: crates/icn_identity/src/attestations.rs
+
+use crate::{DecentralizedIdentity, IdentityService};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::Signature;
+use icn_common::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+/// Longest claim an attestation can make.
+pub const MAX_CLAIM_LENGTH: usize = 256;
+
+/// A claim one member signs about another, such as "is a carpenter" or
+/// "completed 100 hours". It is kept with the subject's identity.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attestation {
+    /// Hex SHA-256 of the signed message.
+    pub id: String,
+    pub issuer: String,
+    pub subject: String,
+    pub claim: String,
+    /// When the issuer made the claim, in seconds since the epoch.
+    pub issued_at: i64,
+    /// The issuer's signature over `attestation_message`.
+    pub signature: Vec<u8>,
+    #[serde(default)]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Attestation {
+    pub fn message(&self) -> Vec<u8> {
+        attestation_message(&self.issuer, &self.subject, &self.claim, self.issued_at)
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
+
+/// The bytes an issuer signs to attest `claim` about `subject`.
+pub fn attestation_message(issuer: &str, subject: &str, claim: &str, issued_at: i64) -> Vec<u8> {
+    format!("icn-attestation:{}:{}:{}:{}", issuer, subject, issued_at, claim).into_bytes()
+}
+
+/// The bytes an issuer signs to withdraw the attestation with `attestation_id`.
+pub fn attestation_revocation_message(attestation_id: &str) -> Vec<u8> {
+    format!("icn-attestation-revocation:{}", attestation_id).into_bytes()
+}
+
+/// How attestations count towards reputation. Each member vouching for
+/// an identity adds `weight`, up to `max_bonus` in total, however many
+/// claims they make about it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttestationPolicy {
+    pub weight: f64,
+    pub max_bonus: f64,
+    /// Issuers with less reputation than this are not counted.
+    #[serde(default)]
+    pub min_issuer_reputation: f64,
+    /// Claims that count; every claim counts when empty.
+    #[serde(default)]
+    pub claims: BTreeSet<String>,
+}
+
+impl IdentityService {
+    /// Stores a signed attestation with its subject. The issuer and the
+    /// subject must be different identities that haven't been revoked.
+    pub fn add_attestation(&mut self, issuer: &str, subject: &str, claim: &str, issued_at: i64, signature: &[u8]) -> IcnResult<Attestation> {
+        if issuer == subject {
+            return Err(IcnError::Identity("Identities cannot attest to themselves".into()));
+        }
+        let claim = claim.trim();
+        if claim.is_empty() || claim.len() > MAX_CLAIM_LENGTH {
+            return Err(IcnError::Identity(format!("Claims must be between 1 and {} bytes", MAX_CLAIM_LENGTH)));
+        }
+        let issuer_identity = self.get_identity(issuer)?;
+        if issuer_identity.revoked {
+            return Err(IcnError::Identity("Revoked identities cannot issue attestations".into()));
+        }
+        let message = attestation_message(issuer, subject, claim, issued_at);
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid attestation signature: {}", e)))?;
+        if !issuer_identity.verify_signature(&message, &signature) {
+            return Err(IcnError::Identity("Attestation was not signed by its issuer".into()));
+        }
+
+        let attestation = Attestation {
+            id: hex::encode(Sha256::digest(&message)),
+            issuer: issuer.to_string(),
+            subject: subject.to_string(),
+            claim: claim.to_string(),
+            issued_at,
+            signature: signature.to_bytes().to_vec(),
+            revoked_at: None,
+        };
+        let subject_identity = self.identities.get_mut(subject)
+            .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+        if subject_identity.revoked {
+            return Err(IcnError::Identity("Cannot attest to a revoked identity".into()));
+        }
+        if subject_identity.attestations.iter().any(|existing| existing.id == attestation.id) {
+            return Err(IcnError::Identity("Attestation already recorded".into()));
+        }
+        subject_identity.attestations.push(attestation.clone());
+        Ok(attestation)
+    }
+
+    /// Withdraws an attestation. Only its issuer can, by signing
+    /// `attestation_revocation_message`. The attestation is kept, marked
+    /// with when it was revoked.
+    pub fn revoke_attestation(&mut self, subject: &str, attestation_id: &str, signature: &[u8]) -> IcnResult<()> {
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid revocation signature: {}", e)))?;
+        let issuer = self.get_identity(subject)?.attestations.iter()
+            .find(|attestation| attestation.id == attestation_id)
+            .map(|attestation| attestation.issuer.clone())
+            .ok_or_else(|| IcnError::Identity("Attestation not found".into()))?;
+        if !self.verify_signature(&issuer, &attestation_revocation_message(attestation_id), &signature)? {
+            return Err(IcnError::Identity("Only the issuer can revoke an attestation".into()));
+        }
+
+        let attestation = self.identities.get_mut(subject)
+            .and_then(|identity| identity.attestations.iter_mut().find(|attestation| attestation.id == attestation_id))
+            .ok_or_else(|| IcnError::Identity("Attestation not found".into()))?;
+        if attestation.is_revoked() {
+            return Err(IcnError::Identity("Attestation is already revoked".into()));
+        }
+        attestation.revoked_at = Some(Utc::now());
+        Ok(())
+    }
+
+    /// Attestations about `subject`, oldest first.
+    pub fn get_attestations(&self, subject: &str, include_revoked: bool) -> IcnResult<Vec<Attestation>> {
+        Ok(self.get_identity(subject)?.attestations.iter()
+            .filter(|attestation| include_revoked || !attestation.is_revoked())
+            .cloned()
+            .collect())
+    }
+
+    /// Attestations `issuer` has made that are still standing.
+    pub fn attestations_issued_by(&self, issuer: &str) -> Vec<Attestation> {
+        let mut issued: Vec<Attestation> = self.identities.values()
+            .flat_map(|identity| identity.attestations.iter())
+            .filter(|attestation| attestation.issuer == issuer && !attestation.is_revoked())
+            .cloned()
+            .collect();
+        issued.sort_by_key(|attestation| attestation.issued_at);
+        issued
+    }
+
+    /// Has attestations count towards reputation, or stops them counting
+    /// with `None`.
+    pub fn set_attestation_policy(&mut self, policy: Option<AttestationPolicy>) -> IcnResult<()> {
+        if let Some(policy) = &policy {
+            if !(policy.weight >= 0.0 && policy.max_bonus >= 0.0 && policy.min_issuer_reputation >= 0.0) {
+                return Err(IcnError::Identity("Attestation policy values cannot be negative".into()));
+            }
+        }
+        self.attestation_policy = policy;
+        Ok(())
+    }
+
+    pub fn attestation_policy(&self) -> Option<&AttestationPolicy> {
+        self.attestation_policy.as_ref()
+    }
+
+    /// Reputation the attestation policy adds to `identity`: one `weight`
+    /// per issuer standing behind a counted claim. Issuers are judged on
+    /// their own reputation, not on attestations about them, so members
+    /// can't vouch each other up in a loop.
+    pub(crate) fn attestation_bonus(&self, identity: &DecentralizedIdentity) -> f64 {
+        let Some(policy) = &self.attestation_policy else {
+            return 0.0;
+        };
+        let issuers: BTreeSet<&str> = identity.attestations.iter()
+            .filter(|attestation| !attestation.is_revoked())
+            .filter(|attestation| policy.claims.is_empty() || policy.claims.contains(&attestation.claim))
+            .filter(|attestation| self.identities.get(&attestation.issuer)
+                .is_some_and(|issuer| !issuer.revoked && issuer.reputation >= policy.min_issuer_reputation))
+            .map(|attestation| attestation.issuer.as_str())
+            .collect();
+        (issuers.len() as f64 * policy.weight).min(policy.max_bonus)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Keypair, Signer};
+    use std::collections::HashMap;
+
+    fn member(service: &mut IdentityService) -> (String, Keypair) {
+        let (identity, keypair) = DecentralizedIdentity::new(HashMap::new());
+        let id = identity.id.clone();
+        service.identities.insert(id.clone(), identity);
+        (id, keypair)
+    }
+
+    fn attest(service: &mut IdentityService, issuer: &(String, Keypair), subject: &str, claim: &str) -> IcnResult<Attestation> {
+        let signature = issuer.1.sign(&attestation_message(&issuer.0, subject, claim, 1_700_000_000));
+        service.add_attestation(&issuer.0, subject, claim, 1_700_000_000, &signature.to_bytes())
+    }
+
+    #[test]
+    fn test_attestations_are_signed_and_revocable_by_their_issuer() {
+        let mut service = IdentityService::new();
+        let alice = member(&mut service);
+        let bob = member(&mut service);
+
+        let attestation = attest(&mut service, &alice, &bob.0, "is a carpenter").unwrap();
+        assert!(attest(&mut service, &alice, &bob.0, "is a carpenter").is_err());
+        assert!(attest(&mut service, &bob, &bob.0, "is a carpenter").is_err());
+        let forged = bob.1.sign(&attestation_message(&alice.0, &bob.0, "completed 100 hours", 1_700_000_000));
+        assert!(service.add_attestation(&alice.0, &bob.0, "completed 100 hours", 1_700_000_000, &forged.to_bytes()).is_err());
+        assert_eq!(service.get_attestations(&bob.0, false).unwrap(), vec![attestation.clone()]);
+        assert_eq!(service.attestations_issued_by(&alice.0).len(), 1);
+
+        // Only the issuer can withdraw it, and it stays on record
+        let message = attestation_revocation_message(&attestation.id);
+        assert!(service.revoke_attestation(&bob.0, &attestation.id, &bob.1.sign(&message).to_bytes()).is_err());
+        service.revoke_attestation(&bob.0, &attestation.id, &alice.1.sign(&message).to_bytes()).unwrap();
+        assert!(service.revoke_attestation(&bob.0, &attestation.id, &alice.1.sign(&message).to_bytes()).is_err());
+        assert!(service.get_attestations(&bob.0, false).unwrap().is_empty());
+        assert!(service.get_attestations(&bob.0, true).unwrap()[0].is_revoked());
+    }
+
+    #[test]
+    fn test_attestation_policy_adds_to_reputation() {
+        let mut service = IdentityService::new();
+        let alice = member(&mut service);
+        let bob = member(&mut service);
+        let carol = member(&mut service);
+        let dave = member(&mut service);
+        service.update_reputation(&dave.0, -1.0).unwrap();
+
+        attest(&mut service, &alice, &carol.0, "is a carpenter").unwrap();
+        attest(&mut service, &alice, &carol.0, "completed 100 hours").unwrap();
+        attest(&mut service, &bob, &carol.0, "is a carpenter").unwrap();
+        attest(&mut service, &dave, &carol.0, "is a carpenter").unwrap();
+        assert_eq!(service.get_reputation(&carol.0).unwrap(), 1.0);
+
+        let policy = AttestationPolicy {
+            weight: 0.5,
+            max_bonus: 5.0,
+            min_issuer_reputation: 0.5,
+            claims: BTreeSet::from(["is a carpenter".to_string()]),
+        };
+        service.set_attestation_policy(Some(policy.clone())).unwrap();
+        // Alice and Bob count once each; Dave's reputation is too low
+        assert_eq!(service.get_reputation(&carol.0).unwrap(), 2.0);
+
+        service.set_attestation_policy(Some(AttestationPolicy { max_bonus: 0.75, ..policy })).unwrap();
+        assert_eq!(service.get_reputation(&carol.0).unwrap(), 1.75);
+        assert!(service.set_attestation_policy(Some(AttestationPolicy {
+            weight: -1.0, max_bonus: 1.0, min_issuer_reputation: 0.0, claims: BTreeSet::new(),
+        })).is_err());
+    }
+}
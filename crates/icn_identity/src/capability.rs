@@ -0,0 +1,284 @@
+// File: crates/icn_identity/src/capability.rs
+
+use crate::IdentityService;
+use icn_common::{public_key_from_did, Amount, IcnResult, IcnError, CurrencyType, ProposalCategory};
+use ed25519_dalek::{Keypair, Signature, Signer, Verifier};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// The UTC day a timestamp falls on, the period a spend limit covers.
+pub fn spending_day(timestamp: i64) -> i64 {
+    timestamp.div_euclid(SECONDS_PER_DAY)
+}
+
+/// Something a capability token lets its holder do for the issuer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum Capability {
+    /// Send up to `daily_limit` of a currency per UTC day.
+    Spend { currency_type: CurrencyType, daily_limit: Amount },
+    /// Vote on proposals in the given categories, or on any proposal if `None`.
+    Vote { categories: Option<Vec<ProposalCategory>> },
+    /// Change the node's runtime settings, if the issuer may.
+    ConfigureNode,
+}
+
+/// The terms of a capability token, as signed by the issuer.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CapabilityGrant {
+    /// DID of the member the holder acts for.
+    pub issuer: String,
+    /// DID of the member or bot the token was given to. Only they can use
+    /// it, by signing each use with the key in this DID.
+    pub delegate: String,
+    pub capabilities: Vec<Capability>,
+    pub issued_at: i64,
+    pub expires_at: i64,
+}
+
+/// A token letting a bot or another member act for the issuer within the
+/// limits of its grant.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CapabilityToken {
+    pub grant: CapabilityGrant,
+    pub signature: Vec<u8>,
+}
+
+impl CapabilityGrant {
+    fn signing_bytes(&self) -> IcnResult<Vec<u8>> {
+        bincode::serialize(self)
+            .map_err(|e| IcnError::Identity(format!("Failed to serialize capability grant: {}", e)))
+    }
+}
+
+impl CapabilityToken {
+    pub fn sign(grant: CapabilityGrant, keypair: &Keypair) -> IcnResult<Self> {
+        let signature = keypair.sign(&grant.signing_bytes()?).to_bytes().to_vec();
+        Ok(CapabilityToken { grant, signature })
+    }
+
+    /// Identifies the token in revocation lists and spending records.
+    pub fn id(&self) -> IcnResult<String> {
+        Ok(hex::encode(Sha256::digest(&self.grant.signing_bytes()?)))
+    }
+
+    /// Checks that `signature` over `message` was made by the token's
+    /// delegate.
+    pub fn verify_delegate_signature(&self, message: &[u8], signature: &[u8]) -> IcnResult<()> {
+        let public_key = public_key_from_did(&self.grant.delegate)?;
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid delegate signature: {}", e)))?;
+        public_key.verify(message, &signature)
+            .map_err(|_| IcnError::Identity("Capability token was not used by its delegate".into()))
+    }
+
+    /// Hex encoding of the token's JSON form, as carried in an
+    /// `Authorization: Capability <token>` header.
+    pub fn encode(&self) -> IcnResult<String> {
+        serde_json::to_vec(self)
+            .map(hex::encode)
+            .map_err(|e| IcnError::Identity(format!("Failed to serialize capability token: {}", e)))
+    }
+
+    pub fn decode(encoded: &str) -> IcnResult<Self> {
+        let bytes = hex::decode(encoded.trim())
+            .map_err(|e| IcnError::Identity(format!("Invalid capability token encoding: {}", e)))?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| IcnError::Identity(format!("Invalid capability token: {}", e)))
+    }
+}
+
+/// Message an issuer signs to revoke one of their tokens.
+pub fn revocation_message(token_id: &str) -> Vec<u8> {
+    format!("revoke-capability:{}", token_id).into_bytes()
+}
+
+/// Ids of revoked capability tokens.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityRegistry {
+    revoked: BTreeSet<String>,
+}
+
+impl IdentityService {
+    /// Checks that a token was signed by its issuer, who must be a registered,
+    /// unrevoked identity, and that it is neither expired nor revoked.
+    /// Returns the token id.
+    pub fn validate_capability(&self, token: &CapabilityToken, now: i64) -> IcnResult<String> {
+        let identity = self.get_identity(&token.grant.issuer)?;
+        if identity.revoked {
+            return Err(IcnError::Identity("Capability issuer has been revoked".into()));
+        }
+        let signature = Signature::from_bytes(&token.signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid capability signature: {}", e)))?;
+        if !identity.verify_signature(&token.grant.signing_bytes()?, &signature) {
+            return Err(IcnError::Identity("Capability signature verification failed".into()));
+        }
+        if now < token.grant.issued_at || now >= token.grant.expires_at {
+            return Err(IcnError::Identity("Capability token is not valid at this time".into()));
+        }
+
+        let token_id = token.id()?;
+        if self.capabilities.revoked.contains(&token_id) {
+            return Err(IcnError::Identity("Capability token has been revoked".into()));
+        }
+        Ok(token_id)
+    }
+
+    /// Authorizes spending `amount` for the issuer on top of `spent_today`,
+    /// what the token has already spent in the currency on the UTC day of
+    /// `now`, within the token's daily limit.
+    pub fn authorize_spend(&self, token: &CapabilityToken, currency_type: &CurrencyType, amount: Amount, spent_today: Amount, now: i64) -> IcnResult<()> {
+        self.validate_capability(token, now)?;
+        let daily_limit = token.grant.capabilities.iter()
+            .find_map(|capability| match capability {
+                Capability::Spend { currency_type: allowed, daily_limit } if allowed == currency_type => Some(*daily_limit),
+                _ => None,
+            })
+            .ok_or_else(|| IcnError::Identity(format!("Capability token does not allow spending {:?}", currency_type)))?;
+
+        if spent_today.checked_add(amount).is_none_or(|total| total > daily_limit) {
+            return Err(IcnError::Identity(format!(
+                "Capability token allows {} {:?} per day and {} has been spent today", daily_limit, currency_type, spent_today
+            )));
+        }
+        Ok(())
+    }
+
+    /// Authorizes a vote for the issuer on a proposal in `category`.
+    pub fn authorize_vote(&self, token: &CapabilityToken, category: &ProposalCategory, now: i64) -> IcnResult<()> {
+        self.validate_capability(token, now)?;
+        let allowed = token.grant.capabilities.iter().any(|capability| match capability {
+            Capability::Vote { categories } => categories.as_ref().is_none_or(|categories| categories.contains(category)),
+            _ => false,
+        });
+        if !allowed {
+            return Err(IcnError::Identity(format!("Capability token does not allow voting on {:?} proposals", category)));
+        }
+        Ok(())
+    }
+
+    /// Authorizes changing the node's runtime settings for the issuer.
+    /// Whether the issuer may do so themselves is up to the node.
+    pub fn authorize_node_configuration(&self, token: &CapabilityToken, now: i64) -> IcnResult<()> {
+        self.validate_capability(token, now)?;
+        if !token.grant.capabilities.contains(&Capability::ConfigureNode) {
+            return Err(IcnError::Identity("Capability token does not allow configuring the node".into()));
+        }
+        Ok(())
+    }
+
+    /// Revokes a token. `signature` is the issuer's signature over
+    /// `revocation_message(token_id)`.
+    pub fn revoke_capability(&mut self, issuer: &str, token_id: &str, signature: &[u8]) -> IcnResult<()> {
+        let identity = self.get_identity(issuer)?;
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid revocation signature: {}", e)))?;
+        if !identity.verify_signature(&revocation_message(token_id), &signature) {
+            return Err(IcnError::Identity("Revocation signature verification failed".into()));
+        }
+        self.capabilities.revoked.insert(token_id.to_string());
+        Ok(())
+    }
+
+    /// Ids of every revoked capability token.
+    pub fn revoked_capabilities(&self) -> Vec<String> {
+        self.capabilities.revoked.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use std::collections::HashMap;
+
+    fn keyed_did() -> (Keypair, String) {
+        let keypair = Keypair::generate(&mut OsRng {});
+        let did = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        (keypair, did)
+    }
+
+    fn setup() -> (IdentityService, Keypair, String) {
+        let (keypair, did) = keyed_did();
+        let mut service = IdentityService::new();
+        service.register_identity(&did, HashMap::new(), 1.0).unwrap();
+        (service, keypair, did)
+    }
+
+    fn grant(issuer: &str, delegate: &str, capabilities: Vec<Capability>) -> CapabilityGrant {
+        CapabilityGrant {
+            issuer: issuer.to_string(),
+            delegate: delegate.to_string(),
+            capabilities,
+            issued_at: 0,
+            expires_at: 10 * SECONDS_PER_DAY,
+        }
+    }
+
+    #[test]
+    fn test_spend_limited_per_day() {
+        let (service, keypair, did) = setup();
+        let (_, bot) = keyed_did();
+        let spend = Capability::Spend { currency_type: CurrencyType::BasicNeeds, daily_limit: Amount::from(50.0) };
+        let token = CapabilityToken::sign(grant(&did, &bot, vec![spend]), &keypair).unwrap();
+        let token = CapabilityToken::decode(&token.encode().unwrap()).unwrap();
+
+        let now = SECONDS_PER_DAY + 60;
+        let basic_needs = CurrencyType::BasicNeeds;
+        service.authorize_spend(&token, &basic_needs, Amount::from(30.0), Amount::ZERO, now).unwrap();
+        service.authorize_spend(&token, &basic_needs, Amount::from(20.0), Amount::from(30.0), now).unwrap();
+        assert!(service.authorize_spend(&token, &basic_needs, Amount::from(30.0), Amount::from(30.0), now).is_err());
+        assert!(service.authorize_spend(&token, &basic_needs, Amount::from_units(1), Amount::from_units(i64::MAX), now).is_err());
+        assert!(service.authorize_spend(&token, &CurrencyType::Education, Amount::from(1.0), Amount::ZERO, now).is_err());
+
+        // The token stops working once expired
+        assert!(service.authorize_spend(&token, &basic_needs, Amount::from(1.0), Amount::ZERO, 10 * SECONDS_PER_DAY).is_err());
+        assert_eq!(spending_day(now), 1);
+        assert_eq!(spending_day(-1), -1);
+
+        // Altering the terms breaks the signature
+        let mut raised = token.clone();
+        raised.grant.capabilities = vec![Capability::Spend { currency_type: basic_needs, daily_limit: Amount::from(5000.0) }];
+        assert!(service.validate_capability(&raised, now).is_err());
+    }
+
+    #[test]
+    fn test_only_the_delegate_can_use_a_token() {
+        let (_, keypair, did) = setup();
+        let (bot_keypair, bot) = keyed_did();
+        let (thief, _) = keyed_did();
+        let spend = Capability::Spend { currency_type: CurrencyType::BasicNeeds, daily_limit: Amount::from(50.0) };
+        let token = CapabilityToken::sign(grant(&did, &bot, vec![spend]), &keypair).unwrap();
+
+        let message = b"pay Bob 20";
+        token.verify_delegate_signature(message, &bot_keypair.sign(message).to_bytes()).unwrap();
+        assert!(token.verify_delegate_signature(message, &thief.sign(message).to_bytes()).is_err());
+        assert!(token.verify_delegate_signature(b"pay Mallory 20", &bot_keypair.sign(message).to_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_vote_scope_and_revocation() {
+        let (mut service, keypair, did) = setup();
+        let (_, bot) = keyed_did();
+        let vote = Capability::Vote { categories: Some(vec![ProposalCategory::Technical]) };
+        let token = CapabilityToken::sign(grant(&did, &bot, vec![vote]), &keypair).unwrap();
+
+        service.authorize_vote(&token, &ProposalCategory::Technical, 60).unwrap();
+        assert!(service.authorize_vote(&token, &ProposalCategory::Economic, 60).is_err());
+        assert!(service.authorize_node_configuration(&token, 60).is_err());
+        let admin = CapabilityToken::sign(grant(&did, &bot, vec![Capability::ConfigureNode]), &keypair).unwrap();
+        service.authorize_node_configuration(&admin, 60).unwrap();
+
+        let token_id = token.id().unwrap();
+        let other = Keypair::generate(&mut OsRng {});
+        let forged = other.sign(&revocation_message(&token_id)).to_bytes();
+        assert!(service.revoke_capability(&did, &token_id, &forged).is_err());
+
+        let signature = keypair.sign(&revocation_message(&token_id)).to_bytes();
+        service.revoke_capability(&did, &token_id, &signature).unwrap();
+        assert!(service.authorize_vote(&token, &ProposalCategory::Technical, 60).is_err());
+        assert_eq!(service.revoked_capabilities(), vec![token_id]);
+    }
+}
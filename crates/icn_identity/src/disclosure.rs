@@ -0,0 +1,358 @@
+// File: crates/icn_identity/src/disclosure.rs
+
+//! Attributes an identity keeps sealed, readable only by those its policy
+//! names. The node acts as the attribute authority: each value is
+//! encrypted under the authority's key, bound to its owner, name and
+//! policy, and only decrypted for a reader whose credentials satisfy the
+//! policy and who has signed for the disclosure.
+
+use crate::{Attestation, IdentityService, PRIVATE_ATTRIBUTE_PREFIX};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::Signature;
+use icn_common::{IcnError, IcnResult};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Deepest a disclosure policy may nest.
+pub const MAX_POLICY_DEPTH: usize = 8;
+
+/// Longest value a sealed attribute can hold, in bytes.
+pub const MAX_SEALED_VALUE_LENGTH: usize = 4096;
+
+/// Oldest a signed disclosure request may be, so an overheard request
+/// can't be replayed later.
+pub const MAX_DISCLOSURE_REQUEST_AGE_SECS: i64 = 300;
+
+/// Who may read a sealed attribute. The owner always may.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DisclosurePolicy {
+    /// One identity, by DID.
+    Identity(String),
+    /// Holders of a network role, such as "treasurer".
+    Role(String),
+    /// Members of a cooperative, by DAO id.
+    DaoMember(String),
+    /// Identities with a standing attestation making this claim.
+    Claim(String),
+    AllOf(Vec<DisclosurePolicy>),
+    AnyOf(Vec<DisclosurePolicy>),
+}
+
+impl DisclosurePolicy {
+    pub fn validate(&self) -> IcnResult<()> {
+        self.validate_at(1)
+    }
+
+    fn validate_at(&self, depth: usize) -> IcnResult<()> {
+        if depth > MAX_POLICY_DEPTH {
+            return Err(IcnError::Identity(format!("Disclosure policies nest at most {} deep", MAX_POLICY_DEPTH)));
+        }
+        match self {
+            DisclosurePolicy::Identity(value) | DisclosurePolicy::Role(value)
+            | DisclosurePolicy::DaoMember(value) | DisclosurePolicy::Claim(value) => {
+                if value.trim().is_empty() {
+                    return Err(IcnError::Identity("Disclosure policy terms cannot be empty".into()));
+                }
+                Ok(())
+            }
+            DisclosurePolicy::AllOf(policies) | DisclosurePolicy::AnyOf(policies) => {
+                if policies.is_empty() {
+                    return Err(IcnError::Identity("Disclosure policy groups need at least one term".into()));
+                }
+                policies.iter().try_for_each(|policy| policy.validate_at(depth + 1))
+            }
+        }
+    }
+
+    pub fn admits(&self, reader: &ReaderCredentials) -> bool {
+        match self {
+            DisclosurePolicy::Identity(id) => reader.id == *id,
+            DisclosurePolicy::Role(role) => reader.roles.contains(role),
+            DisclosurePolicy::DaoMember(dao_id) => reader.daos.contains(dao_id),
+            DisclosurePolicy::Claim(claim) => reader.claims.contains(claim),
+            DisclosurePolicy::AllOf(policies) => policies.iter().all(|policy| policy.admits(reader)),
+            DisclosurePolicy::AnyOf(policies) => policies.iter().any(|policy| policy.admits(reader)),
+        }
+    }
+}
+
+/// What a reader's access to sealed attributes is judged on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReaderCredentials {
+    pub id: String,
+    pub roles: BTreeSet<String>,
+    /// Claims made in the reader's standing attestations.
+    pub claims: BTreeSet<String>,
+    /// Cooperatives the reader belongs to.
+    pub daos: BTreeSet<String>,
+}
+
+/// An attribute value kept encrypted with the identity.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SealedAttribute {
+    pub name: String,
+    pub policy: DisclosurePolicy,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub sealed_at: DateTime<Utc>,
+}
+
+/// A sealed attribute having been disclosed, kept so the owner can see
+/// who read what.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DisclosureRecord {
+    pub attribute: String,
+    pub reader: String,
+    pub disclosed_at: DateTime<Utc>,
+}
+
+/// An identity as a verifier is allowed to see it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdentityPresentation {
+    pub subject: String,
+    pub verifier: String,
+    pub roles: BTreeSet<String>,
+    /// Plain attributes, without private ones.
+    pub attributes: BTreeMap<String, String>,
+    pub attestations: Vec<Attestation>,
+    /// Sealed attributes the verifier's credentials unlock.
+    pub disclosed: BTreeMap<String, String>,
+    /// Sealed attributes held back from the verifier, by name.
+    pub withheld: Vec<String>,
+    pub presented_at: DateTime<Utc>,
+}
+
+/// The bytes an owner signs to seal `name` under `policy`.
+pub fn sealed_attribute_message(owner: &str, name: &str, policy: &DisclosurePolicy) -> IcnResult<Vec<u8>> {
+    let policy = serde_json::to_string(policy)
+        .map_err(|e| IcnError::Identity(format!("Failed to serialize disclosure policy: {}", e)))?;
+    Ok(format!("icn-sealed-attribute:{}:{}:{}", owner, name, policy).into_bytes())
+}
+
+/// The bytes a verifier signs to be shown `subject`'s identity.
+pub fn disclosure_request_message(subject: &str, verifier: &str, requested_at: i64) -> Vec<u8> {
+    format!("icn-disclosure-request:{}:{}:{}", subject, verifier, requested_at).into_bytes()
+}
+
+/// Binds a ciphertext to its owner, name and policy, so it can't be moved
+/// to another identity or put under a looser policy.
+fn associated_data(owner: &str, name: &str, policy: &DisclosurePolicy) -> IcnResult<Vec<u8>> {
+    sealed_attribute_message(owner, name, policy)
+}
+
+impl IdentityService {
+    /// Replaces the authority key attributes are sealed under. Attributes
+    /// sealed under the old key can no longer be read, so this belongs at
+    /// startup, with the key every node of the network shares.
+    pub fn set_disclosure_key(&mut self, key: [u8; 32]) {
+        self.disclosure_key = key;
+    }
+
+    fn disclosure_cipher(&self) -> IcnResult<Aes256Gcm> {
+        Aes256Gcm::new_from_slice(&self.disclosure_key)
+            .map_err(|e| IcnError::Identity(format!("Invalid disclosure key: {}", e)))
+    }
+
+    /// Seals an attribute under `policy`, replacing any sealed or plain
+    /// attribute of the same name. The owner signs
+    /// `sealed_attribute_message`.
+    pub fn seal_attribute(&mut self, owner: &str, name: &str, value: &str, policy: DisclosurePolicy, signature: &[u8]) -> IcnResult<SealedAttribute> {
+        let name = name.trim();
+        if name.is_empty() || value.len() > MAX_SEALED_VALUE_LENGTH {
+            return Err(IcnError::Identity(format!("Sealed attributes need a name and at most {} bytes", MAX_SEALED_VALUE_LENGTH)));
+        }
+        policy.validate()?;
+        let identity = self.get_identity(owner)?;
+        if identity.revoked {
+            return Err(IcnError::Identity("Revoked identities cannot seal attributes".into()));
+        }
+        let message = sealed_attribute_message(owner, name, &policy)?;
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid sealing signature: {}", e)))?;
+        if !identity.verify_signature(&message, &signature) {
+            return Err(IcnError::Identity("Attributes can only be sealed by their owner".into()));
+        }
+
+        let mut nonce = [0u8; 12];
+        OsRng.fill_bytes(&mut nonce);
+        let ciphertext = self.disclosure_cipher()?
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: value.as_bytes(), aad: &associated_data(owner, name, &policy)? })
+            .map_err(|_| IcnError::Identity("Failed to seal attribute".into()))?;
+        let sealed = SealedAttribute { name: name.to_string(), policy, nonce: nonce.to_vec(), ciphertext, sealed_at: Utc::now() };
+
+        let identity = self.identities.get_mut(owner)
+            .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+        identity.attributes.remove(name);
+        identity.sealed_attributes.retain(|existing| existing.name != name);
+        identity.sealed_attributes.push(sealed.clone());
+        Ok(sealed)
+    }
+
+    fn open_attribute(&self, owner: &str, sealed: &SealedAttribute) -> IcnResult<String> {
+        let plaintext = self.disclosure_cipher()?
+            .decrypt(Nonce::from_slice(&sealed.nonce), Payload { msg: &sealed.ciphertext, aad: &associated_data(owner, &sealed.name, &sealed.policy)? })
+            .map_err(|_| IcnError::Identity(format!("Sealed attribute {} could not be opened", sealed.name)))?;
+        String::from_utf8(plaintext)
+            .map_err(|e| IcnError::Identity(format!("Sealed attribute {} is not text: {}", sealed.name, e)))
+    }
+
+    /// The credentials `reader` holds in this service, together with the
+    /// cooperatives the caller knows it belongs to.
+    pub fn reader_credentials(&self, reader: &str, daos: BTreeSet<String>) -> IcnResult<ReaderCredentials> {
+        let identity = self.get_identity(reader)?;
+        if identity.revoked {
+            return Err(IcnError::Identity("Revoked identities hold no credentials".into()));
+        }
+        let claims = identity.attestations.iter()
+            .filter(|attestation| !attestation.is_revoked())
+            .filter(|attestation| self.identities.get(&attestation.issuer).is_some_and(|issuer| !issuer.revoked))
+            .map(|attestation| attestation.claim.clone())
+            .collect();
+        Ok(ReaderCredentials { id: reader.to_string(), roles: identity.roles.clone(), claims, daos })
+    }
+
+    /// Shows `subject` to a verifier: its public attributes, standing
+    /// attestations and the sealed attributes the verifier's credentials
+    /// unlock. The verifier signs `disclosure_request_message` no more than
+    /// `MAX_DISCLOSURE_REQUEST_AGE_SECS` before `now`, and every attribute
+    /// disclosed is recorded with the subject.
+    pub fn present_identity(&mut self, subject: &str, verifier: &ReaderCredentials, requested_at: i64, signature: &[u8], now: DateTime<Utc>) -> IcnResult<IdentityPresentation> {
+        let age = now.timestamp() - requested_at;
+        if !(0..=MAX_DISCLOSURE_REQUEST_AGE_SECS).contains(&age) {
+            return Err(IcnError::Identity(format!("Disclosure requests must be signed within {} seconds", MAX_DISCLOSURE_REQUEST_AGE_SECS)));
+        }
+        let signature = Signature::from_bytes(signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid disclosure request signature: {}", e)))?;
+        if !self.verify_signature(&verifier.id, &disclosure_request_message(subject, &verifier.id, requested_at), &signature)? {
+            return Err(IcnError::Identity("Disclosure request was not signed by the verifier".into()));
+        }
+
+        let identity = self.get_identity(subject)?;
+        let mut disclosed = BTreeMap::new();
+        let mut withheld = Vec::new();
+        for sealed in &identity.sealed_attributes {
+            if verifier.id == subject || sealed.policy.admits(verifier) {
+                disclosed.insert(sealed.name.clone(), self.open_attribute(subject, sealed)?);
+            } else {
+                withheld.push(sealed.name.clone());
+            }
+        }
+        let presentation = IdentityPresentation {
+            subject: subject.to_string(),
+            verifier: verifier.id.clone(),
+            roles: identity.roles.clone(),
+            attributes: identity.attributes.iter()
+                .filter(|(key, _)| !key.starts_with(PRIVATE_ATTRIBUTE_PREFIX))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+            attestations: identity.attestations.iter().filter(|attestation| !attestation.is_revoked()).cloned().collect(),
+            disclosed,
+            withheld,
+            presented_at: now,
+        };
+
+        if verifier.id != subject {
+            let identity = self.identities.get_mut(subject)
+                .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+            identity.disclosures.extend(presentation.disclosed.keys().map(|attribute| DisclosureRecord {
+                attribute: attribute.clone(),
+                reader: verifier.id.clone(),
+                disclosed_at: now,
+            }));
+        }
+        Ok(presentation)
+    }
+
+    /// Who has been shown the owner's sealed attributes, oldest first.
+    pub fn get_disclosures(&self, owner: &str) -> IcnResult<Vec<DisclosureRecord>> {
+        Ok(self.get_identity(owner)?.disclosures.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attestation_message, DecentralizedIdentity};
+    use ed25519_dalek::{Keypair, Signer};
+    use std::collections::HashMap;
+
+    fn member(service: &mut IdentityService) -> (String, Keypair) {
+        let (identity, keypair) = DecentralizedIdentity::new(HashMap::new());
+        let id = identity.id.clone();
+        service.identities.insert(id.clone(), identity);
+        (id, keypair)
+    }
+
+    fn seal(service: &mut IdentityService, owner: &(String, Keypair), name: &str, value: &str, policy: DisclosurePolicy) -> IcnResult<SealedAttribute> {
+        let signature = owner.1.sign(&sealed_attribute_message(&owner.0, name, &policy).unwrap());
+        service.seal_attribute(&owner.0, name, value, policy, &signature.to_bytes())
+    }
+
+    fn present(service: &mut IdentityService, subject: &str, verifier: &(String, Keypair), daos: &[&str], now: DateTime<Utc>) -> IcnResult<IdentityPresentation> {
+        let credentials = service.reader_credentials(&verifier.0, daos.iter().map(|dao| dao.to_string()).collect())?;
+        let signature = verifier.1.sign(&disclosure_request_message(subject, &verifier.0, now.timestamp()));
+        service.present_identity(subject, &credentials, now.timestamp(), &signature.to_bytes(), now)
+    }
+
+    #[test]
+    fn test_sealed_attributes_are_disclosed_by_policy() {
+        let mut service = IdentityService::new();
+        let alice = member(&mut service);
+        let treasurer = member(&mut service);
+        let outsider = member(&mut service);
+        let auditor = member(&mut service);
+        service.grant_role(&treasurer.0, "treasurer").unwrap();
+        service.grant_role(&outsider.0, "treasurer").unwrap();
+        let claim = treasurer.1.sign(&attestation_message(&treasurer.0, &auditor.0, "certified auditor", 1_700_000_000));
+        service.add_attestation(&treasurer.0, &auditor.0, "certified auditor", 1_700_000_000, &claim.to_bytes()).unwrap();
+
+        let treasurers_of_bakery = DisclosurePolicy::AllOf(vec![
+            DisclosurePolicy::DaoMember("bakery".to_string()),
+            DisclosurePolicy::Role("treasurer".to_string()),
+        ]);
+        service.update_attributes(&alice.0, HashMap::from([("income".to_string(), "plain".to_string())])).unwrap();
+        seal(&mut service, &alice, "income", "30-40k", treasurers_of_bakery.clone()).unwrap();
+        seal(&mut service, &alice, "address", "1 Mill Lane", DisclosurePolicy::Claim("certified auditor".to_string())).unwrap();
+        assert!(!service.get_identity(&alice.0).unwrap().attributes.contains_key("income"));
+        let forged = treasurer.1.sign(&sealed_attribute_message(&alice.0, "income", &treasurers_of_bakery).unwrap());
+        assert!(service.seal_attribute(&alice.0, "income", "0", treasurers_of_bakery, &forged.to_bytes()).is_err());
+        assert!(seal(&mut service, &alice, "empty", "x", DisclosurePolicy::AnyOf(Vec::new())).is_err());
+
+        let now = Utc::now();
+        let shown = present(&mut service, &alice.0, &treasurer, &["bakery"], now).unwrap();
+        assert_eq!(shown.disclosed, BTreeMap::from([("income".to_string(), "30-40k".to_string())]));
+        assert_eq!(shown.withheld, vec!["address".to_string()]);
+        // Holding the role without membership of the cooperative isn't enough
+        let shown = present(&mut service, &alice.0, &outsider, &["dairy"], now).unwrap();
+        assert!(shown.disclosed.is_empty());
+        let shown = present(&mut service, &alice.0, &auditor, &[], now).unwrap();
+        assert_eq!(shown.disclosed["address"], "1 Mill Lane");
+        assert_eq!(present(&mut service, &alice.0, &alice, &[], now).unwrap().disclosed.len(), 2);
+
+        // Requests must be fresh and signed by the verifier
+        let stale = now + chrono::Duration::seconds(MAX_DISCLOSURE_REQUEST_AGE_SECS + 1);
+        assert!(present(&mut service, &alice.0, &treasurer, &["bakery"], now).is_ok());
+        let credentials = service.reader_credentials(&treasurer.0, BTreeSet::from(["bakery".to_string()])).unwrap();
+        let signature = treasurer.1.sign(&disclosure_request_message(&alice.0, &treasurer.0, now.timestamp()));
+        assert!(service.present_identity(&alice.0, &credentials, now.timestamp(), &signature.to_bytes(), stale).is_err());
+        let impostor = outsider.1.sign(&disclosure_request_message(&alice.0, &treasurer.0, now.timestamp()));
+        assert!(service.present_identity(&alice.0, &credentials, now.timestamp(), &impostor.to_bytes(), now).is_err());
+
+        let readers: Vec<(String, String)> = service.get_disclosures(&alice.0).unwrap().into_iter()
+            .map(|record| (record.attribute, record.reader))
+            .collect();
+        assert_eq!(readers, vec![
+            ("income".to_string(), treasurer.0.clone()),
+            ("address".to_string(), auditor.0.clone()),
+            ("income".to_string(), treasurer.0.clone()),
+        ]);
+
+        // A different authority key can't open what was sealed
+        service.set_disclosure_key([7; 32]);
+        assert!(present(&mut service, &alice.0, &treasurer, &["bakery"], now).is_err());
+    }
+}
@@ -0,0 +1,50 @@
+// File: crates/icn_identity/src/keystore.rs
+
+use crate::{attestation_message, Attestation, DecentralizedIdentity, IdentityService};
+use ed25519_dalek::Signer;
+use icn_common::IcnResult;
+use icn_keystore::{Keystore, Passphrase};
+use std::collections::HashMap;
+
+impl IdentityService {
+    /// Creates an identity whose key is generated into `keystore` under
+    /// `key_name`, so the secret never leaves the keystore unencrypted.
+    pub fn create_identity_in_keystore(&mut self, keystore: &Keystore, key_name: &str, passphrase: &Passphrase, attributes: HashMap<String, String>) -> IcnResult<DecentralizedIdentity> {
+        let key = keystore.generate(key_name, passphrase)?;
+        Ok(self.register_identity(&key.did, attributes, 1.0)?.clone())
+    }
+
+    /// Attests `claim` about `subject` as the identity whose key is stored
+    /// under `key_name`.
+    pub fn attest_with_keystore(&mut self, keystore: &Keystore, key_name: &str, passphrase: &Passphrase, subject: &str, claim: &str, issued_at: i64) -> IcnResult<Attestation> {
+        let keypair = keystore.unlock(key_name, passphrase)?;
+        let issuer = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        let signature = keypair.sign(&attestation_message(&issuer, subject, claim.trim(), issued_at));
+        self.add_attestation(&issuer, subject, claim, issued_at, &signature.to_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_keystore::KdfParams;
+
+    #[test]
+    fn test_identities_sign_with_keystore_keys() {
+        let dir = std::env::temp_dir().join(format!("icn-identity-keystore-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let keystore = Keystore::open(&dir).unwrap()
+            .with_kdf_params(KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 });
+        let passphrase = Passphrase::new("correct horse").unwrap();
+        let mut service = IdentityService::new();
+
+        let alice = service.create_identity_in_keystore(&keystore, "alice", &passphrase, HashMap::new()).unwrap();
+        assert_eq!(keystore.key_info("alice").unwrap().did, alice.id);
+        let bob = service.create_identity(HashMap::new()).unwrap();
+
+        let attestation = service.attest_with_keystore(&keystore, "alice", &passphrase, &bob.id, "is a carpenter", 1_700_000_000).unwrap();
+        assert_eq!(attestation.issuer, alice.id);
+        assert!(service.attest_with_keystore(&keystore, "alice", &Passphrase::new("wrong").unwrap(), &bob.id, "is a baker", 1_700_000_000).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
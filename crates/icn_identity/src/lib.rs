@@ -1,11 +1,39 @@
 // File: crates/icn_identity/src/lib.rs
 
-use icn_common::{IcnResult, IcnError};
-use std::collections::HashMap;
-use chrono::{DateTime, Utc};
+pub mod attestations;
+pub mod capability;
+pub mod disclosure;
+pub mod keystore;
+pub mod notifications;
+pub mod requests;
+pub mod roster;
+pub mod schemes;
+pub mod vrf;
+
+pub use crate::attestations::{attestation_message, attestation_revocation_message, Attestation, AttestationPolicy, MAX_CLAIM_LENGTH};
+pub use crate::capability::{revocation_message, spending_day, Capability, CapabilityGrant, CapabilityRegistry, CapabilityToken};
+pub use crate::disclosure::{
+    disclosure_request_message, sealed_attribute_message, DisclosurePolicy, DisclosureRecord, IdentityPresentation, ReaderCredentials,
+    SealedAttribute, MAX_DISCLOSURE_REQUEST_AGE_SECS,
+};
+pub use crate::notifications::{ActivityCategory, Delivery, NotificationPreferences};
+pub use crate::requests::signed_request_message;
+pub use crate::roster::{
+    parse_roster, roster_to_csv, ExportedIdentity, ImportReport, ImportedMember, MembershipCredential, RecordError,
+    RosterFormat, RosterRecord, PRIVATE_ATTRIBUTE_PREFIX,
+};
+pub use crate::schemes::{aggregate_bls_signatures, key_registration_message, verify_bls_aggregate, SchemeKeypair, SchemePublicKey};
+pub use crate::vrf::{selection_input, SelectionAudit, VrfKey, VrfOutput, VrfSelection, COMMITTEE_SELECTION, JURY_SELECTION};
+pub use icn_common::public_key_from_did;
+pub use icn_reputation::{ReputationChange, ReputationHistory};
+
+use icn_common::{IcnResult, IcnError, SignatureScheme};
+use std::collections::{BTreeSet, HashMap};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Serialize, Deserialize};
 use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
 use rand::rngs::OsRng;
+use rand::RngCore;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DecentralizedIdentity {
@@ -15,6 +43,31 @@ pub struct DecentralizedIdentity {
     pub reputation: f64,
     pub attributes: HashMap<String, String>,
     pub revoked: bool,
+    /// Roles held within the network, such as "treasurer" or "auditor",
+    /// used to decide which contract functions the identity may call.
+    #[serde(default)]
+    pub roles: BTreeSet<String>,
+    /// Keys registered for schemes other than ed25519, at most one per
+    /// scheme. The ed25519 key is the one in the DID.
+    #[serde(default)]
+    pub keys: Vec<SchemePublicKey>,
+    #[serde(default)]
+    pub notifications: NotificationPreferences,
+    /// Claims other members have signed about this identity.
+    #[serde(default)]
+    pub attestations: Vec<Attestation>,
+    /// Every change made to `reputation`, oldest first.
+    #[serde(default)]
+    pub reputation_history: ReputationHistory,
+    /// Attributes kept encrypted, readable only as their policy allows.
+    #[serde(default)]
+    pub sealed_attributes: Vec<SealedAttribute>,
+    /// Who has been shown which sealed attributes, oldest first.
+    #[serde(default)]
+    pub disclosures: Vec<DisclosureRecord>,
+    /// Nonce of the last signed request accepted from this identity.
+    #[serde(default)]
+    pub request_nonce: u64,
 }
 
 impl DecentralizedIdentity {
@@ -32,6 +85,14 @@ impl DecentralizedIdentity {
                 reputation: 1.0,
                 attributes,
                 revoked: false,
+                roles: BTreeSet::new(),
+                keys: Vec::new(),
+                notifications: NotificationPreferences::default(),
+                attestations: Vec::new(),
+                reputation_history: ReputationHistory::new(),
+            sealed_attributes: Vec::new(),
+            disclosures: Vec::new(),
+            request_nonce: 0,
             },
             keypair,
         )
@@ -40,16 +101,35 @@ impl DecentralizedIdentity {
     pub fn verify_signature(&self, message: &[u8], signature: &Signature) -> bool {
         self.public_key.verify(message, signature).is_ok()
     }
+
+    /// The identity's key for `scheme`, if it has one.
+    pub fn key(&self, scheme: SignatureScheme) -> Option<SchemePublicKey> {
+        match scheme {
+            SignatureScheme::Ed25519 => Some(SchemePublicKey { scheme, bytes: self.public_key.to_bytes().to_vec() }),
+            _ => self.keys.iter().find(|key| key.scheme == scheme).cloned(),
+        }
+    }
 }
 
 pub struct IdentityService {
     identities: HashMap<String, DecentralizedIdentity>,
+    capabilities: CapabilityRegistry,
+    attestation_policy: Option<AttestationPolicy>,
+    // Authority key sealed attributes are encrypted under
+    disclosure_key: [u8; 32],
 }
 
 impl IdentityService {
     pub fn new() -> Self {
         IdentityService {
             identities: HashMap::new(),
+            capabilities: CapabilityRegistry::default(),
+            attestation_policy: None,
+            disclosure_key: {
+                let mut key = [0u8; 32];
+                OsRng.fill_bytes(&mut key);
+                key
+            },
         }
     }
 
@@ -65,6 +145,33 @@ impl IdentityService {
         Ok(identity)
     }
 
+    /// Registers an identity whose key is held elsewhere, such as a founding
+    /// member listed in the genesis file. The DID must embed the public key.
+    pub fn register_identity(&mut self, id: &str, attributes: HashMap<String, String>, reputation: f64) -> IcnResult<&DecentralizedIdentity> {
+        if self.identities.contains_key(id) {
+            return Err(IcnError::Identity("Identity already exists".into()));
+        }
+        let public_key = public_key_from_did(id)?;
+
+        let identity = DecentralizedIdentity {
+            id: id.to_string(),
+            public_key,
+            created_at: Utc::now(),
+            reputation,
+            attributes,
+            revoked: false,
+            roles: BTreeSet::new(),
+            keys: Vec::new(),
+            notifications: NotificationPreferences::default(),
+            attestations: Vec::new(),
+            reputation_history: ReputationHistory::new(),
+            sealed_attributes: Vec::new(),
+            disclosures: Vec::new(),
+            request_nonce: 0,
+        };
+        Ok(self.identities.entry(id.to_string()).or_insert(identity))
+    }
+
     pub fn get_identity(&self, id: &str) -> IcnResult<&DecentralizedIdentity> {
         self.identities.get(id)
             .ok_or_else(|| IcnError::Identity("Identity not found".into()))
@@ -79,14 +186,21 @@ impl IdentityService {
     }
 
     pub fn update_reputation(&mut self, id: &str, change: f64) -> IcnResult<()> {
+        self.adjust_reputation(id, change, "identity", "direct update")
+    }
+
+    /// Changes an identity's reputation, keeping it between 0 and 100, and
+    /// records the change with what made it and why.
+    pub fn adjust_reputation(&mut self, id: &str, change: f64, source: &str, reason: &str) -> IcnResult<()> {
         let identity = self.identities.get_mut(id)
             .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
-        
-        identity.reputation += change;
-        
-        // Ensure reputation stays within a reasonable range (e.g., 0 to 100)
-        identity.reputation = identity.reputation.max(0.0).min(100.0);
-        
+        if !change.is_finite() {
+            return Err(IcnError::Identity("Reputation changes must be finite".into()));
+        }
+
+        let before = identity.reputation;
+        identity.reputation = (before + change).clamp(0.0, 100.0);
+        identity.reputation_history.record(source, reason, before, identity.reputation, Utc::now());
         Ok(())
     }
 
@@ -95,6 +209,46 @@ impl IdentityService {
         Ok(identity.verify_signature(message, signature))
     }
 
+    /// Adds a key of another scheme to an identity, replacing any key it
+    /// had for that scheme. The identity authorizes the key with its
+    /// ed25519 signature, and the key proves it is held with
+    /// `SchemeKeypair::prove_possession`, both over
+    /// `key_registration_message`.
+    pub fn register_key(&mut self, id: &str, key: SchemePublicKey, authorization: &[u8], proof_of_possession: &[u8]) -> IcnResult<()> {
+        let identity = self.identities.get_mut(id)
+            .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+        if identity.revoked {
+            return Err(IcnError::Identity("Cannot register keys for a revoked identity".into()));
+        }
+        if key.scheme == SignatureScheme::Ed25519 {
+            return Err(IcnError::Identity("The ed25519 key of an identity is fixed by its DID".into()));
+        }
+        let key = SchemePublicKey::new(key.scheme, key.bytes)?;
+        let message = key_registration_message(id, &key);
+        let authorization = Signature::from_bytes(authorization)
+            .map_err(|e| IcnError::Identity(format!("Invalid authorization signature: {}", e)))?;
+        if !identity.verify_signature(&message, &authorization) {
+            return Err(IcnError::Identity("Key registration was not authorized by the identity".into()));
+        }
+        key.verify_possession(&message, proof_of_possession)?;
+
+        identity.keys.retain(|existing| existing.scheme != key.scheme);
+        identity.keys.push(key);
+        identity.keys.sort_by_key(|key| key.scheme);
+        Ok(())
+    }
+
+    pub fn get_key(&self, id: &str, scheme: SignatureScheme) -> IcnResult<SchemePublicKey> {
+        self.get_identity(id)?.key(scheme)
+            .ok_or_else(|| IcnError::Identity(format!("{} has no {} key", id, scheme.name())))
+    }
+
+    /// Like `verify_signature`, for a signature of any scheme the identity
+    /// has a key for.
+    pub fn verify_scheme_signature(&self, id: &str, scheme: SignatureScheme, message: &[u8], signature: &[u8]) -> IcnResult<bool> {
+        Ok(self.get_key(id, scheme)?.verify(message, signature).is_ok())
+    }
+
     pub fn list_identities(&self) -> Vec<&DecentralizedIdentity> {
         self.identities.values().collect()
     }
@@ -105,9 +259,37 @@ impl IdentityService {
         Ok(())
     }
 
+    /// The identity's reputation, including what attestations about it
+    /// add under the attestation policy.
     pub fn get_reputation(&self, id: &str) -> IcnResult<f64> {
         let identity = self.get_identity(id)?;
-        Ok(identity.reputation)
+        Ok(self.scored_reputation(identity))
+    }
+
+    /// Like `get_reputation`, for an identity already looked up.
+    pub fn scored_reputation(&self, identity: &DecentralizedIdentity) -> f64 {
+        (identity.reputation + self.attestation_bonus(identity)).min(100.0)
+    }
+
+    /// Changes made to an identity's reputation, oldest first.
+    pub fn get_reputation_history(&self, id: &str) -> IcnResult<&[ReputationChange]> {
+        Ok(self.get_identity(id)?.reputation_history.changes())
+    }
+
+    /// The identity's reputation averaged over the last `window_secs`,
+    /// weighted by how long each value was held, plus what attestations add.
+    pub fn get_trailing_reputation(&self, id: &str, window_secs: i64) -> IcnResult<f64> {
+        let identity = self.get_identity(id)?;
+        Ok(self.trailing_reputation(identity, window_secs, Utc::now()))
+    }
+
+    /// Like `get_trailing_reputation`, for an identity already looked up.
+    /// Identities whose reputation hasn't changed score their current one.
+    pub fn trailing_reputation(&self, identity: &DecentralizedIdentity, window_secs: i64, now: DateTime<Utc>) -> f64 {
+        let average = identity.reputation_history
+            .time_weighted_average(now - Duration::seconds(window_secs.max(0)), now)
+            .unwrap_or(identity.reputation);
+        (average + self.attestation_bonus(identity)).min(100.0)
     }
 
     pub fn get_attribute(&self, id: &str, attribute_key: &str) -> IcnResult<Option<String>> {
@@ -122,6 +304,37 @@ impl IdentityService {
         Ok(())
     }
 
+    pub fn grant_role(&mut self, id: &str, role: &str) -> IcnResult<()> {
+        let identity = self.identities.get_mut(id)
+            .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+        if identity.revoked {
+            return Err(IcnError::Identity("Cannot grant roles to a revoked identity".into()));
+        }
+        if role.trim().is_empty() {
+            return Err(IcnError::Identity("Role name cannot be empty".into()));
+        }
+        identity.roles.insert(role.to_string());
+        Ok(())
+    }
+
+    pub fn revoke_role(&mut self, id: &str, role: &str) -> IcnResult<()> {
+        let identity = self.identities.get_mut(id)
+            .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+        if !identity.roles.remove(role) {
+            return Err(IcnError::Identity(format!("Identity does not hold role {}", role)));
+        }
+        Ok(())
+    }
+
+    /// Roles an identity holds. Revoked identities hold none.
+    pub fn get_roles(&self, id: &str) -> IcnResult<BTreeSet<String>> {
+        let identity = self.get_identity(id)?;
+        if identity.revoked {
+            return Ok(BTreeSet::new());
+        }
+        Ok(identity.roles.clone())
+    }
+
     pub fn revoke_identity(&mut self, id: &str) -> IcnResult<()> {
         let identity = self.identities.get_mut(id)
             .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
@@ -217,6 +430,29 @@ mod tests {
         assert_eq!(updated_identity.reputation, 0.0);
     }
 
+    #[test]
+    fn test_reputation_history_and_trailing_average() {
+        let mut service = IdentityService::new();
+        let identity = service.create_identity(HashMap::new()).unwrap();
+        assert_eq!(service.get_trailing_reputation(&identity.id, 3600).unwrap(), 1.0);
+
+        service.adjust_reputation(&identity.id, 150.0, "contract", "payroll").unwrap();
+        service.adjust_reputation(&identity.id, -10.0, "dispute", "lost dispute d1").unwrap();
+        assert!(service.adjust_reputation(&identity.id, f64::NAN, "contract", "bad").is_err());
+
+        let history = service.get_reputation_history(&identity.id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!((history[0].source.as_str(), history[0].delta), ("contract", 99.0));
+        assert_eq!((history[1].reason.as_str(), history[1].reputation), ("lost dispute d1", 90.0));
+
+        // Raised halfway through the window, so the average sits between the two
+        let identity = service.get_identity(&identity.id).unwrap();
+        let day_later = history[1].timestamp + Duration::days(1);
+        let average = service.trailing_reputation(identity, 2 * 24 * 3600, day_later);
+        assert!(average > 45.0 && average < 46.0);
+        assert_eq!(service.trailing_reputation(identity, 3600, day_later), 90.0);
+    }
+
     #[test]
     fn test_signature_verification() {
         let mut service = IdentityService::new();
@@ -231,6 +467,34 @@ mod tests {
         assert!(service.verify_signature(&identity.id, message, &signature).unwrap());
     }
 
+    #[test]
+    fn test_register_keys_of_other_schemes() {
+        let mut service = IdentityService::new();
+        let (identity, keypair) = DecentralizedIdentity::new(HashMap::new());
+        service.identities.insert(identity.id.clone(), identity.clone());
+
+        let secp = SchemeKeypair::generate(SignatureScheme::Secp256k1);
+        let key = secp.public_key();
+        let message = key_registration_message(&identity.id, &key);
+        let authorization = keypair.sign(&message).to_bytes();
+
+        // The identity must authorize the key and the key must be held
+        let stranger = SchemeKeypair::generate(SignatureScheme::Secp256k1);
+        assert!(service.register_key(&identity.id, key.clone(), &authorization, &stranger.prove_possession(&message)).is_err());
+        assert!(service.register_key(&identity.id, key.clone(), &[0; 64], &secp.prove_possession(&message)).is_err());
+        service.register_key(&identity.id, key.clone(), &authorization, &secp.prove_possession(&message)).unwrap();
+
+        assert_eq!(service.get_key(&identity.id, SignatureScheme::Secp256k1).unwrap(), key);
+        assert!(service.get_key(&identity.id, SignatureScheme::Bls12381).is_err());
+        assert!(service.verify_scheme_signature(&identity.id, SignatureScheme::Secp256k1, b"hello", &secp.sign(b"hello")).unwrap());
+        assert!(!service.verify_scheme_signature(&identity.id, SignatureScheme::Secp256k1, b"hello", &stranger.sign(b"hello")).unwrap());
+        assert!(service.verify_scheme_signature(&identity.id, SignatureScheme::Ed25519, b"hello", &keypair.sign(b"hello").to_bytes()).unwrap());
+
+        let ed25519 = SchemeKeypair::generate(SignatureScheme::Ed25519).public_key();
+        let message = key_registration_message(&identity.id, &ed25519);
+        assert!(service.register_key(&identity.id, ed25519, &keypair.sign(&message).to_bytes(), &[]).is_err());
+    }
+
     #[test]
     fn test_list_identities() {
         let mut service = IdentityService::new();
@@ -315,4 +579,19 @@ mod tests {
         let another_update = HashMap::new();
         assert!(service.update_identity(&identity.id, another_update).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_roles() {
+        let mut service = IdentityService::new();
+        let identity = service.create_identity(HashMap::new()).unwrap();
+
+        service.grant_role(&identity.id, "treasurer").unwrap();
+        assert!(service.get_roles(&identity.id).unwrap().contains("treasurer"));
+        assert!(service.revoke_role(&identity.id, "auditor").is_err());
+
+        // A revoked identity keeps its record but loses the rights its roles gave
+        service.revoke_identity(&identity.id).unwrap();
+        assert!(service.get_roles(&identity.id).unwrap().is_empty());
+        assert!(service.grant_role(&identity.id, "auditor").is_err());
+    }
+}
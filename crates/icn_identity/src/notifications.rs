@@ -0,0 +1,95 @@
+// File: crates/icn_identity/src/notifications.rs
+
+use crate::IdentityService;
+use icn_common::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+
+/// Kinds of activity that show up in a member's feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ActivityCategory {
+    PaymentReceived,
+    /// A proposal the member may vote on was opened.
+    ProposalNeedsVote,
+    DaoAnnouncement,
+    /// Voting closes soon on a proposal the member has not voted on.
+    VotingDeadline,
+}
+
+/// How a member wants to hear about a category of activity. Every item
+/// lands in the feed whatever the choice.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Delivery {
+    /// Sent as soon as it happens.
+    #[default]
+    Immediate,
+    /// Collected and sent together once a day.
+    Digest,
+    /// Only kept in the feed.
+    Muted,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    /// Categories left out are delivered immediately.
+    #[serde(default)]
+    pub categories: BTreeMap<ActivityCategory, Delivery>,
+    /// URL notifications and digests are also POSTed to.
+    #[serde(default)]
+    pub webhook: Option<String>,
+}
+
+impl NotificationPreferences {
+    pub fn delivery(&self, category: ActivityCategory) -> Delivery {
+        self.categories.get(&category).copied().unwrap_or_default()
+    }
+}
+
+impl IdentityService {
+    pub fn set_notification_preferences(&mut self, id: &str, preferences: NotificationPreferences) -> IcnResult<()> {
+        if let Some(webhook) = &preferences.webhook {
+            if !(webhook.starts_with("https://") || webhook.starts_with("http://")) {
+                return Err(IcnError::Identity(format!("Notification webhook must be an http(s) URL: {}", webhook)));
+            }
+        }
+        let identity = self.identities.get_mut(id)
+            .ok_or_else(|| IcnError::Identity("Identity not found".into()))?;
+        identity.notifications = preferences;
+        Ok(())
+    }
+
+    /// The identity's preferences, or the defaults for accounts that are
+    /// not registered identities.
+    pub fn notification_preferences(&self, id: &str) -> NotificationPreferences {
+        self.identities.get(id)
+            .map(|identity| identity.notifications.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_notification_preferences() {
+        let mut service = IdentityService::new();
+        let id = service.create_identity(HashMap::new()).unwrap().id;
+        assert_eq!(service.notification_preferences(&id).delivery(ActivityCategory::PaymentReceived), Delivery::Immediate);
+
+        let preferences = NotificationPreferences {
+            categories: BTreeMap::from([(ActivityCategory::PaymentReceived, Delivery::Digest)]),
+            webhook: Some("https://example.org/hook".to_string()),
+        };
+        service.set_notification_preferences(&id, preferences.clone()).unwrap();
+        assert_eq!(service.notification_preferences(&id), preferences);
+        assert_eq!(preferences.delivery(ActivityCategory::PaymentReceived), Delivery::Digest);
+        assert_eq!(preferences.delivery(ActivityCategory::DaoAnnouncement), Delivery::Immediate);
+
+        let bad_webhook = NotificationPreferences { webhook: Some("ftp://example.org".to_string()), ..Default::default() };
+        assert!(service.set_notification_preferences(&id, bad_webhook).is_err());
+        assert!(service.set_notification_preferences("did:icn:missing", NotificationPreferences::default()).is_err());
+        assert_eq!(service.notification_preferences("did:icn:missing"), NotificationPreferences::default());
+    }
+}
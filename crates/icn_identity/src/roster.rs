@@ -0,0 +1,357 @@
+// File: crates/icn_identity/src/roster.rs
+
+use crate::{public_key_from_did, IdentityService};
+use icn_common::{IcnResult, IcnError};
+use ed25519_dalek::{Keypair, Signature, Signer};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// Attributes whose key starts with this are left out of exports.
+pub const PRIVATE_ATTRIBUTE_PREFIX: &str = "private.";
+
+/// Reputation given to imported members when the roster doesn't say.
+const DEFAULT_IMPORT_REPUTATION: f64 = 1.0;
+
+/// Numbered records read from a roster, and the rows that couldn't be read.
+pub type ParsedRoster = (Vec<(usize, RosterRecord)>, Vec<RecordError>);
+
+// Columns of a CSV roster that aren't attributes
+const DID_COLUMN: &str = "did";
+const REPUTATION_COLUMN: &str = "reputation";
+const ROLES_COLUMN: &str = "roles";
+// Written by exports and ignored on import
+const REVOKED_COLUMN: &str = "revoked";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RosterFormat {
+    /// A header row, then one member per row. `did`, `reputation` and
+    /// `roles` (separated by `;`) have fixed meanings and `revoked` is
+    /// ignored; every other column is an attribute.
+    Csv,
+    /// An array of `RosterRecord`s.
+    Json,
+}
+
+/// One member in a roster being imported.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RosterRecord {
+    /// DID of a member who already holds a key. Members without one are
+    /// given a new identity.
+    #[serde(default)]
+    pub did: Option<String>,
+    #[serde(default)]
+    pub attributes: HashMap<String, String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub reputation: Option<f64>,
+}
+
+/// A record that could not be imported. `record` counts from 1, not
+/// including a CSV header.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordError {
+    pub record: usize,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportedMember {
+    pub record: usize,
+    /// `None` in a dry run for members who would be given a new DID.
+    pub id: Option<String>,
+    pub credential: Option<MembershipCredential>,
+}
+
+/// What an import did, or in a dry run what it would do. Valid records are
+/// imported even when others fail.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub imported: Vec<ImportedMember>,
+    pub errors: Vec<RecordError>,
+}
+
+/// A member's identity without its private attributes.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExportedIdentity {
+    pub did: String,
+    pub reputation: f64,
+    pub roles: Vec<String>,
+    pub revoked: bool,
+    pub attributes: HashMap<String, String>,
+}
+
+/// An issuer's signed statement that `subject` is a member, given to
+/// members as they are imported.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MembershipCredential {
+    pub subject: String,
+    pub issuer: String,
+    pub issued_at: i64,
+    pub signature: Vec<u8>,
+}
+
+impl MembershipCredential {
+    /// Signs a credential for `subject`. The issuer is the DID of `keypair`.
+    pub fn issue(subject: &str, keypair: &Keypair, issued_at: i64) -> Self {
+        let issuer = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+        let signature = keypair.sign(&Self::signing_bytes(subject, &issuer, issued_at)).to_bytes().to_vec();
+        MembershipCredential { subject: subject.to_string(), issuer, issued_at, signature }
+    }
+
+    fn signing_bytes(subject: &str, issuer: &str, issued_at: i64) -> Vec<u8> {
+        format!("membership-credential:{}:{}:{}", issuer, subject, issued_at).into_bytes()
+    }
+}
+
+/// Reads a roster. Fails only if the roster as a whole can't be read;
+/// problems with single CSV rows are returned alongside the records.
+pub fn parse_roster(data: &str, format: RosterFormat) -> IcnResult<ParsedRoster> {
+    match format {
+        RosterFormat::Json => {
+            let records: Vec<RosterRecord> = serde_json::from_str(data)
+                .map_err(|e| IcnError::Identity(format!("Invalid JSON roster: {}", e)))?;
+            Ok((records.into_iter().enumerate().map(|(i, record)| (i + 1, record)).collect(), Vec::new()))
+        }
+        RosterFormat::Csv => parse_csv_roster(data),
+    }
+}
+
+fn parse_csv_roster(data: &str) -> IcnResult<ParsedRoster> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(data.as_bytes());
+    let headers = reader.headers()
+        .map_err(|e| IcnError::Identity(format!("Invalid CSV roster header: {}", e)))?
+        .clone();
+
+    let mut records = Vec::new();
+    let mut errors = Vec::new();
+    for (i, row) in reader.records().enumerate() {
+        let row = match row {
+            Ok(row) => row,
+            Err(e) => {
+                errors.push(RecordError { record: i + 1, message: e.to_string() });
+                continue;
+            }
+        };
+        let mut record = RosterRecord::default();
+        let mut reputation = None;
+        for (column, value) in headers.iter().zip(row.iter()) {
+            if value.is_empty() {
+                continue;
+            }
+            match column {
+                DID_COLUMN => record.did = Some(value.to_string()),
+                REPUTATION_COLUMN => reputation = Some(value),
+                ROLES_COLUMN => record.roles = value.split(';').map(|role| role.trim().to_string()).collect(),
+                REVOKED_COLUMN => {}
+                _ => {
+                    record.attributes.insert(column.to_string(), value.to_string());
+                }
+            }
+        }
+        match reputation.map(str::parse::<f64>).transpose() {
+            Ok(parsed) => record.reputation = parsed,
+            Err(e) => {
+                errors.push(RecordError { record: i + 1, message: format!("Invalid reputation: {}", e) });
+                continue;
+            }
+        }
+        records.push((i + 1, record));
+    }
+    Ok((records, errors))
+}
+
+/// Writes exported identities as a CSV roster that `parse_roster` reads back.
+pub fn roster_to_csv(identities: &[ExportedIdentity]) -> IcnResult<String> {
+    let attribute_columns: BTreeSet<&String> = identities.iter()
+        .flat_map(|identity| identity.attributes.keys())
+        .collect();
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let header = [DID_COLUMN, REPUTATION_COLUMN, ROLES_COLUMN, REVOKED_COLUMN].into_iter()
+        .chain(attribute_columns.iter().map(|column| column.as_str()));
+    writer.write_record(header).map_err(csv_error)?;
+    for identity in identities {
+        let row = [
+            identity.did.clone(),
+            identity.reputation.to_string(),
+            identity.roles.join(";"),
+            identity.revoked.to_string(),
+        ].into_iter()
+            .chain(attribute_columns.iter().map(|column| identity.attributes.get(*column).cloned().unwrap_or_default()));
+        writer.write_record(row).map_err(csv_error)?;
+    }
+    let bytes = writer.into_inner().map_err(|e| IcnError::Identity(format!("Failed to write CSV roster: {}", e)))?;
+    String::from_utf8(bytes).map_err(|e| IcnError::Identity(format!("Failed to write CSV roster: {}", e)))
+}
+
+fn csv_error(e: csv::Error) -> IcnError {
+    IcnError::Identity(format!("Failed to write CSV roster: {}", e))
+}
+
+impl IdentityService {
+    /// Imports a member roster. Each record is checked on its own, and the
+    /// valid ones are imported unless `dry_run` is set. With an `issuer`,
+    /// every imported member is given a membership credential signed by it.
+    pub fn import_roster(&mut self, data: &str, format: RosterFormat, dry_run: bool, issuer: Option<&Keypair>, now: i64) -> IcnResult<ImportReport> {
+        let (records, mut errors) = parse_roster(data, format)?;
+        let mut imported = Vec::new();
+        let mut seen = HashSet::new();
+        for (number, record) in records {
+            if let Err(e) = self.validate_roster_record(&record, &mut seen) {
+                errors.push(RecordError { record: number, message: e.to_string() });
+                continue;
+            }
+            if dry_run {
+                imported.push(ImportedMember { record: number, id: record.did.clone(), credential: None });
+                continue;
+            }
+            match self.import_record(record) {
+                Ok(id) => {
+                    let credential = issuer.map(|keypair| MembershipCredential::issue(&id, keypair, now));
+                    imported.push(ImportedMember { record: number, id: Some(id), credential });
+                }
+                Err(e) => errors.push(RecordError { record: number, message: e.to_string() }),
+            }
+        }
+        errors.sort_by_key(|error| error.record);
+        Ok(ImportReport { dry_run, imported, errors })
+    }
+
+    fn validate_roster_record(&self, record: &RosterRecord, seen: &mut HashSet<String>) -> IcnResult<()> {
+        if let Some(did) = &record.did {
+            public_key_from_did(did)?;
+            if self.identities.contains_key(did) {
+                return Err(IcnError::Identity(format!("{} is already registered", did)));
+            }
+            if !seen.insert(did.clone()) {
+                return Err(IcnError::Identity(format!("{} appears more than once in the roster", did)));
+            }
+        }
+        if record.attributes.keys().any(|key| key.trim().is_empty()) {
+            return Err(IcnError::Identity("Attribute names cannot be empty".into()));
+        }
+        if record.roles.iter().any(|role| role.trim().is_empty()) {
+            return Err(IcnError::Identity("Role names cannot be empty".into()));
+        }
+        if record.reputation.is_some_and(|reputation| !reputation.is_finite() || reputation < 0.0) {
+            return Err(IcnError::Identity("Reputation must be a non-negative number".into()));
+        }
+        Ok(())
+    }
+
+    fn import_record(&mut self, record: RosterRecord) -> IcnResult<String> {
+        let reputation = record.reputation.unwrap_or(DEFAULT_IMPORT_REPUTATION);
+        let id = match &record.did {
+            Some(did) => self.register_identity(did, record.attributes, reputation)?.id.clone(),
+            None => {
+                let id = self.create_identity(record.attributes)?.id;
+                if let Some(identity) = self.identities.get_mut(&id) {
+                    identity.reputation = reputation;
+                }
+                id
+            }
+        };
+        for role in &record.roles {
+            self.grant_role(&id, role)?;
+        }
+        Ok(id)
+    }
+
+    /// Every identity, without attributes whose names start with
+    /// `PRIVATE_ATTRIBUTE_PREFIX`, ordered by DID.
+    pub fn export_roster(&self) -> Vec<ExportedIdentity> {
+        let mut exported: Vec<ExportedIdentity> = self.identities.values()
+            .map(|identity| ExportedIdentity {
+                did: identity.id.clone(),
+                reputation: identity.reputation,
+                roles: identity.roles.iter().cloned().collect(),
+                revoked: identity.revoked,
+                attributes: identity.attributes.iter()
+                    .filter(|(key, _)| !key.starts_with(PRIVATE_ATTRIBUTE_PREFIX))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            })
+            .collect();
+        exported.sort_by(|a, b| a.did.cmp(&b.did));
+        exported
+    }
+
+    /// Checks that a credential was signed by a registered, unrevoked
+    /// issuer for a subject that is still a member.
+    pub fn verify_membership_credential(&self, credential: &MembershipCredential) -> IcnResult<()> {
+        let issuer = self.get_identity(&credential.issuer)?;
+        if issuer.revoked {
+            return Err(IcnError::Identity("Credential issuer has been revoked".into()));
+        }
+        let signature = Signature::from_bytes(&credential.signature)
+            .map_err(|e| IcnError::Identity(format!("Invalid credential signature: {}", e)))?;
+        let message = MembershipCredential::signing_bytes(&credential.subject, &credential.issuer, credential.issued_at);
+        if !issuer.verify_signature(&message, &signature) {
+            return Err(IcnError::Identity("Credential signature verification failed".into()));
+        }
+        if self.get_identity(&credential.subject)?.revoked {
+            return Err(IcnError::Identity("Credential subject has been revoked".into()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn did(keypair: &Keypair) -> String {
+        format!("did:icn:{}", hex::encode(keypair.public.to_bytes()))
+    }
+
+    #[test]
+    fn test_import_and_export_roster() {
+        let mut service = IdentityService::new();
+        let issuer = Keypair::generate(&mut OsRng {});
+        service.register_identity(&did(&issuer), HashMap::new(), 1.0).unwrap();
+        let member = did(&Keypair::generate(&mut OsRng {}));
+
+        let roster = format!(
+            "did,name,private.phone,reputation,roles\n\
+             {member},Alice,555-0100,5,treasurer;baker\n\
+             ,Bob,,,\n\
+             {member},Alice again,,,\n\
+             did:icn:nothex,Carol,,,\n\
+             ,Dave,,lots,\n"
+        );
+
+        let report = service.import_roster(&roster, RosterFormat::Csv, true, Some(&issuer), 0).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.imported.len(), 2);
+        assert_eq!(report.errors.iter().map(|error| error.record).collect::<Vec<_>>(), vec![3, 4, 5]);
+        assert_eq!(service.list_identities().len(), 1);
+
+        let report = service.import_roster(&roster, RosterFormat::Csv, false, Some(&issuer), 0).unwrap();
+        assert_eq!(report.imported.len(), 2);
+        assert_eq!(service.get_reputation(&member).unwrap(), 5.0);
+        assert!(service.get_roles(&member).unwrap().contains("treasurer"));
+        let credential = report.imported[0].credential.clone().unwrap();
+        service.verify_membership_credential(&credential).unwrap();
+        let mut forged = credential.clone();
+        forged.subject = report.imported[1].id.clone().unwrap();
+        assert!(service.verify_membership_credential(&forged).is_err());
+
+        // Private attributes stay out of exports, which import again as JSON
+        let exported = service.export_roster();
+        assert_eq!(exported.len(), 3);
+        assert!(exported.iter().all(|identity| !identity.attributes.contains_key("private.phone")));
+        assert!(roster_to_csv(&exported).unwrap().starts_with("did,reputation,roles,revoked,name\n"));
+
+        let json = serde_json::to_string(&vec![RosterRecord {
+            did: Some(member),
+            ..Default::default()
+        }]).unwrap();
+        let report = service.import_roster(&json, RosterFormat::Json, false, None, 0).unwrap();
+        assert!(report.imported.is_empty());
+        assert_eq!(report.errors.len(), 1);
+    }
+}
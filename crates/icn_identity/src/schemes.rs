@@ -0,0 +1,260 @@
+// File: crates/icn_identity/src/schemes.rs
+
+use icn_common::{IcnError, IcnResult, SignatureScheme};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+
+/// Domain separation tags from the BLS signature draft's proof of
+/// possession ciphersuite. Possession proofs use their own tag so they can
+/// never double as ordinary signatures.
+const BLS_SIGNATURE_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+const BLS_POSSESSION_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// A public key tagged with the scheme it belongs to.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SchemePublicKey {
+    pub scheme: SignatureScheme,
+    /// The key in the scheme's standard encoding: 32 bytes for ed25519, a
+    /// compressed SEC1 point for secp256k1, a compressed G1 point for BLS.
+    #[serde(with = "hex_bytes")]
+    pub bytes: Vec<u8>,
+}
+
+impl SchemePublicKey {
+    /// Checks that `bytes` is a valid key for `scheme`.
+    pub fn new(scheme: SignatureScheme, bytes: Vec<u8>) -> IcnResult<Self> {
+        let key = SchemePublicKey { scheme, bytes };
+        match scheme {
+            SignatureScheme::Ed25519 => key.ed25519().map(|_| ())?,
+            SignatureScheme::Secp256k1 => key.secp256k1().map(|_| ())?,
+            SignatureScheme::Bls12381 => key.bls().map(|_| ())?,
+        }
+        Ok(key)
+    }
+
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> IcnResult<()> {
+        let valid = match self.scheme {
+            SignatureScheme::Ed25519 => {
+                use ed25519_dalek::Verifier;
+                let signature = ed25519_dalek::Signature::from_bytes(signature).map_err(|e| invalid_signature(self.scheme, e))?;
+                self.ed25519()?.verify(message, &signature).is_ok()
+            }
+            SignatureScheme::Secp256k1 => {
+                use k256::ecdsa::signature::Verifier;
+                let signature = k256::ecdsa::Signature::from_slice(signature).map_err(|e| invalid_signature(self.scheme, e))?;
+                self.secp256k1()?.verify(message, &signature).is_ok()
+            }
+            SignatureScheme::Bls12381 => {
+                let signature = blst::min_pk::Signature::sig_validate(signature, true).map_err(|e| invalid_signature(self.scheme, e))?;
+                signature.verify(false, message, BLS_SIGNATURE_DST, &[], &self.bls()?, false) == blst::BLST_ERROR::BLST_SUCCESS
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(IcnError::Identity(format!("{} signature does not verify", self.scheme.name())))
+        }
+    }
+
+    /// Checks a proof made with `SchemeKeypair::prove_possession`.
+    pub fn verify_possession(&self, message: &[u8], proof: &[u8]) -> IcnResult<()> {
+        if self.scheme != SignatureScheme::Bls12381 {
+            return self.verify(message, proof);
+        }
+        let proof = blst::min_pk::Signature::sig_validate(proof, true).map_err(|e| invalid_signature(self.scheme, e))?;
+        if proof.verify(false, message, BLS_POSSESSION_DST, &[], &self.bls()?, false) != blst::BLST_ERROR::BLST_SUCCESS {
+            return Err(IcnError::Identity("Proof of possession does not verify".into()));
+        }
+        Ok(())
+    }
+
+    fn ed25519(&self) -> IcnResult<ed25519_dalek::PublicKey> {
+        ed25519_dalek::PublicKey::from_bytes(&self.bytes).map_err(|e| invalid_key(self.scheme, e))
+    }
+
+    fn secp256k1(&self) -> IcnResult<k256::ecdsa::VerifyingKey> {
+        k256::ecdsa::VerifyingKey::from_sec1_bytes(&self.bytes).map_err(|e| invalid_key(self.scheme, e))
+    }
+
+    fn bls(&self) -> IcnResult<blst::min_pk::PublicKey> {
+        blst::min_pk::PublicKey::key_validate(&self.bytes).map_err(|e| invalid_key(self.scheme, e))
+    }
+}
+
+/// A private key of any supported scheme, for wallets and tests.
+pub enum SchemeKeypair {
+    Ed25519(ed25519_dalek::Keypair),
+    Secp256k1(k256::ecdsa::SigningKey),
+    Bls12381(blst::min_pk::SecretKey),
+}
+
+impl SchemeKeypair {
+    pub fn generate(scheme: SignatureScheme) -> Self {
+        match scheme {
+            SignatureScheme::Ed25519 => SchemeKeypair::Ed25519(ed25519_dalek::Keypair::generate(&mut OsRng {})),
+            SignatureScheme::Secp256k1 => loop {
+                // Fewer than one in 2^127 draws is out of range
+                if let Ok(key) = k256::ecdsa::SigningKey::from_slice(&random_bytes()) {
+                    break SchemeKeypair::Secp256k1(key);
+                }
+            },
+            SignatureScheme::Bls12381 => {
+                let key = blst::min_pk::SecretKey::key_gen(&random_bytes(), &[])
+                    .expect("32 bytes of key material is enough for BLS key generation");
+                SchemeKeypair::Bls12381(key)
+            }
+        }
+    }
+
+    pub fn scheme(&self) -> SignatureScheme {
+        match self {
+            SchemeKeypair::Ed25519(_) => SignatureScheme::Ed25519,
+            SchemeKeypair::Secp256k1(_) => SignatureScheme::Secp256k1,
+            SchemeKeypair::Bls12381(_) => SignatureScheme::Bls12381,
+        }
+    }
+
+    pub fn public_key(&self) -> SchemePublicKey {
+        let bytes = match self {
+            SchemeKeypair::Ed25519(keypair) => keypair.public.to_bytes().to_vec(),
+            SchemeKeypair::Secp256k1(key) => key.verifying_key().to_sec1_bytes().to_vec(),
+            SchemeKeypair::Bls12381(key) => key.sk_to_pk().compress().to_vec(),
+        };
+        SchemePublicKey { scheme: self.scheme(), bytes }
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            SchemeKeypair::Ed25519(keypair) => {
+                use ed25519_dalek::Signer;
+                keypair.sign(message).to_bytes().to_vec()
+            }
+            SchemeKeypair::Secp256k1(key) => {
+                use k256::ecdsa::signature::Signer;
+                let signature: k256::ecdsa::Signature = key.sign(message);
+                signature.to_bytes().to_vec()
+            }
+            SchemeKeypair::Bls12381(key) => key.sign(message, BLS_SIGNATURE_DST, &[]).compress().to_vec(),
+        }
+    }
+
+    /// Signs `message` to prove the key is held. For BLS this is what stops
+    /// a rogue key from cancelling out honest keys in an aggregate.
+    pub fn prove_possession(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            SchemeKeypair::Bls12381(key) => key.sign(message, BLS_POSSESSION_DST, &[]).compress().to_vec(),
+            _ => self.sign(message),
+        }
+    }
+}
+
+/// The message a new key signs, and the identity's ed25519 key
+/// authorizes, when the key is registered.
+pub fn key_registration_message(id: &str, key: &SchemePublicKey) -> Vec<u8> {
+    format!("icn-key-registration:{}:{}:{}", id, key.scheme.name(), hex::encode(&key.bytes)).into_bytes()
+}
+
+/// Combines BLS signatures on one message into a single signature.
+pub fn aggregate_bls_signatures(signatures: &[Vec<u8>]) -> IcnResult<Vec<u8>> {
+    let signatures = signatures.iter()
+        .map(|signature| blst::min_pk::Signature::sig_validate(signature, true).map_err(|e| invalid_signature(SignatureScheme::Bls12381, e)))
+        .collect::<IcnResult<Vec<_>>>()?;
+    let signatures: Vec<&blst::min_pk::Signature> = signatures.iter().collect();
+    let aggregate = blst::min_pk::AggregateSignature::aggregate(&signatures, false)
+        .map_err(|e| invalid_signature(SignatureScheme::Bls12381, e))?;
+    Ok(aggregate.to_signature().compress().to_vec())
+}
+
+/// Checks that every key in `keys` signed `message`, given their aggregate
+/// signature. The keys must have proven possession when registered.
+pub fn verify_bls_aggregate(keys: &[SchemePublicKey], message: &[u8], signature: &[u8]) -> IcnResult<()> {
+    if keys.is_empty() {
+        return Err(IcnError::Identity("An aggregate signature needs at least one key".into()));
+    }
+    let keys = keys.iter()
+        .map(|key| match key.scheme {
+            SignatureScheme::Bls12381 => key.bls(),
+            other => Err(IcnError::Identity(format!("Cannot aggregate {} keys", other.name()))),
+        })
+        .collect::<IcnResult<Vec<_>>>()?;
+    let keys: Vec<&blst::min_pk::PublicKey> = keys.iter().collect();
+    let signature = blst::min_pk::Signature::sig_validate(signature, true).map_err(|e| invalid_signature(SignatureScheme::Bls12381, e))?;
+    if signature.fast_aggregate_verify(false, message, BLS_SIGNATURE_DST, &keys) != blst::BLST_ERROR::BLST_SUCCESS {
+        return Err(IcnError::Identity("Aggregate signature does not verify".into()));
+    }
+    Ok(())
+}
+
+fn random_bytes() -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn invalid_key(scheme: SignatureScheme, e: impl std::fmt::Debug) -> IcnError {
+    IcnError::Identity(format!("Invalid {} public key: {:?}", scheme.name(), e))
+}
+
+fn invalid_signature(scheme: SignatureScheme, e: impl std::fmt::Debug) -> IcnError {
+    IcnError::Identity(format!("Invalid {} signature: {:?}", scheme.name(), e))
+}
+
+pub(crate) mod hex_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::decode(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_scheme_signs_and_verifies() {
+        for scheme in [SignatureScheme::Ed25519, SignatureScheme::Secp256k1, SignatureScheme::Bls12381] {
+            let keypair = SchemeKeypair::generate(scheme);
+            let key = keypair.public_key();
+            assert_eq!(SchemePublicKey::new(scheme, key.bytes.clone()).unwrap(), key);
+
+            let signature = keypair.sign(b"pay bob 10");
+            key.verify(b"pay bob 10", &signature).unwrap();
+            assert!(key.verify(b"pay bob 11", &signature).is_err());
+            assert!(SchemeKeypair::generate(scheme).public_key().verify(b"pay bob 10", &signature).is_err());
+
+            let message = key_registration_message("did:icn:alice", &key);
+            key.verify_possession(&message, &keypair.prove_possession(&message)).unwrap();
+        }
+        assert!(SchemePublicKey::new(SignatureScheme::Bls12381, vec![0; 48]).is_err());
+
+        // A BLS possession proof is not a signature, and vice versa
+        let bls = SchemeKeypair::generate(SignatureScheme::Bls12381);
+        assert!(bls.public_key().verify(b"m", &bls.prove_possession(b"m")).is_err());
+        assert!(bls.public_key().verify_possession(b"m", &bls.sign(b"m")).is_err());
+    }
+
+    #[test]
+    fn test_bls_aggregation() {
+        let keypairs: Vec<SchemeKeypair> = (0..4).map(|_| SchemeKeypair::generate(SignatureScheme::Bls12381)).collect();
+        let keys: Vec<SchemePublicKey> = keypairs.iter().map(SchemeKeypair::public_key).collect();
+        let signatures: Vec<Vec<u8>> = keypairs.iter().map(|keypair| keypair.sign(b"block 7")).collect();
+
+        let aggregate = aggregate_bls_signatures(&signatures).unwrap();
+        assert_eq!(aggregate.len(), 96);
+        verify_bls_aggregate(&keys, b"block 7", &aggregate).unwrap();
+        assert!(verify_bls_aggregate(&keys, b"block 8", &aggregate).is_err());
+        assert!(verify_bls_aggregate(&keys[..3], b"block 7", &aggregate).is_err());
+
+        let partial = aggregate_bls_signatures(&signatures[..3]).unwrap();
+        assert!(verify_bls_aggregate(&keys, b"block 7", &partial).is_err());
+
+        let ed25519 = SchemeKeypair::generate(SignatureScheme::Ed25519).public_key();
+        assert!(verify_bls_aggregate(&[ed25519], b"block 7", &aggregate).is_err());
+    }
+}
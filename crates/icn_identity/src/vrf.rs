@@ -0,0 +1,206 @@
+// File: crates/icn_identity/src/vrf.rs
+
+use crate::schemes::SchemePublicKey;
+use icn_common::{weighted_sample, IcnError, IcnResult, SignatureScheme};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+/// Domain separation tag for VRF proofs, so a proof can never be passed
+/// off as a signature or the other way round.
+const VRF_DST: &[u8] = b"ICN_VRF_BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+/// Draws of dispute juries.
+pub const JURY_SELECTION: &str = "jury";
+/// Draws of block committees.
+pub const COMMITTEE_SELECTION: &str = "committee";
+
+/// A verifiable random function key. The proof for an input is a BLS
+/// signature over it, which is unique for the key, and the output is its
+/// hash: nobody without the key can predict the output, and the key holder
+/// cannot pick among outputs.
+#[derive(Clone)]
+pub struct VrfKey(blst::min_pk::SecretKey);
+
+impl VrfKey {
+    pub fn generate() -> Self {
+        let mut ikm = [0u8; 32];
+        OsRng.fill_bytes(&mut ikm);
+        let key = blst::min_pk::SecretKey::key_gen(&ikm, &[])
+            .expect("32 bytes of key material is enough for BLS key generation");
+        VrfKey(key)
+    }
+
+    pub fn public_key(&self) -> SchemePublicKey {
+        SchemePublicKey {
+            scheme: SignatureScheme::Bls12381,
+            bytes: self.0.sk_to_pk().compress().to_vec(),
+        }
+    }
+
+    pub fn evaluate(&self, input: &[u8]) -> VrfOutput {
+        let proof = self.0.sign(input, VRF_DST, &[]).compress().to_vec();
+        VrfOutput {
+            public_key: self.public_key(),
+            input: input.to_vec(),
+            output: hex::encode(Sha256::digest(&proof)),
+            proof,
+        }
+    }
+}
+
+/// A VRF output with the proof that it belongs to `input` and the key.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VrfOutput {
+    pub public_key: SchemePublicKey,
+    #[serde(with = "crate::schemes::hex_bytes")]
+    pub input: Vec<u8>,
+    #[serde(with = "crate::schemes::hex_bytes")]
+    pub proof: Vec<u8>,
+    /// Hex SHA-256 of the proof.
+    pub output: String,
+}
+
+impl VrfOutput {
+    pub fn verify(&self) -> IcnResult<()> {
+        if self.public_key.scheme != SignatureScheme::Bls12381 {
+            return Err(IcnError::Identity("VRF keys must be BLS12-381 keys".into()));
+        }
+        let key = blst::min_pk::PublicKey::key_validate(&self.public_key.bytes)
+            .map_err(|e| IcnError::Identity(format!("Invalid VRF key: {:?}", e)))?;
+        let proof = blst::min_pk::Signature::sig_validate(&self.proof, true)
+            .map_err(|e| IcnError::Identity(format!("Invalid VRF proof: {:?}", e)))?;
+        if proof.verify(false, &self.input, VRF_DST, &[], &key, false) != blst::BLST_ERROR::BLST_SUCCESS {
+            return Err(IcnError::Identity("VRF proof does not verify".into()));
+        }
+        if self.output != hex::encode(Sha256::digest(&self.proof)) {
+            return Err(IcnError::Identity("VRF output does not match its proof".into()));
+        }
+        Ok(())
+    }
+}
+
+/// The VRF input for a draw of `purpose` about `subject`, seeded by a
+/// recent block hash.
+pub fn selection_input(purpose: &str, block_hash: &str, subject: &str) -> Vec<u8> {
+    format!("icn-vrf:{}:{}:{}", purpose, block_hash, subject).into_bytes()
+}
+
+/// A reputation-weighted draw made with a VRF output, with everything
+/// needed to check it afterwards.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct VrfSelection {
+    pub purpose: String,
+    /// What was drawn for, such as a dispute id or block height.
+    pub subject: String,
+    /// Hash of the recent block that seeded the draw.
+    pub block_hash: String,
+    pub vrf: VrfOutput,
+    /// Eligible candidates and the weights they were drawn with.
+    pub candidates: Vec<(String, f64)>,
+    /// Number of candidates asked for, 0 for all of them.
+    pub size: usize,
+    /// The candidates picked, sorted.
+    pub selected: Vec<String>,
+}
+
+impl VrfSelection {
+    pub fn draw(key: &VrfKey, purpose: &str, subject: &str, block_hash: &str, candidates: Vec<(String, f64)>, size: usize) -> Self {
+        let vrf = key.evaluate(&selection_input(purpose, block_hash, subject));
+        let selected = weighted_sample(vrf.output.as_bytes(), &candidates, size);
+        VrfSelection {
+            purpose: purpose.to_string(),
+            subject: subject.to_string(),
+            block_hash: block_hash.to_string(),
+            vrf,
+            candidates,
+            size,
+            selected,
+        }
+    }
+
+    /// Checks the proof and redoes the draw. It is up to the caller to
+    /// check that the block hash and VRF key are the ones it expects.
+    pub fn audit(&self) -> SelectionAudit {
+        let mut problems = Vec::new();
+        if let Err(e) = self.vrf.verify() {
+            problems.push(e.to_string());
+        }
+        if self.vrf.input != selection_input(&self.purpose, &self.block_hash, &self.subject) {
+            problems.push("VRF input is not derived from the block hash and subject".to_string());
+        }
+        let recomputed = weighted_sample(self.vrf.output.as_bytes(), &self.candidates, self.size);
+        if recomputed != self.selected {
+            problems.push("Selection does not match the draw".to_string());
+        }
+        SelectionAudit {
+            selection: self.clone(),
+            recomputed,
+            problems,
+        }
+    }
+}
+
+/// The result of checking a past draw.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SelectionAudit {
+    pub selection: VrfSelection,
+    /// Who the draw picks when redone from the VRF output.
+    pub recomputed: Vec<String>,
+    /// Why the selection does not check out, empty if it does.
+    pub problems: Vec<String>,
+}
+
+impl SelectionAudit {
+    pub fn is_valid(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<(String, f64)> {
+        (0..10).map(|i| (format!("member{}", i), 10.0 + i as f64)).collect()
+    }
+
+    #[test]
+    fn test_vrf_output_is_unique_and_verifiable() {
+        let key = VrfKey::generate();
+        let output = key.evaluate(b"input");
+        output.verify().unwrap();
+        assert_eq!(output, key.evaluate(b"input"));
+        assert_ne!(output.output, key.evaluate(b"other input").output);
+        assert_ne!(output.output, VrfKey::generate().evaluate(b"input").output);
+
+        let mut forged = output.clone();
+        forged.output = hex::encode(Sha256::digest(b"chosen"));
+        assert!(forged.verify().is_err());
+        let mut moved = output;
+        moved.input = b"other input".to_vec();
+        assert!(moved.verify().is_err());
+    }
+
+    #[test]
+    fn test_selection_audit() {
+        let key = VrfKey::generate();
+        let selection = VrfSelection::draw(&key, JURY_SELECTION, "dispute1", "blockhash", candidates(), 3);
+        assert_eq!(selection.selected.len(), 3);
+        assert!(selection.audit().is_valid());
+
+        let mut swapped = selection.clone();
+        let outsider = swapped.candidates.iter().map(|(id, _)| id.clone()).find(|id| !swapped.selected.contains(id)).unwrap();
+        swapped.selected[0] = outsider;
+        swapped.selected.sort();
+        let audit = swapped.audit();
+        assert!(!audit.is_valid());
+        assert_eq!(audit.recomputed, selection.selected);
+
+        // A draw can't be moved to another block hash
+        let mut reseeded = selection;
+        reseeded.block_hash = "otherhash".to_string();
+        assert!(!reseeded.audit().is_valid());
+    }
+}
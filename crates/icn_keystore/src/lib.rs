@@ -0,0 +1,392 @@
+// File: crates/icn_keystore/src/lib.rs
+
+//! Validator and member keys kept encrypted at rest. Each key lives in its
+//! own file, sealed with AES-256-GCM under a key derived from a passphrase
+//! with Argon2id, so a copied keystore directory is useless without the
+//! passphrase.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+use icn_common::{IcnError, IcnResult};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use zeroize::Zeroizing;
+
+/// Environment variable a node reads its keystore passphrase from when
+/// it runs unattended.
+pub const PASSPHRASE_ENV: &str = "ICN_KEYSTORE_PASSPHRASE";
+
+const KEY_FILE_VERSION: u32 = 1;
+const KEY_FILE_SUFFIX: &str = ".key.json";
+/// Keys replaced by a rotation are kept here so old signatures can still
+/// be traced to them.
+const RETIRED_DIR: &str = "retired";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// A passphrase that is wiped from memory when dropped.
+pub struct Passphrase(Zeroizing<String>);
+
+impl Passphrase {
+    pub fn new(passphrase: impl Into<String>) -> IcnResult<Self> {
+        let passphrase = Zeroizing::new(passphrase.into());
+        if passphrase.is_empty() {
+            return Err(IcnError::Keystore("Passphrase cannot be empty".into()));
+        }
+        Ok(Passphrase(passphrase))
+    }
+
+    /// Reads the passphrase from the environment variable `var`.
+    pub fn from_env(var: &str) -> IcnResult<Self> {
+        let passphrase = std::env::var(var)
+            .map_err(|_| IcnError::Keystore(format!("{} is not set", var)))?;
+        Self::new(passphrase)
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl fmt::Debug for Passphrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Passphrase(..)")
+    }
+}
+
+/// Argon2id cost settings. They are stored with each key so a key keeps
+/// opening after the defaults change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams { memory_kib: 64 * 1024, iterations: 3, parallelism: 1 }
+    }
+}
+
+/// A key as written to disk. Only the secret key is encrypted; the name
+/// and public key are bound to it as associated data.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeyFile {
+    version: u32,
+    name: String,
+    public_key: String,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    retired_at: Option<DateTime<Utc>>,
+    kdf: KdfParams,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+impl KeyFile {
+    fn info(&self) -> KeyInfo {
+        KeyInfo {
+            name: self.name.clone(),
+            did: format!("did:icn:{}", self.public_key),
+            public_key: self.public_key.clone(),
+            created_at: self.created_at,
+            retired_at: self.retired_at,
+        }
+    }
+}
+
+/// What can be learned about a key without its passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct KeyInfo {
+    pub name: String,
+    /// The `did:icn:` identity the key signs as.
+    pub did: String,
+    /// Hex encoding of the ed25519 public key.
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+    /// When a rotation replaced the key; `None` while it is in use.
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+/// A directory of encrypted ed25519 keys, one file per key name.
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    dir: PathBuf,
+    kdf: KdfParams,
+}
+
+impl Keystore {
+    /// Opens the keystore in `dir`, creating the directory if needed.
+    pub fn open(dir: impl Into<PathBuf>) -> IcnResult<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(dir.join(RETIRED_DIR))?;
+        Ok(Keystore { dir, kdf: KdfParams::default() })
+    }
+
+    /// Seals keys written from now on with `kdf` instead of the defaults.
+    pub fn with_kdf_params(mut self, kdf: KdfParams) -> Self {
+        self.kdf = kdf;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Creates a new key called `name`.
+    pub fn generate(&self, name: &str, passphrase: &Passphrase) -> IcnResult<KeyInfo> {
+        self.import(name, &Keypair::generate(&mut OsRng {}), passphrase)
+    }
+
+    /// Stores an existing key under `name`, which must not be taken.
+    pub fn import(&self, name: &str, keypair: &Keypair, passphrase: &Passphrase) -> IcnResult<KeyInfo> {
+        let path = self.key_path(name)?;
+        if path.exists() {
+            return Err(IcnError::Keystore(format!("A key named {} already exists", name)));
+        }
+        let file = seal(name, keypair, passphrase, self.kdf, Utc::now())?;
+        write_key_file(&path, &file)?;
+        Ok(file.info())
+    }
+
+    /// Decrypts the key called `name`.
+    pub fn unlock(&self, name: &str, passphrase: &Passphrase) -> IcnResult<Keypair> {
+        open(&self.read_key_file(name)?, passphrase)
+    }
+
+    pub fn key_info(&self, name: &str) -> IcnResult<KeyInfo> {
+        Ok(self.read_key_file(name)?.info())
+    }
+
+    /// Keys in use, by name.
+    pub fn list(&self) -> IcnResult<Vec<KeyInfo>> {
+        let mut keys = read_key_files(&self.dir)?;
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(keys)
+    }
+
+    /// Keys that rotations have replaced under `name`, oldest first.
+    pub fn retired(&self, name: &str) -> IcnResult<Vec<KeyInfo>> {
+        validate_key_name(name)?;
+        let mut keys: Vec<KeyInfo> = read_key_files(&self.dir.join(RETIRED_DIR))?
+            .into_iter()
+            .filter(|key| key.name == name)
+            .collect();
+        keys.sort_by_key(|key| key.retired_at);
+        Ok(keys)
+    }
+
+    /// Replaces the key called `name` with a fresh one sealed under the
+    /// same passphrase, which must open the current key. The old key is
+    /// moved to the retired keys rather than deleted.
+    pub fn rotate(&self, name: &str, passphrase: &Passphrase) -> IcnResult<KeyInfo> {
+        let mut current = self.read_key_file(name)?;
+        open(&current, passphrase)?;
+
+        let now = Utc::now();
+        let replacement = seal(name, &Keypair::generate(&mut OsRng {}), passphrase, self.kdf, now)?;
+        current.retired_at = Some(now);
+        let retired_path = self.dir.join(RETIRED_DIR)
+            .join(format!("{}.{}{}", name, now.timestamp_millis(), KEY_FILE_SUFFIX));
+        write_key_file(&retired_path, &current)?;
+        write_key_file(&self.key_path(name)?, &replacement)?;
+        Ok(replacement.info())
+    }
+
+    /// Re-seals the key called `name` under a new passphrase.
+    pub fn change_passphrase(&self, name: &str, current: &Passphrase, new: &Passphrase) -> IcnResult<()> {
+        let file = self.read_key_file(name)?;
+        let keypair = open(&file, current)?;
+        let mut resealed = seal(name, &keypair, new, self.kdf, file.created_at)?;
+        resealed.retired_at = file.retired_at;
+        write_key_file(&self.key_path(name)?, &resealed)
+    }
+
+    fn key_path(&self, name: &str) -> IcnResult<PathBuf> {
+        validate_key_name(name)?;
+        Ok(self.dir.join(format!("{}{}", name, KEY_FILE_SUFFIX)))
+    }
+
+    fn read_key_file(&self, name: &str) -> IcnResult<KeyFile> {
+        let path = self.key_path(name)?;
+        if !path.exists() {
+            return Err(IcnError::Keystore(format!("No key named {}", name)));
+        }
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+}
+
+fn validate_key_name(name: &str) -> IcnResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        return Err(IcnError::Keystore(format!("Invalid key name: {}", name)));
+    }
+    Ok(())
+}
+
+fn associated_data(name: &str, public_key: &str) -> Vec<u8> {
+    format!("icn-keystore:{}:{}:{}", KEY_FILE_VERSION, name, public_key).into_bytes()
+}
+
+fn cipher(passphrase: &Passphrase, salt: &[u8], kdf: KdfParams) -> IcnResult<Aes256Gcm> {
+    let params = Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| IcnError::Keystore(format!("Invalid key derivation parameters: {}", e)))?;
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, key.as_mut())
+        .map_err(|e| IcnError::Keystore(format!("Key derivation failed: {}", e)))?;
+    Aes256Gcm::new_from_slice(key.as_ref())
+        .map_err(|e| IcnError::Keystore(format!("Invalid encryption key: {}", e)))
+}
+
+fn seal(name: &str, keypair: &Keypair, passphrase: &Passphrase, kdf: KdfParams, created_at: DateTime<Utc>) -> IcnResult<KeyFile> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let public_key = hex::encode(keypair.public.to_bytes());
+    let ciphertext = cipher(passphrase, &salt, kdf)?
+        .encrypt(Nonce::from_slice(&nonce), Payload {
+            msg: keypair.secret.as_bytes(),
+            aad: &associated_data(name, &public_key),
+        })
+        .map_err(|_| IcnError::Keystore("Failed to encrypt key".into()))?;
+    Ok(KeyFile {
+        version: KEY_FILE_VERSION,
+        name: name.to_string(),
+        public_key,
+        created_at,
+        retired_at: None,
+        kdf,
+        salt: hex::encode(salt),
+        nonce: hex::encode(nonce),
+        ciphertext: hex::encode(ciphertext),
+    })
+}
+
+fn open(file: &KeyFile, passphrase: &Passphrase) -> IcnResult<Keypair> {
+    if file.version != KEY_FILE_VERSION {
+        return Err(IcnError::Keystore(format!("Unsupported key file version {}", file.version)));
+    }
+    let corrupt = |_| IcnError::Keystore(format!("Key file for {} is corrupt", file.name));
+    let salt = hex::decode(&file.salt).map_err(corrupt)?;
+    let nonce = hex::decode(&file.nonce).map_err(corrupt)?;
+    let ciphertext = hex::decode(&file.ciphertext).map_err(corrupt)?;
+    if nonce.len() != NONCE_LEN {
+        return Err(IcnError::Keystore(format!("Key file for {} is corrupt", file.name)));
+    }
+
+    let secret = Zeroizing::new(cipher(passphrase, &salt, file.kdf)?
+        .decrypt(Nonce::from_slice(&nonce), Payload {
+            msg: &ciphertext,
+            aad: &associated_data(&file.name, &file.public_key),
+        })
+        .map_err(|_| IcnError::Keystore(format!("Wrong passphrase for key {}", file.name)))?);
+    let secret = SecretKey::from_bytes(&secret)
+        .map_err(|e| IcnError::Keystore(format!("Key file for {} holds an invalid key: {}", file.name, e)))?;
+    let public = PublicKey::from(&secret);
+    if hex::encode(public.to_bytes()) != file.public_key {
+        return Err(IcnError::Keystore(format!("Key file for {} does not match its public key", file.name)));
+    }
+    Ok(Keypair { secret, public })
+}
+
+fn read_key_files(dir: &Path) -> IcnResult<Vec<KeyInfo>> {
+    let mut keys = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_key_file = path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(KEY_FILE_SUFFIX));
+        if is_key_file {
+            let file: KeyFile = serde_json::from_slice(&fs::read(&path)?)?;
+            keys.push(file.info());
+        }
+    }
+    Ok(keys)
+}
+
+fn write_key_file(path: &Path, file: &KeyFile) -> IcnResult<()> {
+    // Write under a temporary name first so a crash never leaves a key half written
+    let partial = path.with_extension("partial");
+    fs::write(&partial, serde_json::to_vec_pretty(file)?)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&partial, fs::Permissions::from_mode(0o600))?;
+    }
+    fs::rename(&partial, path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, Verifier};
+
+    fn test_keystore(label: &str) -> Keystore {
+        let dir = std::env::temp_dir().join(format!("icn-keystore-{}-{}", label, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        // Cheap settings so the tests don't spend seconds deriving keys
+        Keystore::open(dir).unwrap().with_kdf_params(KdfParams { memory_kib: 64, iterations: 1, parallelism: 1 })
+    }
+
+    #[test]
+    fn test_keys_unlock_only_with_their_passphrase() {
+        let keystore = test_keystore("unlock");
+        let passphrase = Passphrase::new("correct horse").unwrap();
+        let info = keystore.generate("validator", &passphrase).unwrap();
+        assert!(keystore.generate("validator", &passphrase).is_err());
+        assert!(keystore.generate("../validator", &passphrase).is_err());
+
+        let keypair = keystore.unlock("validator", &passphrase).unwrap();
+        assert_eq!(info.did, format!("did:icn:{}", hex::encode(keypair.public.to_bytes())));
+        let signature = keypair.sign(b"block");
+        assert!(keypair.public.verify(b"block", &signature).is_ok());
+        assert!(keystore.unlock("validator", &Passphrase::new("wrong").unwrap()).is_err());
+
+        // Nothing on disk gives the secret away
+        let on_disk = fs::read_to_string(keystore.dir().join("validator.key.json")).unwrap();
+        assert!(!on_disk.contains(&hex::encode(keypair.secret.as_bytes())));
+
+        let renewed = Passphrase::new("battery staple").unwrap();
+        keystore.change_passphrase("validator", &passphrase, &renewed).unwrap();
+        assert!(keystore.unlock("validator", &passphrase).is_err());
+        assert_eq!(keystore.unlock("validator", &renewed).unwrap().public, keypair.public);
+        fs::remove_dir_all(keystore.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_retires_the_old_key() {
+        let keystore = test_keystore("rotate");
+        let passphrase = Passphrase::new("correct horse").unwrap();
+        let original = keystore.generate("validator", &passphrase).unwrap();
+        keystore.generate("treasurer", &passphrase).unwrap();
+        assert!(keystore.rotate("validator", &Passphrase::new("wrong").unwrap()).is_err());
+
+        let rotated = keystore.rotate("validator", &passphrase).unwrap();
+        assert_ne!(rotated.public_key, original.public_key);
+        let names: Vec<String> = keystore.list().unwrap().into_iter().map(|key| key.name).collect();
+        assert_eq!(names, vec!["treasurer".to_string(), "validator".to_string()]);
+        assert_eq!(keystore.key_info("validator").unwrap(), rotated);
+
+        let retired = keystore.retired("validator").unwrap();
+        assert_eq!(retired.len(), 1);
+        assert_eq!(retired[0].public_key, original.public_key);
+        assert!(retired[0].retired_at.is_some());
+        assert!(keystore.retired("treasurer").unwrap().is_empty());
+        fs::remove_dir_all(keystore.dir()).unwrap();
+    }
+}
@@ -4,8 +4,8 @@ use nom::{
     bytes::complete::{tag, take_while1},
     character::complete::{char, multispace0},
     combinator::{map, recognize, opt},
-    multi::separated_list0,
-    sequence::{delimited, pair, tuple},
+    multi::{many0, separated_list0},
+    sequence::{delimited, pair, preceded, tuple},
 };
 use icn_vm::{Opcode, Value};
 
@@ -23,6 +23,37 @@ pub enum Statement {
     CreateProposal { title: String, description: String },
     GetProposalStatus { proposal_id: String },
     EmitEvent { event_name: String, event_data: String },
+    If { condition: Expr, then_branch: Vec<Statement>, else_branch: Vec<Statement> },
+    Repeat { count: Expr, body: Vec<Statement> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Eq,
+    Neq,
+    Lt,
+    Gt,
+    Lte,
+    Gte,
+    And,
+    Or,
+}
+
+/// Expressions usable as `if` conditions and `repeat` counts.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+    /// `get-proposal-status("id")`, evaluating to the proposal's status.
+    ProposalStatus(String),
+    Not(Box<Expr>),
+    Binary { op: BinaryOp, lhs: Box<Expr>, rhs: Box<Expr> },
 }
 
 fn parse_string(input: &str) -> IResult<&str, String> {
@@ -279,8 +310,125 @@ fn parse_emit_event(input: &str) -> IResult<&str, Statement> {
     )(input)
 }
 
+fn parse_primary(input: &str) -> IResult<&str, Expr> {
+    alt((
+        map(
+            tuple((
+                tag("get-proposal-status"),
+                multispace0,
+                char('('),
+                multispace0,
+                parse_string,
+                multispace0,
+                char(')')
+            )),
+            |(_, _, _, _, proposal_id, _, _)| Expr::ProposalStatus(proposal_id)
+        ),
+        map(parse_boolean, Expr::Bool),
+        map(parse_string, Expr::Str),
+        map(parse_number, |n| if n.fract() == 0.0 && n.abs() < i64::MAX as f64 { Expr::Int(n as i64) } else { Expr::Float(n) }),
+        delimited(pair(char('('), multispace0), parse_expr, pair(multispace0, char(')'))),
+    ))(input)
+}
+
+fn parse_unary(input: &str) -> IResult<&str, Expr> {
+    alt((
+        map(preceded(pair(char('!'), multispace0), parse_unary), |expr| Expr::Not(Box::new(expr))),
+        parse_primary,
+    ))(input)
+}
+
+/// Parses `operand (op operand)*` and folds it into a left-associative tree.
+fn parse_binary<'a>(
+    input: &'a str,
+    operand: fn(&'a str) -> IResult<&'a str, Expr>,
+    operator: fn(&'a str) -> IResult<&'a str, BinaryOp>,
+) -> IResult<&'a str, Expr> {
+    let (input, first) = operand(input)?;
+    let (input, rest) = many0(pair(preceded(multispace0, operator), preceded(multispace0, operand)))(input)?;
+    let expr = rest.into_iter().fold(first, |lhs, (op, rhs)| Expr::Binary { op, lhs: Box::new(lhs), rhs: Box::new(rhs) });
+    Ok((input, expr))
+}
+
+fn parse_product(input: &str) -> IResult<&str, Expr> {
+    parse_binary(input, parse_unary, |i| alt((
+        map(char('*'), |_| BinaryOp::Mul),
+        map(char('/'), |_| BinaryOp::Div),
+    ))(i))
+}
+
+fn parse_sum(input: &str) -> IResult<&str, Expr> {
+    parse_binary(input, parse_product, |i| alt((
+        map(char('+'), |_| BinaryOp::Add),
+        map(char('-'), |_| BinaryOp::Sub),
+    ))(i))
+}
+
+fn parse_comparison(input: &str) -> IResult<&str, Expr> {
+    parse_binary(input, parse_sum, |i| alt((
+        map(tag("=="), |_| BinaryOp::Eq),
+        map(tag("!="), |_| BinaryOp::Neq),
+        map(tag("<="), |_| BinaryOp::Lte),
+        map(tag(">="), |_| BinaryOp::Gte),
+        map(char('<'), |_| BinaryOp::Lt),
+        map(char('>'), |_| BinaryOp::Gt),
+    ))(i))
+}
+
+fn parse_conjunction(input: &str) -> IResult<&str, Expr> {
+    parse_binary(input, parse_comparison, |i| map(tag("&&"), |_| BinaryOp::And)(i))
+}
+
+fn parse_expr(input: &str) -> IResult<&str, Expr> {
+    parse_binary(input, parse_conjunction, |i| map(tag("||"), |_| BinaryOp::Or)(i))
+}
+
+fn parse_block(input: &str) -> IResult<&str, Vec<Statement>> {
+    delimited(
+        char('{'),
+        many0(preceded(multispace0, parse_statement)),
+        pair(multispace0, char('}'))
+    )(input)
+}
+
+fn parse_if(input: &str) -> IResult<&str, Statement> {
+    map(
+        tuple((
+            tag("if"),
+            multispace0,
+            parse_expr,
+            multispace0,
+            parse_block,
+            opt(preceded(
+                tuple((multispace0, tag("else"), multispace0)),
+                alt((parse_block, map(parse_if, |nested| vec![nested])))
+            ))
+        )),
+        |(_, _, condition, _, then_branch, else_branch)| Statement::If {
+            condition,
+            then_branch,
+            else_branch: else_branch.unwrap_or_default(),
+        }
+    )(input)
+}
+
+fn parse_repeat(input: &str) -> IResult<&str, Statement> {
+    map(
+        tuple((
+            tag("repeat"),
+            multispace0,
+            parse_expr,
+            multispace0,
+            parse_block
+        )),
+        |(_, _, count, _, body)| Statement::Repeat { count, body }
+    )(input)
+}
+
 fn parse_statement(input: &str) -> IResult<&str, Statement> {
     alt((
+        parse_if,
+        parse_repeat,
         parse_net_node_connect,
         parse_chain_block_create,
         parse_econ_currency_mint,
@@ -318,7 +466,13 @@ pub fn compile(source: &str) -> Result<Vec<Statement>, String> {
 
 pub fn generate_bytecode(statements: &[Statement]) -> Vec<Opcode> {
     let mut bytecode = Vec::new();
+    emit_statements(statements, &mut bytecode);
+    bytecode
+}
 
+/// Appends the code for `statements`. Jump targets are absolute indexes
+/// into `bytecode`, so nested blocks are emitted into the same buffer.
+fn emit_statements(statements: &[Statement], bytecode: &mut Vec<Opcode>) {
     for statement in statements {
         match statement {
             Statement::NetNodeConnect { node1, node2 } => {
@@ -380,15 +534,89 @@ pub fn generate_bytecode(statements: &[Statement]) -> Vec<Opcode> {
                 bytecode.push(Opcode::Push(Value::String(event_data.clone())));
                 bytecode.push(Opcode::EmitEvent);
             },
+            Statement::If { condition, then_branch, else_branch } => {
+                // JumpIf branches on true, so negate to skip the then-branch
+                emit_expr(condition, bytecode);
+                bytecode.push(Opcode::Not);
+                let skip_then = bytecode.len();
+                bytecode.push(Opcode::JumpIf(0));
+                emit_statements(then_branch, bytecode);
+
+                if else_branch.is_empty() {
+                    bytecode[skip_then] = Opcode::JumpIf(bytecode.len());
+                } else {
+                    let skip_else = bytecode.len();
+                    bytecode.push(Opcode::Jump(0));
+                    bytecode[skip_then] = Opcode::JumpIf(bytecode.len());
+                    emit_statements(else_branch, bytecode);
+                    bytecode[skip_else] = Opcode::Jump(bytecode.len());
+                }
+            },
+            Statement::Repeat { count, body } => {
+                // Each loop gets its own counter, named after where it starts
+                let counter = format!("__repeat_{}", bytecode.len());
+                emit_expr(count, bytecode);
+                bytecode.push(Opcode::Store(counter.clone()));
+
+                let loop_start = bytecode.len();
+                bytecode.push(Opcode::Load(counter.clone()));
+                bytecode.push(Opcode::Push(Value::Int(0)));
+                bytecode.push(Opcode::Gt);
+                bytecode.push(Opcode::Not);
+                let exit = bytecode.len();
+                bytecode.push(Opcode::JumpIf(0));
+
+                emit_statements(body, bytecode);
+                bytecode.push(Opcode::Load(counter.clone()));
+                bytecode.push(Opcode::Push(Value::Int(1)));
+                bytecode.push(Opcode::Sub);
+                bytecode.push(Opcode::Store(counter));
+                bytecode.push(Opcode::Jump(loop_start));
+                bytecode[exit] = Opcode::JumpIf(bytecode.len());
+            },
         }
     }
+}
 
-    bytecode
+fn emit_expr(expr: &Expr, bytecode: &mut Vec<Opcode>) {
+    match expr {
+        Expr::Int(i) => bytecode.push(Opcode::Push(Value::Int(*i))),
+        Expr::Float(f) => bytecode.push(Opcode::Push(Value::Float(*f))),
+        Expr::Bool(b) => bytecode.push(Opcode::Push(Value::Bool(*b))),
+        Expr::Str(s) => bytecode.push(Opcode::Push(Value::String(s.clone()))),
+        Expr::ProposalStatus(proposal_id) => {
+            bytecode.push(Opcode::Push(Value::String(proposal_id.clone())));
+            bytecode.push(Opcode::GetProposalStatus);
+        },
+        Expr::Not(inner) => {
+            emit_expr(inner, bytecode);
+            bytecode.push(Opcode::Not);
+        },
+        Expr::Binary { op, lhs, rhs } => {
+            emit_expr(lhs, bytecode);
+            emit_expr(rhs, bytecode);
+            bytecode.push(match op {
+                BinaryOp::Add => Opcode::Add,
+                BinaryOp::Sub => Opcode::Sub,
+                BinaryOp::Mul => Opcode::Mul,
+                BinaryOp::Div => Opcode::Div,
+                BinaryOp::Eq => Opcode::Eq,
+                BinaryOp::Neq => Opcode::Neq,
+                BinaryOp::Lt => Opcode::Lt,
+                BinaryOp::Gt => Opcode::Gt,
+                BinaryOp::Lte => Opcode::Lte,
+                BinaryOp::Gte => Opcode::Gte,
+                BinaryOp::And => Opcode::And,
+                BinaryOp::Or => Opcode::Or,
+            });
+        },
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use icn_common::{IcnError, IcnResult};
 
     #[test]
     fn test_parse_net_node_connect() {
@@ -588,6 +816,111 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_if_else() {
+        let input = r#"
+            if get-proposal-status("proposal1") == "Passed" && 2 > 1 {
+                emit-event("Result", "passed")
+            } else if !true {
+                emit-event("Result", "unreachable")
+            } else {
+                vote-on-proposal("proposal1", false)
+            }
+        "#;
+        let statements = compile(input).unwrap();
+        assert_eq!(statements.len(), 1);
+
+        let Statement::If { condition, then_branch, else_branch } = &statements[0] else {
+            panic!("expected an if statement");
+        };
+        assert_eq!(*condition, Expr::Binary {
+            op: BinaryOp::And,
+            lhs: Box::new(Expr::Binary {
+                op: BinaryOp::Eq,
+                lhs: Box::new(Expr::ProposalStatus("proposal1".to_string())),
+                rhs: Box::new(Expr::Str("Passed".to_string())),
+            }),
+            rhs: Box::new(Expr::Binary {
+                op: BinaryOp::Gt,
+                lhs: Box::new(Expr::Int(2)),
+                rhs: Box::new(Expr::Int(1)),
+            }),
+        });
+        assert_eq!(then_branch.len(), 1);
+        assert!(matches!(&else_branch[..], [Statement::If { .. }]));
+    }
+
+    #[test]
+    fn test_control_flow_bytecode_runs() {
+        let statements = compile(r#"
+            repeat 1 + 2 {
+                if 1 < 2 { emit-event("Tick", "yes") } else { emit-event("Tick", "no") }
+            }
+            if false { emit-event("Never", "never") }
+        "#).unwrap();
+        let bytecode = generate_bytecode(&statements);
+
+        for (index, opcode) in bytecode.iter().enumerate() {
+            if let Opcode::Jump(target) | Opcode::JumpIf(target) = opcode {
+                assert!(*target > 0 && *target <= bytecode.len(), "bad jump at {}", index);
+            }
+        }
+        let events = bytecode.iter().filter(|op| matches!(op, Opcode::EmitEvent)).count();
+        assert_eq!(events, 3);
+
+        let host = EventHost::default();
+        let emitted = host.events.clone();
+        let mut vm = icn_vm::CoopVM::with_host(bytecode, Box::new(host));
+        vm.execute().unwrap();
+        let emitted = emitted.lock().unwrap();
+        assert_eq!(*emitted, vec!["Tick yes".to_string(); 3]);
+    }
+
+    /// Records emitted events and refuses every other system opcode.
+    #[derive(Default)]
+    struct EventHost {
+        events: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl icn_vm::HostInterface for EventHost {
+        fn mint_currency(&mut self, _currency_type: &str, _amount: f64) -> IcnResult<()> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn vote_on_proposal(&mut self, _proposal_id: &str, _in_favor: bool) -> IcnResult<()> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn allocate_resource(&mut self, _resource: &str, _amount: i64) -> IcnResult<()> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn update_reputation(&mut self, _address: &str, _change: i64) -> IcnResult<()> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn create_proposal(&mut self, _title: &str, _description: &str) -> IcnResult<String> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn get_proposal_status(&self, _proposal_id: &str) -> IcnResult<String> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn add_cooperative_member(&mut self, _coop_id: &str, _member_id: &str) -> IcnResult<()> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+
+        fn emit_event(&mut self, name: &str, data: &str) -> IcnResult<()> {
+            self.events.lock().unwrap().push(format!("{} {}", name, data));
+            Ok(())
+        }
+
+        fn block_time(&self) -> IcnResult<i64> {
+            Err(IcnError::Vm("Not supported in tests".into()))
+        }
+    }
+
     #[test]
     fn test_generate_bytecode() {
         let statements = vec![
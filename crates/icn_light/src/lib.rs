@@ -0,0 +1,423 @@
+// File: crates/icn_light/src/lib.rs
+
+//! A client that follows the chain by its block headers alone, for wallets
+//! on phones and small devices that cannot hold the full chain.
+//!
+//! Headers are checked to link up from a trusted checkpoint, and
+//! transactions are only believed once a full node has shown a merkle proof
+//! placing them under one of those headers.
+
+use icn_common::{BlockHeader, HeaderBatch, IcnError, IcnResult, ResumeToken, Transaction, TransactionProof, TransactionReceipt};
+use log::{debug, info};
+use std::collections::{HashMap, HashSet};
+
+/// Most headers asked of a full node in one request.
+pub const HEADER_BATCH_SIZE: usize = 500;
+
+/// What a light client needs from a full node.
+pub trait FullNode {
+    /// Up to `limit` headers starting at block `from`.
+    fn headers(&self, from: u64, limit: usize) -> IcnResult<Vec<BlockHeader>>;
+    /// Headers after the block `resume_token` names, waiting up to
+    /// `wait_secs` for one to be produced when there are none yet.
+    fn subscribe_headers(&self, resume_token: &str, wait_secs: u64) -> IcnResult<HeaderBatch>;
+    /// The committed transaction with `hash` and the proof of its inclusion.
+    fn transaction_proof(&self, hash: &str) -> IcnResult<TransactionProof>;
+    fn submit_transaction(&self, transaction: &Transaction) -> IcnResult<TransactionReceipt>;
+}
+
+/// Talks to a full node's HTTP API. Requests block, so call it off any
+/// async runtime.
+#[cfg(feature = "http")]
+pub struct HttpFullNode {
+    base_url: String,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "http")]
+impl HttpFullNode {
+    pub fn new(base_url: &str) -> Self {
+        HttpFullNode {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::blocking::Client::new(),
+        }
+    }
+
+    fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> IcnResult<T> {
+        let response = self.client.get(format!("{}{}", self.base_url, path))
+            .send()
+            .map_err(|e| IcnError::Network(format!("Request to full node failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(IcnError::Network(format!("Full node answered {} with status {}", path, response.status())));
+        }
+        response.json()
+            .map_err(|e| IcnError::Network(format!("Invalid response from full node: {}", e)))
+    }
+}
+
+#[cfg(feature = "http")]
+impl FullNode for HttpFullNode {
+    fn headers(&self, from: u64, limit: usize) -> IcnResult<Vec<BlockHeader>> {
+        self.get(&format!("/headers?from={}&limit={}", from, limit))
+    }
+
+    fn subscribe_headers(&self, resume_token: &str, wait_secs: u64) -> IcnResult<HeaderBatch> {
+        // The node holds the request open, so allow for that past the
+        // client's default timeout
+        let response = self.client.get(format!("{}/headers/subscribe", self.base_url))
+            .query(&[("token", resume_token.to_string()), ("limit", HEADER_BATCH_SIZE.to_string()), ("wait_secs", wait_secs.to_string())])
+            .timeout(std::time::Duration::from_secs(wait_secs + 30))
+            .send()
+            .map_err(|e| IcnError::Network(format!("Request to full node failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(IcnError::Network(format!("Full node refused the header subscription with status {}", response.status())));
+        }
+        response.json()
+            .map_err(|e| IcnError::Network(format!("Invalid response from full node: {}", e)))
+    }
+
+    fn transaction_proof(&self, hash: &str) -> IcnResult<TransactionProof> {
+        self.get(&format!("/proof/{}", hash))
+    }
+
+    fn submit_transaction(&self, transaction: &Transaction) -> IcnResult<TransactionReceipt> {
+        #[derive(serde::Deserialize)]
+        struct SubmitResponse {
+            receipt: TransactionReceipt,
+        }
+
+        let response = self.client.post(format!("{}/transaction", self.base_url))
+            .json(transaction)
+            .send()
+            .map_err(|e| IcnError::Network(format!("Request to full node failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(IcnError::Network(format!("Full node rejected transaction with status {}", response.status())));
+        }
+        response.json::<SubmitResponse>()
+            .map(|response| response.receipt)
+            .map_err(|e| IcnError::Network(format!("Invalid response from full node: {}", e)))
+    }
+}
+
+/// Block headers from a trusted checkpoint on, plus the verified
+/// transactions of the addresses the wallet watches.
+pub struct LightClient {
+    headers: Vec<BlockHeader>,
+    watched: HashSet<String>,
+    history: HashMap<String, Vec<TransactionProof>>,
+}
+
+impl LightClient {
+    /// Starts from a header the caller trusts, such as the genesis block or
+    /// a checkpoint shipped with the wallet. Nothing before it is checked.
+    pub fn new(checkpoint: BlockHeader) -> IcnResult<Self> {
+        if checkpoint.calculate_hash() != checkpoint.hash {
+            return Err(IcnError::Blockchain("Checkpoint header hash does not match its contents".into()));
+        }
+        Ok(LightClient { headers: vec![checkpoint], watched: HashSet::new(), history: HashMap::new() })
+    }
+
+    pub fn tip(&self) -> &BlockHeader {
+        self.headers.last().expect("light client always holds its checkpoint")
+    }
+
+    pub fn header(&self, index: u64) -> Option<&BlockHeader> {
+        let offset = index.checked_sub(self.headers[0].index)?;
+        self.headers.get(offset as usize)
+    }
+
+    /// Appends a header after checking it extends the current tip.
+    pub fn append_header(&mut self, header: BlockHeader) -> IcnResult<()> {
+        let tip = self.tip();
+        if header.index != tip.index + 1 {
+            return Err(IcnError::Blockchain(format!("Expected header {} but got {}", tip.index + 1, header.index)));
+        }
+        if header.previous_hash != tip.hash {
+            return Err(IcnError::Blockchain(format!("Header {} does not link to the current tip", header.index)));
+        }
+        if header.calculate_hash() != header.hash {
+            return Err(IcnError::Blockchain(format!("Header {} hash does not match its contents", header.index)));
+        }
+        self.headers.push(header);
+        Ok(())
+    }
+
+    /// Fetches and checks headers past the tip until the node has no more.
+    /// Returns how many were added; headers checked before a bad one are
+    /// kept.
+    pub fn sync(&mut self, node: &impl FullNode) -> IcnResult<usize> {
+        let mut added = 0;
+        loop {
+            let batch = node.headers(self.tip().index + 1, HEADER_BATCH_SIZE)?;
+            let batch_len = batch.len();
+            for header in batch {
+                self.append_header(header)?;
+                added += 1;
+            }
+            if batch_len < HEADER_BATCH_SIZE {
+                break;
+            }
+        }
+        if added > 0 {
+            info!("Light client synced {} headers, tip is now block {}", added, self.tip().index);
+        }
+        Ok(added)
+    }
+
+    /// Waits up to `wait_secs` for the node to produce blocks past the tip
+    /// and adds their headers, then keeps fetching while the node says it
+    /// has more. Call it in a loop to follow the chain; each call resumes
+    /// from the tip, so a dropped connection loses nothing.
+    pub fn follow(&mut self, node: &impl FullNode, wait_secs: u64) -> IcnResult<usize> {
+        let mut added = 0;
+        let mut wait_secs = wait_secs;
+        loop {
+            let batch = node.subscribe_headers(&ResumeToken::after(self.tip()).to_string(), wait_secs)?;
+            for header in batch.headers {
+                self.append_header(header)?;
+                added += 1;
+            }
+            if !batch.more {
+                break;
+            }
+            wait_secs = 0;
+        }
+        if added > 0 {
+            debug!("Light client followed {} headers, tip is now block {}", added, self.tip().index);
+        }
+        Ok(added)
+    }
+
+    pub fn watch(&mut self, address: &str) {
+        self.watched.insert(address.to_string());
+    }
+
+    /// Stops watching `address` and forgets its verified transactions.
+    pub fn unwatch(&mut self, address: &str) {
+        self.watched.remove(address);
+        self.history.remove(address);
+    }
+
+    pub fn watched(&self) -> impl Iterator<Item = &String> {
+        self.watched.iter()
+    }
+
+    /// Checks a proof against the header this client holds for its block.
+    /// A verified transaction touching a watched address is added to that
+    /// address's history.
+    pub fn verify_transaction(&mut self, proof: TransactionProof) -> IcnResult<()> {
+        let header = self.header(proof.block_index).ok_or_else(|| IcnError::Blockchain(format!(
+            "No header for block {}; sync before verifying", proof.block_index
+        )))?;
+        if !proof.verify(header) {
+            return Err(IcnError::Blockchain(format!(
+                "Transaction {} is not included in block {}", proof.transaction.hash(), proof.block_index
+            )));
+        }
+        for address in [&proof.transaction.from, &proof.transaction.to] {
+            if !self.watched.contains(address) {
+                continue;
+            }
+            let history = self.history.entry(address.clone()).or_default();
+            if !history.iter().any(|known| known.transaction.hash() == proof.transaction.hash()) {
+                debug!("Verified transaction {} for watched address {}", proof.transaction.hash(), address);
+                history.push(proof.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Fetches the proof for `hash` from `node` and verifies it.
+    pub fn fetch_and_verify(&mut self, node: &impl FullNode, hash: &str) -> IcnResult<Transaction> {
+        let proof = node.transaction_proof(hash)?;
+        if proof.transaction.hash() != hash {
+            return Err(IcnError::Blockchain(format!("Full node sent a proof for a different transaction than {}", hash)));
+        }
+        let transaction = proof.transaction.clone();
+        self.verify_transaction(proof)?;
+        Ok(transaction)
+    }
+
+    /// Verified transactions to or from a watched address, in the order
+    /// they were verified.
+    pub fn history(&self, address: &str) -> &[TransactionProof] {
+        self.history.get(address).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Hands a transaction signed on this device to a full node. The
+    /// signing key never leaves the wallet, so unsigned transactions are
+    /// refused here rather than sent off to be signed.
+    pub fn submit_transaction(&self, node: &impl FullNode, transaction: &Transaction) -> IcnResult<TransactionReceipt> {
+        if transaction.signature.is_none() {
+            return Err(IcnError::Validation("Light clients only submit signed transactions".into()));
+        }
+        node.submit_transaction(transaction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_common::merkle::{merkle_root, transaction_leaf, EMPTY_MERKLE_ROOT};
+    use icn_common::{Amount, CurrencyType, MerkleProof, TransactionStatus};
+    use std::cell::RefCell;
+
+    struct MockNode {
+        headers: Vec<BlockHeader>,
+        blocks: Vec<Vec<Transaction>>,
+        submitted: RefCell<Vec<Transaction>>,
+    }
+
+    impl MockNode {
+        fn new() -> Self {
+            let mut node = MockNode { headers: Vec::new(), blocks: Vec::new(), submitted: RefCell::new(Vec::new()) };
+            node.push_block(Vec::new());
+            node
+        }
+
+        fn push_block(&mut self, transactions: Vec<Transaction>) {
+            let leaves: Vec<String> = transactions.iter().map(transaction_leaf).collect();
+            let mut header = BlockHeader {
+                index: self.headers.len() as u64,
+                timestamp: 0,
+                previous_hash: self.headers.last().map(|h| h.hash.clone()).unwrap_or_else(|| EMPTY_MERKLE_ROOT.to_string()),
+                hash: String::new(),
+                nonce: 0,
+                merkle_root: merkle_root(&leaves),
+                state_root: String::new(),
+                evidence_root: String::new(),
+                producer: String::new(),
+                producer_signature: Vec::new(),
+            };
+            header.hash = header.calculate_hash();
+            self.headers.push(header);
+            self.blocks.push(transactions);
+        }
+    }
+
+    impl FullNode for MockNode {
+        fn headers(&self, from: u64, limit: usize) -> IcnResult<Vec<BlockHeader>> {
+            Ok(self.headers.iter().skip(from as usize).take(limit).cloned().collect())
+        }
+
+        fn subscribe_headers(&self, resume_token: &str, _wait_secs: u64) -> IcnResult<HeaderBatch> {
+            let token = ResumeToken::parse(resume_token)?;
+            if self.headers.get(token.index as usize).is_none_or(|header| header.hash != token.hash) {
+                return Err(IcnError::Validation("unknown block".into()));
+            }
+            // Two at a time, so following takes more than one batch
+            let headers = self.headers(token.index + 1, 2)?;
+            let last = headers.last().map_or(token.index, |header| header.index);
+            let resume_token = headers.last().map_or(token, ResumeToken::after).to_string();
+            Ok(HeaderBatch { headers, resume_token, more: self.headers.len() as u64 > last + 1 })
+        }
+
+        fn transaction_proof(&self, hash: &str) -> IcnResult<TransactionProof> {
+            for (index, transactions) in self.blocks.iter().enumerate() {
+                if let Some(position) = transactions.iter().position(|tx| tx.hash() == hash) {
+                    let leaves: Vec<String> = transactions.iter().map(transaction_leaf).collect();
+                    return Ok(TransactionProof {
+                        transaction: transactions[position].clone(),
+                        block_index: index as u64,
+                        block_hash: self.headers[index].hash.clone(),
+                        proof: MerkleProof::build(&leaves, position).unwrap(),
+                    });
+                }
+            }
+            Err(IcnError::Blockchain("not found".into()))
+        }
+
+        fn submit_transaction(&self, transaction: &Transaction) -> IcnResult<TransactionReceipt> {
+            self.submitted.borrow_mut().push(transaction.clone());
+            Ok(TransactionReceipt {
+                tx_hash: transaction.hash(),
+                block_index: self.headers.len() as u64,
+                status: TransactionStatus::Success,
+                gas_used: 0,
+                fee: Amount::ZERO,
+                events: Vec::new(),
+                shard_id: 0,
+            })
+        }
+    }
+
+    fn transfer(from: &str, to: &str, amount: f64) -> Transaction {
+        Transaction::new(from.to_string(), to.to_string(), amount, CurrencyType::BasicNeeds, 0)
+    }
+
+    #[test]
+    fn test_sync_and_verify_watched_transactions() {
+        let mut node = MockNode::new();
+        let mut client = LightClient::new(node.headers[0].clone()).unwrap();
+        let paid = transfer("Alice", "Bob", 10.0);
+        let other = transfer("Carol", "Dave", 5.0);
+        node.push_block(vec![paid.clone(), other.clone(), transfer("Carol", "Erin", 1.0)]);
+        node.push_block(Vec::new());
+
+        assert_eq!(client.sync(&node).unwrap(), 2);
+        assert_eq!(client.tip(), &node.headers[2]);
+
+        client.watch("Bob");
+        client.fetch_and_verify(&node, &paid.hash()).unwrap();
+        client.fetch_and_verify(&node, &other.hash()).unwrap();
+        client.fetch_and_verify(&node, &paid.hash()).unwrap();
+        assert_eq!(client.history("Bob").len(), 1);
+        assert_eq!(client.history("Bob")[0].transaction, paid);
+        assert!(client.history("Carol").is_empty());
+
+        let mut forged = node.transaction_proof(&paid.hash()).unwrap();
+        forged.transaction.amount = transfer("Alice", "Bob", 1000.0).amount;
+        assert!(client.verify_transaction(forged).is_err());
+
+        client.unwatch("Bob");
+        assert!(client.history("Bob").is_empty());
+    }
+
+    #[test]
+    fn test_rejects_headers_that_do_not_link() {
+        let mut node = MockNode::new();
+        node.push_block(Vec::new());
+        let mut client = LightClient::new(node.headers[0].clone()).unwrap();
+
+        let mut forged = node.headers[1].clone();
+        forged.merkle_root = merkle_root(&[transaction_leaf(&transfer("Mallory", "Mallory", 1.0))]);
+        assert!(client.append_header(forged.clone()).is_err());
+        forged.previous_hash = "unrelated".to_string();
+        forged.hash = forged.calculate_hash();
+        assert!(client.append_header(forged).is_err());
+        assert_eq!(client.tip().index, 0);
+    }
+
+    #[test]
+    fn test_follow_resumes_from_the_tip() {
+        let mut node = MockNode::new();
+        let mut client = LightClient::new(node.headers[0].clone()).unwrap();
+        assert_eq!(client.follow(&node, 0).unwrap(), 0);
+
+        for _ in 0..5 {
+            node.push_block(Vec::new());
+        }
+        assert_eq!(client.follow(&node, 0).unwrap(), 5);
+        assert_eq!(client.tip(), &node.headers[5]);
+
+        // A node whose chain no longer has the tip is refused
+        let mut other = MockNode::new();
+        other.push_block(vec![transfer("Alice", "Bob", 1.0)]);
+        let mut client = LightClient::new(other.headers[0].clone()).unwrap();
+        client.append_header(other.headers[1].clone()).unwrap();
+        assert!(client.follow(&node, 0).is_err());
+    }
+
+    #[test]
+    fn test_only_signed_transactions_are_submitted() {
+        let node = MockNode::new();
+        let client = LightClient::new(node.headers[0].clone()).unwrap();
+        let mut transaction = transfer("Alice", "Bob", 1.0);
+        assert!(client.submit_transaction(&node, &transaction).is_err());
+
+        transaction.signature = Some(vec![0; 64]);
+        let receipt = client.submit_transaction(&node, &transaction).unwrap();
+        assert_eq!(receipt.tx_hash, transaction.hash());
+        assert_eq!(node.submitted.borrow().len(), 1);
+    }
+}
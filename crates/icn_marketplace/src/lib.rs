@@ -0,0 +1,347 @@
+// File: crates/icn_marketplace/src/lib.rs
+
+use chrono::{DateTime, Utc};
+use icn_common::{IcnResult, IcnError, Amount, CurrencyType};
+use icn_currency::CurrencySystem;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ListingKind {
+    Goods,
+    Services,
+}
+
+/// What a member offers, at a price per unit.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Listing {
+    pub id: String,
+    pub seller: String,
+    pub kind: ListingKind,
+    pub title: String,
+    pub description: String,
+    pub unit_price: Amount,
+    pub currency_type: CurrencyType,
+    /// Units still available. Orders take from it and cancellations put
+    /// back what they took.
+    pub quantity: u32,
+    pub posted_at: DateTime<Utc>,
+    /// Withdrawn listings take no new orders; open orders are unaffected.
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum OrderStatus {
+    /// Paid into escrow, waiting for the buyer to confirm fulfillment.
+    Escrowed,
+    /// Escrow released to the seller.
+    Fulfilled,
+    /// Cancelled by the seller before fulfillment, escrow returned.
+    Cancelled,
+    /// Escrow frozen while the dispute runs.
+    Disputed(String),
+    /// Escrow returned to the buyer by a dispute ruling.
+    Refunded,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Order {
+    pub id: String,
+    pub listing_id: String,
+    pub buyer: String,
+    pub seller: String,
+    pub quantity: u32,
+    /// The full price, held in escrow until the order is settled.
+    pub amount: Amount,
+    pub currency_type: CurrencyType,
+    pub status: OrderStatus,
+    pub placed_at: DateTime<Utc>,
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+impl Order {
+    /// Account holding the buyer's payment until it is released or
+    /// refunded.
+    pub fn escrow_account(&self) -> String {
+        format!("marketplace:{}", self.id)
+    }
+
+    /// The other party to the order, if `member` is one of them.
+    pub fn counterparty(&self, member: &str) -> Option<&str> {
+        if member == self.buyer {
+            Some(&self.seller)
+        } else if member == self.seller {
+            Some(&self.buyer)
+        } else {
+            None
+        }
+    }
+}
+
+/// Listings and the orders placed against them. Payments move through
+/// the currency system: into an escrow account per order when it is
+/// placed, and out to the seller or back to the buyer when it settles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Marketplace {
+    listings: BTreeMap<String, Listing>,
+    orders: BTreeMap<String, Order>,
+}
+
+impl Marketplace {
+    pub fn new() -> Self {
+        Marketplace::default()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn post_listing(
+        &mut self,
+        seller: &str,
+        kind: ListingKind,
+        title: &str,
+        description: &str,
+        unit_price: Amount,
+        currency_type: CurrencyType,
+        quantity: u32,
+        now: DateTime<Utc>,
+    ) -> IcnResult<String> {
+        if !unit_price.is_positive() {
+            return Err(IcnError::Marketplace("Price must be positive".into()));
+        }
+        unit_price.check_precision(&currency_type)?;
+        if quantity == 0 {
+            return Err(IcnError::Marketplace("A listing must offer at least one unit".into()));
+        }
+        if title.trim().is_empty() {
+            return Err(IcnError::Marketplace("A listing needs a title".into()));
+        }
+
+        let id = Uuid::new_v4().to_string();
+        self.listings.insert(id.clone(), Listing {
+            id: id.clone(),
+            seller: seller.to_string(),
+            kind,
+            title: title.to_string(),
+            description: description.to_string(),
+            unit_price,
+            currency_type,
+            quantity,
+            posted_at: now,
+            active: true,
+        });
+        Ok(id)
+    }
+
+    /// Stops a listing from taking orders. Only its seller may withdraw it.
+    pub fn withdraw_listing(&mut self, listing_id: &str, seller: &str) -> IcnResult<()> {
+        let listing = self.get_listing_mut(listing_id)?;
+        if listing.seller != seller {
+            return Err(IcnError::Marketplace("Only the seller can withdraw a listing".into()));
+        }
+        listing.active = false;
+        Ok(())
+    }
+
+    pub fn get_listing(&self, listing_id: &str) -> IcnResult<&Listing> {
+        self.listings.get(listing_id)
+            .ok_or_else(|| IcnError::Marketplace("Listing not found".into()))
+    }
+
+    /// Active listings with units left, optionally of one kind only.
+    pub fn list_listings(&self, kind: Option<ListingKind>) -> Vec<&Listing> {
+        self.listings.values()
+            .filter(|listing| listing.active && listing.quantity > 0)
+            .filter(|listing| kind.is_none_or(|kind| listing.kind == kind))
+            .collect()
+    }
+
+    /// Orders `quantity` units, paying their price from the buyer into the
+    /// order's escrow.
+    pub fn place_order(&mut self, listing_id: &str, buyer: &str, quantity: u32, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<String> {
+        let listing = self.get_listing(listing_id)?;
+        if !listing.active {
+            return Err(IcnError::Marketplace("Listing has been withdrawn".into()));
+        }
+        if listing.seller == buyer {
+            return Err(IcnError::Marketplace("A member cannot buy from their own listing".into()));
+        }
+        if quantity == 0 || quantity > listing.quantity {
+            return Err(IcnError::Marketplace(format!("Between 1 and {} units can be ordered", listing.quantity)));
+        }
+
+        let amount = listing.unit_price.checked_mul(quantity.into())
+            .ok_or_else(|| IcnError::Marketplace("Order total is too large".into()))?;
+        let id = Uuid::new_v4().to_string();
+        let order = Order {
+            id: id.clone(),
+            listing_id: listing_id.to_string(),
+            buyer: buyer.to_string(),
+            seller: listing.seller.clone(),
+            quantity,
+            amount,
+            currency_type: listing.currency_type.clone(),
+            status: OrderStatus::Escrowed,
+            placed_at: now,
+            settled_at: None,
+        };
+        currency_system.transfer_amount(buyer, &order.escrow_account(), &order.currency_type, order.amount)?;
+        self.get_listing_mut(listing_id)?.quantity -= quantity;
+        self.orders.insert(id.clone(), order);
+        Ok(id)
+    }
+
+    /// The buyer confirms they received what they paid for, releasing the
+    /// escrow to the seller.
+    pub fn confirm_fulfillment(&mut self, order_id: &str, buyer: &str, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Amount> {
+        let order = self.get_order(order_id)?;
+        if order.buyer != buyer {
+            return Err(IcnError::Marketplace("Only the buyer can confirm fulfillment".into()));
+        }
+        if order.status != OrderStatus::Escrowed {
+            return Err(IcnError::Marketplace(format!("Order is {:?}, not awaiting fulfillment", order.status)));
+        }
+        let seller = order.seller.clone();
+        self.settle(order_id, &seller, OrderStatus::Fulfilled, currency_system, now)
+    }
+
+    /// The seller backs out of an order they can't fill, refunding the
+    /// buyer and restocking the listing.
+    pub fn cancel_order(&mut self, order_id: &str, seller: &str, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Amount> {
+        let order = self.get_order(order_id)?;
+        if order.seller != seller {
+            return Err(IcnError::Marketplace("Only the seller can cancel an order".into()));
+        }
+        if order.status != OrderStatus::Escrowed {
+            return Err(IcnError::Marketplace(format!("Order is {:?} and can no longer be cancelled", order.status)));
+        }
+        let (buyer, listing_id, quantity) = (order.buyer.clone(), order.listing_id.clone(), order.quantity);
+        let refunded = self.settle(order_id, &buyer, OrderStatus::Cancelled, currency_system, now)?;
+        self.get_listing_mut(&listing_id)?.quantity += quantity;
+        Ok(refunded)
+    }
+
+    /// Freezes an order's escrow while a dispute over it runs. Either
+    /// party may dispute an order until it is settled.
+    pub fn mark_disputed(&mut self, order_id: &str, member: &str, dispute_id: &str) -> IcnResult<()> {
+        let order = self.get_order_mut(order_id)?;
+        if order.counterparty(member).is_none() {
+            return Err(IcnError::Marketplace("Only the buyer or seller can dispute an order".into()));
+        }
+        if order.status != OrderStatus::Escrowed {
+            return Err(IcnError::Marketplace(format!("Order is {:?} and can no longer be disputed", order.status)));
+        }
+        order.status = OrderStatus::Disputed(dispute_id.to_string());
+        Ok(())
+    }
+
+    /// Settles a disputed order on the ruling: the escrow goes back to the
+    /// buyer if `refund_buyer`, otherwise to the seller.
+    pub fn settle_dispute(&mut self, order_id: &str, refund_buyer: bool, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Amount> {
+        let order = self.get_order(order_id)?;
+        if !matches!(order.status, OrderStatus::Disputed(_)) {
+            return Err(IcnError::Marketplace("Order is not under dispute".into()));
+        }
+        let (recipient, status) = if refund_buyer {
+            (order.buyer.clone(), OrderStatus::Refunded)
+        } else {
+            (order.seller.clone(), OrderStatus::Fulfilled)
+        };
+        self.settle(order_id, &recipient, status, currency_system, now)
+    }
+
+    pub fn get_order(&self, order_id: &str) -> IcnResult<&Order> {
+        self.orders.get(order_id)
+            .ok_or_else(|| IcnError::Marketplace("Order not found".into()))
+    }
+
+    /// Orders the member placed or is filling, oldest first.
+    pub fn orders_for(&self, member: &str) -> Vec<&Order> {
+        let mut orders: Vec<&Order> = self.orders.values()
+            .filter(|order| order.counterparty(member).is_some())
+            .collect();
+        orders.sort_by_key(|order| order.placed_at);
+        orders
+    }
+
+    /// Empties the order's escrow to `recipient` and closes the order.
+    fn settle(&mut self, order_id: &str, recipient: &str, status: OrderStatus, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Amount> {
+        let order = self.get_order_mut(order_id)?;
+        currency_system.transfer_amount(&order.escrow_account(), recipient, &order.currency_type, order.amount)?;
+        order.status = status;
+        order.settled_at = Some(now);
+        Ok(order.amount)
+    }
+
+    fn get_listing_mut(&mut self, listing_id: &str) -> IcnResult<&mut Listing> {
+        self.listings.get_mut(listing_id)
+            .ok_or_else(|| IcnError::Marketplace("Listing not found".into()))
+    }
+
+    fn get_order_mut(&mut self, order_id: &str) -> IcnResult<&mut Order> {
+        self.orders.get_mut(order_id)
+            .ok_or_else(|| IcnError::Marketplace("Order not found".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENCY: CurrencyType = CurrencyType::Service;
+
+    fn setup(now: DateTime<Utc>) -> (Marketplace, CurrencySystem, String) {
+        let mut marketplace = Marketplace::new();
+        let mut currency_system = CurrencySystem::new();
+        currency_system.allocate("buyer", &CURRENCY, 100.0).unwrap();
+        let listing_id = marketplace.post_listing("seller", ListingKind::Goods, "Bread", "Sourdough loaves", Amount::from(5.0), CURRENCY, 10, now).unwrap();
+        (marketplace, currency_system, listing_id)
+    }
+
+    #[test]
+    fn test_orders_are_escrowed_until_fulfilled() {
+        let now = Utc::now();
+        let (mut marketplace, mut currency_system, listing_id) = setup(now);
+
+        assert!(marketplace.place_order(&listing_id, "seller", 1, &mut currency_system, now).is_err());
+        assert!(marketplace.place_order(&listing_id, "buyer", 11, &mut currency_system, now).is_err());
+        let order_id = marketplace.place_order(&listing_id, "buyer", 4, &mut currency_system, now).unwrap();
+        let escrow = marketplace.get_order(&order_id).unwrap().escrow_account();
+        assert_eq!(currency_system.get_balance(&escrow, &CURRENCY).unwrap(), 20.0);
+        assert_eq!(currency_system.get_balance("buyer", &CURRENCY).unwrap(), 80.0);
+        assert_eq!(marketplace.get_listing(&listing_id).unwrap().quantity, 6);
+
+        assert!(marketplace.confirm_fulfillment(&order_id, "seller", &mut currency_system, now).is_err());
+        assert_eq!(marketplace.confirm_fulfillment(&order_id, "buyer", &mut currency_system, now).unwrap(), Amount::from(20.0));
+        assert_eq!(currency_system.get_balance("seller", &CURRENCY).unwrap(), 20.0);
+        assert_eq!(marketplace.get_order(&order_id).unwrap().status, OrderStatus::Fulfilled);
+        assert!(marketplace.mark_disputed(&order_id, "buyer", "d1").is_err());
+
+        // A cancelled order goes back to the buyer and back on the shelf
+        let order_id = marketplace.place_order(&listing_id, "buyer", 2, &mut currency_system, now).unwrap();
+        assert_eq!(marketplace.cancel_order(&order_id, "seller", &mut currency_system, now).unwrap(), Amount::from(10.0));
+        assert_eq!(currency_system.get_balance("buyer", &CURRENCY).unwrap(), 80.0);
+        assert_eq!(marketplace.get_listing(&listing_id).unwrap().quantity, 6);
+
+        marketplace.withdraw_listing(&listing_id, "seller").unwrap();
+        assert!(marketplace.list_listings(None).is_empty());
+        assert!(marketplace.place_order(&listing_id, "buyer", 1, &mut currency_system, now).is_err());
+    }
+
+    #[test]
+    fn test_disputed_orders_settle_on_the_ruling() {
+        let now = Utc::now();
+        let (mut marketplace, mut currency_system, listing_id) = setup(now);
+        let order_id = marketplace.place_order(&listing_id, "buyer", 2, &mut currency_system, now).unwrap();
+
+        assert!(marketplace.mark_disputed(&order_id, "stranger", "d1").is_err());
+        marketplace.mark_disputed(&order_id, "buyer", "d1").unwrap();
+        assert!(marketplace.confirm_fulfillment(&order_id, "buyer", &mut currency_system, now).is_err());
+        assert!(marketplace.cancel_order(&order_id, "seller", &mut currency_system, now).is_err());
+
+        marketplace.settle_dispute(&order_id, true, &mut currency_system, now).unwrap();
+        assert_eq!(currency_system.get_balance("buyer", &CURRENCY).unwrap(), 100.0);
+        assert_eq!(marketplace.get_order(&order_id).unwrap().status, OrderStatus::Refunded);
+        assert_eq!(marketplace.orders_for("seller").len(), 1);
+        assert!(marketplace.settle_dispute(&order_id, false, &mut currency_system, now).is_err());
+    }
+}
@@ -0,0 +1,240 @@
+// File: crates/icn_network/src/bandwidth.rs
+
+use icn_common::{IcnError, IcnResult, PeerTraffic};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// Limits are counted over fixed windows of this length.
+pub const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// How much traffic each peer is allowed per second.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BandwidthLimits {
+    pub max_bytes_in_per_sec: u64,
+    pub max_bytes_out_per_sec: u64,
+    pub max_messages_per_sec: u32,
+    /// Consecutive windows a peer may go over its inbound limits in
+    /// before it is disconnected.
+    pub max_strikes: u32,
+}
+
+impl Default for BandwidthLimits {
+    fn default() -> Self {
+        BandwidthLimits {
+            max_bytes_in_per_sec: 4 * 1024 * 1024,
+            max_bytes_out_per_sec: 4 * 1024 * 1024,
+            max_messages_per_sec: 200,
+            max_strikes: 5,
+        }
+    }
+}
+
+impl BandwidthLimits {
+    pub fn validate(&self) -> IcnResult<()> {
+        if self.max_bytes_in_per_sec == 0 || self.max_bytes_out_per_sec == 0 || self.max_messages_per_sec == 0 || self.max_strikes == 0 {
+            return Err(IcnError::Config("Bandwidth limits must be greater than zero".into()));
+        }
+        Ok(())
+    }
+}
+
+/// What to do with a message a peer sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateDecision {
+    Accept,
+    /// Over the peer's limits for this window; drop the message.
+    Drop,
+    /// The peer has stayed over its limits too long.
+    Disconnect,
+}
+
+#[derive(Debug)]
+struct PeerUsage {
+    traffic: PeerTraffic,
+    window_start: Instant,
+    bytes_in: u64,
+    bytes_out: u64,
+    messages_in: u32,
+    /// Whether the peer has already been given a strike this window.
+    over_limit: bool,
+}
+
+impl PeerUsage {
+    fn new(now: Instant) -> Self {
+        PeerUsage { traffic: PeerTraffic::default(), window_start: now, bytes_in: 0, bytes_out: 0, messages_in: 0, over_limit: false }
+    }
+
+    /// Starts a new window once the current one has run out. A window
+    /// that passed within the limits clears the peer's strikes.
+    fn roll(&mut self, now: Instant) {
+        if now.duration_since(self.window_start) < RATE_WINDOW {
+            return;
+        }
+        if !self.over_limit {
+            self.traffic.strikes = 0;
+        }
+        self.window_start = now;
+        self.bytes_in = 0;
+        self.bytes_out = 0;
+        self.messages_in = 0;
+        self.over_limit = false;
+    }
+}
+
+/// Traffic counts and rate limits per peer, shared between the manager and
+/// the sessions reading from peers.
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthTracker {
+    limits: Arc<RwLock<BandwidthLimits>>,
+    peers: Arc<RwLock<HashMap<SocketAddr, PeerUsage>>>,
+}
+
+impl BandwidthTracker {
+    pub fn new(limits: BandwidthLimits) -> Self {
+        BandwidthTracker { limits: Arc::new(RwLock::new(limits)), peers: Arc::default() }
+    }
+
+    pub fn limits(&self) -> BandwidthLimits {
+        *self.limits.read().unwrap()
+    }
+
+    /// Changes the limits for every session sharing the tracker, from the
+    /// next message on. Counts and strikes so far are kept.
+    pub fn set_limits(&self, limits: BandwidthLimits) -> IcnResult<()> {
+        limits.validate()?;
+        *self.limits.write().unwrap() = limits;
+        Ok(())
+    }
+
+    /// Counts a message of `bytes` from `peer` and decides whether it is
+    /// processed. The message that crosses a limit still gets through, so
+    /// one frame larger than the byte limit is not refused outright.
+    pub fn record_received(&self, peer: SocketAddr, bytes: usize, now: Instant) -> RateDecision {
+        let limits = self.limits();
+        let mut peers = self.peers.write().unwrap();
+        let usage = peers.entry(peer).or_insert_with(|| PeerUsage::new(now));
+        usage.roll(now);
+
+        let within_limits = usage.bytes_in < limits.max_bytes_in_per_sec && usage.messages_in < limits.max_messages_per_sec;
+        usage.bytes_in += bytes as u64;
+        usage.messages_in += 1;
+        usage.traffic.bytes_received += bytes as u64;
+        usage.traffic.messages_received += 1;
+        if within_limits {
+            return RateDecision::Accept;
+        }
+
+        usage.traffic.messages_dropped += 1;
+        if !usage.over_limit {
+            usage.over_limit = true;
+            usage.traffic.strikes += 1;
+        }
+        if usage.traffic.strikes >= limits.max_strikes {
+            RateDecision::Disconnect
+        } else {
+            RateDecision::Drop
+        }
+    }
+
+    /// Whether the send cap for `peer` leaves room for another message
+    /// this window. Counts the message as deferred if not.
+    pub fn try_send(&self, peer: SocketAddr, now: Instant) -> bool {
+        let limits = self.limits();
+        let mut peers = self.peers.write().unwrap();
+        let usage = peers.entry(peer).or_insert_with(|| PeerUsage::new(now));
+        usage.roll(now);
+        if usage.bytes_out >= limits.max_bytes_out_per_sec {
+            usage.traffic.messages_deferred += 1;
+            return false;
+        }
+        true
+    }
+
+    pub fn record_sent(&self, peer: SocketAddr, bytes: usize, now: Instant) {
+        let mut peers = self.peers.write().unwrap();
+        let usage = peers.entry(peer).or_insert_with(|| PeerUsage::new(now));
+        usage.roll(now);
+        usage.bytes_out += bytes as u64;
+        usage.traffic.bytes_sent += bytes as u64;
+        usage.traffic.messages_sent += 1;
+    }
+
+    /// Moves peers that have been going over their limits to the back,
+    /// keeping the order otherwise.
+    pub fn deprioritize(&self, mut peer_addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        let peers = self.peers.read().unwrap();
+        peer_addrs.sort_by_key(|peer| peers.get(peer).map_or(0, |usage| usage.traffic.strikes));
+        peer_addrs
+    }
+
+    pub fn forget(&self, peer: &SocketAddr) {
+        self.peers.write().unwrap().remove(peer);
+    }
+
+    pub fn peer(&self, peer: &SocketAddr) -> Option<PeerTraffic> {
+        self.peers.read().unwrap().get(peer).map(|usage| usage.traffic.clone())
+    }
+
+    /// Traffic with every tracked peer, by address.
+    pub fn all(&self) -> BTreeMap<String, PeerTraffic> {
+        self.peers.read().unwrap().iter()
+            .map(|(peer, usage)| (peer.to_string(), usage.traffic.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peers_over_their_limits_are_dropped_then_disconnected() {
+        let limits = BandwidthLimits { max_bytes_in_per_sec: 1_000, max_bytes_out_per_sec: 500, max_messages_per_sec: 3, max_strikes: 2 };
+        let tracker = BandwidthTracker::new(limits);
+        let (noisy, quiet): (SocketAddr, SocketAddr) = ("127.0.0.1:9000".parse().unwrap(), "127.0.0.1:9001".parse().unwrap());
+        let start = Instant::now();
+
+        // The message crossing the byte limit is let through, the next is not
+        assert_eq!(tracker.record_received(noisy, 1_200, start), RateDecision::Accept);
+        assert_eq!(tracker.record_received(noisy, 10, start), RateDecision::Drop);
+        assert_eq!(tracker.deprioritize(vec![noisy, quiet]), vec![quiet, noisy]);
+
+        // A clean window clears the strike
+        let later = start + RATE_WINDOW;
+        for _ in 0..3 {
+            assert_eq!(tracker.record_received(noisy, 10, later), RateDecision::Accept);
+        }
+        assert_eq!(tracker.record_received(noisy, 10, later + RATE_WINDOW), RateDecision::Accept);
+        assert_eq!(tracker.peer(&noisy).unwrap().strikes, 0);
+
+        // Two windows over the message limit in a row disconnect the peer
+        let mut now = later + RATE_WINDOW * 2;
+        for expected in [RateDecision::Drop, RateDecision::Disconnect] {
+            for _ in 0..3 {
+                tracker.record_received(noisy, 10, now);
+            }
+            assert_eq!(tracker.record_received(noisy, 10, now), expected);
+            now += RATE_WINDOW;
+        }
+        let traffic = tracker.peer(&noisy).unwrap();
+        assert_eq!((traffic.messages_received, traffic.messages_dropped, traffic.bytes_received), (14, 3, 1_330));
+
+        // Sends stop once the cap is spent and resume the next window
+        assert!(tracker.try_send(quiet, start));
+        tracker.record_sent(quiet, 600, start);
+        assert!(!tracker.try_send(quiet, start));
+        assert!(tracker.try_send(quiet, start + RATE_WINDOW));
+        assert_eq!(tracker.all()[&quiet.to_string()].messages_deferred, 1);
+
+        // New limits reach every clone of the tracker
+        let session = tracker.clone();
+        tracker.record_sent(quiet, 600, start + RATE_WINDOW);
+        assert!(!session.try_send(quiet, start + RATE_WINDOW));
+        tracker.set_limits(BandwidthLimits { max_bytes_out_per_sec: 10_000, ..limits }).unwrap();
+        assert!(session.try_send(quiet, start + RATE_WINDOW));
+        assert!(tracker.set_limits(BandwidthLimits { max_strikes: 0, ..limits }).is_err());
+    }
+}
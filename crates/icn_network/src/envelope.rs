@@ -0,0 +1,160 @@
+// File: crates/icn_network/src/envelope.rs
+
+use crate::handshake::{Handshake, NodeIdentity};
+use crate::NetworkMessage;
+use icn_common::{IcnResult, IcnError};
+use ed25519_dalek::{PublicKey, Signature, Verifier};
+use serde::{Serialize, Deserialize};
+
+/// Invalid messages a peer may send before it is disconnected.
+pub const MAX_INVALID_MESSAGES: u32 = 3;
+
+const MESSAGE_SIGNING_DOMAIN: &str = "icn-message-v1";
+
+/// A message sent after the handshake, signed with the sender's node key.
+///
+/// Receivers check the signature against the key the peer presented in its
+/// handshake, so relayed transactions and blocks can't be forged or altered
+/// on the way. The signature also covers the session, the recipient and a
+/// sequence number, so a message can't be replayed on another connection,
+/// to another node or twice on the same one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedMessage {
+    pub message: NetworkMessage,
+    pub sequence: u64,
+    pub signature: Vec<u8>,
+}
+
+impl SignedMessage {
+    /// Signs a message for the peer at the other end of `session`, using
+    /// the session's next sequence number.
+    pub fn sign(message: NetworkMessage, identity: &NodeIdentity, session: &mut Session) -> IcnResult<Self> {
+        let sequence = session.next_sequence;
+        let signature = identity.sign(&signing_bytes(&message, &session.id, &session.peer_node_id, sequence)?);
+        session.next_sequence += 1;
+        Ok(SignedMessage { message, sequence, signature })
+    }
+
+    /// Checks the signature against the peer's handshake key and that the
+    /// message was sent to this node on this session after the last one
+    /// accepted.
+    pub fn verify(&self, public_key: &[u8], session: &mut Session) -> IcnResult<()> {
+        if session.last_received.is_some_and(|last| self.sequence <= last) {
+            return Err(IcnError::Network(format!("Message {} was already received on this session", self.sequence)));
+        }
+        let public_key = PublicKey::from_bytes(public_key)
+            .map_err(|e| IcnError::Network(format!("Invalid peer public key: {}", e)))?;
+        let signature = Signature::from_bytes(&self.signature)
+            .map_err(|e| IcnError::Network(format!("Invalid message signature: {}", e)))?;
+        public_key.verify(&signing_bytes(&self.message, &session.id, &session.local_node_id, self.sequence)?, &signature)
+            .map_err(|_| IcnError::Network("Message signature verification failed".into()))?;
+        session.last_received = Some(self.sequence);
+        Ok(())
+    }
+
+    pub fn into_message(self) -> NetworkMessage {
+        self.message
+    }
+}
+
+/// One connection between two nodes, identified by the nonces both sides
+/// put in their handshakes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    id: Vec<u8>,
+    local_node_id: String,
+    peer_node_id: String,
+    next_sequence: u64,
+    last_received: Option<u64>,
+}
+
+impl Session {
+    /// The session for a connection this node opened.
+    pub fn outbound(ours: &Handshake, theirs: &Handshake) -> Self {
+        Self::new([ours.nonce.as_slice(), theirs.nonce.as_slice()].concat(), ours, theirs)
+    }
+
+    /// The session for a connection a peer opened to this node.
+    pub fn inbound(ours: &Handshake, theirs: &Handshake) -> Self {
+        Self::new([theirs.nonce.as_slice(), ours.nonce.as_slice()].concat(), ours, theirs)
+    }
+
+    fn new(id: Vec<u8>, ours: &Handshake, theirs: &Handshake) -> Self {
+        Session {
+            id,
+            local_node_id: ours.node_id.clone(),
+            peer_node_id: theirs.node_id.clone(),
+            next_sequence: 0,
+            last_received: None,
+        }
+    }
+}
+
+fn signing_bytes(message: &NetworkMessage, session_id: &[u8], recipient: &str, sequence: u64) -> IcnResult<Vec<u8>> {
+    bincode::serialize(&(MESSAGE_SIGNING_DOMAIN, session_id, recipient, sequence, message))
+        .map_err(|e| IcnError::Network(format!("Failed to serialize message: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(identity: &NodeIdentity) -> Handshake {
+        Handshake::new(identity, "127.0.0.1:9000".parse().unwrap())
+    }
+
+    #[test]
+    fn test_signed_message_verification() {
+        let identity = NodeIdentity::generate("genesis".to_string());
+        let other = NodeIdentity::generate("genesis".to_string());
+        let (sender, receiver) = (handshake(&identity), handshake(&other));
+        let mut outbound = Session::outbound(&sender, &receiver);
+        let public_key = identity.public_key().to_bytes();
+        let message = NetworkMessage::PeerConnect("127.0.0.1:9000".parse().unwrap());
+
+        let signed = SignedMessage::sign(message, &identity, &mut outbound).unwrap();
+        assert!(signed.verify(&other.public_key().to_bytes(), &mut Session::inbound(&receiver, &sender)).is_err());
+
+        let mut tampered = signed.clone();
+        tampered.message = NetworkMessage::PeerConnect("127.0.0.1:9001".parse().unwrap());
+        assert!(tampered.verify(&public_key, &mut Session::inbound(&receiver, &sender)).is_err());
+
+        let mut unsigned = signed.clone();
+        unsigned.signature = Vec::new();
+        assert!(unsigned.verify(&public_key, &mut Session::inbound(&receiver, &sender)).is_err());
+
+        let mut renumbered = signed.clone();
+        renumbered.sequence += 1;
+        assert!(renumbered.verify(&public_key, &mut Session::inbound(&receiver, &sender)).is_err());
+
+        let mut inbound = Session::inbound(&receiver, &sender);
+        assert!(signed.verify(&public_key, &mut inbound).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_replayed_messages() {
+        let identity = NodeIdentity::generate("genesis".to_string());
+        let peer = NodeIdentity::generate("genesis".to_string());
+        let bystander = NodeIdentity::generate("genesis".to_string());
+        let public_key = identity.public_key().to_bytes();
+        let (sender, receiver) = (handshake(&identity), handshake(&peer));
+        let mut outbound = Session::outbound(&sender, &receiver);
+        let mut inbound = Session::inbound(&receiver, &sender);
+
+        let first = SignedMessage::sign(NetworkMessage::Goodbye, &identity, &mut outbound).unwrap();
+        let second = SignedMessage::sign(NetworkMessage::Goodbye, &identity, &mut outbound).unwrap();
+        assert!(first.verify(&public_key, &mut inbound).is_ok());
+        assert!(first.verify(&public_key, &mut inbound).is_err());
+        assert!(second.verify(&public_key, &mut inbound).is_ok());
+        assert!(first.verify(&public_key, &mut inbound).is_err());
+
+        // Not on a later session with the same peer
+        let reconnected = handshake(&identity);
+        assert!(first.verify(&public_key, &mut Session::inbound(&handshake(&peer), &reconnected)).is_err());
+
+        // Nor on a session with another node
+        let mut forwarded = Session::inbound(&handshake(&bystander), &sender);
+        forwarded.id = inbound.id.clone();
+        assert!(first.verify(&public_key, &mut forwarded).is_err());
+    }
+}
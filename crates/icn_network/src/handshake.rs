@@ -0,0 +1,231 @@
+// File: crates/icn_network/src/handshake.rs
+
+use icn_common::{IcnResult, IcnError};
+use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Serialize, Deserialize};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use chrono::Utc;
+
+/// Version of the peer-to-peer protocol spoken by this node.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Oldest protocol version this node can still talk to.
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Length of the random challenge each side puts in its handshake.
+pub const HANDSHAKE_NONCE_LEN: usize = 32;
+
+/// The first message exchanged on every connection, identifying the sender
+/// and the chain it follows.
+///
+/// Each side's handshake carries a fresh random nonce as a challenge. The
+/// other side proves it owns its key by signing that nonce together with
+/// both node ids (see `prove`), so a recorded handshake is useless on any
+/// other connection.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Handshake {
+    pub protocol_version: u32,
+    pub node_id: String,
+    pub genesis_hash: String,
+    pub listen_addr: SocketAddr,
+    pub public_key: Vec<u8>,
+    pub nonce: Vec<u8>,
+    /// When the handshake was made, in milliseconds, for estimating how
+    /// far the sender's clock is from ours.
+    #[serde(default)]
+    pub sent_at_ms: i64,
+}
+
+impl Handshake {
+    pub fn new(identity: &NodeIdentity, listen_addr: SocketAddr) -> Self {
+        let mut nonce = vec![0; HANDSHAKE_NONCE_LEN];
+        OsRng {}.fill_bytes(&mut nonce);
+        Handshake {
+            protocol_version: PROTOCOL_VERSION,
+            node_id: identity.node_id.clone(),
+            genesis_hash: identity.genesis_hash.clone(),
+            listen_addr,
+            public_key: identity.keypair.public.to_bytes().to_vec(),
+            nonce,
+            sent_at_ms: Utc::now().timestamp_millis(),
+        }
+    }
+
+    /// Checks that the peer speaks a compatible protocol, follows the same
+    /// chain and that its DID matches the key it presents. Whether it owns
+    /// that key is checked by `verify_proof`.
+    pub fn verify(&self, expected_genesis_hash: &str) -> IcnResult<()> {
+        if self.protocol_version < MIN_PROTOCOL_VERSION || self.protocol_version > PROTOCOL_VERSION {
+            return Err(IcnError::Network(format!("Incompatible protocol version {}", self.protocol_version)));
+        }
+
+        if self.genesis_hash != expected_genesis_hash {
+            return Err(IcnError::Network("Peer is on a different chain".into()));
+        }
+
+        if self.nonce.len() != HANDSHAKE_NONCE_LEN {
+            return Err(IcnError::Network("Handshake nonce has the wrong length".into()));
+        }
+
+        let public_key = PublicKey::from_bytes(&self.public_key)
+            .map_err(|e| IcnError::Network(format!("Invalid peer public key: {}", e)))?;
+        if self.node_id != did_from_public_key(&public_key) {
+            return Err(IcnError::Network("Peer node id does not match its public key".into()));
+        }
+        Ok(())
+    }
+
+    /// Answers the peer's challenge: signs its nonce and node id along with
+    /// this handshake, which must be `identity`'s own.
+    pub fn prove(&self, identity: &NodeIdentity, peer: &Handshake) -> Vec<u8> {
+        identity.sign(&self.challenge_bytes(peer))
+    }
+
+    /// Checks the peer's answer to the challenge in `ours`, the handshake
+    /// this node sent it.
+    pub fn verify_proof(&self, ours: &Handshake, proof: &[u8]) -> IcnResult<()> {
+        let public_key = PublicKey::from_bytes(&self.public_key)
+            .map_err(|e| IcnError::Network(format!("Invalid peer public key: {}", e)))?;
+        let signature = Signature::from_bytes(proof)
+            .map_err(|e| IcnError::Network(format!("Invalid handshake signature: {}", e)))?;
+        public_key.verify(&self.challenge_bytes(ours), &signature)
+            .map_err(|_| IcnError::Network("Handshake signature verification failed".into()))
+    }
+
+    fn challenge_bytes(&self, peer: &Handshake) -> Vec<u8> {
+        format!(
+            "icn-handshake|{}|{}|{}|{}|{}|{}|{}|{}|{}",
+            self.protocol_version,
+            self.node_id,
+            self.genesis_hash,
+            self.listen_addr,
+            hex::encode(&self.public_key),
+            hex::encode(&self.nonce),
+            self.sent_at_ms,
+            peer.node_id,
+            hex::encode(&peer.nonce)
+        ).into_bytes()
+    }
+}
+
+/// The key and chain this node presents to its peers.
+#[derive(Clone)]
+pub struct NodeIdentity {
+    pub node_id: String,
+    pub genesis_hash: String,
+    keypair: Arc<Keypair>,
+}
+
+impl NodeIdentity {
+    pub fn new(keypair: Keypair, genesis_hash: String) -> Self {
+        NodeIdentity {
+            node_id: did_from_public_key(&keypair.public),
+            genesis_hash,
+            keypair: Arc::new(keypair),
+        }
+    }
+
+    /// Creates an identity with a freshly generated key, for nodes that have
+    /// not been given a persistent one.
+    pub fn generate(genesis_hash: String) -> Self {
+        let mut csprng = OsRng {};
+        Self::new(Keypair::generate(&mut csprng), genesis_hash)
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.keypair.public
+    }
+
+    pub fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self.keypair.sign(message).to_bytes().to_vec()
+    }
+}
+
+pub fn did_from_public_key(public_key: &PublicKey) -> String {
+    format!("did:icn:{}", hex::encode(public_key.to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listen_addr() -> SocketAddr {
+        "127.0.0.1:9000".parse().unwrap()
+    }
+
+    #[test]
+    fn test_valid_handshake() {
+        let initiator = NodeIdentity::generate("genesis".to_string());
+        let responder = NodeIdentity::generate("genesis".to_string());
+        let hello = Handshake::new(&initiator, listen_addr());
+        let reply = Handshake::new(&responder, listen_addr());
+
+        assert_eq!(hello.node_id, initiator.node_id);
+        assert!(hello.verify("genesis").is_ok());
+        assert!(reply.verify("genesis").is_ok());
+        assert_ne!(hello.nonce, reply.nonce);
+
+        let proof = reply.prove(&responder, &hello);
+        assert!(reply.verify_proof(&hello, &proof).is_ok());
+        let proof = hello.prove(&initiator, &reply);
+        assert!(hello.verify_proof(&reply, &proof).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_other_chain_and_version() {
+        let identity = NodeIdentity::generate("genesis".to_string());
+        let handshake = Handshake::new(&identity, listen_addr());
+        assert!(handshake.verify("other_genesis").is_err());
+
+        let mut future = handshake.clone();
+        future.protocol_version = PROTOCOL_VERSION + 1;
+        assert!(future.verify("genesis").is_err());
+
+        let mut short_nonce = handshake;
+        short_nonce.nonce.truncate(8);
+        assert!(short_nonce.verify("genesis").is_err());
+    }
+
+    #[test]
+    fn test_rejects_tampered_identity() {
+        let identity = NodeIdentity::generate("genesis".to_string());
+        let other = NodeIdentity::generate("genesis".to_string());
+        let challenger = Handshake::new(&NodeIdentity::generate("genesis".to_string()), listen_addr());
+
+        let mut spoofed = Handshake::new(&identity, listen_addr());
+        spoofed.node_id = other.node_id.clone();
+        assert!(spoofed.verify("genesis").is_err());
+
+        // Presenting someone else's key passes the DID check, but the
+        // challenge can only be answered with the matching private key
+        let mut replaced_key = Handshake::new(&identity, listen_addr());
+        replaced_key.public_key = other.public_key().to_bytes().to_vec();
+        replaced_key.node_id = other.node_id.clone();
+        assert!(replaced_key.verify("genesis").is_ok());
+        let proof = replaced_key.prove(&identity, &challenger);
+        assert!(replaced_key.verify_proof(&challenger, &proof).is_err());
+    }
+
+    #[test]
+    fn test_proofs_cannot_be_replayed() {
+        let identity = NodeIdentity::generate("genesis".to_string());
+        let handshake = Handshake::new(&identity, listen_addr());
+        let challenger = NodeIdentity::generate("genesis".to_string());
+        let first = Handshake::new(&challenger, listen_addr());
+        let proof = handshake.prove(&identity, &first);
+        assert!(handshake.verify_proof(&first, &proof).is_ok());
+
+        // A new connection from the same peer carries a new nonce
+        let second = Handshake::new(&challenger, listen_addr());
+        assert!(handshake.verify_proof(&second, &proof).is_err());
+
+        // A proof made for one peer is no good to another that happens to
+        // send the same nonce
+        let mut relayed = Handshake::new(&NodeIdentity::generate("genesis".to_string()), listen_addr());
+        relayed.nonce = first.nonce.clone();
+        assert!(handshake.verify_proof(&relayed, &proof).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+// File: crates/icn_network/src/latency.rs
+
+use rand::seq::SliceRandom;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Peers a transaction is gossiped to when the manager isn't told otherwise.
+pub const DEFAULT_GOSSIP_FANOUT: usize = 8;
+
+/// Weight of the newest sample in a peer's smoothed round-trip time.
+const RTT_SMOOTHING: f64 = 0.25;
+
+/// Round-trip times measured to one peer, from handshakes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct PeerLatency {
+    pub smoothed_ms: f64,
+    pub last_ms: f64,
+    pub samples: u64,
+}
+
+/// How long broadcasts of one kind of message took to reach their peers.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PropagationStats {
+    pub broadcasts: u64,
+    /// Peers the messages were delivered to, across all broadcasts.
+    pub deliveries: u64,
+    pub failures: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+impl PropagationStats {
+    pub fn mean_ms(&self) -> f64 {
+        if self.broadcasts == 0 {
+            0.0
+        } else {
+            self.total_ms / self.broadcasts as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct LatencyInner {
+    peers: HashMap<SocketAddr, PeerLatency>,
+    propagation: BTreeMap<String, PropagationStats>,
+}
+
+/// Per-peer latency and broadcast propagation times, shared between the
+/// manager and the sessions it opens.
+#[derive(Clone, Default)]
+pub struct LatencyTracker {
+    inner: Arc<RwLock<LatencyInner>>,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl LatencyTracker {
+    pub fn record_rtt(&self, peer: SocketAddr, rtt: Duration) {
+        let sample = millis(rtt);
+        let mut inner = self.inner.write().unwrap();
+        inner.peers.entry(peer)
+            .and_modify(|latency| {
+                latency.smoothed_ms += RTT_SMOOTHING * (sample - latency.smoothed_ms);
+                latency.last_ms = sample;
+                latency.samples += 1;
+            })
+            .or_insert(PeerLatency { smoothed_ms: sample, last_ms: sample, samples: 1 });
+    }
+
+    pub fn get(&self, peer: &SocketAddr) -> Option<PeerLatency> {
+        self.inner.read().unwrap().peers.get(peer).copied()
+    }
+
+    /// Every measured peer, fastest first.
+    pub fn all(&self) -> Vec<(SocketAddr, PeerLatency)> {
+        let mut peers: Vec<_> = self.inner.read().unwrap().peers.iter().map(|(addr, latency)| (*addr, *latency)).collect();
+        peers.sort_by(|a, b| a.1.smoothed_ms.total_cmp(&b.1.smoothed_ms));
+        peers
+    }
+
+    pub fn forget(&self, peer: &SocketAddr) {
+        self.inner.write().unwrap().peers.remove(peer);
+    }
+
+    /// `peers` ordered fastest first. Peers not measured yet go last, in
+    /// their original order.
+    pub fn by_latency(&self, mut peers: Vec<SocketAddr>) -> Vec<SocketAddr> {
+        let inner = self.inner.read().unwrap();
+        peers.sort_by(|a, b| {
+            let rtt = |peer| inner.peers.get(peer).map_or(f64::INFINITY, |latency: &PeerLatency| latency.smoothed_ms);
+            rtt(a).total_cmp(&rtt(b))
+        });
+        peers
+    }
+
+    /// Up to `fanout` of `peers`, picked at random so gossip load spreads
+    /// across the network, then ordered fastest first.
+    pub fn gossip_targets(&self, peers: Vec<SocketAddr>, fanout: usize) -> Vec<SocketAddr> {
+        let targets = if peers.len() <= fanout {
+            peers
+        } else {
+            peers.choose_multiple(&mut rand::thread_rng(), fanout).copied().collect()
+        };
+        self.by_latency(targets)
+    }
+
+    /// Records a broadcast of `kind` that took `elapsed` to send to every
+    /// peer it targeted.
+    pub fn record_propagation(&self, kind: &str, elapsed: Duration, delivered: usize, failed: usize) {
+        let elapsed = millis(elapsed);
+        let mut inner = self.inner.write().unwrap();
+        let stats = inner.propagation.entry(kind.to_string()).or_default();
+        stats.broadcasts += 1;
+        stats.deliveries += delivered as u64;
+        stats.failures += failed as u64;
+        stats.total_ms += elapsed;
+        stats.max_ms = stats.max_ms.max(elapsed);
+    }
+
+    pub fn propagation(&self) -> BTreeMap<String, PropagationStats> {
+        self.inner.read().unwrap().propagation.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_ordering_and_fanout() {
+        let tracker = LatencyTracker::default();
+        let peers: Vec<SocketAddr> = (0..5).map(|i| format!("127.0.0.1:{}", 9000 + i).parse().unwrap()).collect();
+        tracker.record_rtt(peers[0], Duration::from_millis(80));
+        tracker.record_rtt(peers[1], Duration::from_millis(10));
+        tracker.record_rtt(peers[2], Duration::from_millis(40));
+
+        // Smoothing moves a quarter of the way towards the new sample
+        tracker.record_rtt(peers[0], Duration::from_millis(0));
+        let latency = tracker.get(&peers[0]).unwrap();
+        assert!((latency.smoothed_ms - 60.0).abs() < 1e-6);
+        assert_eq!((latency.last_ms, latency.samples), (0.0, 2));
+
+        assert_eq!(tracker.by_latency(peers.clone()), vec![peers[1], peers[2], peers[0], peers[3], peers[4]]);
+        assert_eq!(tracker.all()[0].0, peers[1]);
+
+        let targets = tracker.gossip_targets(peers.clone(), 3);
+        assert_eq!(targets.len(), 3);
+        assert!(targets.iter().all(|peer| peers.contains(peer)));
+        assert_eq!(tracker.gossip_targets(peers[..2].to_vec(), 3), vec![peers[1], peers[0]]);
+
+        tracker.forget(&peers[1]);
+        assert!(tracker.get(&peers[1]).is_none());
+
+        tracker.record_propagation("block", Duration::from_millis(30), 4, 0);
+        tracker.record_propagation("block", Duration::from_millis(10), 3, 1);
+        let stats = &tracker.propagation()["block"];
+        assert_eq!((stats.broadcasts, stats.deliveries, stats.failures), (2, 7, 1));
+        assert!((stats.mean_ms() - 20.0).abs() < 1e-6);
+        assert!((stats.max_ms - 30.0).abs() < 1e-6);
+    }
+}
@@ -1,49 +1,278 @@
-use icn_common::{IcnResult, IcnError, Transaction, NetworkStats};
+pub mod bandwidth;
+pub mod envelope;
+pub mod handshake;
+pub mod latency;
+pub mod mempool_sync;
+pub mod peer_book;
+pub mod reconnect;
+pub mod time_sync;
+
+use icn_common::{IcnResult, IcnError, SharedClock, Transaction, NetworkStats, PeerTraffic, Proposal, ProposalStatus, SignedBallot, SizeLimits, Vote, ConsensusProposal, ConsensusVote};
+#[cfg(feature = "fault_injection")]
+use icn_common::FaultInjector;
 use icn_blockchain::Block;
 use std::net::SocketAddr;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use log::{info, warn, error};
+use tracing::{debug, info, warn, error};
 use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use ed25519_dalek::Keypair;
+
+pub use crate::bandwidth::{BandwidthLimits, BandwidthTracker, RateDecision, RATE_WINDOW};
+pub use crate::envelope::{Session, SignedMessage, MAX_INVALID_MESSAGES};
+pub use crate::handshake::{Handshake, NodeIdentity, PROTOCOL_VERSION, MIN_PROTOCOL_VERSION};
+pub use crate::latency::{LatencyTracker, PeerLatency, PropagationStats, DEFAULT_GOSSIP_FANOUT};
+pub use crate::mempool_sync::{missing_from, SeenTransactions, MAX_INVENTORY_SIZE, MEMPOOL_SYNC_INTERVAL};
+pub use crate::peer_book::{PeerAddress, PeerBook, PeerRecord};
+pub use crate::reconnect::{
+    backoff_delay, PeerReconnector, PeerState, PeerStateChange, RECONNECT_BASE_DELAY, RECONNECT_CHECK_INTERVAL,
+    RECONNECT_JITTER, RECONNECT_MAX_DELAY,
+};
+pub use crate::time_sync::{check_clock_skew, clock_offset, ClockSample, NetworkClock, TimeSync, MAX_PEER_CLOCK_SKEW_MS, MIN_TIME_SYNC_PEERS};
+
+/// Largest frame accepted from a peer.
+pub const MAX_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
+
+/// How long to wait for each resolved address before trying the next one.
+pub const PEER_CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many peers may be connected at once unless configured otherwise.
+pub const DEFAULT_MAX_PEERS: usize = 50;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum NetworkMessage {
+    Handshake(Handshake),
+    /// The sender's signature answering the challenge in the other side's
+    /// handshake. See `Handshake::prove`.
+    HandshakeProof(Vec<u8>),
     Transaction(Transaction),
     Block(Block),
+    /// A new proposal with its proposer's signature over
+    /// `Proposal::signing_bytes`.
+    Proposal {
+        proposal: Proposal,
+        signature: Vec<u8>,
+    },
+    /// A vote cast on the sending node, signed by its voter. Receivers
+    /// derive the weight themselves.
+    Vote(Vote),
+    /// A ballot on a multi-option proposal cast on the sending node, signed
+    /// by its voter. Receivers derive the weight themselves.
+    Ballot(SignedBallot),
+    /// Sent when a node finalizes a proposal, with the votes it counted so
+    /// peers can merge any they missed.
+    ProposalFinalized {
+        proposal_id: String,
+        status: ProposalStatus,
+        votes: Vec<Vote>,
+    },
     PeerConnect(SocketAddr),
     PeerDisconnect(SocketAddr),
+    /// Hashes of the sender's pending transactions, sent on connect and
+    /// periodically. `from` is the address the sender listens on.
+    MempoolInventory { from: SocketAddr, hashes: Vec<String> },
+    /// Asks the peer at `from`'s end for the transactions with these hashes.
+    MempoolRequest { from: SocketAddr, hashes: Vec<String> },
+    /// Pending transactions sent in answer to a request.
+    MempoolTransactions(Vec<Transaction>),
+    /// Sent by a node that is shutting down, so its peers drop it right
+    /// away instead of waiting for the connection to fail.
+    Goodbye,
+    /// A round's proposer putting a block up for consensus.
+    ConsensusProposal { proposal: ConsensusProposal, block: Block },
+    /// A validator's prevote or precommit.
+    ConsensusVote(ConsensusVote),
 }
 
-struct PeerInfo {
-    last_seen: Instant,
+impl NetworkMessage {
+    /// Name the message's propagation times are recorded under.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NetworkMessage::Handshake(_) => "handshake",
+            NetworkMessage::HandshakeProof(_) => "handshake_proof",
+            NetworkMessage::Transaction(_) => "transaction",
+            NetworkMessage::Block(_) => "block",
+            NetworkMessage::Proposal { .. } => "proposal",
+            NetworkMessage::Vote(_) => "vote",
+            NetworkMessage::Ballot(_) => "ballot",
+            NetworkMessage::ProposalFinalized { .. } => "proposal_finalized",
+            NetworkMessage::PeerConnect(_) => "peer_connect",
+            NetworkMessage::PeerDisconnect(_) => "peer_disconnect",
+            NetworkMessage::MempoolInventory { .. } => "mempool_inventory",
+            NetworkMessage::MempoolRequest { .. } => "mempool_request",
+            NetworkMessage::MempoolTransactions(_) => "mempool_transactions",
+            NetworkMessage::Goodbye => "goodbye",
+            NetworkMessage::ConsensusProposal { .. } => "consensus_proposal",
+            NetworkMessage::ConsensusVote(_) => "consensus_vote",
+        }
+    }
 }
 
+/// What we know about a peer that completed the handshake.
+#[derive(Debug, Clone)]
+pub struct PeerInfo {
+    pub node_id: String,
+    /// Where the peer is reached: the address we dialed, or the one it
+    /// announced in its handshake.
+    pub addr: SocketAddr,
+    pub public_key: Vec<u8>,
+    pub protocol_version: u32,
+    pub last_seen: Instant,
+    /// Messages from this peer that failed signature or size checks.
+    pub invalid_messages: u32,
+}
+
+impl PeerInfo {
+    fn from_handshake(handshake: &Handshake, addr: SocketAddr) -> Self {
+        PeerInfo {
+            node_id: handshake.node_id.clone(),
+            addr,
+            public_key: handshake.public_key.clone(),
+            protocol_version: handshake.protocol_version,
+            last_seen: Instant::now(),
+            invalid_messages: 0,
+        }
+    }
+}
+
+/// Connected peers keyed by node id, which the handshake checks against the
+/// peer's key. Listen addresses are only announced, so they can't be used
+/// to take over another peer's entry.
+type PeerMap = Arc<RwLock<HashMap<String, PeerInfo>>>;
+type TaskList = Arc<Mutex<Vec<JoinHandle<()>>>>;
+
 pub struct NetworkManager {
     local_addr: SocketAddr,
-    peers: Arc<RwLock<HashMap<SocketAddr, PeerInfo>>>,
+    identity: NodeIdentity,
+    peers: PeerMap,
     event_sender: mpsc::Sender<NetworkMessage>,
-    event_receiver: mpsc::Receiver<NetworkMessage>,
+    event_receiver: Option<mpsc::Receiver<NetworkMessage>>,
     start_time: Option<Instant>,
+    peer_book: PeerBook,
+    limits: SizeLimits,
+    seen_transactions: SeenTransactions,
+    latency: LatencyTracker,
+    time_sync: TimeSync,
+    bandwidth: BandwidthTracker,
+    /// Shared with the listener so a lowered limit applies straight away.
+    max_peers: Arc<AtomicUsize>,
+    gossip_fanout: usize,
+    reconnector: PeerReconnector,
+    /// The listener and connection tasks, aborted on `stop`.
+    tasks: TaskList,
+    #[cfg(feature = "fault_injection")]
+    faults: FaultInjector,
 }
 
 impl NetworkManager {
+    /// Creates a manager with a freshly generated node key and an empty
+    /// genesis hash. Use `with_identity` to join a specific chain.
     pub fn new(local_addr: SocketAddr) -> Self {
         let (event_sender, event_receiver) = mpsc::channel(100); // Adjust buffer size as needed
         NetworkManager {
             local_addr,
+            identity: NodeIdentity::generate(String::new()),
             peers: Arc::new(RwLock::new(HashMap::new())),
             event_sender,
-            event_receiver,
+            event_receiver: Some(event_receiver),
             start_time: None,
+            peer_book: PeerBook::default(),
+            limits: SizeLimits::default(),
+            seen_transactions: SeenTransactions::default(),
+            latency: LatencyTracker::default(),
+            time_sync: TimeSync::default(),
+            bandwidth: BandwidthTracker::default(),
+            max_peers: Arc::new(AtomicUsize::new(DEFAULT_MAX_PEERS)),
+            gossip_fanout: DEFAULT_GOSSIP_FANOUT,
+            reconnector: PeerReconnector::default(),
+            tasks: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "fault_injection")]
+            faults: FaultInjector::default(),
         }
     }
 
+    /// Gossips each transaction to `fanout` random peers instead of the
+    /// default. Mempool sync delivers it to the rest.
+    pub fn with_gossip_fanout(mut self, fanout: usize) -> Self {
+        self.gossip_fanout = fanout.max(1);
+        self
+    }
+
+    /// Drops outgoing messages when the injector says to.
+    #[cfg(feature = "fault_injection")]
+    pub fn with_fault_injector(mut self, faults: FaultInjector) -> Self {
+        self.faults = faults;
+        self
+    }
+
+    /// Remembers the peers this node connects to in the given peer book,
+    /// so `reconnect_known_peers` can find them again after a restart.
+    pub fn set_peer_book(&mut self, peer_book: PeerBook) {
+        self.peer_book = peer_book;
+    }
+
+    pub fn peer_book(&self) -> &PeerBook {
+        &self.peer_book
+    }
+
+    /// Sets the key this node signs its handshakes with and the genesis hash
+    /// of the chain it follows.
+    pub fn with_identity(mut self, keypair: Keypair, genesis_hash: String) -> Self {
+        self.identity = NodeIdentity::new(keypair, genesis_hash);
+        self
+    }
+
+    /// Keeps the current node key but follows the chain with the given genesis hash.
+    pub fn with_genesis_hash(mut self, genesis_hash: String) -> Self {
+        self.identity.genesis_hash = genesis_hash;
+        self
+    }
+
+    /// Transactions and blocks from peers larger than these limits are
+    /// dropped instead of being handed to the node.
+    pub fn set_limits(&mut self, limits: SizeLimits) {
+        self.limits = limits;
+    }
+
+    /// Caps what each peer may send and be sent per second. Peers over
+    /// their inbound limits have messages dropped and are moved to the back
+    /// of broadcasts, and are disconnected if they keep it up.
+    /// Takes effect on connections already open as well as new ones.
+    pub fn set_bandwidth_limits(&mut self, limits: BandwidthLimits) -> IcnResult<()> {
+        self.bandwidth.set_limits(limits)
+    }
+
+    pub fn bandwidth_limits(&self) -> BandwidthLimits {
+        self.bandwidth.limits()
+    }
+
+    /// Caps how many peers may be connected at once. New peers past the
+    /// cap are refused; peers already connected when it is lowered stay.
+    pub fn set_max_peers(&mut self, max_peers: usize) -> IcnResult<()> {
+        if max_peers == 0 {
+            return Err(IcnError::Config("The peer limit must be greater than zero".into()));
+        }
+        self.max_peers.store(max_peers, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub fn max_peers(&self) -> usize {
+        self.max_peers.load(Ordering::Relaxed)
+    }
+
+    pub fn node_id(&self) -> &str {
+        &self.identity.node_id
+    }
+
     pub async fn start(&mut self) -> IcnResult<()> {
-        info!("Starting network on {}", self.local_addr);
+        info!("Starting network on {} as {}", self.local_addr, self.identity.node_id);
         self.start_time = Some(Instant::now());
 
         let listener = TcpListener::bind(self.local_addr).await
@@ -51,72 +280,236 @@ impl NetworkManager {
 
         let peers = Arc::clone(&self.peers);
         let event_sender = self.event_sender.clone();
-
-        tokio::spawn(async move {
+        let identity = self.identity.clone();
+        let local_addr = self.local_addr;
+        let limits = self.limits;
+        let seen = self.seen_transactions.clone();
+        let reconnector = self.reconnector.clone();
+        let bandwidth = self.bandwidth.clone();
+        let max_peers = Arc::clone(&self.max_peers);
+        let tasks = Arc::clone(&self.tasks);
+
+        let listener_task = tokio::spawn(async move {
             while let Ok((stream, addr)) = listener.accept().await {
                 let peer_tx = event_sender.clone();
                 let peer_peers = Arc::clone(&peers);
-                tokio::spawn(async move {
-                    if let Err(e) = handle_connection(stream, addr, peer_tx, peer_peers).await {
+                let peer_identity = identity.clone();
+                let peer_seen = seen.clone();
+                let peer_reconnector = reconnector.clone();
+                let peer_bandwidth = bandwidth.clone();
+                let peer_limit = max_peers.load(Ordering::Relaxed);
+                let connection = tokio::spawn(async move {
+                    if let Err(e) = handle_connection(stream, addr, local_addr, peer_identity, peer_tx, peer_peers, limits, peer_seen, peer_reconnector, peer_bandwidth, peer_limit).await {
                         error!("Error handling connection from {}: {}", addr, e);
                     }
                 });
+                track_task(&tasks, connection);
             }
         });
+        track_task(&self.tasks, listener_task);
 
         info!("Network started successfully");
         Ok(())
     }
 
+    /// Says goodbye to every peer, then stops listening and closes all
+    /// connections.
     pub async fn stop(&mut self) -> IcnResult<()> {
         info!("Stopping network");
+        let peer_addrs = self.get_connected_peers();
+        self.send_to_peers(peer_addrs.clone(), NetworkMessage::Goodbye).await?;
+
+        for task in self.tasks.lock().unwrap().drain(..) {
+            task.abort();
+        }
+        self.peers.write().unwrap().clear();
+        for peer_addr in &peer_addrs {
+            self.latency.forget(peer_addr);
+            self.time_sync.forget(peer_addr);
+            self.bandwidth.forget(peer_addr);
+        }
         self.start_time = None;
+        info!("Closed connections to {} peers", peer_addrs.len());
         Ok(())
     }
 
     pub fn get_connected_peers(&self) -> Vec<SocketAddr> {
-        self.peers.read().unwrap().keys().cloned().collect()
+        self.peers.read().unwrap().values().map(|info| info.addr).collect()
+    }
+
+    /// Returns the handshake details recorded for a connected peer.
+    pub fn get_peer_info(&self, peer_addr: &SocketAddr) -> Option<PeerInfo> {
+        self.peers.read().unwrap().values().find(|info| info.addr == *peer_addr).cloned()
     }
 
     pub fn get_uptime(&self) -> Duration {
         self.start_time.map_or(Duration::from_secs(0), |start| start.elapsed())
     }
 
-    pub async fn connect_to_peer(&mut self, peer_addr: SocketAddr) -> IcnResult<()> {
-        if self.peers.read().unwrap().contains_key(&peer_addr) {
+    pub async fn connect_to_peer(&self, peer_addr: SocketAddr) -> IcnResult<()> {
+        if self.get_peer_info(&peer_addr).is_some() {
             return Ok(());  // Already connected
         }
+        if self.reconnector.is_banned(&peer_addr, Instant::now()) {
+            return Err(IcnError::Network(format!("Peer {} is banned", peer_addr)));
+        }
+        let max_peers = self.max_peers();
+        if self.peers.read().unwrap().len() >= max_peers {
+            return Err(IcnError::Network(format!("Already connected to the limit of {} peers", max_peers)));
+        }
 
-        let stream = TcpStream::connect(peer_addr).await
-            .map_err(|e| IcnError::Network(format!("Failed to connect to peer {}: {}", peer_addr, e)))?;
+        let (stream, handshake, session, rtt, clock) = open_session(peer_addr, self.local_addr, &self.identity).await?;
+        self.latency.record_rtt(peer_addr, rtt);
+        self.time_sync.record(peer_addr, clock)?;
 
         let peers = Arc::clone(&self.peers);
         let event_sender = self.event_sender.clone();
+        let limits = self.limits;
+        let seen = self.seen_transactions.clone();
+        let bandwidth = self.bandwidth.clone();
+
+        let peer_handshake = handshake.clone();
 
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(stream, peer_addr, event_sender, peers).await {
+        let connection = tokio::spawn(async move {
+            if let Err(e) = read_messages(stream, peer_addr, &peer_handshake, session, event_sender, peers, limits, seen, bandwidth).await {
                 error!("Error handling connection to {}: {}", peer_addr, e);
             }
         });
+        track_task(&self.tasks, connection);
 
-        self.peers.write().unwrap().insert(peer_addr, PeerInfo { last_seen: Instant::now() });
+        self.peers.write().unwrap().insert(handshake.node_id.clone(), PeerInfo::from_handshake(&handshake, peer_addr));
+        self.reconnector.connected(peer_addr, &handshake.node_id);
         self.event_sender.send(NetworkMessage::PeerConnect(peer_addr)).await
             .map_err(|e| IcnError::Network(format!("Failed to send peer connected event: {}", e)))?;
 
-        info!("Connected to peer: {}", peer_addr);
+        info!("Connected to peer {} ({})", peer_addr, handshake.node_id);
         Ok(())
     }
 
+    /// Connects to a peer reachable at any of `addresses`, trying them in
+    /// order and every address each one resolves to. Returns the socket
+    /// address that worked and records the peer in the peer book.
+    pub async fn connect_to_addresses(&mut self, addresses: &[PeerAddress]) -> IcnResult<SocketAddr> {
+        let mut failures = Vec::new();
+        for address in addresses {
+            let resolved = match address.resolve().await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    failures.push(e.to_string());
+                    continue;
+                }
+            };
+
+            for peer_addr in resolved {
+                match tokio::time::timeout(PEER_CONNECT_TIMEOUT, self.connect_to_peer(peer_addr)).await {
+                    Ok(Ok(())) => {
+                        if let Some(info) = self.get_peer_info(&peer_addr) {
+                            self.peer_book.record_connection(&info.node_id, addresses, address, chrono::Utc::now().timestamp());
+                            if let Err(e) = self.peer_book.save() {
+                                warn!("Peer {} will not be remembered: {}", peer_addr, e);
+                            }
+                        }
+                        return Ok(peer_addr);
+                    }
+                    Ok(Err(e)) => failures.push(e.to_string()),
+                    Err(_) => failures.push(format!("Timed out connecting to {}", peer_addr)),
+                }
+            }
+        }
+
+        Err(IcnError::Network(format!("Could not reach peer: {}", failures.join("; "))))
+    }
+
+    /// Connects to every peer in the peer book. Returns how many were reached.
+    pub async fn reconnect_known_peers(&mut self) -> usize {
+        let known: Vec<PeerRecord> = self.peer_book.peers().to_vec();
+        let mut connected = 0;
+        for peer in known {
+            match self.connect_to_addresses(&peer.addresses).await {
+                Ok(_) => connected += 1,
+                Err(e) => warn!("Failed to reconnect to {}: {}", peer.node_id.as_deref().unwrap_or("unknown peer"), e),
+            }
+        }
+        info!("Reconnected to {} of {} known peers", connected, self.peer_book.peers().len());
+        connected
+    }
+
     pub async fn disconnect_from_peer(&mut self, peer_addr: &SocketAddr) -> IcnResult<()> {
-        self.peers.write().unwrap().remove(peer_addr);
+        remove_peers_at(&self.peers, peer_addr);
+        self.latency.forget(peer_addr);
+        self.time_sync.forget(peer_addr);
+        self.bandwidth.forget(peer_addr);
+        self.reconnector.forget(peer_addr);
         self.event_sender.send(NetworkMessage::PeerDisconnect(*peer_addr)).await
             .map_err(|e| IcnError::Network(format!("Failed to send peer disconnected event: {}", e)))?;
         Ok(())
     }
 
+    /// Schedules a peer that dropped its connection to be reconnected to.
+    /// Called for every `PeerDisconnect` event; peers dropped through
+    /// `disconnect_from_peer` are not reconnected.
+    pub fn peer_lost(&self, peer_addr: SocketAddr) {
+        self.latency.forget(&peer_addr);
+        self.time_sync.forget(&peer_addr);
+        self.bandwidth.forget(&peer_addr);
+        self.reconnector.lost(peer_addr, Instant::now());
+    }
+
+    /// Tries every lost peer whose backoff has run out, most reliable
+    /// first. Returns how many were reached.
+    pub async fn reconnect_due_peers(&self) -> usize {
+        let mut connected = 0;
+        for peer_addr in self.reconnector.due(Instant::now()) {
+            match tokio::time::timeout(PEER_CONNECT_TIMEOUT, self.connect_to_peer(peer_addr)).await {
+                Ok(Ok(())) => connected += 1,
+                Ok(Err(e)) => {
+                    debug!("Reconnect to {} failed: {}", peer_addr, e);
+                    self.reconnector.failed(peer_addr, Instant::now());
+                }
+                Err(_) => {
+                    debug!("Timed out reconnecting to {}", peer_addr);
+                    self.reconnector.failed(peer_addr, Instant::now());
+                }
+            }
+        }
+        connected
+    }
+
+    /// Drops a peer and refuses to connect to it, or accept it, until the
+    /// ban runs out.
+    pub async fn ban_peer(&self, peer_addr: SocketAddr, duration: Duration) -> IcnResult<()> {
+        self.reconnector.ban(peer_addr, duration, Instant::now());
+        if remove_peers_at(&self.peers, &peer_addr) {
+            self.latency.forget(&peer_addr);
+            self.time_sync.forget(&peer_addr);
+            self.bandwidth.forget(&peer_addr);
+            self.event_sender.send(NetworkMessage::PeerDisconnect(peer_addr)).await
+                .map_err(|e| IcnError::Network(format!("Failed to send peer disconnected event: {}", e)))?;
+        }
+        info!("Banned peer {} for {:?}", peer_addr, duration);
+        Ok(())
+    }
+
+    pub fn unban_peer(&self, peer_addr: &SocketAddr) -> bool {
+        self.reconnector.unban(peer_addr, Instant::now())
+    }
+
+    pub fn banned_peers(&self) -> Vec<(SocketAddr, Duration)> {
+        self.reconnector.bans(Instant::now())
+    }
+
+    /// Connection state and reliability tracking for known peers.
+    pub fn reconnector(&self) -> &PeerReconnector {
+        &self.reconnector
+    }
+
+    /// Gossips a transaction to a random subset of peers. Those it misses
+    /// learn of it from mempool inventories.
     pub async fn broadcast_transaction(&self, transaction: Transaction) -> IcnResult<()> {
-        let message = NetworkMessage::Transaction(transaction);
-        self.broadcast_message(message).await
+        // Peers relaying it back are then ignored
+        self.seen_transactions.insert(transaction.hash());
+        let targets = self.latency.gossip_targets(self.get_connected_peers(), self.gossip_fanout);
+        self.send_to_peers(targets, NetworkMessage::Transaction(transaction)).await
     }
 
     pub async fn broadcast_block(&self, block: Block) -> IcnResult<()> {
@@ -124,38 +517,148 @@ impl NetworkManager {
         self.broadcast_message(message).await
     }
 
+    pub async fn broadcast_proposal(&self, proposal: Proposal, signature: Vec<u8>) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::Proposal { proposal, signature }).await
+    }
+
+    pub async fn broadcast_vote(&self, vote: Vote) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::Vote(vote)).await
+    }
+
+    pub async fn broadcast_ballot(&self, ballot: SignedBallot) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::Ballot(ballot)).await
+    }
+
+    pub async fn broadcast_consensus_proposal(&self, proposal: ConsensusProposal, block: Block) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::ConsensusProposal { proposal, block }).await
+    }
+
+    pub async fn broadcast_consensus_vote(&self, vote: ConsensusVote) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::ConsensusVote(vote)).await
+    }
+
+    pub async fn broadcast_proposal_finalized(&self, proposal_id: String, status: ProposalStatus, votes: Vec<Vote>) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::ProposalFinalized { proposal_id, status, votes }).await
+    }
+
+    /// Sends the hashes of this node's pending transactions to one peer.
+    /// The peer answers with a request for those it does not have.
+    pub async fn send_mempool_inventory(&self, peer_addr: SocketAddr, hashes: Vec<String>) -> IcnResult<()> {
+        self.send_message_to_peer(peer_addr, NetworkMessage::MempoolInventory { from: self.local_addr, hashes }).await
+    }
+
+    /// Sends the mempool inventory to every connected peer.
+    pub async fn broadcast_mempool_inventory(&self, hashes: Vec<String>) -> IcnResult<()> {
+        self.broadcast_message(NetworkMessage::MempoolInventory { from: self.local_addr, hashes }).await
+    }
+
+    /// Asks a peer for the pending transactions with the given hashes.
+    pub async fn request_transactions(&self, peer_addr: SocketAddr, hashes: Vec<String>) -> IcnResult<()> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        self.send_message_to_peer(peer_addr, NetworkMessage::MempoolRequest { from: self.local_addr, hashes }).await
+    }
+
+    /// Answers a mempool request.
+    pub async fn send_transactions(&self, peer_addr: SocketAddr, transactions: Vec<Transaction>) -> IcnResult<()> {
+        if transactions.is_empty() {
+            return Ok(());
+        }
+        for transaction in &transactions {
+            self.seen_transactions.insert(transaction.hash());
+        }
+        self.send_message_to_peer(peer_addr, NetworkMessage::MempoolTransactions(transactions)).await
+    }
+
+    /// Round-trip times to connected peers, fastest first.
+    pub fn get_peer_latencies(&self) -> Vec<(SocketAddr, PeerLatency)> {
+        self.latency.all()
+    }
+
+    /// Each sampled peer's clock offset from ours, by peer address.
+    pub fn get_peer_clock_offsets(&self) -> Vec<(SocketAddr, ClockSample)> {
+        self.time_sync.peer_offsets()
+    }
+
+    /// A clock reading the time the network agrees on, for judging block
+    /// timestamps and deadlines. It follows the peers sampled later on.
+    pub fn network_clock(&self) -> SharedClock {
+        self.time_sync.clock()
+    }
+
+    /// Broadcast propagation times by message kind.
+    pub fn get_propagation_stats(&self) -> BTreeMap<String, PropagationStats> {
+        self.latency.propagation()
+    }
+
+    /// Sends to every peer, fastest first, so blocks and votes reach the
+    /// best-connected part of the network before the slow links. Peers
+    /// going over their rate limits come last whatever their latency.
     async fn broadcast_message(&self, message: NetworkMessage) -> IcnResult<()> {
-        let peers = self.peers.read().unwrap();
-        for peer_addr in peers.keys() {
-            if let Err(e) = self.send_message_to_peer(*peer_addr, message.clone()).await {
+        let peer_addrs = self.bandwidth.deprioritize(self.latency.by_latency(self.get_connected_peers()));
+        self.send_to_peers(peer_addrs, message).await
+    }
+
+    async fn send_to_peers(&self, peer_addrs: Vec<SocketAddr>, message: NetworkMessage) -> IcnResult<()> {
+        debug!("Broadcasting {} to {} peers", message.kind(), peer_addrs.len());
+        let started = Instant::now();
+        let mut failed = 0;
+        for &peer_addr in &peer_addrs {
+            if let Err(e) = self.send_message_to_peer(peer_addr, message.clone()).await {
                 warn!("Failed to send message to peer {}: {}", peer_addr, e);
+                failed += 1;
             }
         }
+        if !peer_addrs.is_empty() {
+            self.latency.record_propagation(message.kind(), started.elapsed(), peer_addrs.len() - failed, failed);
+        }
         Ok(())
     }
 
     async fn send_message_to_peer(&self, peer_addr: SocketAddr, message: NetworkMessage) -> IcnResult<()> {
-        let mut stream = TcpStream::connect(peer_addr).await
-            .map_err(|e| IcnError::Network(format!("Failed to connect to peer {}: {}", peer_addr, e)))?;
-
-        let serialized_message = bincode::serialize(&message)
-            .map_err(|e| IcnError::Network(format!("Failed to serialize message: {}", e)))?;
-
-        stream.write_all(&serialized_message).await
-            .map_err(|e| IcnError::Network(format!("Failed to send message to peer {}: {}", peer_addr, e)))?;
-
+        #[cfg(feature = "fault_injection")]
+        if self.faults.should_drop_message() {
+            warn!("Dropping message to {} (injected fault)", peer_addr);
+            return Ok(());
+        }
+        if !self.bandwidth.try_send(peer_addr, Instant::now()) {
+            return Err(IcnError::Network(format!("Send cap for peer {} is spent for this second", peer_addr)));
+        }
+        let (mut stream, _, mut session, rtt, clock) = open_session(peer_addr, self.local_addr, &self.identity).await?;
+        self.latency.record_rtt(peer_addr, rtt);
+        self.time_sync.record(peer_addr, clock)?;
+        let bytes = write_message(&mut stream, &SignedMessage::sign(message, &self.identity, &mut session)?).await?;
+        self.bandwidth.record_sent(peer_addr, bytes, Instant::now());
         Ok(())
     }
 
+    /// Waits for the next inbound message. Returns `None` once the receiver
+    /// has been taken with `take_event_receiver`.
     pub async fn receive_event(&mut self) -> Option<NetworkMessage> {
-        self.event_receiver.recv().await
+        self.event_receiver.as_mut()?.recv().await
+    }
+
+    /// Hands the inbound message stream to a consumer that runs without
+    /// holding on to the manager.
+    pub fn take_event_receiver(&mut self) -> Option<mpsc::Receiver<NetworkMessage>> {
+        self.event_receiver.take()
+    }
+
+    /// Traffic with one peer since it connected.
+    pub fn get_peer_traffic(&self, peer_addr: &SocketAddr) -> Option<PeerTraffic> {
+        self.bandwidth.peer(peer_addr)
     }
 
     pub async fn get_network_stats(&self) -> NetworkStats {
+        let peer_traffic = self.bandwidth.all();
         NetworkStats {
             node_count: self.peers.read().unwrap().len(),
             total_transactions: 0, // Implement tracking logic
             active_proposals: 0,   // Implement tracking logic
+            bytes_sent: peer_traffic.values().map(|traffic| traffic.bytes_sent).sum(),
+            bytes_received: peer_traffic.values().map(|traffic| traffic.bytes_received).sum(),
+            peer_traffic,
         }
     }
 }
@@ -167,43 +670,309 @@ pub struct CrossShardTransaction {
     pub to_shard: u64,
 }
 
+/// Connects to a peer and performs the outbound half of the handshake: both
+/// sides exchange handshakes, then each signs the other's challenge, this
+/// side first so the peer has registered it by the time this returns. The
+/// exchange doubles as a round-trip time measurement and a sample of the
+/// peer's clock.
+async fn open_session(
+    peer_addr: SocketAddr,
+    local_addr: SocketAddr,
+    identity: &NodeIdentity,
+) -> IcnResult<(TcpStream, Handshake, Session, Duration, ClockSample)> {
+    let mut stream = TcpStream::connect(peer_addr).await
+        .map_err(|e| IcnError::Network(format!("Failed to connect to peer {}: {}", peer_addr, e)))?;
+
+    let sent = Instant::now();
+    let ours = Handshake::new(identity, local_addr);
+    write_message(&mut stream, &NetworkMessage::Handshake(ours.clone())).await?;
+
+    let handshake = match read_message::<NetworkMessage>(&mut stream).await? {
+        Some(NetworkMessage::Handshake(handshake)) => handshake,
+        _ => return Err(IcnError::Network(format!("Peer {} did not complete the handshake", peer_addr))),
+    };
+    let rtt = sent.elapsed();
+    handshake.verify(&identity.genesis_hash)?;
+    let clock = clock_offset(handshake.sent_at_ms, ours.sent_at_ms, rtt);
+
+    write_message(&mut stream, &NetworkMessage::HandshakeProof(ours.prove(identity, &handshake))).await?;
+    match read_message::<NetworkMessage>(&mut stream).await? {
+        Some(NetworkMessage::HandshakeProof(proof)) => handshake.verify_proof(&ours, &proof)?,
+        _ => return Err(IcnError::Network(format!("Peer {} did not complete the handshake", peer_addr))),
+    }
+    let session = Session::outbound(&ours, &handshake);
+    Ok((stream, handshake, session, rtt, clock))
+}
+
+/// Handles an inbound connection. The first frame must be a valid handshake
+/// and the second the peer's answer to this node's challenge; anything else
+/// drops the connection before any message is processed.
+#[allow(clippy::too_many_arguments)]
 async fn handle_connection(
     mut stream: TcpStream,
     addr: SocketAddr,
+    local_addr: SocketAddr,
+    identity: NodeIdentity,
     event_sender: mpsc::Sender<NetworkMessage>,
-    peers: Arc<RwLock<HashMap<SocketAddr, PeerInfo>>>,
+    peers: PeerMap,
+    limits: SizeLimits,
+    seen: SeenTransactions,
+    reconnector: PeerReconnector,
+    bandwidth: BandwidthTracker,
+    max_peers: usize,
 ) -> IcnResult<()> {
-    let (mut reader, mut writer) = stream.split();
-    let mut buffer = vec![0; 1024]; // Use a fixed-size buffer
+    let handshake = match read_message::<NetworkMessage>(&mut stream).await? {
+        Some(NetworkMessage::Handshake(handshake)) => handshake,
+        _ => return Err(IcnError::Network(format!("Connection from {} did not start with a handshake", addr))),
+    };
+    let verified = handshake.verify(&identity.genesis_hash)
+        .and_then(|_| check_clock_skew(handshake.sent_at_ms - chrono::Utc::now().timestamp_millis()));
+    if let Err(e) = verified {
+        warn!("Rejected peer {} ({}): {}", addr, handshake.node_id, e);
+        return Err(e);
+    }
+    let peer_addr = handshake.listen_addr;
+    if reconnector.is_banned(&peer_addr, Instant::now()) {
+        return Err(IcnError::Network(format!("Refused banned peer {}", peer_addr)));
+    }
+    {
+        let peers = peers.read().unwrap();
+        if peers.values().any(|info| info.addr == peer_addr && info.node_id != handshake.node_id) {
+            return Err(IcnError::Network(format!("Refused peer {}: {} belongs to another connected peer", handshake.node_id, peer_addr)));
+        }
+        if !peers.contains_key(&handshake.node_id) && peers.len() >= max_peers {
+            return Err(IcnError::Network(format!("Refused peer {}: already connected to the limit of {} peers", peer_addr, max_peers)));
+        }
+    }
 
-    loop {
-        let bytes_read = reader.read(&mut buffer).await
-            .map_err(|e| IcnError::Network(format!("Failed to read from stream: {}", e)))?;
+    let ours = Handshake::new(&identity, local_addr);
+    write_message(&mut stream, &NetworkMessage::Handshake(ours.clone())).await?;
+    let proven = match read_message::<NetworkMessage>(&mut stream).await? {
+        Some(NetworkMessage::HandshakeProof(proof)) => handshake.verify_proof(&ours, &proof),
+        _ => Err(IcnError::Network(format!("Peer {} did not answer the handshake challenge", addr))),
+    };
+    if let Err(e) = proven {
+        warn!("Rejected peer {} ({}): {}", addr, handshake.node_id, e);
+        return Err(e);
+    }
+    write_message(&mut stream, &NetworkMessage::HandshakeProof(ours.prove(&identity, &handshake))).await?;
+    let mut session = Session::inbound(&ours, &handshake);
+
+    // Only the connection that first registers a peer owns its entry, so
+    // short-lived connections used to deliver a single message don't
+    // unregister it when they close.
+    let registered = {
+        let mut peers = peers.write().unwrap();
+        match peers.get_mut(&handshake.node_id) {
+            Some(info) => {
+                info.last_seen = Instant::now();
+                false
+            }
+            None => {
+                peers.insert(handshake.node_id.clone(), PeerInfo::from_handshake(&handshake, peer_addr));
+                true
+            }
+        }
+    };
+
+    if registered {
+        info!("Accepted peer {} ({})", peer_addr, handshake.node_id);
+        reconnector.connected(peer_addr, &handshake.node_id);
+        read_messages(stream, peer_addr, &handshake, session, event_sender, peers, limits, seen, bandwidth).await
+    } else {
+        while let Some((signed, bytes)) = read_frame::<SignedMessage>(&mut stream).await? {
+            if !admit_message(&bandwidth, peer_addr, &handshake.node_id, bytes, &peers)? {
+                continue;
+            }
+            match accept_message(signed, &handshake, &mut session, peer_addr, &peers, &limits)? {
+                Some(NetworkMessage::Goodbye) => return remove_peer(&handshake.node_id, peer_addr, &peers, &event_sender).await,
+                Some(message) => forward_message(message, &event_sender, &seen).await?,
+                None => {}
+            }
+        }
+        Ok(())
+    }
+}
 
-        if bytes_read == 0 {
-            // Connection closed
-            peers.write().unwrap().remove(&addr);
-            event_sender.send(NetworkMessage::PeerDisconnect(addr)).await
-                .map_err(|e| IcnError::Network(format!("Failed to send peer disconnected event: {}", e)))?;
-            break;
+/// Reads messages from an established session until the peer disconnects.
+#[allow(clippy::too_many_arguments)]
+async fn read_messages(
+    mut stream: TcpStream,
+    addr: SocketAddr,
+    handshake: &Handshake,
+    mut session: Session,
+    event_sender: mpsc::Sender<NetworkMessage>,
+    peers: PeerMap,
+    limits: SizeLimits,
+    seen: SeenTransactions,
+    bandwidth: BandwidthTracker,
+) -> IcnResult<()> {
+    while let Some((signed, bytes)) = read_frame::<SignedMessage>(&mut stream).await? {
+        if let Some(info) = peers.write().unwrap().get_mut(&handshake.node_id) {
+            info.last_seen = Instant::now();
+        }
+        if !admit_message(&bandwidth, addr, &handshake.node_id, bytes, &peers)? {
+            continue;
+        }
+        match accept_message(signed, handshake, &mut session, addr, &peers, &limits)? {
+            Some(NetworkMessage::Goodbye) => break,
+            Some(message) => forward_message(message, &event_sender, &seen).await?,
+            None => {}
+        }
+    }
+
+    // Connection closed
+    remove_peer(&handshake.node_id, addr, &peers, &event_sender).await
+}
+
+/// Forgets a peer that left, reporting the disconnect unless a goodbye
+/// already did. An entry the peer has since re-registered from another
+/// address is left alone.
+async fn remove_peer(node_id: &str, addr: SocketAddr, peers: &PeerMap, event_sender: &mpsc::Sender<NetworkMessage>) -> IcnResult<()> {
+    {
+        let mut peers = peers.write().unwrap();
+        if peers.get(node_id).is_none_or(|info| info.addr != addr) {
+            return Ok(());
         }
+        peers.remove(node_id);
+    }
+    info!("Peer {} disconnected", addr);
+    event_sender.send(NetworkMessage::PeerDisconnect(addr)).await
+        .map_err(|e| IcnError::Network(format!("Failed to send peer disconnected event: {}", e)))
+}
 
-        let message: NetworkMessage = bincode::deserialize(&buffer[..bytes_read])
-            .map_err(|e| IcnError::Network(format!("Failed to deserialize message: {}", e)))?;
+/// Drops whichever peers are connected at `addr`. Returns whether any were.
+fn remove_peers_at(peers: &PeerMap, addr: &SocketAddr) -> bool {
+    let mut peers = peers.write().unwrap();
+    let before = peers.len();
+    peers.retain(|_, info| info.addr != *addr);
+    peers.len() < before
+}
 
-        event_sender.send(message).await
-            .map_err(|e| IcnError::Network(format!("Failed to send message to main thread: {}", e)))?;
+/// Keeps a task so `stop` can abort it, dropping those that have ended.
+fn track_task(tasks: &TaskList, task: JoinHandle<()>) {
+    let mut tasks = tasks.lock().unwrap();
+    tasks.retain(|task| !task.is_finished());
+    tasks.push(task);
+}
 
-        buffer.clear();
+/// Counts a message against the peer's rate limits. Returns whether to
+/// process it, or fails once the peer has been over its limits too long.
+fn admit_message(bandwidth: &BandwidthTracker, addr: SocketAddr, node_id: &str, bytes: usize, peers: &PeerMap) -> IcnResult<bool> {
+    match bandwidth.record_received(addr, bytes, Instant::now()) {
+        RateDecision::Accept => Ok(true),
+        RateDecision::Drop => {
+            debug!("Dropped message from peer {} over its rate limits", addr);
+            Ok(false)
+        }
+        RateDecision::Disconnect => {
+            peers.write().unwrap().remove(node_id);
+            Err(IcnError::Network(format!("Disconnecting peer {} for staying over its rate limits", addr)))
+        }
     }
+}
 
-    Ok(())
+/// Verifies a message from a peer. Messages that fail are dropped and count
+/// against the peer, which is disconnected once it sends too many.
+fn accept_message(
+    signed: SignedMessage,
+    handshake: &Handshake,
+    session: &mut Session,
+    addr: SocketAddr,
+    peers: &PeerMap,
+    limits: &SizeLimits,
+) -> IcnResult<Option<NetworkMessage>> {
+    let checked = signed.verify(&handshake.public_key, session)
+        .and_then(|_| check_message_size(&signed.message, limits))
+        .and_then(|_| mempool_sync::check_sync_message(&signed.message, addr));
+    match checked {
+        Ok(()) => Ok(Some(signed.into_message())),
+        Err(e) => {
+            warn!("Dropped message from peer {}: {}", addr, e);
+            let mut peers = peers.write().unwrap();
+            let invalid_messages = peers.get_mut(&handshake.node_id).map_or(1, |info| {
+                info.invalid_messages += 1;
+                info.invalid_messages
+            });
+            if invalid_messages >= MAX_INVALID_MESSAGES {
+                peers.remove(&handshake.node_id);
+                return Err(IcnError::Network(format!("Disconnecting peer {} after {} invalid messages", addr, invalid_messages)));
+            }
+            Ok(None)
+        }
+    }
+}
+
+async fn forward_message(message: NetworkMessage, event_sender: &mpsc::Sender<NetworkMessage>, seen: &SeenTransactions) -> IcnResult<()> {
+    if let NetworkMessage::Handshake(_) | NetworkMessage::HandshakeProof(_) = message {
+        return Err(IcnError::Network("Unexpected handshake on established session".into()));
+    }
+    let message = match seen.filter(message) {
+        Some(message) => message,
+        None => {
+            debug!("Dropped transaction the node has already seen");
+            return Ok(());
+        }
+    };
+    event_sender.send(message).await
+        .map_err(|e| IcnError::Network(format!("Failed to send message to main thread: {}", e)))
+}
+
+fn check_message_size(message: &NetworkMessage, limits: &SizeLimits) -> IcnResult<()> {
+    match message {
+        NetworkMessage::Transaction(transaction) => limits.check_transaction(transaction).map(|_| ()),
+        NetworkMessage::Block(block) | NetworkMessage::ConsensusProposal { block, .. } => limits.check_block(block, block.transactions.len()),
+        NetworkMessage::MempoolTransactions(transactions) => transactions.iter()
+            .try_for_each(|transaction| limits.check_transaction(transaction).map(|_| ())),
+        _ => Ok(()),
+    }
+}
+
+/// Writes a length-prefixed bincode frame, returning its size on the wire.
+async fn write_message<T: Serialize>(stream: &mut TcpStream, message: &T) -> IcnResult<usize> {
+    let serialized_message = bincode::serialize(message)
+        .map_err(|e| IcnError::Network(format!("Failed to serialize message: {}", e)))?;
+    if serialized_message.len() > MAX_MESSAGE_SIZE as usize {
+        return Err(IcnError::Network("Message exceeds maximum size".into()));
+    }
+
+    stream.write_u32(serialized_message.len() as u32).await
+        .map_err(|e| IcnError::Network(format!("Failed to write to stream: {}", e)))?;
+    stream.write_all(&serialized_message).await
+        .map_err(|e| IcnError::Network(format!("Failed to write to stream: {}", e)))?;
+    Ok(serialized_message.len() + 4)
+}
+
+/// Reads a length-prefixed bincode frame, returning `None` once the peer closes the connection.
+async fn read_message<T: DeserializeOwned>(stream: &mut TcpStream) -> IcnResult<Option<T>> {
+    Ok(read_frame(stream).await?.map(|(message, _)| message))
+}
+
+/// Reads a frame like `read_message`, along with its size on the wire.
+async fn read_frame<T: DeserializeOwned>(stream: &mut TcpStream) -> IcnResult<Option<(T, usize)>> {
+    let length = match stream.read_u32().await {
+        Ok(length) => length,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(IcnError::Network(format!("Failed to read from stream: {}", e))),
+    };
+    if length > MAX_MESSAGE_SIZE {
+        return Err(IcnError::Network(format!("Peer sent oversized frame of {} bytes", length)));
+    }
+
+    let mut buffer = vec![0; length as usize];
+    stream.read_exact(&mut buffer).await
+        .map_err(|e| IcnError::Network(format!("Failed to read from stream: {}", e)))?;
+
+    bincode::deserialize(&buffer)
+        .map(|message| Some((message, length as usize + 4)))
+        .map_err(|e| IcnError::Network(format!("Failed to deserialize message: {}", e)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use tokio::runtime::Runtime;
+    use icn_common::{Amount, SignatureScheme};
 
     #[test]
     fn test_network_operations() {
@@ -226,10 +995,14 @@ mod tests {
             let transaction = Transaction {
                 from: "Alice".to_string(),
                 to: "Bob".to_string(),
-                amount: 100.0,
+                amount: Amount::from(100.0),
                 currency_type: icn_common::CurrencyType::BasicNeeds,
                 timestamp: chrono::Utc::now().timestamp(),
                 signature: None,
+                memo: None,
+                signature_scheme: SignatureScheme::Ed25519,
+                valid_until: None,
+                capability: None,
             };
 
             manager1.broadcast_transaction(transaction.clone()).await.unwrap();
@@ -243,6 +1016,16 @@ mod tests {
                 panic!("Did not receive expected transaction");
             }
 
+            let stats = manager1.get_network_stats().await;
+            assert!(stats.bytes_sent > 0);
+            assert_eq!(stats.peer_traffic["127.0.0.1:8001"].messages_sent, 1);
+            assert!(manager1.set_bandwidth_limits(BandwidthLimits { max_strikes: 0, ..BandwidthLimits::default() }).is_err());
+
+            // At the peer limit, new peers are refused before dialing
+            assert!(manager1.set_max_peers(0).is_err());
+            manager1.set_max_peers(1).unwrap();
+            assert!(manager1.connect_to_peer("127.0.0.1:8002".parse().unwrap()).await.is_err());
+
             assert!(manager1.stop().await.is_ok());
         });
     }
@@ -276,10 +1059,14 @@ mod tests {
             let transaction = Transaction {
                 from: "Charlie".to_string(),
                 to: "David".to_string(),
-                amount: 200.0,
+                amount: Amount::from(200.0),
                 currency_type: icn_common::CurrencyType::Education,
                 timestamp: chrono::Utc::now().timestamp(),
                 signature: None,
+                memo: None,
+                signature_scheme: SignatureScheme::Ed25519,
+                valid_until: None,
+                capability: None,
             };
 
             manager1.broadcast_transaction(transaction.clone()).await.unwrap();
@@ -330,4 +1117,239 @@ mod tests {
             assert!(matches!(received, Some(NetworkMessage::PeerDisconnect(_))));
         });
     }
+
+    #[test]
+    fn test_stop_says_goodbye() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8014".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8015".parse().unwrap();
+
+            let mut manager1 = NetworkManager::new(addr1);
+            let mut manager2 = NetworkManager::new(addr2);
+
+            manager1.start().await.unwrap();
+            manager2.start().await.unwrap();
+
+            manager1.connect_to_peer(addr2).await.unwrap();
+            assert!(matches!(manager1.receive_event().await, Some(NetworkMessage::PeerConnect(_))));
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            assert_eq!(manager2.get_connected_peers(), vec![addr1]);
+
+            manager1.stop().await.unwrap();
+            assert!(manager1.get_connected_peers().is_empty());
+
+            // The goodbye arrives before the closed connection is noticed,
+            // and the peer is only reported gone once
+            let received = tokio::time::timeout(std::time::Duration::from_secs(1), manager2.receive_event()).await.unwrap();
+            assert!(matches!(received, Some(NetworkMessage::PeerDisconnect(addr)) if addr == addr1));
+            assert!(manager2.get_connected_peers().is_empty());
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            assert!(tokio::time::timeout(std::time::Duration::from_millis(100), manager2.receive_event()).await.is_err());
+
+            // Nothing listens on the stopped node any more
+            assert!(manager2.connect_to_peer(addr1).await.is_err());
+        });
+    }
+
+    #[test]
+    fn test_lost_peer_reconnected_after_backoff() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8016".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8017".parse().unwrap();
+
+            let mut manager1 = NetworkManager::new(addr1);
+            let mut manager2 = NetworkManager::new(addr2);
+            manager1.start().await.unwrap();
+            manager2.start().await.unwrap();
+            manager1.connect_to_peer(addr2).await.unwrap();
+            assert!(matches!(manager1.receive_event().await, Some(NetworkMessage::PeerConnect(_))));
+
+            manager2.stop().await.unwrap();
+            let received = tokio::time::timeout(std::time::Duration::from_secs(1), manager1.receive_event()).await.unwrap();
+            assert!(matches!(received, Some(NetworkMessage::PeerDisconnect(addr)) if addr == addr2));
+            manager1.peer_lost(addr2);
+            assert!(matches!(manager1.reconnector().state(&addr2), Some(PeerState::Disconnected { .. })));
+            assert_eq!(manager1.reconnect_due_peers().await, 0);
+
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            manager2.start().await.unwrap();
+            tokio::time::sleep(backoff_delay(0, 1.0)).await;
+            assert_eq!(manager1.reconnect_due_peers().await, 1);
+            assert_eq!(manager1.get_connected_peers(), vec![addr2]);
+
+            // A banned peer is dropped and not connected to again
+            manager1.ban_peer(addr2, Duration::from_secs(60)).await.unwrap();
+            assert!(manager1.get_connected_peers().is_empty());
+            assert!(manager1.connect_to_peer(addr2).await.is_err());
+            assert_eq!(manager1.banned_peers().len(), 1);
+            assert!(manager1.unban_peer(&addr2));
+            assert_eq!(manager1.reconnect_due_peers().await, 1);
+        });
+    }
+
+    #[test]
+    fn test_rejects_peer_on_other_chain() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8007".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8008".parse().unwrap();
+
+            let mut manager1 = NetworkManager::new(addr1).with_genesis_hash("chain_a".to_string());
+            let mut manager2 = NetworkManager::new(addr2).with_genesis_hash("chain_b".to_string());
+
+            manager1.start().await.unwrap();
+            manager2.start().await.unwrap();
+
+            assert!(manager1.connect_to_peer(addr2).await.is_err());
+
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+            assert!(manager1.get_connected_peers().is_empty());
+            assert!(manager2.get_connected_peers().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_connect_by_hostname_with_fallback() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8011".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8012".parse().unwrap();
+
+            let mut manager1 = NetworkManager::new(addr1).with_genesis_hash("chain".to_string());
+            let mut manager2 = NetworkManager::new(addr2).with_genesis_hash("chain".to_string());
+            manager1.start().await.unwrap();
+            manager2.start().await.unwrap();
+
+            // Nothing listens on the first address, so the hostname is used
+            let addresses = vec![
+                "127.0.0.1:8013".parse().unwrap(),
+                "localhost:8012".parse().unwrap(),
+            ];
+            assert_eq!(manager1.connect_to_addresses(&addresses).await.unwrap().port(), 8012);
+
+            let record = &manager1.peer_book().peers()[0];
+            assert_eq!(record.node_id.as_deref(), Some(manager2.node_id()));
+            assert_eq!(record.addresses[0], addresses[1]);
+        });
+    }
+
+    #[test]
+    fn test_peer_identity_recorded() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8009".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8010".parse().unwrap();
+
+            let mut manager1 = NetworkManager::new(addr1).with_genesis_hash("chain".to_string());
+            let mut manager2 = NetworkManager::new(addr2).with_genesis_hash("chain".to_string());
+
+            manager1.start().await.unwrap();
+            manager2.start().await.unwrap();
+
+            manager1.connect_to_peer(addr2).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+            let info = manager1.get_peer_info(&addr2).unwrap();
+            assert_eq!(info.node_id, manager2.node_id());
+            assert_eq!(info.protocol_version, PROTOCOL_VERSION);
+
+            let info = manager2.get_peer_info(&addr1).unwrap();
+            assert_eq!(info.node_id, manager1.node_id());
+        });
+    }
+
+    #[test]
+    fn test_replayed_handshake_is_refused() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8020".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8021".parse().unwrap();
+
+            let mut manager = NetworkManager::new(addr2).with_genesis_hash("chain".to_string());
+            manager.start().await.unwrap();
+
+            // A recorded handshake and proof from a real node don't answer
+            // the challenge of a new connection
+            let victim = NodeIdentity::generate("chain".to_string());
+            let recorded = Handshake::new(&victim, addr1);
+            let old_challenge = Handshake::new(&manager.identity, addr2);
+            let recorded_proof = recorded.prove(&victim, &old_challenge);
+
+            let mut stream = TcpStream::connect(addr2).await.unwrap();
+            write_message(&mut stream, &NetworkMessage::Handshake(recorded)).await.unwrap();
+            assert!(matches!(read_message::<NetworkMessage>(&mut stream).await.unwrap(), Some(NetworkMessage::Handshake(_))));
+            write_message(&mut stream, &NetworkMessage::HandshakeProof(recorded_proof)).await.unwrap();
+
+            // The connection is dropped without the node proving itself
+            assert!(read_message::<NetworkMessage>(&mut stream).await.map_or(true, |message| message.is_none()));
+            assert!(manager.get_connected_peers().is_empty());
+        });
+    }
+
+    #[test]
+    fn test_peer_cannot_claim_another_peers_address() {
+        let rt = Runtime::new().unwrap();
+        rt.block_on(async {
+            let addr1: SocketAddr = "127.0.0.1:8018".parse().unwrap();
+            let addr2: SocketAddr = "127.0.0.1:8019".parse().unwrap();
+
+            let mut manager1 = NetworkManager::new(addr1).with_genesis_hash("chain".to_string());
+            let mut manager2 = NetworkManager::new(addr2).with_genesis_hash("chain".to_string());
+            manager1.start().await.unwrap();
+            manager2.start().await.unwrap();
+            manager1.connect_to_peer(addr2).await.unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+            // Another key announcing manager1's address is turned away and
+            // manager1 keeps its entry
+            let impostor = NodeIdentity::generate("chain".to_string());
+            assert!(open_session(addr2, addr1, &impostor).await.is_err());
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            assert_eq!(manager2.get_connected_peers(), vec![addr1]);
+            assert_eq!(manager2.get_peer_info(&addr1).unwrap().node_id, manager1.node_id());
+        });
+    }
+
+    #[test]
+    fn test_invalid_messages_dropped() {
+        let identity = NodeIdentity::generate(String::new());
+        let addr: SocketAddr = "127.0.0.1:9100".parse().unwrap();
+        let handshake = Handshake::new(&identity, addr);
+        let local = Handshake::new(&NodeIdentity::generate(String::new()), addr);
+        let mut outbound = Session::outbound(&handshake, &local);
+        let mut session = Session::inbound(&local, &handshake);
+        let peers: PeerMap = Arc::new(RwLock::new(HashMap::new()));
+        peers.write().unwrap().insert(identity.node_id.clone(), PeerInfo::from_handshake(&handshake, addr));
+        let limits = SizeLimits { max_transaction_bytes: 256, ..SizeLimits::default() };
+        let transaction = |to: String| Transaction {
+            from: "Alice".to_string(),
+            to,
+            amount: Amount::from(1.0),
+            currency_type: icn_common::CurrencyType::BasicNeeds,
+            timestamp: chrono::Utc::now().timestamp(),
+            signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
+        };
+
+        let valid = SignedMessage::sign(NetworkMessage::Transaction(transaction("Bob".to_string())), &identity, &mut outbound).unwrap();
+        assert!(accept_message(valid.clone(), &handshake, &mut session, addr, &peers, &limits).unwrap().is_some());
+
+        let oversized = SignedMessage::sign(NetworkMessage::Transaction(transaction("x".repeat(300))), &identity, &mut outbound).unwrap();
+        assert!(accept_message(oversized, &handshake, &mut session, addr, &peers, &limits).unwrap().is_none());
+
+        let mut forged = SignedMessage::sign(NetworkMessage::Transaction(transaction("Bob".to_string())), &identity, &mut outbound).unwrap();
+        forged.message = NetworkMessage::Transaction(transaction("Mallory".to_string()));
+        assert!(accept_message(forged, &handshake, &mut session, addr, &peers, &limits).unwrap().is_none());
+        assert_eq!(peers.read().unwrap()[&identity.node_id].invalid_messages, 2);
+
+        // The third invalid message, a replay, disconnects the peer
+        assert!(accept_message(valid, &handshake, &mut session, addr, &peers, &limits).is_err());
+        assert!(!peers.read().unwrap().contains_key(&identity.node_id));
+    }
 }
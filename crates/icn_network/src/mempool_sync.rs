@@ -0,0 +1,141 @@
+// File: crates/icn_network/src/mempool_sync.rs
+
+use crate::NetworkMessage;
+use icn_common::{IcnResult, IcnError, Transaction};
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Most transaction hashes one inventory or request may list.
+pub const MAX_INVENTORY_SIZE: usize = 10_000;
+
+/// How often nodes send their mempool inventory to every peer, so pending
+/// sets converge even when a relayed transaction was missed.
+pub const MEMPOOL_SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Transaction hashes remembered for duplicate suppression.
+const SEEN_CAPACITY: usize = 50_000;
+
+/// Hashes of transactions this node has already sent or received, so the
+/// same transaction arriving from several peers is handed to the node once.
+/// The oldest hashes are forgotten once the cache is full.
+#[derive(Clone, Default)]
+pub struct SeenTransactions {
+    inner: Arc<RwLock<SeenInner>>,
+}
+
+#[derive(Default)]
+struct SeenInner {
+    hashes: HashSet<String>,
+    order: VecDeque<String>,
+}
+
+impl SeenTransactions {
+    /// Records a hash. Returns false if it had already been seen.
+    pub fn insert(&self, hash: String) -> bool {
+        let mut inner = self.inner.write().unwrap();
+        if !inner.hashes.insert(hash.clone()) {
+            return false;
+        }
+        inner.order.push_back(hash);
+        if inner.order.len() > SEEN_CAPACITY {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.hashes.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.inner.read().unwrap().hashes.contains(hash)
+    }
+
+    /// Drops transactions that have been seen before, returning `None` if
+    /// nothing in the message is new.
+    pub(crate) fn filter(&self, message: NetworkMessage) -> Option<NetworkMessage> {
+        match message {
+            NetworkMessage::Transaction(transaction) => {
+                self.insert(transaction.hash()).then_some(NetworkMessage::Transaction(transaction))
+            }
+            NetworkMessage::MempoolTransactions(transactions) => {
+                let unseen: Vec<Transaction> = transactions.into_iter()
+                    .filter(|transaction| self.insert(transaction.hash()))
+                    .collect();
+                (!unseen.is_empty()).then_some(NetworkMessage::MempoolTransactions(unseen))
+            }
+            message => Some(message),
+        }
+    }
+}
+
+/// Hashes from a peer's inventory that are not in `local`, in the order the
+/// peer listed them.
+pub fn missing_from(inventory: &[String], local: &HashSet<String>) -> Vec<String> {
+    let mut requested = HashSet::new();
+    inventory.iter()
+        .filter(|hash| !local.contains(*hash) && requested.insert(hash.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Checks that inventories and requests name the peer they came from and
+/// stay within `MAX_INVENTORY_SIZE`.
+pub(crate) fn check_sync_message(message: &NetworkMessage, addr: SocketAddr) -> IcnResult<()> {
+    let (from, hashes) = match message {
+        NetworkMessage::MempoolInventory { from, hashes } | NetworkMessage::MempoolRequest { from, hashes } => (from, hashes),
+        _ => return Ok(()),
+    };
+    if *from != addr {
+        return Err(IcnError::Network(format!("Mempool message claims to be from {} but was sent by {}", from, addr)));
+    }
+    if hashes.len() > MAX_INVENTORY_SIZE {
+        return Err(IcnError::Network(format!("Mempool message lists {} hashes, more than {}", hashes.len(), MAX_INVENTORY_SIZE)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_common::{Amount, CurrencyType, SignatureScheme};
+
+    fn transaction(to: &str) -> Transaction {
+        Transaction {
+            from: "Alice".to_string(),
+            to: to.to_string(),
+            amount: Amount::from(1.0),
+            currency_type: CurrencyType::BasicNeeds,
+            timestamp: 0,
+            signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
+        }
+    }
+
+    #[test]
+    fn test_duplicates_suppressed() {
+        let seen = SeenTransactions::default();
+        assert!(seen.filter(NetworkMessage::Transaction(transaction("Bob"))).is_some());
+        assert!(seen.filter(NetworkMessage::Transaction(transaction("Bob"))).is_none());
+
+        let batch = NetworkMessage::MempoolTransactions(vec![transaction("Bob"), transaction("Carol")]);
+        match seen.filter(batch) {
+            Some(NetworkMessage::MempoolTransactions(unseen)) => assert_eq!(unseen, vec![transaction("Carol")]),
+            other => panic!("Unexpected filter result: {:?}", other),
+        }
+        assert!(seen.contains(&transaction("Carol").hash()));
+
+        let local: HashSet<String> = [transaction("Bob").hash()].into_iter().collect();
+        let inventory = vec![transaction("Bob").hash(), transaction("Dave").hash(), transaction("Dave").hash()];
+        assert_eq!(missing_from(&inventory, &local), vec![transaction("Dave").hash()]);
+
+        let addr: SocketAddr = "127.0.0.1:9200".parse().unwrap();
+        let spoofed = NetworkMessage::MempoolInventory { from: "127.0.0.1:9201".parse().unwrap(), hashes: Vec::new() };
+        assert!(check_sync_message(&spoofed, addr).is_err());
+        let flood = NetworkMessage::MempoolRequest { from: addr, hashes: vec![String::new(); MAX_INVENTORY_SIZE + 1] };
+        assert!(check_sync_message(&flood, addr).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+// File: crates/icn_network/src/peer_book.rs
+
+use icn_common::{IcnResult, IcnError};
+use serde::{Serialize, Deserialize};
+use std::fmt;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tokio::net::lookup_host;
+use tracing::warn;
+
+/// Where a peer can be reached: a literal IPv4 or IPv6 socket address, or a
+/// DNS name resolved at connection time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum PeerAddress {
+    Socket(SocketAddr),
+    Dns { host: String, port: u16 },
+}
+
+impl PeerAddress {
+    /// Resolves the address to the socket addresses to try, in order.
+    pub async fn resolve(&self) -> IcnResult<Vec<SocketAddr>> {
+        match self {
+            PeerAddress::Socket(addr) => Ok(vec![*addr]),
+            PeerAddress::Dns { host, port } => {
+                let addrs: Vec<SocketAddr> = lookup_host((host.as_str(), *port)).await
+                    .map_err(|e| IcnError::Network(format!("Failed to resolve {}: {}", self, e)))?
+                    .collect();
+                if addrs.is_empty() {
+                    return Err(IcnError::Network(format!("{} did not resolve to any address", self)));
+                }
+                Ok(addrs)
+            }
+        }
+    }
+}
+
+impl From<SocketAddr> for PeerAddress {
+    fn from(addr: SocketAddr) -> Self {
+        PeerAddress::Socket(addr)
+    }
+}
+
+/// Parses `1.2.3.4:7000`, `[::1]:7000` or `node.example.org:7000`.
+impl FromStr for PeerAddress {
+    type Err = IcnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(addr) = s.parse::<SocketAddr>() {
+            return Ok(PeerAddress::Socket(addr));
+        }
+
+        let (host, port) = s.rsplit_once(':')
+            .ok_or_else(|| IcnError::Network(format!("Peer address {} has no port", s)))?;
+        let port = port.parse::<u16>()
+            .map_err(|_| IcnError::Network(format!("Invalid port in peer address {}", s)))?;
+        let valid_host = !host.is_empty()
+            && host.split('.').all(|label| {
+                !label.is_empty()
+                    && !label.starts_with('-')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            });
+        if !valid_host {
+            return Err(IcnError::Network(format!("Invalid host in peer address {}", s)));
+        }
+        Ok(PeerAddress::Dns { host: host.to_string(), port })
+    }
+}
+
+impl fmt::Display for PeerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerAddress::Socket(addr) => write!(f, "{}", addr),
+            PeerAddress::Dns { host, port } => write!(f, "{}:{}", host, port),
+        }
+    }
+}
+
+/// A peer we have connected to, with every address it can be reached at.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerRecord {
+    pub node_id: Option<String>,
+    /// Tried in order when reconnecting.
+    pub addresses: Vec<PeerAddress>,
+    /// Unix timestamp of the last successful connection.
+    pub last_connected: Option<i64>,
+}
+
+/// Known peers, optionally persisted as JSON so they can be reconnected to
+/// after a restart.
+#[derive(Debug, Clone, Default)]
+pub struct PeerBook {
+    path: Option<PathBuf>,
+    peers: Vec<PeerRecord>,
+}
+
+impl PeerBook {
+    /// Opens the peer book at `path`, starting empty if the file does not exist.
+    pub fn load(path: impl AsRef<Path>) -> IcnResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let peers = if path.exists() {
+            let contents = fs::read(&path)
+                .map_err(|e| IcnError::Network(format!("Failed to read peer book {}: {}", path.display(), e)))?;
+            serde_json::from_slice(&contents)
+                .map_err(|e| IcnError::Network(format!("Invalid peer book {}: {}", path.display(), e)))?
+        } else {
+            Vec::new()
+        };
+        Ok(PeerBook { path: Some(path), peers })
+    }
+
+    pub fn peers(&self) -> &[PeerRecord] {
+        &self.peers
+    }
+
+    /// Records a successful connection made through one of `addresses`. The
+    /// addresses are merged into any record for the same node or sharing an
+    /// address, with the one that worked moved to the front.
+    pub fn record_connection(&mut self, node_id: &str, addresses: &[PeerAddress], connected: &PeerAddress, timestamp: i64) {
+        let index = self.peers.iter().position(|peer| {
+            peer.node_id.as_deref() == Some(node_id) || peer.addresses.iter().any(|a| addresses.contains(a))
+        });
+        let record = match index {
+            Some(index) => &mut self.peers[index],
+            None => {
+                self.peers.push(PeerRecord { node_id: None, addresses: Vec::new(), last_connected: None });
+                self.peers.last_mut().unwrap()
+            }
+        };
+
+        record.node_id = Some(node_id.to_string());
+        for address in addresses {
+            if !record.addresses.contains(address) {
+                record.addresses.push(address.clone());
+            }
+        }
+        record.addresses.retain(|a| a != connected);
+        record.addresses.insert(0, connected.clone());
+        record.last_connected = Some(timestamp);
+    }
+
+    pub fn remove(&mut self, node_id: &str) -> bool {
+        let before = self.peers.len();
+        self.peers.retain(|peer| peer.node_id.as_deref() != Some(node_id));
+        self.peers.len() != before
+    }
+
+    /// Writes the peer book to its file, if it has one. The file is replaced
+    /// atomically so a crash never leaves a truncated peer book behind.
+    pub fn save(&self) -> IcnResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let contents = serde_json::to_vec_pretty(&self.peers)
+            .map_err(|e| IcnError::Network(format!("Failed to serialize peer book: {}", e)))?;
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, contents)
+            .and_then(|_| fs::rename(&tmp_path, path))
+            .map_err(|e| {
+                warn!("Failed to save peer book to {}: {}", path.display(), e);
+                IcnError::Network(format!("Failed to write peer book {}: {}", path.display(), e))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_peer_addresses() {
+        assert_eq!("127.0.0.1:7000".parse::<PeerAddress>().unwrap(), PeerAddress::Socket("127.0.0.1:7000".parse().unwrap()));
+        assert!(matches!("[::1]:7000".parse::<PeerAddress>().unwrap(), PeerAddress::Socket(addr) if addr.is_ipv6()));
+        assert_eq!(
+            "node-1.example.org:7000".parse::<PeerAddress>().unwrap(),
+            PeerAddress::Dns { host: "node-1.example.org".to_string(), port: 7000 }
+        );
+        assert_eq!("node.example.org:7000".parse::<PeerAddress>().unwrap().to_string(), "node.example.org:7000");
+
+        assert!("node.example.org".parse::<PeerAddress>().is_err());
+        assert!("node..org:7000".parse::<PeerAddress>().is_err());
+        assert!("::1:7000".parse::<PeerAddress>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_localhost() {
+        let addrs = PeerAddress::Dns { host: "localhost".to_string(), port: 7000 }.resolve().await.unwrap();
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback() && addr.port() == 7000));
+    }
+
+    #[test]
+    fn test_peer_book_persists() {
+        let path = std::env::temp_dir().join(format!("icn_peer_book_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let primary: PeerAddress = "node.example.org:7000".parse().unwrap();
+        let fallback: PeerAddress = "[2001:db8::1]:7000".parse().unwrap();
+        let mut book = PeerBook::load(&path).unwrap();
+        book.record_connection("did:icn:peer", &[primary.clone(), fallback.clone()], &fallback, 100);
+        book.record_connection("did:icn:peer", std::slice::from_ref(&primary), &primary, 200);
+        book.save().unwrap();
+
+        let reloaded = PeerBook::load(&path).unwrap();
+        assert_eq!(reloaded.peers().len(), 1);
+        assert_eq!(reloaded.peers()[0].addresses, vec![primary, fallback]);
+        assert_eq!(reloaded.peers()[0].last_connected, Some(200));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,313 @@
+// File: crates/icn_network/src/reconnect.rs
+
+use rand::Rng;
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Wait before the first attempt to reach a lost peer.
+pub const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest wait between attempts; a peer that stays down is retried at
+/// this pace for as long as it is known.
+pub const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// Each wait is moved by up to this fraction either way, so peers lost
+/// together don't all retry at the same moment.
+pub const RECONNECT_JITTER: f64 = 0.2;
+
+/// How often the node looks for peers whose next attempt is due.
+pub const RECONNECT_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Where a peer stands with this node.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum PeerState {
+    Connected,
+    /// Lost, with the next attempt to reach it due in `retry_in_ms`.
+    Disconnected { retry_in_ms: u64 },
+    Reconnecting { attempt: u32 },
+    /// Not reconnected to and refused when it connects.
+    Banned,
+}
+
+/// A peer moving to a new state, keyed by the address it listens on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PeerStateChange {
+    pub peer: SocketAddr,
+    pub node_id: Option<String>,
+    pub state: PeerState,
+}
+
+/// The wait before reconnect attempt `attempt`, counting from zero:
+/// doubling from `RECONNECT_BASE_DELAY` up to `RECONNECT_MAX_DELAY`, then
+/// scaled by `jitter`, a number between -1 and 1, times `RECONNECT_JITTER`.
+pub fn backoff_delay(attempt: u32, jitter: f64) -> Duration {
+    let delay = RECONNECT_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt)).min(RECONNECT_MAX_DELAY);
+    delay.mul_f64(1.0 + jitter.clamp(-1.0, 1.0) * RECONNECT_JITTER)
+}
+
+#[derive(Debug, Clone)]
+struct PeerHistory {
+    node_id: Option<String>,
+    connections: u32,
+    failures: u32,
+    state: PeerState,
+    /// Failed attempts since the peer was lost.
+    attempts: u32,
+    next_attempt: Option<Instant>,
+}
+
+impl PeerHistory {
+    /// Share of connection attempts that worked, starting at one half for
+    /// peers we know nothing about.
+    fn reliability(&self) -> f64 {
+        (self.connections as f64 + 1.0) / ((self.connections + self.failures) as f64 + 2.0)
+    }
+}
+
+#[derive(Default)]
+struct ReconnectInner {
+    peers: HashMap<SocketAddr, PeerHistory>,
+    bans: HashMap<SocketAddr, Instant>,
+    changes: Vec<PeerStateChange>,
+}
+
+impl ReconnectInner {
+    fn set_state(&mut self, peer: SocketAddr, state: PeerState) {
+        let Some(history) = self.peers.get_mut(&peer) else {
+            return;
+        };
+        if history.state != state {
+            history.state = state;
+            self.changes.push(PeerStateChange { peer, node_id: history.node_id.clone(), state });
+        }
+    }
+
+    fn schedule(&mut self, peer: SocketAddr, now: Instant) {
+        let Some(history) = self.peers.get_mut(&peer) else {
+            return;
+        };
+        let delay = backoff_delay(history.attempts, rand::thread_rng().gen_range(-1.0, 1.0));
+        history.next_attempt = Some(now + delay);
+        self.set_state(peer, PeerState::Disconnected { retry_in_ms: delay.as_millis() as u64 });
+    }
+
+    fn is_banned(&mut self, peer: &SocketAddr, now: Instant) -> bool {
+        match self.bans.get(peer) {
+            Some(until) if *until > now => true,
+            Some(_) => {
+                self.bans.remove(peer);
+                if self.peers.get(peer).is_some_and(|history| history.state == PeerState::Banned) {
+                    self.schedule(*peer, now);
+                }
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+/// Tracks how reliable each peer has been and when lost peers are next
+/// tried, backing off exponentially while they stay unreachable. Clones
+/// share the same state.
+#[derive(Clone, Default)]
+pub struct PeerReconnector {
+    inner: Arc<Mutex<ReconnectInner>>,
+}
+
+impl PeerReconnector {
+    pub fn connected(&self, peer: SocketAddr, node_id: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        let history = inner.peers.entry(peer).or_insert_with(|| PeerHistory {
+            node_id: None,
+            connections: 0,
+            failures: 0,
+            state: PeerState::Disconnected { retry_in_ms: 0 },
+            attempts: 0,
+            next_attempt: None,
+        });
+        history.node_id = Some(node_id.to_string());
+        history.connections += 1;
+        history.attempts = 0;
+        history.next_attempt = None;
+        inner.set_state(peer, PeerState::Connected);
+    }
+
+    /// Schedules a connected peer that dropped to be tried again. Peers
+    /// this node isn't connected to, or has forgotten, are left alone.
+    pub fn lost(&self, peer: SocketAddr, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.peers.get(&peer).is_some_and(|history| history.state == PeerState::Connected) {
+            inner.schedule(peer, now);
+        }
+    }
+
+    /// Records a failed attempt and backs off further.
+    pub fn failed(&self, peer: SocketAddr, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        let Some(history) = inner.peers.get_mut(&peer) else {
+            return;
+        };
+        history.failures += 1;
+        history.attempts += 1;
+        if !inner.is_banned(&peer, now) {
+            inner.schedule(peer, now);
+        }
+    }
+
+    /// Stops reconnecting to a peer this node let go of on purpose.
+    pub fn forget(&self, peer: &SocketAddr) {
+        self.inner.lock().unwrap().peers.remove(peer);
+    }
+
+    /// Peers whose next attempt is due, most reliable first. They are
+    /// marked as reconnecting until `connected` or `failed` is called.
+    pub fn due(&self, now: Instant) -> Vec<SocketAddr> {
+        let mut inner = self.inner.lock().unwrap();
+        let mut due: Vec<(SocketAddr, f64)> = inner.peers.iter()
+            .filter(|(_, history)| history.next_attempt.is_some_and(|at| at <= now))
+            .map(|(peer, history)| (*peer, history.reliability()))
+            .collect();
+        due.retain(|(peer, _)| !inner.is_banned(peer, now));
+        due.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        for (peer, _) in &due {
+            let history = inner.peers.get_mut(peer).unwrap();
+            history.next_attempt = None;
+            let attempt = history.attempts + 1;
+            inner.set_state(*peer, PeerState::Reconnecting { attempt });
+        }
+        due.into_iter().map(|(peer, _)| peer).collect()
+    }
+
+    pub fn ban(&self, peer: SocketAddr, duration: Duration, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.bans.insert(peer, now + duration);
+        if let Some(history) = inner.peers.get_mut(&peer) {
+            history.next_attempt = None;
+            inner.set_state(peer, PeerState::Banned);
+        }
+    }
+
+    /// Lifts a ban early; a known peer is tried again straight away.
+    pub fn unban(&self, peer: &SocketAddr, now: Instant) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.bans.remove(peer).is_none() {
+            return false;
+        }
+        if let Some(history) = inner.peers.get_mut(peer) {
+            history.attempts = 0;
+            history.next_attempt = Some(now);
+            inner.set_state(*peer, PeerState::Disconnected { retry_in_ms: 0 });
+        }
+        true
+    }
+
+    pub fn is_banned(&self, peer: &SocketAddr, now: Instant) -> bool {
+        self.inner.lock().unwrap().is_banned(peer, now)
+    }
+
+    /// Banned peers with the time left on their bans.
+    pub fn bans(&self, now: Instant) -> Vec<(SocketAddr, Duration)> {
+        let inner = self.inner.lock().unwrap();
+        let mut bans: Vec<(SocketAddr, Duration)> = inner.bans.iter()
+            .filter(|(_, until)| **until > now)
+            .map(|(peer, until)| (*peer, *until - now))
+            .collect();
+        bans.sort();
+        bans
+    }
+
+    pub fn state(&self, peer: &SocketAddr) -> Option<PeerState> {
+        self.inner.lock().unwrap().peers.get(peer).map(|history| history.state)
+    }
+
+    pub fn reliability(&self, peer: &SocketAddr) -> Option<f64> {
+        self.inner.lock().unwrap().peers.get(peer).map(PeerHistory::reliability)
+    }
+
+    /// State changes since the last call, oldest first.
+    pub fn take_changes(&self) -> Vec<PeerStateChange> {
+        std::mem::take(&mut self.inner.lock().unwrap().changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        assert_eq!(backoff_delay(0, 0.0), RECONNECT_BASE_DELAY);
+        assert_eq!(backoff_delay(3, 0.0), RECONNECT_BASE_DELAY * 8);
+        assert_eq!(backoff_delay(40, 0.0), RECONNECT_MAX_DELAY);
+        assert_eq!(backoff_delay(0, 1.0), RECONNECT_BASE_DELAY.mul_f64(1.0 + RECONNECT_JITTER));
+        assert_eq!(backoff_delay(0, -5.0), RECONNECT_BASE_DELAY.mul_f64(1.0 - RECONNECT_JITTER));
+    }
+
+    #[test]
+    fn test_lost_peers_are_retried_by_reliability() {
+        let reconnector = PeerReconnector::default();
+        let now = Instant::now();
+        let (flaky, steady) = (addr(7001), addr(7002));
+        reconnector.connected(flaky, "flaky");
+        reconnector.connected(steady, "steady");
+        reconnector.connected(steady, "steady");
+
+        reconnector.lost(flaky, now);
+        reconnector.lost(steady, now);
+        reconnector.lost(addr(7003), now);
+        assert!(reconnector.due(now).is_empty());
+        let later = now + RECONNECT_MAX_DELAY * 2;
+        assert_eq!(reconnector.due(later), vec![steady, flaky]);
+        assert_eq!(reconnector.state(&flaky), Some(PeerState::Reconnecting { attempt: 1 }));
+
+        // A failure backs the peer off further and lowers its standing
+        reconnector.failed(flaky, later);
+        assert!(reconnector.due(later + backoff_delay(0, 1.0)).is_empty());
+        assert_eq!(reconnector.due(later + backoff_delay(1, 1.0)), vec![flaky]);
+        assert!(reconnector.reliability(&flaky).unwrap() < reconnector.reliability(&steady).unwrap());
+
+        reconnector.connected(flaky, "flaky");
+        let states: Vec<PeerState> = reconnector.take_changes().into_iter()
+            .filter(|change| change.peer == flaky)
+            .map(|change| change.state)
+            .collect();
+        assert_eq!(states[0], PeerState::Connected);
+        assert!(matches!(states[1], PeerState::Disconnected { .. }));
+        assert_eq!(states[2], PeerState::Reconnecting { attempt: 1 });
+        assert_eq!(states.last(), Some(&PeerState::Connected));
+        assert!(reconnector.take_changes().is_empty());
+    }
+
+    #[test]
+    fn test_banned_peers_are_not_retried() {
+        let reconnector = PeerReconnector::default();
+        let now = Instant::now();
+        let peer = addr(7001);
+        reconnector.connected(peer, "peer");
+        reconnector.lost(peer, now);
+        reconnector.ban(peer, Duration::from_secs(3600), now);
+
+        assert_eq!(reconnector.state(&peer), Some(PeerState::Banned));
+        assert!(reconnector.due(now + RECONNECT_MAX_DELAY * 2).is_empty());
+        assert_eq!(reconnector.bans(now), vec![(peer, Duration::from_secs(3600))]);
+
+        // Once the ban runs out the peer is scheduled again
+        let expired = now + Duration::from_secs(3601);
+        assert!(!reconnector.is_banned(&peer, expired));
+        assert!(matches!(reconnector.state(&peer), Some(PeerState::Disconnected { .. })));
+        assert_eq!(reconnector.due(expired + RECONNECT_MAX_DELAY), vec![peer]);
+
+        reconnector.ban(peer, Duration::from_secs(60), expired);
+        assert!(reconnector.unban(&peer, expired));
+        assert_eq!(reconnector.due(expired), vec![peer]);
+        assert!(!reconnector.unban(&peer, expired));
+    }
+}
@@ -0,0 +1,159 @@
+// File: crates/icn_network/src/time_sync.rs
+
+use chrono::{DateTime, Utc};
+use icn_common::{system_clock, Clock, IcnError, IcnResult, SharedClock};
+use serde::{Serialize, Deserialize};
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// Peers whose clock is further than this from ours are refused.
+pub const MAX_PEER_CLOCK_SKEW_MS: i64 = 60_000;
+
+/// Peers that must have been sampled before the network time moves away
+/// from the local clock.
+pub const MIN_TIME_SYNC_PEERS: usize = 3;
+
+/// Samples kept per peer. The one with the shortest round trip is trusted,
+/// since a short trip leaves little room for lopsided delays.
+const SAMPLES_PER_PEER: usize = 8;
+
+/// How far a peer's clock was ahead of ours, measured from one handshake.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct ClockSample {
+    pub offset_ms: i64,
+    pub rtt_ms: i64,
+}
+
+/// Estimates the offset of a peer's clock from a handshake it stamped with
+/// `peer_ms` in answer to one sent at `sent_ms` on our clock. The answer is
+/// taken to have been stamped halfway through the round trip.
+pub fn clock_offset(peer_ms: i64, sent_ms: i64, rtt: Duration) -> ClockSample {
+    let rtt_ms = rtt.as_millis() as i64;
+    ClockSample { offset_ms: peer_ms - (sent_ms + rtt_ms / 2), rtt_ms }
+}
+
+pub fn check_clock_skew(offset_ms: i64) -> IcnResult<()> {
+    if offset_ms.abs() > MAX_PEER_CLOCK_SKEW_MS {
+        return Err(IcnError::Network(format!(
+            "Peer clock is {} ms off ours, more than the {} ms allowed", offset_ms, MAX_PEER_CLOCK_SKEW_MS
+        )));
+    }
+    Ok(())
+}
+
+/// Clock offsets sampled from outbound handshakes, shared between the
+/// manager and the clocks built from it.
+#[derive(Debug, Clone, Default)]
+pub struct TimeSync {
+    peers: Arc<RwLock<HashMap<SocketAddr, VecDeque<ClockSample>>>>,
+}
+
+impl TimeSync {
+    /// Records a sample from `peer`, or forgets the peer and fails if its
+    /// clock is too far off to trust.
+    pub fn record(&self, peer: SocketAddr, sample: ClockSample) -> IcnResult<()> {
+        if let Err(e) = check_clock_skew(sample.offset_ms) {
+            self.forget(&peer);
+            return Err(e);
+        }
+        let mut peers = self.peers.write().unwrap();
+        let samples = peers.entry(peer).or_default();
+        if samples.len() == SAMPLES_PER_PEER {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+        Ok(())
+    }
+
+    pub fn forget(&self, peer: &SocketAddr) {
+        self.peers.write().unwrap().remove(peer);
+    }
+
+    /// Each sampled peer's most trusted sample.
+    pub fn peer_offsets(&self) -> Vec<(SocketAddr, ClockSample)> {
+        let mut offsets: Vec<_> = self.peers.read().unwrap().iter()
+            .filter_map(|(peer, samples)| samples.iter().min_by_key(|sample| sample.rtt_ms).map(|sample| (*peer, *sample)))
+            .collect();
+        offsets.sort_by_key(|(peer, _)| *peer);
+        offsets
+    }
+
+    /// How far to move the local clock to reach network time: the median
+    /// of the peers' offsets and our own zero. Zero until enough peers
+    /// have been sampled.
+    pub fn offset_ms(&self) -> i64 {
+        let mut offsets: Vec<i64> = self.peer_offsets().into_iter().map(|(_, sample)| sample.offset_ms).collect();
+        if offsets.len() < MIN_TIME_SYNC_PEERS {
+            return 0;
+        }
+        offsets.push(0);
+        offsets.sort_unstable();
+        let middle = offsets.len() / 2;
+        if offsets.len().is_multiple_of(2) {
+            (offsets[middle - 1] + offsets[middle]) / 2
+        } else {
+            offsets[middle]
+        }
+    }
+
+    /// A clock reading network time off the system clock.
+    pub fn clock(&self) -> SharedClock {
+        Arc::new(NetworkClock { sync: self.clone(), local: system_clock() })
+    }
+}
+
+/// The local clock moved by the network's offset.
+#[derive(Debug, Clone)]
+pub struct NetworkClock {
+    sync: TimeSync,
+    local: SharedClock,
+}
+
+impl NetworkClock {
+    pub fn new(sync: TimeSync, local: SharedClock) -> Self {
+        NetworkClock { sync, local }
+    }
+}
+
+impl Clock for NetworkClock {
+    fn now(&self) -> DateTime<Utc> {
+        self.local.now() + chrono::Duration::milliseconds(self.sync.offset_ms())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_common::ManualClock;
+
+    fn peer(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn test_network_time_follows_the_median_peer() {
+        let local = ManualClock::new(Utc::now());
+        let sync = TimeSync::default();
+        let clock = NetworkClock::new(sync.clone(), local.shared());
+
+        // The answer was stamped 50 ms into a 100 ms round trip
+        assert_eq!(clock_offset(10_050, 0, Duration::from_millis(100)).offset_ms, 10_000);
+
+        sync.record(peer(1), ClockSample { offset_ms: 2_000, rtt_ms: 40 }).unwrap();
+        sync.record(peer(2), ClockSample { offset_ms: 3_000, rtt_ms: 40 }).unwrap();
+        assert_eq!(clock.now(), local.now());
+
+        // A slow round trip is outweighed by the peer's faster one
+        sync.record(peer(2), ClockSample { offset_ms: 9_000, rtt_ms: 900 }).unwrap();
+        sync.record(peer(3), ClockSample { offset_ms: -1_000, rtt_ms: 40 }).unwrap();
+        assert_eq!(sync.offset_ms(), 1_000);
+        assert_eq!(clock.now(), local.now() + chrono::Duration::seconds(1));
+
+        // A peer far off is refused and dropped from the estimate
+        assert!(sync.record(peer(3), ClockSample { offset_ms: MAX_PEER_CLOCK_SKEW_MS + 1, rtt_ms: 40 }).is_err());
+        assert_eq!(sync.peer_offsets().len(), 2);
+        assert_eq!(sync.offset_ms(), 0);
+    }
+}
@@ -0,0 +1,104 @@
+// File: crates/icn_reputation/src/history.rs
+
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+
+/// One change to a member's reputation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReputationChange {
+    /// What made the change, such as "dispute" or "contract".
+    pub source: String,
+    pub reason: String,
+    /// How much the reputation actually moved, after any clamping.
+    pub delta: f64,
+    /// The reputation right after the change.
+    pub reputation: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Every change to a member's reputation, oldest first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ReputationHistory {
+    changes: Vec<ReputationChange>,
+}
+
+impl ReputationHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that reputation moved from `before` to `after`. Changes
+    /// must be recorded in time order.
+    pub fn record(&mut self, source: &str, reason: &str, before: f64, after: f64, timestamp: DateTime<Utc>) -> &ReputationChange {
+        let timestamp = self.changes.last().map_or(timestamp, |last| timestamp.max(last.timestamp));
+        self.changes.push(ReputationChange {
+            source: source.to_string(),
+            reason: reason.to_string(),
+            delta: after - before,
+            reputation: after,
+            timestamp,
+        });
+        self.changes.last().unwrap()
+    }
+
+    pub fn changes(&self) -> &[ReputationChange] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Reputation at `at`: the value after the last change made by then,
+    /// or the value the first change started from. `None` without changes.
+    pub fn value_at(&self, at: DateTime<Utc>) -> Option<f64> {
+        let first = self.changes.first()?;
+        Some(self.changes.iter()
+            .take_while(|change| change.timestamp <= at)
+            .last()
+            .map_or(first.reputation - first.delta, |change| change.reputation))
+    }
+
+    /// Reputation averaged over `[from, to]`, each value weighted by how
+    /// long it was held. `None` without changes or for an empty window.
+    pub fn time_weighted_average(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Option<f64> {
+        if to <= from {
+            return None;
+        }
+        let mut value = self.value_at(from)?;
+        let mut since = from;
+        let mut weighted = 0.0;
+        for change in self.changes.iter().filter(|change| change.timestamp > from && change.timestamp <= to) {
+            weighted += value * (change.timestamp - since).num_milliseconds() as f64;
+            value = change.reputation;
+            since = change.timestamp;
+        }
+        weighted += value * (to - since).num_milliseconds() as f64;
+        Some(weighted / (to - from).num_milliseconds() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_time_weighted_average() {
+        let start = Utc::now();
+        let mut history = ReputationHistory::new();
+        assert_eq!(history.time_weighted_average(start, start + Duration::hours(1)), None);
+
+        history.record("dispute", "lost dispute d1", 1.0, 0.5, start + Duration::minutes(30));
+        history.record("contract", "payroll", 0.5, 2.0, start + Duration::minutes(45));
+        assert_eq!(history.changes()[0].delta, -0.5);
+        assert_eq!(history.value_at(start), Some(1.0));
+        assert_eq!(history.value_at(start + Duration::minutes(40)), Some(0.5));
+
+        // Half an hour at 1.0, a quarter at 0.5 and a quarter at 2.0
+        let average = history.time_weighted_average(start, start + Duration::hours(1)).unwrap();
+        assert!((average - 1.125).abs() < 1e-9);
+        let recent = history.time_weighted_average(start + Duration::hours(1), start + Duration::hours(2)).unwrap();
+        assert_eq!(recent, 2.0);
+    }
+}
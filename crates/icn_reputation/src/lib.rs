@@ -1,166 +1,85 @@
-// File: crates/icn_common/src/lib.rs
+// File: crates/icn_reputation/src/lib.rs
 
-pub mod error;
-pub mod bit_utils;
+pub mod history;
 
-pub use crate::error::{IcnError, IcnResult};
+pub use crate::history::{ReputationChange, ReputationHistory};
 
 use serde::{Serialize, Deserialize};
-use chrono::{DateTime, Utc};
-use ed25519_dalek::{Keypair, PublicKey, Signature, Signer, Verifier}; // Importing the necessary traits and types
-use rand_chacha::ChaCha20Rng;
-use rand::RngCore; // Importing necessary traits for random number generation
-use rand::SeedableRng;
-use std::collections::HashMap;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Config {
-    pub shard_count: u64,
-    pub consensus_threshold: f64,
-    pub consensus_quorum: f64,
-    pub network_port: u16,
+
+/// Something a validator did (or failed to do) during a consensus round.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReputationEvent {
+    BlockProposed,
+    VoteCast,
+    MissedRound,
+    /// A vote against the outcome the rest of the validator set reached.
+    InvalidVote,
 }
 
+/// How much each event moves a validator's reputation.
+///
+/// Reputation always stays within `[min_reputation, max_reputation]`;
+/// penalties are larger than rewards so that a validator cannot make up for
+/// misbehaviour by simply showing up more often.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct Transaction {
-    pub from: String,
-    pub to: String,
-    pub amount: f64,
-    pub currency_type: CurrencyType,
-    pub timestamp: i64,
-    pub signature: Option<Vec<u8>>,
+pub struct ReputationPolicy {
+    pub block_proposed: f64,
+    pub vote_cast: f64,
+    pub missed_round: f64,
+    pub invalid_vote: f64,
+    pub min_reputation: f64,
+    pub max_reputation: f64,
 }
 
-impl Transaction {
-    pub fn new(from: String, to: String, amount: f64, currency_type: CurrencyType, timestamp: i64) -> Self {
-        Transaction {
-            from,
-            to,
-            amount,
-            currency_type,
-            timestamp,
-            signature: None,
+impl Default for ReputationPolicy {
+    fn default() -> Self {
+        ReputationPolicy {
+            block_proposed: 0.01,
+            vote_cast: 0.002,
+            missed_round: -0.01,
+            invalid_vote: -0.05,
+            min_reputation: 0.0,
+            max_reputation: 1.0,
         }
     }
+}
 
-    pub fn sign(&mut self, keypair: &Keypair) -> IcnResult<()> {
-        let message = format!("{}{}{}{}", self.from, self.to, self.amount, self.timestamp);
-        let signature = keypair.sign(message.as_bytes()).to_bytes().to_vec();
-        self.signature = Some(signature);
-        Ok(())
-    }
-
-    pub fn verify(&self) -> IcnResult<bool> {
-        if let Some(signature) = &self.signature {
-            let message = format!("{}{}{}{}", self.from, self.to, self.amount, self.timestamp);
-            let public_key = PublicKey::from_bytes(&self.from.as_bytes())
-                .map_err(|e| IcnError::Identity(format!("PublicKey conversion failed: {}", e)))?;
-            let signature = Signature::from_bytes(signature)
-                .map_err(|e| IcnError::Identity(format!("Signature conversion failed: {}", e)))?;
-            public_key
-                .verify(message.as_bytes(), &signature)
-                .map_err(|e| IcnError::Identity(format!("Signature verification failed: {}", e)))?;
-            Ok(true)
-        } else {
-            Ok(false)
+impl ReputationPolicy {
+    pub fn adjustment(&self, event: ReputationEvent) -> f64 {
+        match event {
+            ReputationEvent::BlockProposed => self.block_proposed,
+            ReputationEvent::VoteCast => self.vote_cast,
+            ReputationEvent::MissedRound => self.missed_round,
+            ReputationEvent::InvalidVote => self.invalid_vote,
         }
     }
 
-    pub fn get_fee(&self) -> f64 {
-        // Simplified fee calculation; in a real implementation, fees would be more complex
-        0.01
+    /// Returns the reputation after applying `event` to `current`.
+    pub fn apply(&self, current: f64, event: ReputationEvent) -> f64 {
+        (current + self.adjustment(event)).clamp(self.min_reputation, self.max_reputation)
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Proposal {
-    pub id: String,
-    pub title: String,
-    pub description: String,
-    pub proposer: String,
-    pub created_at: DateTime<Utc>,
-    pub voting_ends_at: DateTime<Utc>,
-    pub status: ProposalStatus,
-    pub proposal_type: ProposalType,
-    pub category: ProposalCategory,
-    pub required_quorum: f64,
-    pub execution_timestamp: Option<DateTime<Utc>>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Vote {
-    pub voter: String,
-    pub proposal_id: String,
-    pub in_favor: bool,
-    pub weight: f64,
-    pub timestamp: i64,
-    pub zkp: Option<Vec<u8>>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProposalStatus {
-    Active,
-    Passed,
-    Rejected,
-    Executed,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProposalType {
-    Constitutional,
-    EconomicAdjustment,
-    NetworkUpgrade,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub enum ProposalCategory {
-    Economic,
-    Technical,
-    Social,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-pub enum CurrencyType {
-    BasicNeeds,
-    Education,
-    Environmental,
-    Community,
-    Custom(String),
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct NetworkStats {
-    pub node_count: usize,
-    pub total_transactions: usize,
-    pub active_proposals: usize,
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ed25519_dalek::Signer;
-    use rand_chacha::ChaCha20Rng;
-    use rand::SeedableRng;
+    use ed25519_dalek::{Keypair, PublicKey, SecretKey};
+    use icn_common::{CurrencyType, NetworkStats, ProposalStatus, Transaction};
+    use std::collections::BTreeMap;
 
     #[test]
-    fn test_transaction_equality() {
-        let tx1 = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: 0,
-            signature: None,
-        };
+    fn test_policy_stays_within_bounds() {
+        let policy = ReputationPolicy::default();
+        assert!((policy.apply(0.5, ReputationEvent::BlockProposed) - 0.51).abs() < 1e-9);
+        assert!((policy.apply(0.5, ReputationEvent::InvalidVote) - 0.45).abs() < 1e-9);
+        assert_eq!(policy.apply(1.0, ReputationEvent::BlockProposed), 1.0);
+        assert_eq!(policy.apply(0.0, ReputationEvent::MissedRound), 0.0);
+    }
 
-        let tx2 = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: 0,
-            signature: None,
-        };
+    #[test]
+    fn test_transaction_equality() {
+        let tx1 = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, 0);
+        let tx2 = Transaction::new("Alice".to_string(), "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, 0);
 
         assert_eq!(tx1, tx2);
     }
@@ -184,6 +103,9 @@ mod tests {
             node_count: 5,
             total_transactions: 100,
             active_proposals: 3,
+            bytes_sent: 0,
+            bytes_received: 0,
+            peer_traffic: BTreeMap::new(),
         };
         assert_eq!(stats.node_count, 5);
         assert_eq!(stats.total_transactions, 100);
@@ -192,17 +114,11 @@ mod tests {
 
     #[test]
     fn test_transaction_signing_and_verification() {
-        let mut rng = ChaCha20Rng::seed_from_u64(0); // Use a deterministic seed for testing
-        let keypair: Keypair = Keypair::generate(&mut rng);
-
-        let mut tx = Transaction {
-            from: "Alice".to_string(),
-            to: "Bob".to_string(),
-            amount: 50.0,
-            currency_type: CurrencyType::BasicNeeds,
-            timestamp: 0,
-            signature: None,
-        };
+        let secret = SecretKey::from_bytes(&[0; 32]).unwrap(); // Use a deterministic key for testing
+        let keypair = Keypair { public: PublicKey::from(&secret), secret };
+        let sender = format!("did:icn:{}", hex::encode(keypair.public.to_bytes()));
+
+        let mut tx = Transaction::new(sender, "Bob".to_string(), 50.0, CurrencyType::BasicNeeds, 0);
 
         tx.sign(&keypair).expect("Signing failed");
         assert!(tx.signature.is_some());
@@ -1,11 +1,11 @@
-use icn_common::{IcnResult, IcnError, Block, Transaction, CurrencyType};
+use icn_common::{IcnResult, IcnError, Amount, Block, Transaction, CurrencyType};
 use std::collections::HashMap;
 use sha2::{Sha256, Digest};
 
 pub struct Shard {
     pub id: u64,
     pub blockchain: Vec<Block>,
-    pub balances: HashMap<String, HashMap<CurrencyType, f64>>,
+    pub balances: HashMap<String, HashMap<CurrencyType, Amount>>,
     pub pending_transactions: Vec<Transaction>,
 }
 
@@ -53,7 +53,7 @@ impl ShardingManager {
 
     fn update_balances(&mut self, shard: &mut Shard, transaction: &Transaction) -> IcnResult<()> {
         let sender_balances = shard.balances.entry(transaction.from.clone()).or_insert_with(HashMap::new);
-        let sender_balance = sender_balances.entry(transaction.currency_type.clone()).or_insert(0.0);
+        let sender_balance = sender_balances.entry(transaction.currency_type.clone()).or_default();
         
         if *sender_balance < transaction.amount {
             return Err(IcnError::Sharding("Insufficient balance".to_string()));
@@ -62,8 +62,15 @@ impl ShardingManager {
         *sender_balance -= transaction.amount;
 
         let recipient_balances = shard.balances.entry(transaction.to.clone()).or_insert_with(HashMap::new);
-        let recipient_balance = recipient_balances.entry(transaction.currency_type.clone()).or_insert(0.0);
-        *recipient_balance += transaction.amount;
+        let recipient_balance = recipient_balances.entry(transaction.currency_type.clone()).or_default();
+        match recipient_balance.checked_add(transaction.amount) {
+            Some(credited) => *recipient_balance = credited,
+            None => {
+                // Puts back what was just taken, so it can't overflow
+                *shard.balances.entry(transaction.from.clone()).or_default().entry(transaction.currency_type.clone()).or_default() += transaction.amount;
+                return Err(IcnError::Sharding(format!("Balance of {} would overflow", transaction.to)));
+            }
+        }
 
         Ok(())
     }
@@ -109,8 +116,7 @@ impl ShardingManager {
         Ok(shard.balances
             .get(address)
             .and_then(|balances| balances.get(currency_type))
-            .cloned()
-            .unwrap_or(0.0))
+            .map_or(0.0, |balance| balance.to_f64()))
     }
 
     fn verify_transaction(&self, shard: &Shard, transaction: &Transaction) -> bool {
@@ -174,6 +180,7 @@ impl ShardingManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use icn_common::SignatureScheme;
 
     #[test]
     fn test_sharding_manager() {
@@ -183,10 +190,14 @@ mod tests {
         let transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 100.0,
+            amount: Amount::from(100.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: 0,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         manager.add_address_to_shard("Alice".to_string(), 0).unwrap();
@@ -194,7 +205,7 @@ mod tests {
 
         // Initialize Alice's balance
         let alice_shard = manager.shards.get_mut(&0).unwrap();
-        alice_shard.balances.entry("Alice".to_string()).or_insert_with(HashMap::new).insert(CurrencyType::BasicNeeds, 200.0);
+        alice_shard.balances.entry("Alice".to_string()).or_insert_with(HashMap::new).insert(CurrencyType::BasicNeeds, Amount::from(200.0));
 
         assert!(manager.process_transaction(&transaction).is_ok());
 
@@ -210,10 +221,14 @@ mod tests {
         let cross_shard_tx = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 50.0,
+            amount: Amount::from(50.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: 1,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         assert!(manager.transfer_between_shards(0, 1, &cross_shard_tx).is_ok());
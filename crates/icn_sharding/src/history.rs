@@ -0,0 +1,228 @@
+// File: crates/icn_sharding/src/history.rs
+
+use crate::{Shard, ShardingManager};
+use icn_common::{IcnResult, IcnError, Amount, Transaction, CurrencyType};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+use tracing::info;
+
+/// What is kept of a pruned transaction: enough to show it happened and
+/// what it moved, without the signature or memo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardReceipt {
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub amount: Amount,
+    pub currency_type: CurrencyType,
+    pub timestamp: i64,
+}
+
+impl From<&Transaction> for ShardReceipt {
+    fn from(transaction: &Transaction) -> Self {
+        ShardReceipt {
+            hash: transaction.hash(),
+            from: transaction.from.clone(),
+            to: transaction.to.clone(),
+            amount: transaction.amount,
+            currency_type: transaction.currency_type.clone(),
+            timestamp: transaction.timestamp,
+        }
+    }
+}
+
+/// How much the pruned transactions moved in or out of one account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetFlow {
+    pub address: String,
+    pub currency_type: CurrencyType,
+    pub amount: Amount,
+}
+
+/// Running total of what every pruned transaction did to the shard's
+/// accounts, so balances can still be reconciled against the transactions
+/// that are left.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BalanceCheckpoint {
+    /// Transactions before this timestamp have been pruned. `None` until
+    /// the shard is first pruned.
+    pub pruned_before: Option<i64>,
+    /// Sorted by address.
+    pub net_flows: Vec<NetFlow>,
+}
+
+impl BalanceCheckpoint {
+    fn add_flows(&mut self, flows: impl IntoIterator<Item = NetFlow>) {
+        let mut totals: HashMap<(String, CurrencyType), Amount> = self.net_flows.drain(..)
+            .map(|flow| ((flow.address, flow.currency_type), flow.amount))
+            .collect();
+        for flow in flows {
+            *totals.entry((flow.address, flow.currency_type)).or_default() += flow.amount;
+        }
+        self.net_flows = totals.into_iter()
+            .map(|((address, currency_type), amount)| NetFlow { address, currency_type, amount })
+            .collect();
+        self.net_flows.sort_by_cached_key(|flow| (flow.address.clone(), format!("{:?}", flow.currency_type)));
+    }
+
+    fn merge(&mut self, other: BalanceCheckpoint) {
+        self.pruned_before = self.pruned_before.max(other.pruned_before);
+        self.add_flows(other.net_flows);
+    }
+}
+
+/// How much of a shard's history is still kept in full.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShardHistory {
+    pub shard_id: u64,
+    /// Transactions from this timestamp on are kept in full; `None` when
+    /// nothing has been pruned.
+    pub full_since: Option<i64>,
+    pub transactions: usize,
+    pub receipts: usize,
+    pub checkpoint: BalanceCheckpoint,
+}
+
+/// What a shard still holds of one transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryAvailability {
+    Full,
+    ReceiptOnly,
+    Unknown,
+}
+
+impl Shard {
+    pub(crate) fn take_history(&mut self) -> (Vec<Transaction>, Vec<ShardReceipt>, BalanceCheckpoint) {
+        (std::mem::take(&mut self.transactions), std::mem::take(&mut self.receipts), std::mem::take(&mut self.checkpoint))
+    }
+
+    pub(crate) fn merge_checkpoint(&mut self, checkpoint: BalanceCheckpoint) {
+        self.checkpoint.merge(checkpoint);
+    }
+}
+
+impl ShardingManager {
+    /// Replaces the transactions older than `before` with receipts in every
+    /// shard, folding what they moved into each shard's balance checkpoint.
+    /// Returns how many transactions were pruned.
+    pub fn prune_history(&self, before: i64) -> IcnResult<usize> {
+        let mut shards = self.write_shards()?;
+        let mut pruned = 0;
+        for shard in shards.iter_mut() {
+            let (old, kept): (Vec<Transaction>, Vec<Transaction>) = std::mem::take(&mut shard.transactions)
+                .into_iter()
+                .partition(|transaction| transaction.timestamp < before);
+            shard.transactions = kept;
+
+            // A cross-shard transfer is kept by both shards, but each only
+            // accounts for its own side of it
+            let mut flows = Vec::new();
+            for transaction in &old {
+                if self.get_shard_for_address(&transaction.from) == shard.id {
+                    flows.push(NetFlow { address: transaction.from.clone(), currency_type: transaction.currency_type.clone(), amount: -transaction.amount });
+                }
+                if self.get_shard_for_address(&transaction.to) == shard.id {
+                    flows.push(NetFlow { address: transaction.to.clone(), currency_type: transaction.currency_type.clone(), amount: transaction.amount });
+                }
+            }
+            shard.checkpoint.add_flows(flows);
+            shard.checkpoint.pruned_before = shard.checkpoint.pruned_before.max(Some(before));
+            shard.receipts.extend(old.iter().map(ShardReceipt::from));
+            pruned += old.len();
+        }
+        if pruned > 0 {
+            info!("Pruned {} shard transactions older than {}", pruned, before);
+        }
+        Ok(pruned)
+    }
+
+    pub fn get_shard_history(&self, shard_id: u64) -> IcnResult<ShardHistory> {
+        let shards = self.read_shard(shard_id)?;
+        let shard = &shards[shard_id as usize];
+        Ok(ShardHistory {
+            shard_id,
+            full_since: shard.checkpoint.pruned_before,
+            transactions: shard.transactions.len(),
+            receipts: shard.receipts.len(),
+            checkpoint: shard.checkpoint.clone(),
+        })
+    }
+
+    /// Whether a shard still has a transaction in full, only its receipt,
+    /// or neither.
+    pub fn transaction_availability(&self, shard_id: u64, hash: &str) -> IcnResult<HistoryAvailability> {
+        let shards = self.read_shard(shard_id)?;
+        let shard = &shards[shard_id as usize];
+        Ok(if shard.transactions.iter().any(|transaction| transaction.hash() == hash) {
+            HistoryAvailability::Full
+        } else if shard.receipts.iter().any(|receipt| receipt.hash == hash) {
+            HistoryAvailability::ReceiptOnly
+        } else {
+            HistoryAvailability::Unknown
+        })
+    }
+
+    pub fn get_shard_receipts(&self, shard_id: u64) -> IcnResult<Vec<ShardReceipt>> {
+        let shards = self.read_shard(shard_id)?;
+        Ok(shards[shard_id as usize].receipts.clone())
+    }
+
+    fn read_shard(&self, shard_id: u64) -> IcnResult<std::sync::RwLockReadGuard<'_, Vec<Shard>>> {
+        if shard_id >= self.shard_count {
+            return Err(IcnError::Sharding(format!("Invalid shard ID: {}", shard_id)));
+        }
+        self.shards.read().map_err(|_| IcnError::Sharding("Failed to acquire read lock".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_common::SignatureScheme;
+
+    fn transaction(from: &str, to: &str, amount: f64, timestamp: i64) -> Transaction {
+        Transaction {
+            from: from.to_string(),
+            to: to.to_string(),
+            amount: Amount::from(amount),
+            currency_type: CurrencyType::BasicNeeds,
+            timestamp,
+            signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
+        }
+    }
+
+    #[test]
+    fn test_pruning_keeps_receipts_and_checkpoint() {
+        let manager = ShardingManager::new(1);
+        manager.initialize_balance("alice", &CurrencyType::BasicNeeds, 100.0).unwrap();
+        let old = transaction("alice", "bob", 30.0, 100);
+        let recent = transaction("bob", "alice", 10.0, 200);
+        manager.process_transaction(&old).unwrap();
+        manager.process_transaction(&recent).unwrap();
+
+        assert_eq!(manager.get_shard_history(0).unwrap().full_since, None);
+        assert_eq!(manager.prune_history(150).unwrap(), 1);
+        assert_eq!(manager.transaction_availability(0, &old.hash()).unwrap(), HistoryAvailability::ReceiptOnly);
+        assert_eq!(manager.transaction_availability(0, &recent.hash()).unwrap(), HistoryAvailability::Full);
+        assert_eq!(manager.transaction_availability(0, "unknown").unwrap(), HistoryAvailability::Unknown);
+
+        let history = manager.get_shard_history(0).unwrap();
+        assert_eq!((history.full_since, history.transactions, history.receipts), (Some(150), 1, 1));
+        let flows: Vec<(&str, f64)> = history.checkpoint.net_flows.iter().map(|flow| (flow.address.as_str(), flow.amount.to_f64())).collect();
+        assert_eq!(flows, vec![("alice", -30.0), ("bob", 30.0)]);
+        assert_eq!(manager.get_shard_receipts(0).unwrap()[0].hash, old.hash());
+
+        // Pruning again folds into the same checkpoint and never moves it back
+        assert_eq!(manager.prune_history(250).unwrap(), 1);
+        assert_eq!(manager.prune_history(50).unwrap(), 0);
+        let history = manager.get_shard_history(0).unwrap();
+        assert_eq!((history.full_since, history.transactions, history.receipts), (Some(250), 0, 2));
+        let flows: Vec<(&str, f64)> = history.checkpoint.net_flows.iter().map(|flow| (flow.address.as_str(), flow.amount.to_f64())).collect();
+        assert_eq!(flows, vec![("alice", -20.0), ("bob", 20.0)]);
+        assert_eq!(manager.get_balance("alice", &CurrencyType::BasicNeeds).unwrap(), 80.0);
+    }
+}
@@ -0,0 +1,335 @@
+// File: crates/icn_smart_contracts/src/analysis.rs
+
+use icn_vm::Opcode;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeSet;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    /// Worth a look, but the contract may still be deployed.
+    Warning,
+    /// The contract fails or misbehaves whenever this code runs, so it is
+    /// not deployed.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FindingKind {
+    UnreachableCode,
+    /// A loop with no way out, or whose condition nothing in it changes.
+    UnboundedLoop,
+    UninitializedLoad,
+    StackUnderflow,
+    /// Paths reach the same instruction with different stack depths.
+    StackImbalance,
+    InvalidJump,
+    /// An opcode the VM always refuses to run.
+    ForbiddenOpcode,
+}
+
+/// One problem found in a contract's bytecode.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub kind: FindingKind,
+    /// The instruction the finding is about.
+    pub instruction: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} at instruction {}: {}", self.severity, self.instruction, self.message)
+    }
+}
+
+/// What the analyzer found, ordered by instruction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisReport {
+    pub findings: Vec<Finding>,
+}
+
+impl AnalysisReport {
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|finding| finding.severity == Severity::Error)
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|finding| finding.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(|finding| finding.severity == Severity::Warning)
+    }
+}
+
+/// Values an opcode pops and pushes. Calls are not run by the VM, so they
+/// are taken to leave the stack alone.
+fn stack_effect(opcode: &Opcode) -> (usize, usize) {
+    match opcode {
+        Opcode::Push(_) | Opcode::Load(_) | Opcode::StorageLoad(_) | Opcode::BlockTime | Opcode::NewList | Opcode::NewMap => (0, 1),
+        Opcode::Pop | Opcode::Store(_) | Opcode::StorageStore(_) | Opcode::JumpIf(_) | Opcode::CommEventOrganize => (1, 0),
+        Opcode::Not | Opcode::Len | Opcode::Keys | Opcode::GovProposalSubmit | Opcode::GetProposalStatus => (1, 1),
+        Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div | Opcode::Mod
+        | Opcode::Eq | Opcode::Neq | Opcode::Gt | Opcode::Lt | Opcode::Gte | Opcode::Lte
+        | Opcode::And | Opcode::Or | Opcode::Append | Opcode::Index | Opcode::Get | Opcode::Remove | Opcode::Contains
+        | Opcode::CreateProposal => (2, 1),
+        Opcode::Set => (3, 1),
+        Opcode::EconCurrencyMint | Opcode::CoopMemberAdd | Opcode::VoteOnProposal
+        | Opcode::AllocateResource | Opcode::UpdateReputation | Opcode::EmitEvent => (2, 0),
+        Opcode::Jump(_) | Opcode::Call(_) | Opcode::Return | Opcode::NetNodeConnect | Opcode::ChainBlockCreate => (0, 0),
+    }
+}
+
+/// Whether running `opcode` can change what a loop condition reads.
+/// The block time and proposal statuses hold still for a whole call.
+fn has_effects(opcode: &Opcode) -> bool {
+    !matches!(opcode,
+        Opcode::Push(_) | Opcode::Pop | Opcode::Load(_) | Opcode::StorageLoad(_)
+        | Opcode::Add | Opcode::Sub | Opcode::Mul | Opcode::Div | Opcode::Mod
+        | Opcode::Eq | Opcode::Neq | Opcode::Gt | Opcode::Lt | Opcode::Gte | Opcode::Lte
+        | Opcode::And | Opcode::Or | Opcode::Not | Opcode::Jump(_) | Opcode::JumpIf(_) | Opcode::Return
+        | Opcode::NewList | Opcode::NewMap | Opcode::Append | Opcode::Index | Opcode::Get | Opcode::Set
+        | Opcode::Remove | Opcode::Contains | Opcode::Len | Opcode::Keys
+        | Opcode::BlockTime | Opcode::GetProposalStatus)
+}
+
+/// Where control goes after `pc`.
+fn successors(program: &[Opcode], pc: usize) -> Vec<usize> {
+    match &program[pc] {
+        Opcode::Jump(target) => vec![*target],
+        Opcode::JumpIf(target) => vec![pc + 1, *target],
+        _ => vec![pc + 1],
+    }
+}
+
+/// What is known on entry to an instruction along every path to it.
+#[derive(Debug, Clone, PartialEq)]
+struct State {
+    depth: usize,
+    /// Variables stored on every path here.
+    stored: BTreeSet<String>,
+    /// Variables stored on at least one path here.
+    maybe_stored: BTreeSet<String>,
+}
+
+struct Analyzer<'a> {
+    program: &'a [Opcode],
+    findings: Vec<Finding>,
+}
+
+impl Analyzer<'_> {
+    fn report(&mut self, severity: Severity, kind: FindingKind, instruction: usize, message: String) {
+        let duplicate = self.findings.iter().any(|finding| finding.kind == kind && finding.instruction == instruction);
+        if !duplicate {
+            self.findings.push(Finding { severity, kind, instruction, message });
+        }
+    }
+
+    /// Walks every path from the start, merging states where paths meet,
+    /// and returns the state on entry to each instruction reached.
+    fn flow(&mut self, entry: State) -> Vec<Option<State>> {
+        let len = self.program.len();
+        let mut states: Vec<Option<State>> = vec![None; len];
+        let mut worklist = vec![0];
+        if len > 0 {
+            states[0] = Some(entry);
+        }
+        while let Some(pc) = worklist.pop() {
+            let Some(mut state) = states[pc].clone() else {
+                continue;
+            };
+            let opcode = &self.program[pc];
+            match opcode {
+                Opcode::Load(name) if !state.maybe_stored.contains(name) => {
+                    self.report(Severity::Error, FindingKind::UninitializedLoad, pc, format!("Variable {} is loaded before anything stores it", name));
+                }
+                Opcode::Load(name) if !state.stored.contains(name) => {
+                    self.report(Severity::Warning, FindingKind::UninitializedLoad, pc, format!("Variable {} is not stored on every path here", name));
+                }
+                Opcode::NetNodeConnect | Opcode::ChainBlockCreate => {
+                    self.report(Severity::Error, FindingKind::ForbiddenOpcode, pc, format!("The VM refuses to run {:?}", opcode));
+                }
+                _ => {}
+            }
+
+            let (pops, pushes) = stack_effect(opcode);
+            if state.depth < pops {
+                self.report(Severity::Error, FindingKind::StackUnderflow, pc, format!("{:?} needs {} values but the stack holds {}", opcode, pops, state.depth));
+                continue;
+            }
+            state.depth = state.depth - pops + pushes;
+            if let Opcode::Store(name) = opcode {
+                state.stored.insert(name.clone());
+                state.maybe_stored.insert(name.clone());
+            }
+
+            for next in successors(self.program, pc) {
+                if next > len {
+                    self.report(Severity::Error, FindingKind::InvalidJump, pc, format!("Jump to {} is past the end of the program", next));
+                    continue;
+                }
+                if next == len {
+                    if state.depth > 1 {
+                        self.report(Severity::Warning, FindingKind::StackImbalance, pc, format!("Leaves {} values on the stack where only the top is returned", state.depth));
+                    }
+                    continue;
+                }
+                let merged = match &states[next] {
+                    None => state.clone(),
+                    Some(existing) => {
+                        if existing.depth != state.depth {
+                            self.report(Severity::Error, FindingKind::StackImbalance, next, format!(
+                                "Reached with {} values on the stack from instruction {} but {} from another path", state.depth, pc, existing.depth
+                            ));
+                        }
+                        State {
+                            depth: existing.depth,
+                            stored: existing.stored.intersection(&state.stored).cloned().collect(),
+                            maybe_stored: existing.maybe_stored.union(&state.maybe_stored).cloned().collect(),
+                        }
+                    }
+                };
+                if states[next].as_ref() != Some(&merged) {
+                    states[next] = Some(merged);
+                    worklist.push(next);
+                }
+            }
+        }
+        states
+    }
+
+    fn check_reachability(&mut self, states: &[Option<State>]) {
+        let mut pc = 0;
+        while pc < states.len() {
+            if states[pc].is_some() {
+                pc += 1;
+                continue;
+            }
+            let start = pc;
+            while pc < states.len() && states[pc].is_none() {
+                pc += 1;
+            }
+            let message = if pc - start == 1 {
+                "This instruction can never run".to_string()
+            } else {
+                format!("Instructions {} to {} can never run", start, pc - 1)
+            };
+            self.report(Severity::Warning, FindingKind::UnreachableCode, start, message);
+        }
+    }
+
+    /// A backward jump closes a loop over `target..=pc`. Without a jump
+    /// out it only ends when gas runs out; with one, something in the loop
+    /// has to be able to change the condition.
+    fn check_loops(&mut self, states: &[Option<State>]) {
+        for (pc, opcode) in self.program.iter().enumerate() {
+            let target = match opcode {
+                Opcode::Jump(target) | Opcode::JumpIf(target) if *target <= pc => *target,
+                _ => continue,
+            };
+            if states[pc].is_none() {
+                continue;
+            }
+            let body = &self.program[target..=pc];
+            let exits = matches!(opcode, Opcode::JumpIf(_)) || body.iter().any(|opcode| match opcode {
+                Opcode::JumpIf(exit) | Opcode::Jump(exit) => *exit < target || *exit > pc,
+                _ => false,
+            });
+            if !exits {
+                self.report(Severity::Error, FindingKind::UnboundedLoop, pc, format!(
+                    "The loop over instructions {} to {} has no way out and only stops when gas runs out", target, pc
+                ));
+            } else if !body.iter().any(has_effects) {
+                self.report(Severity::Warning, FindingKind::UnboundedLoop, pc, format!(
+                    "Nothing in the loop over instructions {} to {} changes its condition, so it may never exit", target, pc
+                ));
+            }
+        }
+    }
+}
+
+/// Checks `program` before it is deployed. It starts with `inputs` on the
+/// stack and the variables in `parameters` set, which is how functions
+/// receive their arguments.
+pub fn analyze(program: &[Opcode], inputs: usize, parameters: &[String]) -> AnalysisReport {
+    let mut analyzer = Analyzer { program, findings: Vec::new() };
+    let parameters: BTreeSet<String> = parameters.iter().cloned().collect();
+    let states = analyzer.flow(State { depth: inputs, stored: parameters.clone(), maybe_stored: parameters });
+    analyzer.check_reachability(&states);
+    analyzer.check_loops(&states);
+    let mut findings = analyzer.findings;
+    findings.sort_by(|a, b| a.instruction.cmp(&b.instruction).then(b.severity.cmp(&a.severity)));
+    AnalysisReport { findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use icn_vm::Value;
+
+    fn kinds(report: &AnalysisReport) -> Vec<(Severity, FindingKind, usize)> {
+        report.findings.iter().map(|finding| (finding.severity, finding.kind, finding.instruction)).collect()
+    }
+
+    #[test]
+    fn test_clean_program_has_no_findings() {
+        let program = vec![
+            Opcode::Push(Value::Int(3)),
+            Opcode::Store("n".to_string()),
+            // Count n down to zero
+            Opcode::Load("n".to_string()),
+            Opcode::Push(Value::Int(0)),
+            Opcode::Lte,
+            Opcode::JumpIf(11),
+            Opcode::Load("n".to_string()),
+            Opcode::Push(Value::Int(1)),
+            Opcode::Sub,
+            Opcode::Store("n".to_string()),
+            Opcode::Jump(2),
+            Opcode::Load("n".to_string()),
+        ];
+        assert_eq!(analyze(&program, 0, &[]), AnalysisReport::default());
+    }
+
+    #[test]
+    fn test_finds_each_kind_of_problem() {
+        let program = vec![
+            Opcode::Load("x".to_string()),
+            Opcode::Jump(3),
+            Opcode::Push(Value::Int(1)),
+            Opcode::Pop,
+            Opcode::Add,
+        ];
+        assert_eq!(kinds(&analyze(&program, 0, &[])), vec![
+            (Severity::Error, FindingKind::UninitializedLoad, 0),
+            (Severity::Warning, FindingKind::UnreachableCode, 2),
+            (Severity::Error, FindingKind::StackUnderflow, 4),
+        ]);
+        // Arguments count as stored and on the stack
+        assert!(!analyze(&program[..1], 0, &["x".to_string()]).has_errors());
+
+        let spin = vec![Opcode::Push(Value::Int(1)), Opcode::Pop, Opcode::Jump(0)];
+        assert_eq!(kinds(&analyze(&spin, 0, &[])), vec![(Severity::Error, FindingKind::UnboundedLoop, 2)]);
+
+        let stuck = vec![Opcode::Push(Value::Bool(true)), Opcode::JumpIf(0)];
+        assert_eq!(kinds(&analyze(&stuck, 0, &[])), vec![(Severity::Warning, FindingKind::UnboundedLoop, 1)]);
+
+        let growing = vec![Opcode::Push(Value::Int(1)), Opcode::Push(Value::Bool(true)), Opcode::JumpIf(0)];
+        let report = analyze(&growing, 0, &[]);
+        assert!(report.has_errors());
+        assert_eq!(report.errors().next().unwrap().kind, FindingKind::StackImbalance);
+
+        let conditional = vec![
+            Opcode::Push(Value::Bool(true)),
+            Opcode::JumpIf(3),
+            Opcode::Push(Value::Int(1)),
+            Opcode::Push(Value::Int(2)),
+            Opcode::Jump(9),
+        ];
+        let report = analyze(&conditional, 0, &[]);
+        assert_eq!(report.errors().map(|finding| finding.kind).collect::<Vec<_>>(), vec![FindingKind::StackImbalance, FindingKind::InvalidJump]);
+        assert_eq!(report.warnings().count(), 0);
+    }
+}
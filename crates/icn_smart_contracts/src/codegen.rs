@@ -0,0 +1,221 @@
+// File: crates/icn_smart_contracts/src/codegen.rs
+
+use crate::{ContractABI, ContractFunction, ContractParameter, ContractValueType, SmartContractExecutor};
+use icn_common::{IcnResult, IcnError};
+use icn_vm::Value;
+use std::fmt::Write;
+
+/// Anything generated clients can send contract calls through, such as a
+/// local executor or an RPC connection to a node.
+pub trait ContractCaller {
+    fn call(&mut self, contract_id: &str, function: &str, args: Vec<Value>) -> IcnResult<Option<Value>>;
+}
+
+impl ContractCaller for SmartContractExecutor {
+    fn call(&mut self, contract_id: &str, function: &str, args: Vec<Value>) -> IcnResult<Option<Value>> {
+        self.execute_contract(contract_id, function, args)
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "yield",
+];
+
+/// Generates Rust source for a typed client of a contract: a
+/// `<Name>Client` with one method per ABI function, and a struct per event.
+///
+/// Lists and maps are passed as VM values whatever their element type, and
+/// maps must be keyed by strings. Functions with more than one output are
+/// rejected.
+pub fn generate_client(contract_name: &str, abi: &ContractABI) -> IcnResult<String> {
+    let client = format!("{}Client", pascal_case(contract_name));
+    let mut out = String::new();
+
+    writeln!(out, "// Generated from the ABI of contract `{}`. Do not edit.", contract_name).unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "use icn_common::IcnResult;").unwrap();
+    writeln!(out, "use icn_smart_contracts::ContractCaller;").unwrap();
+    writeln!(out, "use icn_vm::Value;").unwrap();
+    writeln!(out, "use std::collections::BTreeMap;").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "pub struct {}<'a, C: ContractCaller> {{", client).unwrap();
+    writeln!(out, "    caller: &'a mut C,").unwrap();
+    writeln!(out, "    contract_id: String,").unwrap();
+    writeln!(out, "}}").unwrap();
+    writeln!(out).unwrap();
+    writeln!(out, "impl<'a, C: ContractCaller> {}<'a, C> {{", client).unwrap();
+    writeln!(out, "    pub fn new(caller: &'a mut C, contract_id: impl Into<String>) -> Self {{").unwrap();
+    writeln!(out, "        {} {{ caller, contract_id: contract_id.into() }}", client).unwrap();
+    writeln!(out, "    }}").unwrap();
+    for function in &abi.functions {
+        writeln!(out).unwrap();
+        write_method(&mut out, function)?;
+    }
+    writeln!(out, "}}").unwrap();
+
+    for event in &abi.events {
+        writeln!(out).unwrap();
+        writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+        writeln!(out, "pub struct {}Event {{", pascal_case(&event.name)).unwrap();
+        for parameter in &event.parameters {
+            writeln!(out, "    pub {}: {},", rust_ident(&parameter.name), rust_type(parameter)?).unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    Ok(out)
+}
+
+fn write_method(out: &mut String, function: &ContractFunction) -> IcnResult<()> {
+    let params = function.inputs.iter()
+        .map(|input| Ok(format!(", {}: {}", rust_ident(&input.name), rust_type(input)?)))
+        .collect::<IcnResult<String>>()?;
+    let args = function.inputs.iter()
+        .map(|input| Ok(format!("Value::{}({})", value_variant(input)?, rust_ident(&input.name))))
+        .collect::<IcnResult<Vec<String>>>()?
+        .join(", ");
+    let output = match function.outputs.as_slice() {
+        [] => None,
+        [output] => Some(output),
+        _ => return Err(IcnError::SmartContract(format!("Function {} returns more than one value", function.name))),
+    };
+    let return_type = output.map_or(Ok("()".to_string()), rust_type)?;
+
+    writeln!(out, "    pub fn {}(&mut self{}) -> IcnResult<{}> {{", rust_ident(&function.name), params, return_type).unwrap();
+    writeln!(out, "        let args = vec![{}];", args).unwrap();
+    match output {
+        None => {
+            writeln!(out, "        self.caller.call(&self.contract_id, \"{}\", args)?;", function.name).unwrap();
+            writeln!(out, "        Ok(())").unwrap();
+        }
+        Some(output) => {
+            writeln!(out, "        match self.caller.call(&self.contract_id, \"{}\", args)? {{", function.name).unwrap();
+            writeln!(out, "            Some(Value::{}(value)) => Ok(value),", value_variant(output)?).unwrap();
+            writeln!(out, "            other => Err(icn_common::IcnError::SmartContract(format!(\"Unexpected return value from {}: {{:?}}\", other))),", function.name).unwrap();
+            writeln!(out, "        }}").unwrap();
+        }
+    }
+    writeln!(out, "    }}").unwrap();
+    Ok(())
+}
+
+fn rust_type(parameter: &ContractParameter) -> IcnResult<String> {
+    let rust_type = match &parameter.param_type {
+        ContractValueType::Integer => "i64",
+        ContractValueType::Float => "f64",
+        ContractValueType::Boolean => "bool",
+        ContractValueType::String | ContractValueType::Address => "String",
+        ContractValueType::List(_) => "Vec<Value>",
+        ContractValueType::Map(key, _) if **key == ContractValueType::String => "BTreeMap<String, Value>",
+        ContractValueType::Map(_, _) => {
+            return Err(IcnError::SmartContract(format!(
+                "Parameter {} of type {} cannot be passed to the VM, whose maps are keyed by strings", parameter.name, parameter.param_type
+            )));
+        }
+    };
+    Ok(rust_type.to_string())
+}
+
+/// The `icn_vm::Value` variant carrying a parameter's Rust type.
+fn value_variant(parameter: &ContractParameter) -> IcnResult<&'static str> {
+    Ok(match rust_type(parameter)?.as_str() {
+        "i64" => "Int",
+        "f64" => "Float",
+        "bool" => "Bool",
+        "Vec<Value>" => "List",
+        "BTreeMap<String, Value>" => "Map",
+        _ => "String",
+    })
+}
+
+fn rust_ident(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    name.split(|c: char| c == '_' || c == '-' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ContractEvent;
+
+    fn parameter(name: &str, param_type: ContractValueType) -> ContractParameter {
+        ContractParameter { name: name.to_string(), param_type }
+    }
+
+    fn token_abi() -> ContractABI {
+        ContractABI {
+            functions: vec![
+                ContractFunction {
+                    name: "transfer".to_string(),
+                    inputs: vec![
+                        parameter("from", ContractValueType::Address),
+                        parameter("to", ContractValueType::Address),
+                        parameter("amount", ContractValueType::Integer),
+                    ],
+                    outputs: vec![],
+                },
+                ContractFunction {
+                    name: "balance_of".to_string(),
+                    inputs: vec![parameter("type", ContractValueType::String)],
+                    outputs: vec![parameter("balance", ContractValueType::Float)],
+                },
+            ],
+            events: vec![ContractEvent {
+                name: "transfer_made".to_string(),
+                parameters: vec![parameter("amount", ContractValueType::Integer)],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_generate_client() {
+        let code = generate_client("community_token", &token_abi()).unwrap();
+
+        assert!(code.contains("pub struct CommunityTokenClient<'a, C: ContractCaller>"));
+        assert!(code.contains("pub fn transfer(&mut self, from: String, to: String, amount: i64) -> IcnResult<()>"));
+        assert!(code.contains("let args = vec![Value::String(from), Value::String(to), Value::Int(amount)];"));
+        assert!(code.contains("pub fn balance_of(&mut self, r#type: String) -> IcnResult<f64>"));
+        assert!(code.contains("Some(Value::Float(value)) => Ok(value),"));
+        assert!(code.contains("pub struct TransferMadeEvent {\n    pub amount: i64,\n}"));
+    }
+
+    #[test]
+    fn test_collections_are_passed_as_vm_values() {
+        let mut abi = token_abi();
+        abi.functions[0].inputs.push(parameter("memo", ContractValueType::List(Box::new(ContractValueType::String))));
+        abi.functions[1].outputs[0].param_type = ContractValueType::Map(Box::new(ContractValueType::String), Box::new(ContractValueType::Float));
+        let code = generate_client("token", &abi).unwrap();
+
+        assert!(code.contains("amount: i64, memo: Vec<Value>) -> IcnResult<()>"));
+        assert!(code.contains("Value::Int(amount), Value::List(memo)];"));
+        assert!(code.contains("-> IcnResult<BTreeMap<String, Value>>"));
+        assert!(code.contains("Some(Value::Map(value)) => Ok(value),"));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_signatures() {
+        let mut abi = token_abi();
+        abi.functions[0].inputs.push(parameter("shares", ContractValueType::Map(Box::new(ContractValueType::Address), Box::new(ContractValueType::Integer))));
+        assert!(generate_client("token", &abi).is_err());
+
+        let mut abi = token_abi();
+        abi.functions[1].outputs.push(parameter("frozen", ContractValueType::Boolean));
+        assert!(generate_client("token", &abi).is_err());
+    }
+}
@@ -0,0 +1,103 @@
+// File: crates/icn_smart_contracts/src/events.rs
+
+use crate::ContractABI;
+use icn_common::{IcnResult, IcnError};
+use icn_vm::HostInterface;
+use serde::{Serialize, Deserialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// An event a contract emitted during a successful call.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmittedEvent {
+    pub contract_id: String,
+    pub name: String,
+    /// Values by parameter name, when the ABI declares the event and the
+    /// data holds one comma-separated value per parameter.
+    pub params: BTreeMap<String, String>,
+    /// The data exactly as the contract emitted it.
+    pub data: String,
+}
+
+impl EmittedEvent {
+    /// Names the event's values after the parameters of the ABI event with
+    /// the same name. Undeclared events, or data with the wrong number of
+    /// values, keep only the raw data.
+    pub fn decode(contract_id: &str, abi: &ContractABI, name: &str, data: &str) -> Self {
+        let values: Vec<&str> = data.split(',').map(str::trim).collect();
+        let params = abi.events.iter()
+            .find(|event| event.name == name)
+            .filter(|event| event.parameters.len() == values.len())
+            .map(|event| event.parameters.iter()
+                .zip(values)
+                .map(|(parameter, value)| (parameter.name.clone(), value.to_string()))
+                .collect())
+            .unwrap_or_default();
+        EmittedEvent { contract_id: contract_id.to_string(), name: name.to_string(), params, data: data.to_string() }
+    }
+}
+
+/// The host contracts run against. Contracts may emit events and read the
+/// block time but reach no further into the node, so every other system
+/// opcode fails.
+#[derive(Default)]
+pub(crate) struct EventCollector {
+    events: Arc<Mutex<Vec<(String, String)>>>,
+    block_time: Option<i64>,
+}
+
+impl EventCollector {
+    pub(crate) fn with_block_time(mut self, block_time: Option<i64>) -> Self {
+        self.block_time = block_time;
+        self
+    }
+
+    /// Handle to the `(name, data)` pairs emitted so far, which stays
+    /// readable after the collector is handed to the VM.
+    pub(crate) fn events(&self) -> Arc<Mutex<Vec<(String, String)>>> {
+        self.events.clone()
+    }
+}
+
+fn unavailable<T>(operation: &str) -> IcnResult<T> {
+    Err(IcnError::SmartContract(format!("Contracts cannot {}", operation)))
+}
+
+impl HostInterface for EventCollector {
+    fn mint_currency(&mut self, _currency_type: &str, _amount: f64) -> IcnResult<()> {
+        unavailable("mint currency")
+    }
+
+    fn vote_on_proposal(&mut self, _proposal_id: &str, _in_favor: bool) -> IcnResult<()> {
+        unavailable("vote on proposals")
+    }
+
+    fn allocate_resource(&mut self, _resource: &str, _amount: i64) -> IcnResult<()> {
+        unavailable("allocate resources")
+    }
+
+    fn update_reputation(&mut self, _address: &str, _change: i64) -> IcnResult<()> {
+        unavailable("update reputation")
+    }
+
+    fn create_proposal(&mut self, _title: &str, _description: &str) -> IcnResult<String> {
+        unavailable("create proposals")
+    }
+
+    fn get_proposal_status(&self, _proposal_id: &str) -> IcnResult<String> {
+        unavailable("read proposals")
+    }
+
+    fn add_cooperative_member(&mut self, _coop_id: &str, _member_id: &str) -> IcnResult<()> {
+        unavailable("add cooperative members")
+    }
+
+    fn emit_event(&mut self, name: &str, data: &str) -> IcnResult<()> {
+        self.events.lock().unwrap().push((name.to_string(), data.to_string()));
+        Ok(())
+    }
+
+    fn block_time(&self) -> IcnResult<i64> {
+        self.block_time.map_or_else(|| unavailable("read the block time outside a block"), Ok)
+    }
+}
@@ -1,17 +1,32 @@
 // File: crates/icn_smart_contracts/src/lib.rs
 
+pub mod analysis;
+pub mod codegen;
+pub mod events;
+pub mod permissions;
+pub mod testing;
+
+pub use crate::analysis::{analyze, AnalysisReport, Finding, FindingKind, Severity};
+pub use crate::codegen::{generate_client, ContractCaller};
+pub use crate::events::EmittedEvent;
+pub use crate::permissions::{AccessPolicy, Caller, PermissionAuthority};
+
+use pest::iterators::Pair;
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest::Parser;
 use pest_derive::Parser;
 use icn_common::{IcnResult, IcnError};
-use icn_vm::{CoopVM, Opcode, Value};
-use std::collections::HashMap;
+use crate::events::EventCollector;
+use icn_vm::{ContractStorage, CoopVM, ExecutionLimits, HostInterface, Opcode, Value};
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
 
 #[derive(Parser)]
 #[grammar = "contract.pest"]
 struct ContractParser;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum SmartContractType {
     AssetTransfer,
     VotingSystem,
@@ -25,34 +40,87 @@ pub struct CompiledContract {
     contract_type: SmartContractType,
     bytecode: Vec<Opcode>,
     abi: ContractABI,
+    /// Where each function starts in `bytecode`.
+    entry_points: HashMap<String, usize>,
+    /// Where the top-level statements start, run once on deploy to set up
+    /// the contract's storage.
+    initializer: Option<usize>,
 }
 
-#[derive(Debug)]
+impl CompiledContract {
+    /// Wraps bytecode built by another toolchain, or by hand in tests.
+    pub fn new(contract_type: SmartContractType, bytecode: Vec<Opcode>, abi: ContractABI) -> Self {
+        CompiledContract { contract_type, bytecode, abi, entry_points: HashMap::new(), initializer: None }
+    }
+
+    /// Where `function` starts. Bytecode built without an entry table, by
+    /// hand or by another toolchain, starts every function at the top.
+    pub fn entry_point(&self, function: &str) -> usize {
+        self.entry_points.get(function).copied().unwrap_or(0)
+    }
+
+    pub fn initializer(&self) -> Option<usize> {
+        self.initializer
+    }
+
+    pub fn contract_type(&self) -> &SmartContractType {
+        &self.contract_type
+    }
+
+    pub fn abi(&self) -> &ContractABI {
+        &self.abi
+    }
+
+    /// Checks the bytecode for mistakes. Calls start with their arguments
+    /// on the stack, and parameters may be loaded by name.
+    pub fn analyze(&self) -> AnalysisReport {
+        let inputs = self.abi.functions.iter().map(|function| function.inputs.len()).max().unwrap_or(0);
+        let parameters: Vec<String> = self.abi.functions.iter()
+            .flat_map(|function| function.inputs.iter().map(|input| input.name.clone()))
+            .collect();
+        analyze(&self.bytecode, inputs, &parameters)
+    }
+}
+
+/// The callable interface of a compiled contract.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContractABI {
-    functions: Vec<ContractFunction>,
-    events: Vec<ContractEvent>,
+    pub functions: Vec<ContractFunction>,
+    pub events: Vec<ContractEvent>,
 }
 
-#[derive(Debug)]
+impl ContractABI {
+    pub fn to_json(&self) -> IcnResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| IcnError::SmartContract(format!("Failed to serialize ABI: {}", e)))
+    }
+
+    pub fn from_json(json: &str) -> IcnResult<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| IcnError::SmartContract(format!("Invalid ABI: {}", e)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContractFunction {
-    name: String,
-    inputs: Vec<ContractParameter>,
-    outputs: Vec<ContractParameter>,
+    pub name: String,
+    pub inputs: Vec<ContractParameter>,
+    pub outputs: Vec<ContractParameter>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContractEvent {
-    name: String,
-    parameters: Vec<ContractParameter>,
+    pub name: String,
+    pub parameters: Vec<ContractParameter>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContractParameter {
-    name: String,
-    param_type: ContractValueType,
+    pub name: String,
+    pub param_type: ContractValueType,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum ContractValueType {
     Integer,
     Float,
@@ -79,31 +147,61 @@ impl fmt::Display for ContractValueType {
 
 pub struct NaturalLanguageCompiler;
 
+/// The names a function keeps in VM memory: its parameters and what it
+/// declares with `let`. Every other name is contract state in storage.
+#[derive(Default)]
+struct Scope {
+    locals: HashSet<String>,
+}
+
+impl Scope {
+    fn load(&self, name: &str) -> Opcode {
+        if self.locals.contains(name) {
+            Opcode::Load(name.to_string())
+        } else {
+            Opcode::StorageLoad(name.to_string())
+        }
+    }
+
+    fn store(&self, name: String) -> Opcode {
+        if self.locals.contains(&name) {
+            Opcode::Store(name)
+        } else {
+            Opcode::StorageStore(name)
+        }
+    }
+}
+
 impl NaturalLanguageCompiler {
     pub fn compile(input: &str) -> IcnResult<CompiledContract> {
-        let pairs = ContractParser::parse(Rule::contract, input)
-            .map_err(|e| IcnError::SmartContract(format!("Parsing error: {}", e)))?;
+        let contract = ContractParser::parse(Rule::contract, input)
+            .map_err(|e| IcnError::SmartContract(format!("Parsing error: {}", e)))?
+            .next()
+            .ok_or_else(|| IcnError::SmartContract("Empty contract".into()))?;
 
         let mut bytecode = Vec::new();
         let mut abi = ContractABI {
             functions: Vec::new(),
             events: Vec::new(),
         };
+        let mut entry_points = HashMap::new();
         let mut contract_type = SmartContractType::CustomLogic;
+        let mut top_level = Vec::new();
 
-        for pair in pairs {
+        for pair in contract.into_inner() {
             match pair.as_rule() {
                 Rule::contract_type => {
-                    contract_type = Self::parse_contract_type(pair.into_inner().next().unwrap().as_str())?;
+                    contract_type = Self::parse_contract_type(Self::first_inner(pair)?.as_str())?;
                 }
                 Rule::function_definition => {
-                    let (func_bytecode, func_abi) = Self::compile_function(pair)?;
-                    bytecode.extend(func_bytecode);
-                    abi.functions.push(func_abi);
-                }
-                Rule::statement => {
-                    bytecode.extend(Self::compile_statement(pair)?);
+                    let entry = bytecode.len();
+                    let function = Self::compile_function(pair, &mut bytecode)?;
+                    if entry_points.insert(function.name.clone(), entry).is_some() {
+                        return Err(IcnError::SmartContract(format!("Function {} is defined twice", function.name)));
+                    }
+                    abi.functions.push(function);
                 }
+                Rule::statement => top_level.push(pair),
                 Rule::event_definition => {
                     abi.events.push(Self::compile_event(pair)?);
                 }
@@ -111,10 +209,25 @@ impl NaturalLanguageCompiler {
             }
         }
 
+        // Top-level statements go after the functions, as one initializer
+        let initializer = if top_level.is_empty() {
+            None
+        } else {
+            let entry = bytecode.len();
+            let mut scope = Scope::default();
+            for statement in top_level {
+                Self::compile_statement(statement, &mut bytecode, &mut scope)?;
+            }
+            bytecode.push(Opcode::Return);
+            Some(entry)
+        };
+
         Ok(CompiledContract {
             contract_type,
             bytecode,
             abi,
+            entry_points,
+            initializer,
         })
     }
 
@@ -129,8 +242,16 @@ impl NaturalLanguageCompiler {
         }
     }
 
-    fn compile_function(pair: pest::iterators::Pair<Rule>) -> IcnResult<(Vec<Opcode>, ContractFunction)> {
-        let mut bytecode = Vec::new();
+    fn first_inner(pair: Pair<Rule>) -> IcnResult<Pair<Rule>> {
+        let rule = pair.as_rule();
+        pair.into_inner().next()
+            .ok_or_else(|| IcnError::SmartContract(format!("Malformed {:?}", rule)))
+    }
+
+    /// Compiles a function into `bytecode`. Its arguments arrive on the
+    /// stack, last on top, and are stored under their parameter names.
+    fn compile_function(pair: Pair<Rule>, bytecode: &mut Vec<Opcode>) -> IcnResult<ContractFunction> {
+        let mut scope = Scope::default();
         let mut function = ContractFunction {
             name: String::new(),
             inputs: Vec::new(),
@@ -141,49 +262,85 @@ impl NaturalLanguageCompiler {
             match inner_pair.as_rule() {
                 Rule::identifier => function.name = inner_pair.as_str().to_string(),
                 Rule::parameter_list => {
-                    for param in inner_pair.into_inner() {
-                        let param_pair = param.into_inner().next().unwrap();
-                        let param_name = param_pair.as_str().to_string();
-                        let param_type = Self::parse_type(param_pair.into_inner().next().unwrap().as_str())?;
-                        function.inputs.push(ContractParameter { name: param_name, param_type });
+                    function.inputs = Self::compile_parameters(inner_pair)?;
+                    for input in function.inputs.iter().rev() {
+                        scope.locals.insert(input.name.clone());
+                        bytecode.push(Opcode::Store(input.name.clone()));
                     }
                 }
-                Rule::statement => bytecode.extend(Self::compile_statement(inner_pair)?),
+                Rule::block => Self::compile_statement(inner_pair, bytecode, &mut scope)?,
                 _ => {}
             }
         }
+        bytecode.push(Opcode::Return);
 
-        Ok((bytecode, function))
+        Ok(function)
     }
 
-    fn compile_statement(pair: pest::iterators::Pair<Rule>) -> IcnResult<Vec<Opcode>> {
-        let mut bytecode = Vec::new();
+    fn compile_parameters(pair: Pair<Rule>) -> IcnResult<Vec<ContractParameter>> {
+        pair.into_inner()
+            .map(|param| {
+                let mut inner = param.into_inner();
+                let (Some(name), Some(param_type)) = (inner.next(), inner.next()) else {
+                    return Err(IcnError::SmartContract("Malformed parameter".into()));
+                };
+                Ok(ContractParameter { name: name.as_str().to_string(), param_type: Self::parse_type(param_type.as_str())? })
+            })
+            .collect()
+    }
 
+    /// Compiles a statement or block into `bytecode`, so jump targets are
+    /// positions in the whole program.
+    fn compile_statement(pair: Pair<Rule>, bytecode: &mut Vec<Opcode>, scope: &mut Scope) -> IcnResult<()> {
         match pair.as_rule() {
+            Rule::statement => Self::compile_statement(Self::first_inner(pair)?, bytecode, scope)?,
+            Rule::block => {
+                for statement in pair.into_inner() {
+                    Self::compile_statement(statement, bytecode, scope)?;
+                }
+            }
             Rule::assignment => {
-                let mut inner = pair.into_inner();
-                let var_name = inner.next().unwrap().as_str().to_string();
-                let expr = inner.next().unwrap();
-                bytecode.extend(Self::compile_expression(expr)?);
-                bytecode.push(Opcode::Store(var_name));
+                let mut inner = pair.into_inner().peekable();
+                let declared = inner.next_if(|pair| pair.as_rule() == Rule::let_keyword).is_some();
+                let (Some(target), Some(expr)) = (inner.next(), inner.next()) else {
+                    return Err(IcnError::SmartContract("Malformed assignment".into()));
+                };
+                let mut target = target.into_inner();
+                let var_name = target.next().map(|name| name.as_str().to_string())
+                    .ok_or_else(|| IcnError::SmartContract("Malformed assignment".into()))?;
+                match target.next() {
+                    Some(key) => {
+                        bytecode.push(scope.load(&var_name));
+                        bytecode.extend(Self::compile_expression(key, scope)?);
+                        bytecode.extend(Self::compile_expression(expr, scope)?);
+                        bytecode.push(Opcode::Set);
+                    }
+                    None => bytecode.extend(Self::compile_expression(expr, scope)?),
+                }
+                if declared {
+                    scope.locals.insert(var_name.clone());
+                }
+                bytecode.push(scope.store(var_name));
             }
             Rule::if_statement => {
                 let mut inner = pair.into_inner();
-                let condition = inner.next().unwrap();
-                let if_block = inner.next().unwrap();
+                let (Some(condition), Some(if_block)) = (inner.next(), inner.next()) else {
+                    return Err(IcnError::SmartContract("Malformed if statement".into()));
+                };
                 let else_block = inner.next();
 
-                bytecode.extend(Self::compile_expression(condition)?);
+                bytecode.extend(Self::compile_expression(condition, scope)?);
+                bytecode.push(Opcode::Not);
                 let jump_to_else = bytecode.len();
                 bytecode.push(Opcode::JumpIf(0)); // Placeholder, will be updated later
 
-                bytecode.extend(Self::compile_statement(if_block)?);
+                Self::compile_statement(if_block, bytecode, scope)?;
                 let jump_to_end = bytecode.len();
                 bytecode.push(Opcode::Jump(0)); // Placeholder, will be updated later
 
                 let else_start = bytecode.len();
                 if let Some(else_block) = else_block {
-                    bytecode.extend(Self::compile_statement(else_block)?);
+                    Self::compile_statement(else_block, bytecode, scope)?;
                 }
                 let end = bytecode.len();
 
@@ -192,89 +349,123 @@ impl NaturalLanguageCompiler {
             }
             Rule::while_loop => {
                 let mut inner = pair.into_inner();
-                let condition = inner.next().unwrap();
-                let body = inner.next().unwrap();
+                let (Some(condition), Some(body)) = (inner.next(), inner.next()) else {
+                    return Err(IcnError::SmartContract("Malformed while loop".into()));
+                };
 
                 let loop_start = bytecode.len();
-                bytecode.extend(Self::compile_expression(condition)?);
+                bytecode.extend(Self::compile_expression(condition, scope)?);
+                bytecode.push(Opcode::Not);
                 let jump_to_end = bytecode.len();
                 bytecode.push(Opcode::JumpIf(0)); // Placeholder, will be updated later
 
-                bytecode.extend(Self::compile_statement(body)?);
+                Self::compile_statement(body, bytecode, scope)?;
                 bytecode.push(Opcode::Jump(loop_start));
 
                 let end = bytecode.len();
                 bytecode[jump_to_end] = Opcode::JumpIf(end);
             }
+            Rule::emit_statement => {
+                let mut inner = pair.into_inner();
+                let name = inner.next()
+                    .ok_or_else(|| IcnError::SmartContract("Malformed emit statement".into()))?;
+                bytecode.push(Opcode::Push(Value::String(name.as_str().to_string())));
+                bytecode.push(Opcode::NewList);
+                for arg in inner {
+                    bytecode.extend(Self::compile_expression(arg, scope)?);
+                    bytecode.push(Opcode::Append);
+                }
+                bytecode.push(Opcode::EmitEvent);
+            }
+            Rule::return_statement => {
+                if let Some(value) = pair.into_inner().next() {
+                    bytecode.extend(Self::compile_expression(value, scope)?);
+                }
+                bytecode.push(Opcode::Return);
+            }
             Rule::function_call => {
-                bytecode.extend(Self::compile_function_call(pair)?);
+                bytecode.extend(Self::compile_function_call(pair, scope)?);
             }
             _ => return Err(IcnError::SmartContract("Unsupported statement type".into())),
         }
 
-        Ok(bytecode)
+        Ok(())
     }
 
-    fn compile_expression(pair: pest::iterators::Pair<Rule>) -> IcnResult<Vec<Opcode>> {
-        let mut bytecode = Vec::new();
+    fn compile_expression(pair: Pair<Rule>, scope: &Scope) -> IcnResult<Vec<Opcode>> {
+        let pratt = PrattParser::new()
+            .op(Op::infix(Rule::or, Assoc::Left))
+            .op(Op::infix(Rule::and, Assoc::Left))
+            .op(Op::infix(Rule::eq, Assoc::Left) | Op::infix(Rule::neq, Assoc::Left))
+            .op(Op::infix(Rule::lt, Assoc::Left) | Op::infix(Rule::lte, Assoc::Left)
+                | Op::infix(Rule::gt, Assoc::Left) | Op::infix(Rule::gte, Assoc::Left))
+            .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::sub, Assoc::Left))
+            .op(Op::infix(Rule::mul, Assoc::Left) | Op::infix(Rule::div, Assoc::Left));
+
+        pratt
+            .map_primary(|primary| Self::compile_term(primary, scope))
+            .map_infix(|left, op, right| {
+                let mut bytecode = left?;
+                bytecode.extend(right?);
+                bytecode.push(match op.as_rule() {
+                    Rule::add => Opcode::Add,
+                    Rule::sub => Opcode::Sub,
+                    Rule::mul => Opcode::Mul,
+                    Rule::div => Opcode::Div,
+                    Rule::eq => Opcode::Eq,
+                    Rule::neq => Opcode::Neq,
+                    Rule::gt => Opcode::Gt,
+                    Rule::lt => Opcode::Lt,
+                    Rule::gte => Opcode::Gte,
+                    Rule::lte => Opcode::Lte,
+                    Rule::and => Opcode::And,
+                    Rule::or => Opcode::Or,
+                    _ => return Err(IcnError::SmartContract(format!("Unsupported operator: {}", op.as_str()))),
+                });
+                Ok(bytecode)
+            })
+            .parse(pair.into_inner())
+    }
 
+    fn compile_term(pair: Pair<Rule>, scope: &Scope) -> IcnResult<Vec<Opcode>> {
         match pair.as_rule() {
-            Rule::literal => {
-                let value = Self::parse_literal(pair)?;
-                bytecode.push(Opcode::Push(value));
-            }
-            Rule::identifier => {
-                let var_name = pair.as_str().to_string();
-                bytecode.push(Opcode::Load(var_name));
-            }
-            Rule::binary_operation => {
+            Rule::literal => Ok(vec![Opcode::Push(Self::parse_literal(Self::first_inner(pair)?)?)]),
+            Rule::identifier => Ok(vec![scope.load(pair.as_str())]),
+            Rule::index => {
                 let mut inner = pair.into_inner();
-                let left = inner.next().unwrap();
-                let op = inner.next().unwrap();
-                let right = inner.next().unwrap();
-
-                bytecode.extend(Self::compile_expression(left)?);
-                bytecode.extend(Self::compile_expression(right)?);
-
-                match op.as_str() {
-                    "+" => bytecode.push(Opcode::Add),
-                    "-" => bytecode.push(Opcode::Sub),
-                    "*" => bytecode.push(Opcode::Mul),
-                    "/" => bytecode.push(Opcode::Div),
-                    "==" => bytecode.push(Opcode::Eq),
-                    "!=" => bytecode.push(Opcode::Neq),
-                    ">" => bytecode.push(Opcode::Gt),
-                    "<" => bytecode.push(Opcode::Lt),
-                    ">=" => bytecode.push(Opcode::Gte),
-                    "<=" => bytecode.push(Opcode::Lte),
-                    "&&" => bytecode.push(Opcode::And),
-                    "||" => bytecode.push(Opcode::Or),
-                    _ => return Err(IcnError::SmartContract(format!("Unsupported operator: {}", op.as_str()))),
-                }
+                let (Some(name), Some(key)) = (inner.next(), inner.next()) else {
+                    return Err(IcnError::SmartContract("Malformed index".into()));
+                };
+                let mut bytecode = vec![scope.load(name.as_str())];
+                bytecode.extend(Self::compile_expression(key, scope)?);
+                bytecode.push(Opcode::Get);
+                Ok(bytecode)
             }
-            Rule::function_call => {
-                bytecode.extend(Self::compile_function_call(pair)?);
-            }
-            _ => return Err(IcnError::SmartContract("Unsupported expression type".into())),
+            Rule::empty_list => Ok(vec![Opcode::NewList]),
+            Rule::empty_map => Ok(vec![Opcode::NewMap]),
+            Rule::function_call => Self::compile_function_call(pair, scope),
+            Rule::expression => Self::compile_expression(pair, scope),
+            _ => Err(IcnError::SmartContract("Unsupported expression type".into())),
         }
-
-        Ok(bytecode)
     }
 
-    fn compile_function_call(pair: pest::iterators::Pair<Rule>) -> IcnResult<Vec<Opcode>> {
+    fn compile_function_call(pair: Pair<Rule>, scope: &Scope) -> IcnResult<Vec<Opcode>> {
         let mut bytecode = Vec::new();
         let mut inner = pair.into_inner();
-        let func_name = inner.next().unwrap().as_str().to_string();
+        let func_name = inner.next()
+            .ok_or_else(|| IcnError::SmartContract("Malformed function call".into()))?
+            .as_str()
+            .to_string();
 
         for arg in inner {
-            bytecode.extend(Self::compile_expression(arg)?);
+            bytecode.extend(Self::compile_expression(arg, scope)?);
         }
 
         bytecode.push(Opcode::Call(func_name));
         Ok(bytecode)
     }
 
-    fn compile_event(pair: pest::iterators::Pair<Rule>) -> IcnResult<ContractEvent> {
+    fn compile_event(pair: Pair<Rule>) -> IcnResult<ContractEvent> {
         let mut event = ContractEvent {
             name: String::new(),
             parameters: Vec::new(),
@@ -283,14 +474,7 @@ impl NaturalLanguageCompiler {
         for inner_pair in pair.into_inner() {
             match inner_pair.as_rule() {
                 Rule::identifier => event.name = inner_pair.as_str().to_string(),
-                Rule::parameter_list => {
-                    for param in inner_pair.into_inner() {
-                        let param_pair = param.into_inner().next().unwrap();
-                        let param_name = param_pair.as_str().to_string();
-                        let param_type = Self::parse_type(param_pair.into_inner().next().unwrap().as_str())?;
-                        event.parameters.push(ContractParameter { name: param_name, param_type });
-                    }
-                }
+                Rule::parameter_list => event.parameters = Self::compile_parameters(inner_pair)?,
                 _ => {}
             }
         }
@@ -298,12 +482,12 @@ impl NaturalLanguageCompiler {
         Ok(event)
     }
 
-    fn parse_literal(pair: pest::iterators::Pair<Rule>) -> IcnResult<Value> {
+    fn parse_literal(pair: Pair<Rule>) -> IcnResult<Value> {
         match pair.as_rule() {
             Rule::integer => Ok(Value::Int(pair.as_str().parse().map_err(|e| IcnError::SmartContract(format!("Invalid integer: {}", e)))?)),
             Rule::float => Ok(Value::Float(pair.as_str().parse().map_err(|e| IcnError::SmartContract(format!("Invalid float: {}", e)))?)),
             Rule::boolean => Ok(Value::Bool(pair.as_str().parse().map_err(|e| IcnError::SmartContract(format!("Invalid boolean: {}", e)))?)),
-            Rule::string => Ok(Value::String(pair.into_inner().next().unwrap().as_str().to_string())),
+            Rule::string => Ok(Value::String(Self::first_inner(pair)?.as_str().to_string())),
             _ => Err(IcnError::SmartContract("Unsupported literal type".into())),
         }
     }
@@ -336,25 +520,143 @@ impl NaturalLanguageCompiler {
 pub struct SmartContractExecutor {
     vm: CoopVM,
     contracts: HashMap<String, CompiledContract>,
+    /// Each contract's persistent storage; VM memory is scratch space
+    /// cleared between calls.
+    storage: HashMap<String, ContractStorage>,
+    /// Role requirements per contract; contracts without one are open.
+    policies: HashMap<String, AccessPolicy>,
+    /// Applied afresh to every call.
+    limits: ExecutionLimits,
+    /// Events from successful calls not yet taken by the node.
+    events: Vec<EmittedEvent>,
+    /// What contracts read as the block time; unset, reading it fails.
+    block_time: Option<i64>,
+}
+
+impl Default for SmartContractExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SmartContractExecutor {
     pub fn new() -> Self {
         SmartContractExecutor {
-            vm: CoopVM::new(),
+            vm: CoopVM::new(Vec::new()),
             contracts: HashMap::new(),
+            storage: HashMap::new(),
+            policies: HashMap::new(),
+            limits: ExecutionLimits::default(),
+            events: Vec::new(),
+            block_time: None,
         }
     }
 
-    pub fn deploy_contract(&mut self, contract_id: String, contract: CompiledContract) -> IcnResult<()> {
+    /// Sets the timestamp of the block the following calls run in.
+    pub fn set_block_time(&mut self, block_time: i64) {
+        self.block_time = Some(block_time);
+    }
+
+    pub fn set_limits(&mut self, limits: ExecutionLimits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &ExecutionLimits {
+        &self.limits
+    }
+
+    /// Deploys `contract` unless analysis finds errors in its bytecode, then
+    /// runs its initializer, if any. Returns the analysis, whose warnings
+    /// did not stop the deploy.
+    pub fn deploy_contract(&mut self, contract_id: String, contract: CompiledContract) -> IcnResult<AnalysisReport> {
         if self.contracts.contains_key(&contract_id) {
             return Err(IcnError::SmartContract(format!("Contract with ID {} already exists", contract_id)));
         }
-        self.contracts.insert(contract_id, contract);
+        let report = contract.analyze();
+        if report.has_errors() {
+            let errors: Vec<String> = report.errors().map(ToString::to_string).collect();
+            return Err(IcnError::SmartContract(format!("Contract failed analysis: {}", errors.join("; "))));
+        }
+        let initializer = contract.initializer();
+        self.contracts.insert(contract_id.clone(), contract);
+        if let Some(entry) = initializer {
+            if let Err(e) = self.run_collecting_events(&contract_id, entry, Vec::new()) {
+                self.contracts.remove(&contract_id);
+                self.storage.remove(&contract_id);
+                return Err(e);
+            }
+        }
+        Ok(report)
+    }
+
+    /// Compiles `code` and installs it as a contract's code, replacing any
+    /// earlier version. The contract's storage and access policy are kept
+    /// and, unlike `deploy_contract`, no initializer runs.
+    pub fn load_contract(&mut self, contract_id: &str, code: &str) -> IcnResult<AnalysisReport> {
+        let contract = NaturalLanguageCompiler::compile(code)?;
+        let report = contract.analyze();
+        if report.has_errors() {
+            let errors: Vec<String> = report.errors().map(ToString::to_string).collect();
+            return Err(IcnError::SmartContract(format!("Contract failed analysis: {}", errors.join("; "))));
+        }
+        self.contracts.insert(contract_id.to_string(), contract);
+        Ok(report)
+    }
+
+    /// Replaces a loaded contract's code with `code` and runs `function`
+    /// with `args` to carry its state over. If that fails, the previous
+    /// code and storage are put back.
+    pub fn migrate_contract(&mut self, contract_id: &str, code: &str, function: &str, args: Vec<Value>) -> IcnResult<()> {
+        let previous = self.contracts.remove(contract_id)
+            .ok_or_else(|| IcnError::SmartContract(format!("Contract with ID {} not found", contract_id)))?;
+        let storage = self.storage.get(contract_id).cloned();
+        let migrated = self.load_contract(contract_id, code)
+            .and_then(|_| self.execute_contract(contract_id, function, args));
+        if let Err(e) = migrated {
+            self.contracts.insert(contract_id.to_string(), previous);
+            match storage {
+                Some(storage) => self.storage.insert(contract_id.to_string(), storage),
+                None => self.storage.remove(contract_id),
+            };
+            return Err(e);
+        }
         Ok(())
     }
 
     pub fn execute_contract(&mut self, contract_id: &str, function: &str, args: Vec<Value>) -> IcnResult<Option<Value>> {
+        let entry = self.function_entry(contract_id, function, args.len())?;
+        self.run_collecting_events(contract_id, entry, args)
+    }
+
+    /// Runs from `entry` with events collected for `take_events`.
+    fn run_collecting_events(&mut self, contract_id: &str, entry: usize, args: Vec<Value>) -> IcnResult<Option<Value>> {
+        let collector = EventCollector::default().with_block_time(self.block_time);
+        let emitted = collector.events();
+        let result = self.run(contract_id, entry, args, Box::new(collector))?;
+
+        let abi = &self.contracts[contract_id].abi;
+        for (name, data) in emitted.lock().unwrap().drain(..) {
+            self.events.push(EmittedEvent::decode(contract_id, abi, &name, &data));
+        }
+        Ok(result)
+    }
+
+    /// Like `execute_contract`, with `host` carrying out the contract's
+    /// system opcodes. Events go to the host rather than to `take_events`.
+    pub fn execute_contract_with_host(
+        &mut self,
+        contract_id: &str,
+        function: &str,
+        args: Vec<Value>,
+        host: Box<dyn HostInterface>,
+    ) -> IcnResult<Option<Value>> {
+        let entry = self.function_entry(contract_id, function, args.len())?;
+        self.run(contract_id, entry, args, host)
+    }
+
+    /// Where `function` starts, once it is known to take `arg_count`
+    /// arguments.
+    fn function_entry(&self, contract_id: &str, function: &str, arg_count: usize) -> IcnResult<usize> {
         let contract = self.contracts.get(contract_id)
             .ok_or_else(|| IcnError::SmartContract(format!("Contract with ID {} not found", contract_id)))?;
 
@@ -362,40 +664,80 @@ impl SmartContractExecutor {
             .find(|f| f.name == function)
             .ok_or_else(|| IcnError::SmartContract(format!("Function {} not found in contract {}", function, contract_id)))?;
 
-        if args.len() != function_abi.inputs.len() {
+        if arg_count != function_abi.inputs.len() {
             return Err(IcnError::SmartContract(format!("Invalid number of arguments for function {}", function)));
         }
 
+        Ok(contract.entry_point(function))
+    }
+
+    fn run(&mut self, contract_id: &str, entry: usize, args: Vec<Value>, host: Box<dyn HostInterface>) -> IcnResult<Option<Value>> {
+        let contract = &self.contracts[contract_id];
         self.vm.load_program(contract.bytecode.clone());
+        self.vm.set_limits(self.limits.clone());
+        let storage = self.storage.remove(contract_id)
+            .unwrap_or_else(|| ContractStorage::new(contract_id));
+        self.vm.set_storage(storage);
+        self.vm.set_host(host);
 
         // Push arguments onto the stack
         for arg in args {
             self.vm.push(arg);
         }
 
-        // Call the function
-        self.vm.call(function)?;
-
-        // Run the VM
-        self.vm.run()?;
+        // Run the VM from the entry point, keeping the storage
+        // it wrote even if the call failed so the caller decides whether to
+        // persist it
+        self.vm.jump_to(entry)?;
+        let result = self.vm.execute();
+        self.storage.insert(contract_id.to_string(), self.vm.take_storage());
+        self.vm.take_host();
+        result?;
 
         // Return the top value from the stack, if any
         Ok(self.vm.pop())
     }
 
-    pub fn get_contract_state(&self, contract_id: &str) -> IcnResult<&HashMap<String, Value>> {
-        self.contracts.get(contract_id)
-            .map(|contract| self.vm.get_memory())
-            .ok_or_else(|| IcnError::SmartContract(format!("Contract with ID {} not found", contract_id)))
+    /// Removes and returns the events emitted by calls since the last take.
+    pub fn take_events(&mut self) -> Vec<EmittedEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Returns a contract's persistent storage.
+    pub fn get_contract_state(&self, contract_id: &str) -> IcnResult<&BTreeMap<String, Value>> {
+        self.get_contract(contract_id)?;
+        static EMPTY: BTreeMap<String, Value> = BTreeMap::new();
+        Ok(self.storage.get(contract_id).map_or(&EMPTY, ContractStorage::entries))
     }
 
     pub fn update_contract_state(&mut self, contract_id: &str, key: String, value: Value) -> IcnResult<()> {
-        if !self.contracts.contains_key(contract_id) {
-            return Err(IcnError::SmartContract(format!("Contract with ID {} not found", contract_id)));
+        self.get_contract(contract_id)?;
+        self.storage.entry(contract_id.to_string())
+            .or_insert_with(|| ContractStorage::new(contract_id))
+            .set(&key, value);
+        Ok(())
+    }
+
+    /// Replaces a contract's storage with one loaded from persistent storage.
+    pub fn load_storage(&mut self, storage: ContractStorage) {
+        self.storage.insert(storage.contract_id().to_string(), storage);
+    }
+
+    /// Returns the storage entries written since the last call and clears
+    /// the change set.
+    pub fn take_storage_changes(&mut self, contract_id: &str) -> Vec<(String, Value)> {
+        self.storage.get_mut(contract_id).map_or_else(Vec::new, ContractStorage::take_changes)
+    }
+
+    pub fn storage_root(&self, contract_id: &str) -> IcnResult<String> {
+        match self.storage.get(contract_id) {
+            Some(storage) => storage.state_root(),
+            None => Ok(icn_vm::EMPTY_STATE_ROOT.to_string()),
         }
+    }
 
-        self.vm.store(&key, value);
-        Ok(())
+    pub fn get_contract_abi(&self, contract_id: &str) -> IcnResult<&ContractABI> {
+        self.get_contract(contract_id).map(CompiledContract::abi)
     }
 
     pub fn get_contract(&self, contract_id: &str) -> IcnResult<&CompiledContract> {
@@ -410,6 +752,8 @@ impl SmartContractExecutor {
     pub fn remove_contract(&mut self, contract_id: &str) -> IcnResult<()> {
         self.contracts.remove(contract_id)
             .ok_or_else(|| IcnError::SmartContract(format!("Contract with ID {} not found", contract_id)))?;
+        self.storage.remove(contract_id);
+        self.policies.remove(contract_id);
         Ok(())
     }
 }
@@ -441,6 +785,120 @@ mod tests {
         assert_eq!(compiled_contract.abi.events.len(), 1);
     }
 
+    #[test]
+    fn test_compiled_functions_run_from_their_entry_points() {
+        let input = r#"
+            contract CustomLogic
+
+            function scale(value: int, factor: int) {
+                let result = value + factor * 2
+                emit Scaled(value, result)
+                return result
+            }
+
+            function countdown(from: int) {
+                let steps = 0
+                while from > 0 {
+                    from = from - 1
+                    steps = steps + 1
+                }
+                if steps == 3 { return true } else { return false }
+            }
+
+            event Scaled(value: int, result: int)
+        "#;
+        let mut executor = SmartContractExecutor::new();
+        executor.deploy_contract("calc".to_string(), NaturalLanguageCompiler::compile(input).unwrap()).unwrap();
+
+        let result = executor.execute_contract("calc", "scale", vec![Value::Int(1), Value::Int(3)]).unwrap();
+        assert_eq!(result, Some(Value::Int(7)));
+        let events = executor.take_events();
+        assert_eq!(events[0].params.get("result").map(String::as_str), Some("7"));
+        assert_eq!(executor.execute_contract("calc", "countdown", vec![Value::Int(3)]).unwrap(), Some(Value::Bool(true)));
+        assert_eq!(executor.execute_contract("calc", "countdown", vec![Value::Int(2)]).unwrap(), Some(Value::Bool(false)));
+    }
+
+    #[test]
+    fn test_contract_variables_persist_in_storage() {
+        let input = r#"
+            contract CustomLogic
+
+            total = 0
+            balances = {}
+
+            function credit(member: address, amount: int) {
+                let next = total + amount
+                total = next
+                balances[member] = amount
+                return total
+            }
+        "#;
+        let compiled = NaturalLanguageCompiler::compile(input).unwrap();
+        let stores = |opcode: &Opcode| match opcode {
+            Opcode::Store(name) => Some(format!("memory {}", name)),
+            Opcode::StorageStore(name) => Some(format!("storage {}", name)),
+            _ => None,
+        };
+        let stored: Vec<String> = compiled.bytecode.iter().filter_map(stores).collect();
+        assert!(stored.contains(&"storage total".to_string()));
+        assert!(stored.contains(&"memory next".to_string()));
+        assert!(!stored.contains(&"storage next".to_string()));
+
+        let mut executor = SmartContractExecutor::new();
+        executor.deploy_contract("ledger".to_string(), compiled).unwrap();
+        assert_eq!(executor.get_contract_state("ledger").unwrap().get("total"), Some(&Value::Int(0)));
+
+        executor.execute_contract("ledger", "credit", vec![Value::String("alice".into()), Value::Int(5)]).unwrap();
+        let result = executor.execute_contract("ledger", "credit", vec![Value::String("bob".into()), Value::Int(3)]).unwrap();
+        assert_eq!(result, Some(Value::Int(8)));
+
+        let state = executor.get_contract_state("ledger").unwrap();
+        assert_eq!(state.get("total"), Some(&Value::Int(8)));
+        let Some(Value::Map(balances)) = state.get("balances") else { panic!("balances is not a map") };
+        assert_eq!(balances.get("alice"), Some(&Value::Int(5)));
+        assert!(!state.contains_key("next"));
+    }
+
+    #[test]
+    fn test_migration_carries_state_into_new_code() {
+        let version_1 = r#"
+            contract CustomLogic
+
+            count = 0
+
+            function bump() {
+                count = count + 1
+            }
+        "#;
+        let version_2 = r#"
+            contract CustomLogic
+
+            function migrate(old_state: string) {
+                total = count * 10
+            }
+
+            function bump() {
+                total = total + 10
+            }
+        "#;
+        let mut executor = SmartContractExecutor::new();
+        executor.deploy_contract("counter".to_string(), NaturalLanguageCompiler::compile(version_1).unwrap()).unwrap();
+        executor.execute_contract("counter", "bump", vec![]).unwrap();
+        executor.execute_contract("counter", "bump", vec![]).unwrap();
+
+        // A migration that fails leaves the old code and state in place
+        let failing = "contract CustomLogic\n\nfunction migrate(old_state: string) {\n    total = missing + 1\n}\n";
+        assert!(executor.migrate_contract("counter", failing, "migrate", vec![Value::String("{}".into())]).is_err());
+        assert!(!executor.get_contract_state("counter").unwrap().contains_key("total"));
+        executor.execute_contract("counter", "bump", vec![]).unwrap();
+        assert_eq!(executor.get_contract_state("counter").unwrap().get("count"), Some(&Value::Int(3)));
+
+        executor.migrate_contract("counter", version_2, "migrate", vec![Value::String("{}".into())]).unwrap();
+        executor.execute_contract("counter", "bump", vec![]).unwrap();
+        assert_eq!(executor.get_contract_state("counter").unwrap().get("total"), Some(&Value::Int(40)));
+        assert!(executor.load_contract("counter", "not a contract").is_err());
+    }
+
     #[test]
     fn test_smart_contract_executor() {
         let mut executor = SmartContractExecutor::new();
@@ -449,12 +907,12 @@ mod tests {
             contract_type: SmartContractType::AssetTransfer,
             bytecode: vec![
                 Opcode::Push(Value::Int(100)),
-                Opcode::Store("balance".to_string()),
-                Opcode::Load("balance".to_string()),
+                Opcode::StorageStore("balance".to_string()),
+                Opcode::StorageLoad("balance".to_string()),
                 Opcode::Push(Value::Int(50)),
                 Opcode::Sub,
-                Opcode::Store("balance".to_string()),
-                Opcode::Load("balance".to_string()),
+                Opcode::StorageStore("balance".to_string()),
+                Opcode::StorageLoad("balance".to_string()),
             ],
             abi: ContractABI {
                 functions: vec![
@@ -466,6 +924,8 @@ mod tests {
                 ],
                 events: vec![],
             },
+            entry_points: HashMap::new(),
+            initializer: None,
         };
 
         executor.deploy_contract("test_contract".to_string(), contract).unwrap();
@@ -475,5 +935,67 @@ mod tests {
 
         let state = executor.get_contract_state("test_contract").unwrap();
         assert_eq!(state.get("balance"), Some(&Value::Int(50)));
+        let changes = executor.take_storage_changes("test_contract");
+        assert_eq!(changes, vec![("balance".to_string(), Value::Int(50))]);
+        assert_ne!(executor.storage_root("test_contract").unwrap(), icn_vm::EMPTY_STATE_ROOT);
+    }
+
+    #[test]
+    fn test_contract_events_are_decoded() {
+        let mut executor = SmartContractExecutor::new();
+        let emit = |name: &str, data: &str| vec![
+            Opcode::Push(Value::String(name.to_string())),
+            Opcode::Push(Value::String(data.to_string())),
+            Opcode::EmitEvent,
+        ];
+        let contract = CompiledContract {
+            contract_type: SmartContractType::AssetTransfer,
+            bytecode: [emit("Transfer", "Alice, Bob, 10"), emit("Note", "free text")].concat(),
+            abi: ContractABI {
+                functions: vec![ContractFunction { name: "transfer".to_string(), inputs: vec![], outputs: vec![] }],
+                events: vec![ContractEvent {
+                    name: "Transfer".to_string(),
+                    parameters: ["from", "to", "amount"].iter()
+                        .map(|name| ContractParameter { name: name.to_string(), param_type: ContractValueType::String })
+                        .collect(),
+                }],
+            },
+            entry_points: HashMap::new(),
+            initializer: None,
+        };
+        executor.deploy_contract("token".to_string(), contract).unwrap();
+        executor.execute_contract("token", "transfer", vec![]).unwrap();
+
+        let events = executor.take_events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].contract_id, "token");
+        assert_eq!(events[0].params.get("to").map(String::as_str), Some("Bob"));
+        assert_eq!(events[0].params.get("amount").map(String::as_str), Some("10"));
+        assert!(events[1].params.is_empty());
+        assert_eq!(events[1].data, "free text");
+        assert!(executor.take_events().is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_deploy_refuses_contracts_that_fail_analysis() {
+        let mut executor = SmartContractExecutor::new();
+        let function = |inputs: Vec<ContractParameter>| ContractFunction { name: "run".to_string(), inputs, outputs: vec![] };
+        let broken = CompiledContract::new(
+            SmartContractType::CustomLogic,
+            vec![Opcode::Load("total".to_string()), Opcode::Push(Value::Int(1)), Opcode::Add],
+            ContractABI { functions: vec![function(vec![])], events: vec![] },
+        );
+        let err = executor.deploy_contract("broken".to_string(), broken).unwrap_err();
+        assert!(err.to_string().contains("total"));
+
+        // The same code is fine once `total` is a parameter
+        let total = ContractParameter { name: "total".to_string(), param_type: ContractValueType::Integer };
+        let fixed = CompiledContract::new(
+            SmartContractType::CustomLogic,
+            vec![Opcode::Load("total".to_string()), Opcode::Push(Value::Int(1)), Opcode::Add, Opcode::Pop],
+            ContractABI { functions: vec![function(vec![total])], events: vec![] },
+        );
+        let report = executor.deploy_contract("fixed".to_string(), fixed).unwrap();
+        assert!(!report.has_errors());
+    }
+}
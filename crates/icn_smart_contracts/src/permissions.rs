@@ -0,0 +1,151 @@
+// File: crates/icn_smart_contracts/src/permissions.rs
+
+use crate::SmartContractExecutor;
+use icn_common::{IcnResult, IcnError};
+use icn_vm::Value;
+use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The identity calling a contract function and the roles it holds.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Caller {
+    pub did: String,
+    pub roles: BTreeSet<String>,
+}
+
+impl Caller {
+    pub fn new(did: &str, roles: BTreeSet<String>) -> Self {
+        Caller { did: did.to_string(), roles }
+    }
+}
+
+/// Which roles may call each function of a contract. Functions without an
+/// entry are open to every caller.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AccessPolicy {
+    /// DID allowed to change the policy; `None` leaves changes to governance.
+    pub owner: Option<String>,
+    pub function_roles: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl AccessPolicy {
+    pub fn new(owner: Option<String>) -> Self {
+        AccessPolicy { owner, function_roles: BTreeMap::new() }
+    }
+
+    /// Whether the caller holds one of the roles the function requires.
+    pub fn allows(&self, function: &str, caller: &Caller) -> bool {
+        self.function_roles.get(function)
+            .is_none_or(|roles| roles.iter().any(|role| caller.roles.contains(role)))
+    }
+}
+
+/// Who is changing a contract's permissions.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PermissionAuthority {
+    /// The DID asking for the change; it must be the policy's owner.
+    Owner(String),
+    /// A passed proposal with the given id, already checked by the caller.
+    Governance(String),
+}
+
+impl SmartContractExecutor {
+    /// Attaches an access policy to a newly deployed contract. Later changes
+    /// go through `set_function_roles`.
+    pub fn set_access_policy(&mut self, contract_id: &str, policy: AccessPolicy) -> IcnResult<()> {
+        if self.policies.contains_key(contract_id) {
+            return Err(IcnError::SmartContract(format!("Contract {} already has an access policy", contract_id)));
+        }
+        self.policies.insert(contract_id.to_string(), policy);
+        Ok(())
+    }
+
+    pub fn access_policy(&self, contract_id: &str) -> Option<&AccessPolicy> {
+        self.policies.get(contract_id)
+    }
+
+    /// Restricts a function to the given roles, or opens it to everyone when
+    /// `roles` is `None`.
+    pub fn set_function_roles(&mut self, contract_id: &str, function: &str, roles: Option<BTreeSet<String>>, authority: &PermissionAuthority) -> IcnResult<()> {
+        self.validate_function_roles(contract_id, function, authority)?;
+        let policy = self.policies.entry(contract_id.to_string()).or_default();
+        match roles {
+            Some(roles) => policy.function_roles.insert(function.to_string(), roles),
+            None => policy.function_roles.remove(function),
+        };
+        Ok(())
+    }
+
+    /// Checks that `authority` may change the roles of `function` without
+    /// changing them.
+    pub fn validate_function_roles(&self, contract_id: &str, function: &str, authority: &PermissionAuthority) -> IcnResult<()> {
+        if let Ok(contract) = self.get_contract(contract_id) {
+            if !contract.abi().functions.iter().any(|f| f.name == function) {
+                return Err(IcnError::SmartContract(format!("Function {} not found in contract {}", function, contract_id)));
+            }
+        }
+        if let PermissionAuthority::Owner(did) = authority {
+            let owner = self.policies.get(contract_id).and_then(|policy| policy.owner.as_deref());
+            if owner != Some(did.as_str()) {
+                return Err(IcnError::SmartContract(format!(
+                    "Only the owner of contract {} or a governance vote can change its permissions", contract_id
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn check_permission(&self, contract_id: &str, function: &str, caller: &Caller) -> IcnResult<()> {
+        match self.policies.get(contract_id) {
+            Some(policy) if !policy.allows(function, caller) => Err(IcnError::SmartContract(format!(
+                "{} is not permitted to call {} on contract {}", caller.did, function, contract_id
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Executes a contract function after checking the caller may call it.
+    pub fn execute_contract_as(&mut self, caller: &Caller, contract_id: &str, function: &str, args: Vec<Value>) -> IcnResult<Option<Value>> {
+        self.check_permission(contract_id, function, caller)?;
+        self.execute_contract(contract_id, function, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CompiledContract, ContractABI, ContractFunction, SmartContractType};
+    use icn_vm::Opcode;
+
+    fn function(name: &str) -> ContractFunction {
+        ContractFunction { name: name.to_string(), inputs: vec![], outputs: vec![] }
+    }
+
+    #[test]
+    fn test_role_restricted_calls() {
+        let mut executor = SmartContractExecutor::new();
+        let contract = CompiledContract::new(
+            SmartContractType::CustomLogic,
+            vec![Opcode::Push(Value::Int(1))],
+            ContractABI { functions: vec![function("payout"), function("balance")], events: vec![] },
+        );
+        executor.deploy_contract("treasury".to_string(), contract).unwrap();
+        executor.set_access_policy("treasury", AccessPolicy::new(Some("did:icn:owner".to_string()))).unwrap();
+
+        let treasurer = Caller::new("did:icn:alice", BTreeSet::from(["treasurer".to_string()]));
+        let member = Caller::new("did:icn:bob", BTreeSet::new());
+        let roles = Some(BTreeSet::from(["treasurer".to_string()]));
+
+        assert!(executor.set_function_roles("treasury", "payout", roles.clone(), &PermissionAuthority::Owner("did:icn:bob".to_string())).is_err());
+        assert!(executor.set_function_roles("treasury", "missing", roles.clone(), &PermissionAuthority::Owner("did:icn:owner".to_string())).is_err());
+        executor.set_function_roles("treasury", "payout", roles, &PermissionAuthority::Owner("did:icn:owner".to_string())).unwrap();
+
+        assert!(executor.execute_contract_as(&member, "treasury", "payout", vec![]).is_err());
+        assert!(executor.execute_contract_as(&treasurer, "treasury", "payout", vec![]).is_ok());
+        assert!(executor.execute_contract_as(&member, "treasury", "balance", vec![]).is_ok());
+
+        // Governance can reopen the function without the owner
+        executor.set_function_roles("treasury", "payout", None, &PermissionAuthority::Governance("p1".to_string())).unwrap();
+        assert!(executor.execute_contract_as(&member, "treasury", "payout", vec![]).is_ok());
+    }
+}
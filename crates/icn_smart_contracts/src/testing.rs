@@ -0,0 +1,438 @@
+// File: crates/icn_smart_contracts/src/testing.rs
+
+//! Unit-testing compiled contracts without running a node.
+//!
+//! `ContractHarness` deploys one contract and runs its functions against a
+//! `MockHost`, which keeps fake balances, proposals and memberships, lets
+//! the test move the block time and captures emitted events. A call that
+//! fails leaves storage and the host as they were, as it would on a node.
+//!
+//! ```ignore
+//! contract_test!(fn pays_out(harness) = my_contract() => {
+//!     harness.host().set_block_time(1_800_000_000);
+//!     assert_returns(&harness.call("claim", vec![]), Value::Bool(true));
+//!     assert_emitted(&harness, "Claimed", &[("amount", "10")]);
+//! });
+//! ```
+
+use crate::{CompiledContract, EmittedEvent, NaturalLanguageCompiler, SmartContractExecutor};
+use icn_common::{IcnResult, IcnError};
+use icn_vm::{ContractStorage, HostInterface, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+/// Id the contract under test is deployed as.
+pub const TEST_CONTRACT_ID: &str = "contract_under_test";
+
+/// Caller a harness starts with.
+pub const TEST_CALLER: &str = "did:icn:test-caller";
+
+/// Block time a mock host starts at, 2024-01-01T00:00:00Z.
+pub const DEFAULT_BLOCK_TIME: i64 = 1_704_067_200;
+
+/// System operations a mock host can be told to refuse, to test how a
+/// contract copes with the node saying no.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HostOperation {
+    Mint,
+    Vote,
+    Allocate,
+    UpdateReputation,
+    CreateProposal,
+    AddMember,
+    EmitEvent,
+}
+
+/// A vote a contract cast through the host.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockVote {
+    pub voter: String,
+    pub proposal_id: String,
+    pub in_favor: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+struct MockState {
+    caller: String,
+    block_time: i64,
+    /// Keyed by address and currency name.
+    balances: BTreeMap<(String, String), f64>,
+    /// Status of each proposal, as a `ProposalStatus` variant name.
+    proposals: BTreeMap<String, String>,
+    votes: Vec<MockVote>,
+    allocations: BTreeMap<String, i64>,
+    reputation_changes: BTreeMap<String, i64>,
+    members: BTreeMap<String, BTreeSet<String>>,
+    events: Vec<(String, String)>,
+    refused: BTreeSet<HostOperation>,
+}
+
+/// A host that acts on in-memory state instead of a node. Clones share
+/// that state, so a test can keep a handle while the VM holds another.
+#[derive(Debug, Clone)]
+pub struct MockHost {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockHost {
+    pub fn new(caller: &str) -> Self {
+        let state = MockState { caller: caller.to_string(), block_time: DEFAULT_BLOCK_TIME, ..MockState::default() };
+        MockHost { state: Arc::new(Mutex::new(state)) }
+    }
+
+    pub fn with_balance(self, address: &str, currency: &str, amount: f64) -> Self {
+        self.set_balance(address, currency, amount);
+        self
+    }
+
+    pub fn with_proposal(self, proposal_id: &str, status: &str) -> Self {
+        self.set_proposal_status(proposal_id, status);
+        self
+    }
+
+    pub fn caller(&self) -> String {
+        self.state().caller.clone()
+    }
+
+    pub fn set_caller(&self, caller: &str) {
+        self.state().caller = caller.to_string();
+    }
+
+    pub fn set_balance(&self, address: &str, currency: &str, amount: f64) {
+        self.state().balances.insert((address.to_string(), currency.to_string()), amount);
+    }
+
+    pub fn balance(&self, address: &str, currency: &str) -> f64 {
+        self.state().balances.get(&(address.to_string(), currency.to_string())).copied().unwrap_or(0.0)
+    }
+
+    pub fn block_time(&self) -> i64 {
+        self.state().block_time
+    }
+
+    pub fn set_block_time(&self, block_time: i64) {
+        self.state().block_time = block_time;
+    }
+
+    /// Moves the block time forward by `seconds`.
+    pub fn advance_time(&self, seconds: i64) {
+        self.state().block_time += seconds;
+    }
+
+    pub fn set_proposal_status(&self, proposal_id: &str, status: &str) {
+        self.state().proposals.insert(proposal_id.to_string(), status.to_string());
+    }
+
+    pub fn refuse(&self, operation: HostOperation) {
+        self.state().refused.insert(operation);
+    }
+
+    pub fn allow(&self, operation: HostOperation) {
+        self.state().refused.remove(&operation);
+    }
+
+    pub fn votes(&self) -> Vec<MockVote> {
+        self.state().votes.clone()
+    }
+
+    /// Total allocated per resource.
+    pub fn allocations(&self) -> BTreeMap<String, i64> {
+        self.state().allocations.clone()
+    }
+
+    /// Net reputation change per address.
+    pub fn reputation_changes(&self) -> BTreeMap<String, i64> {
+        self.state().reputation_changes.clone()
+    }
+
+    pub fn members(&self, coop_id: &str) -> BTreeSet<String> {
+        self.state().members.get(coop_id).cloned().unwrap_or_default()
+    }
+
+    /// Every `(name, data)` pair emitted so far, oldest first.
+    pub fn events(&self) -> Vec<(String, String)> {
+        self.state().events.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    fn snapshot(&self) -> MockState {
+        self.state().clone()
+    }
+
+    fn restore(&self, snapshot: MockState) {
+        *self.state() = snapshot;
+    }
+
+    fn check(&self, operation: HostOperation) -> IcnResult<std::sync::MutexGuard<'_, MockState>> {
+        let state = self.state();
+        if state.refused.contains(&operation) {
+            return Err(IcnError::SmartContract(format!("Mock host refused {:?}", operation)));
+        }
+        Ok(state)
+    }
+}
+
+impl HostInterface for MockHost {
+    fn mint_currency(&mut self, currency_type: &str, amount: f64) -> IcnResult<()> {
+        let mut state = self.check(HostOperation::Mint)?;
+        if !amount.is_finite() || amount <= 0.0 {
+            return Err(IcnError::SmartContract("Mint amount must be positive".into()));
+        }
+        let key = (state.caller.clone(), currency_type.to_string());
+        *state.balances.entry(key).or_insert(0.0) += amount;
+        Ok(())
+    }
+
+    fn vote_on_proposal(&mut self, proposal_id: &str, in_favor: bool) -> IcnResult<()> {
+        let mut state = self.check(HostOperation::Vote)?;
+        if state.proposals.get(proposal_id).map(String::as_str) != Some("Active") {
+            return Err(IcnError::SmartContract(format!("Proposal {} is not open for voting", proposal_id)));
+        }
+        let voter = state.caller.clone();
+        state.votes.push(MockVote { voter, proposal_id: proposal_id.to_string(), in_favor });
+        Ok(())
+    }
+
+    fn allocate_resource(&mut self, resource: &str, amount: i64) -> IcnResult<()> {
+        let mut state = self.check(HostOperation::Allocate)?;
+        if amount < 0 {
+            return Err(IcnError::SmartContract("Resource amount cannot be negative".into()));
+        }
+        *state.allocations.entry(resource.to_string()).or_insert(0) += amount;
+        Ok(())
+    }
+
+    fn update_reputation(&mut self, address: &str, change: i64) -> IcnResult<()> {
+        let mut state = self.check(HostOperation::UpdateReputation)?;
+        *state.reputation_changes.entry(address.to_string()).or_insert(0) += change;
+        Ok(())
+    }
+
+    fn create_proposal(&mut self, _title: &str, _description: &str) -> IcnResult<String> {
+        let mut state = self.check(HostOperation::CreateProposal)?;
+        let proposal_id = format!("proposal-{}", state.proposals.len() + 1);
+        state.proposals.insert(proposal_id.clone(), "Active".to_string());
+        Ok(proposal_id)
+    }
+
+    fn get_proposal_status(&self, proposal_id: &str) -> IcnResult<String> {
+        self.state().proposals.get(proposal_id)
+            .cloned()
+            .ok_or_else(|| IcnError::SmartContract(format!("Proposal {} not found", proposal_id)))
+    }
+
+    fn add_cooperative_member(&mut self, coop_id: &str, member_id: &str) -> IcnResult<()> {
+        let mut state = self.check(HostOperation::AddMember)?;
+        state.members.entry(coop_id.to_string()).or_default().insert(member_id.to_string());
+        Ok(())
+    }
+
+    fn emit_event(&mut self, name: &str, data: &str) -> IcnResult<()> {
+        let mut state = self.check(HostOperation::EmitEvent)?;
+        state.events.push((name.to_string(), data.to_string()));
+        Ok(())
+    }
+
+    fn block_time(&self) -> IcnResult<i64> {
+        Ok(self.state().block_time)
+    }
+}
+
+/// One deployed contract and the mock host its calls run against.
+pub struct ContractHarness {
+    executor: SmartContractExecutor,
+    host: MockHost,
+}
+
+impl ContractHarness {
+    pub fn new(contract: CompiledContract) -> IcnResult<Self> {
+        let mut executor = SmartContractExecutor::new();
+        executor.deploy_contract(TEST_CONTRACT_ID.to_string(), contract)?;
+        Ok(ContractHarness { executor, host: MockHost::new(TEST_CALLER) })
+    }
+
+    /// Compiles a contract written in the contract language and deploys it.
+    pub fn compile(source: &str) -> IcnResult<Self> {
+        Self::new(NaturalLanguageCompiler::compile(source)?)
+    }
+
+    /// Replaces the host, for one set up ahead with balances or proposals.
+    pub fn with_host(mut self, host: MockHost) -> Self {
+        self.host = host;
+        self
+    }
+
+    pub fn host(&self) -> &MockHost {
+        &self.host
+    }
+
+    /// The executor the contract is deployed in, to change its limits.
+    pub fn executor(&mut self) -> &mut SmartContractExecutor {
+        &mut self.executor
+    }
+
+    /// Calls a function as the host's caller. A failed call leaves the
+    /// contract's storage and the host unchanged.
+    pub fn call(&mut self, function: &str, args: Vec<Value>) -> IcnResult<Option<Value>> {
+        let storage = self.executor.get_contract_state(TEST_CONTRACT_ID)?.clone();
+        let host_state = self.host.snapshot();
+        let result = self.executor.execute_contract_with_host(TEST_CONTRACT_ID, function, args, Box::new(self.host.clone()));
+        if result.is_err() {
+            self.executor.load_storage(ContractStorage::from_entries(TEST_CONTRACT_ID, storage));
+            self.host.restore(host_state);
+        }
+        self.executor.take_storage_changes(TEST_CONTRACT_ID);
+        result
+    }
+
+    pub fn call_as(&mut self, caller: &str, function: &str, args: Vec<Value>) -> IcnResult<Option<Value>> {
+        self.host.set_caller(caller);
+        self.call(function, args)
+    }
+
+    pub fn storage(&self, key: &str) -> Option<Value> {
+        self.executor.get_contract_state(TEST_CONTRACT_ID).ok()?.get(key).cloned()
+    }
+
+    /// Emitted events, named after the parameters the ABI declares.
+    pub fn events(&self) -> Vec<EmittedEvent> {
+        let Ok(abi) = self.executor.get_contract_abi(TEST_CONTRACT_ID) else {
+            return Vec::new();
+        };
+        self.host.events().iter()
+            .map(|(name, data)| EmittedEvent::decode(TEST_CONTRACT_ID, abi, name, data))
+            .collect()
+    }
+}
+
+/// Fails the test unless the call succeeded and returned `expected`.
+#[track_caller]
+pub fn assert_returns(result: &IcnResult<Option<Value>>, expected: Value) {
+    match result {
+        Ok(Some(value)) if *value == expected => {}
+        Ok(other) => panic!("Expected the call to return {:?}, got {:?}", expected, other),
+        Err(e) => panic!("Expected the call to return {:?}, but it failed: {}", expected, e),
+    }
+}
+
+/// Fails the test unless the call failed with an error mentioning `message`.
+#[track_caller]
+pub fn assert_fails(result: &IcnResult<Option<Value>>, message: &str) {
+    match result {
+        Err(e) if e.to_string().contains(message) => {}
+        Err(e) => panic!("Expected the call to fail with \"{}\", but it failed with: {}", message, e),
+        Ok(value) => panic!("Expected the call to fail with \"{}\", but it returned {:?}", message, value),
+    }
+}
+
+/// Fails the test unless an event called `name` was emitted with all of
+/// `params`.
+#[track_caller]
+pub fn assert_emitted(harness: &ContractHarness, name: &str, params: &[(&str, &str)]) {
+    let events = harness.events();
+    let found = events.iter().any(|event| {
+        event.name == name && params.iter().all(|(key, value)| event.params.get(*key).map(String::as_str) == Some(*value))
+    });
+    if !found {
+        panic!("No {} event with {:?} was emitted; events: {:?}", name, params, events);
+    }
+}
+
+#[track_caller]
+pub fn assert_storage(harness: &ContractHarness, key: &str, expected: Value) {
+    let actual = harness.storage(key);
+    if actual.as_ref() != Some(&expected) {
+        panic!("Expected storage {} to hold {:?}, found {:?}", key, expected, actual);
+    }
+}
+
+#[track_caller]
+pub fn assert_balance(host: &MockHost, address: &str, currency: &str, expected: f64) {
+    let actual = host.balance(address, currency);
+    if (actual - expected).abs() > 1e-9 {
+        panic!("Expected {} to hold {} {}, found {}", address, expected, currency, actual);
+    }
+}
+
+/// Declares a test that runs against a fresh `ContractHarness` for the
+/// given compiled contract.
+///
+/// ```ignore
+/// contract_test!(fn transfer_moves_funds(harness) = token_contract() => {
+///     harness.call("transfer", vec![]).unwrap();
+/// });
+/// ```
+#[macro_export]
+macro_rules! contract_test {
+    ($(#[$meta:meta])* fn $name:ident($harness:ident) = $contract:expr => $body:block) => {
+        $(#[$meta])*
+        #[test]
+        fn $name() {
+            #[allow(unused_mut)]
+            let mut $harness = $crate::testing::ContractHarness::new($contract)
+                .unwrap_or_else(|e| panic!("Failed to deploy the contract under test: {}", e));
+            $body
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContractABI, ContractEvent, ContractFunction, ContractParameter, ContractValueType, SmartContractType};
+    use icn_vm::Opcode;
+
+    /// Records the claim time, mints 10 BasicNeeds to the caller and
+    /// announces it.
+    fn claim_contract() -> CompiledContract {
+        let bytecode = vec![
+            Opcode::BlockTime,
+            Opcode::StorageStore("claimed_at".to_string()),
+            Opcode::Push(Value::Float(10.0)),
+            Opcode::Push(Value::String("BasicNeeds".to_string())),
+            Opcode::EconCurrencyMint,
+            Opcode::Push(Value::String("Claimed".to_string())),
+            Opcode::Push(Value::String("10".to_string())),
+            Opcode::EmitEvent,
+            Opcode::Push(Value::Bool(true)),
+        ];
+        let abi = ContractABI {
+            functions: vec![ContractFunction { name: "claim".to_string(), inputs: vec![], outputs: vec![] }],
+            events: vec![ContractEvent {
+                name: "Claimed".to_string(),
+                parameters: vec![ContractParameter { name: "amount".to_string(), param_type: ContractValueType::Integer }],
+            }],
+        };
+        CompiledContract::new(SmartContractType::AssetTransfer, bytecode, abi)
+    }
+
+    #[test]
+    fn test_failed_call_leaves_storage_and_host_untouched() {
+        let mut harness = ContractHarness::new(claim_contract()).unwrap()
+            .with_host(MockHost::new("did:icn:alice").with_balance("did:icn:alice", "BasicNeeds", 5.0));
+
+        harness.host().refuse(HostOperation::EmitEvent);
+        assert_fails(&harness.call("claim", vec![]), "refused");
+        assert_balance(harness.host(), "did:icn:alice", "BasicNeeds", 5.0);
+        assert_eq!(harness.storage("claimed_at"), None);
+        assert!(harness.events().is_empty());
+
+        harness.host().allow(HostOperation::EmitEvent);
+        harness.host().advance_time(60);
+        assert_returns(&harness.call("claim", vec![]), Value::Bool(true));
+        assert_balance(harness.host(), "did:icn:alice", "BasicNeeds", 15.0);
+        assert_storage(&harness, "claimed_at", Value::Int(DEFAULT_BLOCK_TIME + 60));
+        assert_fails(&harness.call("unknown", vec![]), "not found");
+    }
+
+    crate::contract_test!(fn test_contract_test_macro_deploys_a_fresh_harness(harness) = claim_contract() => {
+        harness.host().set_block_time(1_800_000_000);
+        assert_returns(&harness.call_as("did:icn:bob", "claim", vec![]), Value::Bool(true));
+        assert_emitted(&harness, "Claimed", &[("amount", "10")]);
+        assert_storage(&harness, "claimed_at", Value::Int(1_800_000_000));
+        assert_balance(harness.host(), "did:icn:bob", "BasicNeeds", 10.0);
+    });
+}
@@ -0,0 +1,430 @@
+// File: crates/icn_storage/src/backup.rs
+
+use crate::{ContentManifest, StorageManager, StorageNode};
+use chrono::{DateTime, Utc};
+use icn_common::{IcnError, IcnResult};
+use log::info;
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// First line of every backup archive.
+const BACKUP_MAGIC: &str = "ICN-STORAGE-BACKUP v1";
+
+/// What a backup archive contains, as written in its header.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BackupManifest {
+    pub created_at: DateTime<Utc>,
+    pub node_count: usize,
+    pub key_count: usize,
+    pub content_count: usize,
+    /// Bytes held across all nodes, replicas included.
+    pub stored_bytes: usize,
+    /// SHA-256 of the archive body.
+    pub checksum: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BackupBody {
+    nodes: Vec<StorageNode>,
+    data_location: HashMap<String, Vec<usize>>,
+    content_index: HashMap<String, ContentManifest>,
+    chunk_refs: HashMap<String, usize>,
+    #[serde(default)]
+    expirations: HashMap<String, DateTime<Utc>>,
+}
+
+impl StorageManager {
+    /// Writes every node's data, the key locations and the content index to
+    /// `writer`. The archive is a magic line, a JSON manifest line and a
+    /// JSON body whose hash the manifest records.
+    pub fn export_backup<W: Write>(&self, mut writer: W) -> IcnResult<BackupManifest> {
+        let body = BackupBody {
+            nodes: self.nodes.read().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?.clone(),
+            data_location: self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?.clone(),
+            content_index: self.content_index.read().map_err(|_| IcnError::Storage("Failed to lock content index".into()))?.clone(),
+            chunk_refs: self.chunk_refs.read().map_err(|_| IcnError::Storage("Failed to lock chunk references".into()))?.clone(),
+            expirations: self.expirations.read().map_err(|_| IcnError::Storage("Failed to lock expirations".into()))?.clone(),
+        };
+        let encoded = serde_json::to_vec(&body)?;
+        let manifest = BackupManifest {
+            created_at: Utc::now(),
+            node_count: body.nodes.len(),
+            key_count: body.data_location.len(),
+            content_count: body.content_index.len(),
+            stored_bytes: body.nodes.iter().map(|node| node.data.values().map(Vec::len).sum::<usize>()).sum(),
+            checksum: format!("{:x}", Sha256::digest(&encoded)),
+        };
+
+        writeln!(writer, "{}", BACKUP_MAGIC)?;
+        writeln!(writer, "{}", serde_json::to_string(&manifest)?)?;
+        writer.write_all(&encoded)?;
+        writer.flush()?;
+        info!("Exported storage backup of {} keys ({} bytes)", manifest.key_count, manifest.stored_bytes);
+        Ok(manifest)
+    }
+
+    /// Replaces all stored data with the contents of a backup archive. The
+    /// archive is fully read and verified before anything is replaced, so a
+    /// corrupted backup leaves the current data untouched. Quotas and billed
+    /// ownership are not part of backups and are kept as they are.
+    pub fn import_backup<R: Read>(&self, reader: R) -> IcnResult<BackupManifest> {
+        let mut reader = BufReader::new(reader);
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim_end() != BACKUP_MAGIC {
+            return Err(IcnError::Storage("Not a storage backup archive".into()));
+        }
+        line.clear();
+        reader.read_line(&mut line)?;
+        let manifest: BackupManifest = serde_json::from_str(line.trim_end())?;
+
+        let mut encoded = Vec::new();
+        reader.read_to_end(&mut encoded)?;
+        if format!("{:x}", Sha256::digest(&encoded)) != manifest.checksum {
+            return Err(IcnError::Storage("Backup checksum mismatch".into()));
+        }
+        let body: BackupBody = serde_json::from_slice(&encoded)?;
+        Self::validate_backup(&body, &manifest)?;
+
+        *self.nodes.write().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))? = body.nodes;
+        *self.data_location.write().map_err(|_| IcnError::Storage("Failed to lock data location".into()))? = body.data_location;
+        *self.content_index.write().map_err(|_| IcnError::Storage("Failed to lock content index".into()))? = body.content_index;
+        *self.chunk_refs.write().map_err(|_| IcnError::Storage("Failed to lock chunk references".into()))? = body.chunk_refs;
+        *self.expirations.write().map_err(|_| IcnError::Storage("Failed to lock expirations".into()))? = body.expirations;
+        info!("Imported storage backup from {} with {} keys", manifest.created_at, manifest.key_count);
+        Ok(manifest)
+    }
+
+    fn validate_backup(body: &BackupBody, manifest: &BackupManifest) -> IcnResult<()> {
+        if body.nodes.len() != manifest.node_count || body.data_location.len() != manifest.key_count {
+            return Err(IcnError::Storage("Backup contents do not match its manifest".into()));
+        }
+        for (key, node_ids) in &body.data_location {
+            let held = node_ids.iter().all(|&id| body.nodes.get(id).is_some_and(|node| node.data.contains_key(key)));
+            if !held {
+                return Err(IcnError::Storage(format!("Backup has no data for key {} on its recorded nodes", key)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Somewhere backup archives can be kept, such as a local directory or an
+/// object store.
+pub trait BackupTarget: Send + Sync {
+    fn put(&self, name: &str, archive: &[u8]) -> IcnResult<()>;
+    fn get(&self, name: &str) -> IcnResult<Vec<u8>>;
+    /// Names of the stored archives, oldest first.
+    fn list(&self) -> IcnResult<Vec<String>>;
+    fn delete(&self, name: &str) -> IcnResult<()>;
+}
+
+/// Name for an archive taken at `at`. Names sort in the order backups were taken.
+pub fn backup_name(at: DateTime<Utc>) -> String {
+    format!("storage-{}.icnbak", at.format("%Y%m%dT%H%M%SZ"))
+}
+
+/// Writes a backup to `target` and deletes all but the newest `keep` archives.
+pub fn run_backup(storage: &StorageManager, target: &dyn BackupTarget, keep: usize) -> IcnResult<BackupManifest> {
+    let mut archive = Vec::new();
+    let manifest = storage.export_backup(&mut archive)?;
+    upload_backup(target, &archive, &manifest, keep)?;
+    Ok(manifest)
+}
+
+/// Stores an exported archive and rotates out old ones. Split from
+/// `run_backup` so the export can happen under a lock and the upload after.
+pub fn upload_backup(target: &dyn BackupTarget, archive: &[u8], manifest: &BackupManifest, keep: usize) -> IcnResult<()> {
+    target.put(&backup_name(manifest.created_at), archive)?;
+    let names = target.list()?;
+    for name in names.iter().take(names.len().saturating_sub(keep.max(1))) {
+        target.delete(name)?;
+    }
+    Ok(())
+}
+
+/// Where and how often a node backs up its storage.
+#[derive(Clone)]
+pub struct BackupSchedule {
+    pub target: Arc<dyn BackupTarget>,
+    pub interval: Duration,
+    /// Number of archives kept at the target.
+    pub keep: usize,
+}
+
+/// Restores the newest archive held by `target`.
+pub fn restore_latest(storage: &StorageManager, target: &dyn BackupTarget) -> IcnResult<BackupManifest> {
+    let name = target.list()?.pop()
+        .ok_or_else(|| IcnError::Storage("No backups available".into()))?;
+    storage.import_backup(target.get(&name)?.as_slice())
+}
+
+fn validate_backup_name(name: &str) -> IcnResult<()> {
+    let valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) && !name.starts_with('.');
+    if !valid {
+        return Err(IcnError::Storage(format!("Invalid backup name: {}", name)));
+    }
+    Ok(())
+}
+
+/// Keeps archives as files in a directory, for example a mounted network share.
+pub struct FilesystemTarget {
+    dir: PathBuf,
+}
+
+impl FilesystemTarget {
+    pub fn new(dir: impl Into<PathBuf>) -> IcnResult<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(FilesystemTarget { dir })
+    }
+}
+
+impl BackupTarget for FilesystemTarget {
+    fn put(&self, name: &str, archive: &[u8]) -> IcnResult<()> {
+        validate_backup_name(name)?;
+        // Write under a temporary name first so a crash never leaves a truncated archive
+        let partial = self.dir.join(format!("{}.partial", name));
+        fs::write(&partial, archive)?;
+        fs::rename(&partial, self.dir.join(name))?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> IcnResult<Vec<u8>> {
+        validate_backup_name(name)?;
+        Ok(fs::read(self.dir.join(name))?)
+    }
+
+    fn list(&self) -> IcnResult<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            if name.ends_with(".icnbak") {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    fn delete(&self, name: &str) -> IcnResult<()> {
+        validate_backup_name(name)?;
+        Ok(fs::remove_file(self.dir.join(name))?)
+    }
+}
+
+/// Credentials and location of an S3-compatible bucket.
+#[cfg(feature = "s3")]
+#[derive(Debug, Clone)]
+pub struct S3Config {
+    /// Base URL of the service, e.g. `https://s3.eu-west-1.amazonaws.com`
+    /// or a MinIO address. Buckets are addressed path-style.
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    /// Prepended to archive names, e.g. `node-1/`.
+    pub prefix: String,
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+/// Keeps archives in an S3-compatible bucket, signing requests with AWS
+/// Signature Version 4. Requests block, so call it off the async runtime.
+#[cfg(feature = "s3")]
+pub struct S3Target {
+    config: S3Config,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "s3")]
+impl S3Target {
+    pub fn new(config: S3Config) -> IcnResult<Self> {
+        let prefix_valid = config.prefix.split('/').all(|part| part.is_empty() || validate_backup_name(part).is_ok());
+        if config.bucket.is_empty() || !prefix_valid {
+            return Err(IcnError::Config("S3 backups need a bucket and a plain prefix".into()));
+        }
+        Ok(S3Target { config, client: reqwest::blocking::Client::new() })
+    }
+
+    fn send(&self, method: reqwest::Method, name: Option<&str>, query: &[(&str, &str)], body: Vec<u8>) -> IcnResult<reqwest::blocking::Response> {
+        let path = match name {
+            Some(name) => format!("/{}/{}{}", self.config.bucket, self.config.prefix, name),
+            None => format!("/{}", self.config.bucket),
+        };
+        let endpoint = self.config.endpoint.trim_end_matches('/');
+        let host = endpoint.split("://").last().unwrap_or(endpoint);
+        let canonical_query = query.iter()
+            .map(|(key, value)| format!("{}={}", key, uri_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&");
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = format!("{:x}", Sha256::digest(&body));
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\nhost;x-amz-content-sha256;x-amz-date\n{}",
+            method, path, canonical_query, host, payload_hash, amz_date, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{:x}",
+            amz_date, scope, Sha256::digest(canonical_request.as_bytes())
+        );
+        let mut key = hmac_sha256(format!("AWS4{}", self.config.secret_key).as_bytes(), date.as_bytes());
+        for part in [self.config.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature: String = hmac_sha256(&key, string_to_sign.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect();
+
+        let mut url = format!("{}{}", endpoint, path);
+        if !canonical_query.is_empty() {
+            url = format!("{}?{}", url, canonical_query);
+        }
+        let response = self.client.request(method, url)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature={}",
+                self.config.access_key, scope, signature
+            ))
+            .body(body)
+            .send()
+            .map_err(|e| IcnError::Storage(format!("S3 request failed: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(IcnError::Storage(format!("S3 request failed with status {}", response.status())));
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(feature = "s3")]
+impl BackupTarget for S3Target {
+    fn put(&self, name: &str, archive: &[u8]) -> IcnResult<()> {
+        validate_backup_name(name)?;
+        self.send(reqwest::Method::PUT, Some(name), &[], archive.to_vec())?;
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> IcnResult<Vec<u8>> {
+        validate_backup_name(name)?;
+        let response = self.send(reqwest::Method::GET, Some(name), &[], Vec::new())?;
+        response.bytes()
+            .map(|bytes| bytes.to_vec())
+            .map_err(|e| IcnError::Storage(format!("Failed to read backup from S3: {}", e)))
+    }
+
+    fn list(&self) -> IcnResult<Vec<String>> {
+        let query = [("list-type", "2"), ("prefix", self.config.prefix.as_str())];
+        let listing = self.send(reqwest::Method::GET, None, &query, Vec::new())?
+            .text()
+            .map_err(|e| IcnError::Storage(format!("Failed to read S3 listing: {}", e)))?;
+        // Only the object keys are needed, so skip a full XML parser
+        let mut names: Vec<String> = listing.split("<Key>")
+            .skip(1)
+            .filter_map(|rest| rest.split("</Key>").next())
+            .filter_map(|key| key.strip_prefix(self.config.prefix.as_str()))
+            .filter(|name| name.ends_with(".icnbak"))
+            .map(str::to_string)
+            .collect();
+        names.sort();
+        Ok(names)
+    }
+
+    fn delete(&self, name: &str) -> IcnResult<()> {
+        validate_backup_name(name)?;
+        self.send(reqwest::Method::DELETE, Some(name), &[], Vec::new())?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "s3")]
+fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    const BLOCK_SIZE: usize = 64;
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let inner_pad: Vec<u8> = block.iter().map(|b| b ^ 0x36).collect();
+    let outer_pad: Vec<u8> = block.iter().map(|b| b ^ 0x5c).collect();
+    let inner = Sha256::new().chain(&inner_pad).chain(message).finalize();
+    Sha256::new().chain(&outer_pad).chain(inner).finalize().to_vec()
+}
+
+#[cfg(feature = "s3")]
+fn uri_encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated_storage() -> StorageManager {
+        let storage_manager = StorageManager::new(2).with_chunk_size(4);
+        for i in 0..3 {
+            storage_manager.add_node(format!("node{}", i)).unwrap();
+        }
+        storage_manager.store_data("config", b"replicated".to_vec()).unwrap();
+        storage_manager.store_content(b"cooperative archive".to_vec()).unwrap();
+        storage_manager
+    }
+
+    #[test]
+    fn test_backup_round_trip() {
+        let source = populated_storage();
+        let mut archive = Vec::new();
+        let manifest = source.export_backup(&mut archive).unwrap();
+        assert_eq!(manifest.node_count, 3);
+        assert_eq!(manifest.content_count, 1);
+
+        let restored = StorageManager::new(2);
+        assert_eq!(restored.import_backup(archive.as_slice()).unwrap(), manifest);
+        assert_eq!(restored.retrieve_data("config").unwrap(), b"replicated".to_vec());
+        let content_hash = crate::hash_bytes(b"cooperative archive");
+        assert_eq!(restored.retrieve_content(&content_hash).unwrap(), b"cooperative archive".to_vec());
+        assert_eq!(restored.get_key_count().unwrap(), source.get_key_count().unwrap());
+
+        // A flipped byte in the body is caught and nothing is replaced
+        let last = archive.len() - 2;
+        archive[last] ^= 1;
+        let untouched = StorageManager::new(1);
+        untouched.add_node("node".to_string()).unwrap();
+        assert!(untouched.import_backup(archive.as_slice()).is_err());
+        assert_eq!(untouched.get_node_count(), 1);
+    }
+
+    #[test]
+    fn test_filesystem_target_rotation() {
+        let dir = std::env::temp_dir().join(format!("icn-backup-test-{}", std::process::id()));
+        let target = FilesystemTarget::new(&dir).unwrap();
+        let storage_manager = populated_storage();
+
+        for name in ["storage-20240101T000000Z.icnbak", "storage-20240102T000000Z.icnbak"] {
+            target.put(name, b"old").unwrap();
+        }
+        run_backup(&storage_manager, &target, 2).unwrap();
+        let names = target.list().unwrap();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0], "storage-20240102T000000Z.icnbak");
+        assert!(target.put("../escape", b"x").is_err());
+
+        let restored = StorageManager::new(2);
+        restore_latest(&restored, &target).unwrap();
+        assert_eq!(restored.retrieve_data("config").unwrap(), b"replicated".to_vec());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,276 @@
+// File: crates/icn_storage/src/billing.rs
+
+use chrono::{DateTime, Utc};
+use icn_common::{IcnResult, IcnError, CurrencyType};
+use icn_currency::CurrencySystem;
+use log::{info, warn};
+use serde::{Serialize, Deserialize};
+use std::collections::HashMap;
+
+/// Storage is priced by the 30-day month.
+pub const SECONDS_PER_MONTH: f64 = 30.0 * 24.0 * 60.0 * 60.0;
+
+/// Account that collects storage charges before they are paid out to
+/// storage node operators.
+pub const STORAGE_POOL_ACCOUNT: &str = "storage:pool";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StorageBillingConfig {
+    /// Storage currency charged for holding one byte for a month.
+    pub price_per_byte_month: f64,
+    /// Quota for identities that have not been given one explicitly.
+    pub default_quota_bytes: usize,
+    pub settlement_interval_secs: u64,
+}
+
+impl Default for StorageBillingConfig {
+    fn default() -> Self {
+        StorageBillingConfig {
+            price_per_byte_month: 1e-6,
+            default_quota_bytes: 100 * 1024 * 1024,
+            settlement_interval_secs: 24 * 60 * 60,
+        }
+    }
+}
+
+/// Bytes held by an owner or operator, integrated over time.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct UsageMeter {
+    pub bytes: usize,
+    /// Byte-seconds accrued since the last settlement.
+    pub accrued_byte_seconds: f64,
+    pub last_update: Option<DateTime<Utc>>,
+}
+
+impl UsageMeter {
+    fn accrue(&mut self, now: DateTime<Utc>) {
+        if let Some(last_update) = self.last_update {
+            let seconds = (now - last_update).num_milliseconds().max(0) as f64 / 1000.0;
+            self.accrued_byte_seconds += self.bytes as f64 * seconds;
+        }
+        self.last_update = Some(now);
+    }
+
+    fn add(&mut self, bytes: usize, now: DateTime<Utc>) {
+        self.accrue(now);
+        self.bytes += bytes;
+    }
+
+    fn remove(&mut self, bytes: usize, now: DateTime<Utc>) {
+        self.accrue(now);
+        self.bytes = self.bytes.saturating_sub(bytes);
+    }
+
+    fn take_accrued(&mut self, now: DateTime<Utc>) -> f64 {
+        self.accrue(now);
+        std::mem::take(&mut self.accrued_byte_seconds)
+    }
+}
+
+/// Outcome of one billing period.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct StorageSettlement {
+    pub settled_at: Option<DateTime<Utc>>,
+    /// Storage currency collected from each owner.
+    pub charges: HashMap<String, f64>,
+    /// Amount each owner could not cover, carried into the next period.
+    pub arrears: HashMap<String, f64>,
+    /// Storage currency paid to each node operator.
+    pub payouts: HashMap<String, f64>,
+}
+
+/// Meters who stores what and who holds it, and settles the difference in
+/// `CurrencyType::Storage`.
+///
+/// Owners pay for the logical bytes they store. Operators are paid out of
+/// what was collected, in proportion to the replica bytes their nodes held
+/// during the period.
+#[derive(Clone, Debug, Default)]
+pub struct StorageBilling {
+    config: StorageBillingConfig,
+    quotas: HashMap<String, usize>,
+    owners: HashMap<String, UsageMeter>,
+    operators: HashMap<String, UsageMeter>,
+    arrears: HashMap<String, f64>,
+    last_settlement: Option<DateTime<Utc>>,
+}
+
+impl StorageBilling {
+    pub fn new(config: StorageBillingConfig) -> Self {
+        StorageBilling {
+            config,
+            ..Default::default()
+        }
+    }
+
+    pub fn config(&self) -> &StorageBillingConfig {
+        &self.config
+    }
+
+    pub fn set_quota(&mut self, owner: &str, quota_bytes: usize) {
+        self.quotas.insert(owner.to_string(), quota_bytes);
+    }
+
+    pub fn quota(&self, owner: &str) -> usize {
+        self.quotas.get(owner).copied().unwrap_or(self.config.default_quota_bytes)
+    }
+
+    /// Logical bytes currently stored by an owner.
+    pub fn usage(&self, owner: &str) -> usize {
+        self.owners.get(owner).map_or(0, |meter| meter.bytes)
+    }
+
+    /// Replica bytes currently held by an operator's nodes.
+    pub fn operator_holdings(&self, operator: &str) -> usize {
+        self.operators.get(operator).map_or(0, |meter| meter.bytes)
+    }
+
+    pub fn arrears(&self, owner: &str) -> f64 {
+        self.arrears.get(owner).copied().unwrap_or(0.0)
+    }
+
+    /// Checks that an owner can store `additional` bytes after `released`
+    /// bytes they already hold are replaced.
+    pub fn check_quota(&self, owner: &str, additional: usize, released: usize) -> IcnResult<()> {
+        if self.arrears(owner) > 0.0 {
+            return Err(IcnError::Storage(format!("{} has unpaid storage charges", owner)));
+        }
+        let quota = self.quota(owner);
+        if self.usage(owner).saturating_sub(released) + additional > quota {
+            return Err(IcnError::Storage(format!("Storage quota of {} bytes exceeded for {}", quota, owner)));
+        }
+        Ok(())
+    }
+
+    pub fn record_store(&mut self, owner: &str, bytes: usize, operators: &[String], now: DateTime<Utc>) {
+        self.owners.entry(owner.to_string()).or_default().add(bytes, now);
+        for operator in operators {
+            self.operators.entry(operator.clone()).or_default().add(bytes, now);
+        }
+    }
+
+    pub fn record_remove(&mut self, owner: &str, bytes: usize, operators: &[String], now: DateTime<Utc>) {
+        if let Some(meter) = self.owners.get_mut(owner) {
+            meter.remove(bytes, now);
+        }
+        for operator in operators {
+            if let Some(meter) = self.operators.get_mut(operator) {
+                meter.remove(bytes, now);
+            }
+        }
+    }
+
+    pub fn is_settlement_due(&self, now: DateTime<Utc>) -> bool {
+        self.last_settlement.is_none_or(|last| {
+            (now - last).num_seconds() >= self.config.settlement_interval_secs as i64
+        })
+    }
+
+    /// Charges owners for the byte-months used since the last settlement and
+    /// pays operators out of what was collected. Charges an owner cannot
+    /// cover become arrears, which block further storage until paid.
+    pub fn settle(&mut self, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<StorageSettlement> {
+        let storage = CurrencyType::Storage;
+        let mut settlement = StorageSettlement {
+            settled_at: Some(now),
+            ..Default::default()
+        };
+
+        for (owner, meter) in self.owners.iter_mut() {
+            let used = meter.take_accrued(now) / SECONDS_PER_MONTH * self.config.price_per_byte_month;
+            let due = used + self.arrears.remove(owner).unwrap_or(0.0);
+            if due <= 0.0 {
+                continue;
+            }
+
+            let paid = due.min(currency_system.get_balance(owner, &storage)?);
+            if paid > 0.0 {
+                currency_system.transfer(owner, STORAGE_POOL_ACCOUNT, &storage, paid)?;
+                settlement.charges.insert(owner.clone(), paid);
+            }
+            if due - paid > f64::EPSILON {
+                warn!("{} is short {} Storage for storage charges", owner, due - paid);
+                self.arrears.insert(owner.clone(), due - paid);
+                settlement.arrears.insert(owner.clone(), due - paid);
+            }
+        }
+
+        let collected: f64 = settlement.charges.values().sum();
+        let held: Vec<(String, f64)> = self.operators.iter_mut()
+            .map(|(operator, meter)| (operator.clone(), meter.take_accrued(now)))
+            .filter(|(_, byte_seconds)| *byte_seconds > 0.0)
+            .collect();
+        let total_held: f64 = held.iter().map(|(_, byte_seconds)| byte_seconds).sum();
+
+        // Without any operator holdings the collected amount stays in the pool
+        if collected > 0.0 && total_held > 0.0 {
+            for (operator, byte_seconds) in held {
+                let available = currency_system.get_balance(STORAGE_POOL_ACCOUNT, &storage)?;
+                let payout = (collected * byte_seconds / total_held).min(available);
+                if payout > 0.0 {
+                    currency_system.transfer(STORAGE_POOL_ACCOUNT, &operator, &storage, payout)?;
+                    settlement.payouts.insert(operator, payout);
+                }
+            }
+        }
+
+        self.last_settlement = Some(now);
+        info!("Settled storage billing: collected {} Storage from {} owners", collected, settlement.charges.len());
+        Ok(settlement)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn billing() -> StorageBilling {
+        StorageBilling::new(StorageBillingConfig {
+            price_per_byte_month: 0.01,
+            default_quota_bytes: 1000,
+            settlement_interval_secs: 3600,
+        })
+    }
+
+    #[test]
+    fn test_quota_enforcement() {
+        let mut billing = billing();
+        let now = Utc::now();
+        billing.record_store("Alice", 900, &["node1".to_string()], now);
+
+        assert!(billing.check_quota("Alice", 100, 0).is_ok());
+        assert!(billing.check_quota("Alice", 101, 0).is_err());
+        // Replacing existing data only counts the difference
+        assert!(billing.check_quota("Alice", 500, 400).is_ok());
+
+        billing.set_quota("Alice", 2000);
+        assert!(billing.check_quota("Alice", 1000, 0).is_ok());
+    }
+
+    #[test]
+    fn test_settlement_pays_operators() {
+        let mut billing = billing();
+        let mut currency_system = CurrencySystem::new();
+        currency_system.allocate("Alice", &CurrencyType::Storage, 100.0).unwrap();
+        currency_system.allocate("Bob", &CurrencyType::Storage, 1.0).unwrap();
+
+        let start = Utc::now();
+        let month = Duration::seconds(SECONDS_PER_MONTH as i64);
+        let nodes = ["node1".to_string(), "node2".to_string()];
+        billing.record_store("Alice", 1000, &nodes, start);
+        billing.record_store("Bob", 500, &nodes[..1], start);
+        assert!(billing.is_settlement_due(start));
+
+        let settlement = billing.settle(&mut currency_system, start + month).unwrap();
+        assert!((settlement.charges["Alice"] - 10.0).abs() < 1e-6);
+        assert!((settlement.charges["Bob"] - 1.0).abs() < 1e-6);
+        assert!((billing.arrears("Bob") - 4.0).abs() < 1e-6);
+        assert!(billing.check_quota("Bob", 1, 0).is_err());
+
+        // node1 held 1500 bytes and node2 1000, so they split 11 Storage 3:2
+        assert!((settlement.payouts["node1"] - 6.6).abs() < 1e-6);
+        assert!((settlement.payouts["node2"] - 4.4).abs() < 1e-6);
+        assert!(!billing.is_settlement_due(start + month));
+    }
+}
@@ -0,0 +1,183 @@
+// File: crates/icn_storage/src/gc.rs
+
+use crate::StorageManager;
+use chrono::{DateTime, Duration, Utc};
+use icn_common::{IcnError, IcnResult};
+use log::info;
+use serde::{Serialize, Deserialize};
+
+/// What one garbage collection pass removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GcReport {
+    pub expired_keys: usize,
+    /// Replicas held by nodes the key is not located on, such as those left
+    /// behind by a store that failed partway or a key moved to other nodes.
+    pub orphaned_replicas: usize,
+    pub reclaimed_bytes: usize,
+}
+
+/// Totals over every garbage collection pass since the manager was created.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GcStats {
+    pub runs: u64,
+    pub last_run: Option<DateTime<Utc>>,
+    pub expired_keys: usize,
+    pub orphaned_replicas: usize,
+    pub reclaimed_bytes: usize,
+}
+
+impl StorageManager {
+    /// Stores data that is deleted by the next garbage collection after
+    /// `ttl` has passed. Until then it reads like any other key; afterwards
+    /// it can no longer be retrieved even if it has not been swept yet.
+    pub fn store_data_with_ttl(&self, key: &str, value: Vec<u8>, ttl: Duration) -> IcnResult<()> {
+        self.store_data(key, value)?;
+        self.set_expiry(key, Utc::now() + ttl)
+    }
+
+    /// Like `store_data_for`, but the data expires after `ttl` and stops
+    /// counting against the owner's quota once it is swept.
+    pub fn store_data_for_with_ttl(&self, owner: &str, key: &str, value: Vec<u8>, ttl: Duration) -> IcnResult<()> {
+        self.store_data_for(owner, key, value)?;
+        self.set_expiry(key, Utc::now() + ttl)
+    }
+
+    /// When a key expires, if it was stored with a TTL.
+    pub fn expires_at(&self, key: &str) -> IcnResult<Option<DateTime<Utc>>> {
+        let expirations = self.expirations.read().map_err(|_| IcnError::Storage("Failed to lock expirations".into()))?;
+        Ok(expirations.get(key).copied())
+    }
+
+    pub(crate) fn is_expired(&self, key: &str, now: DateTime<Utc>) -> IcnResult<bool> {
+        Ok(self.expires_at(key)?.is_some_and(|expires_at| expires_at <= now))
+    }
+
+    /// Deletes keys whose TTL has passed and replicas no key points to.
+    pub fn collect_garbage(&self, now: DateTime<Utc>) -> IcnResult<GcReport> {
+        let mut report = GcReport::default();
+
+        let expired: Vec<String> = {
+            let expirations = self.expirations.read().map_err(|_| IcnError::Storage("Failed to lock expirations".into()))?;
+            expirations.iter().filter(|(_, &expires_at)| expires_at <= now).map(|(key, _)| key.clone()).collect()
+        };
+        for key in expired {
+            let size = self.replica_bytes(&key)?;
+            match self.remove_data(&key) {
+                Ok(()) => {
+                    report.expired_keys += 1;
+                    report.reclaimed_bytes += size;
+                }
+                // Removed by someone else since the expiry was read
+                Err(_) => {
+                    self.clear_expiry(&key)?;
+                }
+            }
+        }
+
+        {
+            let mut nodes = self.nodes.write().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?;
+            let data_location = self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+            for (node_id, node) in nodes.iter_mut().enumerate() {
+                node.data.retain(|key, value| {
+                    let located = data_location.get(key).is_some_and(|node_ids| node_ids.contains(&node_id));
+                    if !located {
+                        report.orphaned_replicas += 1;
+                        report.reclaimed_bytes += value.len();
+                    }
+                    located
+                });
+            }
+        }
+
+        let mut stats = self.gc_stats.write().map_err(|_| IcnError::Storage("Failed to lock garbage collection stats".into()))?;
+        stats.runs += 1;
+        stats.last_run = Some(now);
+        stats.expired_keys += report.expired_keys;
+        stats.orphaned_replicas += report.orphaned_replicas;
+        stats.reclaimed_bytes += report.reclaimed_bytes;
+        if report.reclaimed_bytes > 0 {
+            info!(
+                "Garbage collection removed {} expired keys and {} orphaned replicas, reclaiming {} bytes",
+                report.expired_keys, report.orphaned_replicas, report.reclaimed_bytes
+            );
+        }
+        Ok(report)
+    }
+
+    pub fn gc_stats(&self) -> IcnResult<GcStats> {
+        let stats = self.gc_stats.read().map_err(|_| IcnError::Storage("Failed to lock garbage collection stats".into()))?;
+        Ok(stats.clone())
+    }
+
+    pub(crate) fn clear_expiry(&self, key: &str) -> IcnResult<()> {
+        self.expirations.write().map_err(|_| IcnError::Storage("Failed to lock expirations".into()))?.remove(key);
+        Ok(())
+    }
+
+    fn set_expiry(&self, key: &str, expires_at: DateTime<Utc>) -> IcnResult<()> {
+        self.expirations.write().map_err(|_| IcnError::Storage("Failed to lock expirations".into()))?
+            .insert(key.to_string(), expires_at);
+        Ok(())
+    }
+
+    /// Bytes held for a key across all of its replicas.
+    fn replica_bytes(&self, key: &str) -> IcnResult<usize> {
+        let nodes = self.nodes.read().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?;
+        let data_location = self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+        Ok(data_location.get(key)
+            .map(|node_ids| node_ids.iter().filter_map(|&id| nodes.get(id)?.data.get(key).map(Vec::len)).sum())
+            .unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expired_and_orphaned_data_collected() {
+        let storage_manager = StorageManager::new(2);
+        storage_manager.add_node("node1".to_string()).unwrap();
+        storage_manager.add_node("node2".to_string()).unwrap();
+
+        storage_manager.store_data("kept", b"kept".to_vec()).unwrap();
+        storage_manager.store_data_with_ttl("attachment", b"draft".to_vec(), Duration::minutes(10)).unwrap();
+        assert_eq!(storage_manager.retrieve_data("attachment").unwrap(), b"draft".to_vec());
+
+        // Nothing has expired yet
+        assert_eq!(storage_manager.collect_garbage(Utc::now()).unwrap(), GcReport::default());
+
+        // A replica left behind by a store that never recorded its location
+        storage_manager.nodes.write().unwrap()[1].data.insert("partial".to_string(), b"123".to_vec());
+
+        let later = Utc::now() + Duration::minutes(11);
+        let report = storage_manager.collect_garbage(later).unwrap();
+        assert_eq!(report, GcReport { expired_keys: 1, orphaned_replicas: 1, reclaimed_bytes: 13 });
+        assert!(!storage_manager.key_exists("attachment").unwrap());
+        assert_eq!(storage_manager.expires_at("attachment").unwrap(), None);
+        assert_eq!(storage_manager.retrieve_data("kept").unwrap(), b"kept".to_vec());
+        assert_eq!(storage_manager.get_total_storage_size().unwrap(), 8);
+
+        let stats = storage_manager.gc_stats().unwrap();
+        assert_eq!((stats.runs, stats.reclaimed_bytes, stats.last_run), (2, 13, Some(later)));
+    }
+
+    #[test]
+    fn test_expired_owned_data_released_from_quota() {
+        let storage_manager = StorageManager::new(1);
+        storage_manager.add_node("node1".to_string()).unwrap();
+
+        storage_manager.store_data_for_with_ttl("Alice", "upload", b"12345".to_vec(), Duration::seconds(-1)).unwrap();
+        assert!(storage_manager.retrieve_data("upload").is_err());
+        assert_eq!(storage_manager.get_storage_usage("Alice").unwrap().0, 5);
+
+        storage_manager.collect_garbage(Utc::now()).unwrap();
+        assert_eq!(storage_manager.get_storage_usage("Alice").unwrap().0, 0);
+
+        // Storing the key again without a TTL makes it permanent
+        storage_manager.store_data_with_ttl("draft", b"1".to_vec(), Duration::seconds(-1)).unwrap();
+        storage_manager.store_data("draft", b"2".to_vec()).unwrap();
+        storage_manager.collect_garbage(Utc::now()).unwrap();
+        assert_eq!(storage_manager.retrieve_data("draft").unwrap(), b"2".to_vec());
+    }
+}
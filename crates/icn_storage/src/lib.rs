@@ -1,12 +1,31 @@
 // File: crates/icn_storage/src/lib.rs
 
+pub mod backup;
+pub mod billing;
+pub mod gc;
+pub mod wal;
+
+pub use crate::backup::{backup_name, restore_latest, run_backup, upload_backup, BackupManifest, BackupSchedule, BackupTarget, FilesystemTarget};
+#[cfg(feature = "s3")]
+pub use crate::backup::{S3Config, S3Target};
+pub use crate::billing::{StorageBilling, StorageBillingConfig, StorageSettlement, UsageMeter, STORAGE_POOL_ACCOUNT};
+pub use crate::gc::{GcReport, GcStats};
+pub use crate::wal::{DurabilityConfig, RecoveryReport, WalOp, WriteAheadLog};
+
 use icn_common::{IcnResult, IcnError};
+use icn_currency::CurrencySystem;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use chrono::{DateTime, Utc};
 use log::{info, warn, error};
 use serde::{Serialize, Deserialize};
 use sha2::{Sha256, Digest};
 
+/// Default size of the chunks content-addressed blobs are split into.
+pub const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+
+const CHUNK_KEY_PREFIX: &str = "cas:chunk:";
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StorageNode {
     id: String,
@@ -17,6 +36,29 @@ pub struct StorageManager {
     replication_factor: usize,
     nodes: Arc<RwLock<Vec<StorageNode>>>,
     data_location: Arc<RwLock<HashMap<String, Vec<usize>>>>,
+    chunk_size: usize,
+    content_index: Arc<RwLock<HashMap<String, ContentManifest>>>,
+    chunk_refs: Arc<RwLock<HashMap<String, usize>>>,
+    billing: Arc<RwLock<StorageBilling>>,
+    owned_keys: Arc<RwLock<HashMap<String, OwnedEntry>>>,
+    expirations: Arc<RwLock<HashMap<String, DateTime<Utc>>>>,
+    gc_stats: Arc<RwLock<GcStats>>,
+    wal: Option<Arc<Mutex<WriteAheadLog>>>,
+}
+
+/// A key stored on behalf of an identity, billed to that identity.
+#[derive(Clone, Debug)]
+struct OwnedEntry {
+    owner: String,
+    size: usize,
+}
+
+/// Describes how a content-addressed blob is assembled from its chunks.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContentManifest {
+    pub size: usize,
+    pub chunks: Vec<String>,
+    pub ref_count: usize,
 }
 
 impl StorageManager {
@@ -25,15 +67,35 @@ impl StorageManager {
             replication_factor,
             nodes: Arc::new(RwLock::new(Vec::new())),
             data_location: Arc::new(RwLock::new(HashMap::new())),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            content_index: Arc::new(RwLock::new(HashMap::new())),
+            chunk_refs: Arc::new(RwLock::new(HashMap::new())),
+            billing: Arc::new(RwLock::new(StorageBilling::default())),
+            owned_keys: Arc::new(RwLock::new(HashMap::new())),
+            expirations: Arc::new(RwLock::new(HashMap::new())),
+            gc_stats: Arc::new(RwLock::new(GcStats::default())),
+            wal: None,
         }
     }
 
+    pub fn with_billing(self, config: StorageBillingConfig) -> Self {
+        *self.billing.write().unwrap() = StorageBilling::new(config);
+        self
+    }
+
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
     pub fn add_node(&self, id: String) -> IcnResult<()> {
         let mut nodes = self.nodes.write().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?;
+        let txn = self.wal_begin(|| WalOp::AddNode { id: id.clone() })?;
         nodes.push(StorageNode {
             id: id.clone(),
             data: HashMap::new(),
         });
+        self.wal_finish(txn, Ok(()))?;
         info!("Added new storage node with ID: {}", id);
         Ok(())
     }
@@ -48,20 +110,68 @@ impl StorageManager {
         let mut data_location = self.data_location.write().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
 
         let selected_nodes = self.select_nodes(key, node_count);
-        data_location.insert(key.to_string(), selected_nodes.clone());
+        // Logged while the location is held so the log orders writes to a
+        // key the way they are applied.
+        let txn = self.wal_begin(|| WalOp::Store { key: key.to_string(), value: value.clone(), nodes: selected_nodes.clone() })?;
+        let previous = data_location.insert(key.to_string(), selected_nodes.clone());
 
         drop(nodes);
         drop(data_location);
 
         for &node_id in &selected_nodes {
-            self.store_on_node(node_id, key, value.clone())?;
+            if let Err(e) = self.store_on_node(node_id, key, value.clone()) {
+                // Replicas already written are no longer located and get
+                // swept by the next garbage collection.
+                let mut data_location = self.data_location.write().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+                match previous {
+                    Some(node_ids) => data_location.insert(key.to_string(), node_ids),
+                    None => data_location.remove(key),
+                };
+                return self.wal_finish(txn, Err(e));
+            }
         }
+        self.wal_finish(txn, Ok(()))?;
+        self.clear_expiry(key)?;
 
         info!("Stored data with key: {} on {} nodes", key, selected_nodes.len());
         Ok(())
     }
 
+    /// Stores data on behalf of an identity, enforcing its quota. The owner
+    /// is charged for the data until it is removed.
+    pub fn store_data_for(&self, owner: &str, key: &str, value: Vec<u8>) -> IcnResult<()> {
+        let released = {
+            let owned_keys = self.owned_keys.read().map_err(|_| IcnError::Storage("Failed to lock owned keys".into()))?;
+            match owned_keys.get(key) {
+                Some(entry) if entry.owner != owner => {
+                    return Err(IcnError::Storage(format!("Key {} is owned by another identity", key)));
+                }
+                Some(entry) => entry.size,
+                None if self.key_exists(key)? => {
+                    return Err(IcnError::Storage(format!("Key {} is already in use", key)));
+                }
+                None => 0,
+            }
+        };
+        self.lock_billing()?.check_quota(owner, value.len(), released)?;
+
+        if released > 0 {
+            self.remove_data(key)?;
+        }
+        let size = value.len();
+        self.store_data(key, value)?;
+
+        let operators = self.operators_of(key)?;
+        self.lock_billing()?.record_store(owner, size, &operators, Utc::now());
+        self.owned_keys.write().map_err(|_| IcnError::Storage("Failed to lock owned keys".into()))?
+            .insert(key.to_string(), OwnedEntry { owner: owner.to_string(), size });
+        Ok(())
+    }
+
     pub fn retrieve_data(&self, key: &str) -> IcnResult<Vec<u8>> {
+        if self.is_expired(key, Utc::now())? {
+            return Err(IcnError::Storage("Data has expired".into()));
+        }
         let data_location = self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
         let node_ids = data_location.get(key).ok_or_else(|| IcnError::Storage("Data not found".into()))?;
 
@@ -75,17 +185,216 @@ impl StorageManager {
     }
 
     pub fn remove_data(&self, key: &str) -> IcnResult<()> {
-        let mut data_location = self.data_location.write().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
-        let node_ids = data_location.remove(key).ok_or_else(|| IcnError::Storage("Data not found".into()))?;
+        let owned = self.owned_keys.write().map_err(|_| IcnError::Storage("Failed to lock owned keys".into()))?.remove(key);
+        if let Some(entry) = owned {
+            let operators = self.operators_of(key)?;
+            self.lock_billing()?.record_remove(&entry.owner, entry.size, &operators, Utc::now());
+        }
 
-        for &node_id in &node_ids {
-            self.delete_from_node(node_id, key)?;
+        let mut data_location = self.data_location.write().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+        if !data_location.contains_key(key) {
+            return Err(IcnError::Storage("Data not found".into()));
         }
+        let txn = self.wal_begin(|| WalOp::Remove { key: key.to_string() })?;
+        let node_ids = data_location.remove(key).unwrap();
+        drop(data_location);
+        self.clear_expiry(key)?;
+
+        let deleted = node_ids.iter().try_for_each(|&node_id| self.delete_from_node(node_id, key));
+        self.wal_finish(txn, deleted)?;
 
         info!("Deleted data with key: {} from {} nodes", key, node_ids.len());
         Ok(())
     }
 
+    /// Stores a blob by its content hash, splitting it into chunks and storing
+    /// each distinct chunk only once. Storing the same content again only
+    /// bumps its reference count.
+    pub fn store_content(&self, value: Vec<u8>) -> IcnResult<String> {
+        let content_hash = hash_bytes(&value);
+
+        // Held until the manifest is in, so two stores of the same content
+        // can't both miss it and count its chunks twice.
+        let mut content_index = self.content_index.write().map_err(|_| IcnError::Storage("Failed to lock content index".into()))?;
+        if let Some(manifest) = content_index.get_mut(&content_hash) {
+            manifest.ref_count += 1;
+            return Ok(content_hash);
+        }
+
+        let mut chunks = Vec::new();
+        for chunk in value.chunks(self.chunk_size) {
+            let chunk_hash = hash_bytes(chunk);
+            if let Err(e) = self.retain_chunk(&chunk_hash, chunk) {
+                self.release_chunks(&chunks)?;
+                return Err(e);
+            }
+            chunks.push(chunk_hash);
+        }
+
+        content_index.insert(content_hash.clone(), ContentManifest {
+            size: value.len(),
+            chunks,
+            ref_count: 1,
+        });
+
+        info!("Stored content {} ({} bytes)", content_hash, value.len());
+        Ok(content_hash)
+    }
+
+    /// Reassembles a content-addressed blob, rehashing every chunk and the
+    /// whole blob so corrupted replicas are skipped or reported.
+    pub fn retrieve_content(&self, content_hash: &str) -> IcnResult<Vec<u8>> {
+        let manifest = self.get_content_manifest(content_hash)?;
+
+        let mut value = Vec::with_capacity(manifest.size);
+        for chunk_hash in &manifest.chunks {
+            value.extend(self.retrieve_verified_chunk(chunk_hash)?);
+        }
+
+        if hash_bytes(&value) != content_hash {
+            return Err(IcnError::Storage(format!("Integrity check failed for content {}", content_hash)));
+        }
+        Ok(value)
+    }
+
+    /// Drops one reference to a blob, deleting chunks no other content uses.
+    pub fn remove_content(&self, content_hash: &str) -> IcnResult<()> {
+        let manifest = {
+            let mut content_index = self.content_index.write().map_err(|_| IcnError::Storage("Failed to lock content index".into()))?;
+            let manifest = content_index.get_mut(content_hash).ok_or_else(|| IcnError::Storage("Content not found".into()))?;
+            manifest.ref_count -= 1;
+            if manifest.ref_count > 0 {
+                return Ok(());
+            }
+            content_index.remove(content_hash).unwrap()
+        };
+
+        self.release_chunks(&manifest.chunks)?;
+
+        info!("Removed content {}", content_hash);
+        Ok(())
+    }
+
+    /// Counts a reference to a chunk, storing it first if nothing uses it
+    /// yet. The count only goes up once the chunk is stored.
+    fn retain_chunk(&self, chunk_hash: &str, chunk: &[u8]) -> IcnResult<()> {
+        let mut chunk_refs = self.chunk_refs.write().map_err(|_| IcnError::Storage("Failed to lock chunk references".into()))?;
+        match chunk_refs.get_mut(chunk_hash) {
+            Some(refs) => *refs += 1,
+            None => {
+                self.store_data(&chunk_key(chunk_hash), chunk.to_vec())?;
+                chunk_refs.insert(chunk_hash.to_string(), 1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops one reference to each chunk, deleting chunks no content uses.
+    /// A chunk is deleted while its count is locked, so a concurrent store
+    /// can't count it in between.
+    fn release_chunks(&self, chunks: &[String]) -> IcnResult<()> {
+        for chunk_hash in chunks {
+            let mut chunk_refs = self.chunk_refs.write().map_err(|_| IcnError::Storage("Failed to lock chunk references".into()))?;
+            match chunk_refs.get_mut(chunk_hash) {
+                Some(refs) if *refs > 1 => *refs -= 1,
+                _ => {
+                    chunk_refs.remove(chunk_hash);
+                    self.remove_data(&chunk_key(chunk_hash))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_content_manifest(&self, content_hash: &str) -> IcnResult<ContentManifest> {
+        let content_index = self.content_index.read().map_err(|_| IcnError::Storage("Failed to lock content index".into()))?;
+        content_index.get(content_hash).cloned().ok_or_else(|| IcnError::Storage("Content not found".into()))
+    }
+
+    pub fn content_exists(&self, content_hash: &str) -> IcnResult<bool> {
+        let content_index = self.content_index.read().map_err(|_| IcnError::Storage("Failed to lock content index".into()))?;
+        Ok(content_index.contains_key(content_hash))
+    }
+
+    // Number of distinct chunks backing all content-addressed blobs
+    pub fn get_unique_chunk_count(&self) -> IcnResult<usize> {
+        let chunk_refs = self.chunk_refs.read().map_err(|_| IcnError::Storage("Failed to lock chunk references".into()))?;
+        Ok(chunk_refs.len())
+    }
+
+    pub fn set_storage_quota(&self, owner: &str, quota_bytes: usize) -> IcnResult<()> {
+        self.lock_billing()?.set_quota(owner, quota_bytes);
+        Ok(())
+    }
+
+    /// Returns the bytes an identity stores and its quota.
+    pub fn get_storage_usage(&self, owner: &str) -> IcnResult<(usize, usize)> {
+        let billing = self.lock_billing()?;
+        Ok((billing.usage(owner), billing.quota(owner)))
+    }
+
+    /// Settles storage charges if the settlement interval has passed.
+    pub fn settle_billing_if_due(&self, currency_system: &mut CurrencySystem, now: DateTime<Utc>) -> IcnResult<Option<StorageSettlement>> {
+        let mut billing = self.lock_billing()?;
+        if !billing.is_settlement_due(now) {
+            return Ok(None);
+        }
+        billing.settle(currency_system, now).map(Some)
+    }
+
+    /// Flips every bit of the `replica`th copy of `key`, to check that
+    /// readers notice and recover.
+    #[cfg(feature = "fault_injection")]
+    pub fn corrupt_replica(&self, key: &str, replica: usize) -> IcnResult<()> {
+        let node_id = {
+            let data_location = self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+            let node_ids = data_location.get(key).ok_or_else(|| IcnError::Storage("Data not found".into()))?;
+            *node_ids.get(replica).ok_or_else(|| IcnError::Storage(format!("{} has only {} replicas", key, node_ids.len())))?
+        };
+        let mut nodes = self.nodes.write().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?;
+        let data = nodes.get_mut(node_id)
+            .and_then(|node| node.data.get_mut(key))
+            .ok_or_else(|| IcnError::Storage("Data not found on node".into()))?;
+        if data.is_empty() {
+            data.push(0xff);
+        }
+        data.iter_mut().for_each(|byte| *byte = !*byte);
+        warn!("Corrupted replica {} of {} on node {} (injected fault)", replica, key, node_id);
+        Ok(())
+    }
+
+    fn lock_billing(&self) -> IcnResult<std::sync::RwLockWriteGuard<'_, StorageBilling>> {
+        self.billing.write().map_err(|_| IcnError::Storage("Failed to lock storage billing".into()))
+    }
+
+    /// Identities of the nodes holding a key.
+    fn operators_of(&self, key: &str) -> IcnResult<Vec<String>> {
+        let nodes = self.nodes.read().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?;
+        let data_location = self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+        Ok(data_location.get(key)
+            .map(|node_ids| node_ids.iter().filter_map(|&id| nodes.get(id).map(|node| node.id.clone())).collect())
+            .unwrap_or_default())
+    }
+
+    fn retrieve_verified_chunk(&self, chunk_hash: &str) -> IcnResult<Vec<u8>> {
+        let key = chunk_key(chunk_hash);
+        let node_ids = {
+            let data_location = self.data_location.read().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+            data_location.get(&key).cloned().ok_or_else(|| IcnError::Storage("Chunk not found".into()))?
+        };
+
+        for node_id in node_ids {
+            if let Ok(data) = self.retrieve_from_node(node_id, &key) {
+                if hash_bytes(&data) == chunk_hash {
+                    return Ok(data);
+                }
+                warn!("Corrupted replica of chunk {} on node {}", chunk_hash, node_id);
+            }
+        }
+
+        Err(IcnError::Storage(format!("Integrity check failed for chunk {}", chunk_hash)))
+    }
+
     fn select_nodes(&self, key: &str, node_count: usize) -> Vec<usize> {
         let mut hasher = Sha256::new();
         hasher.update(key.as_bytes());
@@ -165,6 +474,16 @@ impl StorageManager {
     }
 }
 
+fn hash_bytes(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    format!("{:x}", hasher.finalize())
+}
+
+fn chunk_key(chunk_hash: &str) -> String {
+    format!("{}{}", CHUNK_KEY_PREFIX, chunk_hash)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -369,4 +688,132 @@ mod tests {
             assert!(listed_keys.contains(&key.to_string()));
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_owned_data_is_metered() {
+        let storage_manager = StorageManager::new(2).with_billing(StorageBillingConfig {
+            default_quota_bytes: 10,
+            ..Default::default()
+        });
+        storage_manager.add_node("node1".to_string()).unwrap();
+        storage_manager.add_node("node2".to_string()).unwrap();
+
+        storage_manager.store_data_for("Alice", "notes", b"12345678".to_vec()).unwrap();
+        assert_eq!(storage_manager.get_storage_usage("Alice").unwrap(), (8, 10));
+        assert_eq!(storage_manager.lock_billing().unwrap().operator_holdings("node1"), 8);
+
+        assert!(storage_manager.store_data_for("Alice", "more", b"123".to_vec()).is_err());
+        assert!(storage_manager.store_data_for("Bob", "notes", b"1".to_vec()).is_err());
+
+        // Overwriting only counts the new size against the quota
+        storage_manager.store_data_for("Alice", "notes", b"1234567890".to_vec()).unwrap();
+        assert_eq!(storage_manager.get_storage_usage("Alice").unwrap().0, 10);
+
+        storage_manager.remove_data("notes").unwrap();
+        assert_eq!(storage_manager.get_storage_usage("Alice").unwrap().0, 0);
+        assert_eq!(storage_manager.lock_billing().unwrap().operator_holdings("node2"), 0);
+    }
+
+    #[test]
+    fn test_store_and_retrieve_content() {
+        let storage_manager = StorageManager::new(2).with_chunk_size(4);
+        for i in 0..3 {
+            storage_manager.add_node(format!("node{}", i)).unwrap();
+        }
+
+        let value = b"cooperative file sharing".to_vec();
+        let content_hash = storage_manager.store_content(value.clone()).unwrap();
+
+        assert_eq!(content_hash.len(), 64);
+        assert_eq!(storage_manager.retrieve_content(&content_hash).unwrap(), value);
+        assert_eq!(storage_manager.get_content_manifest(&content_hash).unwrap().chunks.len(), 6);
+        assert!(storage_manager.retrieve_content("missing").is_err());
+    }
+
+    #[test]
+    fn test_content_deduplication() {
+        let storage_manager = StorageManager::new(1).with_chunk_size(4);
+        storage_manager.add_node("node1".to_string()).unwrap();
+
+        let first = storage_manager.store_content(b"AAAABBBBCCCC".to_vec()).unwrap();
+        let second = storage_manager.store_content(b"AAAABBBBDDDD".to_vec()).unwrap();
+        let again = storage_manager.store_content(b"AAAABBBBCCCC".to_vec()).unwrap();
+
+        assert_eq!(first, again);
+        assert_ne!(first, second);
+        assert_eq!(storage_manager.get_unique_chunk_count().unwrap(), 4);
+        assert_eq!(storage_manager.get_total_storage_size().unwrap(), 16);
+
+        // The first blob is still referenced once after a single removal
+        storage_manager.remove_content(&first).unwrap();
+        assert!(storage_manager.content_exists(&first).unwrap());
+
+        storage_manager.remove_content(&first).unwrap();
+        assert!(!storage_manager.content_exists(&first).unwrap());
+        assert_eq!(storage_manager.get_unique_chunk_count().unwrap(), 3);
+        assert_eq!(storage_manager.retrieve_content(&second).unwrap(), b"AAAABBBBDDDD".to_vec());
+    }
+
+    #[test]
+    fn test_concurrent_and_failed_content_stores() {
+        let storage_manager = StorageManager::new(1).with_chunk_size(4);
+
+        // A chunk that could not be stored is not counted
+        assert!(storage_manager.store_content(b"AAAABBBB".to_vec()).is_err());
+        assert_eq!(storage_manager.get_unique_chunk_count().unwrap(), 0);
+        assert!(!storage_manager.content_exists(&hash_bytes(b"AAAABBBB")).unwrap());
+
+        storage_manager.add_node("node1".to_string()).unwrap();
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| storage_manager.store_content(b"AAAABBBBAAAA".to_vec()).unwrap());
+            }
+        });
+
+        let content_hash = hash_bytes(b"AAAABBBBAAAA");
+        assert_eq!(storage_manager.get_content_manifest(&content_hash).unwrap().ref_count, 8);
+        assert_eq!(storage_manager.get_unique_chunk_count().unwrap(), 2);
+        for _ in 0..8 {
+            storage_manager.remove_content(&content_hash).unwrap();
+        }
+        assert_eq!(storage_manager.get_unique_chunk_count().unwrap(), 0);
+        assert_eq!(storage_manager.get_total_storage_size().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_content_integrity_verification() {
+        let storage_manager = StorageManager::new(2).with_chunk_size(8);
+        storage_manager.add_node("node1".to_string()).unwrap();
+        storage_manager.add_node("node2".to_string()).unwrap();
+
+        let value = b"integrity matters".to_vec();
+        let content_hash = storage_manager.store_content(value.clone()).unwrap();
+        let chunk = chunk_key(&storage_manager.get_content_manifest(&content_hash).unwrap().chunks[0]);
+
+        // A single corrupted replica is skipped in favour of a valid one
+        storage_manager.nodes.write().unwrap()[0].data.insert(chunk.clone(), b"tampered".to_vec());
+        assert_eq!(storage_manager.retrieve_content(&content_hash).unwrap(), value);
+
+        // Once every replica is corrupted retrieval fails
+        storage_manager.nodes.write().unwrap()[1].data.insert(chunk, b"tampered".to_vec());
+        assert!(storage_manager.retrieve_content(&content_hash).is_err());
+    }
+
+    #[cfg(feature = "fault_injection")]
+    #[test]
+    fn test_injected_replica_corruption() {
+        let storage_manager = StorageManager::new(2);
+        storage_manager.add_node("node1".to_string()).unwrap();
+        storage_manager.add_node("node2".to_string()).unwrap();
+
+        let value = b"survives one bad copy".to_vec();
+        let content_hash = storage_manager.store_content(value.clone()).unwrap();
+        let chunk = chunk_key(&storage_manager.get_content_manifest(&content_hash).unwrap().chunks[0]);
+        assert!(storage_manager.corrupt_replica(&chunk, 2).is_err());
+
+        storage_manager.corrupt_replica(&chunk, 0).unwrap();
+        assert_eq!(storage_manager.retrieve_content(&content_hash).unwrap(), value);
+        storage_manager.corrupt_replica(&chunk, 1).unwrap();
+        assert!(storage_manager.retrieve_content(&content_hash).is_err());
+    }
+}
@@ -0,0 +1,345 @@
+// File: crates/icn_storage/src/wal.rs
+
+use crate::{StorageManager, StorageNode};
+use icn_common::{IcnError, IcnResult};
+use log::{info, warn};
+use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Where the write-ahead log lives and how hard it tries to keep it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DurabilityConfig {
+    pub path: PathBuf,
+    /// Whether every record is fsynced before the write it describes goes
+    /// ahead. Without it a power loss can take the last few writes with it,
+    /// though a process crash still cannot.
+    pub sync_writes: bool,
+}
+
+impl DurabilityConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        DurabilityConfig { path: path.into(), sync_writes: true }
+    }
+}
+
+/// A storage write, logged before it is made.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum WalOp {
+    AddNode { id: String },
+    Store { key: String, value: Vec<u8>, nodes: Vec<usize> },
+    Remove { key: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+enum WalRecord {
+    Begin { txn: u64, op: WalOp },
+    Commit { txn: u64 },
+    Abort { txn: u64 },
+}
+
+/// What replaying the log on startup did.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RecoveryReport {
+    /// Committed writes applied again.
+    pub replayed: usize,
+    /// Writes begun but never committed, now marked aborted.
+    pub rolled_back: usize,
+    /// Lines at the end of the log that were cut short or failed their
+    /// checksum, and were truncated.
+    pub torn_records: usize,
+}
+
+/// Append-only log of storage writes. Each line is the SHA-256 of a JSON
+/// record followed by the record, so a write cut short by a crash is
+/// recognised and dropped.
+#[derive(Debug)]
+pub struct WriteAheadLog {
+    config: DurabilityConfig,
+    file: File,
+    next_txn: u64,
+}
+
+impl WriteAheadLog {
+    pub fn open(config: DurabilityConfig) -> IcnResult<Self> {
+        if let Some(dir) = config.path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().read(true).append(true).create(true).open(&config.path)?;
+        Ok(WriteAheadLog { config, file, next_txn: 0 })
+    }
+
+    pub fn config(&self) -> &DurabilityConfig {
+        &self.config
+    }
+
+    /// Logs the intent to make a write and returns its transaction id.
+    pub fn begin(&mut self, op: WalOp) -> IcnResult<u64> {
+        let txn = self.next_txn;
+        self.append(&WalRecord::Begin { txn, op })?;
+        self.next_txn += 1;
+        Ok(txn)
+    }
+
+    pub fn commit(&mut self, txn: u64) -> IcnResult<()> {
+        self.append(&WalRecord::Commit { txn })
+    }
+
+    pub fn abort(&mut self, txn: u64) -> IcnResult<()> {
+        self.append(&WalRecord::Abort { txn })
+    }
+
+    /// Empties the log. Only safe once everything it holds is in a backup
+    /// that will be restored before the log is next replayed.
+    pub fn checkpoint(&mut self) -> IcnResult<()> {
+        self.file.set_len(0)?;
+        self.file.sync_all()?;
+        Ok(())
+    }
+
+    fn append(&mut self, record: &WalRecord) -> IcnResult<()> {
+        let encoded = serde_json::to_string(record)?;
+        writeln!(self.file, "{:x} {}", Sha256::digest(encoded.as_bytes()), encoded)?;
+        if self.config.sync_writes {
+            self.file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Reads every intact record, truncating the log at the first line that
+    /// is incomplete or fails its checksum. Returns the records and how
+    /// many lines were cut off.
+    fn read_records(&mut self) -> IcnResult<(Vec<WalRecord>, usize)> {
+        self.file.seek(SeekFrom::Start(0))?;
+        let mut reader = BufReader::new(&self.file);
+        let mut records = Vec::new();
+        let mut intact_len = 0u64;
+        let mut torn = 0;
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 {
+                break;
+            }
+            if torn > 0 {
+                torn += 1;
+                continue;
+            }
+            match parse_record(&line) {
+                Some(record) => {
+                    records.push(record);
+                    intact_len += read as u64;
+                }
+                None => torn += 1,
+            }
+        }
+        if torn > 0 {
+            warn!("Truncating {} torn records from the write-ahead log", torn);
+            self.file.set_len(intact_len)?;
+            self.file.sync_all()?;
+        }
+        self.next_txn = records.iter()
+            .filter_map(|record| match record {
+                WalRecord::Begin { txn, .. } => Some(txn + 1),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(0)
+            .max(self.next_txn);
+        Ok((records, torn))
+    }
+}
+
+fn parse_record(line: &[u8]) -> Option<WalRecord> {
+    let line = std::str::from_utf8(line.strip_suffix(b"\n")?).ok()?;
+    let (checksum, encoded) = line.split_once(' ')?;
+    if format!("{:x}", Sha256::digest(encoded.as_bytes())) != checksum {
+        return None;
+    }
+    serde_json::from_str(encoded).ok()
+}
+
+impl StorageManager {
+    /// Logs every node added, store and removal to `config.path` before it
+    /// is made. Call `recover` before taking writes to bring back what the
+    /// log already holds.
+    pub fn with_write_ahead_log(mut self, config: DurabilityConfig) -> IcnResult<Self> {
+        self.set_write_ahead_log(config)?;
+        Ok(self)
+    }
+
+    pub fn set_write_ahead_log(&mut self, config: DurabilityConfig) -> IcnResult<()> {
+        self.wal = Some(Arc::new(Mutex::new(WriteAheadLog::open(config)?)));
+        Ok(())
+    }
+
+    pub fn durability(&self) -> Option<DurabilityConfig> {
+        self.wal.as_ref().and_then(|wal| wal.lock().ok()).map(|wal| wal.config().clone())
+    }
+
+    /// Replays the writes the log saw committed, in commit order, and marks
+    /// those a crash interrupted as aborted so they are never applied.
+    /// Replaying over a backup taken after some of the writes is harmless:
+    /// each write leaves a key exactly as the log describes it.
+    pub fn recover(&self) -> IcnResult<RecoveryReport> {
+        let Some(wal) = &self.wal else {
+            return Ok(RecoveryReport::default());
+        };
+        let mut wal = wal.lock().map_err(|_| IcnError::Storage("Failed to lock write-ahead log".into()))?;
+        let (records, torn_records) = wal.read_records()?;
+        let mut report = RecoveryReport { torn_records, ..RecoveryReport::default() };
+
+        let mut begun: HashMap<u64, WalOp> = HashMap::new();
+        let mut order = Vec::new();
+        let mut settled = HashSet::new();
+        for record in records {
+            match record {
+                WalRecord::Begin { txn, op } => {
+                    begun.insert(txn, op);
+                    order.push(txn);
+                }
+                WalRecord::Commit { txn } => {
+                    settled.insert(txn);
+                    if let Some(op) = begun.get(&txn) {
+                        self.apply_op(op)?;
+                        report.replayed += 1;
+                    }
+                }
+                WalRecord::Abort { txn } => {
+                    settled.insert(txn);
+                }
+            }
+        }
+        for txn in order.into_iter().filter(|txn| !settled.contains(txn)) {
+            wal.abort(txn)?;
+            report.rolled_back += 1;
+        }
+
+        info!(
+            "Recovered storage from the write-ahead log: {} writes replayed, {} rolled back, {} torn records",
+            report.replayed, report.rolled_back, report.torn_records,
+        );
+        Ok(report)
+    }
+
+    /// Empties the write-ahead log. Call only right after a backup that
+    /// holds every committed write, with writes stopped.
+    pub fn checkpoint_wal(&self) -> IcnResult<()> {
+        if let Some(wal) = &self.wal {
+            wal.lock().map_err(|_| IcnError::Storage("Failed to lock write-ahead log".into()))?.checkpoint()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn wal_begin(&self, op: impl FnOnce() -> WalOp) -> IcnResult<Option<u64>> {
+        match &self.wal {
+            Some(wal) => wal.lock().map_err(|_| IcnError::Storage("Failed to lock write-ahead log".into()))?.begin(op()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Commits or aborts a logged write, depending on whether it went through.
+    pub(crate) fn wal_finish<T>(&self, txn: Option<u64>, result: IcnResult<T>) -> IcnResult<T> {
+        if let (Some(wal), Some(txn)) = (&self.wal, txn) {
+            let mut wal = wal.lock().map_err(|_| IcnError::Storage("Failed to lock write-ahead log".into()))?;
+            match &result {
+                Ok(_) => wal.commit(txn)?,
+                Err(_) => wal.abort(txn)?,
+            }
+        }
+        result
+    }
+
+    fn apply_op(&self, op: &WalOp) -> IcnResult<()> {
+        let mut nodes = self.nodes.write().map_err(|_| IcnError::Storage("Failed to lock nodes".into()))?;
+        let mut data_location = self.data_location.write().map_err(|_| IcnError::Storage("Failed to lock data location".into()))?;
+        match op {
+            WalOp::AddNode { id } => {
+                if !nodes.iter().any(|node| &node.id == id) {
+                    nodes.push(StorageNode { id: id.clone(), data: HashMap::new() });
+                }
+            }
+            WalOp::Store { key, value, nodes: node_ids } => {
+                if node_ids.iter().any(|&id| id >= nodes.len()) {
+                    return Err(IcnError::Storage(format!("Write-ahead log stores {} on a node that does not exist", key)));
+                }
+                for &id in node_ids {
+                    nodes[id].data.insert(key.clone(), value.clone());
+                }
+                if let Some(previous) = data_location.insert(key.clone(), node_ids.clone()) {
+                    for id in previous.into_iter().filter(|id| !node_ids.contains(id)) {
+                        if let Some(node) = nodes.get_mut(id) {
+                            node.data.remove(key);
+                        }
+                    }
+                }
+            }
+            WalOp::Remove { key } => {
+                if let Some(node_ids) = data_location.remove(key) {
+                    for id in node_ids {
+                        if let Some(node) = nodes.get_mut(id) {
+                            node.data.remove(key);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_replays_committed_writes_and_drops_the_rest() {
+        let path = std::env::temp_dir().join(format!("icn-storage-wal-{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = DurabilityConfig::new(&path);
+
+        let storage = StorageManager::new(2).with_write_ahead_log(config.clone()).unwrap();
+        for id in ["node1", "node2", "node3"] {
+            storage.add_node(id.to_string()).unwrap();
+        }
+        storage.store_data("kept", b"one".to_vec()).unwrap();
+        storage.store_data("gone", b"two".to_vec()).unwrap();
+        storage.store_data("kept", b"three".to_vec()).unwrap();
+        storage.remove_data("gone").unwrap();
+        assert!(storage.remove_data("never-stored").is_err());
+
+        // A crash midway through a store, then while writing the next record
+        let txn = storage.wal_begin(|| WalOp::Store { key: "torn".to_string(), value: b"four".to_vec(), nodes: vec![0] }).unwrap();
+        assert!(txn.is_some());
+        drop(storage);
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(file, "{{\"Commit\":").unwrap();
+        drop(file);
+
+        let restarted = StorageManager::new(2).with_write_ahead_log(config.clone()).unwrap();
+        let report = restarted.recover().unwrap();
+        assert_eq!(report, RecoveryReport { replayed: 7, rolled_back: 1, torn_records: 1 });
+        assert_eq!(restarted.get_node_count(), 3);
+        assert_eq!(restarted.retrieve_data("kept").unwrap(), b"three".to_vec());
+        assert!(!restarted.key_exists("gone").unwrap());
+        assert!(!restarted.key_exists("torn").unwrap());
+        assert_eq!(restarted.get_total_storage_size().unwrap(), 10);
+
+        // The interrupted store stays aborted and new ids do not reuse its
+        restarted.store_data("after", b"five".to_vec()).unwrap();
+        let again = StorageManager::new(2).with_write_ahead_log(config.clone()).unwrap();
+        assert_eq!(again.recover().unwrap(), RecoveryReport { replayed: 8, rolled_back: 0, torn_records: 0 });
+        assert_eq!(again.retrieve_data("after").unwrap(), b"five".to_vec());
+
+        again.checkpoint_wal().unwrap();
+        let empty = StorageManager::new(2).with_write_ahead_log(config).unwrap();
+        assert_eq!(empty.recover().unwrap(), RecoveryReport::default());
+        assert_eq!(empty.get_node_count(), 0);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 // File: crates/icn_testnet/src/main.rs
 
 use icn_core::{IcnNode, Config};
-use icn_common::{Transaction, Proposal, ProposalType, ProposalCategory, CurrencyType, ProposalStatus};
+use icn_common::{Transaction, SignatureScheme, Proposal, ProposalType, ProposalCategory, CurrencyType, ProposalStatus};
 use std::io::{self, Write};
 use chrono::{Duration, Utc};
 use log::{info, warn, error};
@@ -79,6 +79,10 @@ async fn process_transaction(node: &IcnNode) -> Result<(), Box<dyn std::error::E
         currency_type,
         timestamp: Utc::now().timestamp(),
         signature: None,
+        memo: None,
+        signature_scheme: SignatureScheme::Ed25519,
+        valid_until: None,
+        capability: None,
     };
 
     node.process_transaction(transaction).await?;
@@ -107,9 +111,17 @@ async fn create_proposal(node: &IcnNode) -> Result<(), Box<dyn std::error::Error
         category,
         required_quorum: 0.66,
         execution_timestamp: None,
+        weight_mode: Default::default(),
+        actions: Vec::new(),
+        scope: Default::default(),
+        ballot: Default::default(),
+        depends_on: Vec::new(),
+        bundle: None,
     };
 
-    let proposal_id = node.create_proposal(proposal).await?;
+    println!("Sign these bytes with the proposer's key: {}", hex::encode(proposal.signing_bytes()));
+    let signature = hex::decode(get_input("Signature (hex): ")?)?;
+    let proposal_id = node.create_proposal(proposal, &signature).await?;
     println!("Proposal created successfully. ID: {}", proposal_id);
     Ok(())
 }
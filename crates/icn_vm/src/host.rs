@@ -0,0 +1,32 @@
+// File: crates/icn_vm/src/host.rs
+
+use icn_common::IcnResult;
+
+/// The node operations behind the VM's system opcodes.
+///
+/// A host acts for one calling identity. It decides what that identity may
+/// do and returns an error to fail the opcode, which stops the program.
+pub trait HostInterface: Send {
+    /// Mints `amount` of a currency, named as in `CurrencyType`, to the caller.
+    fn mint_currency(&mut self, currency_type: &str, amount: f64) -> IcnResult<()>;
+
+    /// Casts the caller's vote. The weight is derived by the node.
+    fn vote_on_proposal(&mut self, proposal_id: &str, in_favor: bool) -> IcnResult<()>;
+
+    fn allocate_resource(&mut self, resource: &str, amount: i64) -> IcnResult<()>;
+
+    fn update_reputation(&mut self, address: &str, change: i64) -> IcnResult<()>;
+
+    /// Opens a proposal by the caller and returns its id.
+    fn create_proposal(&mut self, title: &str, description: &str) -> IcnResult<String>;
+
+    /// The proposal's status, as the name of a `ProposalStatus` variant.
+    fn get_proposal_status(&self, proposal_id: &str) -> IcnResult<String>;
+
+    fn add_cooperative_member(&mut self, coop_id: &str, member_id: &str) -> IcnResult<()>;
+
+    fn emit_event(&mut self, name: &str, data: &str) -> IcnResult<()>;
+
+    /// Timestamp of the block the program runs in, in seconds since the epoch.
+    fn block_time(&self) -> IcnResult<i64>;
+}
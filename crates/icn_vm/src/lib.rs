@@ -1,13 +1,27 @@
+pub mod host;
+pub mod limits;
+pub mod storage;
+
+pub use crate::host::HostInterface;
+pub use crate::limits::{gas_cost, value_size, ExecutionLimits, STORAGE_GAS_PER_BYTE};
+pub use crate::storage::{ContractStorage, EMPTY_STATE_ROOT};
+
 use icn_common::{IcnError, IcnResult};
+use serde::{Serialize, Deserialize};
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     Int(i64),
     Float(f64),
     Bool(bool),
     String(String),
+    List(Vec<Value>),
+    /// Kept ordered so a map encodes, and hashes into the state root, the
+    /// same way on every node.
+    Map(BTreeMap<String, Value>),
 }
 
 impl PartialOrd for Value {
@@ -44,6 +58,33 @@ pub enum Opcode {
     Not,
     Store(String),
     Load(String),
+    /// Writes the top of the stack to the running contract's storage.
+    StorageStore(String),
+    /// Pushes a value from the running contract's storage.
+    StorageLoad(String),
+    /// Pushes an empty list.
+    NewList,
+    /// Pushes an empty map.
+    NewMap,
+    /// Pops a value and a list, and pushes the list with the value appended.
+    Append,
+    /// Pops an integer index and a list, and pushes the element at the index.
+    Index,
+    /// Pops a string key and a map, and pushes the value under the key.
+    Get,
+    /// Pops a value, a key and a list or map, and pushes the collection with
+    /// the value set. A list index must already be in bounds.
+    Set,
+    /// Pops a key and a list or map, and pushes the collection without the
+    /// element. Later list elements move down by one.
+    Remove,
+    /// Pops a string key and a map, and pushes whether the map has the key.
+    Contains,
+    /// Pops a list, map or string and pushes its length.
+    Len,
+    /// Pops a map and pushes a list of its keys in order. Maps are iterated
+    /// by walking this list with `Len` and `Index`.
+    Keys,
     JumpIf(usize),
     Jump(usize),
     Call(String),
@@ -59,14 +100,23 @@ pub enum Opcode {
     UpdateReputation,
     CreateProposal,
     GetProposalStatus,
+    /// Pops the event data and then its name. The data is a string, or a
+    /// list whose values are joined with commas.
     EmitEvent,
+    /// Pushes the timestamp of the block the program runs in.
+    BlockTime,
 }
 
 pub struct CoopVM {
     stack: Vec<Value>,
     memory: HashMap<String, Value>,
+    storage: ContractStorage,
+    /// Carries out system opcodes; without one they fail.
+    host: Option<Box<dyn HostInterface>>,
     program: Vec<Opcode>,
     pc: usize,
+    limits: ExecutionLimits,
+    gas_used: u64,
 }
 
 impl CoopVM {
@@ -74,15 +124,152 @@ impl CoopVM {
         CoopVM {
             stack: Vec::new(),
             memory: HashMap::new(),
+            storage: ContractStorage::default(),
+            host: None,
             program,
             pc: 0,
+            limits: ExecutionLimits::default(),
+            gas_used: 0,
+        }
+    }
+
+    /// Replaces the default limits applied to each `execute` call.
+    pub fn with_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn set_limits(&mut self, limits: ExecutionLimits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &ExecutionLimits {
+        &self.limits
+    }
+
+    /// Gas spent by the last `execute` call, including a failed one.
+    pub fn gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    /// Creates a VM whose system opcodes call into `host`.
+    pub fn with_host(program: Vec<Opcode>, host: Box<dyn HostInterface>) -> Self {
+        CoopVM {
+            host: Some(host),
+            ..CoopVM::new(program)
+        }
+    }
+
+    pub fn set_host(&mut self, host: Box<dyn HostInterface>) {
+        self.host = Some(host);
+    }
+
+    pub fn take_host(&mut self) -> Option<Box<dyn HostInterface>> {
+        self.host.take()
+    }
+
+    /// The values left on the stack, bottom first.
+    pub fn stack(&self) -> &[Value] {
+        &self.stack
+    }
+
+    /// Replaces the program, clearing the stack and memory of the last run.
+    pub fn load_program(&mut self, program: Vec<Opcode>) {
+        self.program = program;
+        self.pc = 0;
+        self.stack.clear();
+        self.memory.clear();
+    }
+
+    pub fn push(&mut self, value: Value) {
+        self.stack.push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<Value> {
+        self.stack.pop()
+    }
+
+    /// Makes the next `execute` start at `entry`, the first instruction of
+    /// the function being called.
+    pub fn jump_to(&mut self, entry: usize) -> IcnResult<()> {
+        if entry > self.program.len() {
+            return Err(IcnError::Vm(format!("Entry point {} is past the end of the program", entry)));
         }
+        self.pc = entry;
+        Ok(())
     }
 
+    /// Creates a VM whose storage opcodes act on the given contract storage.
+    pub fn with_storage(program: Vec<Opcode>, storage: ContractStorage) -> Self {
+        CoopVM {
+            storage,
+            ..CoopVM::new(program)
+        }
+    }
+
+    pub fn set_storage(&mut self, storage: ContractStorage) {
+        self.storage = storage;
+    }
+
+    pub fn storage(&self) -> &ContractStorage {
+        &self.storage
+    }
+
+    /// Hands back the contract storage, leaving the VM with none.
+    pub fn take_storage(&mut self) -> ContractStorage {
+        std::mem::take(&mut self.storage)
+    }
+
+    /// Runs the program within the VM's limits. Gas and the timeout start
+    /// afresh on every call.
     pub fn execute(&mut self) -> IcnResult<()> {
+        let started = Instant::now();
+        self.gas_used = 0;
         while self.pc < self.program.len() {
+            if started.elapsed() > self.limits.timeout {
+                return Err(IcnError::VmTimeout(format!(
+                    "Execution exceeded {:?} at instruction {}", self.limits.timeout, self.pc
+                )));
+            }
+            self.charge_gas(gas_cost(&self.program[self.pc]))?;
+            if let Opcode::Return = self.program[self.pc] {
+                break;
+            }
             self.execute_instruction()?;
-            self.pc += 1;
+            self.check_memory()?;
+            self.pc = self.pc.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    fn charge_gas(&mut self, cost: u64) -> IcnResult<()> {
+        self.gas_used = self.gas_used.saturating_add(cost);
+        if self.gas_used > self.limits.max_gas {
+            return Err(IcnError::VmOutOfGas(format!(
+                "Used {} gas with a limit of {} at instruction {}", self.gas_used, self.limits.max_gas, self.pc
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks the limits after an instruction. Every value reaches memory or
+    /// storage by way of the top of the stack, so that is the only value
+    /// whose size needs checking.
+    fn check_memory(&self) -> IcnResult<()> {
+        if self.stack.len() > self.limits.max_stack_depth {
+            return Err(IcnError::VmOutOfMemory(format!(
+                "Stack depth {} exceeds the limit of {}", self.stack.len(), self.limits.max_stack_depth
+            )));
+        }
+        if self.memory.len() > self.limits.max_memory_entries {
+            return Err(IcnError::VmOutOfMemory(format!(
+                "{} variables exceed the limit of {}", self.memory.len(), self.limits.max_memory_entries
+            )));
+        }
+        if let Some(size) = self.stack.last().map(value_size).filter(|size| *size > self.limits.max_value_size) {
+            return Err(IcnError::VmOutOfMemory(format!(
+                "A value of {} bytes exceeds the limit of {}", size, self.limits.max_value_size
+            )));
         }
         Ok(())
     }
@@ -94,11 +281,11 @@ impl CoopVM {
             Opcode::Pop => {
                 self.stack.pop().ok_or_else(|| IcnError::Vm("Stack underflow".into()))?;
             }
-            Opcode::Add => self.binary_op(|a, b| a + b)?,
-            Opcode::Sub => self.binary_op(|a, b| a - b)?,
-            Opcode::Mul => self.binary_op(|a, b| a * b)?,
-            Opcode::Div => self.binary_op(|a, b| a / b)?,
-            Opcode::Mod => self.binary_op(|a, b| a % b)?,
+            Opcode::Add => self.binary_op(i64::checked_add, |a, b| a + b)?,
+            Opcode::Sub => self.binary_op(i64::checked_sub, |a, b| a - b)?,
+            Opcode::Mul => self.binary_op(i64::checked_mul, |a, b| a * b)?,
+            Opcode::Div => self.binary_op(i64::checked_div, |a, b| a / b)?,
+            Opcode::Mod => self.binary_op(i64::checked_rem, |a, b| a % b)?,
             Opcode::Eq => self.compare_op(|a, b| a == b)?,
             Opcode::Neq => self.compare_op(|a, b| a != b)?,
             Opcode::Gt => self.compare_op(|a, b| a > b)?,
@@ -127,40 +314,180 @@ impl CoopVM {
                 let value = self.memory.get(&name).ok_or_else(|| IcnError::Vm("Variable not found".into()))?.clone();
                 self.stack.push(value);
             }
+            Opcode::StorageStore(key) => {
+                let value = self.stack.pop().ok_or_else(|| IcnError::Vm("Stack underflow".into()))?;
+                let bytes = (key.len() + value_size(&value)) as u64;
+                self.charge_gas(bytes.saturating_mul(STORAGE_GAS_PER_BYTE))?;
+                self.storage.set(&key, value);
+            }
+            Opcode::StorageLoad(key) => {
+                let value = self.storage.get(&key)
+                    .ok_or_else(|| IcnError::Vm(format!("Storage key {} not found", key)))?
+                    .clone();
+                self.stack.push(value);
+            }
+            Opcode::NewList => self.stack.push(Value::List(Vec::new())),
+            Opcode::NewMap => self.stack.push(Value::Map(BTreeMap::new())),
+            Opcode::Append => {
+                let value = self.pop_value()?;
+                let mut list = self.pop_list()?;
+                list.push(value);
+                self.stack.push(Value::List(list));
+            }
+            Opcode::Index => {
+                let index = self.pop_int()?;
+                let mut list = self.pop_list()?;
+                let index = list_index(index, list.len())?;
+                self.stack.push(list.swap_remove(index));
+            }
+            Opcode::Get => {
+                let key = self.pop_string()?;
+                let mut map = self.pop_map()?;
+                let value = map.remove(&key).ok_or_else(|| IcnError::Vm(format!("Map has no key {}", key)))?;
+                self.stack.push(value);
+            }
+            Opcode::Set => {
+                let value = self.pop_value()?;
+                let key = self.pop_value()?;
+                let collection = match (self.pop_value()?, key) {
+                    (Value::List(mut list), Value::Int(index)) => {
+                        let index = list_index(index, list.len())?;
+                        list[index] = value;
+                        Value::List(list)
+                    }
+                    (Value::Map(mut map), Value::String(key)) => {
+                        map.insert(key, value);
+                        Value::Map(map)
+                    }
+                    _ => return Err(IcnError::Vm("Expected a list and integer index or a map and string key".into())),
+                };
+                self.stack.push(collection);
+            }
+            Opcode::Remove => {
+                let key = self.pop_value()?;
+                let collection = match (self.pop_value()?, key) {
+                    (Value::List(mut list), Value::Int(index)) => {
+                        let index = list_index(index, list.len())?;
+                        list.remove(index);
+                        Value::List(list)
+                    }
+                    (Value::Map(mut map), Value::String(key)) => {
+                        map.remove(&key);
+                        Value::Map(map)
+                    }
+                    _ => return Err(IcnError::Vm("Expected a list and integer index or a map and string key".into())),
+                };
+                self.stack.push(collection);
+            }
+            Opcode::Contains => {
+                let key = self.pop_string()?;
+                let map = self.pop_map()?;
+                self.stack.push(Value::Bool(map.contains_key(&key)));
+            }
+            Opcode::Len => {
+                let len = match self.pop_value()? {
+                    Value::List(list) => list.len(),
+                    Value::Map(map) => map.len(),
+                    Value::String(s) => s.len(),
+                    _ => return Err(IcnError::Vm("Expected a list, map or string".into())),
+                };
+                self.stack.push(Value::Int(len as i64));
+            }
+            Opcode::Keys => {
+                let map = self.pop_map()?;
+                self.stack.push(Value::List(map.into_keys().map(Value::String).collect()));
+            }
             Opcode::JumpIf(target) => {
                 let condition = self.pop_bool()?;
                 if condition {
-                    self.pc = target - 1; // -1 because pc will be incremented after this
+                    self.pc = target.wrapping_sub(1); // -1 because pc will be incremented after this
                 }
             }
             Opcode::Jump(target) => {
-                self.pc = target - 1; // -1 because pc will be incremented after this
+                self.pc = target.wrapping_sub(1); // -1 because pc will be incremented after this
             }
             Opcode::Call(_) => return Err(IcnError::Vm("Function calls not implemented".into())),
-            Opcode::Return => return Ok(()),
-            Opcode::NetNodeConnect => println!("Executing NetNodeConnect"),
-            Opcode::ChainBlockCreate => println!("Executing ChainBlockCreate"),
-            Opcode::EconCurrencyMint => println!("Executing EconCurrencyMint"),
-            Opcode::GovProposalSubmit => println!("Executing GovProposalSubmit"),
-            Opcode::CoopMemberAdd => println!("Executing CoopMemberAdd"),
-            Opcode::CommEventOrganize => println!("Executing CommEventOrganize"),
-            Opcode::VoteOnProposal => println!("Executing VoteOnProposal"),
-            Opcode::AllocateResource => println!("Executing AllocateResource"),
-            Opcode::UpdateReputation => println!("Executing UpdateReputation"),
-            Opcode::CreateProposal => println!("Executing CreateProposal"),
-            Opcode::GetProposalStatus => println!("Executing GetProposalStatus"),
-            Opcode::EmitEvent => println!("Executing EmitEvent"),
+            Opcode::Return => {}
+            Opcode::NetNodeConnect => return Err(IcnError::Vm("Programs cannot open network connections".into())),
+            Opcode::ChainBlockCreate => return Err(IcnError::Vm("Programs cannot create blocks".into())),
+            Opcode::EconCurrencyMint => {
+                let currency_type = self.pop_string()?;
+                let amount = self.pop_float()?;
+                self.host()?.mint_currency(&currency_type, amount)?;
+            }
+            Opcode::GovProposalSubmit => {
+                let description = self.pop_string()?;
+                let proposal_id = self.host()?.create_proposal(&description, &description)?;
+                self.stack.push(Value::String(proposal_id));
+            }
+            Opcode::CoopMemberAdd => {
+                let member_id = self.pop_string()?;
+                let coop_id = self.pop_string()?;
+                self.host()?.add_cooperative_member(&coop_id, &member_id)?;
+            }
+            Opcode::CommEventOrganize => {
+                let details = self.pop_string()?;
+                self.host()?.emit_event("CommunityEvent", &details)?;
+            }
+            Opcode::VoteOnProposal => {
+                let in_favor = self.pop_bool()?;
+                let proposal_id = self.pop_string()?;
+                self.host()?.vote_on_proposal(&proposal_id, in_favor)?;
+            }
+            Opcode::AllocateResource => {
+                let amount = self.pop_int()?;
+                let resource = self.pop_string()?;
+                self.host()?.allocate_resource(&resource, amount)?;
+            }
+            Opcode::UpdateReputation => {
+                let change = self.pop_int()?;
+                let address = self.pop_string()?;
+                self.host()?.update_reputation(&address, change)?;
+            }
+            Opcode::CreateProposal => {
+                let description = self.pop_string()?;
+                let title = self.pop_string()?;
+                let proposal_id = self.host()?.create_proposal(&title, &description)?;
+                self.stack.push(Value::String(proposal_id));
+            }
+            Opcode::GetProposalStatus => {
+                let proposal_id = self.pop_string()?;
+                let status = self.host()?.get_proposal_status(&proposal_id)?;
+                self.stack.push(Value::String(status));
+            }
+            Opcode::EmitEvent => {
+                let data = match self.pop_value()? {
+                    Value::String(data) => data,
+                    Value::List(fields) => fields.iter().map(event_field).collect::<IcnResult<Vec<_>>>()?.join(", "),
+                    _ => return Err(IcnError::Vm("Expected string or list event data".into())),
+                };
+                let name = self.pop_string()?;
+                self.host()?.emit_event(&name, &data)?;
+            }
+            Opcode::BlockTime => {
+                let block_time = self.host()?.block_time()?;
+                self.stack.push(Value::Int(block_time));
+            }
         }
         Ok(())
     }
 
-    fn binary_op<F>(&mut self, op: F) -> IcnResult<()>
+    /// Applies `int_op` when both operands are integers, failing on
+    /// overflow or division by zero, and `float_op` otherwise.
+    fn binary_op<I, F>(&mut self, int_op: I, float_op: F) -> IcnResult<()>
     where
+        I: Fn(i64, i64) -> Option<i64>,
         F: Fn(f64, f64) -> f64,
     {
+        if let [.., Value::Int(a), Value::Int(b)] = self.stack[..] {
+            let result = int_op(a, b).ok_or_else(|| IcnError::Vm("Integer overflow or division by zero".into()))?;
+            self.stack.truncate(self.stack.len() - 2);
+            self.stack.push(Value::Int(result));
+            return Ok(());
+        }
         let b = self.pop_float()?;
         let a = self.pop_float()?;
-        self.stack.push(Value::Float(op(a, b)));
+        self.stack.push(Value::Float(float_op(a, b)));
         Ok(())
     }
 
@@ -174,6 +501,24 @@ impl CoopVM {
         Ok(())
     }
 
+    fn pop_value(&mut self) -> IcnResult<Value> {
+        self.stack.pop().ok_or_else(|| IcnError::Vm("Stack underflow".into()))
+    }
+
+    fn pop_list(&mut self) -> IcnResult<Vec<Value>> {
+        match self.pop_value()? {
+            Value::List(list) => Ok(list),
+            _ => Err(IcnError::Vm("Expected list value".into())),
+        }
+    }
+
+    fn pop_map(&mut self) -> IcnResult<BTreeMap<String, Value>> {
+        match self.pop_value()? {
+            Value::Map(map) => Ok(map),
+            _ => Err(IcnError::Vm("Expected map value".into())),
+        }
+    }
+
     fn pop_float(&mut self) -> IcnResult<f64> {
         match self.stack.pop().ok_or_else(|| IcnError::Vm("Stack underflow".into()))? {
             Value::Float(f) => Ok(f),
@@ -188,11 +533,50 @@ impl CoopVM {
             _ => Err(IcnError::Vm("Expected boolean value".into())),
         }
     }
+
+    fn pop_int(&mut self) -> IcnResult<i64> {
+        match self.stack.pop().ok_or_else(|| IcnError::Vm("Stack underflow".into()))? {
+            Value::Int(i) => Ok(i),
+            _ => Err(IcnError::Vm("Expected integer value".into())),
+        }
+    }
+
+    fn pop_string(&mut self) -> IcnResult<String> {
+        match self.stack.pop().ok_or_else(|| IcnError::Vm("Stack underflow".into()))? {
+            Value::String(s) => Ok(s),
+            _ => Err(IcnError::Vm("Expected string value".into())),
+        }
+    }
+
+    fn host(&mut self) -> IcnResult<&mut dyn HostInterface> {
+        match self.host.as_deref_mut() {
+            Some(host) => Ok(host),
+            None => Err(IcnError::Vm("System opcodes need a host interface".into())),
+        }
+    }
+}
+
+/// Checks a list index taken off the stack against the list's length.
+fn event_field(value: &Value) -> IcnResult<String> {
+    match value {
+        Value::Int(i) => Ok(i.to_string()),
+        Value::Float(f) => Ok(f.to_string()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::String(s) => Ok(s.clone()),
+        _ => Err(IcnError::Vm("Event fields must be plain values".into())),
+    }
+}
+
+fn list_index(index: i64, len: usize) -> IcnResult<usize> {
+    usize::try_from(index).ok()
+        .filter(|&index| index < len)
+        .ok_or_else(|| IcnError::Vm(format!("Index {} is out of bounds for a list of length {}", index, len)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Duration;
 
     #[test]
     fn test_basic_operations() {
@@ -274,7 +658,171 @@ mod tests {
         let mut vm = CoopVM::new(program);
         assert!(vm.execute().is_ok());
         
-        assert_eq!(vm.stack, vec![Value::Float(52.0)]);
+        // Integers stay integers
+        assert_eq!(vm.stack, vec![Value::Int(52)]);
+        assert!(CoopVM::new(vec![Opcode::Push(Value::Int(i64::MAX)), Opcode::Push(Value::Int(1)), Opcode::Add]).execute().is_err());
+    }
+
+    #[test]
+    fn test_storage_is_separate_from_memory() {
+        let program = vec![
+            Opcode::Push(Value::Int(7)),
+            Opcode::StorageStore("x".to_string()),
+            Opcode::Push(Value::Int(1)),
+            Opcode::Store("x".to_string()),
+            Opcode::StorageLoad("x".to_string()),
+        ];
+
+        let mut vm = CoopVM::with_storage(program, ContractStorage::new("counter"));
+        assert!(vm.execute().is_ok());
+        assert_eq!(vm.stack, vec![Value::Int(7)]);
+
+        let mut storage = vm.take_storage();
+        assert_eq!(storage.take_changes(), vec![("x".to_string(), Value::Int(7))]);
+
+        let mut vm = CoopVM::with_storage(vec![Opcode::StorageLoad("y".to_string())], storage);
+        assert!(vm.execute().is_err());
+    }
+
+    #[derive(Default)]
+    struct RecordingHost {
+        calls: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    impl HostInterface for RecordingHost {
+        fn mint_currency(&mut self, currency_type: &str, amount: f64) -> IcnResult<()> {
+            self.calls.lock().unwrap().push(format!("mint {} {}", amount, currency_type));
+            Ok(())
+        }
+
+        fn vote_on_proposal(&mut self, proposal_id: &str, in_favor: bool) -> IcnResult<()> {
+            self.calls.lock().unwrap().push(format!("vote {} {}", proposal_id, in_favor));
+            Ok(())
+        }
+
+        fn allocate_resource(&mut self, _resource: &str, _amount: i64) -> IcnResult<()> {
+            Err(IcnError::Vm("Not allowed".into()))
+        }
+
+        fn update_reputation(&mut self, _address: &str, _change: i64) -> IcnResult<()> {
+            Err(IcnError::Vm("Not allowed".into()))
+        }
+
+        fn create_proposal(&mut self, title: &str, _description: &str) -> IcnResult<String> {
+            self.calls.lock().unwrap().push(format!("propose {}", title));
+            Ok("p1".to_string())
+        }
+
+        fn get_proposal_status(&self, _proposal_id: &str) -> IcnResult<String> {
+            Ok("Active".to_string())
+        }
+
+        fn add_cooperative_member(&mut self, _coop_id: &str, _member_id: &str) -> IcnResult<()> {
+            Err(IcnError::Vm("Not allowed".into()))
+        }
+
+        fn emit_event(&mut self, _name: &str, _data: &str) -> IcnResult<()> {
+            Ok(())
+        }
+
+        fn block_time(&self) -> IcnResult<i64> {
+            Ok(1_700_000_000)
+        }
+    }
+
+    #[test]
+    fn test_system_opcodes_call_host() {
+        let program = vec![
+            Opcode::Push(Value::Float(10.0)),
+            Opcode::Push(Value::String("BasicNeeds".to_string())),
+            Opcode::EconCurrencyMint,
+            Opcode::Push(Value::String("Budget".to_string())),
+            Opcode::Push(Value::String("Next year's budget".to_string())),
+            Opcode::CreateProposal,
+            Opcode::Push(Value::Bool(true)),
+            Opcode::VoteOnProposal,
+            Opcode::Push(Value::String("p1".to_string())),
+            Opcode::GetProposalStatus,
+            Opcode::BlockTime,
+        ];
+        let host = RecordingHost::default();
+        let calls = host.calls.clone();
+        let mut vm = CoopVM::with_host(program.clone(), Box::new(host));
+        vm.execute().unwrap();
+        assert_eq!(*calls.lock().unwrap(), vec!["mint 10 BasicNeeds", "propose Budget", "vote p1 true"]);
+        assert_eq!(vm.stack(), &[Value::String("Active".to_string()), Value::Int(1_700_000_000)]);
+
+        // Refused operations stop the program, and nothing runs without a host
+        let refused = vec![Opcode::Push(Value::String("cpu".to_string())), Opcode::Push(Value::Int(4)), Opcode::AllocateResource];
+        assert!(CoopVM::with_host(refused, Box::new(RecordingHost::default())).execute().is_err());
+        assert!(CoopVM::new(program).execute().is_err());
+    }
+
+    #[test]
+    fn test_lists_and_maps() {
+        let string = |s: &str| Opcode::Push(Value::String(s.to_string()));
+        let program = vec![
+            Opcode::NewMap,
+            string("alice"),
+            Opcode::Push(Value::Int(5)),
+            Opcode::Set,
+            string("bob"),
+            Opcode::Push(Value::Int(7)),
+            Opcode::Set,
+            Opcode::Store("balances".to_string()),
+            Opcode::Load("balances".to_string()),
+            Opcode::Keys,
+            Opcode::Store("members".to_string()),
+            Opcode::Load("members".to_string()),
+            Opcode::Len,
+            Opcode::Load("members".to_string()),
+            Opcode::Push(Value::Int(1)),
+            Opcode::Index,
+            Opcode::Load("balances".to_string()),
+            string("bob"),
+            Opcode::Get,
+            Opcode::Load("balances".to_string()),
+            string("carol"),
+            Opcode::Contains,
+            Opcode::NewList,
+            Opcode::Push(Value::Int(1)),
+            Opcode::Append,
+            Opcode::Push(Value::Int(2)),
+            Opcode::Append,
+            Opcode::Push(Value::Int(0)),
+            Opcode::Push(Value::Int(9)),
+            Opcode::Set,
+            Opcode::Load("balances".to_string()),
+            string("alice"),
+            Opcode::Remove,
+            Opcode::StorageStore("balances".to_string()),
+        ];
+        let mut vm = CoopVM::new(program);
+        vm.execute().unwrap();
+        assert_eq!(vm.stack(), &[
+            Value::Int(2),
+            Value::String("bob".to_string()),
+            Value::Int(7),
+            Value::Bool(false),
+            Value::List(vec![Value::Int(9), Value::Int(2)]),
+        ]);
+
+        // Collections survive the trip through contract storage
+        let stored = vm.storage().get("balances").unwrap();
+        assert_eq!(stored, &Value::Map(BTreeMap::from([("bob".to_string(), Value::Int(7))])));
+        assert_eq!(&Value::from_bytes(&stored.to_bytes().unwrap()).unwrap(), stored);
+
+        // Indexes are bounds checked and map keys must exist
+        let list = Opcode::Push(Value::List(vec![Value::Int(1)]));
+        for program in [
+            vec![list.clone(), Opcode::Push(Value::Int(1)), Opcode::Index],
+            vec![list.clone(), Opcode::Push(Value::Int(-1)), Opcode::Index],
+            vec![list.clone(), Opcode::Push(Value::Int(3)), Opcode::Push(Value::Int(0)), Opcode::Set],
+            vec![list, string("a"), Opcode::Remove],
+            vec![Opcode::NewMap, string("a"), Opcode::Get],
+        ] {
+            assert!(CoopVM::new(program).execute().is_err());
+        }
     }
 
     #[test]
@@ -289,6 +837,44 @@ mod tests {
         assert!(vm.execute().is_err());
     }
 
+    #[test]
+    fn test_execution_limits() {
+        let endless = vec![Opcode::Push(Value::Int(0)), Opcode::Push(Value::Int(1)), Opcode::Pop, Opcode::Jump(1)];
+        let mut vm = CoopVM::new(endless.clone()).with_limits(ExecutionLimits { max_gas: 100, ..Default::default() });
+        assert!(matches!(vm.execute(), Err(IcnError::VmOutOfGas(_))));
+        assert_eq!(vm.gas_used(), 101);
+
+        let limits = ExecutionLimits { max_gas: u64::MAX, timeout: Duration::from_millis(20), ..Default::default() };
+        assert!(matches!(CoopVM::new(endless).with_limits(limits).execute(), Err(IcnError::VmTimeout(_))));
+
+        // Storage writes are charged by the byte
+        let write = |value: &str| vec![Opcode::Push(Value::String(value.to_string())), Opcode::StorageStore("k".to_string())];
+        let mut vm = CoopVM::new(write("abc"));
+        vm.execute().unwrap();
+        assert_eq!(vm.gas_used(), 1 + 20 + 4 * STORAGE_GAS_PER_BYTE);
+        let limits = ExecutionLimits { max_gas: 1_000, ..Default::default() };
+        let mut vm = CoopVM::new(write(&"x".repeat(200))).with_limits(limits);
+        assert!(matches!(vm.execute(), Err(IcnError::VmOutOfGas(_))));
+        assert!(vm.storage().entries().is_empty());
+
+        let limits = ExecutionLimits { max_stack_depth: 2, ..Default::default() };
+        let program = vec![Opcode::Push(Value::Int(1)); 3];
+        assert!(matches!(CoopVM::new(program).with_limits(limits).execute(), Err(IcnError::VmOutOfMemory(_))));
+
+        let limits = ExecutionLimits { max_memory_entries: 1, ..Default::default() };
+        let program = vec![
+            Opcode::Push(Value::Int(1)),
+            Opcode::Store("a".to_string()),
+            Opcode::Push(Value::Int(2)),
+            Opcode::Store("b".to_string()),
+        ];
+        assert!(matches!(CoopVM::new(program).with_limits(limits).execute(), Err(IcnError::VmOutOfMemory(_))));
+
+        let limits = ExecutionLimits { max_value_size: 4, ..Default::default() };
+        let program = vec![Opcode::Push(Value::String("too long".to_string()))];
+        assert!(matches!(CoopVM::new(program).with_limits(limits).execute(), Err(IcnError::VmOutOfMemory(_))));
+    }
+
     #[test]
     fn test_complex_program() {
         let program = vec![
@@ -0,0 +1,71 @@
+// File: crates/icn_vm/src/limits.rs
+
+use crate::{Opcode, Value};
+use serde::{Serialize, Deserialize};
+use std::time::Duration;
+
+/// Resources one execution may use before the VM stops it.
+///
+/// Running out of gas fails with `IcnError::VmOutOfGas`, exceeding a memory
+/// limit with `IcnError::VmOutOfMemory` and running past the timeout with
+/// `IcnError::VmTimeout`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionLimits {
+    pub max_gas: u64,
+    pub max_stack_depth: usize,
+    /// Most variables the program may hold in VM memory at once.
+    pub max_memory_entries: usize,
+    /// Largest value, in bytes, that may be put on the stack.
+    pub max_value_size: usize,
+    pub timeout: Duration,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        ExecutionLimits {
+            max_gas: 1_000_000,
+            max_stack_depth: 1024,
+            max_memory_entries: 1024,
+            max_value_size: 64 * 1024,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// Gas charged for each byte of key and value a contract writes to its
+/// storage, on top of the opcode's own cost.
+pub const STORAGE_GAS_PER_BYTE: u64 = 10;
+
+/// Gas charged for running an opcode. Storage and system opcodes reach
+/// outside the VM and cost more than stack operations.
+pub fn gas_cost(opcode: &Opcode) -> u64 {
+    match opcode {
+        Opcode::StorageStore(_) | Opcode::StorageLoad(_) => 20,
+        Opcode::NetNodeConnect
+        | Opcode::ChainBlockCreate
+        | Opcode::EconCurrencyMint
+        | Opcode::GovProposalSubmit
+        | Opcode::CoopMemberAdd
+        | Opcode::CommEventOrganize
+        | Opcode::VoteOnProposal
+        | Opcode::AllocateResource
+        | Opcode::UpdateReputation
+        | Opcode::CreateProposal
+        | Opcode::GetProposalStatus
+        | Opcode::EmitEvent
+        | Opcode::BlockTime => 100,
+        _ => 1,
+    }
+}
+
+/// Bytes a value takes up, counting strings by their length and
+/// collections by what they hold.
+pub fn value_size(value: &Value) -> usize {
+    match value {
+        Value::String(s) => s.len(),
+        Value::Int(_) | Value::Float(_) => 8,
+        Value::Bool(_) => 1,
+        Value::List(list) => list.iter().map(value_size).sum(),
+        Value::Map(map) => map.iter().map(|(key, value)| key.len() + value_size(value)).sum(),
+    }
+}
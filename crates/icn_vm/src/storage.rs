@@ -0,0 +1,117 @@
+// File: crates/icn_vm/src/storage.rs
+
+use crate::Value;
+use icn_common::{IcnError, IcnResult};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Root of a contract that has never written to storage.
+pub const EMPTY_STATE_ROOT: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+impl Value {
+    /// Encodes a value for persistence outside the VM.
+    pub fn to_bytes(&self) -> IcnResult<Vec<u8>> {
+        serde_json::to_vec(self).map_err(|e| IcnError::Vm(format!("Failed to encode value: {}", e)))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IcnResult<Self> {
+        serde_json::from_slice(bytes).map_err(|e| IcnError::Vm(format!("Failed to decode value: {}", e)))
+    }
+}
+
+/// Persistent key-value storage owned by a single contract.
+///
+/// Unlike VM memory, which only lives for one execution, storage is loaded
+/// before a call and its changed keys are written back afterwards. Only
+/// `StorageLoad` and `StorageStore` reach it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ContractStorage {
+    contract_id: String,
+    entries: BTreeMap<String, Value>,
+    dirty: BTreeSet<String>,
+}
+
+impl ContractStorage {
+    pub fn new(contract_id: &str) -> Self {
+        ContractStorage {
+            contract_id: contract_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Rebuilds storage from persisted entries, with nothing marked changed.
+    pub fn from_entries(contract_id: &str, entries: BTreeMap<String, Value>) -> Self {
+        ContractStorage {
+            contract_id: contract_id.to_string(),
+            entries,
+            dirty: BTreeSet::new(),
+        }
+    }
+
+    pub fn contract_id(&self) -> &str {
+        &self.contract_id
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.get(key)
+    }
+
+    pub fn set(&mut self, key: &str, value: Value) {
+        self.dirty.insert(key.to_string());
+        self.entries.insert(key.to_string(), value);
+    }
+
+    pub fn entries(&self) -> &BTreeMap<String, Value> {
+        &self.entries
+    }
+
+    /// Returns the entries written since storage was loaded and clears the
+    /// change set.
+    pub fn take_changes(&mut self) -> Vec<(String, Value)> {
+        std::mem::take(&mut self.dirty)
+            .into_iter()
+            .filter_map(|key| self.entries.get(&key).cloned().map(|value| (key, value)))
+            .collect()
+    }
+
+    /// Hash committing to every entry, independent of write order.
+    pub fn state_root(&self) -> IcnResult<String> {
+        if self.entries.is_empty() {
+            return Ok(EMPTY_STATE_ROOT.to_string());
+        }
+        let mut hasher = Sha256::new();
+        for (key, value) in &self.entries {
+            let encoded = value.to_bytes()?;
+            hasher.update((key.len() as u64).to_be_bytes());
+            hasher.update(key.as_bytes());
+            hasher.update((encoded.len() as u64).to_be_bytes());
+            hasher.update(&encoded);
+        }
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_and_state_root() {
+        let mut storage = ContractStorage::new("counter");
+        assert_eq!(storage.state_root().unwrap(), EMPTY_STATE_ROOT);
+
+        storage.set("count", Value::Int(1));
+        storage.set("owner", Value::String("Alice".to_string()));
+        storage.set("count", Value::Int(2));
+        assert_eq!(storage.take_changes(), vec![
+            ("count".to_string(), Value::Int(2)),
+            ("owner".to_string(), Value::String("Alice".to_string())),
+        ]);
+        assert!(storage.take_changes().is_empty());
+
+        let reloaded = ContractStorage::from_entries("counter", storage.entries().clone());
+        assert_eq!(reloaded.state_root().unwrap(), storage.state_root().unwrap());
+        assert_ne!(storage.state_root().unwrap(), EMPTY_STATE_ROOT);
+        assert_eq!(Value::from_bytes(&Value::Float(1.5).to_bytes().unwrap()).unwrap(), Value::Float(1.5));
+    }
+}
@@ -15,7 +15,7 @@ pub struct TransactionCircuit {
 impl TransactionCircuit {
     pub fn new(transaction: &Transaction, balance: u64) -> Self {
         TransactionCircuit {
-            amount: transaction.amount as u64,
+            amount: transaction.amount.to_f64() as u64,
             balance,
         }
     }
@@ -73,17 +73,21 @@ impl TransactionCircuit {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use icn_common::CurrencyType;
+    use icn_common::{Amount, CurrencyType, SignatureScheme};
 
     #[test]
     fn test_transaction_proof() {
         let transaction = Transaction {
             from: "Alice".to_string(),
             to: "Bob".to_string(),
-            amount: 50.0,
+            amount: Amount::from(50.0),
             currency_type: CurrencyType::BasicNeeds,
             timestamp: 1234567890,
             signature: None,
+            memo: None,
+            signature_scheme: SignatureScheme::Ed25519,
+            valid_until: None,
+            capability: None,
         };
 
         let balance = 100;
@@ -0,0 +1,902 @@
+// File: crates/icn_zkp/src/confidential.rs
+
+use crate::ZKPManager;
+use bulletproofs::{PedersenGens, RangeProof};
+use curve25519_dalek_ng::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek_ng::scalar::Scalar;
+use curve25519_dalek_ng::traits::Identity;
+use merlin::Transcript;
+use rand::thread_rng;
+use serde::{Serialize, Deserialize};
+use icn_common::{Amount, CurrencyType, IcnResult, IcnError};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Account holding the public funds behind every confidential balance.
+pub const CONFIDENTIAL_POOL_ACCOUNT: &str = "confidential:pool";
+
+/// Confidential amounts are committed in the same millionths as public
+/// amounts, and balances are proven to fit in this many bits.
+const AMOUNT_BITS: usize = 64;
+
+/// Transfer amounts are encrypted as a low and a high half of this many
+/// bits, small enough for a viewing key to decrypt.
+const CHUNK_BITS: usize = 32;
+
+/// Decryption looks up `amount mod BABY_STEPS` in a table and steps
+/// through the rest.
+const BABY_STEPS: u64 = 1 << 16;
+
+/// Giant steps are compressed this many at a time.
+const GIANT_STEP_BATCH: u64 = 1024;
+
+/// The amount behind a Pedersen commitment and the blinding that hides it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Opening {
+    pub amount: u64,
+    pub blinding: Scalar,
+}
+
+impl Opening {
+    /// An opening of a public amount, as credited when funds are shielded.
+    pub fn public(amount: u64) -> Self {
+        Opening { amount, blinding: Scalar::zero() }
+    }
+
+    fn random(amount: u64) -> Self {
+        Opening { amount, blinding: Scalar::random(&mut thread_rng()) }
+    }
+
+    pub fn commitment(&self) -> CompressedRistretto {
+        self.point().compress()
+    }
+
+    fn point(&self) -> RistrettoPoint {
+        PedersenGens::default().commit(Scalar::from(self.amount), self.blinding)
+    }
+
+    pub fn opens(&self, commitment: &[u8]) -> bool {
+        commitment == self.commitment().as_bytes()
+    }
+
+    pub fn add(&self, other: &Opening) -> IcnResult<Opening> {
+        let amount = self.amount.checked_add(other.amount)
+            .ok_or_else(|| IcnError::Zkp("Confidential balance overflow".into()))?;
+        Ok(Opening { amount, blinding: self.blinding + other.blinding })
+    }
+
+    pub fn sub(&self, other: &Opening) -> IcnResult<Opening> {
+        let amount = self.amount.checked_sub(other.amount)
+            .ok_or_else(|| IcnError::Zkp("Insufficient confidential balance".into()))?;
+        Ok(Opening { amount, blinding: self.blinding - other.blinding })
+    }
+}
+
+/// A secret key amounts are encrypted to with twisted ElGamal. Every
+/// confidential account registers the public half of one: it decrypts
+/// what the account is sent, and the owner proves what their balance
+/// holds with it. Auditors hold one that every transfer is also
+/// encrypted to.
+pub struct ViewingKey {
+    secret: Scalar,
+}
+
+impl ViewingKey {
+    pub fn generate() -> Self {
+        ViewingKey { secret: Scalar::random(&mut thread_rng()) }
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> IcnResult<Self> {
+        let bytes: [u8; 32] = bytes.try_into()
+            .map_err(|_| IcnError::Zkp("Viewing keys are 32 bytes".into()))?;
+        let secret = Scalar::from_canonical_bytes(bytes)
+            .filter(|secret| *secret != Scalar::zero())
+            .ok_or_else(|| IcnError::Zkp("Invalid viewing key".into()))?;
+        Ok(ViewingKey { secret })
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.secret.to_bytes()
+    }
+
+    /// The public key `P = secret⁻¹·H`, for `H` the Pedersen blinding
+    /// generator. A handle `r·P` then gives its holder `r·H`.
+    pub fn public_key(&self) -> Vec<u8> {
+        (self.secret.invert() * PedersenGens::default().B_blinding).compress().as_bytes().to_vec()
+    }
+
+    /// The amount of a transfer sent to this key's account.
+    pub fn received_amount(&self, transfer: &ConfidentialTransfer) -> IcnResult<Amount> {
+        self.decrypt_amount(transfer, |chunk| Some(&chunk.recipient_handle))
+    }
+
+    /// The amount of a transfer, decrypted with its auditor handles.
+    pub fn audit(&self, transfer: &ConfidentialTransfer) -> IcnResult<Amount> {
+        self.decrypt_amount(transfer, |chunk| chunk.auditor_handle.as_ref())
+    }
+
+    /// Whether `balance`, encrypted to this key, holds exactly `amount`.
+    pub fn holds(&self, balance: &EncryptedBalance, amount: Amount) -> IcnResult<bool> {
+        let (commitment, handle) = balance.points()?;
+        let expected = PedersenGens::default().B * Scalar::from(public_units(amount)?);
+        Ok(commitment - self.secret * handle == expected)
+    }
+
+    fn decrypt_amount<'a>(&self, transfer: &'a ConfidentialTransfer, handle: impl Fn(&'a AmountChunk) -> Option<&'a Vec<u8>>) -> IcnResult<Amount> {
+        let [low, high] = transfer.chunks()?;
+        let mut halves = [0u64; 2];
+        for (half, chunk) in halves.iter_mut().zip([low, high]) {
+            let handle = handle(chunk)
+                .ok_or_else(|| IcnError::Zkp("Transfer is not encrypted to this key".into()))?;
+            let commitment = decompress(&chunk.commitment, "amount commitment")?;
+            let handle = decompress(handle, "decryption handle")?;
+            *half = discrete_log(commitment - self.secret * handle)
+                .ok_or_else(|| IcnError::Zkp("Transfer is not encrypted to this key".into()))?;
+        }
+        let units = halves[0] + (halves[1] << CHUNK_BITS);
+        i64::try_from(units)
+            .map(Amount::from_units)
+            .map_err(|_| IcnError::Zkp("Decrypted amount is out of range".into()))
+    }
+}
+
+/// `j·G` for every `j` below `BABY_STEPS`, by compressed point.
+fn baby_steps() -> &'static HashMap<[u8; 32], u64> {
+    static TABLE: OnceLock<HashMap<[u8; 32], u64>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        // Batch compression encodes doubles, so step by half of G. It
+        // can't take the identity, which is added on its own.
+        let half_base = Scalar::from(2u64).invert() * PedersenGens::default().B;
+        let mut point = RistrettoPoint::identity();
+        let halves: Vec<RistrettoPoint> = (1..BABY_STEPS)
+            .map(|_| {
+                point += half_base;
+                point
+            })
+            .collect();
+        let mut table: HashMap<[u8; 32], u64> = RistrettoPoint::double_and_compress_batch(&halves).into_iter()
+            .zip(1..)
+            .map(|(compressed, j)| (compressed.to_bytes(), j))
+            .collect();
+        table.insert(RistrettoPoint::identity().compress().to_bytes(), 0);
+        table
+    })
+}
+
+/// The `v` below `2^CHUNK_BITS` with `point = v·G`, by baby-step
+/// giant-step.
+fn discrete_log(point: RistrettoPoint) -> Option<u64> {
+    let table = baby_steps();
+    let half = Scalar::from(2u64).invert();
+    let giant_step = -(half * Scalar::from(BABY_STEPS)) * PedersenGens::default().B;
+    let mut current = half * point;
+    let giant_steps = (1u64 << CHUNK_BITS) / BABY_STEPS;
+    for batch_start in (0..giant_steps).step_by(GIANT_STEP_BATCH as usize) {
+        let batch: Vec<RistrettoPoint> = (0..GIANT_STEP_BATCH)
+            .map(|_| {
+                let step = current;
+                current += giant_step;
+                step
+            })
+            .collect();
+        if let Some(i) = batch.iter().position(|step| *step == RistrettoPoint::identity()) {
+            return Some((batch_start + i as u64) * BABY_STEPS);
+        }
+        let found = RistrettoPoint::double_and_compress_batch(&batch).iter()
+            .enumerate()
+            .find_map(|(i, compressed)| table.get(compressed.as_bytes()).map(|j| (batch_start + i as u64) * BABY_STEPS + j));
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
+/// A value encrypted to one viewing key: the Pedersen commitment
+/// `v·G + r·H` and the decryption handle `r·P` for the key's public `P`.
+/// Confidential balances are kept this way, encrypted to their owner.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedBalance {
+    pub commitment: Vec<u8>,
+    pub handle: Vec<u8>,
+}
+
+impl EncryptedBalance {
+    fn from_points(commitment: RistrettoPoint, handle: RistrettoPoint) -> Self {
+        EncryptedBalance {
+            commitment: commitment.compress().as_bytes().to_vec(),
+            handle: handle.compress().as_bytes().to_vec(),
+        }
+    }
+
+    fn points(&self) -> IcnResult<(RistrettoPoint, RistrettoPoint)> {
+        Ok((decompress(&self.commitment, "balance commitment")?, decompress(&self.handle, "balance handle")?))
+    }
+}
+
+/// One half of a transfer amount: a commitment and a handle for every
+/// key it is encrypted to, all made with the same randomness.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AmountChunk {
+    pub commitment: Vec<u8>,
+    pub sender_handle: Vec<u8>,
+    pub recipient_handle: Vec<u8>,
+    #[serde(default)]
+    pub auditor_handle: Option<Vec<u8>>,
+}
+
+fn decompress(bytes: &[u8], what: &str) -> IcnResult<RistrettoPoint> {
+    if bytes.len() != 32 {
+        return Err(IcnError::Zkp(format!("Invalid {}", what)));
+    }
+    CompressedRistretto::from_slice(bytes)
+        .decompress()
+        .ok_or_else(|| IcnError::Zkp(format!("Invalid {}", what)))
+}
+
+fn to_bytes(point: &RistrettoPoint) -> Vec<u8> {
+    point.compress().as_bytes().to_vec()
+}
+
+/// Reads the points and scalars a sigma proof is made of, in order.
+struct ProofReader<'a> {
+    bytes: &'a [u8],
+    what: &'static str,
+}
+
+impl<'a> ProofReader<'a> {
+    fn next(&mut self) -> IcnResult<[u8; 32]> {
+        if self.bytes.len() < 32 {
+            return Err(IcnError::Zkp(format!("Invalid {}", self.what)));
+        }
+        let (next, rest) = self.bytes.split_at(32);
+        self.bytes = rest;
+        Ok(next.try_into().unwrap())
+    }
+
+    fn point(&mut self, transcript: &mut Transcript, label: &'static [u8]) -> IcnResult<RistrettoPoint> {
+        let bytes = self.next()?;
+        transcript.append_message(label, &bytes);
+        decompress(&bytes, self.what)
+    }
+
+    fn scalar(&mut self) -> IcnResult<Scalar> {
+        Scalar::from_canonical_bytes(self.next()?)
+            .ok_or_else(|| IcnError::Zkp(format!("Invalid {}", self.what)))
+    }
+
+    fn finish(self) -> IcnResult<()> {
+        if !self.bytes.is_empty() {
+            return Err(IcnError::Zkp(format!("Invalid {}", self.what)));
+        }
+        Ok(())
+    }
+}
+
+fn challenge(transcript: &mut Transcript) -> Scalar {
+    let mut bytes = [0u8; 64];
+    transcript.challenge_bytes(b"challenge", &mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+/// Proves a chunk's handles were made with the randomness of its
+/// commitment, so each key holder decrypts the committed value: knowledge
+/// of `v, r` with `C = v·G + r·H` and `D_k = r·P_k` for every key.
+fn prove_chunk_validity(transcript: &mut Transcript, opening: &Opening, keys: &[RistrettoPoint], proof: &mut Vec<u8>) {
+    let gens = PedersenGens::default();
+    let (y_amount, y_blinding) = (Scalar::random(&mut thread_rng()), Scalar::random(&mut thread_rng()));
+    let mut commitments = vec![gens.commit(y_amount, y_blinding)];
+    commitments.extend(keys.iter().map(|key| y_blinding * key));
+    for commitment in &commitments {
+        let bytes = to_bytes(commitment);
+        transcript.append_message(b"validity-commitment", &bytes);
+        proof.extend(bytes);
+    }
+    let c = challenge(transcript);
+    proof.extend((y_amount + c * Scalar::from(opening.amount)).as_bytes());
+    proof.extend((y_blinding + c * opening.blinding).as_bytes());
+}
+
+fn verify_chunk_validity(transcript: &mut Transcript, reader: &mut ProofReader, commitment: &RistrettoPoint, handles: &[(RistrettoPoint, RistrettoPoint)]) -> IcnResult<()> {
+    let gens = PedersenGens::default();
+    let y_commitment = reader.point(transcript, b"validity-commitment")?;
+    let y_handles = handles.iter()
+        .map(|_| reader.point(transcript, b"validity-commitment"))
+        .collect::<IcnResult<Vec<_>>>()?;
+    let c = challenge(transcript);
+    let (z_amount, z_blinding) = (reader.scalar()?, reader.scalar()?);
+    let mut valid = gens.commit(z_amount, z_blinding) == y_commitment + c * commitment;
+    for ((key, handle), y_handle) in handles.iter().zip(y_handles) {
+        valid &= z_blinding * key == y_handle + c * handle;
+    }
+    if !valid {
+        return Err(IcnError::Zkp("Amount is not encrypted to the keys it names".into()));
+    }
+    Ok(())
+}
+
+/// Proves `remaining` commits to the value a balance encrypted to `key`
+/// holds: knowledge of `s, v, r` with `s·P = H`, `C = v·G + s·D` and
+/// `remaining = v·G + r·H`. Only the key's holder can make it.
+fn prove_balance_equality(transcript: &mut Transcript, key: &ViewingKey, balance_handle: &RistrettoPoint, remaining: &Opening) -> Vec<u8> {
+    let gens = PedersenGens::default();
+    let public_key = key.secret.invert() * gens.B_blinding;
+    let mut rng = thread_rng();
+    let (y_secret, y_amount, y_blinding) = (Scalar::random(&mut rng), Scalar::random(&mut rng), Scalar::random(&mut rng));
+    let mut proof = Vec::new();
+    for commitment in [y_secret * public_key, gens.B * y_amount + y_secret * balance_handle, gens.commit(y_amount, y_blinding)] {
+        let bytes = to_bytes(&commitment);
+        transcript.append_message(b"equality-commitment", &bytes);
+        proof.extend(bytes);
+    }
+    let c = challenge(transcript);
+    proof.extend((y_secret + c * key.secret).as_bytes());
+    proof.extend((y_amount + c * Scalar::from(remaining.amount)).as_bytes());
+    proof.extend((y_blinding + c * remaining.blinding).as_bytes());
+    proof
+}
+
+fn verify_balance_equality(transcript: &mut Transcript, proof: &[u8], key: &RistrettoPoint, balance: (RistrettoPoint, RistrettoPoint), remaining: &RistrettoPoint) -> IcnResult<()> {
+    let gens = PedersenGens::default();
+    let mut reader = ProofReader { bytes: proof, what: "balance proof" };
+    let y_key = reader.point(transcript, b"equality-commitment")?;
+    let y_balance = reader.point(transcript, b"equality-commitment")?;
+    let y_remaining = reader.point(transcript, b"equality-commitment")?;
+    let c = challenge(transcript);
+    let (z_secret, z_amount, z_blinding) = (reader.scalar()?, reader.scalar()?, reader.scalar()?);
+    reader.finish()?;
+    let (commitment, handle) = balance;
+    let valid = z_secret * key == y_key + c * gens.B_blinding
+        && gens.B * z_amount + z_secret * handle == y_balance + c * commitment
+        && gens.commit(z_amount, z_blinding) == y_remaining + c * remaining;
+    if !valid {
+        return Err(IcnError::Zkp("Remaining commitment does not match the balance".into()));
+    }
+    Ok(())
+}
+
+/// A transfer whose amount is encrypted to the sender, the recipient and,
+/// when the node has one, the auditor. Proofs show every handle decrypts
+/// to the committed amount, that the amount and what the sender has left
+/// are both non-negative, and that what is left is what the sender's
+/// balance holds minus the amount, without revealing either.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfidentialTransfer {
+    pub from: String,
+    pub to: String,
+    pub currency_type: CurrencyType,
+    pub timestamp: i64,
+    /// The low and high halves of the amount.
+    pub amount_chunks: Vec<AmountChunk>,
+    /// The sender's balance the proofs were made against.
+    pub balance: EncryptedBalance,
+    /// A fresh commitment to what the sender has left.
+    pub remaining_commitment: Vec<u8>,
+    pub validity_proof: Vec<u8>,
+    pub equality_proof: Vec<u8>,
+    /// Shows both halves of the amount fit in 32 bits.
+    pub amount_range_proof: Vec<u8>,
+    /// Shows what the sender has left is not negative.
+    pub remaining_range_proof: Vec<u8>,
+    pub signature: Option<Vec<u8>>,
+}
+
+impl ConfidentialTransfer {
+    /// What the sender signs: everything but the signature.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut unsigned = self.clone();
+        unsigned.signature = None;
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    fn chunks(&self) -> IcnResult<[&AmountChunk; 2]> {
+        match self.amount_chunks.as_slice() {
+            [low, high] => Ok([low, high]),
+            _ => Err(IcnError::Zkp("Transfer amounts are encrypted in two halves".into())),
+        }
+    }
+
+    /// The commitment to the whole amount and its handles for the sender
+    /// and the recipient, recombined from the halves.
+    fn amount_points(&self) -> IcnResult<(RistrettoPoint, RistrettoPoint, RistrettoPoint)> {
+        let [low, high] = self.chunks()?;
+        let shift = Scalar::from(1u64 << CHUNK_BITS);
+        let combine = |low: &[u8], high: &[u8], what| -> IcnResult<RistrettoPoint> {
+            Ok(decompress(low, what)? + shift * decompress(high, what)?)
+        };
+        Ok((
+            combine(&low.commitment, &high.commitment, "amount commitment")?,
+            combine(&low.sender_handle, &high.sender_handle, "decryption handle")?,
+            combine(&low.recipient_handle, &high.recipient_handle, "decryption handle")?,
+        ))
+    }
+
+    fn transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(b"icn-confidential-transfer");
+        transcript.append_message(b"from", self.from.as_bytes());
+        transcript.append_message(b"to", self.to.as_bytes());
+        transcript.append_message(b"currency", format!("{:?}", self.currency_type).as_bytes());
+        transcript.append_u64(b"timestamp", self.timestamp as u64);
+        transcript.append_message(b"balance", &self.balance.commitment);
+        transcript.append_message(b"balance-handle", &self.balance.handle);
+        for chunk in &self.amount_chunks {
+            transcript.append_message(b"chunk", &chunk.commitment);
+            transcript.append_message(b"sender-handle", &chunk.sender_handle);
+            transcript.append_message(b"recipient-handle", &chunk.recipient_handle);
+            transcript.append_message(b"auditor-handle", chunk.auditor_handle.as_deref().unwrap_or_default());
+        }
+        transcript.append_message(b"remaining", &self.remaining_commitment);
+        transcript
+    }
+}
+
+/// Moves part of a confidential balance back to public funds. The amount
+/// is revealed; what is left stays hidden and is proven non-negative.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConfidentialWithdrawal {
+    pub address: String,
+    pub currency_type: CurrencyType,
+    pub amount: Amount,
+    pub timestamp: i64,
+    pub balance: EncryptedBalance,
+    /// A fresh commitment to what is left.
+    pub remaining_commitment: Vec<u8>,
+    pub equality_proof: Vec<u8>,
+    pub range_proof: Vec<u8>,
+    pub signature: Option<Vec<u8>>,
+}
+
+impl ConfidentialWithdrawal {
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut unsigned = self.clone();
+        unsigned.signature = None;
+        serde_json::to_vec(&unsigned).unwrap_or_default()
+    }
+
+    fn transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(b"icn-confidential-withdrawal");
+        transcript.append_message(b"address", self.address.as_bytes());
+        transcript.append_message(b"currency", format!("{:?}", self.currency_type).as_bytes());
+        transcript.append_u64(b"amount", self.amount.units() as u64);
+        transcript.append_u64(b"timestamp", self.timestamp as u64);
+        transcript.append_message(b"balance", &self.balance.commitment);
+        transcript.append_message(b"balance-handle", &self.balance.handle);
+        transcript.append_message(b"remaining", &self.remaining_commitment);
+        transcript
+    }
+}
+
+fn public_units(amount: Amount) -> IcnResult<u64> {
+    u64::try_from(amount.units())
+        .map_err(|_| IcnError::Zkp("Confidential amounts cannot be negative".into()))
+}
+
+fn positive_units(amount: Amount) -> IcnResult<u64> {
+    Some(public_units(amount)?)
+        .filter(|units| *units > 0)
+        .ok_or_else(|| IcnError::Zkp("Confidential amounts must be positive".into()))
+}
+
+impl ZKPManager {
+    /// Builds a transfer of `amount` out of the sender's `balance`, which
+    /// must hold `balance_amount` and be encrypted to `sender_key`.
+    /// Returns the transfer, still to be signed, and what the sender has
+    /// left.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_confidential_transfer(
+        &self,
+        from: &str,
+        to: &str,
+        currency_type: CurrencyType,
+        amount: Amount,
+        balance: &EncryptedBalance,
+        balance_amount: Amount,
+        sender_key: &ViewingKey,
+        recipient_viewing_key: &[u8],
+        auditor_viewing_key: Option<&[u8]>,
+        timestamp: i64,
+    ) -> IcnResult<(ConfidentialTransfer, Amount)> {
+        if !sender_key.holds(balance, balance_amount)? {
+            return Err(IcnError::Zkp("Balance does not hold the stated amount".into()));
+        }
+        let units = positive_units(amount)?;
+        let remaining = Opening::random(public_units(balance_amount)?.checked_sub(units)
+            .ok_or_else(|| IcnError::Zkp("Insufficient confidential balance".into()))?);
+        let halves = [Opening::random(units & ((1 << CHUNK_BITS) - 1)), Opening::random(units >> CHUNK_BITS)];
+
+        let mut keys = vec![
+            decompress(&sender_key.public_key(), "viewing key")?,
+            decompress(recipient_viewing_key, "viewing key")?,
+        ];
+        if let Some(auditor_key) = auditor_viewing_key {
+            keys.push(decompress(auditor_key, "auditor key")?);
+        }
+        let amount_chunks = halves.iter()
+            .map(|half| AmountChunk {
+                commitment: half.commitment().as_bytes().to_vec(),
+                sender_handle: to_bytes(&(half.blinding * keys[0])),
+                recipient_handle: to_bytes(&(half.blinding * keys[1])),
+                auditor_handle: keys.get(2).map(|key| to_bytes(&(half.blinding * key))),
+            })
+            .collect();
+        let mut transfer = ConfidentialTransfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            currency_type,
+            timestamp,
+            amount_chunks,
+            balance: balance.clone(),
+            remaining_commitment: remaining.commitment().as_bytes().to_vec(),
+            validity_proof: Vec::new(),
+            equality_proof: Vec::new(),
+            amount_range_proof: Vec::new(),
+            remaining_range_proof: Vec::new(),
+            signature: None,
+        };
+
+        let mut transcript = transfer.transcript();
+        for half in &halves {
+            prove_chunk_validity(&mut transcript, half, &keys, &mut transfer.validity_proof);
+        }
+        let (_, sender_handle, _) = transfer.amount_points()?;
+        let (_, balance_handle) = balance.points()?;
+        transfer.equality_proof = prove_balance_equality(&mut transcript, sender_key, &(balance_handle - sender_handle), &remaining);
+        let (proof, _) = RangeProof::prove_multiple(
+            &self.bp_gens,
+            &self.pc_gens,
+            &mut transcript,
+            &[halves[0].amount, halves[1].amount],
+            &[halves[0].blinding, halves[1].blinding],
+            CHUNK_BITS,
+        )
+        .map_err(|e| IcnError::Zkp(format!("Failed to create transfer proof: {}", e)))?;
+        transfer.amount_range_proof = proof.to_bytes();
+        let (proof, _) = RangeProof::prove_single(&self.bp_gens, &self.pc_gens, &mut transcript, remaining.amount, &remaining.blinding, AMOUNT_BITS)
+            .map_err(|e| IcnError::Zkp(format!("Failed to create transfer proof: {}", e)))?;
+        transfer.remaining_range_proof = proof.to_bytes();
+        Ok((transfer, Amount::from_units(remaining.amount as i64)))
+    }
+
+    /// Checks a transfer's proofs against the public keys of the sender,
+    /// the recipient and, when there is one, the auditor.
+    pub fn verify_confidential_transfer(&self, transfer: &ConfidentialTransfer, sender_key: &[u8], recipient_key: &[u8], auditor_key: Option<&[u8]>) -> IcnResult<()> {
+        let [low, high] = transfer.chunks()?;
+        let mut keys = vec![decompress(sender_key, "viewing key")?, decompress(recipient_key, "viewing key")?];
+        if let Some(auditor_key) = auditor_key {
+            keys.push(decompress(auditor_key, "auditor key")?);
+        }
+
+        let mut transcript = transfer.transcript();
+        let mut reader = ProofReader { bytes: &transfer.validity_proof, what: "validity proof" };
+        for chunk in [low, high] {
+            let mut handles = vec![&chunk.sender_handle, &chunk.recipient_handle];
+            match (auditor_key, &chunk.auditor_handle) {
+                (Some(_), Some(handle)) => handles.push(handle),
+                (Some(_), None) => return Err(IcnError::Zkp("Transfers must be encrypted to the auditor".into())),
+                (None, Some(_)) => return Err(IcnError::Zkp("Transfer names an auditor the node doesn't have".into())),
+                (None, None) => {}
+            }
+            let handles = keys.iter().zip(handles)
+                .map(|(key, handle)| Ok((*key, decompress(handle, "decryption handle")?)))
+                .collect::<IcnResult<Vec<_>>>()?;
+            verify_chunk_validity(&mut transcript, &mut reader, &decompress(&chunk.commitment, "amount commitment")?, &handles)?;
+        }
+        reader.finish()?;
+
+        let (amount, sender_handle, _) = transfer.amount_points()?;
+        let (balance, balance_handle) = transfer.balance.points()?;
+        let remaining = decompress(&transfer.remaining_commitment, "remaining commitment")?;
+        verify_balance_equality(&mut transcript, &transfer.equality_proof, &keys[0], (balance - amount, balance_handle - sender_handle), &remaining)?;
+
+        let proof = RangeProof::from_bytes(&transfer.amount_range_proof)
+            .map_err(|e| IcnError::Zkp(format!("Invalid transfer proof: {}", e)))?;
+        proof.verify_multiple(
+            &self.bp_gens,
+            &self.pc_gens,
+            &mut transcript,
+            &[CompressedRistretto::from_slice(&low.commitment), CompressedRistretto::from_slice(&high.commitment)],
+            CHUNK_BITS,
+        )
+        .map_err(|e| IcnError::Zkp(format!("Invalid transfer proof: {}", e)))?;
+        let proof = RangeProof::from_bytes(&transfer.remaining_range_proof)
+            .map_err(|e| IcnError::Zkp(format!("Invalid transfer proof: {}", e)))?;
+        proof.verify_single(&self.bp_gens, &self.pc_gens, &mut transcript, &remaining.compress(), AMOUNT_BITS)
+            .map_err(|e| IcnError::Zkp(format!("Invalid transfer proof: {}", e)))
+    }
+
+    /// Builds a withdrawal of `amount` from `balance`, which must hold
+    /// `balance_amount` and be encrypted to `key`. Returns it unsigned
+    /// with what is left.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_confidential_withdrawal(
+        &self,
+        address: &str,
+        currency_type: CurrencyType,
+        amount: Amount,
+        balance: &EncryptedBalance,
+        balance_amount: Amount,
+        key: &ViewingKey,
+        timestamp: i64,
+    ) -> IcnResult<(ConfidentialWithdrawal, Amount)> {
+        if !key.holds(balance, balance_amount)? {
+            return Err(IcnError::Zkp("Balance does not hold the stated amount".into()));
+        }
+        let remaining = Opening::random(public_units(balance_amount)?.checked_sub(positive_units(amount)?)
+            .ok_or_else(|| IcnError::Zkp("Insufficient confidential balance".into()))?);
+        let mut withdrawal = ConfidentialWithdrawal {
+            address: address.to_string(),
+            currency_type,
+            amount,
+            timestamp,
+            balance: balance.clone(),
+            remaining_commitment: remaining.commitment().as_bytes().to_vec(),
+            equality_proof: Vec::new(),
+            range_proof: Vec::new(),
+            signature: None,
+        };
+        let mut transcript = withdrawal.transcript();
+        let (_, balance_handle) = balance.points()?;
+        withdrawal.equality_proof = prove_balance_equality(&mut transcript, key, &balance_handle, &remaining);
+        let (proof, _) = RangeProof::prove_single(&self.bp_gens, &self.pc_gens, &mut transcript, remaining.amount, &remaining.blinding, AMOUNT_BITS)
+            .map_err(|e| IcnError::Zkp(format!("Failed to create withdrawal proof: {}", e)))?;
+        withdrawal.range_proof = proof.to_bytes();
+        Ok((withdrawal, Amount::from_units(remaining.amount as i64)))
+    }
+
+    /// Checks a withdrawal's proofs against the owner's public key.
+    pub fn verify_confidential_withdrawal(&self, withdrawal: &ConfidentialWithdrawal, key: &[u8]) -> IcnResult<()> {
+        let key = decompress(key, "viewing key")?;
+        let (balance, balance_handle) = withdrawal.balance.points()?;
+        let withdrawn = self.pc_gens.B * Scalar::from(positive_units(withdrawal.amount)?);
+        let remaining = decompress(&withdrawal.remaining_commitment, "remaining commitment")?;
+        let mut transcript = withdrawal.transcript();
+        verify_balance_equality(&mut transcript, &withdrawal.equality_proof, &key, (balance - withdrawn, balance_handle), &remaining)?;
+        let proof = RangeProof::from_bytes(&withdrawal.range_proof)
+            .map_err(|e| IcnError::Zkp(format!("Invalid withdrawal proof: {}", e)))?;
+        proof.verify_single(&self.bp_gens, &self.pc_gens, &mut transcript, &remaining.compress(), AMOUNT_BITS)
+            .map_err(|e| IcnError::Zkp(format!("Invalid withdrawal proof: {}", e)))
+    }
+}
+
+/// The ledger as it is written to storage.
+#[derive(Serialize, Deserialize)]
+struct LedgerSnapshot {
+    viewing_keys: Vec<(String, Vec<u8>)>,
+    balances: Vec<(String, CurrencyType, EncryptedBalance)>,
+    transfers: Vec<ConfidentialTransfer>,
+}
+
+/// Confidential balances, each encrypted to its owner's viewing key, and
+/// the keys transfers to each account are encrypted to.
+#[derive(Debug, Clone, Default)]
+pub struct ConfidentialLedger {
+    viewing_keys: HashMap<String, Vec<u8>>,
+    balances: HashMap<(String, CurrencyType), (RistrettoPoint, RistrettoPoint)>,
+    transfers: Vec<ConfidentialTransfer>,
+    auditor_key: Option<Vec<u8>>,
+}
+
+impl ConfidentialLedger {
+    /// Requires every transfer to be encrypted to the auditor too.
+    pub fn set_auditor_key(&mut self, public_key: Vec<u8>) -> IcnResult<()> {
+        decompress(&public_key, "auditor key")?;
+        self.auditor_key = Some(public_key);
+        Ok(())
+    }
+
+    pub fn auditor_key(&self) -> Option<&[u8]> {
+        self.auditor_key.as_deref()
+    }
+
+    pub fn register_account(&mut self, address: &str, viewing_public_key: Vec<u8>) -> IcnResult<()> {
+        decompress(&viewing_public_key, "viewing key")?;
+        if self.viewing_keys.contains_key(address) {
+            return Err(IcnError::Zkp(format!("{} already has a confidential account", address)));
+        }
+        self.viewing_keys.insert(address.to_string(), viewing_public_key);
+        Ok(())
+    }
+
+    pub fn viewing_key(&self, address: &str) -> Option<&[u8]> {
+        self.viewing_keys.get(address).map(Vec::as_slice)
+    }
+
+    fn account_key(&self, address: &str) -> IcnResult<&[u8]> {
+        self.viewing_key(address)
+            .ok_or_else(|| IcnError::Zkp(format!("{} has no confidential account", address)))
+    }
+
+    /// An account's balance, an encryption of zero for accounts that have
+    /// none.
+    pub fn balance(&self, address: &str, currency_type: &CurrencyType) -> EncryptedBalance {
+        let (commitment, handle) = self.balance_points(address, currency_type);
+        EncryptedBalance::from_points(commitment, handle)
+    }
+
+    fn balance_points(&self, address: &str, currency_type: &CurrencyType) -> (RistrettoPoint, RistrettoPoint) {
+        self.balances.get(&(address.to_string(), currency_type.clone()))
+            .copied()
+            .unwrap_or_else(|| (RistrettoPoint::identity(), RistrettoPoint::identity()))
+    }
+
+    fn adjust(&mut self, address: &str, currency_type: &CurrencyType, commitment: RistrettoPoint, handle: RistrettoPoint) {
+        let balance = self.balances.entry((address.to_string(), currency_type.clone()))
+            .or_insert_with(|| (RistrettoPoint::identity(), RistrettoPoint::identity()));
+        balance.0 += commitment;
+        balance.1 += handle;
+    }
+
+    /// Credits publicly known funds, encrypted with no randomness.
+    pub fn shield(&mut self, address: &str, currency_type: &CurrencyType, amount: Amount) -> IcnResult<()> {
+        self.account_key(address)?;
+        let commitment = Opening::public(positive_units(amount)?).point();
+        self.adjust(address, currency_type, commitment, RistrettoPoint::identity());
+        Ok(())
+    }
+
+    /// Moves a verified transfer's encrypted amount between balances.
+    pub fn apply_transfer(&mut self, zkp_manager: &ZKPManager, transfer: ConfidentialTransfer) -> IcnResult<()> {
+        let sender_key = self.account_key(&transfer.from)?;
+        let recipient_key = self.account_key(&transfer.to)?;
+        if self.balance(&transfer.from, &transfer.currency_type) != transfer.balance {
+            return Err(IcnError::Zkp("Sender's balance has changed since the transfer was made".into()));
+        }
+        zkp_manager.verify_confidential_transfer(&transfer, sender_key, recipient_key, self.auditor_key())?;
+        let (amount, sender_handle, recipient_handle) = transfer.amount_points()?;
+        self.adjust(&transfer.from, &transfer.currency_type, -amount, -sender_handle);
+        self.adjust(&transfer.to, &transfer.currency_type, amount, recipient_handle);
+        self.transfers.push(transfer);
+        Ok(())
+    }
+
+    /// Debits a verified withdrawal. The caller pays the amount out of
+    /// the pool.
+    pub fn apply_withdrawal(&mut self, zkp_manager: &ZKPManager, withdrawal: &ConfidentialWithdrawal) -> IcnResult<()> {
+        let key = self.account_key(&withdrawal.address)?;
+        if self.balance(&withdrawal.address, &withdrawal.currency_type) != withdrawal.balance {
+            return Err(IcnError::Zkp("Balance has changed since the withdrawal was made".into()));
+        }
+        zkp_manager.verify_confidential_withdrawal(withdrawal, key)?;
+        let withdrawn = zkp_manager.pc_gens.B * Scalar::from(positive_units(withdrawal.amount)?);
+        self.adjust(&withdrawal.address, &withdrawal.currency_type, -withdrawn, RistrettoPoint::identity());
+        Ok(())
+    }
+
+    /// Transfers sent or received by `address`, oldest first, which the
+    /// recipient decrypts to learn what they were sent.
+    pub fn transfers_for(&self, address: &str) -> Vec<&ConfidentialTransfer> {
+        self.transfers.iter()
+            .filter(|transfer| transfer.from == address || transfer.to == address)
+            .collect()
+    }
+
+    /// Every transfer, for auditors to decrypt.
+    pub fn transfers(&self) -> &[ConfidentialTransfer] {
+        &self.transfers
+    }
+
+    /// Encodes the accounts, balances and transfers for storage. The
+    /// auditor key is node configuration and isn't included.
+    pub fn to_bytes(&self) -> IcnResult<Vec<u8>> {
+        let mut viewing_keys: Vec<_> = self.viewing_keys.iter()
+            .map(|(address, key)| (address.clone(), key.clone()))
+            .collect();
+        viewing_keys.sort();
+        let mut balances: Vec<_> = self.balances.iter()
+            .map(|((address, currency_type), (commitment, handle))| {
+                (address.clone(), currency_type.clone(), EncryptedBalance::from_points(*commitment, *handle))
+            })
+            .collect();
+        balances.sort_by(|a, b| (&a.0, format!("{:?}", a.1)).cmp(&(&b.0, format!("{:?}", b.1))));
+        let snapshot = LedgerSnapshot { viewing_keys, balances, transfers: self.transfers.clone() };
+        Ok(serde_json::to_vec(&snapshot)?)
+    }
+
+    /// Replaces the accounts, balances and transfers with ones read back
+    /// from `to_bytes`, keeping the auditor key.
+    pub fn restore(&mut self, bytes: &[u8]) -> IcnResult<()> {
+        let snapshot: LedgerSnapshot = serde_json::from_slice(bytes)?;
+        let balances = snapshot.balances.into_iter()
+            .map(|(address, currency_type, balance)| Ok(((address, currency_type), balance.points()?)))
+            .collect::<IcnResult<HashMap<_, _>>>()?;
+        self.viewing_keys = snapshot.viewing_keys.into_iter().collect();
+        self.balances = balances;
+        self.transfers = snapshot.transfers;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confidential_transfer_round_trip() {
+        let zkp_manager = ZKPManager::new(64);
+        let (alice_key, bob_key, auditor_key) = (ViewingKey::generate(), ViewingKey::generate(), ViewingKey::generate());
+        let mut ledger = ConfidentialLedger::default();
+        ledger.set_auditor_key(auditor_key.public_key()).unwrap();
+        ledger.register_account("Alice", alice_key.public_key()).unwrap();
+        ledger.register_account("Bob", bob_key.public_key()).unwrap();
+
+        let currency = CurrencyType::BasicNeeds;
+        ledger.shield("Alice", &currency, Amount::from(100.0)).unwrap();
+        let alice_balance = ledger.balance("Alice", &currency);
+        assert!(alice_key.holds(&alice_balance, Amount::from(100.0)).unwrap());
+
+        // Overdrawing cannot be proven, nor can spending someone else's balance
+        assert!(zkp_manager.create_confidential_transfer(
+            "Alice", "Bob", currency.clone(), Amount::from(150.0), &alice_balance, Amount::from(100.0),
+            &alice_key, &bob_key.public_key(), ledger.auditor_key(), 1,
+        ).is_err());
+        let (stolen, _) = zkp_manager.create_confidential_transfer(
+            "Alice", "Bob", currency.clone(), Amount::from(30.0), &alice_balance, Amount::from(100.0),
+            &bob_key, &bob_key.public_key(), ledger.auditor_key(), 1,
+        ).unwrap();
+        assert!(ledger.apply_transfer(&zkp_manager, stolen).is_err());
+
+        let (transfer, alice_left) = zkp_manager.create_confidential_transfer(
+            "Alice", "Bob", currency.clone(), Amount::from(30.0), &alice_balance, Amount::from(100.0),
+            &alice_key, &bob_key.public_key(), ledger.auditor_key(), 1,
+        ).unwrap();
+        assert_eq!(alice_left, Amount::from(70.0));
+        let mut tampered = transfer.clone();
+        tampered.to = "Mallory".to_string();
+        let keys = (alice_key.public_key(), bob_key.public_key(), auditor_key.public_key());
+        assert!(zkp_manager.verify_confidential_transfer(&tampered, &keys.0, &keys.1, Some(&keys.2)).is_err());
+
+        // Handles the recipient or auditor can't decrypt are refused
+        let mut garbled = transfer.clone();
+        garbled.amount_chunks[0].auditor_handle = Some(to_bytes(&(Scalar::from(7u64) * PedersenGens::default().B)));
+        assert!(ledger.apply_transfer(&zkp_manager, garbled).is_err());
+        let mut garbled = transfer.clone();
+        garbled.amount_chunks[1].recipient_handle = transfer.amount_chunks[1].sender_handle.clone();
+        assert!(ledger.apply_transfer(&zkp_manager, garbled).is_err());
+        let mut unaudited = transfer.clone();
+        unaudited.amount_chunks.iter_mut().for_each(|chunk| chunk.auditor_handle = None);
+        assert!(ledger.apply_transfer(&zkp_manager, unaudited).is_err());
+
+        ledger.apply_transfer(&zkp_manager, transfer.clone()).unwrap();
+        assert!(alice_key.holds(&ledger.balance("Alice", &currency), Amount::from(70.0)).unwrap());
+        assert_eq!(bob_key.received_amount(&transfer).unwrap(), Amount::from(30.0));
+        assert!(bob_key.holds(&ledger.balance("Bob", &currency), Amount::from(30.0)).unwrap());
+        assert_eq!(auditor_key.audit(&transfer).unwrap(), Amount::from(30.0));
+        // A replayed transfer no longer matches the sender's balance
+        assert!(ledger.apply_transfer(&zkp_manager, transfer).is_err());
+
+        // Bob spends what he was sent without ever learning its blinding
+        let (withdrawal, bob_left) = zkp_manager.create_confidential_withdrawal(
+            "Bob", currency.clone(), Amount::from(10.0), &ledger.balance("Bob", &currency), Amount::from(30.0), &bob_key, 2,
+        ).unwrap();
+        ledger.apply_withdrawal(&zkp_manager, &withdrawal).unwrap();
+        assert_eq!(bob_left, Amount::from(20.0));
+        assert!(bob_key.holds(&ledger.balance("Bob", &currency), Amount::from(20.0)).unwrap());
+
+        // The ledger survives a round trip through storage
+        let mut restored = ConfidentialLedger::default();
+        restored.restore(&ledger.to_bytes().unwrap()).unwrap();
+        assert_eq!(restored.balance("Bob", &currency), ledger.balance("Bob", &currency));
+        assert_eq!(restored.viewing_key("Alice"), ledger.viewing_key("Alice"));
+        assert_eq!(restored.transfers(), ledger.transfers());
+    }
+
+    #[test]
+    fn test_amounts_above_one_half_decrypt() {
+        let zkp_manager = ZKPManager::new(64);
+        let (alice_key, bob_key) = (ViewingKey::generate(), ViewingKey::generate());
+        let mut ledger = ConfidentialLedger::default();
+        ledger.register_account("Alice", alice_key.public_key()).unwrap();
+        ledger.register_account("Bob", bob_key.public_key()).unwrap();
+        let currency = CurrencyType::BasicNeeds;
+        // Past 2^32 millionths, so both halves are used
+        ledger.shield("Alice", &currency, Amount::from(10_000.5)).unwrap();
+
+        let (transfer, _) = zkp_manager.create_confidential_transfer(
+            "Alice", "Bob", currency.clone(), Amount::from(5_000.25), &ledger.balance("Alice", &currency), Amount::from(10_000.5),
+            &alice_key, &bob_key.public_key(), None, 1,
+        ).unwrap();
+        ledger.apply_transfer(&zkp_manager, transfer.clone()).unwrap();
+        assert_eq!(bob_key.received_amount(&transfer).unwrap(), Amount::from(5_000.25));
+        assert!(alice_key.audit(&transfer).is_err());
+    }
+}
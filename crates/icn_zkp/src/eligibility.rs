@@ -0,0 +1,183 @@
+// File: crates/icn_zkp/src/eligibility.rs
+
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+use curve25519_dalek_ng::ristretto::CompressedRistretto;
+use curve25519_dalek_ng::scalar::Scalar;
+use merlin::Transcript;
+use rand::thread_rng;
+use serde::{Serialize, Deserialize};
+use icn_common::{IcnResult, IcnError};
+
+/// Vote weights are committed as integers in thousandths.
+pub const WEIGHT_SCALE: f64 = 1000.0;
+
+/// Bits available for the distance between the weight and either bound.
+const RANGE_BITS: usize = 32;
+
+/// Proof that a committed vote weight lies in `[min_weight, max_weight]`,
+/// bound to a single voter and proposal.
+///
+/// The weight is never revealed: the proof shows that both `weight - min`
+/// and `max - weight` are non-negative, using commitments derived from the
+/// one weight commitment.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WeightRangeProof {
+    pub commitment: Vec<u8>,
+    pub min_weight: u64,
+    pub max_weight: u64,
+    pub proof: Vec<u8>,
+}
+
+/// The blinding factor that opens a `WeightRangeProof` commitment. Whoever
+/// counts the vote keeps it, to check that the weight counted is the one
+/// committed; it is left out of anything shown to auditors.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WeightOpening {
+    pub blinding: Vec<u8>,
+}
+
+impl WeightRangeProof {
+    /// Proves that `weight` lies in the power-of-two bucket containing it.
+    pub fn prove(proposal_id: &str, voter: &str, weight: f64) -> IcnResult<(Self, WeightOpening)> {
+        let scaled = scale_weight(weight)?;
+        let (min_weight, max_weight) = weight_bucket(scaled);
+        Self::prove_range(proposal_id, voter, scaled, min_weight, max_weight)
+    }
+
+    pub fn prove_range(proposal_id: &str, voter: &str, weight: u64, min_weight: u64, max_weight: u64) -> IcnResult<(Self, WeightOpening)> {
+        if weight < min_weight || weight > max_weight {
+            return Err(IcnError::Zkp("Weight is outside the claimed range".into()));
+        }
+
+        let pc_gens = PedersenGens::default();
+        let blinding = Scalar::random(&mut thread_rng());
+        let commitment = pc_gens.commit(Scalar::from(weight), blinding).compress();
+
+        let mut transcript = vote_transcript(proposal_id, voter, min_weight, max_weight);
+        let (proof, _) = RangeProof::prove_multiple(
+            &BulletproofGens::new(RANGE_BITS, 2),
+            &pc_gens,
+            &mut transcript,
+            &[weight - min_weight, max_weight - weight],
+            &[blinding, -blinding],
+            RANGE_BITS,
+        )
+        .map_err(|e| IcnError::Zkp(format!("Failed to create weight range proof: {}", e)))?;
+
+        let range_proof = WeightRangeProof {
+            commitment: commitment.as_bytes().to_vec(),
+            min_weight,
+            max_weight,
+            proof: proof.to_bytes(),
+        };
+        Ok((range_proof, WeightOpening { blinding: blinding.to_bytes().to_vec() }))
+    }
+
+    /// Checks the proof for the given voter and proposal.
+    pub fn verify(&self, proposal_id: &str, voter: &str) -> IcnResult<()> {
+        let invalid = |e: &dyn std::fmt::Display| IcnError::Zkp(format!("Invalid weight range proof: {}", e));
+        if self.commitment.len() != 32 {
+            return Err(IcnError::Zkp("Invalid weight commitment".into()));
+        }
+        let pc_gens = PedersenGens::default();
+        let commitment = CompressedRistretto::from_slice(&self.commitment)
+            .decompress()
+            .ok_or_else(|| IcnError::Zkp("Invalid weight commitment".into()))?;
+        let proof = RangeProof::from_bytes(&self.proof).map_err(|e| invalid(&e))?;
+
+        // C - min·B commits to weight - min, and max·B - C to max - weight
+        let above_min = commitment - pc_gens.B * Scalar::from(self.min_weight);
+        let below_max = pc_gens.B * Scalar::from(self.max_weight) - commitment;
+
+        let mut transcript = vote_transcript(proposal_id, voter, self.min_weight, self.max_weight);
+        proof.verify_multiple(
+            &BulletproofGens::new(RANGE_BITS, 2),
+            &pc_gens,
+            &mut transcript,
+            &[above_min.compress(), below_max.compress()],
+            RANGE_BITS,
+        )
+        .map_err(|e| invalid(&e))
+    }
+
+    /// Returns true if an unscaled weight falls within the proven range.
+    pub fn covers(&self, weight: f64) -> bool {
+        scale_weight(weight).is_ok_and(|scaled| scaled >= self.min_weight && scaled <= self.max_weight)
+    }
+
+    /// Returns true if the commitment opens to exactly this unscaled
+    /// weight, so the weight counted is the one whose range was proven.
+    pub fn opens(&self, weight: f64, opening: &WeightOpening) -> bool {
+        let (Ok(scaled), Ok(blinding)) = (scale_weight(weight), <[u8; 32]>::try_from(opening.blinding.as_slice())) else {
+            return false;
+        };
+        let Some(blinding) = Scalar::from_canonical_bytes(blinding) else {
+            return false;
+        };
+        let commitment = PedersenGens::default().commit(Scalar::from(scaled), blinding).compress();
+        commitment.as_bytes().as_slice() == self.commitment.as_slice()
+    }
+}
+
+fn scale_weight(weight: f64) -> IcnResult<u64> {
+    let scaled = (weight * WEIGHT_SCALE).round();
+    if !scaled.is_finite() || scaled < 0.0 || scaled >= (1u64 << RANGE_BITS) as f64 {
+        return Err(IcnError::Zkp(format!("Weight {} cannot be committed", weight)));
+    }
+    Ok(scaled as u64)
+}
+
+/// The power-of-two interval `[2^k, 2^(k+1) - 1]` containing a scaled
+/// weight, or `[0, 0]` for a zero weight.
+fn weight_bucket(scaled: u64) -> (u64, u64) {
+    if scaled == 0 {
+        return (0, 0);
+    }
+    let low = 1u64 << (63 - scaled.leading_zeros());
+    (low, low * 2 - 1)
+}
+
+fn vote_transcript(proposal_id: &str, voter: &str, min_weight: u64, max_weight: u64) -> Transcript {
+    let mut transcript = Transcript::new(b"icn-vote-eligibility");
+    transcript.append_message(b"proposal", proposal_id.as_bytes());
+    transcript.append_message(b"voter", voter.as_bytes());
+    transcript.append_u64(b"min", min_weight);
+    transcript.append_u64(b"max", max_weight);
+    transcript
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_range_proof() {
+        let (proof, opening) = WeightRangeProof::prove("proposal1", "Alice", 12.5).unwrap();
+        assert_eq!((proof.min_weight, proof.max_weight), (8192, 16383));
+        assert!(proof.covers(12.5));
+        assert!(!proof.covers(20.0));
+        assert!(proof.verify("proposal1", "Alice").is_ok());
+
+        // Only the committed weight opens the commitment, not others in the bucket
+        assert!(proof.opens(12.5, &opening));
+        assert!(!proof.opens(15.0, &opening));
+        assert!(!proof.opens(12.5, &WeightOpening::default()));
+
+        // Proofs are bound to the voter and proposal
+        assert!(proof.verify("proposal1", "Bob").is_err());
+        assert!(proof.verify("proposal2", "Alice").is_err());
+    }
+
+    #[test]
+    fn test_rejects_forged_range() {
+        assert!(WeightRangeProof::prove_range("p", "Alice", 50, 100, 200).is_err());
+
+        let (mut proof, _) = WeightRangeProof::prove_range("p", "Alice", 150, 100, 200).unwrap();
+        proof.min_weight = 160;
+        assert!(proof.verify("p", "Alice").is_err());
+
+        let (zero, _) = WeightRangeProof::prove("p", "Alice", 0.0).unwrap();
+        assert!(zero.verify("p", "Alice").is_ok());
+        assert!(WeightRangeProof::prove("p", "Alice", -1.0).is_err());
+    }
+}
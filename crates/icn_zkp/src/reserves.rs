@@ -0,0 +1,256 @@
+// File: crates/icn_zkp/src/reserves.rs
+
+use crate::{Opening, ZKPManager};
+use bulletproofs::RangeProof;
+use curve25519_dalek_ng::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek_ng::scalar::Scalar;
+use curve25519_dalek_ng::traits::Identity;
+use merlin::Transcript;
+use rand::thread_rng;
+use serde::{Serialize, Deserialize};
+use icn_common::{Amount, CurrencyType, IcnResult, IcnError};
+use std::collections::HashMap;
+
+/// Balances and supplies are proven to fit in this many bits of millionths.
+const RESERVE_BITS: usize = 64;
+
+/// Proof that the balances held in a currency add up to no more than its
+/// recorded supply.
+///
+/// Each balance appears only as a Pedersen commitment with a proof that it
+/// is not negative, sorted so commitments can't be matched to accounts.
+/// The sum of the commitments, taken from the public supply, commits to
+/// what is left over, which a last proof shows is not negative either.
+/// Only the number of balances and the supply are revealed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReserveProof {
+    pub currency_type: CurrencyType,
+    pub supply: Amount,
+    pub timestamp: i64,
+    pub liabilities: Vec<Vec<u8>>,
+    /// Proofs that each liability is not negative, in the same order.
+    pub liability_proofs: Vec<Vec<u8>>,
+    pub solvency_proof: Vec<u8>,
+}
+
+/// What a member needs to check their balance was counted in a proof.
+/// Only the member should be shown it, since it reveals their balance.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReserveInclusion {
+    pub currency_type: CurrencyType,
+    pub timestamp: i64,
+    pub balance: Amount,
+    pub blinding: Vec<u8>,
+}
+
+impl ReserveProof {
+    fn liability_transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(b"icn-reserve-liability");
+        transcript.append_message(b"currency", format!("{:?}", self.currency_type).as_bytes());
+        transcript.append_u64(b"timestamp", self.timestamp as u64);
+        transcript
+    }
+
+    fn solvency_transcript(&self) -> Transcript {
+        let mut transcript = Transcript::new(b"icn-reserve-solvency");
+        transcript.append_message(b"currency", format!("{:?}", self.currency_type).as_bytes());
+        transcript.append_u64(b"supply", self.supply.units() as u64);
+        transcript.append_u64(b"timestamp", self.timestamp as u64);
+        for liability in &self.liabilities {
+            transcript.append_message(b"liability", liability);
+        }
+        transcript
+    }
+
+    /// Checks that the member's balance described by `inclusion` is one of
+    /// the liabilities.
+    pub fn verify_inclusion(&self, inclusion: &ReserveInclusion) -> IcnResult<()> {
+        if inclusion.currency_type != self.currency_type || inclusion.timestamp != self.timestamp {
+            return Err(IcnError::Zkp("Inclusion is for a different reserve proof".into()));
+        }
+        let blinding: [u8; 32] = inclusion.blinding.as_slice().try_into()
+            .map_err(|_| IcnError::Zkp("Invalid inclusion blinding".into()))?;
+        let blinding = Scalar::from_canonical_bytes(blinding)
+            .ok_or_else(|| IcnError::Zkp("Invalid inclusion blinding".into()))?;
+        let commitment = Opening { amount: balance_units(inclusion.balance)?, blinding }.commitment();
+        if !self.liabilities.iter().any(|liability| liability.as_slice() == commitment.as_bytes()) {
+            return Err(IcnError::Zkp("Balance is not among the proof's liabilities".into()));
+        }
+        Ok(())
+    }
+}
+
+fn balance_units(amount: Amount) -> IcnResult<u64> {
+    u64::try_from(amount.units())
+        .map_err(|_| IcnError::Zkp("Negative balances cannot be proven".into()))
+}
+
+fn decompress(bytes: &[u8]) -> IcnResult<RistrettoPoint> {
+    if bytes.len() != 32 {
+        return Err(IcnError::Zkp("Invalid liability commitment".into()));
+    }
+    CompressedRistretto::from_slice(bytes)
+        .decompress()
+        .ok_or_else(|| IcnError::Zkp("Invalid liability commitment".into()))
+}
+
+impl ZKPManager {
+    /// Proves that `balances` add up to no more than `supply`. Returns the
+    /// proof and each balance's opening, in the order given, for members
+    /// to check their balance was counted.
+    pub fn prove_reserves(&self, currency_type: CurrencyType, supply: Amount, balances: &[Amount], timestamp: i64) -> IcnResult<(ReserveProof, Vec<Opening>)> {
+        let openings = balances.iter()
+            .map(|balance| Ok(Opening { amount: balance_units(*balance)?, blinding: Scalar::random(&mut thread_rng()) }))
+            .collect::<IcnResult<Vec<_>>>()?;
+        let liabilities = openings.iter().try_fold(Opening::public(0), |total, opening| total.add(opening))?;
+        let surplus = Opening::public(balance_units(supply)?).sub(&liabilities)
+            .map_err(|_| IcnError::Zkp(format!("Balances of {:?} exceed its supply", currency_type)))?;
+
+        let mut proof = ReserveProof {
+            currency_type,
+            supply,
+            timestamp,
+            liabilities: Vec::new(),
+            liability_proofs: Vec::new(),
+            solvency_proof: Vec::new(),
+        };
+        let mut proven = Vec::with_capacity(openings.len());
+        for opening in &openings {
+            let (range_proof, commitment) = RangeProof::prove_single(
+                &self.bp_gens,
+                &self.pc_gens,
+                &mut proof.liability_transcript(),
+                opening.amount,
+                &opening.blinding,
+                RESERVE_BITS,
+            )
+            .map_err(|e| IcnError::Zkp(format!("Failed to create liability proof: {}", e)))?;
+            proven.push((commitment.as_bytes().to_vec(), range_proof.to_bytes()));
+        }
+        proven.sort();
+        (proof.liabilities, proof.liability_proofs) = proven.into_iter().unzip();
+
+        let (solvency_proof, _) = RangeProof::prove_single(
+            &self.bp_gens,
+            &self.pc_gens,
+            &mut proof.solvency_transcript(),
+            surplus.amount,
+            &surplus.blinding,
+            RESERVE_BITS,
+        )
+        .map_err(|e| IcnError::Zkp(format!("Failed to create solvency proof: {}", e)))?;
+        proof.solvency_proof = solvency_proof.to_bytes();
+        Ok((proof, openings))
+    }
+
+    pub fn verify_reserves(&self, proof: &ReserveProof) -> IcnResult<()> {
+        if proof.liabilities.len() != proof.liability_proofs.len() {
+            return Err(IcnError::Zkp("Every liability needs a proof".into()));
+        }
+        let mut total = RistrettoPoint::identity();
+        for (liability, liability_proof) in proof.liabilities.iter().zip(&proof.liability_proofs) {
+            let commitment = decompress(liability)?;
+            RangeProof::from_bytes(liability_proof)
+                .and_then(|range_proof| range_proof.verify_single(
+                    &self.bp_gens,
+                    &self.pc_gens,
+                    &mut proof.liability_transcript(),
+                    &commitment.compress(),
+                    RESERVE_BITS,
+                ))
+                .map_err(|e| IcnError::Zkp(format!("Invalid liability proof: {}", e)))?;
+            total += commitment;
+        }
+
+        // supply·B - Σ liabilities commits to the surplus
+        let surplus = self.pc_gens.B * Scalar::from(balance_units(proof.supply)?) - total;
+        RangeProof::from_bytes(&proof.solvency_proof)
+            .and_then(|range_proof| range_proof.verify_single(
+                &self.bp_gens,
+                &self.pc_gens,
+                &mut proof.solvency_transcript(),
+                &surplus.compress(),
+                RESERVE_BITS,
+            ))
+            .map_err(|e| IcnError::Zkp(format!("Invalid solvency proof: {}", e)))
+    }
+}
+
+/// The latest reserve proof for each currency, and the openings members
+/// are given to check their own balance against it.
+#[derive(Debug, Clone, Default)]
+pub struct PublishedReserves {
+    proofs: HashMap<CurrencyType, ReserveProof>,
+    openings: HashMap<(String, CurrencyType), Opening>,
+}
+
+impl PublishedReserves {
+    /// Replaces the currency's proof. `openings` pairs each account with
+    /// the opening of its balance.
+    pub fn publish(&mut self, proof: ReserveProof, openings: impl IntoIterator<Item = (String, Opening)>) {
+        let currency_type = proof.currency_type.clone();
+        self.openings.retain(|(_, currency), _| currency != &currency_type);
+        self.openings.extend(openings.into_iter().map(|(address, opening)| ((address, currency_type.clone()), opening)));
+        self.proofs.insert(currency_type, proof);
+    }
+
+    pub fn proof(&self, currency_type: &CurrencyType) -> Option<&ReserveProof> {
+        self.proofs.get(currency_type)
+    }
+
+    pub fn proofs(&self) -> Vec<&ReserveProof> {
+        let mut proofs: Vec<&ReserveProof> = self.proofs.values().collect();
+        proofs.sort_by_key(|proof| format!("{:?}", proof.currency_type));
+        proofs
+    }
+
+    /// How `address`'s balance appears in the latest proof for the currency.
+    pub fn inclusion(&self, address: &str, currency_type: &CurrencyType) -> IcnResult<ReserveInclusion> {
+        let proof = self.proof(currency_type)
+            .ok_or_else(|| IcnError::Zkp(format!("No reserve proof published for {:?}", currency_type)))?;
+        let opening = self.openings.get(&(address.to_string(), currency_type.clone()))
+            .ok_or_else(|| IcnError::Zkp(format!("{} held no {:?} when reserves were last proven", address, currency_type)))?;
+        Ok(ReserveInclusion {
+            currency_type: currency_type.clone(),
+            timestamp: proof.timestamp,
+            balance: Amount::from_units(opening.amount as i64),
+            blinding: opening.blinding.to_bytes().to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserves_are_proven_without_revealing_balances() {
+        let zkp_manager = ZKPManager::new(64);
+        let currency = CurrencyType::BasicNeeds;
+        let balances = [Amount::from(40.0), Amount::from(25.5), Amount::from(0.0)];
+        let (proof, openings) = zkp_manager.prove_reserves(currency.clone(), Amount::from(70.0), &balances, 1_000).unwrap();
+        zkp_manager.verify_reserves(&proof).unwrap();
+        assert_eq!(proof.liabilities.len(), 3);
+
+        // Balances over the supply, or negative ones, cannot be proven
+        assert!(zkp_manager.prove_reserves(currency.clone(), Amount::from(65.0), &balances, 1_000).is_err());
+        assert!(zkp_manager.prove_reserves(currency.clone(), Amount::from(70.0), &[Amount::from(-1.0)], 1_000).is_err());
+
+        // Nor can a proof be stretched to a smaller supply or a dropped liability
+        let mut shrunk = proof.clone();
+        shrunk.supply = Amount::from(60.0);
+        assert!(zkp_manager.verify_reserves(&shrunk).is_err());
+        let mut dropped = proof.clone();
+        dropped.liabilities.pop();
+        dropped.liability_proofs.pop();
+        assert!(zkp_manager.verify_reserves(&dropped).is_err());
+
+        let mut published = PublishedReserves::default();
+        published.publish(proof.clone(), ["Alice", "Bob", "Carol"].map(String::from).into_iter().zip(openings));
+        let inclusion = published.inclusion("Bob", &currency).unwrap();
+        assert_eq!(inclusion.balance, Amount::from(25.5));
+        proof.verify_inclusion(&inclusion).unwrap();
+        assert!(proof.verify_inclusion(&ReserveInclusion { balance: Amount::from(30.0), ..inclusion }).is_err());
+        assert!(published.inclusion("Dave", &currency).is_err());
+    }
+}